@@ -0,0 +1,133 @@
+//! # Serial Logger
+//!
+//! Headless logger appending the data received on a port to a file, each
+//! line prefixed with the time it arrived, until Ctrl+C closes the port.
+//!
+//! ```text
+//! serial_logger <PORT> <BAUD> <OUTPUT> [--send TEXT]
+//! ```
+//!
+//! `--send` writes `TEXT` to the port once it is open, expanding escapes such
+//! as `\r`, `\n` and `\xNN`.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::prelude::*;
+use serial_bevy::serial::SerialPlugin;
+use serial_bevy::serial::encoding::parse_escapes;
+use serial_bevy::serial::logger::SerialLoggerPlugin;
+
+/// Pause between two updates of the app.
+const TICK: Duration = Duration::from_millis(10);
+
+/// Usage line printed on invalid arguments.
+const USAGE: &str = "usage: serial_logger <PORT> <BAUD> <OUTPUT> [--send TEXT]";
+
+/// Command line arguments.
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    /// Name of the port to log.
+    port_name: String,
+    /// Baud rate to open the port at.
+    baud_rate: u32,
+    /// File the received lines are appended to.
+    output: PathBuf,
+    /// Text written to the port once it is open.
+    send: Option<String>,
+}
+
+/// Parses the command line arguments, without the program name.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut send = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--send" => {
+                send = Some(args.next().ok_or("--send needs a value")?);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => positional.push(arg),
+        }
+    }
+
+    let [port_name, baud_rate, output] = <[String; 3]>::try_from(positional)
+        .map_err(|_| "expected a port, a baud rate and an output file".to_string())?;
+    let baud_rate = baud_rate
+        .parse()
+        .map_err(|_| format!("invalid baud rate {baud_rate}"))?;
+    Ok(Args {
+        port_name,
+        baud_rate,
+        output: PathBuf::from(output),
+        send,
+    })
+}
+
+fn main() -> AppExit {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return AppExit::error();
+        }
+    };
+
+    let mut logger = SerialLoggerPlugin::new(args.port_name, args.baud_rate, args.output);
+    if let Some(text) = args.send {
+        logger = logger.with_send(parse_escapes(&text));
+    }
+
+    // Ctrl+C asks the logger to close the port and exit.
+    let stop = logger.stop_handle();
+    std::thread::spawn(move || {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        if runtime.block_on(tokio::signal::ctrl_c()).is_ok() {
+            stop.stop();
+        }
+    });
+
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(TICK)),
+            SerialPlugin::default(),
+            logger,
+        ))
+        .run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| (*arg).to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            args(&["/dev/ttyUSB0", "115200", "out.log", "--send", "AT\\r\\n"]),
+            Ok(Args {
+                port_name: "/dev/ttyUSB0".to_string(),
+                baud_rate: 115200,
+                output: PathBuf::from("out.log"),
+                send: Some("AT\\r\\n".to_string()),
+            })
+        );
+        assert!(args(&["COM3", "fast", "out.log"]).is_err());
+        assert!(args(&["COM3", "9600"]).is_err());
+        assert!(args(&["COM3", "9600", "out.log", "--send"]).is_err());
+        assert!(args(&["COM3", "9600", "out.log", "--verbose"]).is_err());
+    }
+}
@@ -42,6 +42,18 @@ pub enum SerialBevyError {
     /// Invalid configuration.
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
+
+    /// Data could not be serialized or deserialized.
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    /// Operation timed out.
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    /// The OS keychain could not be accessed.
+    #[error("Keychain error: {0}")]
+    Keyring(String),
 }
 
 impl SerialBevyError {
@@ -60,6 +72,18 @@ impl SerialBevyError {
         }
     }
 
+    /// Creates a new port read error.
+    #[must_use]
+    pub fn port_read(msg: impl Into<String>) -> Self {
+        Self::PortRead(msg.into())
+    }
+
+    /// Creates a new port write error.
+    #[must_use]
+    pub fn port_write(msg: impl Into<String>) -> Self {
+        Self::PortWrite(msg.into())
+    }
+
     /// Creates a new channel error.
     #[must_use]
     pub fn channel(msg: impl Into<String>) -> Self {
@@ -71,6 +95,24 @@ impl SerialBevyError {
     pub fn encoding(msg: impl Into<String>) -> Self {
         Self::Encoding(msg.into())
     }
+
+    /// Creates a new serialization error.
+    #[must_use]
+    pub fn serialization(msg: impl Into<String>) -> Self {
+        Self::Serialization(msg.into())
+    }
+
+    /// Creates a new timeout error.
+    #[must_use]
+    pub fn timeout(msg: impl Into<String>) -> Self {
+        Self::Timeout(msg.into())
+    }
+
+    /// Creates a new keychain error.
+    #[must_use]
+    pub fn keyring(msg: impl Into<String>) -> Self {
+        Self::Keyring(msg.into())
+    }
 }
 
 #[cfg(test)]
@@ -91,6 +133,24 @@ mod tests {
         assert!(msg.contains("Permission denied"));
     }
 
+    #[test]
+    fn test_port_read_error() {
+        let error = SerialBevyError::port_read("COM3: device disconnected");
+        assert_eq!(
+            error.to_string(),
+            "Failed to read from serial port: COM3: device disconnected"
+        );
+    }
+
+    #[test]
+    fn test_port_write_error() {
+        let error = SerialBevyError::port_write("COM3: broken pipe");
+        assert_eq!(
+            error.to_string(),
+            "Failed to write to serial port: COM3: broken pipe"
+        );
+    }
+
     #[test]
     fn test_channel_error() {
         let error = SerialBevyError::channel("Receiver dropped");
@@ -102,4 +162,22 @@ mod tests {
         let error = SerialBevyError::encoding("Invalid hex string");
         assert!(error.to_string().contains("Invalid hex string"));
     }
+
+    #[test]
+    fn test_serialization_error() {
+        let error = SerialBevyError::serialization("line 3: expected value");
+        assert!(error.to_string().contains("line 3: expected value"));
+    }
+
+    #[test]
+    fn test_timeout_error() {
+        let error = SerialBevyError::timeout("no echo after 2s");
+        assert!(error.to_string().contains("no echo after 2s"));
+    }
+
+    #[test]
+    fn test_keyring_error() {
+        let error = SerialBevyError::keyring("no secret service");
+        assert_eq!(error.to_string(), "Keychain error: no secret service");
+    }
 }
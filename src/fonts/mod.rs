@@ -19,38 +19,101 @@
 //!     .add_plugins(EguiPlugin::default())
 //!     .add_plugins(
 //!         EguiFontPlugin::default()
-//!             .with_font("Song", "assets/fonts/STSong.ttf")
-//!             .with_font("Custom", "assets/fonts/CustomFont.ttf")
-//!             .with_font_config( FontConfig::new("Song", "assets/fonts/STSong.ttf").primary() )
+//!             .with_font("Noto", "assets/fonts/NotoSansJP.ttf")
+//!             .with_font_config(FontConfig::from_asset("Custom", "fonts/CustomFont.ttf"))
+//!             .with_font_config(
+//!                 FontConfig::new("Song", "assets/fonts/STSong.ttf")
+//!                     .primary()
+//!                     .with_fallback("Noto"),
+//!             )
 //!             .with_theme(egui::Theme::Light)
 //!     );
 //! ```
+//!
+//! Fonts can also be loaded after startup by writing a [`LoadFontEvent`].
+//!
+//! A font is read from a file, embedded in the binary with
+//! [`EguiFontPlugin::with_font_bytes`], or loaded through Bevy's
+//! `AssetServer` with [`FontConfig::from_asset`], which also works when the
+//! assets are bundled or on wasm. Fonts that fail to load are skipped and egui
+//! keeps its default fonts. The plugin registers the [`FontBytes`] asset type,
+//! so, like `EguiPlugin`, it goes after Bevy's `AssetPlugin`, e.g. after
+//! `DefaultPlugins`.
+//!
+//! The size of labels and the family of monospace text are set by the
+//! [`UiFontPreferences`] resource, which the app can change at any time;
+//! a change only restyles egui and does not reload the fonts.
 
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, LoadState};
 use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
 use bevy_egui::{EguiContexts, EguiPreUpdateSet, egui};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+/// Where the data of a font comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    /// A font file, read at startup
+    Path(PathBuf),
+    /// Font data embedded in the binary, e.g. with `include_bytes!`
+    Bytes(&'static [u8]),
+    /// A font file loaded through the `AssetServer`, relative to the assets
+    /// folder; the font is applied once it has loaded
+    Asset(String),
+}
+
 /// Configuration for a single font
 #[derive(Debug, Clone)]
 pub struct FontConfig {
     /// Name of the font (used as the font family name)
     pub name: String,
-    /// Path to the font file
+    /// Path to the font file, or empty if the font is not read from a file;
+    /// set by the constructors, the font is loaded from `source`
+    #[deprecated(note = "use `source`, which also covers embedded and asset fonts")]
     pub path: PathBuf,
+    /// Where the font data comes from
+    pub source: FontSource,
     /// Whether this font should be set as primary for proportional text
     pub primary_proportional: bool,
     /// Whether this font should be set as primary for monospace text
     pub primary_monospace: bool,
+    /// Fonts used, in order, for glyphs missing from this one
+    pub fallbacks: Vec<String>,
 }
 
 impl FontConfig {
-    /// Create a new font configuration
+    /// Create a new font configuration reading the font file at `path`
     pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self::with_source(name, FontSource::Path(path.into()))
+    }
+
+    /// Create a new font configuration for font data embedded in the binary
+    pub fn from_bytes(name: impl Into<String>, bytes: &'static [u8]) -> Self {
+        Self::with_source(name, FontSource::Bytes(bytes))
+    }
+
+    /// Create a new font configuration loading `path` through the `AssetServer`
+    pub fn from_asset(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self::with_source(name, FontSource::Asset(path.into()))
+    }
+
+    /// Create a new font configuration for a font from `source`
+    pub fn with_source(name: impl Into<String>, source: FontSource) -> Self {
+        let path = match &source {
+            FontSource::Path(path) => path.clone(),
+            FontSource::Bytes(_) | FontSource::Asset(_) => PathBuf::new(),
+        };
+        #[allow(deprecated)]
         Self {
             name: name.into(),
-            path: path.into(),
+            path,
+            source,
             primary_proportional: false,
             primary_monospace: false,
+            fallbacks: Vec::new(),
         }
     }
 
@@ -70,6 +133,144 @@ impl FontConfig {
     pub fn primary(self) -> Self {
         self.primary_proportional().primary_monospace()
     }
+
+    /// Use the font added as `fallback_name` for glyphs missing from this one.
+    ///
+    /// Fallbacks follow this font in every family it belongs to, in the order
+    /// they were added, before egui's built-in fonts.
+    pub fn with_fallback(mut self, fallback_name: &str) -> Self {
+        self.fallbacks.push(fallback_name.to_string());
+        self
+    }
+}
+
+/// Loads a font after startup, e.g. one downloaded on first run or added by
+/// the user.
+///
+/// The font is added to the [`EguiFontConfig`] as [`EguiFontPlugin`] adds its
+/// startup fonts, replacing a font of the same name, and egui uses it from
+/// the frame after it loaded; a font file is read in the background. Its
+/// fallbacks must already be loaded.
+#[derive(Message, Debug, Clone)]
+pub struct LoadFontEvent {
+    /// The font to load.
+    pub config: FontConfig,
+}
+
+/// Font file loaded through the `AssetServer`
+#[derive(Asset, TypePath, Debug)]
+pub struct FontBytes(pub Vec<u8>);
+
+/// Loads font files as [`FontBytes`]
+#[derive(Default, TypePath)]
+struct FontBytesLoader;
+
+impl AssetLoader for FontBytesLoader {
+    type Asset = FontBytes;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<FontBytes, std::io::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(FontBytes(bytes))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ttf", "otf", "ttc"]
+    }
+}
+
+/// Fonts loading through the `AssetServer`, added to the [`EguiFontConfig`]
+/// once loaded
+#[derive(Resource, Default)]
+struct PendingFontAssets(Vec<(FontConfig, Handle<FontBytes>)>);
+
+impl PendingFontAssets {
+    /// Starts loading the asset font of `config`
+    fn load(&mut self, config: &FontConfig, path: &str, asset_server: Option<&AssetServer>) {
+        let Some(asset_server) = asset_server else {
+            warn!(
+                "Failed to load font '{}' from asset {path}: no AssetServer",
+                config.name
+            );
+            return;
+        };
+        self.0.push((
+            config.clone(),
+            asset_server.load::<FontBytes>(path.to_string()),
+        ));
+    }
+}
+
+/// Font files of [`LoadFontEvent`]s being read in the background, added to
+/// the [`EguiFontConfig`] once read
+#[derive(Resource, Default)]
+struct PendingFontFiles(Vec<(FontConfig, Task<std::io::Result<Vec<u8>>>)>);
+
+impl PendingFontFiles {
+    /// Starts reading the font file of `config` on the
+    /// [`AsyncComputeTaskPool`]
+    fn load(&mut self, config: &FontConfig, path: &std::path::Path) {
+        let path = path.to_path_buf();
+        let task = AsyncComputeTaskPool::get().spawn(async move { std::fs::read(path) });
+        self.0.push((config.clone(), task));
+    }
+}
+
+/// Font sizes and monospace font chosen by the user, applied to the egui
+/// styles whenever the resource changes
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiFontPreferences {
+    /// Font size of the receive view
+    pub receive_view_size: f32,
+    /// Font size of labels and buttons
+    pub label_size: f32,
+    /// Font size of the send input
+    pub input_size: f32,
+    /// Name of the font used for monospace text, or empty for egui's
+    pub monospace_family: String,
+}
+
+impl Default for UiFontPreferences {
+    fn default() -> Self {
+        Self {
+            receive_view_size: 18.0,
+            label_size: 12.5,
+            input_size: 18.0,
+            monospace_family: String::new(),
+        }
+    }
+}
+
+impl UiFontPreferences {
+    /// Returns the family of monospace text: the chosen font if it is one of
+    /// `fonts`, egui's monospace family otherwise
+    pub fn monospace(&self, fonts: &egui::FontDefinitions) -> egui::FontFamily {
+        let family = egui::FontFamily::Name(self.monospace_family.as_str().into());
+        if !self.monospace_family.is_empty() && fonts.families.contains_key(&family) {
+            family
+        } else {
+            egui::FontFamily::Monospace
+        }
+    }
+
+    /// Sets the label size and the monospace family of `style`
+    pub fn apply(&self, style: &mut egui::Style, fonts: &egui::FontDefinitions) {
+        for (text_style, font) in &mut style.text_styles {
+            match text_style {
+                egui::TextStyle::Body | egui::TextStyle::Button => font.size = self.label_size,
+                egui::TextStyle::Monospace => font.family = self.monospace(fonts),
+                _ => {}
+            }
+        }
+    }
 }
 
 /// Resource storing the complete font configuration
@@ -110,6 +311,12 @@ impl EguiFontPlugin {
         self
     }
 
+    /// Add a font embedded in the binary, e.g. with `include_bytes!`
+    pub fn with_font_bytes(mut self, name: impl Into<String>, bytes: &'static [u8]) -> Self {
+        self.fonts.push(FontConfig::from_bytes(name, bytes));
+        self
+    }
+
     /// Add a font with full configuration
     pub fn with_font_config(mut self, config: FontConfig) -> Self {
         self.fonts.push(config);
@@ -122,86 +329,283 @@ impl EguiFontPlugin {
         self
     }
 
-    /// Load font configuration in Startup system
-    fn load_font_config(mut commands: Commands, font_configs: Res<FontConfigsResource>) {
-        let mut fonts = egui::FontDefinitions::default();
+    /// Load font configuration in Startup system, and start loading the
+    /// asset fonts
+    fn load_font_config(
+        mut commands: Commands,
+        font_configs: Res<FontConfigsResource>,
+        asset_server: Option<Res<AssetServer>>,
+        mut pending: ResMut<PendingFontAssets>,
+    ) {
+        let fonts = build_font_definitions(&font_configs.fonts);
+        let theme = font_configs.theme.unwrap_or(egui::Theme::Light);
+
+        commands.insert_resource(EguiFontConfig { fonts, theme });
 
-        // Load fonts in the order they were added
         for config in &font_configs.fonts {
-            match std::fs::read(&config.path) {
-                Ok(bytes) => {
-                    debug!(
-                        "Loaded font '{}' from: {}",
-                        config.name,
-                        config.path.display()
-                    );
-
-                    fonts.font_data.insert(
-                        config.name.clone(),
-                        egui::FontData::from_owned(bytes).into(),
-                    );
-
-                    // Register the font family
-                    fonts.families.insert(
-                        egui::FontFamily::Name(config.name.clone().into()),
-                        vec![config.name.clone()],
-                    );
-
-                    // Set as primary fonts if requested
-                    if config.primary_proportional {
-                        fonts
-                            .families
-                            .entry(egui::FontFamily::Proportional)
-                            .or_default()
-                            .insert(0, config.name.clone());
-                    }
+            if let FontSource::Asset(path) = &config.source {
+                pending.load(config, path, asset_server.as_deref());
+            }
+        }
 
-                    if config.primary_monospace {
-                        fonts
-                            .families
-                            .entry(egui::FontFamily::Monospace)
-                            .or_default()
-                            .insert(0, config.name.clone());
-                    }
+        debug!(
+            "Font configuration prepared with {} fonts",
+            font_configs.fonts.len()
+        );
+    }
+
+    /// Loads the fonts of [`LoadFontEvent`]s into the [`EguiFontConfig`],
+    /// which [`Self::apply_font_config`] then applies to egui.
+    ///
+    /// Font files are read in the background, so a large font does not hold
+    /// up the frame; [`Self::apply_font_files`] adds them once read.
+    fn apply_runtime_fonts(
+        mut events: MessageReader<LoadFontEvent>,
+        font_config: Option<ResMut<EguiFontConfig>>,
+        asset_server: Option<Res<AssetServer>>,
+        mut pending: ResMut<PendingFontAssets>,
+        mut pending_files: ResMut<PendingFontFiles>,
+    ) {
+        let Some(mut font_config) = font_config else {
+            return;
+        };
+        for event in events.read() {
+            match &event.config.source {
+                FontSource::Asset(path) => {
+                    pending.load(&event.config, path, asset_server.as_deref());
                 }
-                Err(e) => {
-                    warn!(
-                        "Failed to load font '{}' from: {}: {}",
-                        config.name,
-                        config.path.display(),
-                        e
-                    );
+                FontSource::Path(path) => pending_files.load(&event.config, path),
+                FontSource::Bytes(_) => {
+                    if add_font(&mut font_config.fonts, &event.config) {
+                        add_fallbacks(&mut font_config.fonts, &event.config);
+                    }
                 }
             }
         }
+    }
 
-        let theme = font_configs.theme.unwrap_or(egui::Theme::Light);
+    /// Adds the font files of [`LoadFontEvent`]s that were read to the
+    /// [`EguiFontConfig`], dropping those that could not be read.
+    fn apply_font_files(
+        font_config: Option<ResMut<EguiFontConfig>>,
+        mut pending: ResMut<PendingFontFiles>,
+    ) {
+        if pending.0.is_empty() {
+            return;
+        }
+        let Some(mut font_config) = font_config else {
+            return;
+        };
 
-        commands.insert_resource(EguiFontConfig { fonts, theme });
+        let fonts = &mut font_config.fonts;
+        pending.0.retain_mut(|(config, task)| {
+            let Some(result) = check_ready(task) else {
+                return true;
+            };
+            match result {
+                Ok(bytes) => {
+                    debug!("Loaded font '{}' from file", config.name);
+                    insert_font(fonts, config, egui::FontData::from_owned(bytes));
+                    add_fallbacks(fonts, config);
+                }
+                Err(e) => warn!("Failed to load font '{}' from file: {e}", config.name),
+            }
+            false
+        });
+    }
 
-        debug!(
-            "Font configuration prepared with {} fonts",
-            font_configs.fonts.len()
-        );
+    /// Adds the asset fonts that finished loading to the [`EguiFontConfig`],
+    /// dropping those that failed to load.
+    fn apply_font_assets(
+        font_configs: Res<FontConfigsResource>,
+        font_config: Option<ResMut<EguiFontConfig>>,
+        asset_server: Option<Res<AssetServer>>,
+        font_assets: Option<Res<Assets<FontBytes>>>,
+        mut pending: ResMut<PendingFontAssets>,
+    ) {
+        if pending.0.is_empty() {
+            return;
+        }
+        let (Some(mut font_config), Some(asset_server), Some(font_assets)) =
+            (font_config, asset_server, font_assets)
+        else {
+            return;
+        };
+
+        let fonts = &mut font_config.fonts;
+        let mut loaded = false;
+        pending
+            .0
+            .retain(|(config, handle)| match asset_server.load_state(handle) {
+                LoadState::Loaded => {
+                    if let Some(font) = font_assets.get(handle) {
+                        debug!("Loaded font '{}' from asset", config.name);
+                        insert_font(fonts, config, egui::FontData::from_owned(font.0.clone()));
+                        loaded = true;
+                    }
+                    false
+                }
+                LoadState::Failed(e) => {
+                    warn!("Failed to load font '{}' from asset: {e}", config.name);
+                    false
+                }
+                LoadState::NotLoaded | LoadState::Loading => true,
+            });
+
+        // Fallbacks naming the new fonts can be applied now
+        if loaded {
+            for config in &font_configs.fonts {
+                add_fallbacks(fonts, config);
+            }
+        }
     }
 
-    /// Apply font and theme configuration using EguiPreUpdateSet::InitContexts
+    /// Apply font and theme configuration using
+    /// EguiPreUpdateSet::InitContexts, once at startup and again whenever the
+    /// configuration changes.
+    ///
+    /// The theme is only set the first time and when it changes, so loading
+    /// a font later keeps a theme the user picked in egui meanwhile.
     fn apply_font_config(
         mut contexts: EguiContexts,
         font_config: Res<EguiFontConfig>,
         mut has_applied: Local<bool>,
+        mut applied_theme: Local<Option<egui::Theme>>,
     ) {
-        if *has_applied {
+        if *has_applied && !font_config.is_changed() {
             return;
         }
 
         if let Ok(ctx) = contexts.ctx_mut() {
             ctx.set_fonts(font_config.fonts.clone());
-            ctx.set_theme(font_config.theme);
+            if !*has_applied || *applied_theme != Some(font_config.theme) {
+                ctx.set_theme(font_config.theme);
+                *applied_theme = Some(font_config.theme);
+            }
             *has_applied = true;
             debug!("Fonts and theme applied successfully");
         }
     }
+
+    /// Apply the [`UiFontPreferences`] to the egui styles, once at startup
+    /// and again whenever they or the fonts change; the monospace font they
+    /// name may only be loaded later. Unlike [`Self::apply_font_config`],
+    /// this leaves the fonts and the theme alone.
+    fn apply_font_preferences(
+        mut contexts: EguiContexts,
+        font_config: Res<EguiFontConfig>,
+        prefs: Res<UiFontPreferences>,
+        mut has_applied: Local<bool>,
+    ) {
+        if *has_applied && !prefs.is_changed() && !font_config.is_changed() {
+            return;
+        }
+
+        if let Ok(ctx) = contexts.ctx_mut() {
+            ctx.all_styles_mut(|style| prefs.apply(style, &font_config.fonts));
+            *has_applied = true;
+        }
+    }
+}
+
+/// Builds the egui font definitions for `configs`, loading the font files.
+///
+/// Fonts that fail to load are skipped, as are fallbacks naming them.
+fn build_font_definitions(configs: &[FontConfig]) -> egui::FontDefinitions {
+    let mut fonts = egui::FontDefinitions::default();
+
+    // Load fonts in the order they were added
+    for config in configs {
+        add_font(&mut fonts, config);
+    }
+
+    // Fallbacks go right after their font, once every font is loaded
+    for config in configs {
+        add_fallbacks(&mut fonts, config);
+    }
+
+    fonts
+}
+
+/// Loads the font of `config` into `fonts` and registers its family.
+///
+/// A font already in `fonts` under the same name is replaced. Returns false
+/// if the font file could not be read, or if the font is an asset, which
+/// [`PendingFontAssets`] loads instead.
+fn add_font(fonts: &mut egui::FontDefinitions, config: &FontConfig) -> bool {
+    let data = match &config.source {
+        FontSource::Path(path) => match std::fs::read(path) {
+            Ok(bytes) => {
+                debug!("Loaded font '{}' from: {}", config.name, path.display());
+                egui::FontData::from_owned(bytes)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to load font '{}' from: {}: {}",
+                    config.name,
+                    path.display(),
+                    e
+                );
+                return false;
+            }
+        },
+        FontSource::Bytes(bytes) => egui::FontData::from_static(bytes),
+        FontSource::Asset(_) => return false,
+    };
+    insert_font(fonts, config, data);
+    true
+}
+
+/// Inserts the font `data` of `config` into `fonts` and registers its family.
+fn insert_font(fonts: &mut egui::FontDefinitions, config: &FontConfig, data: egui::FontData) {
+    fonts.font_data.insert(config.name.clone(), data.into());
+
+    // Register the font family
+    fonts.families.insert(
+        egui::FontFamily::Name(config.name.clone().into()),
+        vec![config.name.clone()],
+    );
+
+    // Set as primary fonts if requested
+    for (primary, family) in [
+        (config.primary_proportional, egui::FontFamily::Proportional),
+        (config.primary_monospace, egui::FontFamily::Monospace),
+    ] {
+        if primary {
+            let names = fonts.families.entry(family).or_default();
+            names.retain(|name| *name != config.name);
+            names.insert(0, config.name.clone());
+        }
+    }
+}
+
+/// Puts the loaded fallbacks of `config` right after it in every family it
+/// belongs to.
+fn add_fallbacks(fonts: &mut egui::FontDefinitions, config: &FontConfig) {
+    if !fonts.font_data.contains_key(&config.name) {
+        return;
+    }
+    let fallbacks: Vec<String> = config
+        .fallbacks
+        .iter()
+        .filter(|name| {
+            let loaded = fonts.font_data.contains_key(*name) && **name != config.name;
+            if !loaded {
+                warn!("Fallback font '{name}' of '{}' is not loaded", config.name);
+            }
+            loaded
+        })
+        .cloned()
+        .collect();
+    for family in fonts.families.values_mut() {
+        if !family.contains(&config.name) {
+            continue;
+        }
+        family.retain(|name| !fallbacks.contains(name));
+        if let Some(index) = family.iter().position(|name| *name == config.name) {
+            family.splice(index + 1..index + 1, fallbacks.iter().cloned());
+        }
+    }
 }
 
 /// Resource to store font configurations
@@ -219,11 +623,202 @@ impl Plugin for EguiFontPlugin {
             theme: self.theme,
         });
 
+        // Asset fonts need the AssetPlugin, e.g. from DefaultPlugins, which
+        // EguiPlugin needs as well
+        app.init_asset::<FontBytes>()
+            .init_asset_loader::<FontBytesLoader>();
+
         // Add systems for loading and applying fonts
-        app.add_systems(Startup, Self::load_font_config)
+        app.add_message::<LoadFontEvent>()
+            .init_resource::<PendingFontAssets>()
+            .init_resource::<PendingFontFiles>()
+            .init_resource::<UiFontPreferences>()
+            .add_systems(Startup, Self::load_font_config)
             .add_systems(
                 PreUpdate,
-                Self::apply_font_config.in_set(EguiPreUpdateSet::InitContexts),
+                (
+                    Self::apply_runtime_fonts,
+                    Self::apply_font_files,
+                    Self::apply_font_assets,
+                    Self::apply_font_config,
+                    Self::apply_font_preferences,
+                )
+                    .chain()
+                    .in_set(EguiPreUpdateSet::InitContexts),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_font_event_updates_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Noto.ttf");
+        std::fs::write(&path, "Noto").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(TaskPoolPlugin::default())
+            .add_message::<LoadFontEvent>()
+            .init_resource::<PendingFontAssets>()
+            .init_resource::<PendingFontFiles>()
+            .insert_resource(EguiFontConfig::default())
+            .add_systems(
+                Update,
+                (
+                    EguiFontPlugin::apply_runtime_fonts,
+                    EguiFontPlugin::apply_font_files,
+                )
+                    .chain(),
+            );
+        app.world_mut().write_message(LoadFontEvent {
+            config: FontConfig::new("Noto", &path).primary_proportional(),
+        });
+        app.world_mut().write_message(LoadFontEvent {
+            config: FontConfig::new("Missing", dir.path().join("missing.ttf")),
+        });
+        // The files are read in the background and added once read.
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        app.update();
+        while !app.world().resource::<PendingFontFiles>().0.is_empty()
+            && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            app.update();
+        }
+
+        let fonts = &app.world().resource::<EguiFontConfig>().fonts;
+        assert!(fonts.font_data.contains_key("Noto"));
+        assert!(!fonts.font_data.contains_key("Missing"));
+        assert_eq!(fonts.families[&egui::FontFamily::Proportional][0], "Noto");
+    }
+
+    #[test]
+    fn test_fallbacks_follow_their_font_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let font = |name: &str| {
+            let path = dir.path().join(format!("{name}.ttf"));
+            std::fs::write(&path, name).unwrap();
+            FontConfig::new(name, path)
+        };
+        let configs = [
+            font("Noto"),
+            font("Song")
+                .primary()
+                .with_fallback("Noto")
+                .with_fallback("Emoji")
+                .with_fallback("Missing"),
+            font("Emoji"),
+            FontConfig::new("Missing", dir.path().join("missing.ttf")),
+        ];
+
+        let fonts = build_font_definitions(&configs);
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            let names = &fonts.families[&family];
+            assert_eq!(names[..3], ["Song", "Noto", "Emoji"]);
+            assert!(!names.contains(&"Missing".to_string()));
+        }
+        assert_eq!(
+            fonts.families[&egui::FontFamily::Name("Song".into())],
+            ["Song", "Noto", "Emoji"]
+        );
+        assert_eq!(
+            fonts.families[&egui::FontFamily::Name("Noto".into())],
+            ["Noto"]
+        );
+    }
+
+    #[test]
+    fn test_embedded_font_needs_no_file() {
+        let configs = [
+            FontConfig::from_bytes("Embedded", b"Embedded").primary_monospace(),
+            FontConfig::from_asset("Asset", "fonts/Asset.ttf"),
+        ];
+
+        let fonts = build_font_definitions(&configs);
+        assert!(fonts.font_data.contains_key("Embedded"));
+        assert_eq!(fonts.families[&egui::FontFamily::Monospace][0], "Embedded");
+        // Asset fonts are added once the AssetServer has loaded them.
+        assert!(!fonts.font_data.contains_key("Asset"));
+    }
+
+    #[test]
+    fn test_font_preferences_set_style() {
+        let fonts = build_font_definitions(&[FontConfig::from_bytes("Mono", b"Mono")]);
+        let mut prefs = UiFontPreferences {
+            label_size: 16.0,
+            monospace_family: "Mono".to_string(),
+            ..UiFontPreferences::default()
+        };
+        let mut style = egui::Style::default();
+        prefs.apply(&mut style, &fonts);
+        assert_eq!(style.text_styles[&egui::TextStyle::Body].size, 16.0);
+        assert_eq!(style.text_styles[&egui::TextStyle::Button].size, 16.0);
+        assert_eq!(
+            style.text_styles[&egui::TextStyle::Monospace].family,
+            egui::FontFamily::Name("Mono".into())
+        );
+
+        // A font that is not loaded falls back to egui's monospace font.
+        prefs.monospace_family = "Missing".to_string();
+        prefs.apply(&mut style, &fonts);
+        assert_eq!(
+            style.text_styles[&egui::TextStyle::Monospace].family,
+            egui::FontFamily::Monospace
+        );
+    }
+
+    #[test]
+    fn test_plugin_registers_font_assets() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .add_plugins(
+                EguiFontPlugin::new()
+                    .with_font_config(FontConfig::from_asset("Song", "fonts/STSong.ttf")),
             );
+        assert!(app.world().contains_resource::<Assets<FontBytes>>());
+        assert!(app.world().contains_resource::<UiFontPreferences>());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_path_mirrors_the_file_source() {
+        let font = FontConfig::new("Noto", "assets/fonts/Noto.ttf");
+        assert_eq!(font.path, PathBuf::from("assets/fonts/Noto.ttf"));
+        assert_eq!(font.source, FontSource::Path(font.path.clone()));
+        assert!(
+            FontConfig::from_bytes("Embedded", b"Embedded")
+                .path
+                .as_os_str()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_plugin_builder_chain() {
+        use crate::prelude::{EguiFontPlugin, FontConfig};
+
+        let plugin = EguiFontPlugin::new()
+            .with_font("Noto", "assets/fonts/Noto.ttf")
+            .with_font_bytes("Embedded", b"Embedded")
+            .with_font_config(
+                FontConfig::from_asset("Song", "fonts/STSong.ttf")
+                    .primary()
+                    .with_fallback("Noto"),
+            )
+            .with_theme(egui::Theme::Dark);
+
+        let names: Vec<_> = plugin.fonts.iter().map(|font| font.name.as_str()).collect();
+        assert_eq!(names, ["Noto", "Embedded", "Song"]);
+        assert!(matches!(
+            plugin.fonts[1].source,
+            FontSource::Bytes(b"Embedded")
+        ));
+        let song = &plugin.fonts[2];
+        assert!(song.primary_proportional && song.primary_monospace);
+        assert_eq!(song.fallbacks, ["Noto"]);
+        assert_eq!(plugin.theme, Some(egui::Theme::Dark));
     }
 }
@@ -21,6 +21,7 @@
 //!
 //! - [`serial`]: Core serial port communication functionality
 //! - [`serial_ui`]: User interface components for serial communication
+//! - [`fonts`]: Font loading for the egui interface
 //! - [`error`]: Custom error types for the application
 
 #![allow(clippy::mut_mutex_lock)]
@@ -33,7 +34,7 @@ pub mod serial_ui;
 /// Re-exports for convenience
 pub mod prelude {
     pub use crate::error::*;
-    pub use crate::fonts::EguiFontPlugin;
+    pub use crate::fonts::{EguiFontPlugin, FontConfig};
     pub use crate::serial::SerialPlugin;
     pub use crate::serial_ui::SerialUiPlugin;
 }
@@ -10,10 +10,14 @@
 //! - Hex and UTF-8 data encoding
 //! - Command history with arrow key navigation
 //! - Optional LLM integration
+//! - `--mock` flag to add a virtual demo port (no hardware required)
+//! - `--port <name>` and `--baud <rate>` to open a port at startup
 
 use bevy::prelude::*;
 use serial_bevy::fonts::FontConfig;
 use serial_bevy::prelude::*;
+use serial_bevy::serial::mock::MockPortConfig;
+use serial_bevy::serial::startup::StartupArgs;
 
 /// Application entry point.
 fn main() {
@@ -35,7 +39,9 @@ fn main() {
                 })
                 .build(),
         )
-        .add_plugins(SerialPlugin)
+        .insert_resource(MockPortConfig::from_args(std::env::args().skip(1)))
+        .insert_resource(StartupArgs::from_args_or_env(std::env::args().skip(1)))
+        .add_plugins(SerialPlugin::default())
         .add_plugins(
             EguiFontPlugin::default()
                 .with_font_config(FontConfig::new("Song", "assets/fonts/STSong.ttf").primary()),
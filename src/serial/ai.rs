@@ -2,26 +2,97 @@
 //!
 //! LLM request orchestration and response handling for serial port AI features.
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
 use bevy::prelude::*;
+use log::warn;
 
-use super::Serials;
 use super::data::{AiChannel, AiResponse};
 use super::discovery::Runtime;
-use super::llm::LlmMessage;
+use super::llm::{DEFAULT_BASE_URL, LlmMessage, LlmProvider, chat_endpoint};
+use super::port::Serial;
+use super::snapshot::lock_serial;
+use super::status::{SerialIo, SerialPortName};
 
-/// Sends an AI chat request using zai-rs.
+/// Sends a streaming chat request to an OpenAI-compatible chat completions
+/// endpoint, or to the Anthropic Messages API.
+///
+/// `body` is built with [`chat_request_body`] or [`LlmConfig::request_body`],
+/// and `headers`, carrying the key, with
+/// [`LlmConfig::build_request_headers`]. Tokens are appended to `partial` as
+/// Server-Sent Events arrive. Endpoints that ignore `stream` and answer with a
+/// single JSON response are supported too. Returns the complete response
+/// text.
+///
+/// [`chat_request_body`]: super::llm::chat_request_body
+/// [`LlmConfig::request_body`]: super::llm::LlmConfig::request_body
+/// [`LlmConfig::build_request_headers`]: super::llm::LlmConfig::build_request_headers
 pub async fn send_ai_chat(
+    url: String,
+    headers: HashMap<String, String>,
+    mut body: serde_json::Value,
+    partial: Arc<Mutex<String>>,
+) -> Result<String, String> {
+    if body["messages"].as_array().is_none_or(Vec::is_empty) {
+        return Err("No messages to send".to_string());
+    }
+    body["stream"] = serde_json::Value::Bool(true);
+
+    let mut request = reqwest::Client::new().post(&url).json(&body);
+    for (name, value) in &headers {
+        request = request.header(name, value);
+    }
+    let mut response = request.send().await.map_err(|e| e.to_string())?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        return Err(format!("HTTP {status}: {text}"));
+    }
+
+    let is_event_stream = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    if !is_event_stream {
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        let value: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        return Ok(value["choices"][0]["message"]["content"]
+            .as_str()
+            .or_else(|| value["content"][0]["text"].as_str())
+            .unwrap_or_default()
+            .to_string());
+    }
+
+    let mut parser = SseParser::default();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        parser.append_chunk(&chunk, &partial);
+        if parser.is_done() {
+            break;
+        }
+    }
+    Ok(partial.lock().map(|text| text.clone()).unwrap_or_default())
+}
+
+/// Sends a chat request to the Zhipu AI endpoint using zai-rs.
+///
+/// Used for the [`LlmProvider::ZhipuAI`] provider at its default endpoint;
+/// the answer arrives as a whole rather than streamed.
+pub async fn send_zai_chat(
     model: &str,
     key: String,
     with_coding_plan: bool,
     messages: Vec<LlmMessage>,
+    temperature: f32,
+    max_tokens: u32,
 ) -> Result<String, String> {
     use zai_rs::model::{chat_base_response::ChatCompletionResponse, *};
 
     let mut chat_msgs: Vec<TextMessage> = messages
         .iter()
         .map(|m| match m.role.as_str() {
-            "user" => TextMessage::user(&m.content),
             "assistant" => TextMessage::assistant(&m.content),
             _ => TextMessage::user(&m.content),
         })
@@ -35,12 +106,14 @@ pub async fn send_ai_chat(
 
     /// Macro to dispatch to a specific GLM model type, reducing repetitive match arms.
     macro_rules! glm_chat {
-        ($model:ident, $first:ident, $key:ident, $chat_msgs:ident, $with_coding_plan:ident) => {{
-            let mut c = ChatCompletion::new($model {}, $first, $key);
-            for m in $chat_msgs {
+        ($model:ident) => {{
+            let mut c = ChatCompletion::new($model {}, first, key)
+                .with_temperature(temperature)
+                .with_max_tokens(max_tokens);
+            for m in chat_msgs {
                 c = c.add_messages(m);
             }
-            if $with_coding_plan {
+            if with_coding_plan {
                 c = c.with_coding_plan();
             }
             c.send().await.map_err(|e| e.to_string())?
@@ -48,18 +121,18 @@ pub async fn send_ai_chat(
     }
 
     let resp: ChatCompletionResponse = match model {
-        "glm-5.1" => glm_chat!(GLM5_1, first, key, chat_msgs, with_coding_plan),
-        "glm-5" => glm_chat!(GLM5, first, key, chat_msgs, with_coding_plan),
-        "glm-5-turbo" => glm_chat!(GLM5_turbo, first, key, chat_msgs, with_coding_plan),
-        "glm-4.7" => glm_chat!(GLM4_7, first, key, chat_msgs, with_coding_plan),
-        "glm-4.7-flash" => glm_chat!(GLM4_7_flash, first, key, chat_msgs, with_coding_plan),
-        "glm-4.7-flashx" => glm_chat!(GLM4_7_flashx, first, key, chat_msgs, with_coding_plan),
-        "glm-4.6" => glm_chat!(GLM4_6, first, key, chat_msgs, with_coding_plan),
-        "glm-4.5" => glm_chat!(GLM4_5, first, key, chat_msgs, with_coding_plan),
-        "glm-4.5-flash" => glm_chat!(GLM4_5_flash, first, key, chat_msgs, with_coding_plan),
-        "glm-4.5-air" => glm_chat!(GLM4_5_air, first, key, chat_msgs, with_coding_plan),
-        "glm-4.5-X" => glm_chat!(GLM4_5_x, first, key, chat_msgs, with_coding_plan),
-        "glm-4.5-airx" => glm_chat!(GLM4_5_airx, first, key, chat_msgs, with_coding_plan),
+        "glm-5.1" => glm_chat!(GLM5_1),
+        "glm-5" => glm_chat!(GLM5),
+        "glm-5-turbo" => glm_chat!(GLM5_turbo),
+        "glm-4.7" => glm_chat!(GLM4_7),
+        "glm-4.7-flash" => glm_chat!(GLM4_7_flash),
+        "glm-4.7-flashx" => glm_chat!(GLM4_7_flashx),
+        "glm-4.6" => glm_chat!(GLM4_6),
+        "glm-4.5" => glm_chat!(GLM4_5),
+        "glm-4.5-flash" => glm_chat!(GLM4_5_flash),
+        "glm-4.5-air" => glm_chat!(GLM4_5_air),
+        "glm-4.5-X" => glm_chat!(GLM4_5_x),
+        "glm-4.5-airx" => glm_chat!(GLM4_5_airx),
         _ => return Err(format!("Unknown model: {model}")),
     };
 
@@ -73,120 +146,493 @@ pub async fn send_ai_chat(
     Ok(text)
 }
 
-/// System: processes pending AI chat requests.
+/// Incremental parser of a chat completions or Anthropic Messages
+/// Server-Sent Events stream.
+///
+/// Events may be split across any number of chunks, including in the middle
+/// of a UTF-8 character.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    /// Bytes of the line being received.
+    line: Vec<u8>,
+    /// Whether the `[DONE]` or `message_stop` event was received.
+    done: bool,
+}
+
+impl SseParser {
+    /// Processes a chunk of the stream and returns the content tokens it completed.
+    pub fn push(&mut self, data: &[u8]) -> Vec<String> {
+        let mut tokens = Vec::new();
+        for &byte in data {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&line);
+            let Some(payload) = line.trim_end_matches('\r').strip_prefix("data:") else {
+                continue;
+            };
+            let payload = payload.trim_start();
+            if payload == "[DONE]" {
+                self.done = true;
+                continue;
+            }
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(payload) else {
+                continue;
+            };
+            if value["type"] == "message_stop" {
+                self.done = true;
+            } else if let Some(token) = delta_content(&value) {
+                tokens.push(token);
+            }
+        }
+        tokens
+    }
+
+    /// Processes a chunk of the stream and appends its tokens to `partial`.
+    pub fn append_chunk(&mut self, data: &[u8], partial: &Mutex<String>) {
+        let tokens = self.push(data);
+        if let Ok(mut text) = partial.lock() {
+            text.extend(tokens);
+        }
+    }
+
+    /// Returns true once the `[DONE]` or `message_stop` event was received.
+    #[must_use]
+    pub const fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Extracts the content token from a streamed chat completion chunk, or
+/// from an Anthropic `content_block_delta` event.
+fn delta_content(value: &serde_json::Value) -> Option<String> {
+    let content = value["choices"][0]["delta"]["content"]
+        .as_str()
+        .or_else(|| value["delta"]["text"].as_str())?;
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+/// Asks the LLM of a port to answer a message from the user.
+///
+/// Handled by [`process_llm_requests`], which answers with a
+/// [`LlmResponseEvent`].
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct LlmRequestEvent {
+    /// Name of the port whose conversation the message belongs to.
+    pub port_name: String,
+    /// Message from the user.
+    pub user_message: String,
+}
+
+/// Answer of the LLM of a port, or the error of its request.
 ///
-/// This system runs every frame and checks if there is a pending AI chat request
-/// that needs to be sent. It ensures:
-/// - LLM features are enabled for the serial port
-/// - A request is currently being processed (user clicked send)
-/// - No request is already in flight
-/// - The API key and model are configured
-/// - The last message is from the user (indicating we need to respond)
-pub fn process_ai_requests(
-    mut serials: Query<&mut Serials>,
+/// Not sent for requests stopped by the user.
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct LlmResponseEvent {
+    /// Name of the port the request was made for.
+    pub port_name: String,
+    /// Answer of the LLM, or the error message.
+    pub response: String,
+    /// Whether the request succeeded.
+    pub success: bool,
+}
+
+/// System: sends the LLM requests of [`LlmRequestEvent`]s.
+///
+/// The message is added to the conversation of the matching port, and the
+/// conversation is sent along with the port's recent data if enabled. The
+/// answer comes back as a [`LlmResponseEvent`] from
+/// [`receive_ai_responses`], or right away if the request cannot be sent.
+/// Requests for unknown ports, or for ports already waiting for an answer,
+/// are dropped.
+pub fn process_llm_requests(
+    mut requests: MessageReader<LlmRequestEvent>,
+    ports: Query<(&SerialPortName, &SerialIo)>,
     runtime: Res<Runtime>,
     ai_channel: Res<AiChannel>,
     app_config: Res<crate::serial_ui::PanelWidths>,
+    mut events: MessageWriter<LlmResponseEvent>,
 ) {
-    let Ok(mut serials) = serials.single_mut() else {
-        return;
-    };
-
-    for serial in &mut serials.serial {
-        let Ok(mut serial) = serial.lock() else {
+    for request in requests.read() {
+        let Some((_, io)) = ports.iter().find(|(name, _)| name.0 == request.port_name) else {
+            warn!("LLM request for unknown port {}", request.port_name);
             continue;
         };
-
-        let port_name = serial.set.port_name.clone();
-        let llm = serial.llm();
-        if !llm.enable || !llm.is_processing || app_config.llm_key.is_empty() {
+        let mut serial = lock_serial(&io.0);
+        if serial.llm().is_processing {
+            warn!(
+                "{}: LLM request dropped while another one is running",
+                request.port_name
+            );
             continue;
         }
-        // Prevent spawning duplicate requests every frame while waiting for response
-        if llm.request_in_flight {
-            continue;
+        if let Err(error) = send_llm_request(
+            &mut serial,
+            &request.user_message,
+            &runtime,
+            &ai_channel,
+            &app_config,
+        ) {
+            serial.llm().fail_request(error.clone());
+            events.write(LlmResponseEvent {
+                port_name: request.port_name.clone(),
+                response: error,
+                success: false,
+            });
         }
+    }
+}
 
-        // Take the messages to send
-        let messages = llm.messages.clone();
-        let model = app_config.llm_model.clone();
-        let key = app_config.llm_key.clone();
-        let with_coding_plan = app_config.llm_with_coding_plan;
+/// Adds `user_message` to the conversation of `serial` and spawns the
+/// request answering it. Returns the error if it cannot be sent.
+fn send_llm_request(
+    serial: &mut Serial,
+    user_message: &str,
+    runtime: &Runtime,
+    ai_channel: &AiChannel,
+    app_config: &crate::serial_ui::PanelWidths,
+) -> Result<(), String> {
+    let port_name = serial.set.port_name.clone();
+    let llm = serial.llm();
+    llm.add_user_message(user_message);
+    llm.last_error = None;
+    *llm.enable() = true;
+    llm.is_processing = true;
+    if llm.provider.needs_key() && app_config.llm_key.is_empty() {
+        return Err("No API key set".to_string());
+    }
 
-        // Check if the last message is from user (we need to respond)
-        let should_send = messages.last().map(|m| m.role == "user").unwrap_or(false);
-        if !should_send {
-            continue;
-        }
+    // Build the request from the conversation, recent data and endpoint settings
+    let window = llm.include_recent_data.then_some(llm.context_window_bytes);
+    let recent_data = window
+        .map(|bytes| serial.data().recent_received_text(bytes))
+        .unwrap_or_default();
+    let llm = serial.llm();
+    let base_url = if llm.base_url.trim().is_empty() {
+        &app_config.llm_base_url
+    } else {
+        &llm.base_url
+    };
+    // The Zhipu AI endpoint is asked through zai-rs, any other one over HTTP
+    let use_zai = llm.provider == LlmProvider::ZhipuAI && base_url == DEFAULT_BASE_URL;
+    let url = chat_endpoint(base_url, app_config.llm_with_coding_plan).to_string();
+    let headers = llm.build_request_headers(&app_config.llm_key);
+    let body = llm.request_body(&app_config.llm_model, &recent_data);
+    let messages = llm.current_messages(&recent_data);
+    let (temperature, max_tokens) = (llm.temperature, llm.max_tokens);
+    let model = app_config.llm_model.clone();
+    let key = app_config.llm_key.clone();
+    let with_coding_plan = app_config.llm_with_coding_plan;
 
-        // Mark request as dispatched so we don't spawn again next frame
-        llm.request_in_flight = true;
-        let tx = ai_channel
-            .tx
-            .lock()
-            .expect("AI channel tx poisoned")
-            .clone();
-
-        // Spawn async task
-        runtime.spawn(async move {
-            let result = send_ai_chat(&model, key, with_coding_plan, messages).await;
-
-            match result {
-                Ok(content) => {
-                    let _ = tx.send(AiResponse {
-                        port_name,
-                        content,
-                        is_error: false,
-                    });
-                }
-                Err(content) => {
-                    let _ = tx.send(AiResponse {
-                        port_name,
-                        content,
-                        is_error: true,
-                    });
-                }
-            }
+    let tx = ai_channel
+        .tx
+        .lock()
+        .map(|tx| tx.clone())
+        .map_err(|_| "AI response channel is unavailable".to_string())?;
+    llm.request_in_flight = true;
+    let partial = llm.start_streaming();
+
+    llm.request_task = Some(runtime.spawn(async move {
+        let result = if use_zai {
+            send_zai_chat(
+                &model,
+                key,
+                with_coding_plan,
+                messages,
+                temperature,
+                max_tokens,
+            )
+            .await
+        } else {
+            send_ai_chat(url, headers, body, partial).await
+        };
+        let (content, is_error) = match result {
+            Ok(content) => (content, false),
+            Err(content) => (content, true),
+        };
+        let _ = tx.send(AiResponse {
+            port_name,
+            content,
+            is_error,
         });
-    }
+    }));
+    Ok(())
 }
 
 /// System: receives AI chat responses and updates serial state.
 ///
 /// This system runs every frame and checks for incoming AI chat responses.
 /// When a response is received, it updates the corresponding serial port's
-/// LLM configuration with the assistant's message.
-pub fn receive_ai_responses(mut serials: Query<&mut Serials>, ai_channel: Res<AiChannel>) {
-    let Ok(mut serials) = serials.single_mut() else {
+/// LLM configuration with the assistant's message, or with the error shown in
+/// the panel's error banner, and sends it as a [`LlmResponseEvent`].
+/// Responses of requests stopped by the user are dropped.
+pub fn receive_ai_responses(
+    ports: Query<(&SerialPortName, &SerialIo)>,
+    ai_channel: Res<AiChannel>,
+    mut events: MessageWriter<LlmResponseEvent>,
+) {
+    while let Some(response) = ai_channel.rx.lock().ok().and_then(|rx| rx.try_recv().ok()) {
+        let Some((_, io)) = ports.iter().find(|(name, _)| name.0 == response.port_name) else {
+            continue;
+        };
+        let mut serial = lock_serial(&io.0);
+        if !serial.llm().request_in_flight {
+            continue;
+        }
+        if response.is_error {
+            serial.llm().fail_request(response.content.clone());
+        } else {
+            serial.llm().finish_request();
+            serial.llm().add_assistant_message(&response.content);
+        }
+        events.write(LlmResponseEvent {
+            port_name: response.port_name,
+            response: response.content,
+            success: !response.is_error,
+        });
+    }
+}
+
+/// System: saves the LLM conversation of every port when the app exits.
+pub fn save_llm_conversations_on_exit(
+    ports: Query<&SerialIo>,
+    mut exit_events: MessageReader<AppExit>,
+) {
+    if exit_events.is_empty() {
         return;
-    };
+    }
+    exit_events.clear();
+    for port in &ports {
+        lock_serial(&port.0).save_llm_conversation();
+    }
+}
 
-    while let Ok(response) = ai_channel
-        .rx
-        .lock()
-        .expect("AI channel rx poisoned")
-        .try_recv()
-    {
-        for serial in &mut serials.serial {
-            let Ok(mut serial) = serial.lock() else {
-                continue;
-            };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::Serials;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::time::{Duration, Instant};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
 
-            if serial.set.port_name != response.port_name {
-                continue;
+    /// Answers one HTTP request on `listener` with `status` and the JSON
+    /// `body`, returning the request as received.
+    async fn serve_once(listener: TcpListener, status: &str, body: String) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    break;
+                }
             }
+        }
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).into_owned()
+    }
+
+    /// Builds a world with the port `COM1` and an LLM endpoint at `url`.
+    ///
+    /// The port's own endpoint is set too, so requests never reach the
+    /// provider's real endpoint whatever the per-port default is.
+    fn llm_world(runtime: Runtime, url: String) -> World {
+        let mut serial = Serial::new();
+        serial.set.port_name = "COM1".to_string();
+        serial.llm().base_url.clone_from(&url);
+        let mut serials = Serials::new();
+        serials.add(serial);
+
+        let mut world = World::new();
+        crate::serial::status::spawn_ports(&mut world, serials);
+        world.insert_resource(runtime);
+        world.insert_resource(AiChannel::init());
+        world.insert_resource(crate::serial_ui::PanelWidths {
+            llm_key: "test-key".to_string(),
+            llm_base_url: url,
+            ..Default::default()
+        });
+        world.init_resource::<Messages<LlmRequestEvent>>();
+        world.init_resource::<Messages<LlmResponseEvent>>();
+        world
+    }
 
-            serial.llm().is_processing = false;
-            serial.llm().request_in_flight = false;
+    /// Sends `user_message` to the LLM of `COM1` and waits for the answer.
+    fn request_roundtrip(world: &mut World, user_message: &str) -> Vec<LlmResponseEvent> {
+        world.resource_mut::<Messages<LlmResponseEvent>>().clear();
+        let mut requests = world.resource_mut::<Messages<LlmRequestEvent>>();
+        requests.clear();
+        requests.write(LlmRequestEvent {
+            port_name: "COM1".to_string(),
+            user_message: user_message.to_string(),
+        });
+        world.run_system_once(process_llm_requests).unwrap();
 
-            if response.is_error {
-                serial
-                    .llm()
-                    .add_assistant_message(&format!("Error: {}", response.content));
-            } else {
-                serial.llm().add_assistant_message(&response.content);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            world.run_system_once(receive_ai_responses).unwrap();
+            let events: Vec<_> = world
+                .resource::<Messages<LlmResponseEvent>>()
+                .iter_current_update_messages()
+                .cloned()
+                .collect();
+            if !events.is_empty() || Instant::now() > deadline {
+                return events;
             }
-            break;
+            std::thread::sleep(Duration::from_millis(10));
         }
     }
+
+    fn with_serial<T>(world: &mut World, f: impl FnOnce(&mut Serial) -> T) -> T {
+        let mut query = world.query::<&SerialIo>();
+        let port = query.single(&*world).unwrap();
+        f(&mut port.0.lock().unwrap())
+    }
+
+    #[test]
+    fn test_llm_request_event_roundtrip() {
+        let runtime = Runtime::init();
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}/chat", listener.local_addr().unwrap());
+        let body = r#"{"choices":[{"message":{"content":"pong"}}]}"#.to_string();
+        let server = runtime.spawn(serve_once(listener, "200 OK", body));
+        let mut world = llm_world(runtime, url);
+
+        let events = request_roundtrip(&mut world, "ping");
+        assert_eq!(
+            events,
+            [LlmResponseEvent {
+                port_name: "COM1".to_string(),
+                response: "pong".to_string(),
+                success: true,
+            }]
+        );
+        let request = world.resource::<Runtime>().block_on(server).unwrap();
+        assert!(request.starts_with("POST /chat"));
+        assert!(request.contains("Bearer test-key"));
+        assert!(request.contains("\"ping\""));
+        with_serial(&mut world, |serial| {
+            let llm = serial.llm();
+            assert!(!llm.is_processing);
+            let messages: Vec<_> = llm
+                .messages
+                .iter()
+                .map(|m| (m.role.as_str(), m.content.as_str()))
+                .collect();
+            assert_eq!(messages, [("user", "ping"), ("assistant", "pong")]);
+        });
+    }
+
+    #[test]
+    fn test_llm_request_event_reports_errors() {
+        let runtime = Runtime::init();
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}/chat", listener.local_addr().unwrap());
+        let server = runtime.spawn(serve_once(
+            listener,
+            "500 Internal Server Error",
+            "{}".to_string(),
+        ));
+        let mut world = llm_world(runtime, url);
+
+        let events = request_roundtrip(&mut world, "ping");
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].success);
+        assert!(events[0].response.starts_with("HTTP 500"));
+        world.resource::<Runtime>().block_on(server).unwrap();
+        with_serial(&mut world, |serial| {
+            assert_eq!(
+                serial.llm().last_error.as_deref(),
+                Some(events[0].response.as_str())
+            );
+        });
+
+        // Without a key nothing is sent, and the error comes back at once.
+        world
+            .resource_mut::<crate::serial_ui::PanelWidths>()
+            .llm_key
+            .clear();
+        let events = request_roundtrip(&mut world, "again");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].response, "No API key set");
+    }
+
+    fn event(token: &str) -> String {
+        format!("data: {{\"choices\":[{{\"delta\":{{\"content\":\"{token}\"}}}}]}}\n\n")
+    }
+
+    #[test]
+    fn test_sse_parser_accumulates_partial_tokens() {
+        let stream = format!(
+            "{}{}: keep-alive\n\n{}data: [DONE]\n\n",
+            event("Hel"),
+            event("lo"),
+            event(" wörld")
+        );
+        let bytes = stream.as_bytes();
+        let partial = Arc::new(Mutex::new(String::new()));
+        let mut parser = SseParser::default();
+
+        // Split in the middle of the first event.
+        parser.append_chunk(&bytes[..20], &partial);
+        assert_eq!(*partial.lock().unwrap(), "");
+        let first_end = event("Hel").len() + 5;
+        parser.append_chunk(&bytes[20..first_end], &partial);
+        assert_eq!(*partial.lock().unwrap(), "Hel");
+
+        // Split in the middle of the two-byte 'ö'.
+        let split = stream.find('ö').unwrap() + 1;
+        parser.append_chunk(&bytes[first_end..split], &partial);
+        assert_eq!(*partial.lock().unwrap(), "Hello");
+        assert!(!parser.is_done());
+        parser.append_chunk(&bytes[split..], &partial);
+        assert_eq!(*partial.lock().unwrap(), "Hello wörld");
+        assert!(parser.is_done());
+    }
+
+    #[test]
+    fn test_sse_parser_ignores_non_content_events() {
+        let mut parser = SseParser::default();
+        let tokens = parser.push(
+            b"data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\r\n\r\n\
+              event: ping\r\ndata: not json\r\n\r\n\
+              data:{\"choices\":[{\"delta\":{\"content\":\"ok\"}}]}\r\n",
+        );
+        assert_eq!(tokens, ["ok"]);
+        assert!(!parser.is_done());
+    }
+
+    #[test]
+    fn test_sse_parser_reads_anthropic_events() {
+        let mut parser = SseParser::default();
+        let tokens = parser.push(
+            b"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{}}\n\n\
+              event: content_block_delta\n\
+              data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n\
+              event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+        );
+        assert_eq!(tokens, ["Hi"]);
+        assert!(parser.is_done());
+    }
 }
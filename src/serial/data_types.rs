@@ -4,10 +4,13 @@
 
 use std::fmt;
 
+use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
+
 /// Data encoding type for serial communication.
 ///
 /// This enum defines the supported data encoding formats for serial port data.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect)]
 pub enum DataType {
     /// Binary data.
     Binary,
@@ -21,8 +24,16 @@ pub enum DataType {
     Utf32,
     /// GBK encoding.
     Gbk,
+    /// GB2312 encoding.
+    Gb2312,
+    /// Big5 encoding.
+    Big5,
     /// ASCII text.
     Ascii,
+    /// Intel HEX records.
+    IntelHex,
+    /// Bytes passed through unmodified, shown as `\xNN` escapes.
+    Raw,
 }
 
 impl fmt::Display for DataType {
@@ -34,12 +45,31 @@ impl fmt::Display for DataType {
             Self::Utf16 => write!(f, "UTF-16"),
             Self::Utf32 => write!(f, "UTF-32"),
             Self::Gbk => write!(f, "GBK"),
+            Self::Gb2312 => write!(f, "GB2312"),
+            Self::Big5 => write!(f, "Big5"),
             Self::Ascii => write!(f, "ASCII"),
+            Self::IntelHex => write!(f, "Intel HEX"),
+            Self::Raw => write!(f, "Raw"),
         }
     }
 }
 
 impl DataType {
+    /// All data types, in the order they are offered in the UI.
+    pub const ALL: [Self; 11] = [
+        Self::Hex,
+        Self::Utf8,
+        Self::Ascii,
+        Self::Binary,
+        Self::Utf16,
+        Self::Utf32,
+        Self::Gbk,
+        Self::Gb2312,
+        Self::Big5,
+        Self::IntelHex,
+        Self::Raw,
+    ];
+
     /// Gets the English name of the data type.
     #[must_use]
     pub const fn as_str_en(&self) -> &'static str {
@@ -50,7 +80,11 @@ impl DataType {
             Self::Utf16 => "UTF-16",
             Self::Utf32 => "UTF-32",
             Self::Gbk => "GBK",
+            Self::Gb2312 => "GB2312",
+            Self::Big5 => "Big5",
             Self::Ascii => "ASCII",
+            Self::IntelHex => "Intel HEX",
+            Self::Raw => "Raw",
         }
     }
 
@@ -64,7 +98,11 @@ impl DataType {
             Self::Utf16 => "UTF-16 text encoding",
             Self::Utf32 => "UTF-32 text encoding",
             Self::Gbk => "GBK Chinese encoding",
+            Self::Gb2312 => "GB2312 Simplified Chinese encoding",
+            Self::Big5 => "Big5 Traditional Chinese encoding",
             Self::Ascii => "ASCII text encoding",
+            Self::IntelHex => "Intel HEX firmware records",
+            Self::Raw => "Raw bytes (\\xNN display)",
         }
     }
 }
@@ -81,7 +119,11 @@ mod tests {
         assert_eq!(format!("{}", DataType::Utf16), "UTF-16");
         assert_eq!(format!("{}", DataType::Utf32), "UTF-32");
         assert_eq!(format!("{}", DataType::Gbk), "GBK");
+        assert_eq!(format!("{}", DataType::Gb2312), "GB2312");
+        assert_eq!(format!("{}", DataType::Big5), "Big5");
         assert_eq!(format!("{}", DataType::Ascii), "ASCII");
+        assert_eq!(format!("{}", DataType::IntelHex), "Intel HEX");
+        assert_eq!(format!("{}", DataType::Raw), "Raw");
     }
 
     #[test]
@@ -96,5 +138,6 @@ mod tests {
         assert_eq!(DataType::Binary.description(), "Binary data format");
         assert_eq!(DataType::Hex.description(), "Hexadecimal data format");
         assert_eq!(DataType::Utf8.description(), "UTF-8 text encoding");
+        assert_eq!(DataType::Raw.description(), "Raw bytes (\\xNN display)");
     }
 }
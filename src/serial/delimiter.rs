@@ -0,0 +1,175 @@
+//! # Delimiter Module
+//!
+//! Splitting of received data into records ended by a delimiter, such as
+//! `\r\n`, independent of how the bytes arrive in reads.
+//!
+//! The delimiter is found with the Boyer-Moore-Horspool algorithm, which
+//! skips ahead by up to the delimiter length on each mismatch.
+
+use super::port::MAX_FRAME_SIZE;
+
+/// Largest record kept while waiting for its delimiter; longer data is
+/// emitted as it is.
+pub const MAX_RECORD_SIZE: usize = 16 * MAX_FRAME_SIZE;
+
+/// How received data is split into records.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PacketDelimiter {
+    /// Data is passed on as it is read.
+    #[default]
+    None,
+    /// Data is passed on in records ending with these bytes.
+    Bytes(Vec<u8>),
+}
+
+/// Returns the index of the first occurrence of `needle` in `haystack`, or
+/// `None` if there is none or `needle` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use serial_bevy::serial::delimiter::find_delimiter;
+///
+/// assert_eq!(find_delimiter(b"OK\r\nREADY\r\n", b"\r\n"), Some(2));
+/// assert_eq!(find_delimiter(b"OK\r", b"\r\n"), None);
+/// ```
+#[must_use]
+pub fn find_delimiter(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let last = needle.len().checked_sub(1)?;
+    if haystack.len() < needle.len() {
+        return None;
+    }
+    // Distance from the last occurrence of each byte in the needle, ignoring
+    // its final byte, to the needle's end.
+    let mut skip = [needle.len(); 256];
+    for (index, byte) in needle[..last].iter().enumerate() {
+        skip[usize::from(*byte)] = last - index;
+    }
+    let mut start = 0;
+    while start + last < haystack.len() {
+        let window = &haystack[start..=start + last];
+        if window == needle {
+            return Some(start);
+        }
+        start += skip[usize::from(window[last])];
+    }
+    None
+}
+
+/// Splits received data into records ended by a delimiter, keeping an
+/// incomplete record until the rest of it arrives.
+#[derive(Debug, Default)]
+pub struct RecordSplitter {
+    /// Received bytes after the last complete record.
+    buffer: Vec<u8>,
+    /// Bytes at the start of `buffer` already known not to contain the
+    /// delimiter.
+    searched: usize,
+}
+
+impl RecordSplitter {
+    /// Adds received `data` and returns the records it completes, each
+    /// including its delimiter.
+    ///
+    /// Without a delimiter the data is returned as one record, after any
+    /// incomplete record kept from before. If the incomplete record grows
+    /// past [`MAX_RECORD_SIZE`], it is returned as it is.
+    pub fn push(&mut self, data: &[u8], delimiter: &PacketDelimiter) -> Vec<Vec<u8>> {
+        let delimiter = match delimiter {
+            PacketDelimiter::Bytes(delimiter) if !delimiter.is_empty() => delimiter,
+            _ => {
+                let mut record = self.take();
+                record.extend_from_slice(data);
+                return vec![record];
+            }
+        };
+        self.buffer.extend_from_slice(data);
+        let mut records = Vec::new();
+        let mut start = 0;
+        let mut from = self.searched;
+        while let Some(index) = find_delimiter(&self.buffer[from..], delimiter) {
+            let end = from + index + delimiter.len();
+            records.push(self.buffer[start..end].to_vec());
+            start = end;
+            from = end;
+        }
+        self.buffer.drain(..start);
+        // The end of the buffer may hold the start of a delimiter.
+        self.searched = self.buffer.len().saturating_sub(delimiter.len() - 1);
+        if self.buffer.len() > MAX_RECORD_SIZE {
+            records.push(self.take());
+        }
+        records
+    }
+
+    /// Returns the incomplete record and empties the buffer.
+    pub fn take(&mut self) -> Vec<u8> {
+        self.searched = 0;
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Returns the bytes of the incomplete record.
+    #[must_use]
+    pub fn pending(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_delimiter() {
+        assert_eq!(find_delimiter(b"abcabd", b"abd"), Some(3));
+        assert_eq!(find_delimiter(b"\r\r\n", b"\r\n"), Some(1));
+        assert_eq!(find_delimiter(b"aaaa", b"a"), Some(0));
+        assert_eq!(find_delimiter(b"abc", b""), None);
+        assert_eq!(find_delimiter(b"ab", b"abc"), None);
+        assert_eq!(find_delimiter(b"xx\x0D\x0Ayy", &[0x0D, 0x0A]), Some(2));
+    }
+
+    #[test]
+    fn test_crlf_segments_stream() {
+        let delimiter = PacketDelimiter::Bytes(vec![0x0D, 0x0A]);
+        let mut splitter = RecordSplitter::default();
+        let reads: [&[u8]; 5] = [b"OK", b"\r", b"\nREADY\r\nTEMP=2", b"1\r\n\r", b"\n"];
+        let records: Vec<Vec<u8>> = reads
+            .iter()
+            .flat_map(|read| splitter.push(read, &delimiter))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                b"OK\r\n".to_vec(),
+                b"READY\r\n".to_vec(),
+                b"TEMP=21\r\n".to_vec(),
+                b"\r\n".to_vec(),
+            ]
+        );
+        assert!(splitter.pending().is_empty());
+
+        assert!(splitter.push(b"partial\r", &delimiter).is_empty());
+        assert_eq!(splitter.take(), b"partial\r");
+    }
+
+    #[test]
+    fn test_without_delimiter_data_passes_through() {
+        let mut splitter = RecordSplitter::default();
+        assert_eq!(
+            splitter.push(b"a\r\nb", &PacketDelimiter::None),
+            [b"a\r\nb".to_vec()]
+        );
+        assert!(splitter.pending().is_empty());
+    }
+
+    #[test]
+    fn test_long_record_is_emitted() {
+        let delimiter = PacketDelimiter::Bytes(b"\r\n".to_vec());
+        let mut splitter = RecordSplitter::default();
+        let records = splitter.push(&vec![b'x'; MAX_RECORD_SIZE + 1], &delimiter);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].len(), MAX_RECORD_SIZE + 1);
+        assert!(splitter.pending().is_empty());
+    }
+}
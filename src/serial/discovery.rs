@@ -2,23 +2,36 @@
 //!
 //! Port discovery and tokio runtime management.
 
+use std::collections::HashMap;
+use std::time::Duration;
+
 use bevy::prelude::*;
-use log::{debug, error};
-use tokio_serial::available_ports;
+use log::{debug, error, info, warn};
+use tokio::sync::{broadcast, watch};
+use tokio_serial::{SerialPortType, available_ports};
 
 use super::Serials;
 use super::data::SerialNameChannel;
-use super::selection::Selected;
+use super::mock::{MOCK_DEMO_PORT, MockPortConfig};
+use super::port::PortSettings;
+use super::snapshot::lock_serial;
 use super::state::PortChannelData;
+use super::status::SerialIo;
+
+/// Longest time [`Runtime::shutdown`] waits for tasks still running.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Tokio runtime resource for async operations.
 ///
 /// This resource wraps the Tokio runtime to enable async operations
-/// within the Bevy ECS framework.
+/// within the Bevy ECS framework. Long-running tasks watch a
+/// [`ShutdownSignal`] so they stop when the app exits.
 #[derive(Resource)]
 pub struct Runtime {
     /// The Tokio runtime instance.
     rt: tokio::runtime::Runtime,
+    /// Set to true when the app shuts down.
+    shutdown: watch::Sender<bool>,
 }
 
 impl Runtime {
@@ -31,6 +44,27 @@ impl Runtime {
     pub fn init() -> Self {
         Self {
             rt: tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime"),
+            shutdown: watch::channel(false).0,
+        }
+    }
+
+    /// Creates a single-threaded runtime whose clock is paused and only
+    /// advances when every task waits on a timer, for timing tests that do
+    /// not depend on the load of the machine.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the Tokio runtime cannot be created.
+    #[cfg(test)]
+    #[must_use]
+    pub(crate) fn paused() -> Self {
+        Self {
+            rt: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .start_paused(true)
+                .build()
+                .expect("Failed to create Tokio runtime"),
+            shutdown: watch::channel(false).0,
         }
     }
 
@@ -42,6 +76,31 @@ impl Runtime {
     {
         self.rt.spawn(future)
     }
+
+    /// Runs a future to completion on the runtime.
+    ///
+    /// Must not be called from within an async task.
+    pub fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        self.rt.block_on(future)
+    }
+
+    /// Returns a signal that completes once shutdown is requested.
+    #[must_use]
+    pub fn shutdown_signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.shutdown.subscribe())
+    }
+
+    /// Asks the tasks watching a [`ShutdownSignal`] to stop.
+    pub fn request_shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Stops all tasks and shuts the runtime down, waiting at most `timeout`
+    /// for tasks blocked outside an `.await`.
+    pub fn shutdown(self, timeout: Duration) {
+        self.request_shutdown();
+        self.rt.shutdown_timeout(timeout);
+    }
 }
 
 impl Default for Runtime {
@@ -50,28 +109,238 @@ impl Default for Runtime {
     }
 }
 
+/// Completes once the [`Runtime`] shuts down.
+#[derive(Clone, Debug)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    /// Waits until shutdown is requested, or the runtime is gone.
+    pub async fn cancelled(&mut self) {
+        let _ = self.0.wait_for(|shutdown| *shutdown).await;
+    }
+
+    /// Returns true if shutdown was requested.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+/// Default interval between two automatic polls of the port list.
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 500;
+
+/// Default number of consecutive scans a closed port may be missing from
+/// before it is removed.
+pub const DEFAULT_MISSING_SCANS: u32 = 3;
+
+/// Port discovery configuration.
+#[derive(Resource, Clone, Debug)]
+pub struct DiscoveryConfig {
+    /// Time to wait before re-polling a changed port list, in milliseconds.
+    ///
+    /// A change is only reported if the second poll confirms it, which hides
+    /// ports that briefly appear and disappear while a USB device settles.
+    pub debounce_ms: u64,
+    /// Whether the port list is polled automatically. Without it, the list
+    /// only changes when a refresh is requested with [`PortScan`].
+    pub auto_poll: bool,
+    /// Interval between two automatic polls, in milliseconds.
+    pub poll_interval_ms: u64,
+    /// Number of consecutive scans a closed port may be missing from before
+    /// it is removed. Open ports are never removed.
+    pub missing_scans_before_removal: u32,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 200,
+            auto_poll: true,
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            missing_scans_before_removal: DEFAULT_MISSING_SCANS,
+        }
+    }
+}
+
+/// Manual refresh of the port list, requested from the UI.
+#[derive(Resource, Debug, Default)]
+pub struct PortScan {
+    /// Whether a refresh was requested but not sent to the discovery task yet.
+    requested: bool,
+    /// Whether the discovery task is scanning for a requested refresh.
+    in_flight: bool,
+}
+
+impl PortScan {
+    /// Requests a refresh of the port list, unless one is in progress.
+    pub fn request(&mut self) {
+        if !self.in_flight {
+            self.requested = true;
+        }
+    }
+
+    /// Returns true from a refresh request until its result arrives.
+    #[must_use]
+    pub const fn is_busy(&self) -> bool {
+        self.requested || self.in_flight
+    }
+}
+
 /// Spawns the port discovery background task.
-pub fn spawn_port_discovery(channel: Res<SerialNameChannel>, runtime: Res<Runtime>) {
+///
+/// The task scans once at startup, then every
+/// [`DiscoveryConfig::poll_interval_ms`] if automatic polling is enabled, and
+/// whenever a [`PortChannelData::RequestScan`] arrives on the channel.
+pub fn spawn_port_discovery(
+    channel: Res<SerialNameChannel>,
+    runtime: Res<Runtime>,
+    config: Res<DiscoveryConfig>,
+) {
     let tx = channel.tx_world2_serial.clone();
+    let mut requests = channel.tx_world2_serial.subscribe();
+    let debounce = Duration::from_millis(config.debounce_ms);
+    let interval = Duration::from_millis(config.poll_interval_ms);
+    let auto_poll = config.auto_poll;
+    let mut shutdown = runtime.shutdown_signal();
     runtime.spawn(async move {
         debug!(
             "Starting port discovery task. Available ports: {:?}",
             available_ports()
         );
+        let mut confirmed = None;
+        let mut requested = false;
         loop {
-            let port_names = discover_ports();
-            if let Err(e) = tx.send(PortChannelData::PortName(port_names)) {
-                error!("Failed to send port names: {e:?}");
+            let ports = if requested {
+                // A requested scan is reported as is, without debouncing.
+                let ports = discover_ports();
+                confirmed = Some(ports.clone());
+                Some(ports)
+            } else {
+                poll_debounced(&mut confirmed, debounce, discover_ports).await
+            };
+            if let Some(ports) = ports {
+                send_discovered_ports(&tx, ports);
             }
-            tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
+            requested = tokio::select! {
+                () = shutdown.cancelled() => break,
+                () = wait_for_scan_request(&mut requests) => true,
+                () = tokio::time::sleep(interval), if auto_poll => false,
+            };
+        }
+        debug!("Port discovery task stopped");
+    });
+}
+
+/// Sends the names and USB serial numbers of the discovered ports.
+fn send_discovered_ports(tx: &broadcast::Sender<PortChannelData>, ports: Vec<DiscoveredPort>) {
+    let port_names = ports.iter().map(|port| port.name.clone()).collect();
+    let serial_numbers = ports
+        .into_iter()
+        .filter_map(|port| Some((port.name, port.serial_number?)))
+        .collect();
+    if let Err(e) = tx.send(PortChannelData::PortName(port_names)) {
+        error!("Failed to send port names: {e:?}");
+    }
+    let _ = tx.send(PortChannelData::PortSerialNumbers(serial_numbers));
+}
+
+/// Waits for a [`PortChannelData::RequestScan`], skipping other messages.
+async fn wait_for_scan_request(requests: &mut broadcast::Receiver<PortChannelData>) {
+    loop {
+        match requests.recv().await {
+            Ok(PortChannelData::RequestScan) => return,
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+        }
+    }
+}
+
+/// System: sends a requested refresh of the port list to the discovery task.
+pub fn request_port_scan(channel: Res<SerialNameChannel>, mut scan: ResMut<PortScan>) {
+    if !scan.requested {
+        return;
+    }
+    scan.requested = false;
+    match channel.tx_world2_serial.send(PortChannelData::RequestScan) {
+        Ok(_) => scan.in_flight = true,
+        Err(e) => warn!("Failed to request a port scan: {e}"),
+    }
+}
+
+/// System: shuts the runtime down when the app exits.
+///
+/// Open ports are asked to close and their logs are flushed first. The
+/// runtime is then removed and shut down, waiting at most
+/// [`SHUTDOWN_TIMEOUT`] for tasks that do not stop on their own.
+pub fn shutdown_runtime_on_exit(
+    mut commands: Commands,
+    mut exit_events: MessageReader<AppExit>,
+    ports: Query<&SerialIo>,
+    runtime: Res<Runtime>,
+) {
+    if exit_events.is_empty() {
+        return;
+    }
+    exit_events.clear();
+
+    runtime.request_shutdown();
+    for port in &ports {
+        lock_serial(&port.0).shutdown();
+    }
+    commands.queue(|world: &mut World| {
+        if let Some(runtime) = world.remove_resource::<Runtime>() {
+            runtime.shutdown(SHUTDOWN_TIMEOUT);
+            info!("Tokio runtime shut down");
         }
     });
 }
 
+/// Polls the port list, confirming any change with a second poll after `debounce`.
+///
+/// `confirmed` holds the last confirmed list. Returns the list to report, or
+/// `None` if a change was not confirmed by the second poll.
+async fn poll_debounced<T: Clone + PartialEq>(
+    confirmed: &mut Option<Vec<T>>,
+    debounce: tokio::time::Duration,
+    mut poll: impl FnMut() -> Vec<T>,
+) -> Option<Vec<T>> {
+    let port_names = poll();
+    if confirmed.as_ref() == Some(&port_names) {
+        return Some(port_names);
+    }
+
+    tokio::time::sleep(debounce).await;
+    if poll() != port_names {
+        debug!("Port list still changing, waiting for it to settle");
+        return None;
+    }
+
+    *confirmed = Some(port_names.clone());
+    Some(port_names)
+}
+
+/// A port found by discovery.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DiscoveredPort {
+    /// Port name, e.g. `/dev/ttyUSB0`.
+    name: String,
+    /// Serial number of the USB device, if the port is a USB adapter that reports one.
+    serial_number: Option<String>,
+}
+
 /// Discovers available USB serial ports.
-fn discover_ports() -> Vec<String> {
+fn discover_ports() -> Vec<DiscoveredPort> {
     match available_ports() {
-        Ok(ports) => ports.into_iter().map(|p| p.port_name).collect(),
+        Ok(ports) => ports
+            .into_iter()
+            .map(|p| DiscoveredPort {
+                serial_number: match p.port_type {
+                    SerialPortType::UsbPort(info) => info.serial_number,
+                    _ => None,
+                },
+                name: p.port_name,
+            })
+            .collect(),
         Err(e) => {
             debug!("Error listing ports: {e}");
             Vec::new()
@@ -80,24 +349,235 @@ fn discover_ports() -> Vec<String> {
 }
 
 /// Updates the serial port names based on discovery results.
+///
+/// Adds and removes the ports of the [`Serials`]; their entities follow in
+/// [`sync_port_entities`](super::status::sync_port_entities).
 pub fn update_serial_port_names(
     mut channel: ResMut<SerialNameChannel>,
     mut serials: Query<&mut Serials>,
-    mut selected: ResMut<Selected>,
+    mut scan: ResMut<PortScan>,
+    mock_config: Res<MockPortConfig>,
+    config: Res<DiscoveryConfig>,
+    mut reported_drift: Local<HashMap<String, Vec<String>>>,
 ) {
     let Ok(mut serials) = serials.single_mut() else {
         return;
     };
 
-    if let Ok(names) = channel.rx_serial2_world.try_recv() {
-        let port_names: Vec<String> = names.into();
-        serials.sync_discovered_ports(&port_names);
+    let Ok(message) = channel.rx_serial2_world.try_recv() else {
+        return;
+    };
+    let port_names = match message {
+        PortChannelData::PortName(port_names) => port_names,
+        PortChannelData::PortSerialNumbers(serial_numbers) => {
+            serials.set_usb_serial_numbers(&serial_numbers);
+            return;
+        }
+        // Our own scan requests come back on the shared channel.
+        _ => return,
+    };
+    scan.in_flight = false;
+    serials.sync_discovered_ports(&port_names, config.missing_scans_before_removal);
+    warn_settings_drift(&serials, &mut reported_drift);
 
-        // Auto-select the first port if no port is currently selected
-        if selected.selected().is_empty()
-            && let Some(first_port_name) = serials.first_port_name()
-        {
-            selected.select(&first_port_name);
+    if mock_config.enabled {
+        serials.add_mock(MOCK_DEMO_PORT, mock_config.traffic.clone());
+    }
+}
+
+/// Logs the ports whose settings differ from the ones they were discovered with.
+///
+/// `reported` holds the drift last logged for each port, so a port is only
+/// logged again once its drift changes. Returns the number of ports logged.
+fn warn_settings_drift(serials: &Serials, reported: &mut HashMap<String, Vec<String>>) -> usize {
+    let mut current = HashMap::new();
+    let mut warned = 0;
+    for serial in &serials.serial {
+        let serial = lock_serial(serial);
+        let name = &serial.set.port_name;
+        let changes = PortSettings::discovered(name).diff(&serial.set);
+        if changes.is_empty() {
+            continue;
         }
+        if reported.get(name) != Some(&changes) {
+            warn!(
+                "{name} settings differ from defaults: {}",
+                changes.join(", ")
+            );
+            warned += 1;
+        }
+        current.insert(name.clone(), changes);
+    }
+    *reported = current;
+    warned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use tokio::time::Duration;
+
+    /// Returns a poll function that yields the given port lists in order.
+    fn fake_polls(lists: &[&[&str]]) -> impl FnMut() -> Vec<String> {
+        let mut lists: VecDeque<Vec<String>> = lists
+            .iter()
+            .map(|list| list.iter().map(ToString::to_string).collect())
+            .collect();
+        move || lists.pop_front().unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn test_debounce_confirms_stable_change() {
+        let mut confirmed = None;
+        let poll = fake_polls(&[&["COM1"], &["COM1"]]);
+        let result = poll_debounced(&mut confirmed, Duration::ZERO, poll).await;
+        assert_eq!(result, Some(vec!["COM1".to_string()]));
+        assert_eq!(confirmed, Some(vec!["COM1".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_debounce_drops_phantom_port() {
+        let mut confirmed = Some(vec!["COM1".to_string()]);
+        let poll = fake_polls(&[&["COM1", "COM2"], &["COM1"]]);
+        let result = poll_debounced(&mut confirmed, Duration::ZERO, poll).await;
+        assert_eq!(result, None);
+        assert_eq!(confirmed, Some(vec!["COM1".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_debounce_unchanged_list_skips_repoll() {
+        let mut confirmed = Some(vec!["COM1".to_string()]);
+        let mut polls = 0;
+        let result = poll_debounced(&mut confirmed, Duration::ZERO, || {
+            polls += 1;
+            vec!["COM1".to_string()]
+        })
+        .await;
+        assert_eq!(result, Some(vec!["COM1".to_string()]));
+        assert_eq!(polls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_scan_request_skips_other_messages() {
+        let (tx, mut rx) = broadcast::channel(8);
+        tx.send(PortChannelData::PortName(vec!["COM1".to_string()]))
+            .unwrap();
+        tx.send(PortChannelData::RequestScan).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), wait_for_scan_request(&mut rx))
+            .await
+            .unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_port_scan_is_busy_until_ports_arrive() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.spawn(Serials::new());
+        world.init_resource::<SerialNameChannel>();
+        world.init_resource::<MockPortConfig>();
+        world.init_resource::<PortScan>();
+        world.init_resource::<DiscoveryConfig>();
+        let mut discovery = world
+            .resource::<SerialNameChannel>()
+            .tx_world2_serial
+            .subscribe();
+
+        world.resource_mut::<PortScan>().request();
+        assert!(world.resource::<PortScan>().is_busy());
+        world.run_system_once(request_port_scan).unwrap();
+        assert!(matches!(
+            discovery.try_recv(),
+            Ok(PortChannelData::RequestScan)
+        ));
+
+        // The request itself comes back on the shared channel and is ignored.
+        world.run_system_once(update_serial_port_names).unwrap();
+        assert!(world.resource::<PortScan>().is_busy());
+
+        let tx = world
+            .resource::<SerialNameChannel>()
+            .tx_world2_serial
+            .clone();
+        send_discovered_ports(
+            &tx,
+            vec![DiscoveredPort {
+                name: "COM1".to_string(),
+                serial_number: None,
+            }],
+        );
+        world.run_system_once(update_serial_port_names).unwrap();
+        assert!(!world.resource::<PortScan>().is_busy());
+        let mut serials = world.query::<&Serials>();
+        assert_eq!(
+            serials.single(&world).unwrap().first_port_name().as_deref(),
+            Some("COM1")
+        );
+    }
+
+    #[test]
+    fn test_shutdown_signal_stops_tasks() {
+        let runtime = Runtime::init();
+        let mut signal = runtime.shutdown_signal();
+        let task = runtime.spawn(async move { signal.cancelled().await });
+        assert!(!runtime.shutdown_signal().is_cancelled());
+
+        runtime.request_shutdown();
+        assert!(runtime.shutdown_signal().is_cancelled());
+        let stopped = runtime.block_on(tokio::time::timeout(Duration::from_secs(1), task));
+        assert!(matches!(stopped, Ok(Ok(()))));
+    }
+
+    #[test]
+    fn test_runtime_shuts_down_on_exit() {
+        use crate::serial::io::create_serial_port_threads;
+        use crate::serial::mock::MockTraffic;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut serials = Serials::new();
+        serials.add_mock("mock://exit", MockTraffic::Echo);
+        let mut world = World::new();
+        crate::serial::status::spawn_ports(&mut world, serials);
+        world.insert_resource(Runtime::init());
+        world.init_resource::<Messages<AppExit>>();
+        world.run_system_once(create_serial_port_threads).unwrap();
+        // A task blocked outside an `.await` must not hold up the exit.
+        world.resource::<Runtime>().rt.spawn_blocking(|| {
+            std::thread::sleep(Duration::from_secs(30));
+        });
+
+        world.run_system_once(shutdown_runtime_on_exit).unwrap();
+        assert!(world.contains_resource::<Runtime>());
+
+        world.write_message(AppExit::Success);
+        let start = std::time::Instant::now();
+        world.run_system_once(shutdown_runtime_on_exit).unwrap();
+        assert!(!world.contains_resource::<Runtime>());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_settings_drift_is_logged_once_per_change() {
+        let mut serials = Serials::new();
+        serials.add_mock("mock://drift", crate::serial::mock::MockTraffic::Echo);
+        let mut reported = HashMap::new();
+        assert_eq!(warn_settings_drift(&serials, &mut reported), 0);
+
+        let baud_rate = serials.serial[0].lock().unwrap().set.baud_rate;
+        serials.serial[0].lock().unwrap().set.baud_rate = baud_rate * 2;
+        assert_eq!(warn_settings_drift(&serials, &mut reported), 1);
+        assert_eq!(warn_settings_drift(&serials, &mut reported), 0);
+
+        serials.serial[0].lock().unwrap().set.baud_rate = baud_rate * 4;
+        assert_eq!(warn_settings_drift(&serials, &mut reported), 1);
+
+        // Drift that goes away and comes back is logged again.
+        serials.serial[0].lock().unwrap().set.baud_rate = baud_rate;
+        assert_eq!(warn_settings_drift(&serials, &mut reported), 0);
+        assert!(reported.is_empty());
+        serials.serial[0].lock().unwrap().set.baud_rate = baud_rate * 4;
+        assert_eq!(warn_settings_drift(&serials, &mut reported), 1);
     }
 }
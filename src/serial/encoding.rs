@@ -1,20 +1,18 @@
 //! # Encoding Module
 //!
 //! This module provides data encoding and decoding functionality for serial communication.
-//! It supports various encoding formats including Hex and UTF-8.
+//! It supports various encoding formats including Hex, UTF-8/16/32, the
+//! common Chinese code pages (GBK, GB2312, Big5), Intel HEX and raw bytes.
+
+pub mod intel_hex;
+
+use std::fmt;
 
 use log::error;
-use regex::Regex;
-use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
 
 use crate::serial::port::DataType;
 
-/// Cached regex for hex sanitization.
-fn hex_regex() -> &'static Regex {
-    static HEX_RE: OnceLock<Regex> = OnceLock::new();
-    HEX_RE.get_or_init(|| Regex::new(r"[^0-9a-fA-F]").expect("Invalid regex pattern"))
-}
-
 /// Encodes a string to bytes based on the specified data type.
 ///
 /// # Arguments
@@ -42,7 +40,9 @@ fn hex_regex() -> &'static Regex {
 pub fn encode_string(source_data: &str, data_type: DataType) -> Vec<u8> {
     match data_type {
         DataType::Hex => encode_hex(source_data),
-        DataType::Utf8 | DataType::Ascii | DataType::Binary => source_data.as_bytes().to_vec(),
+        DataType::Utf8 | DataType::Ascii | DataType::Binary | DataType::Raw => {
+            source_data.as_bytes().to_vec()
+        }
         DataType::Utf16 => source_data
             .encode_utf16()
             .flat_map(|c| c.to_le_bytes())
@@ -51,10 +51,19 @@ pub fn encode_string(source_data: &str, data_type: DataType) -> Vec<u8> {
             .chars()
             .flat_map(|c| u32::from(c).to_le_bytes())
             .collect(),
-        DataType::Gbk => {
+        // encoding_rs treats GB2312 as an alias of GBK (its superset), as browsers do.
+        DataType::Gbk | DataType::Gb2312 => {
             let (encoded, _, _) = encoding_rs::GBK.encode(source_data);
             encoded.into_owned()
         }
+        DataType::Big5 => {
+            let (encoded, _, _) = encoding_rs::BIG5.encode(source_data);
+            encoded.into_owned()
+        }
+        DataType::IntelHex => intel_hex::decode_from_ihex(source_data).unwrap_or_else(|err| {
+            error!("{err}");
+            Vec::new()
+        }),
     }
 }
 
@@ -107,32 +116,64 @@ pub fn decode_bytes(source_data: &[u8], data_type: DataType) -> String {
                 .map(|&cp| char::from_u32(cp).unwrap_or('\u{FFFD}'))
                 .collect()
         }
-        DataType::Gbk => {
+        DataType::Gbk | DataType::Gb2312 => {
             let (decoded, _, _) = encoding_rs::GBK.decode(source_data);
             decoded.into_owned()
         }
+        DataType::Big5 => {
+            let (decoded, _, _) = encoding_rs::BIG5.decode(source_data);
+            decoded.into_owned()
+        }
+        DataType::IntelHex => intel_hex::encode_to_ihex(source_data, 0),
+        DataType::Raw => source_data.iter().map(|b| format!("\\x{b:02x}")).collect(),
+    }
+}
+
+/// Confidence above which UTF-8 data that fails to decode is taken as GBK,
+/// see [`gbk_confidence`].
+pub const GBK_CONFIDENCE_THRESHOLD: f32 = 0.8;
+
+/// Returns how likely `source_data` is GBK-encoded Chinese text, from 0.0 to 1.0.
+///
+/// This is the share of non-ASCII characters that decode to CJK ideographs,
+/// CJK punctuation or full-width forms. Data that is not valid GBK, or has no
+/// non-ASCII characters, gives 0.0.
+#[must_use]
+pub fn gbk_confidence(source_data: &[u8]) -> f32 {
+    let (decoded, had_errors) = encoding_rs::GBK.decode_without_bom_handling(source_data);
+    if had_errors {
+        return 0.0;
+    }
+    let mut total = 0usize;
+    let mut chinese = 0usize;
+    for c in decoded.chars().filter(|c| !c.is_ascii()) {
+        total += 1;
+        if matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3000}'..='\u{303F}' | '\u{FF00}'..='\u{FFEF}')
+        {
+            chinese += 1;
+        }
     }
+    if total == 0 {
+        return 0.0;
+    }
+    chinese as f32 / total as f32
 }
 
 /// Encodes a hex string to bytes.
 ///
-/// This function removes all non-hex characters and pads with a leading zero
-/// if the string has an odd length.
+/// Separators and `0x` prefixes are removed as in [`validate_hex_input`], any
+/// other non-hex characters are dropped, and a leading zero is added if the
+/// number of digits is odd.
 fn encode_hex(source_data: &str) -> Vec<u8> {
-    let hex_str = hex_regex().replace_all(source_data, "");
+    let hex_str = validate_hex_input(source_data).cleaned;
 
     let cleaned_hex = if !hex_str.len().is_multiple_of(2) {
         format!("0{hex_str}")
     } else {
-        hex_str.to_string()
+        hex_str
     };
 
-    let bytes_result: Result<Vec<u8>, _> = (0..cleaned_hex.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&cleaned_hex[i..i + 2], 16))
-        .collect();
-
-    match bytes_result {
+    match hex::decode(&cleaned_hex) {
         Ok(bytes) => bytes,
         Err(err) => {
             error!("Hex encoding error: {err}");
@@ -141,10 +182,525 @@ fn encode_hex(source_data: &str) -> Vec<u8> {
     }
 }
 
+/// Parses backslash escape sequences into bytes.
+///
+/// Supports `\n`, `\r`, `\t`, `\0`, `\\` and `\xNN`. Unknown or incomplete
+/// escapes are kept literally.
+///
+/// # Examples
+///
+/// ```
+/// use serial_bevy::serial::encoding::parse_escapes;
+///
+/// assert_eq!(parse_escapes(r"A\x00B\n"), vec![0x41, 0x00, 0x42, 0x0A]);
+/// ```
+#[must_use]
+pub fn parse_escapes(source_data: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(source_data.len());
+    let mut chars = source_data.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut utf8 = [0u8; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut utf8).as_bytes());
+            continue;
+        }
+
+        match chars.peek().copied() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let digits: String = chars.clone().skip(1).take(2).collect();
+                if digits.len() == 2
+                    && digits.chars().all(|c| c.is_ascii_hexdigit())
+                    && let Ok(byte) = u8::from_str_radix(&digits, 16)
+                {
+                    bytes.push(byte);
+                    chars.next();
+                    chars.next();
+                } else {
+                    bytes.push(b'\\');
+                    continue;
+                }
+            }
+            _ => {
+                bytes.push(b'\\');
+                continue;
+            }
+        }
+        chars.next();
+    }
+
+    bytes
+}
+
+/// Result of validating user-entered hex text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HexValidation {
+    /// Hex digits that will be sent, with separators and `0x` prefixes removed.
+    pub cleaned: String,
+    /// Characters that are neither hex digits nor accepted separators, in order of first use.
+    pub invalid_chars: Vec<char>,
+    /// True if the number of hex digits is odd.
+    pub odd_length: bool,
+}
+
+impl HexValidation {
+    /// Returns true if the input can be sent as-is.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.invalid_chars.is_empty() && !self.odd_length
+    }
+
+    /// Returns a short, human-readable description of the problems found.
+    #[must_use]
+    pub fn message(&self) -> String {
+        let mut problems = Vec::new();
+        if !self.invalid_chars.is_empty() {
+            let chars: String = self.invalid_chars.iter().collect();
+            problems.push(format!("Invalid characters: {chars}"));
+        }
+        if self.odd_length {
+            problems.push("Odd number of hex digits".to_string());
+        }
+        problems.join("\n")
+    }
+}
+
+/// Validates user-entered hex text.
+///
+/// Whitespace, `,`, `:`, `-`, `;` and `0x`/`0X` prefixes are accepted as
+/// separators. Every other non-hex character is reported as invalid.
+///
+/// # Examples
+///
+/// ```
+/// use serial_bevy::serial::encoding::validate_hex_input;
+///
+/// let result = validate_hex_input("0x12, 0x34");
+/// assert_eq!(result.cleaned, "1234");
+/// assert!(result.is_valid());
+///
+/// let result = validate_hex_input("GG zz");
+/// assert_eq!(result.invalid_chars, vec!['G', 'z']);
+/// ```
+#[must_use]
+pub fn validate_hex_input(source_data: &str) -> HexValidation {
+    let mut result = HexValidation::default();
+    let mut chars = source_data.chars().peekable();
+    let mut at_token_start = true;
+
+    while let Some(ch) = chars.next() {
+        if ch.is_whitespace() || matches!(ch, ',' | ':' | '-' | ';') {
+            at_token_start = true;
+            continue;
+        }
+        if at_token_start && ch == '0' && matches!(chars.peek(), Some('x' | 'X')) {
+            chars.next();
+            at_token_start = false;
+            continue;
+        }
+        at_token_start = false;
+
+        if ch.is_ascii_hexdigit() {
+            result.cleaned.push(ch);
+        } else if !result.invalid_chars.contains(&ch) {
+            result.invalid_chars.push(ch);
+        }
+    }
+
+    result.odd_length = !result.cleaned.len().is_multiple_of(2);
+    result
+}
+
+/// Encodes a hex string to bytes, refusing anything [`validate_hex_input`] rejects.
+///
+/// Unlike [`encode_string`] with [`DataType::Hex`], invalid characters are not
+/// silently dropped and odd-length input is not zero-padded.
+pub fn encode_hex_strict(source_data: &str) -> Result<Vec<u8>, HexValidation> {
+    let validation = validate_hex_input(source_data);
+    if !validation.is_valid() {
+        return Err(validation);
+    }
+    hex::decode(&validation.cleaned).map_err(|_| validation)
+}
+
+/// Groups the hex digits of `raw` into space-separated bytes, e.g.
+/// `48656c` into `48 65 6c`.
+///
+/// Separators and `0x` prefixes are removed as in [`validate_hex_input`], as
+/// is every other non-hex character. A trailing odd digit is kept on its own.
+///
+/// # Examples
+///
+/// ```
+/// use serial_bevy::serial::encoding::format_hex_input;
+///
+/// assert_eq!(format_hex_input("0x48,0x65 6c"), "48 65 6c");
+/// assert_eq!(format_hex_input("123"), "12 3");
+/// ```
+#[must_use]
+pub fn format_hex_input(raw: &str) -> String {
+    let cleaned = validate_hex_input(raw).cleaned;
+    cleaned
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| String::from_utf8_lossy(pair))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Why submitted input was refused instead of sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SendRefusal {
+    /// Strict hex input is invalid.
+    Hex(HexValidation),
+    /// Intel HEX input could not be decoded; holds the reason.
+    IntelHex(String),
+}
+
+impl SendRefusal {
+    /// Returns a short, human-readable description of why the input was refused.
+    #[must_use]
+    pub fn message(&self) -> String {
+        match self {
+            Self::Hex(validation) => validation.message(),
+            Self::IntelHex(reason) => reason.clone(),
+        }
+    }
+}
+
+/// Number of bytes shown in a [`EncodedPreview`].
+pub const PREVIEW_BYTES: usize = 64;
+
+/// Line ending appended to sent data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineEnding {
+    /// Nothing is appended.
+    #[default]
+    None,
+    /// Line feed, `\n`.
+    Lf,
+    /// Carriage return, `\r`.
+    Cr,
+    /// Carriage return and line feed, `\r\n`.
+    CrLf,
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Lf => write!(f, "LF"),
+            Self::Cr => write!(f, "CR"),
+            Self::CrLf => write!(f, "CRLF"),
+        }
+    }
+}
+
+impl LineEnding {
+    /// All line endings, in the order they are offered in the UI.
+    pub const ALL: [Self; 4] = [Self::None, Self::Lf, Self::Cr, Self::CrLf];
+
+    /// Returns the characters of the line ending.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Lf => "\n",
+            Self::Cr => "\r",
+            Self::CrLf => "\r\n",
+        }
+    }
+
+    /// Returns the line ending of the former line feed toggle: [`Self::Lf`]
+    /// if it was on, [`Self::None`] if it was off.
+    #[must_use]
+    pub const fn from_line_feed(line_feed: bool) -> Self {
+        if line_feed { Self::Lf } else { Self::None }
+    }
+}
+
+/// Per-port settings that decide how typed input is turned into bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendSettings {
+    /// Data type of the input.
+    pub data_type: DataType,
+    /// Line ending appended to the input.
+    pub line_ending: LineEnding,
+    /// Whether invalid hex input is refused instead of cleaned.
+    pub strict_hex: bool,
+    /// Whether backslash escapes in text input are parsed.
+    pub escapes: bool,
+}
+
+/// Returns true if input of `data_type` describes bytes rather than text, so
+/// the line ending is appended to the encoded bytes instead of the text.
+const fn is_byte_notation(data_type: DataType) -> bool {
+    matches!(data_type, DataType::Hex)
+}
+
+/// Applies the line ending setting to submitted input: line breaks at the
+/// end of the input are removed and the line ending is appended.
+///
+/// For [`DataType::Hex`], where a line break would only separate digits,
+/// the line ending is left to [`encode_for_send`] instead.
+/// [`DataType::IntelHex`] input is decoded into an image, which gets no
+/// line ending.
+#[must_use]
+pub fn apply_line_ending(input: &str, settings: &SendSettings) -> String {
+    let input = input.trim_end_matches(['\r', '\n']);
+    if is_byte_notation(settings.data_type) || settings.data_type == DataType::IntelHex {
+        input.to_string()
+    } else {
+        format!("{input}{}", settings.line_ending.as_str())
+    }
+}
+
+/// Encodes submitted text, after [`apply_line_ending`], into the bytes
+/// written to the port.
+///
+/// Strict hex applies to [`DataType::Hex`] only and escapes to
+/// [`DataType::Utf8`] and [`DataType::Ascii`] only. The line ending bytes are
+/// appended here for [`DataType::Hex`].
+///
+/// # Errors
+///
+/// Returns why the input was refused if strict hex input is invalid or
+/// Intel HEX input cannot be decoded.
+pub fn encode_for_send(text: &str, settings: &SendSettings) -> Result<Vec<u8>, SendRefusal> {
+    let mut bytes = match settings.data_type {
+        DataType::Hex if settings.strict_hex => {
+            encode_hex_strict(text).map_err(SendRefusal::Hex)?
+        }
+        DataType::IntelHex => intel_hex::decode_from_ihex(text)
+            .map_err(|err| SendRefusal::IntelHex(err.to_string()))?,
+        DataType::Utf8 | DataType::Ascii if settings.escapes => parse_escapes(text),
+        data_type => encode_string(text, data_type),
+    };
+    if is_byte_notation(settings.data_type) {
+        bytes.extend_from_slice(settings.line_ending.as_str().as_bytes());
+    }
+    Ok(bytes)
+}
+
+/// What sending some input would write to the port.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncodedPreview {
+    /// Number of bytes that would be written.
+    pub len: usize,
+    /// The first [`PREVIEW_BYTES`] of them.
+    pub head: Vec<u8>,
+    /// Why nothing would be written, if the input would be refused.
+    pub error: Option<SendRefusal>,
+}
+
+impl EncodedPreview {
+    /// Returns the shown bytes as space-separated hex, with `…` if more
+    /// bytes would be written.
+    #[must_use]
+    pub fn hex_dump(&self) -> String {
+        let mut dump = self
+            .head
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if self.len > self.head.len() {
+            dump.push_str(" …");
+        }
+        dump
+    }
+}
+
+/// Previews the bytes that submitting `input` would write, going through
+/// the same steps as sending: [`apply_line_ending`], then [`encode_for_send`].
+#[must_use]
+pub fn preview_encoded(input: &str, settings: &SendSettings) -> EncodedPreview {
+    match encode_for_send(&apply_line_ending(input, settings), settings) {
+        Ok(bytes) => EncodedPreview {
+            len: bytes.len(),
+            head: bytes.into_iter().take(PREVIEW_BYTES).collect(),
+            error: None,
+        },
+        Err(refusal) => EncodedPreview {
+            error: Some(refusal),
+            ..EncodedPreview::default()
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn settings(data_type: DataType) -> SendSettings {
+        SendSettings {
+            data_type,
+            line_ending: LineEnding::None,
+            strict_hex: false,
+            escapes: false,
+        }
+    }
+
+    #[test]
+    fn test_apply_line_ending() {
+        let text = |line_ending| SendSettings {
+            line_ending,
+            ..settings(DataType::Utf8)
+        };
+        assert_eq!(apply_line_ending("AT", &text(LineEnding::Lf)), "AT\n");
+        assert_eq!(apply_line_ending("AT\n", &text(LineEnding::CrLf)), "AT\r\n");
+        assert_eq!(apply_line_ending("AT\r\n", &text(LineEnding::Cr)), "AT\r");
+        assert_eq!(
+            apply_line_ending("A\nT\r\n", &text(LineEnding::None)),
+            "A\nT"
+        );
+        assert_eq!(LineEnding::from_line_feed(true), LineEnding::Lf);
+        assert_eq!(LineEnding::from_line_feed(false), LineEnding::None);
+    }
+
+    #[test]
+    fn test_line_ending_bytes_for_each_mode() {
+        let expected: [(LineEnding, &[u8]); 4] = [
+            (LineEnding::None, b"AT"),
+            (LineEnding::Lf, b"AT\n"),
+            (LineEnding::Cr, b"AT\r"),
+            (LineEnding::CrLf, b"AT\r\n"),
+        ];
+        for (line_ending, bytes) in expected {
+            let text = SendSettings {
+                line_ending,
+                ..settings(DataType::Ascii)
+            };
+            assert_eq!(preview_encoded("AT\n", &text).head, bytes, "{line_ending}");
+
+            // Hex input gets the same ending as bytes, and none by default.
+            let hex = SendSettings {
+                line_ending,
+                ..settings(DataType::Hex)
+            };
+            assert_eq!(
+                preview_encoded("41 54\n", &hex).head,
+                bytes,
+                "{line_ending}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_preview_applies_send_settings() {
+        let preview = preview_encoded(
+            "AT",
+            &SendSettings {
+                line_ending: LineEnding::Lf,
+                ..settings(DataType::Utf8)
+            },
+        );
+        assert_eq!(preview.head, b"AT\n");
+        assert_eq!(preview.hex_dump(), "41 54 0A");
+
+        let escapes = SendSettings {
+            escapes: true,
+            ..settings(DataType::Ascii)
+        };
+        assert_eq!(preview_encoded(r"\x01\x02", &escapes).head, [1, 2]);
+        // Escapes only apply to text data types.
+        assert_eq!(
+            preview_encoded(
+                r"\x01",
+                &SendSettings {
+                    escapes: true,
+                    ..settings(DataType::Hex)
+                }
+            )
+            .head,
+            [0x01]
+        );
+    }
+
+    #[test]
+    fn test_preview_of_hex_input() {
+        // A typed line feed is a separator in hex input, so it adds no byte.
+        let lenient = SendSettings {
+            line_ending: LineEnding::None,
+            ..settings(DataType::Hex)
+        };
+        assert_eq!(preview_encoded("0x12 3", &lenient).head, [0x01, 0x23]);
+
+        let strict = SendSettings {
+            strict_hex: true,
+            ..lenient
+        };
+        let preview = preview_encoded("12 3", &strict);
+        assert_eq!(preview.len, 0);
+        assert!(matches!(
+            preview.error,
+            Some(SendRefusal::Hex(validation)) if validation.odd_length
+        ));
+    }
+
+    #[test]
+    fn test_invalid_intel_hex_is_refused() {
+        let settings = settings(DataType::IntelHex);
+        assert_eq!(
+            encode_for_send(":020000000102FC", &settings),
+            Ok(vec![0x01, 0x02])
+        );
+        assert!(matches!(
+            encode_for_send(":0200000001", &settings),
+            Err(SendRefusal::IntelHex(_))
+        ));
+        let with_crlf = SendSettings {
+            line_ending: LineEnding::CrLf,
+            ..settings
+        };
+        assert_eq!(
+            preview_encoded(":020000000102FC", &with_crlf).head,
+            [0x01, 0x02]
+        );
+        let preview = preview_encoded("not intel hex", &settings);
+        assert_eq!(preview.len, 0);
+        assert!(matches!(preview.error, Some(SendRefusal::IntelHex(_))));
+    }
+
+    #[test]
+    fn test_format_hex_input() {
+        assert_eq!(format_hex_input("48656c"), "48 65 6c");
+        assert_eq!(format_hex_input("4"), "4");
+        assert_eq!(format_hex_input("48 656C\n6c-6F"), "48 65 6C 6c 6F");
+        assert_eq!(format_hex_input(""), "");
+        assert_eq!(
+            encode_string(&format_hex_input("123"), DataType::Hex),
+            encode_string("123", DataType::Hex)
+        );
+    }
+
+    #[test]
+    fn test_preview_is_truncated() {
+        let input = "x".repeat(PREVIEW_BYTES + 6);
+        let preview = preview_encoded(&input, &settings(DataType::Utf8));
+        assert_eq!(preview.len, PREVIEW_BYTES + 6);
+        assert_eq!(preview.head.len(), PREVIEW_BYTES);
+        assert!(preview.hex_dump().ends_with(" …"));
+    }
+
+    #[test]
+    fn test_gbk_confidence() {
+        let gbk = encoding_rs::GBK
+            .encode("温度：25℃，湿度正常")
+            .0
+            .into_owned();
+        assert!(gbk_confidence(&gbk) > GBK_CONFIDENCE_THRESHOLD);
+        assert_eq!(gbk_confidence(b"plain ascii"), 0.0);
+        assert_eq!(gbk_confidence(&[0x81]), 0.0);
+        // Random high bytes decode to few common characters.
+        assert!(gbk_confidence(&[0xA8, 0xA1, 0xA8, 0xA2, 0xA6, 0xA1]) < GBK_CONFIDENCE_THRESHOLD);
+    }
+
     #[test]
     fn test_encode_hex_simple() {
         let result = encode_string("48656C6C6F", DataType::Hex);
@@ -199,6 +755,14 @@ mod tests {
         assert!(!result.is_empty());
     }
 
+    #[test]
+    fn test_raw_round_trip() {
+        let text = decode_bytes(&[0x00, 0xFF], DataType::Raw);
+        assert_eq!(text, "\\x00\\xff");
+        assert_eq!(parse_escapes(&text), [0x00, 0xFF]);
+        assert_eq!(encode_string("a\x01", DataType::Raw), b"a\x01");
+    }
+
     #[test]
     fn test_encode_utf16() {
         let result = encode_string("Hello", DataType::Utf16);
@@ -248,6 +812,50 @@ mod tests {
         assert_eq!(result, "中文");
     }
 
+    #[test]
+    fn test_utf16_round_trip() {
+        let text = "串口调试 Serial 🚀";
+        let encoded = encode_string(text, DataType::Utf16);
+        assert_eq!(encoded.len(), text.encode_utf16().count() * 2);
+        assert_eq!(decode_bytes(&encoded, DataType::Utf16), text);
+    }
+
+    #[test]
+    fn test_utf32_round_trip() {
+        let text = "串口调试 Serial 🚀";
+        let encoded = encode_string(text, DataType::Utf32);
+        assert_eq!(encoded.len(), text.chars().count() * 4);
+        assert_eq!(decode_bytes(&encoded, DataType::Utf32), text);
+    }
+
+    #[test]
+    fn test_gbk_round_trip() {
+        let text = "串口调试助手";
+        let encoded = encode_string(text, DataType::Gbk);
+        assert_eq!(decode_bytes(&encoded, DataType::Gbk), text);
+    }
+
+    #[test]
+    fn test_gb2312_round_trip() {
+        let text = "串口调试助手";
+        let encoded = encode_string(text, DataType::Gb2312);
+        assert_eq!(
+            encoded,
+            vec![
+                0xB4, 0xAE, 0xBF, 0xDA, 0xB5, 0xF7, 0xCA, 0xD4, 0xD6, 0xFA, 0xCA, 0xD6
+            ]
+        );
+        assert_eq!(decode_bytes(&encoded, DataType::Gb2312), text);
+    }
+
+    #[test]
+    fn test_big5_round_trip() {
+        let text = "中文資料傳輸";
+        let encoded = encode_string(text, DataType::Big5);
+        assert_eq!(&encoded[..4], &[0xA4, 0xA4, 0xA4, 0xE5]);
+        assert_eq!(decode_bytes(&encoded, DataType::Big5), text);
+    }
+
     #[test]
     fn test_encode_decode_ascii() {
         let encoded = encode_string("Hello", DataType::Ascii);
@@ -255,4 +863,90 @@ mod tests {
         let decoded = decode_bytes(&encoded, DataType::Ascii);
         assert_eq!(decoded, "Hello");
     }
+
+    #[test]
+    fn test_intel_hex_round_trip() {
+        let text = decode_bytes(&[0x01, 0x02], DataType::IntelHex);
+        assert_eq!(text, ":020000000102FB\n:00000001FF\n");
+        assert_eq!(encode_string(&text, DataType::IntelHex), vec![0x01, 0x02]);
+        assert_eq!(
+            encode_string(":020000000102FC", DataType::IntelHex),
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn test_validate_hex_mixed_separators() {
+        let result = validate_hex_input("0x12, 0x34");
+        assert_eq!(result.cleaned, "1234");
+        assert!(result.invalid_chars.is_empty());
+        assert!(!result.odd_length);
+        assert!(result.is_valid());
+
+        let result = validate_hex_input("AA:bb-CC;0Xdd\n01 02");
+        assert_eq!(result.cleaned, "AAbbCCdd0102");
+        assert!(result.is_valid());
+    }
+
+    #[test]
+    fn test_validate_hex_invalid_chars() {
+        let result = validate_hex_input("GG zz");
+        assert_eq!(result.cleaned, "");
+        assert_eq!(result.invalid_chars, vec!['G', 'z']);
+        assert!(!result.is_valid());
+        assert!(result.message().contains("Gz"));
+    }
+
+    #[test]
+    fn test_validate_hex_odd_length() {
+        let result = validate_hex_input("ABC");
+        assert!(result.invalid_chars.is_empty());
+        assert!(result.odd_length);
+        assert!(!result.is_valid());
+    }
+
+    #[test]
+    fn test_validate_hex_x_inside_token_is_invalid() {
+        let result = validate_hex_input("10x2");
+        assert_eq!(result.invalid_chars, vec!['x']);
+    }
+
+    #[test]
+    fn test_encode_hex_strict() {
+        assert_eq!(encode_hex_strict("0x12, 0x34"), Ok(vec![0x12, 0x34]));
+        assert!(encode_hex_strict("ABC").is_err());
+        assert!(encode_hex_strict("GG").is_err());
+    }
+
+    #[test]
+    fn test_encode_hex_lenient_unchanged() {
+        // Lenient encoding keeps its historical behavior.
+        assert_eq!(encode_string("ABC", DataType::Hex), vec![0x0A, 0xBC]);
+        assert_eq!(encode_string("GG zz", DataType::Hex), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_encode_hex_prefixed_and_comma_separated() {
+        assert_eq!(encode_string("0x12,0x34", DataType::Hex), vec![0x12, 0x34]);
+        assert_eq!(
+            encode_string("0x12; 0X34 0xab", DataType::Hex),
+            vec![0x12, 0x34, 0xAB]
+        );
+    }
+
+    #[test]
+    fn test_parse_escapes() {
+        assert_eq!(parse_escapes(r"A\x00B\n"), vec![0x41, 0x00, 0x42, 0x0A]);
+        assert_eq!(parse_escapes(r"\r\n\t\0\\"), b"\r\n\t\0\\".to_vec());
+        assert_eq!(parse_escapes("中"), "中".as_bytes());
+    }
+
+    #[test]
+    fn test_parse_escapes_keeps_unknown_sequences() {
+        assert_eq!(parse_escapes(r"\q"), b"\\q".to_vec());
+        assert_eq!(parse_escapes(r"\xZZ"), b"\\xZZ".to_vec());
+        assert_eq!(parse_escapes(r"\x4"), b"\\x4".to_vec());
+        assert_eq!(parse_escapes(r"\x+1"), b"\\x+1".to_vec());
+        assert_eq!(parse_escapes("end\\"), b"end\\".to_vec());
+    }
 }
@@ -0,0 +1,257 @@
+//! # Intel HEX Module
+//!
+//! Encoding and decoding of Intel HEX, the text format most flash tools use
+//! for firmware images.
+//!
+//! Each line is a record `:LLAAAATT<data>CC`: the data length, a 16-bit
+//! address, the record type, the data and a checksum that makes all bytes of
+//! the record sum to zero. Addresses above 64 KiB are reached through
+//! extended address records.
+
+use crate::error::SerialBevyError;
+
+/// Data bytes per data record written by [`encode_to_ihex`].
+const RECORD_DATA_LEN: usize = 16;
+
+/// Largest image [`decode_from_ihex`] builds, from the lowest to the highest
+/// address.
+const MAX_IMAGE_LEN: u64 = 16 * 1024 * 1024;
+
+/// Data record.
+const DATA: u8 = 0x00;
+/// End-of-file record.
+const END_OF_FILE: u8 = 0x01;
+/// Extended segment address record: bits 4-19 of the following addresses.
+const EXTENDED_SEGMENT_ADDRESS: u8 = 0x02;
+/// Start segment address record, the CS:IP entry point.
+const START_SEGMENT_ADDRESS: u8 = 0x03;
+/// Extended linear address record: bits 16-31 of the following addresses.
+const EXTENDED_LINEAR_ADDRESS: u8 = 0x04;
+/// Start linear address record, the 32-bit entry point.
+const START_LINEAR_ADDRESS: u8 = 0x05;
+
+/// Returns the checksum of a record: the two's complement of the sum of
+/// its bytes.
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg()
+}
+
+/// Formats one record. `data` must be at most 255 bytes.
+fn record(kind: u8, address: u16, data: &[u8]) -> String {
+    let [high, low] = address.to_be_bytes();
+    let mut bytes = vec![data.len() as u8, high, low, kind];
+    bytes.extend_from_slice(data);
+    bytes.push(checksum(&bytes));
+    format!(":{}", hex::encode_upper(bytes))
+}
+
+/// Parses one record into its bytes, checking its length and checksum.
+fn parse_record(line: &str) -> Result<Vec<u8>, String> {
+    let digits = line
+        .strip_prefix(':')
+        .ok_or_else(|| "record does not start with ':'".to_string())?;
+    let bytes = hex::decode(digits).map_err(|err| format!("invalid record: {err}"))?;
+    let Some((&found, body)) = bytes.split_last() else {
+        return Err("empty record".to_string());
+    };
+    if body.len() < 4 || body.len() != usize::from(body[0]) + 4 {
+        return Err("record length does not match its data".to_string());
+    }
+    let expected = checksum(body);
+    if found != expected {
+        return Err(format!(
+            "checksum mismatch: expected {expected:02X}, found {found:02X}"
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Returns the 16-bit value of an extended address record's data.
+fn address_value(data: &[u8]) -> Result<u32, String> {
+    match data {
+        [high, low] => Ok(u32::from(u16::from_be_bytes([*high, *low]))),
+        _ => Err("address record must have 2 data bytes".to_string()),
+    }
+}
+
+/// Encodes `data` as Intel HEX, starting at `base_address`.
+///
+/// Data records hold 16 bytes each and never cross a 64 KiB boundary; an
+/// extended linear address record is written before the data past each
+/// boundary. The text ends with the end-of-file record, one record per line.
+///
+/// # Examples
+///
+/// ```
+/// use serial_bevy::serial::encoding::intel_hex::encode_to_ihex;
+///
+/// let text = encode_to_ihex(&[0x01, 0x02], 0x0100);
+/// assert_eq!(text, ":020100000102FA\n:00000001FF\n");
+/// ```
+#[must_use]
+pub fn encode_to_ihex(data: &[u8], base_address: u16) -> String {
+    let mut text = String::new();
+    let mut address = u32::from(base_address);
+    let mut upper = 0;
+    let mut rest = data;
+    while !rest.is_empty() {
+        let [high_0, high_1, low_0, low_1] = address.to_be_bytes();
+        let high = u16::from_be_bytes([high_0, high_1]);
+        let low = u16::from_be_bytes([low_0, low_1]);
+        if high != upper {
+            text.push_str(&record(EXTENDED_LINEAR_ADDRESS, 0, &high.to_be_bytes()));
+            text.push('\n');
+            upper = high;
+        }
+        let len = rest
+            .len()
+            .min(RECORD_DATA_LEN)
+            .min(0x1_0000 - usize::from(low));
+        let (chunk, tail) = rest.split_at(len);
+        text.push_str(&record(DATA, low, chunk));
+        text.push('\n');
+        address = address.wrapping_add(len as u32);
+        rest = tail;
+    }
+    text.push_str(&record(END_OF_FILE, 0, &[]));
+    text.push('\n');
+    text
+}
+
+/// Decodes Intel HEX text into the bytes it describes.
+///
+/// The image runs from the lowest to the highest address written; gaps
+/// between data records are filled with `0xFF`, as in erased flash. Blank
+/// lines are ignored, start address records are skipped and a missing
+/// end-of-file record is accepted.
+///
+/// # Errors
+///
+/// Returns an encoding error naming the line if a record is malformed, has
+/// a wrong checksum or an unknown type, if records follow the end-of-file
+/// record, or if the image would be larger than 16 MiB.
+///
+/// # Examples
+///
+/// ```
+/// use serial_bevy::serial::encoding::intel_hex::decode_from_ihex;
+///
+/// let bytes = decode_from_ihex(":020100000102FA\n:00000001FF\n").unwrap();
+/// assert_eq!(bytes, vec![0x01, 0x02]);
+/// assert!(decode_from_ihex(":020100000102FB").is_err());
+/// ```
+pub fn decode_from_ihex(text: &str) -> Result<Vec<u8>, SerialBevyError> {
+    let mut chunks: Vec<(u64, Vec<u8>)> = Vec::new();
+    let mut offset = 0u32;
+    let mut ended = false;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let line_error = |reason: String| {
+            SerialBevyError::encoding(format!("Intel HEX line {}: {reason}", index + 1))
+        };
+        if ended {
+            return Err(line_error("record after end of file".to_string()));
+        }
+        let bytes = parse_record(line).map_err(line_error)?;
+        let address = u32::from(u16::from_be_bytes([bytes[1], bytes[2]]));
+        let data = &bytes[4..bytes.len() - 1];
+        match bytes[3] {
+            DATA => chunks.push((u64::from(offset) + u64::from(address), data.to_vec())),
+            END_OF_FILE => ended = true,
+            EXTENDED_SEGMENT_ADDRESS => offset = address_value(data).map_err(line_error)? << 4,
+            EXTENDED_LINEAR_ADDRESS => offset = address_value(data).map_err(line_error)? << 16,
+            START_SEGMENT_ADDRESS | START_LINEAR_ADDRESS => {}
+            kind => return Err(line_error(format!("unknown record type {kind:02X}"))),
+        }
+    }
+
+    let Some(start) = chunks.iter().map(|(address, _)| *address).min() else {
+        return Ok(Vec::new());
+    };
+    let end = chunks
+        .iter()
+        .map(|(address, data)| address + data.len() as u64)
+        .max()
+        .unwrap_or(start);
+    if end - start > MAX_IMAGE_LEN {
+        return Err(SerialBevyError::encoding(format!(
+            "Intel HEX image spans {} bytes, more than {MAX_IMAGE_LEN}",
+            end - start
+        )));
+    }
+
+    let mut image = vec![0xFF; (end - start) as usize];
+    for (address, data) in chunks {
+        let at = (address - start) as usize;
+        image[at..at + data.len()].copy_from_slice(&data);
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sample program loaded at 0x0100.
+    const SAMPLE: &str = "\
+:10010000214601360121470136007EFE09D2190140
+:100110002146017E17C20001FF5F16002148011928
+:10012000194E79234623965778239EDA3F01B2CAA7
+:100130003F0156702B5E712B722B732146013421C7
+:00000001FF
+";
+
+    #[test]
+    fn test_decode_sample_and_encode_back() {
+        let bytes = decode_from_ihex(SAMPLE).unwrap();
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(bytes[..4], [0x21, 0x46, 0x01, 0x36]);
+        assert_eq!(bytes[62..], [0x34, 0x21]);
+        assert_eq!(encode_to_ihex(&bytes, 0x0100), SAMPLE);
+    }
+
+    #[test]
+    fn test_round_trip_across_64k_boundary() {
+        let data: Vec<u8> = (0..40).collect();
+        let text = encode_to_ihex(&data, 0xFFF8);
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                ":08FFF8000001020304050607E5",
+                ":020000040001F9",
+                ":1000000008090A0B0C0D0E0F1011121314151617F8",
+                ":1000100018191A1B1C1D1E1F2021222324252627E8",
+                ":00000001FF",
+            ]
+        );
+        assert_eq!(decode_from_ihex(&text).unwrap(), data);
+    }
+
+    #[test]
+    fn test_decode_fills_gaps() {
+        let text = ":0100000011EE\r\n\r\n:0100030022DA\n";
+        assert_eq!(decode_from_ihex(text).unwrap(), [0x11, 0xFF, 0xFF, 0x22]);
+        assert_eq!(decode_from_ihex("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_records() {
+        let bad_checksum = decode_from_ihex(":0100000011EF").unwrap_err();
+        assert!(
+            bad_checksum
+                .to_string()
+                .contains("line 1: checksum mismatch")
+        );
+        assert!(decode_from_ihex("0100000011EE").is_err());
+        assert!(decode_from_ihex(":0200000011EE").is_err());
+        assert!(decode_from_ihex(":00000001FF\n:0100000011EE").is_err());
+        assert!(decode_from_ihex(":0100000611E8").is_err());
+    }
+}
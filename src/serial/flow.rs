@@ -0,0 +1,228 @@
+//! # Flow Module
+//!
+//! Detection of sending stalled by flow control.
+//!
+//! With hardware flow control, a device holding CTS low pauses every write
+//! until it raises it again; with software flow control, an XOFF (`0x13`)
+//! byte pauses writes until an XON (`0x11`) follows. Either way sent data
+//! just waits without an error. A port that has been paused for longer than
+//! [`SerialConfig::flow_stall_timeout`] while data was sent raises a
+//! [`FlowStall`], and another one once it resumes.
+//!
+//! [`SerialConfig::flow_stall_timeout`]: super::SerialConfig::flow_stall_timeout
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use log::warn;
+
+use super::SerialConfig;
+use super::port::{FlowControl, Serial};
+use super::snapshot::lock_serial;
+use super::status::SerialIo;
+
+/// Time a port is paused with data pending before a [`FlowStall`] is raised.
+pub const DEFAULT_FLOW_STALL_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Byte asking the sender to pause, in software flow control.
+pub const XOFF: u8 = 0x13;
+
+/// Byte asking the sender to resume, in software flow control.
+pub const XON: u8 = 0x11;
+
+/// Why sending on a port is paused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowStallKind {
+    /// The device holds CTS low, with hardware flow control.
+    CtsLow,
+    /// The device sent XOFF, with software flow control.
+    Xoff,
+}
+
+/// Sent when sending on a port stalls because of flow control, or resumes.
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct FlowStall {
+    /// Name of the port.
+    pub port_name: String,
+    /// Why sending is paused, or `None` once it resumed.
+    pub kind: Option<FlowStallKind>,
+}
+
+/// Flow control state of a port, as seen from its status lines and
+/// received data.
+#[derive(Debug, Default)]
+pub struct FlowMonitor {
+    /// Since when CTS has been low.
+    cts_low_since: Option<Instant>,
+    /// Since when an XOFF has been in effect.
+    xoff_since: Option<Instant>,
+    /// Stall last reported.
+    reported: Option<FlowStallKind>,
+}
+
+impl FlowMonitor {
+    /// Records the CTS line read at `now`.
+    pub fn update_cts(&mut self, cts: bool, now: Instant) {
+        if cts {
+            self.cts_low_since = None;
+        } else {
+            self.cts_low_since.get_or_insert(now);
+        }
+    }
+
+    /// Records the XOFF and XON bytes of data received at `now`; the last one
+    /// of them decides whether sending is paused.
+    pub fn scan_received(&mut self, data: &[u8], now: Instant) {
+        match data.iter().rev().find(|byte| matches!(**byte, XOFF | XON)) {
+            Some(&XOFF) => {
+                self.xoff_since.get_or_insert(now);
+            }
+            Some(_) => self.xoff_since = None,
+            None => {}
+        }
+    }
+
+    /// Returns why sending is paused at `now` under `flow_control`: paused
+    /// for longer than `timeout`, with data sent (`last_tx`) during the pause
+    /// or at most `timeout` before it began.
+    #[must_use]
+    pub fn stall(
+        &self,
+        flow_control: FlowControl,
+        last_tx: Option<Instant>,
+        timeout: Duration,
+        now: Instant,
+    ) -> Option<FlowStallKind> {
+        let (since, kind) = match flow_control {
+            FlowControl::Hardware => (self.cts_low_since?, FlowStallKind::CtsLow),
+            FlowControl::Software => (self.xoff_since?, FlowStallKind::Xoff),
+            FlowControl::None => return None,
+        };
+        let pending = last_tx.is_some_and(|tx| tx + timeout >= since);
+        (pending && now.saturating_duration_since(since) > timeout).then_some(kind)
+    }
+
+    /// Updates the reported stall and returns it if it changed.
+    pub fn report(&mut self, stall: Option<FlowStallKind>) -> Option<Option<FlowStallKind>> {
+        (self.reported != stall).then(|| {
+            self.reported = stall;
+            stall
+        })
+    }
+
+    /// Forgets the flow control state, e.g. when the port closes.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Checks the flow control of `serial` at `now`. Returns the event to send
+/// if its stall started or ended.
+fn check_port(serial: &mut Serial, timeout: Duration, now: Instant) -> Option<FlowStall> {
+    let flow_control = serial.set.flow_control;
+    let last_tx = serial.data().last_tx_instant();
+    let stall = if serial.is_open() {
+        serial
+            .data()
+            .flow_monitor()
+            .stall(flow_control, last_tx, timeout, now)
+    } else {
+        None
+    };
+    let kind = serial.data().flow_monitor().report(stall)?;
+    let port_name = serial.set.port_name.clone();
+    if let Some(kind) = kind {
+        warn!("{port_name}: sending paused by flow control ({kind:?})");
+    }
+    Some(FlowStall { port_name, kind })
+}
+
+/// System: raises a [`FlowStall`] for ports whose sending stalled because of
+/// flow control, or resumed.
+pub fn check_flow_stalls(
+    ports: Query<&SerialIo>,
+    config: Res<SerialConfig>,
+    mut events: MessageWriter<FlowStall>,
+) {
+    let now = Instant::now();
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        if let Some(event) = check_port(&mut serial, config.flow_stall_timeout, now) {
+            events.write(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cts_low_stalls_pending_data() {
+        let start = Instant::now();
+        let timeout = Duration::from_millis(500);
+        let late = start + Duration::from_secs(1);
+        let mut monitor = FlowMonitor::default();
+        monitor.update_cts(false, start);
+        monitor.update_cts(false, start + Duration::from_millis(100));
+
+        // Nothing was sent, so nothing waits.
+        assert_eq!(
+            monitor.stall(FlowControl::Hardware, None, timeout, late),
+            None
+        );
+        let sent = Some(start + Duration::from_millis(10));
+        assert_eq!(
+            monitor.stall(FlowControl::Hardware, sent, timeout, late),
+            Some(FlowStallKind::CtsLow)
+        );
+        assert_eq!(
+            monitor.stall(FlowControl::Hardware, sent, timeout, start + timeout),
+            None
+        );
+        assert_eq!(monitor.stall(FlowControl::None, sent, timeout, late), None);
+
+        assert_eq!(
+            monitor.report(Some(FlowStallKind::CtsLow)),
+            Some(Some(FlowStallKind::CtsLow))
+        );
+        assert_eq!(monitor.report(Some(FlowStallKind::CtsLow)), None);
+        monitor.update_cts(true, late);
+        assert_eq!(
+            monitor.stall(FlowControl::Hardware, sent, timeout, late),
+            None
+        );
+        assert_eq!(monitor.report(None), Some(None));
+    }
+
+    #[test]
+    fn test_xoff_until_xon() {
+        let start = Instant::now();
+        let timeout = Duration::from_millis(500);
+        let late = start + Duration::from_secs(1);
+        let sent = Some(start);
+        let mut monitor = FlowMonitor::default();
+
+        monitor.scan_received(&[XON, b'a', XOFF, b'b'], start);
+        assert_eq!(
+            monitor.stall(FlowControl::Software, sent, timeout, late),
+            Some(FlowStallKind::Xoff)
+        );
+        assert_eq!(
+            monitor.stall(FlowControl::Hardware, sent, timeout, late),
+            None
+        );
+
+        monitor.scan_received(b"plain", late);
+        assert!(
+            monitor
+                .stall(FlowControl::Software, sent, timeout, late)
+                .is_some()
+        );
+        monitor.scan_received(&[XOFF, XON], late);
+        assert_eq!(
+            monitor.stall(FlowControl::Software, sent, timeout, late),
+            None
+        );
+    }
+}
@@ -3,61 +3,193 @@
 //! Serial port I/O operations including thread lifecycle management,
 //! read/write handling, and data transfer between Bevy ECS and async serial threads.
 
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
 use bevy::prelude::*;
-use log::{debug, error, info};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use log::{debug, error, info, warn};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
 use tokio::sync::broadcast;
+use tokio::time::Duration;
 
-use super::Serials;
-use super::data_types::DataType;
+use super::SerialConfig;
 use super::discovery::Runtime;
-use super::encoding::encode_string;
-use super::port::Serial;
-use super::port::open_port;
+use super::mock::{MockTraffic, spawn_mock_device};
+use super::parser::DataProcessor;
+use super::port::{FlowControl, Serial};
+use super::port::{MAX_FRAME_SIZE, PortBackend, PortSettings, open_port};
+use super::session::SessionDirection;
+use super::snapshot::lock_serial;
 use super::state::{DataSource, PortChannelData, PortRwData, PortState};
+use super::status::SerialIo;
+use super::traffic::TrafficLog;
 use crate::error::SerialBevyError;
 
-// SerialStream comes from tokio_serial, re-exported via super::port
-use tokio_serial::SerialStream;
+/// Backend owned by a port thread: a hardware stream or a virtual port.
+type BoxedBackend = Box<dyn PortBackend>;
+
+/// Interval between two reads of the modem status lines.
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time a failed port waits before [`reconnect_failed_ports`] opens it again.
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Backend shared by the read, write and signal tasks of a port thread.
+///
+/// Each poll locks the backend only for the duration of the call, like
+/// `tokio::io::split`, but the signal task can also reach the port.
+#[derive(Clone)]
+struct SharedBackend(Arc<Mutex<BoxedBackend>>);
+
+impl SharedBackend {
+    fn new(backend: BoxedBackend) -> Self {
+        Self(Arc::new(Mutex::new(backend)))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BoxedBackend> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl AsyncRead for SharedBackend {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let mut backend = self.lock();
+        Pin::new(&mut **backend).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for SharedBackend {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let mut backend = self.lock();
+        Pin::new(&mut **backend).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let mut backend = self.lock();
+        Pin::new(&mut **backend).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let mut backend = self.lock();
+        Pin::new(&mut **backend).poll_shutdown(cx)
+    }
+}
 
 /// Creates threads for serial ports that don't have one.
 ///
 /// This system runs every frame and checks if any managed serial port
 /// is missing its async communication thread, spawning one if needed.
-pub fn create_serial_port_threads(mut serials: Query<&mut Serials>, runtime: Res<Runtime>) {
-    let Ok(mut serials) = serials.single_mut() else {
+pub fn create_serial_port_threads(
+    ports: Query<&SerialIo>,
+    runtime: Res<Runtime>,
+    config: Res<SerialConfig>,
+) {
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        if serial.thread_handle().is_none() {
+            setup_serial_thread(&mut serial, &runtime, config.channel_buffer_size);
+        }
+    }
+}
+
+/// Replaces the port thread of `serial` with a new one waiting for an open
+/// request, stopping the old one if it still runs.
+///
+/// A thread ends when its port fails to open, so a retry needs a new one.
+pub fn restart_serial_thread(serial: &mut Serial, runtime: &Runtime, buffer_size: usize) {
+    serial.stop_thread();
+    setup_serial_thread(serial, runtime, buffer_size);
+}
+
+/// Clears the error of a port and opens it again on a new port thread, as
+/// the thread of a port that failed has ended.
+pub fn reopen_port(serial: &mut Serial, runtime: &Runtime, buffer_size: usize) {
+    // Closing forgets the thread handle, so the thread is stopped first.
+    serial.stop_thread();
+    serial.close();
+    serial.data().set_last_error(None);
+    setup_serial_thread(serial, runtime, buffer_size);
+    match serial.open_session() {
+        Ok(path) => debug!("Reopening {}, logging to {path}", serial.set.port_name),
+        Err(e) => warn!("Failed to reopen {}: {e}", serial.set.port_name),
+    }
+}
+
+/// System: reopens failed ports after [`RECONNECT_DELAY`] while
+/// [`SerialConfig::reconnect_on_error`] is set.
+///
+/// A port missing from the last discovered port list waits until it shows
+/// up again, so an unplugged adapter is reopened once it is plugged back in.
+/// A reopen that fails again waits another delay.
+pub fn reconnect_failed_ports(
+    time: Res<Time>,
+    config: Res<SerialConfig>,
+    runtime: Res<Runtime>,
+    ports: Query<&SerialIo>,
+    mut failed_for: Local<HashMap<String, Duration>>,
+) {
+    if !config.reconnect_on_error {
+        failed_for.clear();
         return;
-    };
+    }
 
-    for serial in &mut serials.serial {
-        let Ok(mut serial) = serial.lock() else {
+    let mut still_failed = HashMap::new();
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        if !serial.is_error() {
+            continue;
+        }
+        let name = serial.set.port_name.clone();
+        let waited = failed_for.get(&name).copied().unwrap_or_default() + time.delta();
+        if waited < RECONNECT_DELAY || serial.missing_scans() > 0 {
+            still_failed.insert(name, waited.min(RECONNECT_DELAY));
             continue;
-        };
-        if serial.thread_handle().is_none() {
-            setup_serial_thread(&mut serial, &runtime);
         }
+        info!("Reconnecting {name}");
+        reopen_port(&mut serial, &runtime, config.channel_buffer_size);
     }
+    *failed_for = still_failed;
 }
 
 /// Sets up the serial port communication thread.
 ///
-/// Creates broadcast channels for communication between the main ECS thread
-/// and the async port worker, then spawns an async task that:
+/// Creates broadcast channels of `buffer_size` messages for communication
+/// between the main ECS thread and the async port worker, then spawns an async task that:
 /// 1. Waits for a port open command
-/// 2. Splits the serial stream into read/write halves
-/// 3. Spawns dedicated read and write handlers
-fn setup_serial_thread(serial: &mut Serial, runtime: &Runtime) {
-    let (tx, mut rx) = broadcast::channel(100);
-    let (tx1, rx1) = broadcast::channel(100);
+/// 2. Shares the serial stream between the read, write and signal tasks
+/// 3. Spawns dedicated read, write and signal handlers
+///
+/// The task stops early when the runtime shuts down.
+fn setup_serial_thread(serial: &mut Serial, runtime: &Runtime, buffer_size: usize) {
+    let (tx, mut rx) = broadcast::channel(buffer_size);
+    let (tx1, rx1) = broadcast::channel(buffer_size);
     let rx_shutdown = tx.subscribe();
+    let rx_signal_shutdown = tx.subscribe();
 
     *serial.tx_channel() = Some(tx);
     *serial.rx_channel() = Some(rx1);
 
     let port_name = serial.set.port_name.clone();
+    let mock = serial.mock_traffic().cloned();
+    let mut shutdown = runtime.shutdown_signal();
 
     let handle = runtime.spawn(async move {
-        let port = match wait_for_port_open(&mut rx, &tx1).await {
+        let port = tokio::select! {
+            () = shutdown.cancelled() => return Ok(()),
+            port = wait_for_port_open(&mut rx, &tx1, mock.as_ref()) => port,
+        };
+        let (port, settings) = match port {
             Ok(p) => p,
             Err(e) => {
                 error!("Failed to open port: {e:?}");
@@ -70,14 +202,34 @@ fn setup_serial_thread(serial: &mut Serial, runtime: &Runtime) {
             return Err(SerialBevyError::channel(e.to_string()));
         }
 
-        let (read, write) = tokio::io::split(port);
-        let read_handle = spawn_read_thread(read, tx1.clone(), rx_shutdown, &port_name);
+        let port = SharedBackend::new(port);
+        let read_handle = spawn_read_thread(
+            port.clone(),
+            tx1.clone(),
+            rx_shutdown,
+            &port_name,
+            settings.frame_gap,
+        );
+        let signal_handle = spawn_signal_thread(port.clone(), tx1.clone(), rx_signal_shutdown);
 
-        handle_write_thread(write, rx, tx1, &port_name).await;
+        let result = tokio::select! {
+            () = shutdown.cancelled() => {
+                debug!("Stopping serial port thread: {port_name}");
+                Ok(())
+            }
+            result = handle_write_thread(port, rx, tx1, &port_name, settings.send_rate_limit) => result,
+        };
 
         read_handle.abort();
+        signal_handle.abort();
+        if let Ok(Err(e)) = read_handle.await {
+            error!("{e}");
+        }
+        if let Err(e) = &result {
+            error!("{e}");
+        }
         info!("Serial port thread exited: {port_name}");
-        Ok(())
+        result
     });
 
     *serial.thread_handle() = Some(handle);
@@ -86,15 +238,22 @@ fn setup_serial_thread(serial: &mut Serial, runtime: &Runtime) {
 /// Waits for a port open request on the command channel and opens the serial port
 /// with the provided settings.
 ///
-/// Returns an open `SerialStream` once the user triggers a port open command.
+/// Returns the opened backend and the session's settings once the user
+/// triggers a port open command. Virtual ports (`mock` is set) spawn their mock
+/// device instead of touching hardware.
 async fn wait_for_port_open(
     rx: &mut broadcast::Receiver<PortChannelData>,
     tx1: &broadcast::Sender<PortChannelData>,
-) -> Result<SerialStream, SerialBevyError> {
+    mock: Option<&MockTraffic>,
+) -> Result<(BoxedBackend, PortSettings), SerialBevyError> {
     loop {
         if let Ok(PortChannelData::PortOpen(settings)) = rx.recv().await {
+            if let Some(traffic) = mock {
+                debug!("Opening mock port: {}", settings.port_name);
+                return Ok((Box::new(spawn_mock_device(traffic.clone())), settings));
+            }
             return match open_port(&settings).await {
-                Ok(port) => Ok(port),
+                Ok(port) => Ok((Box::new(port), settings)),
                 Err(e) => {
                     let _ = tx1.send(PortChannelData::PortError(PortRwData {
                         data: b"open port failed".to_vec(),
@@ -114,20 +273,82 @@ fn notify_port_ready(
     Ok(())
 }
 
+/// Splits received data into frames at silent gaps on the line.
+///
+/// Without a gap, every read is forwarded as it is. With one, bytes
+/// accumulate until the line stays silent for the gap, or until
+/// [`MAX_FRAME_SIZE`] bytes have arrived.
+#[derive(Debug)]
+struct FrameAssembler {
+    /// Silence that ends a frame, if framing by gaps.
+    gap: Option<Duration>,
+    /// Bytes of the frame being received.
+    frame: Vec<u8>,
+    /// When the frame being received ends if nothing more arrives.
+    deadline: Option<tokio::time::Instant>,
+}
+
+impl FrameAssembler {
+    fn new(gap: Option<Duration>) -> Self {
+        Self {
+            gap,
+            frame: Vec::new(),
+            deadline: None,
+        }
+    }
+
+    /// Adds received bytes and returns the frames they complete.
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        let Some(gap) = self.gap else {
+            return vec![data.to_vec()];
+        };
+        self.frame.extend_from_slice(data);
+        let mut frames = Vec::new();
+        while self.frame.len() >= MAX_FRAME_SIZE {
+            let rest = self.frame.split_off(MAX_FRAME_SIZE);
+            frames.push(std::mem::replace(&mut self.frame, rest));
+        }
+        self.deadline = (!self.frame.is_empty()).then(|| tokio::time::Instant::now() + gap);
+        frames
+    }
+
+    /// Ends the frame being received and returns it.
+    fn take(&mut self) -> Vec<u8> {
+        self.deadline = None;
+        std::mem::take(&mut self.frame)
+    }
+}
+
+/// Forwards received bytes to the main thread.
+fn forward_read(tx1_read: &broadcast::Sender<PortChannelData>, port_name: &str, data: Vec<u8>) {
+    if data.is_empty() {
+        return;
+    }
+    debug!("{port_name} read: {data:?}");
+    if let Err(e) = tx1_read.send(PortChannelData::PortRead(PortRwData { data })) {
+        error!("Failed to send read data: {e}");
+    }
+}
+
 /// Spawns an async read thread that continuously reads data from the serial port.
 ///
 /// Reads are performed in 1024-byte chunks and forwarded to the main thread
-/// via the broadcast channel. The loop exits on shutdown signal or error.
+/// via the broadcast channel, as they are or as frames ended by `frame_gap`
+/// (see [`FrameAssembler`]). The loop exits on shutdown signal or error,
+/// returning [`SerialBevyError::PortRead`] for the latter.
 fn spawn_read_thread(
-    mut read: tokio::io::ReadHalf<SerialStream>,
+    mut read: SharedBackend,
     tx1_read: broadcast::Sender<PortChannelData>,
     mut rx_shutdown: broadcast::Receiver<PortChannelData>,
     port_name: &str,
-) -> tokio::task::JoinHandle<()> {
+    frame_gap: Option<Duration>,
+) -> tokio::task::JoinHandle<Result<(), SerialBevyError>> {
     let port_name = port_name.to_owned();
     tokio::spawn(async move {
         let mut buffer = [0u8; 1024];
+        let mut frames = FrameAssembler::new(frame_gap);
         loop {
+            let deadline = frames.deadline;
             tokio::select! {
                 result = rx_shutdown.recv() => {
                     if let Ok(PortChannelData::PortClose(name)) = result {
@@ -135,24 +356,61 @@ fn spawn_read_thread(
                         break;
                     }
                 }
+                () = tokio::time::sleep_until(deadline.unwrap_or_else(tokio::time::Instant::now)),
+                    if deadline.is_some() =>
+                {
+                    forward_read(&tx1_read, &port_name, frames.take());
+                }
                 result = read.read(&mut buffer) => {
                     match result {
                         Ok(n) if n > 0 => {
-                            let data = PortRwData {
-                                data: buffer[..n].to_vec(),
-                            };
-                            if let Err(e) = tx1_read.send(PortChannelData::PortRead(data.clone())) {
-                                error!("Failed to send read data: {e}");
-                            } else {
-                                debug!("{} read: {:?}", port_name, data.data);
+                            for frame in frames.push(&buffer[..n]) {
+                                forward_read(&tx1_read, &port_name, frame);
                             }
                         }
                         Ok(_) => {
                             // Zero bytes read, connection closed
+                            forward_read(&tx1_read, &port_name, frames.take());
                             break;
                         }
                         Err(e) => {
-                            error!("Read error on {port_name}: {e}");
+                            return Err(SerialBevyError::port_read(format!("{port_name}: {e}")));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Spawns an async task that polls the modem status lines.
+///
+/// The state is read every [`SIGNAL_POLL_INTERVAL`] and sent to the main thread
+/// whenever it changes. The task exits on shutdown, or immediately if the
+/// backend has no status lines.
+fn spawn_signal_thread(
+    port: SharedBackend,
+    tx1_signal: broadcast::Sender<PortChannelData>,
+    mut rx_shutdown: broadcast::Receiver<PortChannelData>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last = None;
+        let mut ticker = tokio::time::interval(SIGNAL_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                result = rx_shutdown.recv() => {
+                    if matches!(result, Ok(PortChannelData::PortClose(_)) | Err(broadcast::error::RecvError::Closed)) {
+                        break;
+                    }
+                }
+                _ = ticker.tick() => {
+                    let Some(signals) = port.lock().read_signals() else {
+                        break;
+                    };
+                    if last != Some(signals) {
+                        last = Some(signals);
+                        if tx1_signal.send(PortChannelData::SignalUpdate(signals)).is_err() {
                             break;
                         }
                     }
@@ -162,139 +420,1118 @@ fn spawn_read_thread(
     })
 }
 
+/// Paces writes to at most a given number of bytes per second.
+///
+/// Data is written in chunks of a tenth of a second's worth, each followed by
+/// a wait until the chunk would have been sent at the limit.
+#[derive(Debug)]
+struct WriteThrottle {
+    /// Limit in bytes per second, if any.
+    rate: Option<u32>,
+    /// When the bytes written so far are sent at the limit.
+    next_write: tokio::time::Instant,
+}
+
+impl WriteThrottle {
+    fn new(rate: Option<u32>) -> Self {
+        Self {
+            rate: rate.filter(|rate| *rate > 0),
+            next_write: tokio::time::Instant::now(),
+        }
+    }
+
+    /// Returns the size of the chunks data is written in.
+    fn chunk_len(&self) -> usize {
+        self.rate
+            .map_or(usize::MAX, |rate| (rate as usize / 10).max(1))
+    }
+
+    /// Accounts for `len` written bytes and returns when the next write may
+    /// start, or `None` without a limit.
+    fn delay(&mut self, len: usize) -> Option<tokio::time::Instant> {
+        let rate = self.rate?;
+        let duration = Duration::from_secs_f64(len as f64 / f64::from(rate));
+        self.next_write = self.next_write.max(tokio::time::Instant::now()) + duration;
+        Some(self.next_write)
+    }
+}
+
+/// Writes `data` paced by `throttle`.
+///
+/// Messages arriving while it waits are queued in `pending`; a close request
+/// stops the write early.
+async fn write_throttled(
+    write: &mut (impl AsyncWrite + Unpin),
+    data: &[u8],
+    throttle: &mut WriteThrottle,
+    rx: &mut broadcast::Receiver<PortChannelData>,
+    pending: &mut VecDeque<PortChannelData>,
+) -> std::io::Result<()> {
+    for chunk in data.chunks(throttle.chunk_len()) {
+        write.write_all(chunk).await?;
+        let Some(deadline) = throttle.delay(chunk.len()) else {
+            continue;
+        };
+        loop {
+            tokio::select! {
+                () = tokio::time::sleep_until(deadline) => break,
+                result = rx.recv() => match result {
+                    Ok(message) => {
+                        let close = matches!(message, PortChannelData::PortClose(_));
+                        pending.push_back(message);
+                        if close {
+                            return Ok(());
+                        }
+                    }
+                    Err(_) => {
+                        tokio::time::sleep_until(deadline).await;
+                        break;
+                    }
+                },
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Holds the line of `port` in the break condition for `duration`, once the
+/// data written before has been flushed.
+///
+/// The backend is only locked to start and end the break, so the other
+/// tasks of the port keep running while the line is held.
+async fn send_break(port: &mut SharedBackend, duration: Duration) -> std::io::Result<()> {
+    port.flush().await?;
+    port.lock().set_break_condition(true)?;
+    tokio::time::sleep(duration).await;
+    port.lock().set_break_condition(false)
+}
+
 /// Handles writing data to the serial port.
 ///
-/// Listens on the command channel for write requests and port close commands.
-/// Writes data to the serial stream and forwards close/state messages back
-/// to the main thread.
+/// Listens on the command channel for write requests, break requests and
+/// port close commands.
+/// Writes data to the serial stream, at most `send_rate_limit` bytes per
+/// second if set, and forwards close/state messages back to the main thread.
+///
+/// # Errors
+///
+/// Returns [`SerialBevyError::PortWrite`] if writing to the port fails.
 async fn handle_write_thread(
-    mut write: tokio::io::WriteHalf<SerialStream>,
+    mut write: SharedBackend,
     mut rx: broadcast::Receiver<PortChannelData>,
     tx1: broadcast::Sender<PortChannelData>,
     port_name: &str,
-) {
+    send_rate_limit: Option<u32>,
+) -> Result<(), SerialBevyError> {
+    let mut throttle = WriteThrottle::new(send_rate_limit);
+    let mut pending = VecDeque::new();
     loop {
-        if let Ok(data) = rx.recv().await {
-            match data {
-                PortChannelData::PortWrite(data) => {
-                    debug!("{} write: {:?}", port_name, data.data);
-                    if write.write_all(&data.data).await.is_err() {
-                        error!("{port_name} write error");
-                        break;
-                    }
+        let data = match pending.pop_front() {
+            Some(data) => data,
+            None => match rx.recv().await {
+                Ok(data) => data,
+                Err(_) => continue,
+            },
+        };
+        match data {
+            PortChannelData::PortWrite(data) | PortChannelData::ProxyWrite(data) => {
+                debug!("{} write: {:?}", port_name, data.data);
+                if let Err(e) =
+                    write_throttled(&mut write, &data.data, &mut throttle, &mut rx, &mut pending)
+                        .await
+                {
+                    return Err(SerialBevyError::port_write(format!("{port_name}: {e}")));
                 }
-                PortChannelData::PortClose(name) => {
-                    debug!("Closing serial port write thread: {name}");
-                    let _ = tx1.send(PortChannelData::PortState(PortState::Close));
-                    break;
+            }
+            PortChannelData::PortReconfigure(settings) => {
+                if let Err(e) = write.lock().reconfigure(&settings) {
+                    warn!("Failed to reconfigure {port_name}: {e}");
+                }
+                throttle = WriteThrottle::new(settings.send_rate_limit);
+            }
+            PortChannelData::SendBreak { duration_ms } => {
+                debug!("{port_name} break: {duration_ms} ms");
+                let duration = Duration::from_millis(u64::from(duration_ms));
+                if let Err(e) = send_break(&mut write, duration).await {
+                    warn!("Failed to send a break on {port_name}: {e}");
                 }
-                _ => {}
             }
+            PortChannelData::PortClose(name) => {
+                debug!("Closing serial port write thread: {name}");
+                let _ = tx1.send(PortChannelData::PortState(PortState::Close));
+                return Ok(());
+            }
+            _ => {}
         }
     }
 }
 
 /// Sends data queued on each serial port's send buffer to the port's async thread.
 ///
-/// Encodes queued string data according to the port's configured `DataType`,
-/// then dispatches it via the broadcast channel to the serial port write thread.
-/// In non-console mode, the sent data is also written to the log file with a
-/// "Write" source indicator.
-pub fn send_serial_data(mut serials: Query<&mut Serials>) {
-    let Ok(mut serials) = serials.single_mut() else {
-        return;
-    };
+/// See [`process_outgoing`].
+pub fn send_serial_data(ports: Query<&SerialIo>, mut traffic: ResMut<TrafficLog>) {
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        process_outgoing(&mut serial, &mut traffic);
+    }
+}
+
+/// Sends the payloads queued on `serial` to its port thread.
+///
+/// Each payload is written as it was encoded when queued, and logged
+/// separately with its own display text, as described in [`write_to_port`].
+///
+/// Returns the bytes written to the port thread, in order, or `None` if
+/// nothing was written.
+pub fn process_outgoing(serial: &mut Serial, traffic: &mut TrafficLog) -> Option<Vec<u8>> {
+    let mut written = Vec::new();
+    for payload in serial.data().get_send_data() {
+        if write_to_port(serial, payload.raw.clone(), &payload.display, traffic) {
+            written.extend(payload.raw);
+        }
+    }
+    (!written.is_empty()).then_some(written)
+}
+
+/// Logs `text` as sent data and writes the encoded `data` to the port.
+///
+/// In non-console mode the text is written to the log file and the
+/// [`TrafficLog`] with a "Write" source indicator, and to the receive view as
+/// set by [`PortData::local_echo`](super::port_data::PortData::local_echo);
+/// in console mode the log entry is skipped, as the terminal echoes it back. Returns true if the data
+/// was handed to the port thread, which requires the port to be open.
+pub fn write_to_port(
+    serial: &mut Serial,
+    data: Vec<u8>,
+    text: &str,
+    traffic: &mut TrafficLog,
+) -> bool {
+    if !serial.data().is_console_mode() {
+        serial.data().write_sent(text.as_bytes(), data.clone());
+        traffic.record(&serial.set.port_name, DataSource::Write, text.as_bytes());
+    }
 
-    for serial in &mut serials.serial {
-        let Ok(mut serial) = serial.lock() else {
+    if serial.is_open()
+        && let Some(tx) = serial.tx_channel()
+    {
+        let len = data.len();
+        serial
+            .data()
+            .record_session_frame(SessionDirection::Tx, &data);
+        match tx.send(PortChannelData::PortWrite(PortRwData { data })) {
+            Ok(_) => {
+                serial.data().mark_sent();
+                serial.data().count_sent(len);
+                return true;
+            }
+            Err(e) => error!("Failed to send data: {e}"),
+        }
+    }
+    false
+}
+
+/// Sent for each chunk of raw bytes received on a port.
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct PortDataReceived {
+    /// Name of the port.
+    pub port_name: String,
+    /// Bytes as read from the port, before decoding.
+    pub data: Vec<u8>,
+}
+
+/// Receives data from serial ports and routes it to the port data manager.
+///
+/// Polls each serial port's receive channel for one message per frame and
+/// applies it with [`process_incoming`], followed by the messages of a log
+/// replay in progress. Received bytes are also sent as [`PortDataReceived`]
+/// messages.
+pub fn receive_serial_data(
+    ports: Query<&SerialIo>,
+    mut traffic: ResMut<TrafficLog>,
+    mut received: MessageWriter<PortDataReceived>,
+) {
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+
+        let Some(rx) = serial.rx_channel() else {
             continue;
         };
 
-        let data = serial.data().get_send_data();
-        if data.is_empty() {
-            continue;
+        let data = rx.try_recv().ok();
+        for data in data.into_iter().chain(serial.data().take_replayed()) {
+            if let PortChannelData::PortRead(read) = &data {
+                received.write(PortDataReceived {
+                    port_name: serial.set.port_name.clone(),
+                    data: read.data.clone(),
+                });
+            }
+            process_incoming(&mut serial, data, &mut traffic);
         }
+        serial.data().session_log_mut().flush_if_due();
+    }
+}
 
-        let file_data = data.join("\n");
-        let mut data_vec_u8: Vec<u8> = vec![];
-        for string in data {
-            let data_u8 = encode_string(&string, *serial.data().data_type());
-            data_vec_u8.extend(data_u8);
+/// Applies one message from a port thread to the port's state and data.
+///
+/// State changes open, close or fail the port. Received data and errors are
+/// written to the source file and the [`TrafficLog`] with appropriate source
+/// indicators; received data recognized as the echo of sent data only goes
+/// to the source file. Messages meant for the port thread or the discovery task are
+/// ignored.
+pub fn process_incoming(serial: &mut Serial, data: PortChannelData, traffic: &mut TrafficLog) {
+    match data {
+        PortChannelData::PortState(state) => match state {
+            PortState::Ready | PortState::Close => {
+                if state == PortState::Ready {
+                    serial.open();
+                    serial.data().set_last_error(None);
+                } else {
+                    flush_decoder(serial, traffic);
+                    serial.data().stop_binary_recording();
+                    serial.data().stop_session_recording();
+                    serial.close();
+                    serial.data().set_signal_state(None);
+                    serial.data().flow_monitor().reset();
+                }
+                serial.data().clear_send_data();
+            }
+            PortState::Error => {
+                flush_decoder(serial, traffic);
+                serial.data().stop_binary_recording();
+                serial.data().stop_session_recording();
+                serial.error();
+                serial.data().set_signal_state(None);
+                serial.data().flow_monitor().reset();
+            }
+        },
+        PortChannelData::PortRead(data) => {
+            let echo_len = serial.data().take_echo(&data.data, Instant::now());
+            serial.data().mark_received();
+            serial.data().count_received(data.data.len());
+            serial.data().record_raw(&data.data);
+            serial
+                .data()
+                .record_session_frame(SessionDirection::Rx, &data.data);
+            if serial.set.flow_control == FlowControl::Software {
+                serial
+                    .data()
+                    .flow_monitor()
+                    .scan_received(&data.data, Instant::now());
+            }
+            let (echo, rest) = data.data.split_at(echo_len);
+            for (part, is_echo) in [(echo, true), (rest, false)] {
+                if part.is_empty() {
+                    continue;
+                }
+                for record in serial.data().split_records(part) {
+                    receive_record(serial, &record, is_echo, traffic);
+                }
+            }
         }
-
-        // Write sent data to log file
-        // In console mode: skip local echo (terminal will echo back)
-        // In normal mode: write with Write source indicator
-        if !serial.data().is_console_mode() {
+        PortChannelData::PortError(data) => {
+            serial.error();
+            serial.data().count_error();
+            let message = String::from_utf8_lossy(&data.data).trim().to_string();
             serial
                 .data()
-                .write_source_file(file_data.as_bytes(), DataSource::Write);
+                .write_source_file(&data.data, DataSource::Error);
+            serial.data().set_last_error(Some(message));
+            traffic.record(&serial.set.port_name, DataSource::Error, &data.data);
         }
-
-        if serial.is_open()
-            && let Some(tx) = serial.tx_channel()
-            && let Err(e) = tx.send(PortChannelData::PortWrite(PortRwData { data: data_vec_u8 }))
-        {
-            error!("Failed to send data: {e}");
+        PortChannelData::SignalUpdate(signals) => {
+            serial.data().set_signal_state(Some(signals));
+            serial
+                .data()
+                .flow_monitor()
+                .update_cts(signals.cts, Instant::now());
         }
+        _ => {}
     }
 }
 
-/// Receives data from serial ports and routes it to the port data manager.
+/// Passes one received record, as split by the packet delimiter, to the
+/// receive buffer, the parsers and the log.
 ///
-/// Polls each serial port's receive channel for state changes, incoming data,
-/// and error messages. Updates the port state and writes received/error data
-/// to the source file with appropriate source indicators.
-pub fn receive_serial_data(mut serials: Query<&mut Serials>) {
-    let Ok(mut serials) = serials.single_mut() else {
-        return;
-    };
+/// An `echo` of sent data only goes to the log file.
+fn receive_record(serial: &mut Serial, record: &[u8], echo: bool, traffic: &mut TrafficLog) {
+    serial.data().push_received(record);
+    if let Some(parser) = serial.data().nmea_parser_mut() {
+        parser.process(record);
+    }
+    let was_gbk = serial.data().is_gbk_detected();
+    let processed_data = serial.data().decode_received(record);
+    if !was_gbk && serial.data().is_gbk_detected() {
+        info!(
+            "Auto-detected GBK encoding for port {}",
+            serial.set.port_name
+        );
+    }
+    if let Some(run) = serial.data().sequence_run_mut() {
+        run.feed(&processed_data);
+    }
+    if echo {
+        serial.data().write_echo(&processed_data);
+    } else {
+        serial.data().write_received(record, &processed_data);
+        if !processed_data.is_empty() {
+            traffic.record(&serial.set.port_name, DataSource::Read, &processed_data);
+        }
+    }
+}
 
-    for serial in &mut serials.serial {
-        let Ok(mut serial) = serial.lock() else {
-            continue;
+/// Writes any bytes still held by the packet delimiter and the stream
+/// decoder before the port stops receiving.
+fn flush_decoder(serial: &mut Serial, traffic: &mut TrafficLog) {
+    let record = serial.data().take_partial_record();
+    if !record.is_empty() {
+        receive_record(serial, &record, false, traffic);
+    }
+    let rest = serial.data().flush_utf8_buffer();
+    if !rest.is_empty() {
+        serial.data().write_received(&[], &rest);
+        traffic.record(&serial.set.port_name, DataSource::Read, &rest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::data_types::DataType;
+    use crate::serial::repeat::SendOptions;
+    use crate::serial::sim::SimulatedSerial;
+    use crate::serial::state::SignalState;
+    use crate::serial::status::spawn_ports;
+    use crate::serial::{DEFAULT_CHANNEL_BUFFER_SIZE, Serials};
+    use std::time::Instant;
+
+    /// Polls the port's receive channel until a message matches `predicate`.
+    fn recv_until(
+        serial: &mut Serial,
+        mut predicate: impl FnMut(&PortChannelData) -> bool,
+    ) -> Option<PortChannelData> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            if let Some(rx) = serial.rx_channel()
+                && let Ok(data) = rx.try_recv()
+                && predicate(&data)
+            {
+                return Some(data);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        None
+    }
+
+    #[test]
+    fn test_mock_port_thread_echoes_writes() {
+        let runtime = Runtime::init();
+        let mut serial = Serial::new_mock("mock://test", MockTraffic::Echo);
+        setup_serial_thread(&mut serial, &runtime, DEFAULT_CHANNEL_BUFFER_SIZE);
+
+        let settings = serial.set.clone();
+        let tx = serial.tx_channel().clone().expect("tx channel");
+        tx.send(PortChannelData::PortOpen(settings)).unwrap();
+        assert!(
+            recv_until(&mut serial, |data| matches!(
+                data,
+                PortChannelData::PortState(PortState::Ready)
+            ))
+            .is_some()
+        );
+
+        tx.send(PortChannelData::PortWrite(PortRwData {
+            data: b"ping".to_vec(),
+        }))
+        .unwrap();
+        match recv_until(&mut serial, |data| {
+            matches!(data, PortChannelData::PortRead(_))
+        }) {
+            Some(PortChannelData::PortRead(data)) => assert_eq!(data.data, b"ping"),
+            other => panic!("expected echoed data, got {other:?}"),
+        }
+
+        tx.send(PortChannelData::PortClose("mock://test".to_string()))
+            .unwrap();
+        assert!(
+            recv_until(&mut serial, |data| matches!(
+                data,
+                PortChannelData::PortState(PortState::Close)
+            ))
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn test_restarted_thread_retries_failed_open() {
+        let runtime = Runtime::init();
+        let mut serial = Serial::new();
+        serial.set.port_name = "/dev/serial_bevy_missing".to_string();
+        setup_serial_thread(&mut serial, &runtime, DEFAULT_CHANNEL_BUFFER_SIZE);
+        serial.request_open().unwrap();
+        let failed = |data: &PortChannelData| matches!(data, PortChannelData::PortError(_));
+        assert!(recv_until(&mut serial, failed).is_some());
+
+        // The failed open ended the thread; a new one answers the retry.
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !serial.thread_handle().as_ref().unwrap().is_finished() {
+            assert!(Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        restart_serial_thread(&mut serial, &runtime, DEFAULT_CHANNEL_BUFFER_SIZE);
+        assert!(!serial.thread_handle().as_ref().unwrap().is_finished());
+        serial.request_open().unwrap();
+        assert!(recv_until(&mut serial, failed).is_some());
+    }
+
+    #[test]
+    fn test_break_keeps_the_port_writing() {
+        let runtime = Runtime::init();
+        let mut serial = Serial::new_mock("mock://break", MockTraffic::Echo);
+        setup_serial_thread(&mut serial, &runtime, DEFAULT_CHANNEL_BUFFER_SIZE);
+        serial.request_open().unwrap();
+        assert!(
+            recv_until(&mut serial, |data| matches!(
+                data,
+                PortChannelData::PortState(PortState::Ready)
+            ))
+            .is_some()
+        );
+
+        // Virtual ports have no line; the break only delays later writes.
+        serial.send_break(20).unwrap();
+        let tx = serial.tx_channel().clone().expect("tx channel");
+        tx.send(PortChannelData::PortWrite(PortRwData {
+            data: b"sync".to_vec(),
+        }))
+        .unwrap();
+        match recv_until(&mut serial, |data| {
+            matches!(
+                data,
+                PortChannelData::PortRead(_) | PortChannelData::PortError(_)
+            )
+        }) {
+            Some(PortChannelData::PortRead(data)) => assert_eq!(data.data, b"sync"),
+            other => panic!("expected echoed data, got {other:?}"),
+        }
+    }
+
+    /// Runs on a paused clock, which only advances to the throttle's
+    /// deadlines, so the measured time is exact rather than wall-clock.
+    #[tokio::test(start_paused = true)]
+    async fn test_send_rate_limit_paces_writes() {
+        let (_tx, mut rx) = broadcast::channel(4);
+        let mut written = Vec::new();
+        let mut throttle = WriteThrottle::new(Some(100));
+        let start = tokio::time::Instant::now();
+        write_throttled(
+            &mut written,
+            &[0x55; 100],
+            &mut throttle,
+            &mut rx,
+            &mut VecDeque::new(),
+        )
+        .await
+        .unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(written, [0x55; 100]);
+        assert!(
+            (Duration::from_millis(999)..=Duration::from_millis(1001)).contains(&elapsed),
+            "100 bytes at 100 B/s took {elapsed:?}"
+        );
+
+        let start = tokio::time::Instant::now();
+        let mut throttle = WriteThrottle::new(None);
+        write_throttled(
+            &mut written,
+            &[0; 4096],
+            &mut throttle,
+            &mut rx,
+            &mut VecDeque::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_close_stops_throttled_write() {
+        let (tx, mut rx) = broadcast::channel(4);
+        let mut written = Vec::new();
+        let mut pending = VecDeque::new();
+        let mut throttle = WriteThrottle::new(Some(100));
+        tx.send(PortChannelData::PortClose("COM1".to_string()))
+            .unwrap();
+        write_throttled(
+            &mut written,
+            &[0; 100],
+            &mut throttle,
+            &mut rx,
+            &mut pending,
+        )
+        .await
+        .unwrap();
+        assert_eq!(written.len(), 10);
+        assert!(matches!(
+            pending.pop_front(),
+            Some(PortChannelData::PortClose(_))
+        ));
+    }
+
+    impl PortBackend for tokio::io::DuplexStream {}
+
+    /// Collects the frames a read thread forwards from a device writing
+    /// `bursts`, each after its delay, until the device hangs up.
+    async fn read_frames(frame_gap: Option<Duration>, bursts: Vec<(u64, Vec<u8>)>) -> Vec<Vec<u8>> {
+        let (port, mut device) = tokio::io::duplex(8192);
+        let (tx, _) = broadcast::channel(4);
+        let (tx1, mut rx1) = broadcast::channel(64);
+        let handle = spawn_read_thread(
+            SharedBackend::new(Box::new(port)),
+            tx1,
+            tx.subscribe(),
+            "COM1",
+            frame_gap,
+        );
+        for (delay_ms, data) in bursts {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            device.write_all(&data).await.unwrap();
+        }
+        drop(device);
+        handle.await.unwrap().unwrap();
+
+        let mut frames = Vec::new();
+        while let Ok(message) = rx1.try_recv() {
+            if let PortChannelData::PortRead(data) = message {
+                frames.push(data.data);
+            }
+        }
+        frames
+    }
+
+    #[tokio::test]
+    async fn test_frame_gap_splits_reads_at_silence() {
+        let bursts = vec![
+            (0, vec![0x01, 0x03]),
+            (5, vec![0x00]),
+            (300, vec![0x02, 0x04]),
+            (300, vec![0xFF]),
+        ];
+        let frames = read_frames(Some(Duration::from_millis(100)), bursts).await;
+        assert_eq!(
+            frames,
+            [vec![0x01, 0x03, 0x00], vec![0x02, 0x04], vec![0xFF]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reads_are_forwarded_as_is_without_frame_gap() {
+        let bursts = vec![(0, b"AB".to_vec()), (100, b"C".to_vec())];
+        let frames = read_frames(None, bursts).await;
+        assert_eq!(frames, [b"AB".to_vec(), b"C".to_vec()]);
+    }
+
+    #[test]
+    fn test_frame_assembler_splits_long_frames() {
+        let mut frames = FrameAssembler::new(Some(Duration::from_millis(10)));
+        assert!(frames.push(&[1; MAX_FRAME_SIZE - 1]).is_empty());
+        assert!(frames.deadline.is_some());
+        let full = frames.push(&[2; MAX_FRAME_SIZE + 2]);
+        assert_eq!(full.len(), 2);
+        assert!(full.iter().all(|frame| frame.len() == MAX_FRAME_SIZE));
+        assert_eq!(frames.take(), [2; 1]);
+        assert!(frames.deadline.is_none());
+    }
+
+    /// Returns an open port whose port thread is replaced by a channel, and
+    /// the receiving end of that channel.
+    fn open_with_channel() -> (Serial, broadcast::Receiver<PortChannelData>) {
+        let mut serial = Serial::new();
+        serial.set.port_name = "COM1".to_string();
+        let (tx, rx) = broadcast::channel(DEFAULT_CHANNEL_BUFFER_SIZE);
+        *serial.tx_channel() = Some(tx);
+        serial.open();
+        (serial, rx)
+    }
+
+    /// Returns the data of the write requests sent to the port thread.
+    fn written(rx: &mut broadcast::Receiver<PortChannelData>) -> Vec<Vec<u8>> {
+        std::iter::from_fn(|| match rx.try_recv() {
+            Ok(PortChannelData::PortWrite(data)) => Some(data.data),
+            Ok(other) => panic!("unexpected message {other:?}"),
+            Err(_) => None,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_process_outgoing_sends_packets_then_text() {
+        let (mut serial, mut rx) = open_with_channel();
+        let mut traffic = TrafficLog::default();
+        serial.data().send_packet(vec![0x01, 0xFF]);
+        serial.data().send_data("AT".to_string());
+        serial.data().send_data("ATI".to_string());
+
+        let sent = process_outgoing(&mut serial, &mut traffic);
+        assert_eq!(sent.as_deref(), Some(&b"\x01\xFFATATI"[..]));
+        assert_eq!(
+            written(&mut rx),
+            [vec![0x01, 0xFF], b"AT".to_vec(), b"ATI".to_vec()]
+        );
+        assert_eq!(serial.data().tx_bytes(), 7);
+        assert!(serial.data().last_tx_instant().is_some());
+        let logged: Vec<_> = traffic.entries().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(logged, ["01ff", "AT", "ATI"]);
+
+        // The queues were drained.
+        assert_eq!(process_outgoing(&mut serial, &mut traffic), None);
+        assert!(written(&mut rx).is_empty());
+    }
+
+    #[test]
+    fn test_repeats_are_written_at_their_interval() {
+        let (mut serial, mut rx) = open_with_channel();
+        let mut traffic = TrafficLog::default();
+        let options = SendOptions {
+            repeat_count: Some(3),
+            repeat_interval: Some(Duration::from_millis(100)),
         };
+        serial
+            .data()
+            .send_data_with_options("AT".to_string(), options);
 
-        let Some(rx) = serial.rx_channel() else {
-            continue;
+        let mut sent = Vec::new();
+        for _ in 0..10 {
+            process_outgoing(&mut serial, &mut traffic);
+            sent.extend(written(&mut rx));
+            serial.data().advance_repeat(Duration::from_millis(50));
+        }
+        assert_eq!(sent, [b"AT".to_vec(), b"AT".to_vec(), b"AT".to_vec()]);
+        assert!(serial.data().repeat_schedule().is_none());
+    }
+
+    #[test]
+    fn test_process_outgoing_drops_invalid_hex() {
+        let (mut serial, mut rx) = open_with_channel();
+        let mut traffic = TrafficLog::default();
+        *serial.data().data_type() = DataType::Hex;
+        *serial.data().strict_hex() = true;
+        serial.data().send_data("zz".to_string());
+        serial.data().send_data("0A 0B".to_string());
+
+        assert_eq!(
+            process_outgoing(&mut serial, &mut traffic),
+            Some(vec![0x0A, 0x0B])
+        );
+        assert_eq!(written(&mut rx), [vec![0x0A, 0x0B]]);
+        assert_eq!(traffic.len(), 1);
+    }
+
+    #[test]
+    fn test_queued_data_keeps_its_encoding() {
+        let (mut serial, mut rx) = open_with_channel();
+        let mut traffic = TrafficLog::default();
+        *serial.data().data_type() = DataType::Hex;
+        serial.data().send_data("0A 0B".to_string());
+        *serial.data().data_type() = DataType::Utf8;
+        serial.data().send_data("0A".to_string());
+
+        process_outgoing(&mut serial, &mut traffic);
+        assert_eq!(written(&mut rx), [vec![0x0A, 0x0B], b"0A".to_vec()]);
+        let logged: Vec<_> = traffic.entries().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(logged, ["0A 0B", "0A"]);
+    }
+
+    #[test]
+    fn test_process_outgoing_logs_but_does_not_send_on_closed_port() {
+        let (mut serial, mut rx) = open_with_channel();
+        let mut traffic = TrafficLog::default();
+        serial.close();
+        serial.data().send_data("AT".to_string());
+
+        assert_eq!(process_outgoing(&mut serial, &mut traffic), None);
+        assert!(written(&mut rx).is_empty());
+        assert_eq!(traffic.len(), 1);
+        assert_eq!(serial.data().tx_bytes(), 0);
+    }
+
+    #[test]
+    fn test_process_incoming_applies_state_changes() {
+        let mut serial = Serial::new();
+        let mut traffic = TrafficLog::default();
+        serial.data().set_last_error(Some("old".to_string()));
+
+        process_incoming(
+            &mut serial,
+            PortChannelData::PortState(PortState::Ready),
+            &mut traffic,
+        );
+        assert!(serial.is_open());
+        assert_eq!(serial.data().last_error(), None);
+
+        process_incoming(
+            &mut serial,
+            PortChannelData::PortState(PortState::Error),
+            &mut traffic,
+        );
+        assert!(serial.is_error());
+
+        process_incoming(
+            &mut serial,
+            PortChannelData::PortState(PortState::Close),
+            &mut traffic,
+        );
+        assert!(serial.is_close());
+        assert!(traffic.is_empty());
+    }
+
+    #[test]
+    fn test_process_incoming_records_reads_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut serial = Serial::new();
+        serial.set.port_name = "COM1".to_string();
+        serial
+            .data()
+            .session_log_mut()
+            .set_config(&crate::serial::log::LogConfig {
+                base_dir: dir.path().to_path_buf(),
+                max_file_size: None,
+            });
+        *serial.data().show_timestamp() = true;
+        serial.start_session_log();
+        let mut traffic = TrafficLog::default();
+        serial.open();
+
+        process_incoming(
+            &mut serial,
+            PortChannelData::PortRead(PortRwData {
+                data: b"OK\r\n".to_vec(),
+            }),
+            &mut traffic,
+        );
+        assert_eq!(serial.data().rx_bytes(), 4);
+        assert!(serial.data().last_rx_instant().is_some());
+        assert_eq!(
+            serial
+                .data()
+                .ring_buffer()
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            b"OK\r\n"
+        );
+        let entry = traffic.entries().next().unwrap();
+        assert_eq!(
+            (entry.port_name.as_str(), entry.source),
+            ("COM1", DataSource::Read)
+        );
+
+        process_incoming(
+            &mut serial,
+            PortChannelData::PortError(PortRwData {
+                data: b"device unplugged\n".to_vec(),
+            }),
+            &mut traffic,
+        );
+        assert!(serial.is_error());
+        assert_eq!(serial.data().last_error(), Some("device unplugged"));
+        assert!(serial.data().last_error_at().is_some());
+        let lines = serial.data().recent_error_lines();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(" E]device unplugged"));
+        assert_eq!(traffic.entries().last().unwrap().source, DataSource::Error);
+    }
+
+    #[test]
+    fn test_process_incoming_splits_records_at_delimiter() {
+        use crate::serial::delimiter::PacketDelimiter;
+
+        let mut serial = Serial::new();
+        let mut traffic = TrafficLog::default();
+        serial.open();
+        serial
+            .data()
+            .set_packet_delimiter(PacketDelimiter::Bytes(vec![0x0D, 0x0A]));
+
+        for read in [b"OK\r".as_slice(), b"\nREA", b"DY\r\nTE"] {
+            process_incoming(
+                &mut serial,
+                PortChannelData::PortRead(PortRwData {
+                    data: read.to_vec(),
+                }),
+                &mut traffic,
+            );
+        }
+        assert_eq!(serial.data().rx_bytes(), 14);
+        assert_eq!(
+            serial
+                .data()
+                .ring_buffer()
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            b"OK\r\nREADY\r\n"
+        );
+        assert_eq!(traffic.entries().count(), 2);
+
+        // The incomplete record is passed on when the port closes.
+        process_incoming(
+            &mut serial,
+            PortChannelData::PortState(PortState::Close),
+            &mut traffic,
+        );
+        assert_eq!(serial.data().ring_buffer().len(), 13);
+        assert_eq!(traffic.entries().count(), 3);
+    }
+
+    #[test]
+    fn test_process_incoming_ignores_requests() {
+        let mut serial = Serial::new();
+        let mut traffic = TrafficLog::default();
+        serial.open();
+        let data = PortRwData {
+            data: b"AT".to_vec(),
         };
+        for message in [
+            PortChannelData::PortName(vec!["COM1".to_string()]),
+            PortChannelData::PortSerialNumbers(vec![]),
+            PortChannelData::PortWrite(data.clone()),
+            PortChannelData::ProxyWrite(data),
+            PortChannelData::PortOpen(PortSettings::default()),
+            PortChannelData::PortReconfigure(PortSettings::default()),
+            PortChannelData::SendBreak { duration_ms: 10 },
+            PortChannelData::PortClose(String::new()),
+            PortChannelData::RequestScan,
+        ] {
+            process_incoming(&mut serial, message, &mut traffic);
+        }
+        assert!(serial.is_open());
+        assert_eq!(serial.data().rx_bytes(), 0);
+        assert!(traffic.is_empty());
+    }
 
-        if let Ok(data) = rx.try_recv() {
-            match data {
-                PortChannelData::PortState(state) => match state {
-                    PortState::Ready | PortState::Close => {
-                        if state == PortState::Ready {
-                            serial.open();
-                        } else {
-                            serial.close();
-                            serial.data().clear_utf8_buffer();
-                        }
-                        serial.data().clear_send_data();
-                    }
-                    PortState::Error => {
-                        serial.error();
-                        serial.data().clear_utf8_buffer();
-                    }
-                },
-                PortChannelData::PortRead(data) => {
-                    let processed_data = if *serial.data().data_type() == DataType::Utf8 {
-                        serial.data().process_raw_bytes(&data.data)
-                    } else {
-                        data.data.clone()
-                    };
+    #[test]
+    fn test_signal_update_is_stored() {
+        let mut serial = Serial::new();
+        let signals = SignalState {
+            cts: true,
+            dsr: false,
+            dcd: true,
+            ri: false,
+        };
+        process_incoming(
+            &mut serial,
+            PortChannelData::SignalUpdate(signals),
+            &mut TrafficLog::default(),
+        );
+        assert_eq!(serial.data().signal_state(), Some(signals));
 
-                    serial
-                        .data()
-                        .write_source_file(&processed_data, DataSource::Read);
-                }
-                PortChannelData::PortError(data) => {
-                    serial.error();
-                    serial
-                        .data()
-                        .write_source_file(&data.data, DataSource::Error);
-                }
-                _ => {}
+        process_incoming(
+            &mut serial,
+            PortChannelData::PortState(PortState::Close),
+            &mut TrafficLog::default(),
+        );
+        assert_eq!(serial.data().signal_state(), None);
+    }
+
+    #[test]
+    fn test_mock_port_thread_reports_signals() {
+        let runtime = Runtime::init();
+        let mut serial = Serial::new_mock("mock://test", MockTraffic::Echo);
+        setup_serial_thread(&mut serial, &runtime, DEFAULT_CHANNEL_BUFFER_SIZE);
+
+        let settings = serial.set.clone();
+        let tx = serial.tx_channel().clone().expect("tx channel");
+        tx.send(PortChannelData::PortOpen(settings)).unwrap();
+        match recv_until(&mut serial, |data| {
+            matches!(data, PortChannelData::SignalUpdate(_))
+        }) {
+            Some(PortChannelData::SignalUpdate(signals)) => {
+                assert!(signals.cts && signals.dsr && signals.dcd && !signals.ri);
             }
+            other => panic!("expected signal update, got {other:?}"),
         }
     }
+
+    #[test]
+    fn test_failed_port_is_reconnected_after_delay() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut serial = Serial::new_mock("mock://flaky", MockTraffic::Echo);
+        serial.error();
+        serial
+            .data()
+            .set_last_error(Some("device disconnected".to_string()));
+        let mut serials = Serials::new();
+        serials.add(serial);
+        let mut world = World::new();
+        spawn_ports(&mut world, serials);
+        world.insert_resource(Runtime::init());
+        world.init_resource::<Time>();
+        world.insert_resource(SerialConfig {
+            reconnect_on_error: true,
+            ..SerialConfig::default()
+        });
+
+        let is_error = |world: &mut World| {
+            let mut query = world.query::<&Serials>();
+            query
+                .single(world)
+                .unwrap()
+                .get(0)
+                .lock()
+                .unwrap()
+                .is_error()
+        };
+        world.run_system_once(reconnect_failed_ports).unwrap();
+        assert!(is_error(&mut world));
+
+        // Each `run_system_once` starts with a fresh `Local`, so the whole
+        // delay passes in one frame.
+        world.resource_mut::<Time>().advance_by(RECONNECT_DELAY);
+        world.run_system_once(reconnect_failed_ports).unwrap();
+        assert!(!is_error(&mut world));
+
+        let mut query = world.query::<&Serials>();
+        let serials = query.single(&world).unwrap();
+        let mut serial = serials.get(0).lock().unwrap();
+        assert_eq!(serial.data().last_error(), None);
+        let ready = recv_until(&mut serial, |data| {
+            matches!(data, PortChannelData::PortState(PortState::Ready))
+        });
+        assert!(ready.is_some());
+    }
+
+    #[test]
+    fn test_poisoned_failed_port_is_still_reconnected() {
+        use crate::serial::snapshot::poison;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut serial = Serial::new_mock("mock://flaky", MockTraffic::Echo);
+        serial.error();
+        let mut serials = Serials::new();
+        serials.add(serial);
+        let handle = Arc::clone(&serials.serial[0]);
+        let mut world = World::new();
+        spawn_ports(&mut world, serials);
+        world.insert_resource(Runtime::init());
+        world.init_resource::<Time>();
+        world.insert_resource(SerialConfig {
+            reconnect_on_error: true,
+            ..SerialConfig::default()
+        });
+        poison(&handle);
+
+        world.resource_mut::<Time>().advance_by(RECONNECT_DELAY);
+        world.run_system_once(reconnect_failed_ports).unwrap();
+        assert!(!handle.is_poisoned());
+        assert!(!lock_serial(&handle).is_error());
+    }
+
+    /// Opens a port thread for `serial` and waits until it reports Ready.
+    fn open_for_test(serial: &mut Serial, runtime: &Runtime) {
+        setup_serial_thread(serial, runtime, DEFAULT_CHANNEL_BUFFER_SIZE);
+        let settings = serial.set.clone();
+        let tx = serial.tx_channel().clone().expect("tx channel");
+        tx.send(PortChannelData::PortOpen(settings)).unwrap();
+        let ready = recv_until(serial, |data| {
+            matches!(data, PortChannelData::PortState(PortState::Ready))
+        });
+        process_incoming(
+            serial,
+            ready.expect("port ready"),
+            &mut TrafficLog::default(),
+        );
+    }
+
+    /// Opens a port thread for `serial` on a [`Runtime::paused`] runtime,
+    /// whose tasks only run inside `block_on`, and waits until it is Ready.
+    fn open_paused_for_test(serial: &mut Serial, runtime: &Runtime) {
+        setup_serial_thread(serial, runtime, DEFAULT_CHANNEL_BUFFER_SIZE);
+        let settings = serial.set.clone();
+        let tx = serial.tx_channel().clone().expect("tx channel");
+        let mut rx = serial
+            .rx_channel()
+            .as_ref()
+            .expect("rx channel")
+            .resubscribe();
+        tx.send(PortChannelData::PortOpen(settings)).unwrap();
+        let ready = runtime.block_on(async {
+            loop {
+                match rx.recv().await {
+                    Ok(data @ PortChannelData::PortState(PortState::Ready)) => break data,
+                    Ok(_) => {}
+                    Err(e) => panic!("port thread stopped: {e}"),
+                }
+            }
+        });
+        process_incoming(serial, ready, &mut TrafficLog::default());
+    }
+
+    #[test]
+    fn test_measure_latency_with_loopback() {
+        let latency = Duration::from_millis(20);
+        let sim = SimulatedSerial::new("sim://loopback", vec![], Some).with_latency(latency);
+        let runtime = Runtime::paused();
+        let mut serial = Serial::new_mock("sim://loopback", MockTraffic::Simulated(sim));
+        open_paused_for_test(&mut serial, &runtime);
+
+        let measured = runtime
+            .block_on(serial.measure_latency(&runtime))
+            .unwrap()
+            .unwrap();
+        // The clock is paused, so only the simulated delay counts; Tokio
+        // timers may round it up to the next millisecond.
+        assert!(measured >= latency, "measured {measured:?}");
+        assert!(
+            measured <= latency + Duration::from_millis(1),
+            "measured {measured:?}"
+        );
+    }
+
+    #[test]
+    fn test_measure_latency_times_out_without_echo() {
+        let runtime = Runtime::paused();
+        let sim = SimulatedSerial::canned("sim://silent", vec![]);
+        let mut serial = Serial::new_mock("sim://silent", MockTraffic::Simulated(sim));
+        open_paused_for_test(&mut serial, &runtime);
+
+        let result = runtime.block_on(serial.measure_latency(&runtime)).unwrap();
+        assert!(matches!(result, Err(SerialBevyError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_measure_throughput_with_loopback() {
+        let sim = SimulatedSerial::new("sim://loopback", vec![], Some);
+        let runtime = Runtime::init();
+        let mut serial = Serial::new_mock("sim://loopback", MockTraffic::Simulated(sim));
+        open_for_test(&mut serial, &runtime);
+
+        let result = runtime
+            .block_on(serial.measure_throughput(&runtime, 32, 50))
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.drop_count, 0);
+        assert!(result.actual_bps > 0.0, "{result:?}");
+    }
+
+    #[test]
+    fn test_measure_throughput_times_out_without_echo() {
+        let runtime = Runtime::init();
+        let sim = SimulatedSerial::canned("sim://silent", vec![]);
+        let mut serial = Serial::new_mock("sim://silent", MockTraffic::Simulated(sim));
+        open_for_test(&mut serial, &runtime);
+
+        let result = runtime
+            .block_on(serial.measure_throughput(&runtime, 16, 4))
+            .unwrap();
+        assert!(matches!(result, Err(SerialBevyError::Timeout(_))));
+    }
+
+    #[test]
+    fn test_measure_latency_requires_open_port() {
+        let runtime = Runtime::init();
+        let serial = Serial::new();
+        let result = runtime.block_on(serial.measure_latency(&runtime)).unwrap();
+        assert!(result.is_err());
+    }
 }
@@ -1,6 +1,64 @@
 //! # LLM Module
 //!
 //! LLM configuration and message types for AI features.
+//!
+//! Requests use the OpenAI-compatible chat completions format, so any
+//! compatible endpoint (e.g. a local Ollama server) can be used. The
+//! Anthropic Messages API is supported as well, see [`LlmProvider`].
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::task::JoinHandle;
+
+use super::log::sanitize_port_name;
+use crate::error::{Result, SerialBevyError};
+
+/// Default chat completions endpoint.
+pub const DEFAULT_BASE_URL: &str = "https://open.bigmodel.cn/api/paas/v4/chat/completions";
+
+/// Chat completions endpoint of the coding plan, used instead of
+/// [`DEFAULT_BASE_URL`] when the coding plan is enabled.
+pub const CODING_PLAN_BASE_URL: &str =
+    "https://open.bigmodel.cn/api/coding/paas/v4/chat/completions";
+
+/// Chat completions endpoint of OpenAI.
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+/// Messages endpoint of Anthropic.
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1/messages";
+
+/// OpenAI-compatible chat completions endpoint of a local Ollama server.
+pub const OLLAMA_BASE_URL: &str = "http://localhost:11434/v1/chat/completions";
+
+/// Version of the Anthropic API requests are written for.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Default sampling temperature.
+pub const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+/// Supported sampling temperature range.
+pub const TEMPERATURE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+
+/// Default maximum number of tokens in a response.
+pub const DEFAULT_MAX_TOKENS: u32 = 2048;
+
+/// Default number of recently received bytes sent along with a query.
+pub const DEFAULT_CONTEXT_WINDOW_BYTES: usize = 4096;
+
+/// Default number of conversation characters sent along with a query.
+pub const DEFAULT_HISTORY_BUDGET_CHARS: usize = 16_000;
+
+/// Directory conversations are saved to.
+pub const CONVERSATION_DIR: &str = "config";
+
+/// Directory Markdown transcripts of conversations are saved to.
+pub const TRANSCRIPT_DIR: &str = "logs/llm";
 
 /// Available text models for AI chat.
 pub const TEXT_MODELS: &[(&str, &str)] = &[
@@ -18,7 +76,84 @@ pub const TEXT_MODELS: &[(&str, &str)] = &[
     ("glm-4.5-airx", "GLM-4.5-AirX"),
 ];
 
+/// API an LLM request is sent to, which decides the request format and
+/// authentication.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LlmProvider {
+    /// Zhipu AI (BigModel), OpenAI-compatible.
+    #[default]
+    ZhipuAI,
+    /// OpenAI chat completions.
+    OpenAI,
+    /// Anthropic Messages API.
+    Anthropic,
+    /// Local Ollama server through its OpenAI-compatible endpoint, with the
+    /// name of the local model.
+    OllamaLocal(String),
+}
+
+impl LlmProvider {
+    /// Returns the name shown in the provider selector.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::ZhipuAI => "Zhipu AI",
+            Self::OpenAI => "OpenAI",
+            Self::Anthropic => "Anthropic",
+            Self::OllamaLocal(_) => "Ollama (local)",
+        }
+    }
+
+    /// Returns the endpoint requests are sent to by default.
+    #[must_use]
+    pub const fn default_base_url(&self) -> &'static str {
+        match self {
+            Self::ZhipuAI => DEFAULT_BASE_URL,
+            Self::OpenAI => OPENAI_BASE_URL,
+            Self::Anthropic => ANTHROPIC_BASE_URL,
+            Self::OllamaLocal(_) => OLLAMA_BASE_URL,
+        }
+    }
+
+    /// Returns true if requests need an API key.
+    #[must_use]
+    pub const fn needs_key(&self) -> bool {
+        !matches!(self, Self::OllamaLocal(_))
+    }
+
+    /// Returns the model to request: the local model for Ollama, `model`
+    /// otherwise.
+    #[must_use]
+    pub fn model<'a>(&'a self, model: &'a str) -> &'a str {
+        match self {
+            Self::OllamaLocal(local) if !local.is_empty() => local,
+            _ => model,
+        }
+    }
+
+    /// Returns the authentication headers of a request with `key`: a bearer
+    /// token, or `x-api-key` and the API version for Anthropic. An empty key
+    /// sends no key.
+    #[must_use]
+    pub fn request_headers(&self, key: &str) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        if matches!(self, Self::Anthropic) {
+            headers.insert(
+                "anthropic-version".to_string(),
+                ANTHROPIC_VERSION.to_string(),
+            );
+            if !key.is_empty() {
+                headers.insert("x-api-key".to_string(), key.to_string());
+            }
+        } else if !key.is_empty() {
+            headers.insert("Authorization".to_string(), format!("Bearer {key}"));
+        }
+        headers
+    }
+}
+
 /// LLM configuration for AI features (per-serial state).
+#[derive(Serialize)]
 pub struct LlmConfig {
     /// Whether LLM features are enabled for this serial port.
     pub enable: bool,
@@ -31,6 +166,37 @@ pub struct LlmConfig {
     /// Whether the request has already been dispatched to async runtime.
     /// Prevents spawning duplicate requests every frame.
     pub request_in_flight: bool,
+    /// API the requests are sent to.
+    pub provider: LlmProvider,
+    /// Chat completions endpoint URL; empty uses the global one.
+    pub base_url: String,
+    /// Sampling temperature, within [`TEMPERATURE_RANGE`].
+    pub temperature: f32,
+    /// Maximum number of tokens in a response.
+    pub max_tokens: u32,
+    /// Whether recently received serial data is sent along with a query.
+    /// Off by default, so nothing from the device leaves the machine unless
+    /// the user opts in.
+    pub include_recent_data: bool,
+    /// Maximum number of recently received bytes sent along with a query.
+    pub context_window_bytes: usize,
+    /// Maximum number of conversation characters sent along with a query;
+    /// older messages beyond it are left out.
+    pub history_budget_chars: usize,
+    /// Response text streamed so far by the request in flight.
+    #[serde(skip)]
+    pub streaming_response: Arc<Mutex<String>>,
+    /// Task of the request in flight, aborted by [`LlmConfig::stop`].
+    #[serde(skip)]
+    pub request_task: Option<JoinHandle<()>>,
+    /// Error of the last failed request, shown until dismissed or the next
+    /// request is sent.
+    #[serde(skip)]
+    pub last_error: Option<String>,
+    /// Saved transcript shown read-only instead of the conversation: its
+    /// path and Markdown text.
+    #[serde(skip)]
+    pub viewed_transcript: Option<(PathBuf, String)>,
 }
 
 impl Default for LlmConfig {
@@ -49,7 +215,149 @@ impl LlmConfig {
             input_buffer: String::new(),
             is_processing: false,
             request_in_flight: false,
+            provider: LlmProvider::default(),
+            base_url: String::new(),
+            temperature: DEFAULT_TEMPERATURE,
+            max_tokens: DEFAULT_MAX_TOKENS,
+            include_recent_data: false,
+            context_window_bytes: DEFAULT_CONTEXT_WINDOW_BYTES,
+            history_budget_chars: DEFAULT_HISTORY_BUDGET_CHARS,
+            streaming_response: Arc::default(),
+            request_task: None,
+            last_error: None,
+            viewed_transcript: None,
+        }
+    }
+
+    /// Clears the streamed response and returns a handle for the request task
+    /// to append tokens to.
+    pub fn start_streaming(&mut self) -> Arc<Mutex<String>> {
+        if let Ok(mut text) = self.streaming_response.lock() {
+            text.clear();
+        }
+        Arc::clone(&self.streaming_response)
+    }
+
+    /// Returns the response text streamed so far.
+    #[must_use]
+    pub fn streaming_text(&self) -> String {
+        self.streaming_response
+            .lock()
+            .map(|text| text.clone())
+            .unwrap_or_default()
+    }
+
+    /// Marks the request in flight as finished and clears the streamed response.
+    pub fn finish_request(&mut self) {
+        self.is_processing = false;
+        self.request_in_flight = false;
+        self.request_task = None;
+        if let Ok(mut text) = self.streaming_response.lock() {
+            text.clear();
+        }
+    }
+
+    /// Aborts the request in flight, keeping the text streamed so far as the
+    /// assistant's answer.
+    pub fn stop(&mut self) {
+        if let Some(task) = self.request_task.take() {
+            task.abort();
+        }
+        let partial = self.streaming_text();
+        if !partial.is_empty() {
+            self.add_assistant_message(&partial);
+        }
+        self.finish_request();
+    }
+
+    /// Marks the request in flight as failed with `error`, keeping the text
+    /// streamed so far as the assistant's answer.
+    pub fn fail_request(&mut self, error: impl Into<String>) {
+        let partial = self.streaming_text();
+        if !partial.is_empty() {
+            self.add_assistant_message(&partial);
         }
+        self.finish_request();
+        self.last_error = Some(error.into());
+    }
+
+    /// Sets the sampling temperature, clamped to [`TEMPERATURE_RANGE`].
+    pub fn set_temperature(&mut self, temperature: f32) {
+        self.temperature = if temperature.is_nan() {
+            DEFAULT_TEMPERATURE
+        } else {
+            temperature.clamp(*TEMPERATURE_RANGE.start(), *TEMPERATURE_RANGE.end())
+        };
+    }
+
+    /// Returns the messages to send: the conversation so far, preceded by a
+    /// system message with `recent_data` if recent data is included.
+    ///
+    /// `recent_data` is truncated from the beginning to
+    /// [`LlmConfig::context_window_bytes`], and the conversation to its most
+    /// recent messages within [`LlmConfig::history_budget_chars`].
+    #[must_use]
+    pub fn current_messages(&self, recent_data: &str) -> Vec<LlmMessage> {
+        let recent_data = truncate_front(recent_data, self.context_window_bytes);
+        let history = self.recent_history();
+        let mut messages = Vec::with_capacity(history.len() + 1);
+        if self.include_recent_data && !recent_data.is_empty() {
+            messages.push(LlmMessage::system(format!(
+                "Recent serial data: {recent_data}"
+            )));
+        }
+        messages.extend(history.iter().cloned());
+        messages
+    }
+
+    /// Returns the most recent messages whose content fits in
+    /// [`LlmConfig::history_budget_chars`]. The last message is always
+    /// included.
+    #[must_use]
+    pub fn recent_history(&self) -> &[LlmMessage] {
+        let mut chars = 0;
+        let mut start = self.messages.len();
+        for (index, message) in self.messages.iter().enumerate().rev() {
+            chars += message.content.chars().count();
+            if chars > self.history_budget_chars && start < self.messages.len() {
+                break;
+            }
+            start = index;
+        }
+        &self.messages[start..]
+    }
+
+    /// Builds the JSON body of a chat request for `model` with the conversation
+    /// so far, see [`LlmConfig::current_messages`].
+    #[must_use]
+    pub fn request_body(&self, model: &str, recent_data: &str) -> serde_json::Value {
+        self.build_request_body(model, &self.current_messages(recent_data))
+    }
+
+    /// Builds the JSON body of a request for `model` with `messages` in the
+    /// format of the [`LlmConfig::provider`].
+    #[must_use]
+    pub fn build_request_body(&self, model: &str, messages: &[LlmMessage]) -> serde_json::Value {
+        let model = self.provider.model(model);
+        match self.provider {
+            LlmProvider::Anthropic => {
+                anthropic_request_body(model, messages, self.temperature, self.max_tokens)
+            }
+            _ => chat_request_body(model, messages, self.temperature, self.max_tokens),
+        }
+    }
+
+    /// Returns the authentication headers of a request with `key`, see
+    /// [`LlmProvider::request_headers`].
+    #[must_use]
+    pub fn build_request_headers(&self, key: &str) -> HashMap<String, String> {
+        self.provider.request_headers(key)
+    }
+
+    /// Sets the provider and sends requests to its default endpoint.
+    pub fn set_provider(&mut self, provider: LlmProvider) {
+        self.base_url = provider.default_base_url().to_string();
+        self.provider = provider;
     }
 
     /// Gets a mutable reference to the enable flag.
@@ -77,10 +385,242 @@ impl LlmConfig {
     pub fn has_messages(&self) -> bool {
         !self.messages.is_empty()
     }
+
+    /// Saves the conversation as newline-delimited JSON, one message per line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save_conversation(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        for message in &self.messages {
+            serde_json::to_writer(&mut writer, message)
+                .map_err(|e| SerialBevyError::serialization(e.to_string()))?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Replaces the conversation with the one saved in `path`.
+    ///
+    /// Blank lines are skipped. The conversation is left unchanged on error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or a line is not a valid
+    /// message.
+    pub fn load_conversation(&mut self, path: &Path) -> Result<()> {
+        let text = fs::read_to_string(path)?;
+        let messages = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    SerialBevyError::serialization(format!("{}:{}: {e}", path.display(), index + 1))
+                })
+            })
+            .collect::<Result<Vec<LlmMessage>>>()?;
+        self.messages = messages;
+        Ok(())
+    }
+
+    /// Formats the conversation as a Markdown transcript titled with `port_name`.
+    #[must_use]
+    pub fn transcript(&self, port_name: &str) -> String {
+        let mut text = format!("# LLM conversation: {port_name}\n");
+        for message in &self.messages {
+            let role = match message.role.as_str() {
+                "user" => "User",
+                "assistant" => "Assistant",
+                "system" => "System",
+                other => other,
+            };
+            text.push_str(&format!(
+                "\n## {role} ({})\n\n{}\n",
+                message.timestamp,
+                message.content.trim_end()
+            ));
+        }
+        text
+    }
+
+    /// Saves the conversation as a Markdown transcript, see
+    /// [`LlmConfig::transcript`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save_transcript(&self, path: &Path, port_name: &str) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.transcript(port_name))?;
+        Ok(())
+    }
+
+    /// Opens a saved transcript read-only, in place of the conversation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn view_transcript(&mut self, path: &Path) -> Result<()> {
+        let text = fs::read_to_string(path)?;
+        self.viewed_transcript = Some((path.to_path_buf(), text));
+        Ok(())
+    }
+
+    /// Clears the conversation and deletes its saved file, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be deleted.
+    pub fn clear_history(&mut self, path: &Path) -> Result<()> {
+        self.clear_messages();
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the file the conversation of a port is saved to:
+/// `config/llm_<port_name>.jsonl`, with the port name made file-name safe.
+#[must_use]
+pub fn conversation_path(port_name: &str) -> PathBuf {
+    Path::new(CONVERSATION_DIR).join(format!("llm_{}.jsonl", sanitize_port_name(port_name)))
+}
+
+/// Returns the file a transcript of a port's conversation saved at `time`
+/// is written to: `logs/llm/<port_name>_<time>.md`.
+#[must_use]
+pub fn transcript_path(port_name: &str, time: chrono::DateTime<chrono::Local>) -> PathBuf {
+    Path::new(TRANSCRIPT_DIR).join(format!(
+        "{}_{}.md",
+        sanitize_port_name(port_name),
+        time.format("%Y%m%d_%H%M%S")
+    ))
+}
+
+/// Returns the transcripts saved for a port in `dir`, newest first.
+#[must_use]
+pub fn saved_transcripts(dir: &Path, port_name: &str) -> Vec<PathBuf> {
+    let prefix = format!("{}_", sanitize_port_name(port_name));
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "md")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix))
+        })
+        .collect();
+    // File names end with the time they were saved at, so they sort by age.
+    paths.sort();
+    paths.reverse();
+    paths
+}
+
+/// Returns the endpoint to send requests to: the coding plan endpoint if it
+/// is enabled and `base_url` is the default one, `base_url` otherwise.
+#[must_use]
+pub fn chat_endpoint(base_url: &str, with_coding_plan: bool) -> &str {
+    if with_coding_plan && base_url == DEFAULT_BASE_URL {
+        CODING_PLAN_BASE_URL
+    } else {
+        base_url
+    }
+}
+
+/// Returns the last `max_bytes` of `text`, starting at a character boundary.
+fn truncate_front(text: &str, max_bytes: usize) -> &str {
+    let mut start = text.len().saturating_sub(max_bytes);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// Builds an OpenAI-compatible chat completions request body.
+///
+/// Roles other than `assistant` and `system` are sent as `user`.
+#[must_use]
+pub fn chat_request_body(
+    model: &str,
+    messages: &[LlmMessage],
+    temperature: f32,
+    max_tokens: u32,
+) -> serde_json::Value {
+    let messages: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|m| {
+            let role = match m.role.as_str() {
+                "assistant" => "assistant",
+                "system" => "system",
+                _ => "user",
+            };
+            json!({ "role": role, "content": m.content })
+        })
+        .collect();
+    json!({
+        "model": model,
+        "messages": messages,
+        "temperature": temperature,
+        "max_tokens": max_tokens,
+    })
+}
+
+/// Builds an Anthropic Messages API request body.
+///
+/// System messages are joined into the top-level `system` prompt; roles other
+/// than `assistant` and `system` are sent as `user`. The temperature is
+/// capped at 1, the highest the API accepts.
+#[must_use]
+pub fn anthropic_request_body(
+    model: &str,
+    messages: &[LlmMessage],
+    temperature: f32,
+    max_tokens: u32,
+) -> serde_json::Value {
+    let system: Vec<&str> = messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let messages: Vec<serde_json::Value> = messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| {
+            let role = if m.role == "assistant" {
+                "assistant"
+            } else {
+                "user"
+            };
+            json!({ "role": role, "content": m.content })
+        })
+        .collect();
+    let mut body = json!({
+        "model": model,
+        "messages": messages,
+        "temperature": temperature.min(1.0),
+        "max_tokens": max_tokens,
+    });
+    if !system.is_empty() {
+        body["system"] = json!(system.join("\n\n"));
+    }
+    body
 }
 
 /// A message in an LLM conversation.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LlmMessage {
     /// The role (user, assistant, system).
     pub role: String,
@@ -108,11 +648,21 @@ impl LlmMessage {
             timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
         }
     }
+
+    /// Creates a new system message with current timestamp.
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: String::from("system"),
+            content: content.into(),
+            timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
 
     #[test]
     fn test_llm_config() {
@@ -134,6 +684,365 @@ mod tests {
         assert!(config.messages.is_empty());
     }
 
+    #[test]
+    fn test_llm_config_defaults() {
+        let mut config = LlmConfig::new();
+        assert!(config.base_url.is_empty());
+        assert_eq!(config.temperature, 0.7);
+        assert_eq!(config.max_tokens, 2048);
+
+        config.set_temperature(3.5);
+        assert_eq!(config.temperature, 2.0);
+        config.set_temperature(-1.0);
+        assert_eq!(config.temperature, 0.0);
+    }
+
+    #[test]
+    fn test_llm_config_serializes_all_fields() {
+        let mut config = LlmConfig::new();
+        config.base_url = "http://localhost:11434/v1/chat/completions".to_string();
+        config.add_user_message("Hello");
+        let value = serde_json::to_value(&config).unwrap();
+        for field in [
+            "enable",
+            "messages",
+            "input_buffer",
+            "is_processing",
+            "request_in_flight",
+            "provider",
+            "base_url",
+            "temperature",
+            "max_tokens",
+            "include_recent_data",
+            "context_window_bytes",
+        ] {
+            assert!(value.get(field).is_some(), "missing {field}");
+        }
+        assert_eq!(
+            value["base_url"],
+            "http://localhost:11434/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn test_request_body_includes_parameters() {
+        let mut config = LlmConfig::new();
+        config.set_temperature(0.25);
+        config.max_tokens = 512;
+        config.add_user_message("Hello");
+        config.add_assistant_message("Hi");
+
+        let body = config.request_body("llama3", "");
+        assert_eq!(body["model"], "llama3");
+        assert_eq!(body["temperature"], 0.25);
+        assert_eq!(body["max_tokens"], 512);
+        assert_eq!(body["messages"][0]["role"], "user");
+        assert_eq!(body["messages"][1]["content"], "Hi");
+    }
+
+    #[test]
+    fn test_recent_data_is_first_system_message() {
+        let mut config = LlmConfig::new();
+        assert_eq!(config.context_window_bytes, 4096);
+        assert!(!config.include_recent_data);
+        config.add_user_message("Why did it reset?");
+        assert_eq!(config.current_messages("boot\n").len(), 1);
+
+        config.include_recent_data = true;
+
+        let messages = config.current_messages("boot\nwatchdog reset\n");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(
+            messages[0].content,
+            "Recent serial data: boot\nwatchdog reset\n"
+        );
+        assert_eq!(messages[1].content, "Why did it reset?");
+
+        let body = config.request_body("llama3", "boot\n");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["role"], "user");
+
+        config.include_recent_data = false;
+        assert_eq!(config.current_messages("boot\n").len(), 1);
+        config.include_recent_data = true;
+        assert_eq!(config.current_messages("").len(), 1);
+    }
+
+    #[test]
+    fn test_recent_data_is_truncated_from_the_beginning() {
+        let mut config = LlmConfig::new();
+        config.include_recent_data = true;
+        config.context_window_bytes = 4;
+        let messages = config.current_messages("old data\nOK\r\n");
+        assert_eq!(messages[0].content, "Recent serial data: OK\r\n");
+
+        // The window never splits a character.
+        config.context_window_bytes = 5;
+        let messages = config.current_messages("温度: 25");
+        assert_eq!(messages[0].content, "Recent serial data: : 25");
+    }
+
+    #[test]
+    fn test_stop_aborts_request_and_keeps_partial_response() {
+        struct DropFlag(Arc<AtomicBool>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let mut config = LlmConfig::new();
+        config.add_user_message("Hello");
+        config.is_processing = true;
+        config.request_in_flight = true;
+
+        let partial = config.start_streaming();
+        let flag = DropFlag(Arc::clone(&dropped));
+        config.request_task = Some(rt.spawn(async move {
+            let _flag = flag;
+            partial.lock().unwrap().push_str("Hi th");
+            std::future::pending::<()>().await;
+        }));
+        while config.streaming_text().is_empty() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        config.stop();
+        assert!(!config.is_processing);
+        assert!(!config.request_in_flight);
+        assert!(config.request_task.is_none());
+        assert!(config.streaming_text().is_empty());
+        assert_eq!(config.messages.last().unwrap().content, "Hi th");
+        assert_eq!(config.messages.last().unwrap().role, "assistant");
+
+        rt.block_on(async {
+            for _ in 0..100 {
+                if dropped.load(Ordering::SeqCst) {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+        });
+        assert!(dropped.load(Ordering::SeqCst), "task was not aborted");
+    }
+
+    #[test]
+    fn test_stop_without_partial_response_adds_no_message() {
+        let mut config = LlmConfig::new();
+        config.add_user_message("Hello");
+        config.is_processing = true;
+        config.stop();
+        assert_eq!(config.messages.len(), 1);
+        assert!(!config.is_processing);
+    }
+
+    #[test]
+    fn test_fail_request_sets_error_and_keeps_partial_response() {
+        let mut config = LlmConfig::new();
+        config.add_user_message("Hello");
+        config.is_processing = true;
+        config.request_in_flight = true;
+        config.start_streaming().lock().unwrap().push_str("Hi");
+
+        config.fail_request("HTTP 401 Unauthorized: invalid api key");
+        assert!(!config.is_processing);
+        assert!(!config.request_in_flight);
+        assert_eq!(
+            config.last_error.as_deref(),
+            Some("HTTP 401 Unauthorized: invalid api key")
+        );
+        assert_eq!(config.messages.len(), 2);
+        assert_eq!(config.messages[1], LlmMessage::assistant("Hi"));
+        assert!(config.streaming_text().is_empty());
+    }
+
+    #[test]
+    fn test_history_is_trimmed_to_budget() {
+        let mut config = LlmConfig::new();
+        config.history_budget_chars = 10;
+        config.add_user_message("first question");
+        config.add_assistant_message("12345");
+        config.add_user_message("67890");
+        let messages = config.current_messages("");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "12345");
+        assert_eq!(messages[1].content, "67890");
+
+        // The prompt is sent even if it alone exceeds the budget.
+        config.add_user_message("a question longer than the budget");
+        let messages = config.current_messages("");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(config.messages.len(), 4);
+    }
+
+    #[test]
+    fn test_transcript_save_and_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = LlmConfig::new();
+        config.add_user_message("What does AT+GMR return?");
+        config.add_assistant_message("The firmware version.\n");
+        let time = chrono::TimeZone::with_ymd_and_hms(&chrono::Local, 2025, 3, 4, 5, 6, 7).unwrap();
+        let name = transcript_path("/dev/ttyUSB0", time);
+        assert_eq!(
+            name,
+            Path::new("logs/llm").join("dev_ttyUSB0_20250304_050607.md")
+        );
+        let path = dir.path().join(name.file_name().unwrap());
+        config.save_transcript(&path, "/dev/ttyUSB0").unwrap();
+        fs::write(dir.path().join("COM1_20250304_050607.md"), "").unwrap();
+
+        assert_eq!(
+            saved_transcripts(dir.path(), "/dev/ttyUSB0"),
+            [path.clone()]
+        );
+        config.view_transcript(&path).unwrap();
+        let (viewed, text) = config.viewed_transcript.as_ref().unwrap();
+        assert_eq!(viewed, &path);
+        assert!(text.starts_with("# LLM conversation: /dev/ttyUSB0\n"));
+        assert!(text.contains("\n## User ("));
+        assert!(text.contains(")\n\nWhat does AT+GMR return?\n\n## Assistant ("));
+        assert!(text.ends_with("The firmware version.\n"));
+    }
+
+    #[test]
+    fn test_conversation_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("llm_COM1.jsonl");
+        let mut config = LlmConfig::new();
+        config.add_user_message("What does AT+GMR return?");
+        config.add_assistant_message("The firmware version,\nfollowed by OK.");
+        config.save_conversation(&path).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+
+        let mut loaded = LlmConfig::new();
+        loaded.load_conversation(&path).unwrap();
+        assert_eq!(loaded.messages, config.messages);
+
+        loaded.clear_history(&path).unwrap();
+        assert!(!loaded.has_messages());
+        assert!(!path.exists());
+        // Clearing again without a file is fine.
+        loaded.clear_history(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_conversation_rejects_invalid_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("llm.jsonl");
+        fs::write(
+            &path,
+            "{\"role\":\"user\",\"content\":\"hi\",\"timestamp\":\"12:00:00\"}\n\nnot json\n",
+        )
+        .unwrap();
+
+        let mut config = LlmConfig::new();
+        config.add_user_message("keep me");
+        let error = config.load_conversation(&path).unwrap_err();
+        assert!(error.to_string().contains(":3:"));
+        assert_eq!(config.messages.len(), 1);
+        assert_eq!(config.messages[0].content, "keep me");
+    }
+
+    #[test]
+    fn test_conversation_path_is_sanitized() {
+        assert_eq!(
+            conversation_path("/dev/ttyUSB0"),
+            Path::new("config").join("llm_dev_ttyUSB0.jsonl")
+        );
+    }
+
+    #[test]
+    fn test_request_body_follows_provider() {
+        let messages = [
+            LlmMessage::system("Recent serial data: OK"),
+            LlmMessage::user("Hello"),
+            LlmMessage::assistant("Hi"),
+        ];
+        let mut config = LlmConfig::new();
+        config.set_temperature(1.5);
+        config.max_tokens = 256;
+
+        for provider in [LlmProvider::ZhipuAI, LlmProvider::OpenAI] {
+            config.set_provider(provider);
+            let body = config.build_request_body("glm-4.7", &messages);
+            assert_eq!(
+                body,
+                json!({
+                    "model": "glm-4.7",
+                    "messages": [
+                        { "role": "system", "content": "Recent serial data: OK" },
+                        { "role": "user", "content": "Hello" },
+                        { "role": "assistant", "content": "Hi" },
+                    ],
+                    "temperature": 1.5,
+                    "max_tokens": 256,
+                })
+            );
+        }
+
+        config.set_provider(LlmProvider::OllamaLocal("llama3".to_string()));
+        assert_eq!(config.base_url, OLLAMA_BASE_URL);
+        let body = config.build_request_body("glm-4.7", &messages);
+        assert_eq!(body["model"], "llama3");
+        assert_eq!(body["messages"][0]["role"], "system");
+
+        config.set_provider(LlmProvider::Anthropic);
+        assert_eq!(config.base_url, ANTHROPIC_BASE_URL);
+        let body = config.build_request_body("claude-sonnet-4-5", &messages);
+        assert_eq!(
+            body,
+            json!({
+                "model": "claude-sonnet-4-5",
+                "system": "Recent serial data: OK",
+                "messages": [
+                    { "role": "user", "content": "Hello" },
+                    { "role": "assistant", "content": "Hi" },
+                ],
+                "temperature": 1.0,
+                "max_tokens": 256,
+            })
+        );
+    }
+
+    #[test]
+    fn test_request_headers_follow_provider() {
+        let bearer = HashMap::from([("Authorization".to_string(), "Bearer sk".to_string())]);
+        assert_eq!(LlmProvider::ZhipuAI.request_headers("sk"), bearer);
+        assert_eq!(LlmProvider::OpenAI.request_headers("sk"), bearer);
+        assert!(LlmProvider::OpenAI.request_headers("").is_empty());
+        assert!(
+            LlmProvider::OllamaLocal("llama3".to_string())
+                .request_headers("")
+                .is_empty()
+        );
+
+        let mut config = LlmConfig::new();
+        config.set_provider(LlmProvider::Anthropic);
+        assert_eq!(
+            config.build_request_headers("sk"),
+            HashMap::from([
+                ("x-api-key".to_string(), "sk".to_string()),
+                (
+                    "anthropic-version".to_string(),
+                    ANTHROPIC_VERSION.to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_chat_endpoint_coding_plan() {
+        assert_eq!(chat_endpoint(DEFAULT_BASE_URL, true), CODING_PLAN_BASE_URL);
+        assert_eq!(chat_endpoint(DEFAULT_BASE_URL, false), DEFAULT_BASE_URL);
+        let local = "http://localhost:11434/v1/chat/completions";
+        assert_eq!(chat_endpoint(local, true), local);
+    }
+
     #[test]
     fn text_models_include_current_zai_rs_text_models() {
         for model in [
@@ -0,0 +1,943 @@
+//! # Log Module
+//!
+//! Session log files of a port.
+//!
+//! A [`SessionLog`] owns the log files of one port: it names the file of a
+//! new session, hands written entries to its [`LogWriter`] thread, rolls over
+//! to a `_partN` file once the current one reaches the size limit of its
+//! [`LogPolicy`], deletes the oldest files beyond the retention count, and
+//! reads the files back, whole, by byte range or as [`LogEntry`] values.
+//! [`PortData`](super::port_data::PortData) holds the session log of its port.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::Resource;
+use chrono::{DateTime, Local};
+
+use super::log_entry::{LogEntries, LogEntry};
+use super::log_writer::{LogEvent, LogWriter};
+use super::state::DataSource;
+use super::timestamp::LOG_TIMESTAMP_FORMAT;
+use crate::error::Result;
+
+/// Default directory session log files are written to.
+pub const LOG_DIR: &str = "logs";
+
+/// Buffered log bytes that trigger a flush.
+pub const LOG_FLUSH_BYTES: usize = 8 * 1024;
+
+/// Maximum time log data stays buffered before it is flushed.
+pub const LOG_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Size and retention limits of a port's log files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogPolicy {
+    /// Size in bytes after which the log rolls over to a new `_partN` file.
+    pub max_file_size: u64,
+    /// Number of log files kept per port; older ones are deleted.
+    pub max_files_per_port: usize,
+}
+
+impl Default for LogPolicy {
+    fn default() -> Self {
+        Self {
+            max_file_size: 16 * 1024 * 1024,
+            max_files_per_port: 10,
+        }
+    }
+}
+
+/// Where and how large log files are written, for all ports.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    /// Directory of the session logs and raw recordings.
+    pub base_dir: PathBuf,
+    /// Size after which a log rolls over, overriding
+    /// [`LogPolicy::max_file_size`] if set.
+    pub max_file_size: Option<u64>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            base_dir: PathBuf::from(LOG_DIR),
+            max_file_size: None,
+        }
+    }
+}
+
+/// Error lines of the session kept in memory for
+/// [`SessionLog::recent_errors`].
+pub const RECENT_ERRORS_KEPT: usize = 32;
+
+/// Log files of a port's sessions.
+pub struct SessionLog {
+    /// Paths of the log files kept, oldest first; the last one is current.
+    files: Vec<String>,
+    /// Path of the session's first file without the `.txt` extension; rollover parts derive from it.
+    base: Option<String>,
+    /// Part number of the current file, starting at 1.
+    part: u32,
+    /// Bytes in the current file.
+    size: u64,
+    /// Path of the current file while the writer thread has not reported
+    /// the bytes it held when opened.
+    unsized_path: Option<PathBuf>,
+    /// Whether a failure to empty the file of the last [`Self::clear`] is
+    /// still retried once.
+    clear_retry: bool,
+    /// Why the last [`Self::clear`] failed, until taken.
+    clear_failure: Option<String>,
+    /// Latest error lines of the session, oldest first, at most
+    /// [`RECENT_ERRORS_KEPT`].
+    recent_errors: VecDeque<String>,
+    /// Size and retention limits.
+    policy: LogPolicy,
+    /// Log directory and size override.
+    config: LogConfig,
+    /// Writer thread of the log files, once a log file was opened.
+    writer: Option<LogWriter>,
+    /// When `writer` was last flushed.
+    last_flush: Instant,
+}
+
+impl Default for SessionLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionLog {
+    /// Creates a session log with no file open yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            base: None,
+            part: 1,
+            size: 0,
+            unsized_path: None,
+            clear_retry: false,
+            clear_failure: None,
+            recent_errors: VecDeque::new(),
+            policy: LogPolicy::default(),
+            config: LogConfig::default(),
+            writer: None,
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Adds a log file under the log directory and returns the new file count.
+    ///
+    /// Sanitization rules:
+    /// - Leading `/` or `\` is stripped (prevents absolute paths).
+    /// - Inner `/` or `\` are replaced with `_`.
+    /// - `..` components are removed to prevent directory traversal attacks.
+    ///
+    /// The final stored path is always `<log dir>/<sanitized_name>`, see
+    /// [`LogConfig::base_dir`]. The log writer thread creates the directory
+    /// and the file; on failure, an error is logged but the path is still
+    /// recorded.
+    pub fn add_file(&mut self, name: String) -> usize {
+        let dir = self.dir().to_path_buf();
+
+        // Sanitize user-provided file name (e.g. "/dev/ttyUSB0_20250101_010101.txt").
+        // Strip leading slashes, replace inner slashes/backslashes with underscores,
+        // and remove `..` components to prevent path traversal attacks.
+        let sanitized = name
+            .trim_start_matches('/')
+            .trim_start_matches('\\')
+            .replace(['/', '\\'], "_")
+            .replace("..", "");
+
+        let path = dir.join(sanitized).to_string_lossy().into_owned();
+        self.open(path)
+    }
+
+    /// Starts the log file of a new port session and returns its path.
+    ///
+    /// The path is `<log dir>/<port>_<time>.txt`, where `<port>` is the port
+    /// name made safe by [`sanitize_port_name`]. If another session of this
+    /// app already took that path, for example because two port names
+    /// sanitize to the same string, a counter is appended (`_1`, `_2`, ...).
+    /// The file system is not touched here: the log writer thread creates
+    /// the directory and the file.
+    pub fn start_session(&mut self, port_name: &str) -> String {
+        let dir = self.dir().to_path_buf();
+        self.start_session_in(&dir, port_name, Local::now())
+    }
+
+    /// Starts a session log for `port_name` at `time` in `dir`.
+    fn start_session_in(&mut self, dir: &Path, port_name: &str, time: DateTime<Local>) -> String {
+        let stem = format!(
+            "{}_{}",
+            sanitize_port_name(port_name),
+            time.format("%Y%m%d_%H%M%S")
+        );
+        let path = unique_log_path(dir, &stem).to_string_lossy().into_owned();
+        self.open(path.clone());
+        path
+    }
+
+    /// Opens `path` as the first file of a new log session and returns the new file count.
+    ///
+    /// The previous log file, if any, is flushed first.
+    pub fn open(&mut self, path: String) -> usize {
+        self.base = Some(path.strip_suffix(".txt").unwrap_or(&path).to_string());
+        self.part = 1;
+        self.recent_errors.clear();
+        self.open_path(path)
+    }
+
+    /// Opens `path` as the current log file, prunes old files and returns the new file count.
+    ///
+    /// The file is opened by the log writer thread, which logs any failure
+    /// and reports the bytes the file already held, see
+    /// [`Self::receive_writer_events`].
+    fn open_path(&mut self, path: String) -> usize {
+        self.size = 0;
+        self.unsized_path = Some(PathBuf::from(&path));
+        match &mut self.writer {
+            Some(writer) => writer.open(PathBuf::from(&path)),
+            None => self.writer = Some(LogWriter::spawn(PathBuf::from(&path))),
+        }
+        self.last_flush = Instant::now();
+
+        self.files.push(path);
+        self.prune();
+        self.files.len()
+    }
+
+    /// Continues the current log session in the next `_partN` file. Does
+    /// nothing if no session was started.
+    pub fn rotate(&mut self) {
+        let Some(base) = self.base.clone() else {
+            return;
+        };
+        self.part += 1;
+        let path = format!("{base}_part{}.txt", self.part);
+        self.open_path(path);
+    }
+
+    /// Counts the bytes the current file held when the log writer thread
+    /// opened it, once it reported them, and handles a failed
+    /// [`Self::clear`]: it is retried once, so the file does not keep the
+    /// old data with the new appended, and then kept for
+    /// [`Self::take_clear_failure`].
+    fn receive_writer_events(&mut self) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        let events: Vec<LogEvent> = writer.events().collect();
+        for event in events {
+            match event {
+                LogEvent::Opened { path, size } => {
+                    if self.unsized_path.as_ref() == Some(&path) {
+                        self.size += size;
+                        self.unsized_path = None;
+                    }
+                }
+                LogEvent::TruncateFailed { path, error } => {
+                    let retried =
+                        std::mem::take(&mut self.clear_retry) && writer.truncate(path).is_ok();
+                    if retried {
+                        self.size = 0;
+                    } else {
+                        self.clear_failure = Some(error);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns why the last [`Self::clear`] failed, once the log writer
+    /// thread reported it and the retry failed as well.
+    pub fn take_clear_failure(&mut self) -> Option<String> {
+        self.receive_writer_events();
+        self.clear_failure.take()
+    }
+
+    /// Deletes the oldest log files beyond the retention count.
+    fn prune(&mut self) {
+        let keep = self.policy.max_files_per_port.max(1);
+        while self.files.len() > keep {
+            let oldest = self.files.remove(0);
+            if let Some(writer) = &self.writer {
+                writer.remove(PathBuf::from(oldest));
+            }
+        }
+    }
+
+    /// Returns the directory log files are written to.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.config.base_dir
+    }
+
+    /// Returns the log directory and size override in use.
+    #[must_use]
+    pub const fn config(&self) -> &LogConfig {
+        &self.config
+    }
+
+    /// Sets the log directory and size override; applies from the next log
+    /// file. Without an override, the default size is used again.
+    pub fn set_config(&mut self, config: &LogConfig) {
+        self.policy.max_file_size = config
+            .max_file_size
+            .unwrap_or(LogPolicy::default().max_file_size);
+        self.config.clone_from(config);
+    }
+
+    /// Gets the log size and retention policy.
+    #[must_use]
+    pub const fn policy(&self) -> LogPolicy {
+        self.policy
+    }
+
+    /// Sets the log size and retention policy.
+    pub fn set_policy(&mut self, policy: LogPolicy) {
+        self.policy = policy;
+        self.prune();
+    }
+
+    /// Gets the number of log files kept.
+    #[must_use]
+    pub const fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Gets a log file path by index, or an empty string if there is none.
+    #[must_use]
+    pub fn file_name(&self, index: usize) -> &str {
+        self.files
+            .get(index)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Returns the path of the session's first file without the `.txt`
+    /// extension, if a session was started.
+    #[must_use]
+    pub fn base(&self) -> Option<&str> {
+        self.base.as_deref()
+    }
+
+    /// Returns the path of the log file currently written, if any.
+    #[must_use]
+    pub fn current_path(&self) -> Option<&str> {
+        self.writer
+            .as_ref()
+            .and(self.files.last())
+            .map(String::as_str)
+    }
+
+    /// Writes one entry to the current log file, if one is open, prefixed
+    /// with its time and source and ended with a line feed if `timestamped`.
+    ///
+    /// The file rolls over to the next `_partN` file first if the entry
+    /// would take it past the size limit. The data is buffered and flushed
+    /// every [`LOG_FLUSH_BYTES`] bytes, after [`LOG_FLUSH_INTERVAL`] (see
+    /// [`Self::flush_if_due`]), and when the log is flushed explicitly.
+    pub fn write_entry(
+        &mut self,
+        time: DateTime<Local>,
+        text: &str,
+        source: DataSource,
+        timestamped: bool,
+    ) {
+        if self.writer.is_none() {
+            return;
+        }
+        if source == DataSource::Error {
+            if self.recent_errors.len() == RECENT_ERRORS_KEPT {
+                self.recent_errors.pop_front();
+            }
+            let stamp = time.format(LOG_TIMESTAMP_FORMAT);
+            self.recent_errors
+                .push_back(format!("[{stamp} {source}]{}", text.trim_end()));
+        }
+        let line = if timestamped {
+            let stamp = time.format(LOG_TIMESTAMP_FORMAT);
+            terminate_line(format!("[{stamp} {source}]{text}"))
+        } else {
+            text.to_string()
+        };
+        self.receive_writer_events();
+        let len = line.len() as u64;
+        if self.size > 0 && self.size + len > self.policy.max_file_size {
+            self.rotate();
+        }
+        if let Some(writer) = &mut self.writer {
+            writer.write(line.as_bytes());
+            if writer.buffered() >= LOG_FLUSH_BYTES {
+                self.queue_flush();
+            }
+        }
+        self.size += len;
+    }
+
+    /// Flushes the log file if data has been buffered for longer than
+    /// [`LOG_FLUSH_INTERVAL`].
+    pub fn flush_if_due(&mut self) {
+        if self
+            .writer
+            .as_ref()
+            .is_some_and(|writer| writer.buffered() > 0)
+            && self.last_flush.elapsed() >= LOG_FLUSH_INTERVAL
+        {
+            self.queue_flush();
+        }
+    }
+
+    /// Hands the buffered log data to the log writer thread, without waiting
+    /// for it to be written.
+    pub fn queue_flush(&mut self) {
+        if let Some(writer) = &mut self.writer {
+            writer.flush();
+        }
+        self.last_flush = Instant::now();
+    }
+
+    /// Flushes the buffered log data and blocks until the log writer thread
+    /// has written it, e.g. before the log files are read or the app exits.
+    /// The frame loop uses [`Self::queue_flush`] instead.
+    pub fn flush(&mut self) {
+        if let Some(writer) = &mut self.writer {
+            writer.sync();
+        }
+        self.last_flush = Instant::now();
+    }
+
+    /// Empties the current log file, dropping the data not flushed yet, and
+    /// keeps writing the session to it. The earlier `_partN` files of the
+    /// session are kept. Does nothing if no log file is open.
+    ///
+    /// The file is emptied on the log writer thread, without waiting for it;
+    /// see [`Self::take_clear_failure`] for a failure there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log writer thread stopped and cannot empty
+    /// the file.
+    pub fn clear(&mut self) -> Result<()> {
+        let (Some(writer), Some(path)) = (&mut self.writer, self.files.last()) else {
+            return Ok(());
+        };
+        writer.truncate(PathBuf::from(path))?;
+        self.size = 0;
+        self.unsized_path = None;
+        self.clear_retry = true;
+        self.clear_failure = None;
+        self.recent_errors.clear();
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+
+    /// Reads a log file by index, or returns an empty string if there is no
+    /// such file or it cannot be read.
+    ///
+    /// Buffered log data is handed to the log writer thread first, without
+    /// waiting for it; call [`Self::flush`] before to read everything logged
+    /// so far.
+    #[must_use]
+    pub fn read_file(&mut self, index: usize) -> String {
+        self.queue_flush();
+        self.files
+            .get(index)
+            .and_then(|path| {
+                OpenOptions::new()
+                    .read(true)
+                    .open(path)
+                    .ok()
+                    .map(|mut file| {
+                        let mut data = String::new();
+                        let _ = file.read_to_string(&mut data);
+                        data
+                    })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Reads the bytes of a log file in `range`, without reading the rest of
+    /// the file, for showing a window of a large log.
+    ///
+    /// The range is cut at the end of the file; the result is empty if there
+    /// is no such file or it cannot be read. Like [`Self::read_file`], this
+    /// does not wait for the buffered log data to be written.
+    #[must_use]
+    pub fn read_range(&mut self, index: usize, range: Range<u64>) -> Vec<u8> {
+        self.queue_flush();
+        let Some(mut file) = self.files.get(index).and_then(|path| File::open(path).ok()) else {
+            return Vec::new();
+        };
+        let mut data = Vec::new();
+        if range.start < range.end && file.seek(SeekFrom::Start(range.start)).is_ok() {
+            let _ = file.take(range.end - range.start).read_to_end(&mut data);
+        }
+        data
+    }
+
+    /// Returns up to `count` of the most recent error entries of the session,
+    /// oldest first, as log lines tagged `E`.
+    ///
+    /// The lines are kept in memory as they are logged, at most
+    /// [`RECENT_ERRORS_KEPT`], so this does not touch the file; they are
+    /// tagged whether or not the log file has timestamps.
+    #[must_use]
+    pub fn recent_errors(&self, count: usize) -> Vec<String> {
+        let skip = self.recent_errors.len().saturating_sub(count);
+        self.recent_errors.iter().skip(skip).cloned().collect()
+    }
+
+    /// Parses the current log file into its entries.
+    ///
+    /// Like [`Self::read_file`], this does not wait for the buffered log data
+    /// to be written. The file is read line by line as the iterator advances;
+    /// see [`LogEntries`] for how lines are grouped into entries. The
+    /// iterator is empty if there is no log file or it cannot be opened.
+    pub fn entries(&mut self) -> impl Iterator<Item = LogEntry> + use<> {
+        self.queue_flush();
+        self.files
+            .last()
+            .and_then(|path| File::open(path).ok())
+            .map(|file| LogEntries::new(BufReader::new(file)))
+            .into_iter()
+            .flatten()
+    }
+}
+
+/// Makes a port name safe to use in a file name.
+///
+/// Windows device prefixes (`\\.\`, `\\?\`) and leading separators are
+/// stripped, separators and characters invalid in Windows file names become
+/// `_`, and `..` is removed. Reserved Windows device names such as `CON`,
+/// `AUX` or `COM3` are prefixed with `_`.
+#[must_use]
+pub fn sanitize_port_name(port_name: &str) -> String {
+    let name = port_name
+        .strip_prefix(r"\\.\")
+        .or_else(|| port_name.strip_prefix(r"\\?\"))
+        .unwrap_or(port_name);
+    let name: String = name
+        .trim_start_matches(['/', '\\'])
+        .chars()
+        .map(|c| {
+            let invalid = matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|');
+            if invalid || c.is_control() { '_' } else { c }
+        })
+        .collect();
+    let name = name.replace("..", "");
+    // Windows drops trailing dots and spaces from file names.
+    let name = name.trim_end_matches(['.', ' ']);
+
+    if name.is_empty() {
+        "port".to_string()
+    } else if is_reserved_file_name(name) {
+        format!("_{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Returns true for names Windows reserves for devices (`CON`, `COM1`, ...).
+fn is_reserved_file_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    if matches!(upper.as_str(), "CON" | "PRN" | "AUX" | "NUL") {
+        return true;
+    }
+    match upper.as_bytes() {
+        [b'C', b'O', b'M', digit] | [b'L', b'P', b'T', digit] => digit.is_ascii_digit(),
+        _ => false,
+    }
+}
+
+/// Returns `dir/<stem>.txt`, or `dir/<stem>_N.txt` with the first free `N`
+/// if that file already exists.
+fn unique_log_path(dir: &Path, stem: &str) -> PathBuf {
+    /// Session log paths handed out so far.
+    static TAKEN: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+    let mut taken = TAKEN
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut path = dir.join(format!("{stem}.txt"));
+    let mut counter = 1;
+    while taken.contains(&path) {
+        path = dir.join(format!("{stem}_{counter}.txt"));
+        counter += 1;
+    }
+    taken.insert(path.clone());
+    path
+}
+
+/// Appends a line feed unless the line already ends with one.
+pub(super) fn terminate_line(mut line: String) -> String {
+    if !line.ends_with('\n') {
+        line.push('\n');
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a session log writing to a fresh file in a temporary directory.
+    fn session_log_in(dir: &tempfile::TempDir) -> SessionLog {
+        let mut log = SessionLog::new();
+        log.open(dir.path().join("log.txt").to_string_lossy().into_owned());
+        log
+    }
+
+    #[test]
+    fn test_entries_end_with_newline() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = session_log_in(&dir);
+        let time = Local::now();
+        log.write_entry(time, "AT", DataSource::Write, true);
+        log.write_entry(time, "OK\n", DataSource::Read, true);
+        log.write_entry(time, "lost", DataSource::Error, true);
+
+        log.flush();
+        let text = log.read_file(0);
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with('[') && lines[0].ends_with(" T]AT\n"));
+        assert!(lines[1].ends_with(" R]OK\n"));
+        assert!(lines[2].ends_with(" E]lost\n"));
+    }
+
+    #[test]
+    fn test_entries_recover_multi_line_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = session_log_in(&dir);
+        log.write_entry(Local::now(), "AT", DataSource::Write, true);
+        log.write_entry(Local::now(), "0D 0A\n0A\n0D\n", DataSource::Read, true);
+        log.write_entry(Local::now(), "lost", DataSource::Error, true);
+
+        log.flush();
+        let entries: Vec<LogEntry> = log.entries().collect();
+        let parsed: Vec<(DataSource, &[u8])> = entries
+            .iter()
+            .map(|entry| (entry.source, entry.data.as_slice()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                (DataSource::Write, b"AT\n".as_slice()),
+                (DataSource::Read, b"0D 0A\n0A\n0D\n".as_slice()),
+                (DataSource::Error, b"lost\n".as_slice()),
+            ]
+        );
+        assert!(
+            entries
+                .windows(2)
+                .all(|pair| pair[0].timestamp <= pair[1].timestamp)
+        );
+        assert_eq!(SessionLog::new().entries().count(), 0);
+    }
+
+    #[test]
+    fn test_raw_entries_are_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = session_log_in(&dir);
+        log.write_entry(Local::now(), "AB", DataSource::Read, false);
+        log.write_entry(Local::now(), "C\r\n", DataSource::Read, false);
+        log.flush();
+        assert_eq!(log.read_file(0), "ABC\r\n");
+    }
+
+    #[test]
+    fn test_nothing_is_written_without_a_file() {
+        let mut log = SessionLog::new();
+        log.write_entry(Local::now(), "lost", DataSource::Read, true);
+        assert_eq!(log.file_count(), 0);
+        assert_eq!(log.current_path(), None);
+        assert_eq!(log.read_file(0), "");
+        assert_eq!(log.file_name(0), "");
+    }
+
+    #[test]
+    fn test_writes_are_buffered_until_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = session_log_in(&dir);
+        log.write_entry(Local::now(), "x", DataSource::Read, false);
+        let path = log.file_name(0).to_string();
+        log.writer.as_ref().unwrap().wait();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+        let full = "y".repeat(LOG_FLUSH_BYTES);
+        log.write_entry(Local::now(), &full, DataSource::Read, false);
+        log.writer.as_ref().unwrap().wait();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().len(),
+            LOG_FLUSH_BYTES + 1
+        );
+
+        log.write_entry(Local::now(), "z", DataSource::Read, false);
+        log.last_flush -= LOG_FLUSH_INTERVAL;
+        log.flush_if_due();
+        log.writer.as_ref().unwrap().wait();
+        assert!(std::fs::read_to_string(&path).unwrap().ends_with("yz"));
+    }
+
+    #[test]
+    fn test_rollover_and_pruning() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = SessionLog::new();
+        log.set_policy(LogPolicy {
+            max_file_size: 1024,
+            max_files_per_port: 3,
+        });
+        log.open(dir.path().join("port.txt").to_string_lossy().into_owned());
+
+        // Five 200-byte chunks fit in each 1 KB file, so 20 chunks make 4 files.
+        let chunk = "x".repeat(200);
+        for _ in 0..20 {
+            log.write_entry(Local::now(), &chunk, DataSource::Read, false);
+        }
+        log.flush();
+
+        let names: Vec<String> = (0..log.file_count())
+            .map(|i| {
+                let path = Path::new(log.file_name(i));
+                path.file_name().unwrap().to_string_lossy().into_owned()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            ["port_part2.txt", "port_part3.txt", "port_part4.txt"]
+        );
+        assert!(!dir.path().join("port.txt").exists());
+        for i in 0..log.file_count() {
+            assert_eq!(log.read_file(i).len(), 1000);
+        }
+        assert_eq!(
+            log.current_path(),
+            Some(log.file_name(2)),
+            "the newest part is written"
+        );
+        assert_eq!(log.base(), dir.path().join("port").to_str());
+
+        // Tightening the retention count prunes right away.
+        log.set_policy(LogPolicy {
+            max_file_size: 1024,
+            max_files_per_port: 1,
+        });
+        log.flush();
+        assert_eq!(log.file_count(), 1);
+        assert!(!dir.path().join("port_part3.txt").exists());
+    }
+
+    #[test]
+    fn test_clear_empties_only_the_current_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = SessionLog::new();
+        assert!(log.clear().is_ok());
+        assert_eq!(log.take_clear_failure(), None);
+        log.set_policy(LogPolicy {
+            max_file_size: 10,
+            max_files_per_port: 3,
+        });
+        log.open(dir.path().join("port.txt").to_string_lossy().into_owned());
+        log.write_entry(Local::now(), "0123456789", DataSource::Read, false);
+        log.write_entry(Local::now(), "abc", DataSource::Read, false);
+
+        log.clear().unwrap();
+        log.flush();
+        assert_eq!(log.read_file(0), "0123456789");
+        assert_eq!(log.read_file(1), "");
+        // The emptied file fills up to the full size again before rolling over.
+        log.write_entry(Local::now(), "ABCDEFGHIJ", DataSource::Read, false);
+        assert_eq!(log.file_count(), 2);
+        log.flush();
+        assert_eq!(log.read_file(1), "ABCDEFGHIJ");
+    }
+
+    #[test]
+    fn test_clear_failure_is_retried_then_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        let mut log = SessionLog::new();
+        log.open(sub.join("port.txt").to_string_lossy().into_owned());
+        log.write_entry(Local::now(), "old", DataSource::Read, false);
+        log.flush();
+
+        // Without its directory the file cannot be created empty again.
+        std::fs::remove_dir_all(&sub).unwrap();
+        log.clear().unwrap();
+        log.flush();
+        assert_eq!(log.take_clear_failure(), None);
+        log.flush();
+        assert!(log.take_clear_failure().is_some());
+        assert_eq!(log.take_clear_failure(), None);
+    }
+
+    #[test]
+    fn test_read_range_is_cut_at_end_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = session_log_in(&dir);
+        log.write_entry(Local::now(), "0123456789", DataSource::Read, false);
+        log.flush();
+
+        assert_eq!(log.read_range(0, 2..5), b"234");
+        assert_eq!(log.read_range(0, 8..100), b"89");
+        assert!(log.read_range(0, 20..30).is_empty());
+        assert!(log.read_range(0, 5..5).is_empty());
+        assert!(log.read_range(1, 0..10).is_empty());
+    }
+
+    #[test]
+    fn test_recent_errors_are_kept_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = session_log_in(&dir);
+        assert!(SessionLog::new().recent_errors(2).is_empty());
+        for i in 0..3 {
+            log.write_entry(Local::now(), &format!("read {i}"), DataSource::Read, true);
+            log.write_entry(
+                Local::now(),
+                &format!("error {i}\n"),
+                DataSource::Error,
+                true,
+            );
+        }
+
+        let lines = log.recent_errors(2);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" E]error 1"));
+        assert!(lines[1].ends_with(" E]error 2"));
+
+        // Only the latest lines are kept, and clearing the log drops them.
+        for i in 0..RECENT_ERRORS_KEPT {
+            log.write_entry(Local::now(), &format!("late {i}"), DataSource::Error, false);
+        }
+        let lines = log.recent_errors(usize::MAX);
+        assert_eq!(lines.len(), RECENT_ERRORS_KEPT);
+        assert!(lines[0].ends_with(" E]late 0"));
+        log.clear().unwrap();
+        assert!(log.recent_errors(2).is_empty());
+    }
+
+    #[test]
+    fn test_sanitize_port_name() {
+        assert_eq!(sanitize_port_name("/dev/tty/USB0"), "dev_tty_USB0");
+        assert_eq!(sanitize_port_name("/dev/ttyUSB0"), "dev_ttyUSB0");
+        assert_eq!(sanitize_port_name(r"\\.\COM12"), "COM12");
+        assert_eq!(sanitize_port_name("mock://demo"), "mock___demo");
+        assert_eq!(sanitize_port_name("../../etc"), "__etc");
+        assert_eq!(sanitize_port_name(""), "port");
+    }
+
+    #[test]
+    fn test_sanitize_reserved_names() {
+        assert_eq!(sanitize_port_name("COM3"), "_COM3");
+        assert_eq!(sanitize_port_name(r"\\.\COM3"), "_COM3");
+        assert_eq!(sanitize_port_name("con"), "_con");
+        assert_eq!(sanitize_port_name("AUX"), "_AUX");
+        assert_eq!(sanitize_port_name("LPT1"), "_LPT1");
+        assert_eq!(sanitize_port_name("CONSOLE"), "CONSOLE");
+    }
+
+    #[test]
+    fn test_config_sets_directory_and_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = SessionLog::new();
+        log.set_config(&LogConfig {
+            base_dir: dir.path().join("nested"),
+            max_file_size: Some(1024),
+        });
+        assert_eq!(log.policy().max_file_size, 1024);
+
+        log.add_file("/dev/ttyUSB0.txt".to_string());
+        // The writer thread creates the directory and file.
+        log.flush();
+        assert!(dir.path().join("nested").join("dev_ttyUSB0.txt").exists());
+        let path = log.start_session("COM1");
+        assert!(Path::new(&path).starts_with(dir.path().join("nested")));
+        log.flush();
+        assert!(Path::new(&path).exists());
+    }
+
+    #[test]
+    fn test_starting_a_session_leaves_file_work_to_the_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = SessionLog::new();
+        log.set_config(&LogConfig {
+            base_dir: dir.path().join("a").join("b"),
+            max_file_size: Some(1024 * 1024),
+        });
+
+        // Starting a session in a new directory and rolling over a few
+        // times stays well within a 60 fps frame.
+        let start = Instant::now();
+        let path = log.start_session("COM1");
+        let chunk = "x".repeat(512 * 1024);
+        for _ in 0..8 {
+            log.write_entry(Local::now(), &chunk, DataSource::Read, false);
+        }
+        assert!(start.elapsed() < Duration::from_millis(8));
+
+        log.flush();
+        assert!(Path::new(&path).exists());
+        assert_eq!(log.file_count(), 4);
+    }
+
+    #[test]
+    fn test_appending_counts_the_existing_file_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("port.txt");
+        std::fs::write(&path, "0123456789").unwrap();
+        let mut log = SessionLog::new();
+        log.set_policy(LogPolicy {
+            max_file_size: 15,
+            max_files_per_port: 3,
+        });
+        log.open(path.to_string_lossy().into_owned());
+        log.flush();
+
+        log.write_entry(Local::now(), "abcdef", DataSource::Read, false);
+        assert_eq!(log.file_count(), 2);
+        log.flush();
+        assert_eq!(log.read_file(0), "0123456789");
+        assert_eq!(log.read_file(1), "abcdef");
+    }
+
+    #[test]
+    fn test_session_collisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let time = Local::now();
+        let stamp = time.format("%Y%m%d_%H%M%S").to_string();
+        let file_name = |path: &str| {
+            Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        // Two different ports that sanitize to the same name get separate files.
+        let mut first = SessionLog::new();
+        let mut second = SessionLog::new();
+        let mut third = SessionLog::new();
+        let a = first.start_session_in(dir.path(), "/dev/ttyUSB0", time);
+        let b = second.start_session_in(dir.path(), "dev/ttyUSB0", time);
+        let c = third.start_session_in(dir.path(), "/dev/ttyUSB0", time);
+        assert_eq!(file_name(&a), format!("dev_ttyUSB0_{stamp}.txt"));
+        assert_eq!(file_name(&b), format!("dev_ttyUSB0_{stamp}_1.txt"));
+        assert_eq!(file_name(&c), format!("dev_ttyUSB0_{stamp}_2.txt"));
+
+        first.write_entry(time, "first", DataSource::Read, false);
+        second.write_entry(time, "second", DataSource::Read, false);
+        first.flush();
+        second.flush();
+        assert_eq!(first.read_file(0), "first");
+        assert_eq!(second.read_file(0), "second");
+    }
+}
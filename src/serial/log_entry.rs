@@ -0,0 +1,140 @@
+//! # Log Entry Module
+//!
+//! Parsing of session log files back into their entries.
+//!
+//! With timestamps enabled, each entry of a session log starts with a
+//! `[YYYYMMDD HH:MM:SS.mmm S]` header, where `S` is the source: `T` for sent
+//! data, `R` for received data and `E` for errors. Data containing line
+//! feeds spans several lines, so lines that do not start with a header belong
+//! to the entry before them.
+
+use std::io::BufRead;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use log::warn;
+
+use super::state::DataSource;
+use super::timestamp::LOG_TIMESTAMP_FORMAT;
+
+/// One entry of a session log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// When the data was written or received.
+    pub timestamp: DateTime<Local>,
+    /// Direction of the data.
+    pub source: DataSource,
+    /// The data as logged, including the line feed that ends the entry.
+    pub data: Vec<u8>,
+}
+
+/// Iterator over the entries of a session log, read line by line.
+///
+/// Lines before the first header, such as those of a log written without
+/// timestamps, are skipped. Reading stops at the first I/O error.
+pub struct LogEntries<R> {
+    /// The log being read.
+    reader: R,
+    /// The entry being read, returned once the next header or the end of the
+    /// log is reached.
+    pending: Option<LogEntry>,
+}
+
+impl<R: BufRead> LogEntries<R> {
+    /// Creates an iterator over the entries of `reader`.
+    pub const fn new(reader: R) -> Self {
+        Self {
+            reader,
+            pending: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for LogEntries<R> {
+    type Item = LogEntry;
+
+    fn next(&mut self) -> Option<LogEntry> {
+        loop {
+            let mut line = Vec::new();
+            match self.reader.read_until(b'\n', &mut line) {
+                Ok(0) => return self.pending.take(),
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Failed to read log file: {e}");
+                    return self.pending.take();
+                }
+            }
+            if let Some((timestamp, source, header_len)) = parse_header(&line) {
+                let entry = LogEntry {
+                    timestamp,
+                    source,
+                    data: line[header_len..].to_vec(),
+                };
+                if let Some(previous) = self.pending.replace(entry) {
+                    return Some(previous);
+                }
+            } else if let Some(entry) = &mut self.pending {
+                entry.data.extend_from_slice(&line);
+            }
+        }
+    }
+}
+
+/// Parses the `[YYYYMMDD HH:MM:SS.mmm S]` header at the start of `line`.
+///
+/// Returns the timestamp, the source and the length of the header, or `None`
+/// if the line does not start with a header.
+fn parse_header(line: &[u8]) -> Option<(DateTime<Local>, DataSource, usize)> {
+    let rest = line.strip_prefix(b"[")?;
+    let end = rest.iter().take(32).position(|byte| *byte == b']')?;
+    let header = std::str::from_utf8(&rest[..end]).ok()?;
+    let (stamp, source) = header.rsplit_once(' ')?;
+    let source = match source {
+        "T" => DataSource::Write,
+        "R" => DataSource::Read,
+        "E" => DataSource::Error,
+        _ => return None,
+    };
+    let naive = NaiveDateTime::parse_from_str(stamp, LOG_TIMESTAMP_FORMAT).ok()?;
+    let timestamp = Local.from_local_datetime(&naive).earliest()?;
+    Some((timestamp, source, end + 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_parse_header() {
+        let (time, source, len) = parse_header(b"[20250102 03:04:05.678 T]AT\n").unwrap();
+        assert_eq!(source, DataSource::Write);
+        assert_eq!(len, 25);
+        assert_eq!((time.hour(), time.minute(), time.second()), (3, 4, 5));
+        assert_eq!(time.timestamp_subsec_millis(), 678);
+
+        assert!(parse_header(b"[20250102 03:04:05.678 X]AT\n").is_none());
+        assert!(parse_header(b"[+0.000s R]OK\n").is_none());
+        assert!(parse_header(b"OK\n").is_none());
+    }
+
+    #[test]
+    fn test_entries_join_continuation_lines() {
+        let log = b"boot\n\
+            [20250102 03:04:05.000 T]AT\n\
+            [20250102 03:04:05.010 R]line 1\nline 2\n\n\
+            [20250102 03:04:05.020 E]lost";
+        let entries: Vec<LogEntry> = LogEntries::new(&log[..]).collect();
+        let data: Vec<(DataSource, &[u8])> = entries
+            .iter()
+            .map(|entry| (entry.source, entry.data.as_slice()))
+            .collect();
+        assert_eq!(
+            data,
+            [
+                (DataSource::Write, b"AT\n".as_slice()),
+                (DataSource::Read, b"line 1\nline 2\n\n".as_slice()),
+                (DataSource::Error, b"lost".as_slice()),
+            ]
+        );
+    }
+}
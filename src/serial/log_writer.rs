@@ -0,0 +1,381 @@
+//! # Log Writer Module
+//!
+//! Session log files and recordings written on a thread of their own.
+//!
+//! Ports log and record from the frame loop, where creating directories, opening,
+//! writing or deleting files on a slow disk would stall the frame. A
+//! [`LogWriter`] hands that work to its thread instead, which does it in the
+//! order it was queued and reports back through [`LogEvent`]s; the frame
+//! loop only collects the log data in memory and hands it over in batches.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+use log::{error, warn};
+
+/// Work queued for the writer thread.
+enum LogCommand {
+    /// Makes the file the current one, appending to it, after creating its
+    /// directory.
+    Open(PathBuf),
+    /// Appends a batch of data to the current file.
+    Write(Vec<u8>),
+    /// Deletes a file.
+    Remove(PathBuf),
+    /// Closes the current file, which is at the path, empties it and
+    /// continues writing to it.
+    Truncate(PathBuf),
+    /// Answers once the work queued before it is done.
+    Sync(mpsc::Sender<()>),
+}
+
+/// What the writer thread reports back to its handle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogEvent {
+    /// The file at the path was opened and already held `size` bytes.
+    Opened {
+        /// Path of the opened file.
+        path: PathBuf,
+        /// Bytes in the file before anything was appended.
+        size: u64,
+    },
+    /// The file at the path could not be emptied; writing goes on appending
+    /// to it.
+    TruncateFailed {
+        /// Path of the file.
+        path: PathBuf,
+        /// Why it could not be emptied.
+        error: String,
+    },
+}
+
+/// Handle to the thread writing the log files of a port.
+///
+/// Written data is kept until [`flush`](Self::flush) hands it to the thread
+/// as one batch. Dropping the handle flushes it and lets the thread finish
+/// the queued work and exit.
+#[derive(Debug)]
+pub struct LogWriter {
+    /// Queue of the writer thread.
+    commands: mpsc::Sender<LogCommand>,
+    /// Reports of the writer thread.
+    events: mpsc::Receiver<LogEvent>,
+    /// Data written since the last flush.
+    buffer: Vec<u8>,
+}
+
+impl LogWriter {
+    /// Starts a writer thread with `path` as the current file.
+    #[must_use]
+    pub fn spawn(path: PathBuf) -> Self {
+        let (commands, queue) = mpsc::channel();
+        let (report, events) = mpsc::channel();
+        if let Err(e) = thread::Builder::new()
+            .name("log-writer".to_string())
+            .spawn(move || run(&queue, &report))
+        {
+            error!("Failed to start the log writer: {e}");
+        }
+        let mut writer = Self {
+            commands,
+            events,
+            buffer: Vec::new(),
+        };
+        writer.open(path);
+        writer
+    }
+
+    /// Starts a writer thread with `path` as the current file, emptying it
+    /// first if it exists, e.g. for a recording replacing an older one.
+    #[must_use]
+    pub fn create(path: PathBuf) -> Self {
+        let mut writer = Self::spawn(path.clone());
+        if let Err(e) = writer.truncate(path) {
+            warn!("Failed to empty the new file: {e}");
+        }
+        writer
+    }
+
+    /// Makes `path` the current file, after flushing the data written to the
+    /// previous one; later data is appended to it.
+    pub fn open(&mut self, path: PathBuf) {
+        self.flush();
+        self.send(LogCommand::Open(path));
+    }
+
+    /// Appends `data` to the current file once flushed.
+    pub fn write(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Returns the number of bytes written since the last flush.
+    #[must_use]
+    pub const fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Hands the data written since the last flush to the thread, without
+    /// waiting for it to be written.
+    pub fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            let batch = std::mem::take(&mut self.buffer);
+            self.send(LogCommand::Write(batch));
+        }
+    }
+
+    /// Deletes the file at `path`, e.g. an old log beyond the retention count.
+    pub fn remove(&self, path: PathBuf) {
+        self.send(LogCommand::Remove(path));
+    }
+
+    /// Empties the current file, which is at `path`, dropping the data not
+    /// flushed yet, without waiting for it; a failure to empty it is
+    /// reported as [`LogEvent::TruncateFailed`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread stopped and cannot take the request.
+    pub fn truncate(&mut self, path: PathBuf) -> io::Result<()> {
+        self.buffer.clear();
+        self.commands
+            .send(LogCommand::Truncate(path))
+            .map_err(|_| io::Error::other("log writer stopped"))
+    }
+
+    /// Flushes and blocks until everything written so far is on disk.
+    pub fn sync(&mut self) {
+        self.flush();
+        self.wait();
+    }
+
+    /// Blocks until the work handed to the thread so far is done; data not
+    /// flushed yet stays buffered.
+    pub fn wait(&self) {
+        let (done, wait) = mpsc::channel();
+        self.send(LogCommand::Sync(done));
+        let _ = wait.recv();
+    }
+
+    /// Returns the events the thread reported since the last call, without
+    /// waiting for more.
+    pub fn events(&self) -> impl Iterator<Item = LogEvent> + '_ {
+        self.events.try_iter()
+    }
+
+    /// Queues `command`; the thread only stops once the handle is dropped.
+    fn send(&self, command: LogCommand) {
+        if self.commands.send(command).is_err() {
+            warn!("Log writer stopped, dropping log data");
+        }
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Runs the writer thread until its handle is dropped.
+fn run(queue: &mpsc::Receiver<LogCommand>, report: &mpsc::Sender<LogEvent>) {
+    let mut file: Option<File> = None;
+    for command in queue {
+        match command {
+            LogCommand::Open(path) => {
+                if let Some(dir) = path.parent()
+                    && let Err(e) = fs::create_dir_all(dir)
+                {
+                    warn!("Failed to create log directory {}: {e}", dir.display());
+                }
+                file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .inspect_err(|e| {
+                        error!("Failed to create source file {}: {e}", path.display());
+                    })
+                    .ok();
+                if let Some(opened) = &file {
+                    let size = opened.metadata().map_or(0, |meta| meta.len());
+                    // The handle may be gone already; nothing is left to report to.
+                    let _ = report.send(LogEvent::Opened { path, size });
+                }
+            }
+            LogCommand::Write(data) => {
+                if let Some(file) = &mut file
+                    && let Err(e) = file.write_all(&data)
+                {
+                    warn!("Failed to write to source file: {e}");
+                }
+            }
+            LogCommand::Remove(path) => {
+                if let Err(e) = fs::remove_file(&path)
+                    && e.kind() != ErrorKind::NotFound
+                {
+                    warn!("Failed to delete old log file {}: {e}", path.display());
+                }
+            }
+            LogCommand::Truncate(path) => {
+                // Close the file first; some platforms cannot empty a file
+                // that is open.
+                file = None;
+                match File::create(&path).and_then(|_| OpenOptions::new().append(true).open(&path))
+                {
+                    Ok(emptied) => file = Some(emptied),
+                    Err(e) => {
+                        warn!("Failed to clear log file {}: {e}", path.display());
+                        file = OpenOptions::new().append(true).open(&path).ok();
+                        let _ = report.send(LogEvent::TruncateFailed {
+                            path,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
+            LogCommand::Sync(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Creates a writer with `path` as the current file whose queue is
+    /// returned instead of being run by a thread.
+    fn unstarted_writer(path: PathBuf) -> (LogWriter, mpsc::Receiver<LogCommand>) {
+        let (commands, queue) = mpsc::channel();
+        let (_report, events) = mpsc::channel();
+        let mut writer = LogWriter {
+            commands,
+            events,
+            buffer: Vec::new(),
+        };
+        writer.open(path);
+        (writer, queue)
+    }
+
+    #[test]
+    fn test_writing_a_large_log_does_not_block_the_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.txt");
+        let next = dir.path().join("big_part2.txt");
+        let (mut writer, queue) = unstarted_writer(path.clone());
+        writer.write(&vec![b'x'; 50 * 1024 * 1024]);
+
+        // A frame hands over 50 MB, logs a line to the next file and deletes
+        // the first one; it only queues the work for the writer thread.
+        writer.flush();
+        writer.open(next.clone());
+        writer.write(b"line\n");
+        writer.flush();
+        writer.remove(path.clone());
+        assert_eq!(writer.buffered(), 0);
+        let queued: Vec<LogCommand> = queue.try_iter().collect();
+        assert!(matches!(
+            queued.as_slice(),
+            [
+                LogCommand::Open(first),
+                LogCommand::Write(big),
+                LogCommand::Open(second),
+                LogCommand::Write(line),
+                LogCommand::Remove(removed),
+            ] if *first == path
+                && big.len() == 50 * 1024 * 1024
+                && *second == next
+                && line == b"line\n"
+                && *removed == path
+        ));
+        assert!(!path.exists() && !next.exists());
+
+        // The writer thread then does the work in order.
+        let (commands, queue) = mpsc::channel();
+        queued
+            .into_iter()
+            .for_each(|command| commands.send(command).unwrap());
+        drop(commands);
+        let (report, _events) = mpsc::channel();
+        run(&queue, &report);
+        assert!(!path.exists());
+        assert_eq!(fs::read(&next).unwrap(), b"line\n");
+    }
+
+    #[test]
+    fn test_open_creates_the_directory_and_reports_the_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("existing.txt");
+        fs::write(&existing, b"12345").unwrap();
+        let nested = dir.path().join("a").join("b").join("log.txt");
+
+        let mut writer = LogWriter::spawn(existing.clone());
+        writer.open(nested.clone());
+        writer.sync();
+        assert!(nested.exists());
+        assert_eq!(
+            writer.events().collect::<Vec<_>>(),
+            [
+                LogEvent::Opened {
+                    path: existing,
+                    size: 5
+                },
+                LogEvent::Opened {
+                    path: nested,
+                    size: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncate_empties_the_file_and_keeps_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.txt");
+        let mut writer = LogWriter::spawn(path.clone());
+        writer.write(b"old\n");
+        writer.sync();
+        writer.write(b"unflushed\n");
+
+        writer.truncate(path.clone()).unwrap();
+        assert_eq!(writer.buffered(), 0);
+        writer.wait();
+        assert_eq!(fs::read(&path).unwrap(), b"");
+        writer.write(b"new\n");
+        writer.sync();
+        assert_eq!(fs::read(&path).unwrap(), b"new\n");
+        writer.events().for_each(drop);
+
+        let missing = dir.path().join("missing").join("log.txt");
+        writer.truncate(missing.clone()).unwrap();
+        writer.wait();
+        assert!(matches!(
+            writer.events().collect::<Vec<_>>().as_slice(),
+            [LogEvent::TruncateFailed { path, .. }] if *path == missing
+        ));
+    }
+
+    #[test]
+    fn test_dropping_the_writer_keeps_buffered_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.txt");
+        let mut writer = LogWriter::spawn(path.clone());
+        writer.write(b"first\n");
+        writer.sync();
+        writer.write(b"second\n");
+        assert_eq!(writer.buffered(), 7);
+        assert_eq!(fs::read(&path).unwrap(), b"first\n");
+
+        // The thread exits once it wrote what was queued.
+        drop(writer);
+        let start = Instant::now();
+        while fs::read(&path).unwrap() != b"first\nsecond\n" {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! # Logger Module
+//!
+//! Headless logging of one port to a file, without the UI.
+//!
+//! The [`SerialLoggerPlugin`] opens a port through the [`SerialPlugin`] once
+//! its thread is running, appends each received line to a file together with
+//! the time its first byte arrived, and closes the port cleanly when stopped
+//! through its [`LoggerStop`] handle. The `serial_logger` binary runs it with
+//! `MinimalPlugins` and stops it on Ctrl+C.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use bevy::app::ScheduleRunnerPlugin;
+//! use bevy::prelude::*;
+//! use serial_bevy::serial::SerialPlugin;
+//! use serial_bevy::serial::logger::SerialLoggerPlugin;
+//!
+//! App::new()
+//!     .add_plugins((
+//!         MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_millis(10))),
+//!         SerialPlugin::default(),
+//!         SerialLoggerPlugin::new("/dev/ttyUSB0", 115200, "capture.log"),
+//!     ))
+//!     .run();
+//! ```
+//!
+//! [`SerialPlugin`]: super::SerialPlugin
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use chrono::{DateTime, Local};
+use log::{error, info, warn};
+
+use super::Serials;
+use super::io::{PortDataReceived, receive_serial_data};
+use super::port::Serial;
+use super::snapshot::lock_serial;
+use super::status::{SerialIo, SerialPortName};
+use super::timestamp::LOG_TIMESTAMP_FORMAT;
+use crate::error::Result;
+
+/// Time to wait for the port to close after a stop before exiting anyway.
+pub const LOGGER_CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Handle stopping a [`SerialLoggerPlugin`], usable from any thread.
+///
+/// Once stopped, the logger closes the port, flushes the file and exits the
+/// app with [`AppExit::Success`].
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LoggerStop(Arc<AtomicBool>);
+
+impl LoggerStop {
+    /// Asks the logger to close the port and exit.
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the logger was asked to stop.
+    #[must_use]
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Splits received text into lines, each stamped with the time its first
+/// byte arrived.
+#[derive(Debug, Default)]
+pub struct LineStamper {
+    /// Line not ended yet, with the time its first byte arrived.
+    partial: Option<(DateTime<Local>, String)>,
+}
+
+impl LineStamper {
+    /// Adds `text` received at `time` and returns the lines it ends,
+    /// formatted as `[timestamp] line`.
+    pub fn push(&mut self, text: &str, time: DateTime<Local>) -> Vec<String> {
+        let mut lines = Vec::new();
+        for piece in text.split_inclusive('\n') {
+            let (_, line) = self.partial.get_or_insert_with(|| (time, String::new()));
+            line.push_str(piece);
+            if piece.ends_with('\n')
+                && let Some((start, line)) = self.partial.take()
+            {
+                lines.push(stamp_line(start, &line));
+            }
+        }
+        lines
+    }
+
+    /// Returns the line not ended yet, if any, formatted as `[timestamp] line`.
+    pub fn flush(&mut self) -> Option<String> {
+        self.partial
+            .take()
+            .map(|(start, line)| stamp_line(start, &line))
+    }
+}
+
+/// Formats `line` received at `time` as a log file line, without its line ending.
+fn stamp_line(time: DateTime<Local>, line: &str) -> String {
+    format!(
+        "[{}] {}",
+        time.format(LOG_TIMESTAMP_FORMAT),
+        line.trim_end_matches(['\r', '\n'])
+    )
+}
+
+/// Plugin logging the data received on one port to a file.
+///
+/// Requires the [`SerialPlugin`](super::SerialPlugin). A port not discovered
+/// (yet) is added to the port list under the given name.
+pub struct SerialLoggerPlugin {
+    /// Name of the port to log.
+    port_name: String,
+    /// Baud rate to open the port at.
+    baud_rate: u32,
+    /// File the received lines are appended to.
+    output: PathBuf,
+    /// Bytes written to the port once it is open.
+    send: Option<Vec<u8>>,
+    /// Handle stopping the logger.
+    stop: LoggerStop,
+}
+
+impl SerialLoggerPlugin {
+    /// Creates a plugin logging `port_name` opened at `baud_rate` to `output`.
+    #[must_use]
+    pub fn new(port_name: impl Into<String>, baud_rate: u32, output: impl Into<PathBuf>) -> Self {
+        Self {
+            port_name: port_name.into(),
+            baud_rate,
+            output: output.into(),
+            send: None,
+            stop: LoggerStop::default(),
+        }
+    }
+
+    /// Writes `data` to the port once it is open, e.g. a command that starts
+    /// the device's output.
+    #[must_use]
+    pub fn with_send(mut self, data: impl Into<Vec<u8>>) -> Self {
+        self.send = Some(data.into());
+        self
+    }
+
+    /// Returns the handle stopping the logger.
+    #[must_use]
+    pub fn stop_handle(&self) -> LoggerStop {
+        self.stop.clone()
+    }
+}
+
+impl Plugin for SerialLoggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.stop.clone())
+            .insert_resource(SerialLogger {
+                port_name: self.port_name.clone(),
+                baud_rate: self.baud_rate,
+                output: self.output.clone(),
+                send: self.send.clone(),
+                file: None,
+                stamper: LineStamper::default(),
+                phase: LoggerPhase::Waiting,
+            })
+            .add_systems(Update, run_serial_logger.after(receive_serial_data));
+    }
+}
+
+/// Progress of the logger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoggerPhase {
+    /// Waiting for the port thread to accept the open request.
+    Waiting,
+    /// Open requested, waiting for the port to open.
+    Opening,
+    /// Port open, logging.
+    Open,
+    /// Close requested at the given time, waiting for the port to close.
+    Closing(Instant),
+    /// Exit requested.
+    Done,
+}
+
+/// State of the logger.
+#[derive(Resource)]
+struct SerialLogger {
+    /// Name of the port to log.
+    port_name: String,
+    /// Baud rate to open the port at.
+    baud_rate: u32,
+    /// File the received lines are appended to.
+    output: PathBuf,
+    /// Bytes written to the port once it is open, until written.
+    send: Option<Vec<u8>>,
+    /// Output file, once opened.
+    file: Option<BufWriter<File>>,
+    /// Lines being received.
+    stamper: LineStamper,
+    /// Progress of the logger.
+    phase: LoggerPhase,
+}
+
+impl SerialLogger {
+    /// Appends `lines` to the output file.
+    fn write_lines(&mut self, lines: &[String]) {
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let result = lines
+            .iter()
+            .try_for_each(|line| writeln!(file, "{line}"))
+            .and_then(|()| file.flush());
+        if let Err(e) = result {
+            warn!("Failed to write {}: {e}", self.output.display());
+        }
+    }
+
+    /// Writes the line not ended yet and flushes the output file.
+    fn finish(&mut self) {
+        if let Some(line) = self.stamper.flush() {
+            self.write_lines(&[line]);
+        }
+        self.phase = LoggerPhase::Done;
+    }
+}
+
+/// Opens `path` for appending, creating it and its directory if needed.
+fn open_output(path: &Path) -> Result<BufWriter<File>> {
+    if let Some(dir) = path.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(BufWriter::new(file))
+}
+
+/// Adds the port named `port_name` unless discovery already did.
+fn add_port(serials: &mut Serials, port_name: &str) {
+    if serials.port_names().iter().any(|name| name == port_name) {
+        return;
+    }
+    let mut serial = Serial::new();
+    serial.set.port_name = port_name.to_string();
+    serials.add(serial);
+}
+
+/// System: opens the port, appends what it receives to the output file and
+/// closes it once stopped.
+fn run_serial_logger(
+    mut serials: Query<&mut Serials>,
+    ports: Query<(&SerialPortName, &SerialIo)>,
+    mut logger: ResMut<SerialLogger>,
+    stop: Res<LoggerStop>,
+    mut received: MessageReader<PortDataReceived>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let logger = &mut *logger;
+    if logger.phase == LoggerPhase::Done {
+        return;
+    }
+
+    if logger.file.is_none() {
+        match open_output(&logger.output) {
+            Ok(file) => logger.file = Some(file),
+            Err(e) => {
+                error!("Failed to open {}: {e}", logger.output.display());
+                logger.phase = LoggerPhase::Done;
+                exit.write(AppExit::error());
+                return;
+            }
+        }
+    }
+
+    let now = Local::now();
+    let mut lines = Vec::new();
+    for event in received.read() {
+        if event.port_name == logger.port_name {
+            lines.extend(
+                logger
+                    .stamper
+                    .push(&String::from_utf8_lossy(&event.data), now),
+            );
+        }
+    }
+    logger.write_lines(&lines);
+
+    let Some((_, io)) = ports.iter().find(|(name, _)| name.0 == logger.port_name) else {
+        // Its entity is spawned on the next frame.
+        if let Ok(mut serials) = serials.single_mut() {
+            add_port(&mut serials, &logger.port_name);
+        }
+        return;
+    };
+    let mut serial = lock_serial(&io.0);
+
+    if serial.is_error() {
+        let message = serial
+            .data()
+            .last_error()
+            .unwrap_or("port error")
+            .to_string();
+        error!("{}: {message}", logger.port_name);
+        logger.finish();
+        exit.write(AppExit::error());
+        return;
+    }
+
+    if stop.is_stopped() && !matches!(logger.phase, LoggerPhase::Closing(_)) {
+        if logger.phase != LoggerPhase::Waiting
+            && let Err(e) = serial.request_close()
+        {
+            warn!("Failed to close {}: {e}", logger.port_name);
+        }
+        logger.phase = LoggerPhase::Closing(Instant::now());
+    }
+
+    match logger.phase {
+        LoggerPhase::Waiting => {
+            serial.set.baud_rate = logger.baud_rate;
+            // Fails until the port thread has been created.
+            if serial.request_open().is_ok() {
+                logger.phase = LoggerPhase::Opening;
+            }
+        }
+        LoggerPhase::Opening => {
+            if serial.is_open() {
+                info!(
+                    "Logging {} to {}",
+                    logger.port_name,
+                    logger.output.display()
+                );
+                if let Some(data) = logger.send.take() {
+                    serial.data().send_packet(data);
+                }
+                logger.phase = LoggerPhase::Open;
+            }
+        }
+        LoggerPhase::Closing(since) => {
+            if serial.is_close() || since.elapsed() >= LOGGER_CLOSE_TIMEOUT {
+                info!("Closed {}", logger.port_name);
+                logger.finish();
+                exit.write(AppExit::Success);
+            }
+        }
+        LoggerPhase::Open | LoggerPhase::Done => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_line_stamper_stamps_lines_at_first_byte() {
+        let first = Local.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+        let second = first + chrono::Duration::seconds(1);
+        let mut stamper = LineStamper::default();
+
+        assert!(stamper.push("tem", first).is_empty());
+        assert_eq!(
+            stamper.push("p=21\r\nOK\nhum", second),
+            [
+                "[20250102 03:04:05.000] temp=21",
+                "[20250102 03:04:06.000] OK"
+            ]
+        );
+        assert_eq!(
+            stamper.flush().as_deref(),
+            Some("[20250102 03:04:06.000] hum")
+        );
+        assert_eq!(stamper.flush(), None);
+    }
+}
@@ -0,0 +1,384 @@
+//! # Mock Module
+//!
+//! Virtual serial ports for demos and tests.
+//!
+//! A mock port looks exactly like a real port to the rest of the application:
+//! it is opened with the same `PortOpen` command and its bytes flow through the
+//! same read/write loops. Internally it is backed by an in-memory duplex pipe
+//! whose far end is driven by a configurable traffic generator.
+
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bevy::prelude::*;
+use log::{debug, warn};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf};
+use tokio::time::Duration;
+
+use super::port::PortBackend;
+use super::sim::{SimulatedSerial, run_simulated};
+use super::state::SignalState;
+use crate::error::SerialBevyError;
+
+/// Name prefix that identifies virtual (mock) ports.
+pub const MOCK_PORT_PREFIX: &str = "mock://";
+
+/// Name of the demo mock port injected by discovery when enabled.
+pub const MOCK_DEMO_PORT: &str = "mock://demo";
+
+/// Size of the in-memory pipe between the application and the mock device.
+const MOCK_PIPE_SIZE: usize = 4096;
+
+/// Returns true if the port name refers to a mock port.
+#[must_use]
+pub fn is_mock_port(port_name: &str) -> bool {
+    port_name.starts_with(MOCK_PORT_PREFIX)
+}
+
+/// A single step of a scripted mock sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockScriptStep {
+    /// Delay before the data is emitted.
+    pub delay: Duration,
+    /// Bytes emitted by the mock device.
+    pub data: Vec<u8>,
+}
+
+/// Traffic generated by a mock port.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockTraffic {
+    /// Echoes every written byte back.
+    Echo,
+    /// Emits a fixed payload at a fixed interval.
+    Periodic {
+        /// Bytes emitted on every tick.
+        payload: Vec<u8>,
+        /// Time between two payloads.
+        interval: Duration,
+    },
+    /// Plays a scripted sequence once.
+    Script(Vec<MockScriptStep>),
+    /// Answers writes like a simulated device.
+    Simulated(SimulatedSerial),
+}
+
+impl Default for MockTraffic {
+    fn default() -> Self {
+        Self::Periodic {
+            payload: b"Hello from mock://demo\n".to_vec(),
+            interval: Duration::from_millis(1000),
+        }
+    }
+}
+
+impl MockTraffic {
+    /// Loads a scripted sequence from a file.
+    ///
+    /// See [`MockTraffic::parse_script`] for the format.
+    pub fn from_script_file(path: impl AsRef<Path>) -> crate::error::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse_script(&text)
+    }
+
+    /// Parses a scripted sequence.
+    ///
+    /// Each non-empty line has the form `<delay_ms> <text>`: after waiting
+    /// `delay_ms` milliseconds the mock device emits `text` followed by a line
+    /// feed. Lines starting with `#` are comments.
+    pub fn parse_script(text: &str) -> crate::error::Result<Self> {
+        let mut steps = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (delay, payload) = line.split_once(' ').unwrap_or((line, ""));
+            let delay_ms: u64 = delay.trim().parse().map_err(|_| {
+                SerialBevyError::InvalidConfig(format!(
+                    "mock script line {}: invalid delay '{delay}'",
+                    line_no + 1
+                ))
+            })?;
+
+            let mut data = payload.as_bytes().to_vec();
+            data.push(b'\n');
+            steps.push(MockScriptStep {
+                delay: Duration::from_millis(delay_ms),
+                data,
+            });
+        }
+        Ok(Self::Script(steps))
+    }
+}
+
+/// Resource controlling the demo mock port.
+///
+/// When enabled, discovery injects a port named [`MOCK_DEMO_PORT`] that
+/// generates the configured traffic once opened.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct MockPortConfig {
+    /// Whether discovery should inject the demo mock port.
+    pub enabled: bool,
+    /// Traffic generated by the demo mock port.
+    pub traffic: MockTraffic,
+}
+
+impl MockPortConfig {
+    /// Creates an enabled configuration with the given traffic.
+    #[must_use]
+    pub const fn enabled(traffic: MockTraffic) -> Self {
+        Self {
+            enabled: true,
+            traffic,
+        }
+    }
+
+    /// Builds the configuration from command-line arguments.
+    ///
+    /// - `--mock` enables the demo port with the default periodic traffic.
+    /// - `--mock-echo` enables the demo port in echo mode.
+    /// - `--mock-script <path>` enables the demo port playing a script file.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Self {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--mock" => config.enabled = true,
+                "--mock-echo" => {
+                    config.enabled = true;
+                    config.traffic = MockTraffic::Echo;
+                }
+                "--mock-script" => {
+                    let Some(path) = args.next() else {
+                        warn!("--mock-script requires a file path");
+                        continue;
+                    };
+                    match MockTraffic::from_script_file(&path) {
+                        Ok(traffic) => {
+                            config.enabled = true;
+                            config.traffic = traffic;
+                        }
+                        Err(e) => warn!("Failed to load mock script {path}: {e}"),
+                    }
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+}
+
+/// Application-side end of a mock port.
+pub struct MockBackend {
+    /// In-memory pipe connected to the mock device task.
+    stream: DuplexStream,
+}
+
+impl AsyncRead for MockBackend {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for MockBackend {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+impl PortBackend for MockBackend {
+    /// A mock device behaves like a null-modem cable with the handshake lines asserted.
+    fn read_signals(&mut self) -> Option<SignalState> {
+        Some(SignalState {
+            cts: true,
+            dsr: true,
+            dcd: true,
+            ri: false,
+        })
+    }
+}
+
+/// Spawns the device side of a mock port and returns the application side.
+///
+/// Must be called from within a Tokio runtime. The device task exits once the
+/// returned backend is dropped.
+#[must_use]
+pub fn spawn_mock_device(traffic: MockTraffic) -> MockBackend {
+    let (app, device) = tokio::io::duplex(MOCK_PIPE_SIZE);
+    tokio::spawn(async move {
+        let (reader, writer) = tokio::io::split(device);
+        match traffic {
+            MockTraffic::Echo => run_echo(reader, writer).await,
+            MockTraffic::Periodic { payload, interval } => {
+                run_periodic(reader, writer, &payload, interval).await;
+            }
+            MockTraffic::Script(steps) => run_script(reader, writer, steps).await,
+            MockTraffic::Simulated(sim) => run_simulated(reader, writer, sim).await,
+        }
+        debug!("Mock device task exited");
+    });
+    MockBackend { stream: app }
+}
+
+pub(super) type DeviceReader = tokio::io::ReadHalf<DuplexStream>;
+pub(super) type DeviceWriter = tokio::io::WriteHalf<DuplexStream>;
+
+/// Reads (and discards) one chunk written by the application.
+///
+/// Returns false once the application side is closed.
+async fn drain_once(reader: &mut DeviceReader, buffer: &mut [u8]) -> bool {
+    matches!(reader.read(buffer).await, Ok(n) if n > 0)
+}
+
+/// Echoes everything the application writes.
+async fn run_echo(mut reader: DeviceReader, mut writer: DeviceWriter) {
+    let mut buffer = [0u8; 1024];
+    loop {
+        match reader.read(&mut buffer).await {
+            Ok(n) if n > 0 => {
+                if writer.write_all(&buffer[..n]).await.is_err() {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+}
+
+/// Emits `payload` every `interval` while discarding application writes.
+async fn run_periodic(
+    mut reader: DeviceReader,
+    mut writer: DeviceWriter,
+    payload: &[u8],
+    interval: Duration,
+) {
+    let mut buffer = [0u8; 1024];
+    let mut ticker = tokio::time::interval(interval.max(Duration::from_millis(1)));
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if writer.write_all(payload).await.is_err() {
+                    break;
+                }
+            }
+            open = drain_once(&mut reader, &mut buffer) => {
+                if !open {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Plays the scripted steps once while discarding application writes.
+async fn run_script(
+    mut reader: DeviceReader,
+    mut writer: DeviceWriter,
+    steps: Vec<MockScriptStep>,
+) {
+    let mut buffer = [0u8; 1024];
+    for step in steps {
+        let sleep = tokio::time::sleep(step.delay);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                () = &mut sleep => break,
+                open = drain_once(&mut reader, &mut buffer) => {
+                    if !open {
+                        return;
+                    }
+                }
+            }
+        }
+        if writer.write_all(&step.data).await.is_err() {
+            return;
+        }
+    }
+
+    // Keep draining writes until the application closes the port.
+    while drain_once(&mut reader, &mut buffer).await {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_mock_port() {
+        assert!(is_mock_port(MOCK_DEMO_PORT));
+        assert!(!is_mock_port("/dev/ttyUSB0"));
+        assert!(!is_mock_port("COM3"));
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let traffic = MockTraffic::parse_script("# boot log\n0 READY\n\n250 OK\n").unwrap();
+        assert_eq!(
+            traffic,
+            MockTraffic::Script(vec![
+                MockScriptStep {
+                    delay: Duration::from_millis(0),
+                    data: b"READY\n".to_vec(),
+                },
+                MockScriptStep {
+                    delay: Duration::from_millis(250),
+                    data: b"OK\n".to_vec(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_script_invalid_delay() {
+        assert!(MockTraffic::parse_script("soon READY").is_err());
+    }
+
+    #[test]
+    fn test_mock_config_from_args() {
+        let config = MockPortConfig::from_args(Vec::<String>::new());
+        assert!(!config.enabled);
+
+        let config = MockPortConfig::from_args(["--mock".to_string()]);
+        assert!(config.enabled);
+        assert_eq!(config.traffic, MockTraffic::default());
+
+        let config = MockPortConfig::from_args(["--mock-echo".to_string()]);
+        assert!(config.enabled);
+        assert_eq!(config.traffic, MockTraffic::Echo);
+    }
+
+    #[tokio::test]
+    async fn test_echo_device() {
+        let mut backend = spawn_mock_device(MockTraffic::Echo);
+        backend.write_all(b"ping").await.unwrap();
+        let mut buffer = [0u8; 4];
+        backend.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"ping");
+    }
+
+    #[tokio::test]
+    async fn test_script_device() {
+        let traffic = MockTraffic::parse_script("0 A\n5 B\n").unwrap();
+        let mut backend = spawn_mock_device(traffic);
+        let mut buffer = [0u8; 4];
+        backend.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"A\nB\n");
+    }
+}
@@ -7,6 +7,28 @@
 //! - Async read/write operations
 //! - Data encoding/decoding (Hex, UTF-8, etc.)
 //! - Thread-safe communication channels
+//! - Virtual (mock) ports for demos and tests
+//! - Simulated devices for offline testing
+//! - Send sequences for scripted bring-up
+//! - Repeated sends for stress tests
+//! - Packet templates for binary protocols
+//! - A test plugin playing send/expect steps against virtual devices
+//! - Telnet/RFC 2217 proxy for remote access
+//! - Protocol parsers such as NMEA 0183
+//! - Splitting of received data into delimited records
+//! - A merged, chronological traffic log of all ports
+//! - A receive timeout watchdog
+//! - Detection of sending stalled by RTS/CTS or XON/XOFF flow control
+//! - Per-session traffic statistics
+//! - One entity per port, with reflected status components for inspectors
+//! - Latency and throughput measurement against echoing devices
+//! - Response times of commands, matched to the received replies
+//! - A headless logger appending received data to a file
+//! - Session log files, rolled over by size and written on a thread of their own
+//! - Parsing of session logs back into entries
+//! - Replay of session logs as received data
+//! - Session recordings of raw traffic, replayed by virtual ports
+//! - A port opened at startup from the command line or the environment
 //! - LLM integration for AI-assisted chat
 
 // ---------------------------------------------------------------------------
@@ -15,26 +37,80 @@
 pub mod ai;
 pub mod data;
 pub mod data_types;
+pub mod delimiter;
 pub mod discovery;
 pub mod encoding;
+pub mod flow;
 pub mod io;
 pub mod llm;
+pub mod log;
+pub mod log_entry;
+pub mod log_writer;
+pub mod logger;
+pub mod mock;
+pub mod net;
+pub mod packet;
+pub mod parser;
 pub mod port;
 pub mod port_data;
+pub mod recording;
+pub mod repeat;
+pub mod replay;
+pub mod response;
 pub mod selection;
+pub mod sequence;
+pub mod session;
+pub mod sim;
+pub mod snapshot;
+pub mod startup;
 pub mod state;
+pub mod stats;
+pub mod status;
+pub mod testing;
+pub mod throughput;
+pub mod timestamp;
+pub mod traffic;
+pub mod watchdog;
 
 // ---------------------------------------------------------------------------
 // Internal imports needed by this module's definitions
 // ---------------------------------------------------------------------------
-use std::sync::Mutex;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use bevy::prelude::*;
 
-use ai::{process_ai_requests, receive_ai_responses};
+use self::log::LogConfig;
+use ai::{
+    LlmRequestEvent, LlmResponseEvent, process_llm_requests, receive_ai_responses,
+    save_llm_conversations_on_exit,
+};
 use data::{AiChannel, SerialNameChannel};
-use discovery::{Runtime, spawn_port_discovery, update_serial_port_names};
-use io::{create_serial_port_threads, receive_serial_data, send_serial_data};
+use discovery::{
+    DEFAULT_POLL_INTERVAL_MS, DiscoveryConfig, PortScan, Runtime, request_port_scan,
+    shutdown_runtime_on_exit, spawn_port_discovery, update_serial_port_names,
+};
+use flow::{DEFAULT_FLOW_STALL_TIMEOUT, FlowStall, check_flow_stalls};
+use io::{
+    PortDataReceived, create_serial_port_threads, process_incoming, receive_serial_data,
+    reconnect_failed_ports, send_serial_data,
+};
+use mock::{MockPortConfig, MockTraffic};
+use recording::play_binary_recordings;
+use repeat::run_send_repeats;
+use sequence::run_send_sequences;
+use sim::SimulatedSerial;
+use snapshot::{PortSnapshots, lock_serial, update_port_snapshots};
+use startup::{StartupArgs, open_startup_port};
+use state::{PortChannelData, PortState};
+use status::{
+    PortEntities, SerialIo, SerialPortName, SerialSettingsComp, SerialState, SerialStatus,
+    sync_port_entities, update_serial_statuses,
+};
+use traffic::TrafficLog;
+use watchdog::{PortStateChangeEvent, check_receive_timeout};
 
 // ---------------------------------------------------------------------------
 // Public re-exports – maintain backward compatibility for existing consumers
@@ -47,23 +123,35 @@ pub use selection::*;
 // Serials – collection of Mutex‑protected Serial instances
 // ---------------------------------------------------------------------------
 
+/// Shared handle of a port, held by [`Serials`] and by the
+/// [`SerialIo`](status::SerialIo) component of the port's entity.
+pub type SerialHandle = Arc<Mutex<Serial>>;
+
 /// Container for managing multiple serial ports.
 ///
-/// This component holds a collection of serial port instances,
-/// each protected by a mutex for thread-safe access.
+/// This component is the registry ports are added to and removed from, in
+/// list order, each protected by a mutex for thread-safe access. Each port
+/// gets an entity sharing it through its [`SerialIo`](status::SerialIo)
+/// component, see [`sync_port_entities`]; systems working on ports query
+/// those instead.
 #[derive(Component)]
 pub struct Serials {
-    /// Vector of mutex-protected serial port instances.
-    pub serial: Vec<Mutex<Serial>>,
+    /// Mutex-protected serial port instances, in list order.
+    pub serial: Vec<SerialHandle>,
+    /// Names of the ports removed by the user, which discovery does not add
+    /// again.
+    ignored: BTreeSet<String>,
+    /// User-defined order of port names, applied when discovery changes the
+    /// list. See [`Serials::set_port_order`].
+    port_order: Vec<String>,
 }
 
 impl std::fmt::Debug for Serials {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut debug = f.debug_list();
         for serial in &self.serial {
-            if let Ok(s) = serial.lock() {
-                debug.entry(&format!("{}: {}bps", s.set.port_name, s.set.baud_rate));
-            }
+            let s = lock_serial(serial);
+            debug.entry(&format!("{}: {}bps", s.set.port_name, s.set.baud_rate));
         }
         debug.finish()
     }
@@ -79,35 +167,115 @@ impl Serials {
     /// Creates a new empty Serials container.
     #[must_use]
     pub const fn new() -> Self {
-        Self { serial: vec![] }
+        Self {
+            serial: vec![],
+            ignored: BTreeSet::new(),
+            port_order: Vec::new(),
+        }
     }
 
     /// Adds a serial port to the container.
+    ///
+    /// Its entity, spawned by [`sync_port_entities`], gives it the
+    /// [`LogConfig`].
     pub fn add(&mut self, serial: Serial) {
-        self.serial.push(Mutex::new(serial));
+        self.serial.push(Arc::new(Mutex::new(serial)));
+    }
+
+    /// Adds a virtual port unless a port with the same name already exists
+    /// or the port is ignored.
+    pub fn add_mock(&mut self, port_name: &str, traffic: MockTraffic) {
+        if self.is_ignored(port_name) {
+            return;
+        }
+        let already_exists = self
+            .serial
+            .iter()
+            .any(|port| lock_serial(port).set.port_name == port_name);
+
+        if !already_exists {
+            self.add(Serial::new_mock(port_name, traffic));
+        }
+    }
+
+    /// Adds a simulated device as a virtual port.
+    pub fn add_simulated(&mut self, sim: SimulatedSerial) {
+        let port_name = sim.port_name.clone();
+        self.add_mock(&port_name, MockTraffic::Simulated(sim));
+    }
+
+    /// Adds a virtual port replaying the received frames of the session
+    /// recording at `path` once opened, at `speed_multiplier` times the
+    /// recorded speed, and returns its name. See [`session::replay_traffic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recording cannot be read or parsed.
+    pub fn add_session_replay(
+        &mut self,
+        path: &std::path::Path,
+        speed_multiplier: f32,
+    ) -> crate::error::Result<String> {
+        let frames = session::load_session(path)?;
+        let port_name = session::replay_port_name(path);
+        self.add_mock(
+            &port_name,
+            session::replay_traffic(&frames, speed_multiplier),
+        );
+        Ok(port_name)
     }
 
     /// Synchronizes the managed serial ports with the currently discovered port names.
-    pub fn sync_discovered_ports(&mut self, port_names: &[String]) {
+    ///
+    /// Virtual ports are never discovered and are always kept. A port missing
+    /// from the list is only removed once it has been missing from
+    /// `missing_limit` consecutive lists, and never while it is open, so a
+    /// scan that briefly loses a port does not destroy its session. Its port
+    /// thread is stopped when it is removed. Ignored ports are not added.
+    pub fn sync_discovered_ports(&mut self, port_names: &[String], missing_limit: u32) {
         self.serial.retain(|port| {
-            port.lock()
-                .map(|serial| port_names.contains(&serial.set.port_name))
-                .unwrap_or(false)
+            let mut serial = lock_serial(port);
+            if serial.is_mock() || port_names.contains(&serial.set.port_name) {
+                serial.set_missing_scans(0);
+                return true;
+            }
+            let missing_scans = serial.missing_scans() + 1;
+            serial.set_missing_scans(missing_scans);
+            if serial.is_open() || missing_scans < missing_limit {
+                return true;
+            }
+            serial.stop_thread();
+            false
         });
 
         for name in port_names {
-            let already_exists = self.serial.iter().any(|port| {
-                port.lock()
-                    .map(|serial| serial.set.port_name == *name)
-                    .unwrap_or(false)
-            });
+            let already_exists = self
+                .serial
+                .iter()
+                .any(|port| lock_serial(port).set.port_name == *name);
 
-            if !already_exists {
+            if !already_exists && !self.ignored.contains(name) {
                 let mut serial = Serial::new();
                 serial.set.port_name = name.clone();
                 self.add(serial);
             }
         }
+        let order = std::mem::take(&mut self.port_order);
+        self.apply_order(&order);
+        self.port_order = order;
+    }
+
+    /// Records the USB serial numbers reported by discovery, given as
+    /// `(port name, serial number)` pairs.
+    pub fn set_usb_serial_numbers(&mut self, serial_numbers: &[(String, String)]) {
+        for serial in &self.serial {
+            let mut serial = lock_serial(serial);
+            let serial_number = serial_numbers
+                .iter()
+                .find(|(name, _)| *name == serial.set.port_name)
+                .map(|(_, serial_number)| serial_number.clone());
+            serial.set_usb_serial_number(serial_number);
+        }
     }
 
     /// Removes a serial port at the specified index.
@@ -119,6 +287,104 @@ impl Serials {
         self.serial.remove(index);
     }
 
+    /// Removes the port named `port_name` and keeps discovery from adding it
+    /// again until it is [unignored](Self::unignore).
+    ///
+    /// The port thread is stopped; an open port is closed the same way as
+    /// when its thread reports it closed, so its received data is flushed to
+    /// the log and `traffic` and its session ends. Returns false if the port
+    /// was already ignored.
+    pub fn ignore(&mut self, port_name: &str, traffic: &mut TrafficLog) -> bool {
+        if !self.ignored.insert(port_name.to_string()) {
+            return false;
+        }
+        self.serial.retain(|port| {
+            let mut serial = lock_serial(port);
+            if serial.set.port_name != port_name {
+                return true;
+            }
+            serial.stop_thread();
+            if serial.is_open() {
+                process_incoming(
+                    &mut serial,
+                    PortChannelData::PortState(PortState::Close),
+                    traffic,
+                );
+            }
+            false
+        });
+        true
+    }
+
+    /// Lets discovery add the port named `port_name` again. Returns false if
+    /// it was not ignored.
+    pub fn unignore(&mut self, port_name: &str) -> bool {
+        self.ignored.remove(port_name)
+    }
+
+    /// Returns true if the port named `port_name` is ignored.
+    #[must_use]
+    pub fn is_ignored(&self, port_name: &str) -> bool {
+        self.ignored.contains(port_name)
+    }
+
+    /// Returns the names of the ignored ports, sorted.
+    #[must_use]
+    pub fn ignored_ports(&self) -> Vec<String> {
+        self.ignored.iter().cloned().collect()
+    }
+
+    /// Moves the port at index `from` to index `to`, shifting the ports in
+    /// between. Out of range indices are ignored or clamped to the last port.
+    pub fn reorder(&mut self, from: usize, to: usize) {
+        if from >= self.serial.len() {
+            return;
+        }
+        let serial = self.serial.remove(from);
+        let to = to.min(self.serial.len());
+        self.serial.insert(to, serial);
+    }
+
+    /// Returns the port names, in list order.
+    #[must_use]
+    pub fn port_names(&self) -> Vec<String> {
+        self.serial
+            .iter()
+            .map(|serial| lock_serial(serial).set.port_name.clone())
+            .collect()
+    }
+
+    /// Returns the user-defined order of port names.
+    #[must_use]
+    pub fn port_order(&self) -> &[String] {
+        &self.port_order
+    }
+
+    /// Sets the user-defined order of port names and sorts the ports into
+    /// it. Ports added by discovery later are sorted into it as well.
+    pub fn set_port_order(&mut self, order: Vec<String>) {
+        self.apply_order(&order);
+        self.port_order = order;
+    }
+
+    /// Sorts the ports into a user-defined `order` of port names. Ports not
+    /// in `order` keep their relative order after the named ones.
+    pub fn apply_order(&mut self, order: &[String]) {
+        if order.is_empty() {
+            return;
+        }
+        let rank = |serial: &SerialHandle| {
+            let name = lock_serial(serial).set.port_name.clone();
+            order
+                .iter()
+                .position(|order_name| *order_name == name)
+                .unwrap_or(usize::MAX)
+        };
+        if !self.serial.is_sorted_by_key(rank) {
+            self.serial.sort_by_cached_key(rank);
+        }
+    }
+
     /// Gets a reference to the mutex-protected serial port at the specified index.
     ///
     /// # Panics
@@ -144,12 +410,9 @@ impl Serials {
     /// Returns the first managed port name, if any.
     #[must_use]
     pub fn first_port_name(&self) -> Option<String> {
-        self.serial.first().and_then(|serial| {
-            serial
-                .lock()
-                .ok()
-                .map(|serial| serial.set.port_name.clone())
-        })
+        self.serial
+            .first()
+            .map(|serial| lock_serial(serial).set.port_name.clone())
     }
 }
 
@@ -157,6 +420,42 @@ impl Serials {
 // SerialPlugin – Bevy plugin that wires up the serial system
 // ---------------------------------------------------------------------------
 
+/// Default capacity of the channels between the ECS and each port thread.
+pub const DEFAULT_CHANNEL_BUFFER_SIZE: usize = 100;
+
+/// Configuration the [`SerialPlugin`] was built with.
+///
+/// Inserted as a resource so other plugins can read it. The live log and
+/// discovery settings are the [`LogConfig`] and [`DiscoveryConfig`] resources,
+/// which start from these values.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct SerialConfig {
+    /// Capacity of the channels between the ECS and each port thread.
+    pub channel_buffer_size: usize,
+    /// Interval between two automatic polls of the port list.
+    pub discovery_interval: Duration,
+    /// Log directory and size override of all ports.
+    pub log_config: LogConfig,
+    /// Time sending may be paused by flow control before a [`FlowStall`] is
+    /// raised.
+    pub flow_stall_timeout: Duration,
+    /// Whether ports that fail are opened again, see
+    /// [`io::reconnect_failed_ports`].
+    pub reconnect_on_error: bool,
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self {
+            channel_buffer_size: DEFAULT_CHANNEL_BUFFER_SIZE,
+            discovery_interval: Duration::from_millis(DEFAULT_POLL_INTERVAL_MS),
+            log_config: LogConfig::default(),
+            flow_stall_timeout: DEFAULT_FLOW_STALL_TIMEOUT,
+            reconnect_on_error: false,
+        }
+    }
+}
+
 /// The main serial communication plugin.
 ///
 /// This plugin provides:
@@ -164,33 +463,165 @@ impl Serials {
 /// - Async read/write operations
 /// - Port state management
 /// - AI chat integration
+///
+/// Simulated devices added with [`SerialPlugin::with_simulated`] are available
+/// as ports from startup, which lets tests run without hardware.
 #[derive(Default)]
-pub struct SerialPlugin;
+pub struct SerialPlugin {
+    /// Simulated devices added at startup.
+    simulated: Vec<SimulatedSerial>,
+    /// Configuration inserted as the [`SerialConfig`] resource.
+    config: SerialConfig,
+}
+
+impl SerialPlugin {
+    /// Adds a simulated device that is available as a port from startup.
+    #[must_use]
+    pub fn with_simulated(mut self, sim: SimulatedSerial) -> Self {
+        self.simulated.push(sim);
+        self
+    }
+
+    /// Writes the session logs and raw recordings of all ports to `path`
+    /// instead of `logs/`.
+    #[must_use]
+    pub fn with_log_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.log_config.base_dir = path.into();
+        self
+    }
+
+    /// Sets the capacity of the channels between the ECS and each port
+    /// thread; at least 1.
+    #[must_use]
+    pub fn with_channel_buffer_size(mut self, size: usize) -> Self {
+        self.config.channel_buffer_size = size.max(1);
+        self
+    }
+
+    /// Sets the interval between two automatic polls of the port list.
+    #[must_use]
+    pub const fn with_discovery_interval(mut self, interval: Duration) -> Self {
+        self.config.discovery_interval = interval;
+        self
+    }
+
+    /// Sets the time sending may be paused by flow control before a
+    /// [`FlowStall`] is raised.
+    #[must_use]
+    pub const fn with_flow_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.config.flow_stall_timeout = timeout;
+        self
+    }
+
+    /// Opens ports that fail again once they are available, see
+    /// [`io::reconnect_failed_ports`].
+    #[must_use]
+    pub const fn with_reconnect_on_error(mut self, reconnect: bool) -> Self {
+        self.config.reconnect_on_error = reconnect;
+        self
+    }
+
+    /// Returns the configuration the plugin inserts as [`SerialConfig`].
+    #[must_use]
+    pub fn build_config(&self) -> SerialConfig {
+        self.config.clone()
+    }
+}
+
+/// Simulated devices registered on the plugin.
+#[derive(Resource, Default)]
+struct SimulatedPorts(Vec<SimulatedSerial>);
 
 impl Plugin for SerialPlugin {
     fn build(&self, app: &mut App) {
+        let config = self.build_config();
+        // A discovery configuration inserted before the plugin takes precedence.
+        if !app.world().contains_resource::<DiscoveryConfig>() {
+            app.insert_resource(DiscoveryConfig {
+                poll_interval_ms: config.discovery_interval.as_millis() as u64,
+                ..DiscoveryConfig::default()
+            });
+        }
         app.insert_resource(Runtime::init())
+            .insert_resource(SimulatedPorts(self.simulated.clone()))
+            .insert_resource(config.log_config.clone())
+            .insert_resource(config)
             .insert_resource(SerialNameChannel::init())
             .insert_resource(AiChannel::init())
+            .init_resource::<MockPortConfig>()
+            .init_resource::<StartupArgs>()
+            .init_resource::<PortScan>()
+            .init_resource::<TrafficLog>()
+            .init_resource::<PortSnapshots>()
+            .add_message::<PortStateChangeEvent>()
+            .add_message::<PortDataReceived>()
+            .add_message::<FlowStall>()
+            .add_message::<LlmRequestEvent>()
+            .add_message::<LlmResponseEvent>()
+            .init_resource::<PortEntities>()
+            .init_resource::<Selected>()
+            .register_type::<SerialPortName>()
+            .register_type::<SerialStatus>()
+            .register_type::<SerialState>()
+            .register_type::<SerialSettingsComp>()
+            .register_type::<PortState>()
+            .register_type::<data_types::DataType>()
+            .register_type::<status::PortSettingsReflect>()
+            .register_type::<status::ParityReflect>()
+            .register_type::<status::FlowControlReflect>()
+            .register_type::<port::TimeoutAction>()
             .add_systems(Startup, (init_serial_components, spawn_port_discovery))
             .add_systems(
                 Update,
                 (
+                    request_port_scan,
                     update_serial_port_names,
+                    apply_log_config,
+                    sync_port_entities,
+                    select_first_port,
                     create_serial_port_threads,
+                    reconnect_failed_ports,
+                    open_startup_port,
                     send_serial_data,
                     receive_serial_data,
-                    process_ai_requests,
+                    check_receive_timeout,
+                    check_flow_stalls,
+                    run_send_sequences,
+                    run_send_repeats,
+                    play_binary_recordings,
+                    update_port_snapshots,
+                    update_serial_statuses,
+                    process_llm_requests,
                     receive_ai_responses,
                 )
                     .chain(),
-            );
+            )
+            .add_systems(PostUpdate, shutdown_runtime_on_exit)
+            .add_systems(Last, save_llm_conversations_on_exit);
+    }
+}
+
+/// Applies the [`LogConfig`] to all ports when it changes. Ports added later
+/// get it when [`sync_port_entities`] spawns their entity.
+fn apply_log_config(ports: Query<&SerialIo>, config: Res<LogConfig>) {
+    if !config.is_changed() {
+        return;
+    }
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        if serial.data().session_log().config() != &*config {
+            serial.data().session_log_mut().set_config(&config);
+        }
     }
 }
 
 /// Initializes the serial components by spawning a `Serials` entity.
-fn init_serial_components(mut commands: Commands) {
-    commands.spawn(Serials::new());
+fn init_serial_components(mut commands: Commands, simulated: Res<SimulatedPorts>) {
+    let mut serials = Serials::new();
+    for sim in &simulated.0 {
+        serials.add_simulated(sim.clone());
+    }
+    commands.spawn(serials);
 }
 
 // ---------------------------------------------------------------------------
@@ -201,6 +632,34 @@ fn init_serial_components(mut commands: Commands) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_serial_config_resource_after_build() {
+        let mut app = App::new();
+        app.add_plugins(
+            SerialPlugin::default()
+                .with_log_dir("serial_logs")
+                .with_channel_buffer_size(16)
+                .with_discovery_interval(Duration::from_millis(500))
+                .with_flow_stall_timeout(Duration::from_secs(3))
+                .with_reconnect_on_error(true),
+        );
+
+        let config = app.world().resource::<SerialConfig>();
+        assert_eq!(config.channel_buffer_size, 16);
+        assert_eq!(config.discovery_interval, Duration::from_millis(500));
+        assert_eq!(config.flow_stall_timeout, Duration::from_secs(3));
+        assert!(config.reconnect_on_error);
+        assert_eq!(config.log_config.base_dir, PathBuf::from("serial_logs"));
+        assert_eq!(
+            app.world().resource::<LogConfig>(),
+            &app.world().resource::<SerialConfig>().log_config
+        );
+        assert_eq!(
+            app.world().resource::<DiscoveryConfig>().poll_interval_ms,
+            500
+        );
+    }
+
     #[test]
     fn test_serials_new() {
         let serials = Serials::new();
@@ -214,6 +673,267 @@ mod tests {
         assert_eq!(serials.len(), 1);
     }
 
+    #[test]
+    fn test_sync_discovered_ports_keeps_mock_ports() {
+        let mut serials = Serials::new();
+        serials.add_mock(mock::MOCK_DEMO_PORT, MockTraffic::Echo);
+        serials.add_mock(mock::MOCK_DEMO_PORT, MockTraffic::Echo);
+        assert_eq!(serials.len(), 1);
+
+        serials.sync_discovered_ports(&["COM1".to_string()], 1);
+        assert_eq!(serials.len(), 2);
+
+        serials.sync_discovered_ports(&[], 1);
+        assert_eq!(serials.len(), 1);
+        assert_eq!(
+            serials.first_port_name().as_deref(),
+            Some(mock::MOCK_DEMO_PORT)
+        );
+    }
+
+    #[test]
+    fn test_flapping_discovery_keeps_ports() {
+        let com1 = ["COM1".to_string()];
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&com1, 3);
+
+        // Two empty scans in a row are tolerated, and a scan that finds the
+        // port again resets the count.
+        for _ in 0..2 {
+            serials.sync_discovered_ports(&[], 3);
+        }
+        assert_eq!(serials.len(), 1);
+        assert_eq!(serials.get(0).lock().unwrap().missing_scans(), 2);
+        serials.sync_discovered_ports(&com1, 3);
+        assert_eq!(serials.get(0).lock().unwrap().missing_scans(), 0);
+        serials.sync_discovered_ports(&[], 3);
+        serials.sync_discovered_ports(&[], 3);
+        assert_eq!(serials.len(), 1);
+
+        serials.sync_discovered_ports(&[], 3);
+        assert!(serials.is_empty());
+    }
+
+    #[test]
+    fn test_open_port_survives_missing_scans() {
+        let runtime = Runtime::init();
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string()], 3);
+        let (tx, mut rx) = tokio::sync::broadcast::channel(4);
+        {
+            let mut serial = serials.get(0).lock().unwrap();
+            serial.open();
+            *serial.tx_channel() = Some(tx);
+            *serial.thread_handle() =
+                Some(runtime.spawn(std::future::pending::<crate::error::Result<()>>()));
+        }
+
+        for _ in 0..5 {
+            serials.sync_discovered_ports(&[], 3);
+        }
+        assert_eq!(serials.len(), 1);
+        assert!(rx.try_recv().is_err());
+
+        serials.get(0).lock().unwrap().close();
+        *serials.get(0).lock().unwrap().thread_handle() =
+            Some(runtime.spawn(std::future::pending::<crate::error::Result<()>>()));
+        serials.sync_discovered_ports(&[], 3);
+        assert!(serials.is_empty());
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(PortChannelData::PortClose(name)) if name == "COM1"
+        ));
+    }
+
+    #[test]
+    fn test_ignored_port_is_closed_and_not_rediscovered() {
+        let runtime = Runtime::init();
+        let ports = ["COM1".to_string(), "COM2".to_string()];
+        let mut traffic = TrafficLog::default();
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&ports, 1);
+        let (tx, mut rx) = tokio::sync::broadcast::channel(4);
+        {
+            let mut serial = serials.get(0).lock().unwrap();
+            serial.open();
+            *serial.tx_channel() = Some(tx);
+            *serial.thread_handle() =
+                Some(runtime.spawn(std::future::pending::<crate::error::Result<()>>()));
+        }
+
+        assert!(serials.ignore("COM1", &mut traffic));
+        assert!(!serials.ignore("COM1", &mut traffic));
+        assert_eq!(serials.port_names(), ["COM2"]);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(PortChannelData::PortClose(name)) if name == "COM1"
+        ));
+
+        serials.sync_discovered_ports(&ports, 1);
+        assert_eq!(serials.port_names(), ["COM2"]);
+        assert!(serials.ignore(mock::MOCK_DEMO_PORT, &mut traffic));
+        serials.add_mock(mock::MOCK_DEMO_PORT, MockTraffic::Echo);
+        assert_eq!(serials.ignored_ports(), ["COM1", mock::MOCK_DEMO_PORT]);
+
+        assert!(serials.unignore("COM1"));
+        assert!(!serials.unignore("COM1"));
+        serials.sync_discovered_ports(&ports, 1);
+        assert_eq!(serials.port_names(), ["COM2", "COM1"]);
+    }
+
+    fn named_ports(names: &[&str]) -> Serials {
+        let mut serials = Serials::new();
+        for name in names {
+            let mut serial = Serial::new();
+            serial.set.port_name = (*name).to_string();
+            serial.set.baud_rate = name.len() as u32;
+            serials.add(serial);
+        }
+        serials
+    }
+
+    #[test]
+    fn test_reorder_moves_port() {
+        let mut serials = named_ports(&["COM1", "COM2", "COM3", "COM4"]);
+        serials.reorder(0, 2);
+        assert_eq!(serials.port_names(), ["COM2", "COM3", "COM1", "COM4"]);
+        serials.reorder(3, 0);
+        assert_eq!(serials.port_names(), ["COM4", "COM2", "COM3", "COM1"]);
+        serials.reorder(1, 10);
+        assert_eq!(serials.port_names(), ["COM4", "COM3", "COM1", "COM2"]);
+        serials.reorder(10, 0);
+        serials.reorder(2, 2);
+        assert_eq!(serials.port_names(), ["COM4", "COM3", "COM1", "COM2"]);
+
+        // The ports themselves move, settings included.
+        assert_eq!(serials.len(), 4);
+        for index in 0..serials.len() {
+            assert_eq!(serials.get(index).lock().unwrap().set.baud_rate, 4);
+        }
+    }
+
+    #[test]
+    fn test_apply_order_keeps_unknown_ports_last() {
+        let mut serials = named_ports(&["COM1", "COM2", "COM3", "COM4"]);
+        serials.apply_order(&["COM3".to_string(), "gone".to_string(), "COM1".to_string()]);
+        assert_eq!(serials.port_names(), ["COM3", "COM1", "COM2", "COM4"]);
+        serials.apply_order(&[]);
+        assert_eq!(serials.port_names(), ["COM3", "COM1", "COM2", "COM4"]);
+    }
+
+    #[test]
+    fn test_port_order_is_applied_on_discovery() {
+        let mut serials = Serials::new();
+        serials.set_port_order(vec!["COM3".to_string(), "COM1".to_string()]);
+        serials.sync_discovered_ports(&["COM1".to_string(), "COM2".to_string()], 1);
+        assert_eq!(serials.port_names(), ["COM1", "COM2"]);
+
+        serials.sync_discovered_ports(
+            &["COM1".to_string(), "COM2".to_string(), "COM3".to_string()],
+            1,
+        );
+        assert_eq!(serials.port_names(), ["COM3", "COM1", "COM2"]);
+        assert_eq!(serials.port_order(), ["COM3", "COM1"]);
+    }
+
+    #[test]
+    fn test_label_key_prefers_usb_serial_number() {
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["/dev/ttyUSB0".to_string(), "/dev/ttyUSB1".to_string()], 1);
+        serials.set_usb_serial_numbers(&[("/dev/ttyUSB1".to_string(), "A1B2".to_string())]);
+
+        assert_eq!(serials.get(0).lock().unwrap().label_key(), "/dev/ttyUSB0");
+        assert_eq!(serials.get(1).lock().unwrap().label_key(), "usb:A1B2");
+    }
+
+    #[test]
+    fn test_plugin_with_simulated_spawns_port() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let plugin = SerialPlugin::default()
+            .with_simulated(SimulatedSerial::canned("sim://test", vec![b"OK".to_vec()]));
+        let mut world = World::new();
+        world.insert_resource(SimulatedPorts(plugin.simulated));
+        world.run_system_once(init_serial_components).unwrap();
+
+        let mut query = world.query::<&Serials>();
+        let serials = query.single(&world).unwrap();
+        assert_eq!(serials.len(), 1);
+        assert_eq!(serials.first_port_name().as_deref(), Some("sim://test"));
+    }
+
+    #[test]
+    fn test_plugin_with_log_dir_writes_logs_there() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = SerialPlugin::default().with_log_dir(dir.path().join("serial"));
+        let mut serials = Serials::new();
+        serials.add_mock("mock://log", MockTraffic::Echo);
+        let mut world = World::new();
+        status::spawn_ports(&mut world, serials);
+        world.insert_resource(plugin.log_config);
+        world.run_system_once(apply_log_config).unwrap();
+
+        let mut query = world.query::<&SerialIo>();
+        let port = query.single(&world).unwrap();
+        let path = PathBuf::from(port.0.lock().unwrap().start_session_log());
+        assert!(path.starts_with(dir.path().join("serial")));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_log_config_reaches_added_ports_and_resets_size() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let config = LogConfig {
+            base_dir: PathBuf::from("elsewhere"),
+            max_file_size: Some(1024),
+        };
+        let mut world = World::new();
+        world.insert_resource(config.clone());
+        status::spawn_ports(&mut world, named_ports(&["COM1"]));
+        world.run_system_once(apply_log_config).unwrap();
+        let mut serials = world.query::<&mut Serials>();
+        serials
+            .single_mut(&mut world)
+            .unwrap()
+            .add_mock("mock://later", MockTraffic::Echo);
+        world.run_system_once(sync_port_entities).unwrap();
+        let later = world
+            .resource::<PortEntities>()
+            .get("mock://later")
+            .unwrap();
+        let mut ports = world.query::<&SerialIo>();
+        assert_eq!(ports.iter(&world).count(), 2);
+        for port in ports.iter(&world) {
+            let mut serial = port.0.lock().unwrap();
+            assert_eq!(serial.data().session_log().config(), &config);
+            assert_eq!(serial.data().session_log().policy().max_file_size, 1024);
+        }
+
+        world.insert_resource(LogConfig::default());
+        world.run_system_once(apply_log_config).unwrap();
+        let port = world.get::<SerialIo>(later).unwrap();
+        let mut serial = port.0.lock().unwrap();
+        assert_eq!(
+            serial.data().session_log().policy().max_file_size,
+            super::log::LogPolicy::default().max_file_size
+        );
+    }
+
+    #[test]
+    fn test_serials_add_simulated() {
+        let mut serials = Serials::new();
+        serials.add_simulated(SimulatedSerial::canned("sim://test", vec![]));
+        let serial = serials.get(0).lock().unwrap();
+        assert!(serial.is_mock());
+        assert!(matches!(
+            serial.mock_traffic(),
+            Some(MockTraffic::Simulated(sim)) if sim.port_name == "sim://test"
+        ));
+    }
+
     #[test]
     fn test_runtime_creation() {
         let runtime = Runtime::init();
@@ -0,0 +1,738 @@
+//! # Network Module
+//!
+//! Telnet proxy giving remote access to a serial port, with RFC 2217 COM port
+//! control.
+//!
+//! The proxy listens on a TCP address and forwards bytes between one client
+//! and a port's channels: client data is written with
+//! [`PortChannelData::ProxyWrite`] and data read from the port is sent back to
+//! the client. RFC 2217 clients (e.g. pyserial's `rfc2217://` URLs) can change
+//! the baud rate, data bits, parity and stop bits of the port.
+
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+use bevy::prelude::*;
+use log::{debug, error, info, warn};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+
+use super::discovery::Runtime;
+use super::io::receive_serial_data;
+use super::port::{DataBits, Parity, PortSettings, StopBits};
+use super::snapshot::lock_serial;
+use super::state::{PortChannelData, PortRwData, PortState};
+use super::status::{SerialIo, SerialPortName};
+
+/// Interpret As Command.
+const IAC: u8 = 255;
+/// End of subnegotiation.
+const SE: u8 = 240;
+/// Start of subnegotiation.
+const SB: u8 = 250;
+const WILL: u8 = 251;
+const WONT: u8 = 252;
+const DO: u8 = 253;
+const DONT: u8 = 254;
+
+/// Telnet binary transmission option (RFC 856).
+const OPT_BINARY: u8 = 0;
+/// Telnet suppress go-ahead option (RFC 858).
+const OPT_SGA: u8 = 3;
+/// Telnet COM port control option (RFC 2217).
+const OPT_COM_PORT: u8 = 44;
+
+/// RFC 2217 client commands; the server answers with the command + 100.
+const SET_BAUDRATE: u8 = 1;
+const SET_DATASIZE: u8 = 2;
+const SET_PARITY: u8 = 3;
+const SET_STOPSIZE: u8 = 4;
+const SERVER_OFFSET: u8 = 100;
+
+/// Telnet proxy configuration.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct TelnetProxyConfig {
+    /// Address the proxy listens on.
+    pub listen_addr: SocketAddr,
+    /// Name of the port the proxy forwards to.
+    pub port_name: String,
+}
+
+/// Bevy plugin exposing a serial port through a Telnet/RFC 2217 proxy.
+///
+/// Requires [`SerialPlugin`](super::SerialPlugin). The proxy serves one client
+/// at a time while the port is open and disconnects it when the port closes.
+pub struct TelnetProxyPlugin {
+    /// Proxy configuration.
+    config: TelnetProxyConfig,
+}
+
+impl TelnetProxyPlugin {
+    /// Creates a proxy forwarding connections on `addr` to the port `name`.
+    #[must_use]
+    pub fn with_port(name: &str, addr: SocketAddr) -> Self {
+        Self {
+            config: TelnetProxyConfig {
+                listen_addr: addr,
+                port_name: name.to_string(),
+            },
+        }
+    }
+}
+
+impl Plugin for TelnetProxyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .add_systems(Startup, start_telnet_proxy)
+            .add_systems(Update, update_telnet_proxy.after(receive_serial_data));
+    }
+}
+
+/// Channels of an open port handed to the proxy task.
+struct ProxyLink {
+    /// Command channel of the port thread.
+    tx: broadcast::Sender<PortChannelData>,
+    /// Subscription to the data read from the port.
+    rx: broadcast::Receiver<PortChannelData>,
+    /// Port settings when the link was made.
+    settings: PortSettings,
+}
+
+/// Events sent from the proxy task to the Bevy world.
+#[derive(Debug)]
+enum ProxyEvent {
+    /// An RFC 2217 client changed the line settings.
+    Configure(PortSettings),
+}
+
+/// Runtime state of the proxy on the Bevy side.
+#[derive(Resource)]
+struct TelnetProxy {
+    /// Hands the channels of a newly opened port to the proxy task.
+    links: mpsc::UnboundedSender<ProxyLink>,
+    /// Events from the proxy task.
+    events: Mutex<std::sync::mpsc::Receiver<ProxyEvent>>,
+    /// Whether the current port session has been handed to the proxy task.
+    attached: bool,
+}
+
+/// System: bind the proxy listener and spawn the proxy task.
+fn start_telnet_proxy(
+    mut commands: Commands,
+    config: Res<TelnetProxyConfig>,
+    runtime: Res<Runtime>,
+) {
+    let (link_tx, link_rx) = mpsc::unbounded_channel();
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    let addr = config.listen_addr;
+    let port_name = config.port_name.clone();
+
+    runtime.spawn(async move {
+        match TcpListener::bind(addr).await {
+            Ok(listener) => {
+                info!("Telnet proxy for {port_name} listening on {addr}");
+                run_proxy(listener, link_rx, event_tx).await;
+            }
+            Err(e) => error!("Failed to start Telnet proxy on {addr}: {e}"),
+        }
+    });
+
+    commands.insert_resource(TelnetProxy {
+        links: link_tx,
+        events: Mutex::new(event_rx),
+        attached: false,
+    });
+}
+
+/// System: hand newly opened port sessions to the proxy and apply RFC 2217
+/// settings changes.
+fn update_telnet_proxy(
+    ports: Query<(&SerialPortName, &SerialIo)>,
+    config: Res<TelnetProxyConfig>,
+    proxy: Option<ResMut<TelnetProxy>>,
+) {
+    let Some(mut proxy) = proxy else {
+        return;
+    };
+    let Some((_, port)) = ports.iter().find(|(name, _)| name.0 == config.port_name) else {
+        return;
+    };
+    let mut serial = lock_serial(&port.0);
+
+    let events: Vec<ProxyEvent> = proxy
+        .events
+        .lock()
+        .map(|events| events.try_iter().collect())
+        .unwrap_or_default();
+    for event in events {
+        match event {
+            ProxyEvent::Configure(settings) => {
+                serial.set.baud_rate = settings.baud_rate;
+                serial.set.data_bits = settings.data_bits;
+                serial.set.parity = settings.parity;
+                serial.set.stop_bits = settings.stop_bits;
+                let settings = serial.set.clone();
+                if serial.is_open()
+                    && let Some(tx) = serial.tx_channel()
+                {
+                    let _ = tx.send(PortChannelData::PortReconfigure(settings));
+                }
+            }
+        }
+    }
+
+    if !serial.is_open() {
+        proxy.attached = false;
+        return;
+    }
+    if proxy.attached {
+        return;
+    }
+    let settings = serial.set.clone();
+    let tx = serial.tx_channel().clone();
+    let rx = serial
+        .rx_channel()
+        .as_ref()
+        .map(broadcast::Receiver::resubscribe);
+    if let (Some(tx), Some(rx)) = (tx, rx) {
+        proxy.attached = proxy.links.send(ProxyLink { tx, rx, settings }).is_ok();
+    }
+}
+
+/// Serves clients for each port session handed over on `links`.
+async fn run_proxy(
+    listener: TcpListener,
+    mut links: mpsc::UnboundedReceiver<ProxyLink>,
+    events: std::sync::mpsc::Sender<ProxyEvent>,
+) {
+    while let Some(mut link) = links.recv().await {
+        // Older links belong to sessions that have already closed.
+        while let Ok(newer) = links.try_recv() {
+            link = newer;
+        }
+        serve_link(&listener, &mut link, &events).await;
+    }
+}
+
+/// Accepts clients one at a time until the port closes.
+async fn serve_link(
+    listener: &TcpListener,
+    link: &mut ProxyLink,
+    events: &std::sync::mpsc::Sender<ProxyEvent>,
+) {
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, addr)) = accepted else {
+                    continue;
+                };
+                info!("Telnet proxy client connected: {addr}");
+                let port_closed = serve_client(listener, stream, link, events).await;
+                info!("Telnet proxy client disconnected: {addr}");
+                if port_closed {
+                    return;
+                }
+            }
+            message = link.rx.recv() => {
+                if is_port_closed(&message) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Forwards data between one client and the port.
+///
+/// Returns true if the session ended because the port closed, false if the
+/// client disconnected.
+async fn serve_client(
+    listener: &TcpListener,
+    stream: TcpStream,
+    link: &mut ProxyLink,
+    events: &std::sync::mpsc::Sender<ProxyEvent>,
+) -> bool {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut session = TelnetSession::new(link.settings.clone());
+    let mut buffer = [0u8; 1024];
+
+    loop {
+        tokio::select! {
+            result = reader.read(&mut buffer) => {
+                let n = match result {
+                    Ok(n) if n > 0 => n,
+                    _ => return false,
+                };
+                let output = session.feed(&buffer[..n]);
+                if !output.reply.is_empty() && writer.write_all(&output.reply).await.is_err() {
+                    return false;
+                }
+                if !output.data.is_empty() {
+                    let data = PortRwData { data: output.data };
+                    let _ = link.tx.send(PortChannelData::ProxyWrite(data));
+                }
+                if let Some(settings) = output.settings {
+                    let _ = events.send(ProxyEvent::Configure(settings));
+                }
+            }
+            accepted = listener.accept() => {
+                if let Ok((_, addr)) = accepted {
+                    warn!("Telnet proxy is busy, rejecting {addr}");
+                }
+            }
+            message = link.rx.recv() => {
+                if let Ok(PortChannelData::PortRead(data)) = &message {
+                    if writer.write_all(&escape_iac(&data.data)).await.is_err() {
+                        return false;
+                    }
+                } else if is_port_closed(&message) {
+                    let _ = writer.shutdown().await;
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if a port thread message means the port stopped.
+fn is_port_closed(message: &Result<PortChannelData, broadcast::error::RecvError>) -> bool {
+    matches!(
+        message,
+        Ok(PortChannelData::PortState(
+            PortState::Close | PortState::Error
+        )) | Err(broadcast::error::RecvError::Closed)
+    )
+}
+
+/// Doubles IAC bytes so data is not mistaken for Telnet commands.
+#[must_use]
+pub fn escape_iac(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    for &byte in data {
+        out.push(byte);
+        if byte == IAC {
+            out.push(IAC);
+        }
+    }
+    out
+}
+
+/// Result of feeding client bytes to a [`TelnetSession`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TelnetOutput {
+    /// Data to write to the port.
+    pub data: Vec<u8>,
+    /// Bytes to send back to the client.
+    pub reply: Vec<u8>,
+    /// New line settings, if the client changed them.
+    pub settings: Option<PortSettings>,
+}
+
+/// Parser state between two reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParseState {
+    Data,
+    Iac,
+    Negotiate(u8),
+    Sub,
+    SubIac,
+}
+
+/// Telnet protocol state of one client connection.
+///
+/// Strips Telnet commands from the client data, answers option negotiation
+/// and handles RFC 2217 `SET-BAUDRATE`, `SET-DATASIZE`, `SET-PARITY` and
+/// `SET-STOPSIZE` requests.
+pub struct TelnetSession {
+    /// Current line settings.
+    settings: PortSettings,
+    /// Parser state.
+    state: ParseState,
+    /// Subnegotiation payload being collected.
+    sub: Vec<u8>,
+    /// Options the client has enabled on its side.
+    remote: BTreeSet<u8>,
+    /// Options enabled on the proxy side.
+    local: BTreeSet<u8>,
+    /// Whether the previous data byte was a CR (for CR NUL in non-binary mode).
+    last_cr: bool,
+}
+
+impl TelnetSession {
+    /// Creates a session for a port with `settings`.
+    #[must_use]
+    pub const fn new(settings: PortSettings) -> Self {
+        Self {
+            settings,
+            state: ParseState::Data,
+            sub: Vec::new(),
+            remote: BTreeSet::new(),
+            local: BTreeSet::new(),
+            last_cr: false,
+        }
+    }
+
+    /// Gets the current line settings.
+    #[must_use]
+    pub const fn settings(&self) -> &PortSettings {
+        &self.settings
+    }
+
+    /// Processes bytes received from the client.
+    pub fn feed(&mut self, input: &[u8]) -> TelnetOutput {
+        let mut output = TelnetOutput::default();
+        for &byte in input {
+            self.state = match self.state {
+                ParseState::Data if byte == IAC => ParseState::Iac,
+                ParseState::Data => {
+                    self.push_data(&mut output, byte);
+                    ParseState::Data
+                }
+                ParseState::Iac => match byte {
+                    IAC => {
+                        self.push_data(&mut output, IAC);
+                        ParseState::Data
+                    }
+                    WILL | WONT | DO | DONT => ParseState::Negotiate(byte),
+                    SB => {
+                        self.sub.clear();
+                        ParseState::Sub
+                    }
+                    // NOP, GA and other commands carry no data.
+                    _ => ParseState::Data,
+                },
+                ParseState::Negotiate(verb) => {
+                    self.negotiate(&mut output, verb, byte);
+                    ParseState::Data
+                }
+                ParseState::Sub if byte == IAC => ParseState::SubIac,
+                ParseState::Sub => {
+                    self.sub.push(byte);
+                    ParseState::Sub
+                }
+                ParseState::SubIac => match byte {
+                    IAC => {
+                        self.sub.push(IAC);
+                        ParseState::Sub
+                    }
+                    SE => {
+                        self.subnegotiate(&mut output);
+                        ParseState::Data
+                    }
+                    _ => ParseState::Data,
+                },
+            };
+        }
+        output
+    }
+
+    /// Appends a data byte, dropping the NUL of a CR NUL pair outside binary mode.
+    fn push_data(&mut self, output: &mut TelnetOutput, byte: u8) {
+        let skip = byte == 0 && self.last_cr && !self.remote.contains(&OPT_BINARY);
+        self.last_cr = byte == b'\r';
+        if !skip {
+            output.data.push(byte);
+        }
+    }
+
+    /// Answers a WILL/WONT/DO/DONT request, replying only when the state changes.
+    fn negotiate(&mut self, output: &mut TelnetOutput, verb: u8, option: u8) {
+        let supported = matches!(option, OPT_BINARY | OPT_SGA | OPT_COM_PORT);
+        let reply = match verb {
+            WILL if !supported => Some(DONT),
+            WILL => self.remote.insert(option).then_some(DO),
+            WONT => self.remote.remove(&option).then_some(DONT),
+            DO if !supported || option == OPT_COM_PORT => Some(WONT),
+            DO => self.local.insert(option).then_some(WILL),
+            DONT => self.local.remove(&option).then_some(WONT),
+            _ => None,
+        };
+        if let Some(reply) = reply {
+            output.reply.extend([IAC, reply, option]);
+        }
+    }
+
+    /// Handles a complete `IAC SB ... IAC SE` payload.
+    fn subnegotiate(&mut self, output: &mut TelnetOutput) {
+        let sub = std::mem::take(&mut self.sub);
+        let [OPT_COM_PORT, command, value @ ..] = sub.as_slice() else {
+            return;
+        };
+        let command = *command;
+        let before = self.settings.clone();
+        let response = match command {
+            SET_BAUDRATE => {
+                if let Ok(bytes) = <[u8; 4]>::try_from(value) {
+                    let baud_rate = u32::from_be_bytes(bytes);
+                    if baud_rate != 0 {
+                        self.settings.baud_rate = baud_rate;
+                    }
+                }
+                self.settings.baud_rate.to_be_bytes().to_vec()
+            }
+            SET_DATASIZE => {
+                if let Some(data_bits) = value.first().and_then(|&v| data_bits_from_rfc2217(v)) {
+                    self.settings.data_bits = data_bits;
+                }
+                vec![data_bits_to_rfc2217(self.settings.data_bits)]
+            }
+            SET_PARITY => {
+                if let Some(parity) = value.first().and_then(|&v| parity_from_rfc2217(v)) {
+                    self.settings.parity = parity;
+                }
+                vec![parity_to_rfc2217(self.settings.parity)]
+            }
+            SET_STOPSIZE => {
+                if let Some(stop_bits) = value.first().and_then(|&v| stop_bits_from_rfc2217(v)) {
+                    self.settings.stop_bits = stop_bits;
+                }
+                vec![stop_bits_to_rfc2217(self.settings.stop_bits)]
+            }
+            _ => {
+                debug!("Ignoring RFC 2217 command {command}");
+                return;
+            }
+        };
+
+        output
+            .reply
+            .extend([IAC, SB, OPT_COM_PORT, command + SERVER_OFFSET]);
+        output.reply.extend(escape_iac(&response));
+        output.reply.extend([IAC, SE]);
+
+        let changed = before.baud_rate != self.settings.baud_rate
+            || before.data_bits != self.settings.data_bits
+            || before.parity != self.settings.parity
+            || before.stop_bits != self.settings.stop_bits;
+        if changed {
+            output.settings = Some(self.settings.clone());
+        }
+    }
+}
+
+/// Maps an RFC 2217 data size; `0` (query) and unsupported sizes map to `None`.
+const fn data_bits_from_rfc2217(value: u8) -> Option<DataBits> {
+    match value {
+        5 => Some(DataBits::Five),
+        6 => Some(DataBits::Six),
+        7 => Some(DataBits::Seven),
+        8 => Some(DataBits::Eight),
+        _ => None,
+    }
+}
+
+const fn data_bits_to_rfc2217(data_bits: DataBits) -> u8 {
+    match data_bits {
+        DataBits::Five => 5,
+        DataBits::Six => 6,
+        DataBits::Seven => 7,
+        DataBits::Eight => 8,
+    }
+}
+
+/// Maps an RFC 2217 parity; MARK and SPACE are not supported by the port.
+const fn parity_from_rfc2217(value: u8) -> Option<Parity> {
+    match value {
+        1 => Some(Parity::None),
+        2 => Some(Parity::Odd),
+        3 => Some(Parity::Even),
+        _ => None,
+    }
+}
+
+const fn parity_to_rfc2217(parity: Parity) -> u8 {
+    match parity {
+        Parity::None => 1,
+        Parity::Odd => 2,
+        Parity::Even => 3,
+    }
+}
+
+/// Maps an RFC 2217 stop size; 1.5 stop bits are not supported by the port.
+const fn stop_bits_from_rfc2217(value: u8) -> Option<StopBits> {
+    match value {
+        1 => Some(StopBits::One),
+        2 => Some(StopBits::Two),
+        _ => None,
+    }
+}
+
+const fn stop_bits_to_rfc2217(stop_bits: StopBits) -> u8 {
+    match stop_bits {
+        StopBits::One => 1,
+        StopBits::Two => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an RFC 2217 subnegotiation request.
+    fn com_port_request(command: u8, value: &[u8]) -> Vec<u8> {
+        let mut request = vec![IAC, SB, OPT_COM_PORT, command];
+        request.extend(escape_iac(value));
+        request.extend([IAC, SE]);
+        request
+    }
+
+    #[test]
+    fn test_plain_data_and_escaped_iac() {
+        let mut session = TelnetSession::new(PortSettings::default());
+        let output = session.feed(&[b'A', IAC, IAC, b'B']);
+        assert_eq!(output.data, [b'A', IAC, b'B']);
+        assert!(output.reply.is_empty());
+    }
+
+    #[test]
+    fn test_command_split_across_reads() {
+        let mut session = TelnetSession::new(PortSettings::default());
+        assert_eq!(session.feed(&[b'x', IAC]).data, b"x");
+        let output = session.feed(&[WILL, OPT_COM_PORT, b'y']);
+        assert_eq!(output.data, b"y");
+        assert_eq!(output.reply, [IAC, DO, OPT_COM_PORT]);
+    }
+
+    #[test]
+    fn test_negotiation_replies_once() {
+        let mut session = TelnetSession::new(PortSettings::default());
+        assert_eq!(
+            session.feed(&[IAC, WILL, OPT_COM_PORT]).reply,
+            [IAC, DO, OPT_COM_PORT]
+        );
+        assert!(session.feed(&[IAC, WILL, OPT_COM_PORT]).reply.is_empty());
+        // Unsupported options are refused.
+        assert_eq!(session.feed(&[IAC, WILL, 24]).reply, [IAC, DONT, 24]);
+        assert_eq!(session.feed(&[IAC, DO, 1]).reply, [IAC, WONT, 1]);
+    }
+
+    #[test]
+    fn test_set_baud_rate() {
+        let mut session = TelnetSession::new(PortSettings::default());
+        let output = session.feed(&com_port_request(SET_BAUDRATE, &9600u32.to_be_bytes()));
+        let settings = output.settings.expect("settings changed");
+        assert_eq!(settings.baud_rate, 9600);
+
+        let mut expected = vec![IAC, SB, OPT_COM_PORT, SET_BAUDRATE + SERVER_OFFSET];
+        expected.extend(9600u32.to_be_bytes());
+        expected.extend([IAC, SE]);
+        assert_eq!(output.reply, expected);
+    }
+
+    #[test]
+    fn test_query_does_not_change_settings() {
+        let mut session = TelnetSession::new(PortSettings::default());
+        let output = session.feed(&com_port_request(SET_BAUDRATE, &[0, 0, 0, 0]));
+        assert!(output.settings.is_none());
+        assert!(output.reply.ends_with(&[0, 1, 0xC2, 0, IAC, SE]));
+
+        let output = session.feed(&com_port_request(SET_DATASIZE, &[0]));
+        assert!(output.settings.is_none());
+        assert_eq!(output.reply[4], 8);
+    }
+
+    #[test]
+    fn test_set_data_size_parity_and_stop_bits() {
+        let mut session = TelnetSession::new(PortSettings::default());
+        session.feed(&com_port_request(SET_DATASIZE, &[7]));
+        session.feed(&com_port_request(SET_PARITY, &[3]));
+        let output = session.feed(&com_port_request(SET_STOPSIZE, &[2]));
+        assert!(output.settings.is_some());
+        assert_eq!(session.settings().data_bits, DataBits::Seven);
+        assert_eq!(session.settings().parity, Parity::Even);
+        assert_eq!(session.settings().stop_bits, StopBits::Two);
+
+        // MARK parity is not supported, so the current parity is reported.
+        let output = session.feed(&com_port_request(SET_PARITY, &[4]));
+        assert!(output.settings.is_none());
+        assert_eq!(output.reply[4], 3);
+    }
+
+    #[test]
+    fn test_cr_nul_outside_binary_mode() {
+        let mut session = TelnetSession::new(PortSettings::default());
+        assert_eq!(session.feed(b"a\r\0b").data, b"a\rb");
+
+        session.feed(&[IAC, WILL, OPT_BINARY]);
+        assert_eq!(session.feed(b"a\r\0b").data, b"a\r\0b");
+    }
+
+    #[tokio::test]
+    async fn test_proxy_forwards_until_port_closes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (link_tx, link_rx) = mpsc::unbounded_channel();
+        let (event_tx, _event_rx) = std::sync::mpsc::channel();
+        tokio::spawn(run_proxy(listener, link_rx, event_tx));
+
+        // Stand-ins for the port thread's command and data channels.
+        let (port_tx, mut port_commands) = broadcast::channel(16);
+        let (port_data, port_rx) = broadcast::channel(16);
+        link_tx
+            .send(ProxyLink {
+                tx: port_tx,
+                rx: port_rx,
+                settings: PortSettings::default(),
+            })
+            .unwrap();
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"AT").await.unwrap();
+        match port_commands.recv().await.unwrap() {
+            PortChannelData::ProxyWrite(data) => assert_eq!(data.data, b"AT"),
+            other => panic!("expected proxied write, got {other:?}"),
+        }
+
+        port_data
+            .send(PortChannelData::PortRead(PortRwData {
+                data: vec![b'O', b'K', IAC],
+            }))
+            .unwrap();
+        let mut buffer = [0u8; 4];
+        client.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(buffer, [b'O', b'K', IAC, IAC]);
+
+        port_data
+            .send(PortChannelData::PortState(PortState::Close))
+            .unwrap();
+        assert_eq!(client.read(&mut buffer).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_proxy_settings_reach_the_port_entity() {
+        use crate::serial::Serials;
+        use crate::serial::status::spawn_ports;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string(), "COM2".to_string()], 1);
+        let com2 = serials.serial[1].clone();
+        let mut world = World::new();
+        spawn_ports(&mut world, serials);
+        let (links, _link_rx) = mpsc::unbounded_channel();
+        let (event_tx, event_rx) = std::sync::mpsc::channel();
+        world.insert_resource(TelnetProxy {
+            links,
+            events: Mutex::new(event_rx),
+            attached: true,
+        });
+        world.insert_resource(TelnetProxyConfig {
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            port_name: "COM2".to_string(),
+        });
+
+        let mut settings = PortSettings::discovered("COM2");
+        settings.baud_rate = 9600;
+        settings.parity = Parity::Even;
+        event_tx.send(ProxyEvent::Configure(settings)).unwrap();
+        world.run_system_once(update_telnet_proxy).unwrap();
+
+        let serial = lock_serial(&com2);
+        assert_eq!(serial.set.baud_rate, 9600);
+        assert_eq!(serial.set.parity, Parity::Even);
+        // The port is closed, so the next session is handed over again.
+        assert!(!world.resource::<TelnetProxy>().attached);
+    }
+}
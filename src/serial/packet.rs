@@ -0,0 +1,195 @@
+//! # Packet Module
+//!
+//! Packet templates for binary protocols: a named list of fixed-size integer
+//! fields, packed in order into the bytes of one packet.
+
+use serde::{Deserialize, Serialize};
+
+/// Largest field size, in bytes.
+pub const MAX_FIELD_BYTES: u8 = 8;
+
+/// Byte order of a field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Endian {
+    /// Least significant byte first.
+    #[default]
+    Little,
+    /// Most significant byte first.
+    Big,
+}
+
+impl Endian {
+    /// Both byte orders, in display order.
+    pub const ALL: [Self; 2] = [Self::Little, Self::Big];
+
+    /// Returns the short display name, e.g. `LE`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Little => "LE",
+            Self::Big => "BE",
+        }
+    }
+}
+
+/// One integer field of a packet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketField {
+    /// Field name, e.g. `length`.
+    pub name: String,
+    /// Size in bytes, at most [`MAX_FIELD_BYTES`].
+    pub size_bytes: u8,
+    /// Value; bits beyond the field size are dropped when encoding.
+    pub value: u64,
+    /// Byte order.
+    #[serde(default)]
+    pub endian: Endian,
+}
+
+impl Default for PacketField {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            size_bytes: 1,
+            value: 0,
+            endian: Endian::default(),
+        }
+    }
+}
+
+impl PacketField {
+    /// Returns the field size in bytes, capped at [`MAX_FIELD_BYTES`].
+    #[must_use]
+    pub fn len(&self) -> usize {
+        usize::from(self.size_bytes.min(MAX_FIELD_BYTES))
+    }
+
+    /// Returns true if the field has no bytes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the largest value that fits in the field.
+    #[must_use]
+    pub fn max_value(&self) -> u64 {
+        match self.len() {
+            0 => 0,
+            len if len >= 8 => u64::MAX,
+            len => (1 << (len * 8)) - 1,
+        }
+    }
+
+    /// Appends the field's bytes to `packet`.
+    pub fn encode_into(&self, packet: &mut Vec<u8>) {
+        let len = self.len();
+        match self.endian {
+            Endian::Little => packet.extend_from_slice(&self.value.to_le_bytes()[..len]),
+            Endian::Big => packet.extend_from_slice(&self.value.to_be_bytes()[8 - len..]),
+        }
+    }
+}
+
+/// A named list of fields making up one packet.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PacketTemplate {
+    /// Name shown in the templates panel.
+    #[serde(default)]
+    pub name: String,
+    /// Fields, in packet order.
+    #[serde(default)]
+    pub fields: Vec<PacketField>,
+}
+
+impl PacketTemplate {
+    /// Packs the fields in order, each in its own byte order.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(self.fields.iter().map(PacketField::len).sum());
+        for field in &self.fields {
+            field.encode_into(&mut packet);
+        }
+        packet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(size_bytes: u8, value: u64, endian: Endian) -> PacketField {
+        PacketField {
+            name: String::new(),
+            size_bytes,
+            value,
+            endian,
+        }
+    }
+
+    #[test]
+    fn test_little_endian_encoding() {
+        let template = PacketTemplate {
+            name: "le".to_string(),
+            fields: vec![
+                field(1, 0xAA, Endian::Little),
+                field(2, 0x1234, Endian::Little),
+                field(4, 0xDEAD_BEEF, Endian::Little),
+            ],
+        };
+        assert_eq!(
+            template.encode(),
+            [0xAA, 0x34, 0x12, 0xEF, 0xBE, 0xAD, 0xDE]
+        );
+    }
+
+    #[test]
+    fn test_big_endian_encoding() {
+        let template = PacketTemplate {
+            name: "be".to_string(),
+            fields: vec![
+                field(2, 0x1234, Endian::Big),
+                field(3, 0x0A_0B0C, Endian::Big),
+                field(8, 1, Endian::Big),
+            ],
+        };
+        assert_eq!(
+            template.encode(),
+            [0x12, 0x34, 0x0A, 0x0B, 0x0C, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_values_are_truncated_to_field_size() {
+        assert_eq!(
+            PacketTemplate {
+                name: String::new(),
+                fields: vec![
+                    field(1, 0x1FF, Endian::Big),
+                    field(2, 0x12_3456, Endian::Little),
+                    field(0, 7, Endian::Little),
+                    field(12, 2, Endian::Little),
+                ],
+            }
+            .encode(),
+            [0xFF, 0x56, 0x34, 2, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(field(2, 0, Endian::Little).max_value(), 0xFFFF);
+        assert_eq!(field(8, 0, Endian::Little).max_value(), u64::MAX);
+    }
+
+    #[test]
+    fn test_template_round_trips_through_toml() {
+        let template = PacketTemplate {
+            name: "ping".to_string(),
+            fields: vec![
+                PacketField {
+                    name: "header".to_string(),
+                    ..field(2, 0x55AA, Endian::Big)
+                },
+                field(1, 3, Endian::Little),
+            ],
+        };
+        let text = toml::to_string(&template).unwrap();
+        assert_eq!(toml::from_str::<PacketTemplate>(&text).unwrap(), template);
+    }
+}
@@ -0,0 +1,317 @@
+//! # Parser Module
+//!
+//! Protocol parsers that turn the received byte stream into structured data.
+//!
+//! Parsers implement [`DataProcessor`] and are fed every chunk read from a
+//! port, in order. They keep their own line buffers, so a message may be
+//! split across any number of chunks.
+
+use std::collections::BTreeMap;
+
+/// Maximum length of a buffered line; longer input is not NMEA and is dropped.
+const MAX_LINE_LEN: usize = 1024;
+
+/// A parser fed with the raw bytes received on a port.
+pub trait DataProcessor {
+    /// Structured item produced by the parser.
+    type Output;
+
+    /// Processes a chunk of received bytes and returns the items it completed.
+    fn process(&mut self, data: &[u8]) -> Vec<Self::Output>;
+
+    /// Discards buffered input and accumulated state.
+    fn reset(&mut self);
+}
+
+/// A checksum-validated NMEA 0183 sentence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NmeaSentence {
+    /// Address field including the talker ID, e.g. `GPGGA`.
+    pub sentence_type: String,
+    /// Comma-separated data fields after the address field.
+    pub fields: Vec<String>,
+}
+
+impl NmeaSentence {
+    /// Returns the sentence formatter without the talker ID, e.g. `GGA`.
+    #[must_use]
+    pub fn formatter(&self) -> &str {
+        let start = self.sentence_type.len().saturating_sub(3);
+        self.sentence_type
+            .get(start..)
+            .unwrap_or(&self.sentence_type)
+    }
+
+    /// Returns field `index`, or an empty string if it is missing.
+    #[must_use]
+    pub fn field(&self, index: usize) -> &str {
+        self.fields.get(index).map_or("", String::as_str)
+    }
+
+    /// Returns the interesting fields of well-known sentences as `(name, value)` pairs.
+    ///
+    /// Unknown sentence types return all fields, numbered.
+    #[must_use]
+    pub fn summary(&self) -> Vec<(String, String)> {
+        let named = |pairs: &[(&str, String)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), value.clone()))
+                .collect()
+        };
+        match self.formatter() {
+            "GGA" => named(&[
+                ("Time", self.field(0).to_string()),
+                ("Latitude", coordinate(self.field(1), self.field(2))),
+                ("Longitude", coordinate(self.field(3), self.field(4))),
+                ("Fix quality", self.field(5).to_string()),
+                ("Satellites", self.field(6).to_string()),
+                ("Altitude", with_unit(self.field(8), self.field(9))),
+            ]),
+            "RMC" => named(&[
+                ("Time", self.field(0).to_string()),
+                ("Status", self.field(1).to_string()),
+                ("Latitude", coordinate(self.field(2), self.field(3))),
+                ("Longitude", coordinate(self.field(4), self.field(5))),
+                ("Speed", with_unit(self.field(6), "kn")),
+                ("Course", with_unit(self.field(7), "°")),
+                ("Date", self.field(8).to_string()),
+            ]),
+            "GLL" => named(&[
+                ("Latitude", coordinate(self.field(0), self.field(1))),
+                ("Longitude", coordinate(self.field(2), self.field(3))),
+                ("Time", self.field(4).to_string()),
+                ("Status", self.field(5).to_string()),
+            ]),
+            "VTG" => named(&[
+                ("Course", with_unit(self.field(0), "°")),
+                ("Speed", with_unit(self.field(4), "kn")),
+                ("Speed (km/h)", self.field(6).to_string()),
+            ]),
+            "GSA" => named(&[
+                ("Mode", self.field(1).to_string()),
+                ("PDOP", self.field(14).to_string()),
+                ("HDOP", self.field(15).to_string()),
+                ("VDOP", self.field(16).to_string()),
+            ]),
+            "GSV" => named(&[("Satellites in view", self.field(2).to_string())]),
+            _ => self
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (format!("Field {}", i + 1), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Converts an NMEA `(d)ddmm.mmmm` coordinate and hemisphere to decimal degrees.
+///
+/// Returns the raw value if it cannot be parsed.
+fn coordinate(value: &str, hemisphere: &str) -> String {
+    if value.is_empty() {
+        return String::new();
+    }
+    let dot = value.find('.').unwrap_or(value.len());
+    if dot < 2 {
+        return value.to_string();
+    }
+    let (degrees, minutes) = value.split_at(dot - 2);
+    let (Ok(degrees), Ok(minutes)) = (degrees.parse::<f64>(), minutes.parse::<f64>()) else {
+        return value.to_string();
+    };
+    let decimal = degrees + minutes / 60.0;
+    let sign = if matches!(hemisphere, "S" | "W") {
+        -1.0
+    } else {
+        1.0
+    };
+    format!("{:.6}", sign * decimal)
+}
+
+/// Joins a value and its unit, or returns an empty string for an empty value.
+fn with_unit(value: &str, unit: &str) -> String {
+    if value.is_empty() {
+        String::new()
+    } else {
+        format!("{value} {unit}")
+    }
+}
+
+/// Parses NMEA 0183 sentences (`$...*XX`) from the received byte stream.
+///
+/// Lines that do not start with `$` are ignored. Sentences with a missing or
+/// wrong checksum are counted in [`NmeaParser::checksum_errors`] and dropped.
+#[derive(Debug, Default)]
+pub struct NmeaParser {
+    /// Bytes of the line being received.
+    line: Vec<u8>,
+    /// Latest valid sentence of each type, keyed by address field.
+    latest: BTreeMap<String, NmeaSentence>,
+    /// Number of valid sentences parsed.
+    valid: u64,
+    /// Number of sentences rejected because of their checksum.
+    checksum_errors: u64,
+}
+
+impl NmeaParser {
+    /// Creates an empty parser.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the latest valid sentence of each type, ordered by type.
+    pub fn latest(&self) -> impl Iterator<Item = &NmeaSentence> {
+        self.latest.values()
+    }
+
+    /// Number of valid sentences parsed.
+    #[must_use]
+    pub const fn valid_count(&self) -> u64 {
+        self.valid
+    }
+
+    /// Number of sentences rejected because of a missing or wrong checksum.
+    #[must_use]
+    pub const fn checksum_errors(&self) -> u64 {
+        self.checksum_errors
+    }
+
+    /// Parses one complete line.
+    fn parse_line(&mut self, line: &[u8]) -> Option<NmeaSentence> {
+        let line = std::str::from_utf8(line).ok()?.trim();
+        let body = line.strip_prefix('$')?;
+
+        let sentence = body
+            .rsplit_once('*')
+            .filter(|(data, checksum)| parse_checksum(checksum) == Some(nmea_checksum(data)))
+            .map(|(data, _)| {
+                let mut parts = data.split(',');
+                NmeaSentence {
+                    sentence_type: parts.next().unwrap_or_default().to_string(),
+                    fields: parts.map(str::to_string).collect(),
+                }
+            });
+
+        let Some(sentence) = sentence else {
+            self.checksum_errors += 1;
+            return None;
+        };
+        self.valid += 1;
+        self.latest
+            .insert(sentence.sentence_type.clone(), sentence.clone());
+        Some(sentence)
+    }
+}
+
+impl DataProcessor for NmeaParser {
+    type Output = NmeaSentence;
+
+    fn process(&mut self, data: &[u8]) -> Vec<NmeaSentence> {
+        let mut sentences = Vec::new();
+        for &byte in data {
+            match byte {
+                b'\n' | b'\r' => {
+                    if !self.line.is_empty() {
+                        let line = std::mem::take(&mut self.line);
+                        sentences.extend(self.parse_line(&line));
+                    }
+                }
+                _ if self.line.len() < MAX_LINE_LEN => self.line.push(byte),
+                _ => self.line.clear(),
+            }
+        }
+        sentences
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Computes the NMEA checksum: the XOR of all bytes between `$` and `*`.
+#[must_use]
+pub fn nmea_checksum(data: &str) -> u8 {
+    data.bytes().fold(0, |checksum, byte| checksum ^ byte)
+}
+
+/// Parses the two hex digits after `*`.
+fn parse_checksum(checksum: &str) -> Option<u8> {
+    if checksum.len() == 2 && checksum.bytes().all(|b| b.is_ascii_hexdigit()) {
+        u8::from_str_radix(checksum, 16).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GGA: &str = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n";
+    const RMC: &str = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\r\n";
+
+    #[test]
+    fn test_parse_gga() {
+        let mut parser = NmeaParser::new();
+        let sentences = parser.process(GGA.as_bytes());
+        assert_eq!(sentences.len(), 1);
+        let gga = &sentences[0];
+        assert_eq!(gga.sentence_type, "GPGGA");
+        assert_eq!(gga.formatter(), "GGA");
+        assert_eq!(gga.fields.len(), 14);
+
+        let summary = gga.summary();
+        assert!(summary.contains(&("Latitude".to_string(), "48.117300".to_string())));
+        assert!(summary.contains(&("Longitude".to_string(), "11.516667".to_string())));
+        assert!(summary.contains(&("Altitude".to_string(), "545.4 M".to_string())));
+    }
+
+    #[test]
+    fn test_parse_rmc_split_across_chunks() {
+        let mut parser = NmeaParser::new();
+        let (a, b) = RMC.as_bytes().split_at(20);
+        assert!(parser.process(a).is_empty());
+        let sentences = parser.process(b);
+        assert_eq!(sentences.len(), 1);
+        let summary = sentences[0].summary();
+        assert!(summary.contains(&("Speed".to_string(), "022.4 kn".to_string())));
+        assert!(summary.contains(&("Course".to_string(), "084.4 °".to_string())));
+    }
+
+    #[test]
+    fn test_bad_checksum_is_counted() {
+        let mut parser = NmeaParser::new();
+        let bad = GGA.replace("*47", "*48");
+        let missing = GGA.replace("*47", "");
+        let input = format!("{bad}{missing}hello\r\n{RMC}");
+        let sentences = parser.process(input.as_bytes());
+        assert_eq!(sentences.len(), 1);
+        assert_eq!(parser.valid_count(), 1);
+        assert_eq!(parser.checksum_errors(), 2);
+        assert_eq!(parser.latest().count(), 1);
+    }
+
+    #[test]
+    fn test_latest_keeps_one_sentence_per_type() {
+        let mut parser = NmeaParser::new();
+        parser.process(format!("{GGA}{RMC}{GGA}").as_bytes());
+        let types: Vec<&str> = parser
+            .latest()
+            .map(|sentence| sentence.sentence_type.as_str())
+            .collect();
+        assert_eq!(types, ["GPGGA", "GPRMC"]);
+        assert_eq!(parser.valid_count(), 3);
+
+        parser.reset();
+        assert_eq!(parser.latest().count(), 0);
+    }
+
+    #[test]
+    fn test_coordinate_hemisphere() {
+        assert_eq!(coordinate("4807.038", "S"), "-48.117300");
+        assert_eq!(coordinate("", "N"), "");
+        assert_eq!(coordinate("abc", "N"), "abc");
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! This module provides serial port types, settings, and state management.
 
-use log::{debug, error};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bevy::reflect::Reflect;
+use log::{debug, error, warn};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
 use tokio::time::Duration;
@@ -15,9 +19,13 @@ use crate::error::SerialBevyError;
 // Re-exports for backward compatibility (types that were previously defined in this module).
 // These also serve as imports for the types used in this module's struct definitions.
 pub use super::data_types::DataType;
+use super::discovery::Runtime;
 pub use super::llm::{LlmConfig, LlmMessage, TEXT_MODELS};
+use super::llm::{conversation_path, transcript_path};
+use super::mock::MockTraffic;
 pub use super::port_data::PortData;
-pub use super::state::{DataSource, PortChannelData, PortRwData, PortState};
+pub use super::state::{DataSource, PortChannelData, PortRwData, PortState, SignalState};
+use super::throughput::{ThroughputResult, run_throughput_test};
 // Note: these re-exports maintain the public API so that
 // `use crate::serial::port::*` and direct paths like
 // `crate::serial::port::DataType` continue to work.
@@ -28,6 +36,63 @@ pub const COMMON_BAUD_RATES: &[u32] = &[
     1500000, 2000000,
 ];
 
+/// Highest baud rate accepted without a warning; few adapters go faster.
+pub const MAX_TYPICAL_BAUD_RATE: u32 = 4_000_000;
+
+/// Parses a baud rate typed by the user. Rates outside
+/// [`COMMON_BAUD_RATES`], e.g. 250000 for DMX, are accepted as is.
+///
+/// # Errors
+///
+/// Returns an error if the text is not a whole number above zero.
+pub fn parse_baud_rate(text: &str) -> Result<u32, SerialBevyError> {
+    match text.trim().parse::<u32>() {
+        Ok(0) => Err(SerialBevyError::InvalidConfig(
+            "baud rate must be above 0".to_string(),
+        )),
+        Ok(rate) => Ok(rate),
+        Err(e) => Err(SerialBevyError::InvalidConfig(format!(
+            "invalid baud rate {:?}: {e}",
+            text.trim()
+        ))),
+    }
+}
+
+/// Scheme of the serial port URLs read by [`PortSettings::from_url`].
+pub const PORT_URL_SCHEME: &str = "serial://";
+
+/// Send rate limits offered in the settings panel, in bytes per second.
+pub const SEND_RATE_RANGE: std::ops::RangeInclusive<u32> = 100..=100 * 1024;
+
+/// Send rate limit set when limiting is turned on: about what 9600 baud carries.
+pub const DEFAULT_SEND_RATE: u32 = 960;
+
+/// Frame gaps offered in the settings panel, in milliseconds.
+pub const FRAME_GAP_RANGE_MS: std::ops::RangeInclusive<u64> = 1..=1000;
+
+/// Frame gap set when gap framing is turned on: 3.5 characters at 9600 baud,
+/// the Modbus RTU frame delimiter.
+pub const DEFAULT_FRAME_GAP: Duration = Duration::from_millis(4);
+
+/// Break durations offered in the settings panel, in milliseconds.
+pub const BREAK_DURATION_RANGE_MS: std::ops::RangeInclusive<u32> = 1..=5000;
+
+/// Break duration offered by default: well over a frame time at any common
+/// baud rate.
+pub const DEFAULT_BREAK_DURATION_MS: u32 = 100;
+
+/// Largest frame forwarded with gap framing; longer bursts are split.
+pub const MAX_FRAME_SIZE: usize = 4096;
+
+/// Time to wait for the echo of a latency probe.
+pub const LATENCY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Handle of a running latency measurement.
+type LatencyTask = JoinHandle<Result<Duration, SerialBevyError>>;
+
+/// Handle of a running throughput test.
+type ThroughputTask = JoinHandle<Result<ThroughputResult, SerialBevyError>>;
+
 /// Represents a serial port with its settings, data, and communication channels.
 pub struct Serial {
     /// Port settings.
@@ -44,6 +109,20 @@ pub struct Serial {
     rx_channel: Option<broadcast::Receiver<PortChannelData>>,
     /// LLM configuration.
     llm: LlmConfig,
+    /// Traffic generator for virtual ports; `None` for hardware ports.
+    mock: Option<MockTraffic>,
+    /// Running latency measurement started from the UI.
+    latency_task: Option<LatencyTask>,
+    /// Result of the last latency measurement started from the UI.
+    latency_result: Option<Result<Duration, String>>,
+    /// Running throughput test started from the UI.
+    throughput_task: Option<ThroughputTask>,
+    /// Result of the last throughput test started from the UI.
+    throughput_result: Option<Result<ThroughputResult, String>>,
+    /// Serial number of the USB adapter, if discovery reported one.
+    usb_serial_number: Option<String>,
+    /// Number of consecutive discovery scans the port was missing from.
+    missing_scans: u32,
 }
 
 impl Default for Serial {
@@ -64,9 +143,69 @@ impl Serial {
             tx_channel: None,
             rx_channel: None,
             llm: LlmConfig::new(),
+            mock: None,
+            latency_task: None,
+            latency_result: None,
+            throughput_task: None,
+            throughput_result: None,
+            usb_serial_number: None,
+            missing_scans: 0,
         }
     }
 
+    /// Creates a virtual port that generates `traffic` once opened.
+    #[must_use]
+    pub fn new_mock(port_name: impl Into<String>, traffic: MockTraffic) -> Self {
+        let mut serial = Self::new();
+        serial.set.port_name = port_name.into();
+        serial.mock = Some(traffic);
+        serial
+    }
+
+    /// Returns the mock traffic generator if this is a virtual port.
+    #[must_use]
+    pub const fn mock_traffic(&self) -> Option<&MockTraffic> {
+        self.mock.as_ref()
+    }
+
+    /// Returns true if this is a virtual port.
+    #[must_use]
+    pub const fn is_mock(&self) -> bool {
+        self.mock.is_some()
+    }
+
+    /// Returns the serial number of the USB adapter, if known.
+    #[must_use]
+    pub fn usb_serial_number(&self) -> Option<&str> {
+        self.usb_serial_number.as_deref()
+    }
+
+    /// Sets the serial number of the USB adapter.
+    pub fn set_usb_serial_number(&mut self, serial_number: Option<String>) {
+        self.usb_serial_number = serial_number;
+    }
+
+    /// Returns the number of consecutive discovery scans the port was missing from.
+    #[must_use]
+    pub const fn missing_scans(&self) -> u32 {
+        self.missing_scans
+    }
+
+    /// Sets the number of consecutive discovery scans the port was missing from.
+    pub const fn set_missing_scans(&mut self, missing_scans: u32) {
+        self.missing_scans = missing_scans;
+    }
+
+    /// Returns the key that identifies the device across restarts: `usb:<serial
+    /// number>` if known, otherwise the port name.
+    #[must_use]
+    pub fn label_key(&self) -> String {
+        self.usb_serial_number.as_ref().map_or_else(
+            || self.set.port_name.clone(),
+            |serial_number| format!("usb:{serial_number}"),
+        )
+    }
+
     /// Gets a reference to the port settings.
     #[must_use]
     pub const fn set(&self) -> &PortSettings {
@@ -101,6 +240,7 @@ impl Serial {
     /// Opens the serial port (sets state to Ready).
     pub fn open(&mut self) {
         self.data.state().open();
+        self.data.mark_opened();
     }
 
     /// Returns true if the port is open.
@@ -112,10 +252,89 @@ impl Serial {
     /// Closes the serial port.
     pub fn close(&mut self) {
         self.data.state().close();
-        self.data.flush_file_writer();
+        self.data.end_session();
+        self.data.session_log_mut().queue_flush();
         self.thread_handle = None;
     }
 
+    /// Stops the port thread, if any: asks it to close the port, then aborts it.
+    pub fn stop_thread(&mut self) {
+        if let Some(handle) = self.thread_handle.take() {
+            if let Some(tx) = &self.tx_channel {
+                let _ = tx.send(PortChannelData::PortClose(self.set.port_name.clone()));
+            }
+            handle.abort();
+        }
+    }
+
+    /// Asks the port thread to open the port with the current settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns a channel error if the port has no thread yet or its thread
+    /// exited.
+    pub fn request_open(&self) -> Result<(), SerialBevyError> {
+        self.send_to_thread(PortChannelData::PortOpen(self.set.clone()))
+    }
+
+    /// Asks the port thread to open the port, then starts a new session log
+    /// and restores the saved LLM conversation. Returns the log path.
+    ///
+    /// # Errors
+    ///
+    /// Returns a channel error if the port has no thread yet or its thread
+    /// exited; nothing is started then.
+    pub fn open_session(&mut self) -> Result<String, SerialBevyError> {
+        self.request_open()?;
+        let path = self.start_session_log();
+        self.load_llm_conversation();
+        Ok(path)
+    }
+
+    /// Asks the port thread to close the port.
+    ///
+    /// # Errors
+    ///
+    /// Returns a channel error if the port has no thread yet or its thread
+    /// exited.
+    pub fn request_close(&self) -> Result<(), SerialBevyError> {
+        self.send_to_thread(PortChannelData::PortClose(self.set.port_name.clone()))
+    }
+
+    /// Asks the port thread to send a break: to hold the line low for
+    /// `duration_ms` milliseconds, e.g. to synchronize a LIN bus.
+    ///
+    /// # Errors
+    ///
+    /// Returns a channel error if the port has no thread yet or its thread
+    /// exited.
+    pub fn send_break(&self, duration_ms: u32) -> Result<(), SerialBevyError> {
+        self.send_to_thread(PortChannelData::SendBreak { duration_ms })
+    }
+
+    /// Sends `data` to the port thread.
+    fn send_to_thread(&self, data: PortChannelData) -> Result<(), SerialBevyError> {
+        let tx = self.tx_channel.as_ref().ok_or_else(|| {
+            SerialBevyError::channel(format!("no port thread for {}", self.set.port_name))
+        })?;
+        tx.send(data)
+            .map(|_| ())
+            .map_err(|e| SerialBevyError::channel(e.to_string()))
+    }
+
+    /// Asks the port thread to close the port and flushes the port's logs,
+    /// before the app exits.
+    pub fn shutdown(&mut self) {
+        if self.is_open()
+            && let Some(tx) = &self.tx_channel
+        {
+            let _ = tx.send(PortChannelData::PortClose(self.set.port_name.clone()));
+        }
+        self.data.stop_binary_recording();
+        self.data.stop_session_recording();
+        self.data.session_log_mut().flush();
+    }
+
     /// Returns true if the port is closed.
     #[must_use]
     pub fn is_close(&self) -> bool {
@@ -133,12 +352,313 @@ impl Serial {
         self.data.state_ref().is_error()
     }
 
+    /// Starts a new log file for this port session and returns its path.
+    pub fn start_session_log(&mut self) -> String {
+        self.data
+            .session_log_mut()
+            .start_session(&self.set.port_name)
+    }
+
     /// Gets a mutable reference to the LLM configuration.
     pub const fn llm(&mut self) -> &mut LlmConfig {
         &mut self.llm
     }
+
+    /// Restores the port's saved LLM conversation, unless one is in progress.
+    pub fn load_llm_conversation(&mut self) {
+        let path = conversation_path(&self.set.port_name);
+        if self.llm.has_messages() || !path.exists() {
+            return;
+        }
+        match self.llm.load_conversation(&path) {
+            Ok(()) => debug!("Loaded LLM conversation from {}", path.display()),
+            Err(e) => error!("Failed to load LLM conversation: {e}"),
+        }
+    }
+
+    /// Saves the port's LLM conversation, if it has any messages.
+    pub fn save_llm_conversation(&self) {
+        if !self.llm.has_messages() {
+            return;
+        }
+        let path = conversation_path(&self.set.port_name);
+        if let Err(e) = self.llm.save_conversation(&path) {
+            error!("Failed to save LLM conversation: {e}");
+        }
+    }
+
+    /// Clears the port's LLM conversation and deletes its saved file.
+    pub fn clear_llm_history(&mut self) {
+        let path = conversation_path(&self.set.port_name);
+        if let Err(e) = self.llm.clear_history(&path) {
+            error!("Failed to delete LLM conversation: {e}");
+        }
+    }
+
+    /// Saves the port's LLM conversation as a Markdown transcript under
+    /// `logs/llm/` and returns its path.
+    pub fn save_llm_transcript(&self) -> Option<std::path::PathBuf> {
+        if !self.llm.has_messages() {
+            return None;
+        }
+        let path = transcript_path(&self.set.port_name, chrono::Local::now());
+        match self.llm.save_transcript(&path, &self.set.port_name) {
+            Ok(()) => Some(path),
+            Err(e) => {
+                error!("Failed to save LLM transcript: {e}");
+                None
+            }
+        }
+    }
+
+    /// Starts a new LLM conversation: the current one is archived as a
+    /// transcript, then cleared along with its saved file.
+    pub fn start_new_llm_conversation(&mut self) {
+        if self.llm.is_processing {
+            self.llm.stop();
+        }
+        if self.llm.has_messages() && self.save_llm_transcript().is_none() {
+            return;
+        }
+        self.clear_llm_history();
+    }
+
+    /// Measures the round-trip latency of the port.
+    ///
+    /// Sends a unique sentinel through the port's `tx_channel` and waits on a
+    /// subscription to its `rx_channel` until the sentinel is read back. The
+    /// device (or a loopback plug) must echo what it receives. Fails after
+    /// [`LATENCY_TIMEOUT`] without an echo.
+    pub fn measure_latency(&self, runtime: &Runtime) -> LatencyTask {
+        let port_name = self.set.port_name.clone();
+        let channels = self
+            .tx_channel
+            .clone()
+            .zip(
+                self.rx_channel
+                    .as_ref()
+                    .map(broadcast::Receiver::resubscribe),
+            )
+            .filter(|_| self.is_open());
+
+        runtime.spawn(async move {
+            let Some((tx, mut rx)) = channels else {
+                return Err(SerialBevyError::serial_port(format!(
+                    "{port_name} is not open"
+                )));
+            };
+
+            let sentinel = latency_sentinel();
+            let start = tokio::time::Instant::now();
+            tx.send(PortChannelData::PortWrite(PortRwData {
+                data: sentinel.clone(),
+            }))
+            .map_err(|e| SerialBevyError::channel(e.to_string()))?;
+
+            let mut received = Vec::new();
+            let echo = async {
+                loop {
+                    match rx.recv().await {
+                        Ok(PortChannelData::PortRead(data)) => {
+                            received.extend_from_slice(&data.data);
+                            if received
+                                .windows(sentinel.len())
+                                .any(|window| window == sentinel.as_slice())
+                            {
+                                return Ok(start.elapsed());
+                            }
+                        }
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => {
+                            return Err(SerialBevyError::channel("port thread exited"));
+                        }
+                    }
+                }
+            };
+
+            tokio::time::timeout(LATENCY_TIMEOUT, echo)
+                .await
+                .map_err(|_| {
+                    SerialBevyError::timeout(format!(
+                        "no echo from {port_name} within {} ms",
+                        LATENCY_TIMEOUT.as_millis()
+                    ))
+                })?
+        })
+    }
+
+    /// Starts a latency measurement unless one is already running.
+    pub fn start_latency_measurement(&mut self, runtime: &Runtime) {
+        if self.latency_task.is_none() {
+            self.latency_result = None;
+            self.latency_task = Some(self.measure_latency(runtime));
+        }
+    }
+
+    /// Collects the result of a finished latency measurement.
+    pub fn update_latency_measurement(&mut self, runtime: &Runtime) {
+        if self
+            .latency_task
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+            && let Some(task) = self.latency_task.take()
+        {
+            self.latency_result = Some(match runtime.block_on(task) {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            });
+        }
+    }
+
+    /// Returns true while a latency measurement is running.
+    #[must_use]
+    pub const fn is_measuring_latency(&self) -> bool {
+        self.latency_task.is_some()
+    }
+
+    /// Returns the result of the last latency measurement.
+    #[must_use]
+    pub const fn latency_result(&self) -> Option<&Result<Duration, String>> {
+        self.latency_result.as_ref()
+    }
+
+    /// Measures the bytes per second the port actually carries.
+    ///
+    /// Writes `iterations` numbered packets of `payload_size` bytes through
+    /// the port's `tx_channel` and counts their echoes on a subscription to
+    /// its `rx_channel`, as described in [`super::throughput`]. The device
+    /// (or a loopback plug) must echo what it receives.
+    pub fn measure_throughput(
+        &self,
+        runtime: &Runtime,
+        payload_size: usize,
+        iterations: u32,
+    ) -> ThroughputTask {
+        let port_name = self.set.port_name.clone();
+        let channels = self
+            .tx_channel
+            .clone()
+            .zip(
+                self.rx_channel
+                    .as_ref()
+                    .map(broadcast::Receiver::resubscribe),
+            )
+            .filter(|_| self.is_open());
+
+        runtime.spawn(async move {
+            let Some((tx, rx)) = channels else {
+                return Err(SerialBevyError::serial_port(format!(
+                    "{port_name} is not open"
+                )));
+            };
+            run_throughput_test(tx, rx, port_name, payload_size, iterations).await
+        })
+    }
+
+    /// Starts a throughput test unless one is already running.
+    pub fn start_throughput_test(
+        &mut self,
+        runtime: &Runtime,
+        payload_size: usize,
+        iterations: u32,
+    ) {
+        if self.throughput_task.is_none() {
+            self.throughput_result = None;
+            self.throughput_task = Some(self.measure_throughput(runtime, payload_size, iterations));
+        }
+    }
+
+    /// Collects the result of a finished throughput test.
+    pub fn update_throughput_test(&mut self, runtime: &Runtime) {
+        if self
+            .throughput_task
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+            && let Some(task) = self.throughput_task.take()
+        {
+            self.throughput_result = Some(match runtime.block_on(task) {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            });
+        }
+    }
+
+    /// Returns true while a throughput test is running.
+    #[must_use]
+    pub const fn is_testing_throughput(&self) -> bool {
+        self.throughput_task.is_some()
+    }
+
+    /// Returns the result of the last throughput test.
+    #[must_use]
+    pub const fn throughput_result(&self) -> Option<&Result<ThroughputResult, String>> {
+        self.throughput_result.as_ref()
+    }
+}
+
+/// Returns a byte sequence that is unique to one latency probe.
+fn latency_sentinel() -> Vec<u8> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos());
+    format!("\x02PING{count:08x}{nanos:08x}\x03").into_bytes()
+}
+
+/// What happens when an open port receives nothing for its receive timeout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum TimeoutAction {
+    /// Only raise an alert.
+    #[default]
+    Alert,
+    /// Raise an alert and close the port.
+    Close,
+    /// Raise an alert and put the port in error state.
+    Error,
+}
+
+impl TimeoutAction {
+    /// All actions, in display order.
+    pub const ALL: [Self; 3] = [Self::Alert, Self::Close, Self::Error];
+
+    /// Returns the display name of the action.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Alert => "Alert",
+            Self::Close => "Close port",
+            Self::Error => "Error",
+        }
+    }
 }
 
+/// A combination of port settings that serial hardware rejects.
+#[derive(Clone, Copy, Debug)]
+pub struct SettingsRule {
+    /// Description of the problem, shown to the user.
+    pub problem: &'static str,
+    /// Returns true if the settings break the rule.
+    pub violated: fn(&PortSettings) -> bool,
+}
+
+/// Combinations rejected by most serial hardware, checked by
+/// [`PortSettings::validate`]. Adapters with further constraints can be
+/// checked against these rules and their own with
+/// [`PortSettings::validate_with`].
+pub const DEFAULT_SETTINGS_RULES: &[SettingsRule] = &[
+    SettingsRule {
+        problem: "Baud rate must be above 0",
+        violated: |settings| settings.baud_rate == 0,
+    },
+    SettingsRule {
+        problem: "5 data bits take 1.5 stop bits, not 2",
+        violated: |settings| {
+            settings.data_bits == DataBits::Five && settings.stop_bits == StopBits::Two
+        },
+    },
+];
+
 /// Serial port configuration settings.
 #[derive(Clone, Debug)]
 pub struct PortSettings {
@@ -156,6 +676,15 @@ pub struct PortSettings {
     pub flow_control: FlowControl,
     /// Timeout duration.
     pub timeout: Duration,
+    /// Longest time an open port may go without receiving data, if watched.
+    pub receive_timeout: Option<Duration>,
+    /// What happens when the receive timeout elapses.
+    pub timeout_action: TimeoutAction,
+    /// Largest number of bytes written per second, if limited.
+    pub send_rate_limit: Option<u32>,
+    /// Silence on the line that ends a received frame, if received data is
+    /// framed by gaps.
+    pub frame_gap: Option<Duration>,
 }
 
 impl Default for PortSettings {
@@ -168,6 +697,10 @@ impl Default for PortSettings {
             parity: Parity::None,
             flow_control: FlowControl::None,
             timeout: Duration::from_millis(100),
+            receive_timeout: None,
+            timeout_action: TimeoutAction::default(),
+            send_rate_limit: None,
+            frame_gap: None,
         }
     }
 }
@@ -188,6 +721,241 @@ impl PortSettings {
         self.parity = other.parity;
         self.flow_control = other.flow_control;
         self.timeout = other.timeout;
+        self.receive_timeout = other.receive_timeout;
+        self.timeout_action = other.timeout_action;
+        self.send_rate_limit = other.send_rate_limit;
+        self.frame_gap = other.frame_gap;
+    }
+
+    /// Creates the settings a newly discovered port starts with.
+    #[must_use]
+    pub fn discovered(port_name: &str) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the problems of the settings under `rules`, in rule order.
+    #[must_use]
+    pub fn problems(&self, rules: &[SettingsRule]) -> Vec<&'static str> {
+        rules
+            .iter()
+            .filter(|rule| (rule.violated)(self))
+            .map(|rule| rule.problem)
+            .collect()
+    }
+
+    /// Checks the settings against [`DEFAULT_SETTINGS_RULES`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerialBevyError::InvalidConfig`] listing every problem found.
+    pub fn validate(&self) -> Result<(), SerialBevyError> {
+        self.validate_with(DEFAULT_SETTINGS_RULES)
+    }
+
+    /// Checks the settings against `rules`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerialBevyError::InvalidConfig`] listing every problem found.
+    pub fn validate_with(&self, rules: &[SettingsRule]) -> Result<(), SerialBevyError> {
+        let problems = self.problems(rules);
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SerialBevyError::InvalidConfig(format!(
+                "{}: {}",
+                self.port_name,
+                problems.join("; ")
+            )))
+        }
+    }
+
+    /// Returns the usual short form of the settings, e.g. `115200 8N1`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        format!(
+            "{} {}{parity}{}",
+            self.baud_rate,
+            u8::from(self.data_bits),
+            u8::from(self.stop_bits)
+        )
+    }
+
+    /// Parses a serial port URL such as
+    /// `serial:///dev/ttyUSB0?baud=115200&parity=none&databits=8`.
+    ///
+    /// The path is the port name, percent-decoded. The query may set `baud`,
+    /// `parity` (`none`, `odd`, `even`), `databits` (5 to 8), `stopbits` (1
+    /// or 2), `flowcontrol` (`none`, `software`, `hardware`) and
+    /// `timeout_us`; other settings keep their defaults. Unknown parameters
+    /// are ignored with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerialBevyError::InvalidConfig`] if the URL does not use the
+    /// `serial://` scheme, has no port name, or has an invalid value.
+    pub fn from_url(url: &str) -> Result<Self, SerialBevyError> {
+        let invalid = |message: String| SerialBevyError::InvalidConfig(format!("{url}: {message}"));
+        let rest = url
+            .get(..PORT_URL_SCHEME.len())
+            .filter(|scheme| scheme.eq_ignore_ascii_case(PORT_URL_SCHEME))
+            .map(|_| &url[PORT_URL_SCHEME.len()..])
+            .ok_or_else(|| invalid(format!("expected a {PORT_URL_SCHEME} URL")))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let port_name = percent_decode(path).ok_or_else(|| invalid("invalid port name".into()))?;
+        if port_name.is_empty() {
+            return Err(invalid("missing port name".into()));
+        }
+
+        let mut settings = Self::discovered(&port_name);
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value).unwrap_or_default();
+            let bad_value = || invalid(format!("invalid {key} {value:?}"));
+            match key {
+                "baud" => {
+                    settings.baud_rate =
+                        parse_baud_rate(&value).map_err(|e| invalid(e.to_string()))?;
+                }
+                "parity" => {
+                    settings.parity = match value.to_ascii_lowercase().as_str() {
+                        "none" | "n" => Parity::None,
+                        "odd" | "o" => Parity::Odd,
+                        "even" | "e" => Parity::Even,
+                        _ => return Err(bad_value()),
+                    };
+                }
+                "databits" => {
+                    settings.data_bits = match value.as_str() {
+                        "5" => DataBits::Five,
+                        "6" => DataBits::Six,
+                        "7" => DataBits::Seven,
+                        "8" => DataBits::Eight,
+                        _ => return Err(bad_value()),
+                    };
+                }
+                "stopbits" => {
+                    settings.stop_bits = match value.as_str() {
+                        "1" => StopBits::One,
+                        "2" => StopBits::Two,
+                        _ => return Err(bad_value()),
+                    };
+                }
+                "flowcontrol" => {
+                    settings.flow_control = match value.to_ascii_lowercase().as_str() {
+                        "none" => FlowControl::None,
+                        "software" | "xonxoff" => FlowControl::Software,
+                        "hardware" | "rtscts" => FlowControl::Hardware,
+                        _ => return Err(bad_value()),
+                    };
+                }
+                "timeout_us" => {
+                    let micros = value.parse().map_err(|_| bad_value())?;
+                    settings.timeout = Duration::from_micros(micros);
+                }
+                _ => warn!("Ignoring unknown parameter {key:?} in port URL {url}"),
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Returns the settings as a serial port URL read back by
+    /// [`Self::from_url`], e.g.
+    /// `serial:///dev/ttyUSB0?baud=115200&parity=none&databits=8&stopbits=1&flowcontrol=none&timeout_us=100000`.
+    #[must_use]
+    pub fn to_url(&self) -> String {
+        let parity = match self.parity {
+            Parity::None => "none",
+            Parity::Odd => "odd",
+            Parity::Even => "even",
+        };
+        let flow_control = match self.flow_control {
+            FlowControl::None => "none",
+            FlowControl::Software => "software",
+            FlowControl::Hardware => "hardware",
+        };
+        format!(
+            "{PORT_URL_SCHEME}{}?baud={}&parity={parity}&databits={}&stopbits={}&flowcontrol={flow_control}&timeout_us={}",
+            percent_encode(&self.port_name),
+            self.baud_rate,
+            u8::from(self.data_bits),
+            u8::from(self.stop_bits),
+            self.timeout.as_micros()
+        )
+    }
+
+    /// Describes each setting that differs in `other`, e.g.
+    /// `"baud_rate: 9600 → 115200"`. Identical settings give an empty list.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut compare = |field: &str, old: String, new: String| {
+            if old != new {
+                changes.push(format!("{field}: {old} → {new}"));
+            }
+        };
+        compare("port_name", self.port_name.clone(), other.port_name.clone());
+        compare(
+            "baud_rate",
+            self.baud_rate.to_string(),
+            other.baud_rate.to_string(),
+        );
+        compare(
+            "data_bits",
+            u8::from(self.data_bits).to_string(),
+            u8::from(other.data_bits).to_string(),
+        );
+        compare(
+            "stop_bits",
+            u8::from(self.stop_bits).to_string(),
+            u8::from(other.stop_bits).to_string(),
+        );
+        compare("parity", self.parity.to_string(), other.parity.to_string());
+        compare(
+            "flow_control",
+            self.flow_control.to_string(),
+            other.flow_control.to_string(),
+        );
+        compare(
+            "timeout",
+            format!("{} ms", self.timeout.as_millis()),
+            format!("{} ms", other.timeout.as_millis()),
+        );
+        let optional_millis = |duration: Option<Duration>| {
+            duration.map_or_else(|| "off".to_string(), |d| format!("{} ms", d.as_millis()))
+        };
+        compare(
+            "receive_timeout",
+            optional_millis(self.receive_timeout),
+            optional_millis(other.receive_timeout),
+        );
+        compare(
+            "timeout_action",
+            self.timeout_action.as_str().to_string(),
+            other.timeout_action.as_str().to_string(),
+        );
+        let send_rate_limit = |rate: Option<u32>| {
+            rate.map_or_else(|| "off".to_string(), |rate| format!("{rate} B/s"))
+        };
+        compare(
+            "send_rate_limit",
+            send_rate_limit(self.send_rate_limit),
+            send_rate_limit(other.send_rate_limit),
+        );
+        compare(
+            "frame_gap",
+            optional_millis(self.frame_gap),
+            optional_millis(other.frame_gap),
+        );
+        changes
     }
 
     /// Gets a mutable reference to the port name.
@@ -250,6 +1018,66 @@ impl PortSettings {
     }
 }
 
+/// A byte stream a serial port thread can read from and write to.
+///
+/// Implemented by hardware serial streams and by virtual ports
+/// ([`MockBackend`](super::mock::MockBackend)), so the read/write loops are
+/// identical for both.
+pub trait PortBackend: AsyncRead + AsyncWrite + Send + Unpin {
+    /// Reads the modem status lines, or `None` if the backend has none.
+    fn read_signals(&mut self) -> Option<SignalState> {
+        None
+    }
+
+    /// Applies new line settings to the open port. Backends without line
+    /// settings ignore them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port rejects a setting.
+    fn reconfigure(&mut self, _settings: &PortSettings) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Starts (`on`) or ends the break condition on the line. Backends
+    /// without a line ignore it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the port rejects the change.
+    fn set_break_condition(&mut self, _on: bool) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl PortBackend for SerialStream {
+    fn read_signals(&mut self) -> Option<SignalState> {
+        Some(SignalState {
+            cts: self.read_clear_to_send().ok()?,
+            dsr: self.read_data_set_ready().ok()?,
+            dcd: self.read_carrier_detect().ok()?,
+            ri: self.read_ring_indicator().ok()?,
+        })
+    }
+
+    fn reconfigure(&mut self, settings: &PortSettings) -> std::io::Result<()> {
+        self.set_baud_rate(settings.baud_rate)?;
+        self.set_data_bits(settings.data_bits)?;
+        self.set_parity(settings.parity)?;
+        self.set_stop_bits(settings.stop_bits)?;
+        Ok(())
+    }
+
+    fn set_break_condition(&mut self, on: bool) -> std::io::Result<()> {
+        if on {
+            SerialPort::set_break(self)?;
+        } else {
+            SerialPort::clear_break(self)?;
+        }
+        Ok(())
+    }
+}
+
 /// Opens a serial port with the specified settings.
 ///
 /// # Arguments
@@ -258,8 +1086,10 @@ impl PortSettings {
 ///
 /// # Returns
 ///
-/// A Result containing the opened `SerialStream` or an error.
+/// A Result containing the opened `SerialStream` or an error. Settings that
+/// fail [`PortSettings::validate`] are refused before the port is touched.
 pub async fn open_port(settings: &PortSettings) -> Result<SerialStream, SerialBevyError> {
+    settings.validate()?;
     tokio_serial::new(&settings.port_name, settings.baud_rate)
         .data_bits(settings.data_bits)
         .parity(settings.parity)
@@ -284,6 +1114,14 @@ pub struct CacheData {
     history_index: usize,
     /// Current input data.
     current_data: String,
+    /// Whether a reverse history search is in progress.
+    search_mode: bool,
+    /// Text the history entry found by the search must contain.
+    search_query: String,
+    /// Input before the search started, restored if it is cancelled.
+    search_original: String,
+    /// Index of the history entry found by the search.
+    search_match: Option<usize>,
 }
 
 impl Default for CacheData {
@@ -300,6 +1138,10 @@ impl CacheData {
             history_data: Vec::new(),
             history_index: 0,
             current_data: String::new(),
+            search_mode: false,
+            search_query: String::new(),
+            search_original: String::new(),
+            search_match: None,
         }
     }
 
@@ -356,6 +1198,169 @@ impl CacheData {
     pub fn clear_current_data(&mut self) {
         self.current_data.clear();
     }
+
+    /// Returns the distinct history entries starting with `prefix`, oldest first.
+    #[must_use]
+    pub fn find_completions(&self, prefix: &str) -> Vec<&String> {
+        let mut completions: Vec<&String> = Vec::new();
+        for entry in &self.history_data {
+            if entry.starts_with(prefix) && !completions.contains(&entry) {
+                completions.push(entry);
+            }
+        }
+        completions
+    }
+
+    /// Completes the current input to the longest common prefix of the
+    /// history entries starting with it, or to the whole entry if only one
+    /// matches. Returns true if the input changed.
+    pub fn complete_current_data(&mut self) -> bool {
+        let completions = self.find_completions(&self.current_data);
+        let Some((first, rest)) = completions.split_first() else {
+            return false;
+        };
+        let completion = rest
+            .iter()
+            .fold(first.as_str(), |common, entry| common_prefix(common, entry));
+        if completion.len() <= self.current_data.len() {
+            return false;
+        }
+        self.current_data = completion.to_string();
+        true
+    }
+
+    /// Starts a reverse history search, keeping the current input to restore
+    /// if the search is cancelled.
+    pub fn start_search(&mut self) {
+        self.search_mode = true;
+        self.search_query.clear();
+        self.search_original.clone_from(&self.current_data);
+        self.search_match = None;
+    }
+
+    /// Returns true while a reverse history search is in progress.
+    #[must_use]
+    pub const fn is_searching(&self) -> bool {
+        self.search_mode
+    }
+
+    /// Returns the text of the reverse history search.
+    #[must_use]
+    pub fn search_query(&self) -> &str {
+        &self.search_query
+    }
+
+    /// Returns true if the search text matches no history entry.
+    #[must_use]
+    pub fn is_search_failing(&self) -> bool {
+        self.search_mode && !self.search_query.is_empty() && self.search_match.is_none()
+    }
+
+    /// Returns the index of the most recent history entry before `before`
+    /// that contains `query`.
+    #[must_use]
+    pub fn find_most_recent(&self, query: &str, before: usize) -> Option<usize> {
+        self.history_data[..before.min(self.history_data.len())]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+
+    /// Shows the most recent entry before `before` that contains the search
+    /// text, if any, in the input.
+    fn show_search_match(&mut self, before: usize) -> bool {
+        let Some(index) = self.find_most_recent(&self.search_query, before) else {
+            return false;
+        };
+        self.search_match = Some(index);
+        self.current_data.clone_from(&self.history_data[index]);
+        true
+    }
+
+    /// Appends `text` to the search text and shows the most recent entry
+    /// containing it.
+    pub fn push_search_text(&mut self, text: &str) {
+        self.search_query.push_str(text);
+        if !self.show_search_match(self.history_data.len()) {
+            self.search_match = None;
+        }
+    }
+
+    /// Removes the last character of the search text.
+    pub fn pop_search_text(&mut self) {
+        self.search_query.pop();
+        if self.search_query.is_empty() {
+            self.search_match = None;
+            self.current_data.clone_from(&self.search_original);
+        } else if !self.show_search_match(self.history_data.len()) {
+            self.search_match = None;
+        }
+    }
+
+    /// Shows the next older entry containing the search text, if any.
+    pub fn search_older(&mut self) {
+        let before = self.search_match.unwrap_or(self.history_data.len());
+        self.show_search_match(before);
+    }
+
+    /// Ends the search, keeping the found entry in the input.
+    pub fn confirm_search(&mut self) {
+        if let Some(index) = self.search_match {
+            self.history_index = index + 1;
+        }
+        self.search_mode = false;
+        self.search_query.clear();
+        self.search_match = None;
+    }
+
+    /// Ends the search, restoring the input from before it started.
+    pub fn cancel_search(&mut self) {
+        self.search_mode = false;
+        self.search_query.clear();
+        self.search_match = None;
+        self.current_data = std::mem::take(&mut self.search_original);
+    }
+}
+
+/// Returns the longest common prefix of `a` and `b`.
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, ca), cb)| ca != cb)
+        .map_or_else(|| a.len().min(b.len()), |((index, _), _)| index);
+    &a[..len]
+}
+
+/// Escapes the characters of `text` that would end or split the path of a
+/// port URL, such as `?` and `&`, as `%XX`.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_graphic() && !matches!(byte, b'%' | b'?' | b'&' | b'#' | b'=' | b'+') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Decodes the `%XX` escapes of `text`, or returns `None` if an escape is
+/// invalid or the result is not UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let digits = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(digits, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
 }
 
 #[cfg(test)]
@@ -372,6 +1377,144 @@ mod tests {
         assert_eq!(settings.timeout, Duration::from_millis(100));
     }
 
+    #[test]
+    fn test_validate_rejects_invalid_combinations() {
+        let data_bits = [
+            DataBits::Five,
+            DataBits::Six,
+            DataBits::Seven,
+            DataBits::Eight,
+        ];
+        for data_bits in data_bits {
+            for stop_bits in [StopBits::One, StopBits::Two] {
+                for parity in [Parity::None, Parity::Odd, Parity::Even] {
+                    for baud_rate in [0, 9600] {
+                        let settings = PortSettings {
+                            baud_rate,
+                            data_bits,
+                            stop_bits,
+                            parity,
+                            ..PortSettings::default()
+                        };
+                        let invalid = baud_rate == 0
+                            || (data_bits == DataBits::Five && stop_bits == StopBits::Two);
+                        assert_eq!(
+                            settings.validate().is_err(),
+                            invalid,
+                            "{}",
+                            settings.summary()
+                        );
+                    }
+                }
+            }
+        }
+
+        let settings = PortSettings {
+            baud_rate: 0,
+            data_bits: DataBits::Five,
+            stop_bits: StopBits::Two,
+            ..PortSettings::default()
+        };
+        assert_eq!(settings.problems(DEFAULT_SETTINGS_RULES).len(), 2);
+        assert!(matches!(
+            settings.validate(),
+            Err(SerialBevyError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_adapter_rules() {
+        let no_two_stop_bits = SettingsRule {
+            problem: "This adapter only supports 1 stop bit",
+            violated: |settings| settings.stop_bits == StopBits::Two,
+        };
+        let rules = [DEFAULT_SETTINGS_RULES, &[no_two_stop_bits]].concat();
+        let settings = PortSettings {
+            stop_bits: StopBits::Two,
+            ..PortSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+        assert_eq!(
+            settings.problems(&rules),
+            ["This adapter only supports 1 stop bit"]
+        );
+        assert!(settings.validate_with(&rules).is_err());
+    }
+
+    #[test]
+    fn test_parse_baud_rate() {
+        assert_eq!(parse_baud_rate("250000").unwrap(), 250_000);
+        assert_eq!(parse_baud_rate(" 74880 ").unwrap(), 74_880);
+        assert!(parse_baud_rate("0").is_err());
+        assert!(parse_baud_rate("").is_err());
+        assert!(parse_baud_rate("9600.5").is_err());
+        assert!(parse_baud_rate("-9600").is_err());
+    }
+
+    #[test]
+    fn test_identical_settings_have_no_diff() {
+        let settings = PortSettings::discovered("COM1");
+        assert!(settings.diff(&settings.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_settings_summary() {
+        let mut settings = PortSettings::discovered("COM1");
+        assert_eq!(settings.summary(), "115200 8N1");
+        settings.baud_rate = 9600;
+        settings.data_bits = DataBits::Seven;
+        settings.parity = Parity::Even;
+        settings.stop_bits = StopBits::Two;
+        assert_eq!(settings.summary(), "9600 7E2");
+    }
+
+    #[test]
+    fn test_diff_reports_each_changed_field() {
+        let defaults = PortSettings::discovered("COM1");
+        let changes: [(fn(&mut PortSettings), &str); 10] = [
+            (
+                |s| s.port_name = "COM2".to_string(),
+                "port_name: COM1 → COM2",
+            ),
+            (|s| s.baud_rate = 9600, "baud_rate: 115200 → 9600"),
+            (|s| s.data_bits = DataBits::Seven, "data_bits: 8 → 7"),
+            (|s| s.stop_bits = StopBits::Two, "stop_bits: 1 → 2"),
+            (|s| s.parity = Parity::Even, "parity: None → Even"),
+            (
+                |s| s.flow_control = FlowControl::Hardware,
+                "flow_control: None → Hardware",
+            ),
+            (
+                |s| s.timeout = Duration::from_millis(500),
+                "timeout: 100 ms → 500 ms",
+            ),
+            (
+                |s| s.receive_timeout = Some(Duration::from_secs(5)),
+                "receive_timeout: off → 5000 ms",
+            ),
+            (
+                |s| s.timeout_action = TimeoutAction::Close,
+                "timeout_action: Alert → Close port",
+            ),
+            (
+                |s| s.send_rate_limit = Some(960),
+                "send_rate_limit: off → 960 B/s",
+            ),
+            (
+                |s| s.frame_gap = Some(DEFAULT_FRAME_GAP),
+                "frame_gap: off → 4 ms",
+            ),
+        ];
+        let mut all = defaults.clone();
+        for (change, expected) in changes {
+            let mut settings = defaults.clone();
+            change(&mut settings);
+            assert_eq!(defaults.diff(&settings), [expected]);
+            change(&mut all);
+        }
+        assert_eq!(defaults.diff(&all).len(), 11);
+    }
+
     #[test]
     fn test_cache_data_history() {
         let mut cache = CacheData::new();
@@ -394,6 +1537,84 @@ mod tests {
         assert_eq!(cache.history_data.len(), 1);
     }
 
+    #[test]
+    fn test_completion_to_common_prefix() {
+        let mut cache = CacheData::new();
+        for command in ["AT+CGMI", "AT+CGMM", "AT+CSQ", "AT+CGMI", "ATI"] {
+            cache.add_history_data(command.to_string());
+        }
+        assert_eq!(cache.find_completions("AT+CG"), ["AT+CGMI", "AT+CGMM"]);
+
+        cache.get_current_data().push_str("AT+");
+        assert!(cache.complete_current_data());
+        assert_eq!(cache.get_current_data(), "AT+C");
+        // "AT+C" is already the common prefix of all candidates.
+        assert!(!cache.complete_current_data());
+
+        cache.get_current_data().push('G');
+        assert!(cache.complete_current_data());
+        assert_eq!(cache.get_current_data(), "AT+CGM");
+    }
+
+    #[test]
+    fn test_single_completion_is_completed_fully() {
+        let mut cache = CacheData::new();
+        cache.add_history_data("AT+CSQ".to_string());
+        cache.add_history_data("ping 温度".to_string());
+
+        cache.get_current_data().push_str("pi");
+        assert!(cache.complete_current_data());
+        assert_eq!(cache.get_current_data(), "ping 温度");
+
+        cache.get_current_data().clear();
+        cache.get_current_data().push_str("xyz");
+        assert!(!cache.complete_current_data());
+        assert_eq!(cache.get_current_data(), "xyz");
+    }
+
+    #[test]
+    fn test_reverse_search_finds_most_recent_match() {
+        let mut cache = CacheData::new();
+        for command in ["AT+CSQ", "AT+CGMI", "ping", "AT+CSQ?", "reset"] {
+            cache.add_history_data(command.to_string());
+        }
+        cache.get_current_data().push_str("draft");
+
+        cache.start_search();
+        assert!(cache.is_searching());
+        cache.push_search_text("CS");
+        assert_eq!(cache.get_current_data(), "AT+CSQ?");
+        cache.search_older();
+        assert_eq!(cache.get_current_data(), "AT+CSQ");
+        // No older match: the input stays on the oldest one.
+        cache.search_older();
+        assert_eq!(cache.get_current_data(), "AT+CSQ");
+
+        cache.push_search_text("X");
+        assert!(cache.is_search_failing());
+        cache.pop_search_text();
+        assert_eq!(cache.get_current_data(), "AT+CSQ?");
+
+        cache.confirm_search();
+        assert!(!cache.is_searching());
+        assert_eq!(cache.get_current_data(), "AT+CSQ?");
+        assert_eq!(cache.get_current_data_index(), 4);
+    }
+
+    #[test]
+    fn test_cancelled_search_restores_input() {
+        let mut cache = CacheData::new();
+        cache.add_history_data("AT+GMR".to_string());
+        cache.get_current_data().push_str("draft");
+
+        cache.start_search();
+        cache.push_search_text("GMR");
+        assert_eq!(cache.get_current_data(), "AT+GMR");
+        cache.cancel_search();
+        assert!(!cache.is_searching());
+        assert_eq!(cache.get_current_data(), "draft");
+    }
+
     #[test]
     fn test_timeout_setting() {
         let mut settings = PortSettings::default();
@@ -409,4 +1630,76 @@ mod tests {
         // Test that timeout as_millis works correctly
         assert_eq!(settings.timeout.as_millis(), 1000);
     }
+    #[test]
+    fn test_port_url_round_trips() {
+        let data_bits = [
+            DataBits::Five,
+            DataBits::Six,
+            DataBits::Seven,
+            DataBits::Eight,
+        ];
+        let flow_controls = [
+            FlowControl::None,
+            FlowControl::Software,
+            FlowControl::Hardware,
+        ];
+        for port_name in ["/dev/ttyUSB0", "COM3", r"\\.\COM10", "odd name?&%"] {
+            for data_bits in data_bits {
+                for stop_bits in [StopBits::One, StopBits::Two] {
+                    for parity in [Parity::None, Parity::Odd, Parity::Even] {
+                        for flow_control in flow_controls {
+                            let settings = PortSettings {
+                                port_name: port_name.to_string(),
+                                baud_rate: 9600,
+                                data_bits,
+                                stop_bits,
+                                parity,
+                                flow_control,
+                                timeout: Duration::from_micros(2500),
+                                ..PortSettings::default()
+                            };
+                            let url = settings.to_url();
+                            let parsed = PortSettings::from_url(&url).unwrap();
+                            assert!(settings.diff(&parsed).is_empty(), "{url}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_port_url_parsing() {
+        let settings =
+            PortSettings::from_url("serial:///dev/ttyUSB0?baud=9600&parity=even&color=blue")
+                .unwrap();
+        assert_eq!(settings.port_name, "/dev/ttyUSB0");
+        assert_eq!(settings.baud_rate, 9600);
+        assert_eq!(settings.parity, Parity::Even);
+        assert_eq!(settings.data_bits, DataBits::Eight);
+        assert_eq!(settings.timeout, Duration::from_millis(100));
+        assert_eq!(
+            PortSettings::default().to_url().split_once('?').unwrap().1,
+            "baud=115200&parity=none&databits=8&stopbits=1&flowcontrol=none&timeout_us=100000"
+        );
+
+        for url in [
+            "/dev/ttyUSB0?baud=9600",
+            "tcp://host:23",
+            "serial://?baud=9600",
+            "serial://COM3?baud=fast",
+            "serial://COM3?databits=9",
+            "serial://COM3?stopbits=1.5",
+            "serial://COM3?parity=mark",
+            "serial://COM%ZZ",
+        ] {
+            assert!(
+                matches!(
+                    PortSettings::from_url(url),
+                    Err(SerialBevyError::InvalidConfig(_))
+                ),
+                "{url}"
+            );
+        }
+    }
 }
@@ -1,54 +1,274 @@
 //! # Port Data Module
 //!
 //! This module provides data management for serial port communication,
-//! including file logging, display buffering, and UTF-8 processing.
+//! including display buffering and UTF-8 processing. File logging is done
+//! by the port's [`SessionLog`].
 
 use std::collections::VecDeque;
-use std::fs::OpenOptions;
-use std::io::{BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use log::{error, warn};
+use chrono::{DateTime, Local};
+use log::warn;
+use serde::{Deserialize, Serialize};
 
 use super::data_types::DataType;
+use super::delimiter::{PacketDelimiter, RecordSplitter};
+use super::encoding::{
+    GBK_CONFIDENCE_THRESHOLD, LineEnding, SendRefusal, SendSettings, decode_bytes, encode_for_send,
+    gbk_confidence,
+};
+use super::flow::FlowMonitor;
+use super::log::{SessionLog, terminate_line};
+use super::log_entry::LogEntry;
+use super::packet::PacketTemplate;
+use super::parser::NmeaParser;
 use super::port::CacheData;
-use super::state::{DataSource, PortState};
+use super::recording::{BinaryPlayback, BinaryRecorder};
+use super::repeat::{RepeatSchedule, SendOptions};
+use super::replay::LogReplay;
+use super::response::{ResponseStats, ResponseTimer, ResponseTiming, format_response_time};
+use super::sequence::{SendSequence, SequenceRun};
+use super::session::{SessionDirection, SessionRecorder, session_recording_path};
+use super::state::{DataSource, PortChannelData, PortState, SignalState};
+use super::stats::{MAX_SESSION_HISTORY, SessionStats};
+use super::timestamp::TimestampMode;
+use crate::error::Result;
 
-/// File data storage.
-struct FileData {
-    /// List of file paths.
-    file: Vec<String>,
+pub use super::log::{
+    LOG_DIR, LOG_FLUSH_BYTES, LOG_FLUSH_INTERVAL, LogConfig, LogPolicy, sanitize_port_name,
+};
+
+/// Maximum number of entries kept in the in-memory display buffer.
+const MAX_DISPLAY_ENTRIES: usize = 5000;
+
+/// Error log lines kept for the error window of a failed port.
+pub const RECENT_ERROR_LINES: usize = 10;
+
+/// Default size limit of the receive ring buffer.
+pub const DEFAULT_RING_BYTES: usize = 128 * 1024;
+
+/// Receive ring buffer limits offered in the settings panel, in KB.
+pub const RING_KB_RANGE: std::ops::RangeInclusive<usize> = 16..=4096;
+
+/// Receive window of [`LocalEcho::AutoDedupe`] set in the UI, in
+/// milliseconds.
+pub const DEFAULT_ECHO_WINDOW_MS: u64 = 500;
+
+/// ANSI sequences around the response time of a received entry, showing it
+/// in gray.
+const RESPONSE_TIME_STYLE: (&str, &str) = ("\x1b[90m", "\x1b[39m");
+
+/// How data sent on a port is shown in its receive view.
+///
+/// Sent data is always written to the log file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LocalEcho {
+    /// Sent data is shown.
+    #[default]
+    Always,
+    /// Sent data is not shown, for devices that echo it back.
+    Never,
+    /// Sent data is shown, and received data that repeats it byte for byte
+    /// within `window_ms` milliseconds, possibly over several reads, is left
+    /// out as its echo.
+    AutoDedupe {
+        /// Longest time after sending that received data counts as the echo.
+        window_ms: u64,
+    },
+}
+
+/// Sent bytes whose echo is still expected, see [`PortData::take_echo`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingEcho {
+    /// Sent bytes not echoed yet.
+    remaining: Vec<u8>,
+    /// Whether part of the echo was received already.
+    started: bool,
+    /// When the bytes were sent.
+    sent_at: Instant,
+}
+
+/// Data queued to be sent, encoded when it was queued so later changes to
+/// the port's data type do not affect it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendPayload {
+    /// Bytes written to the port.
+    pub raw: Vec<u8>,
+    /// Text logged and shown for the data.
+    pub display: String,
+    /// Data type the data was entered in.
+    pub source_type: DataType,
+}
+
+impl SendPayload {
+    /// Encodes submitted `text` with `settings`, see [`encode_for_send`].
+    ///
+    /// # Errors
+    ///
+    /// Returns why the input was refused, see [`encode_for_send`].
+    pub fn encode(text: &str, settings: &SendSettings) -> Result<Self, SendRefusal> {
+        Ok(Self {
+            raw: encode_for_send(text, settings)?,
+            display: text.to_string(),
+            source_type: settings.data_type,
+        })
+    }
+
+    /// Creates a payload of bytes sent as they are, shown as hex.
+    #[must_use]
+    pub fn bytes(raw: Vec<u8>) -> Self {
+        Self {
+            display: decode_bytes(&raw, DataType::Hex),
+            raw,
+            source_type: DataType::Hex,
+        }
+    }
+}
+
+/// A chunk of data shown in the receive view.
+struct DisplayEntry {
+    /// When the data was written or received.
+    time: DateTime<Local>,
+    /// Direction of the data.
+    source: DataSource,
+    /// The data as text, without any prefix.
+    text: String,
+    /// Bytes the text was decoded from, or encoded to for sent data.
+    raw: Vec<u8>,
+    /// Time from the send this received data answers, if timed.
+    response_time: Option<Duration>,
+    /// Length of this entry in the rendered `display_text`.
+    rendered_len: usize,
 }
 
 /// Port data management for files and communication.
 pub struct PortData {
-    /// Source file paths for logging.
-    source_file: FileData,
-    /// Data pending to be sent.
-    send_data: Vec<String>,
+    /// Session log files of the port.
+    session_log: SessionLog,
+    /// Whether the log file of a [`Self::clear_log`] still has to be
+    /// emptied before more data is logged.
+    clear_pending: bool,
+    /// Encoded data queued for sending, in order.
+    send_queue: Vec<SendPayload>,
     /// Command cache and history.
     cache_data: CacheData,
     /// Current port state.
     state: PortState,
     /// Data encoding type.
     data_type: DataType,
-    /// Whether to include line feeds in sent data.
-    line_feed: bool,
-    /// Buffer for incomplete UTF-8 sequences.
+    /// Line ending appended to sent data.
+    line_ending: LineEnding,
+    /// Line feed toggle last handed out by the deprecated
+    /// [`Self::line_feed`]; overrides [`Self::line_ending`] once toggled.
+    line_feed: Option<bool>,
+    /// How sent data is shown in the receive view.
+    local_echo: LocalEcho,
+    /// Last sent bytes whose echo is still expected.
+    last_sent: Option<PendingEcho>,
+    /// Response times of received data to the sends before it.
+    response_timer: ResponseTimer,
+    /// How received data is split into records.
+    packet_delimiter: PacketDelimiter,
+    /// Received bytes after the last record ended by the delimiter.
+    delimiter_buffer: RecordSplitter,
+    /// Buffer for incomplete multi-byte sequences (UTF-8 or a double-byte lead byte).
     utf8_buffer: Vec<u8>,
+    /// Received bytes not decoded into text yet, kept for the next display entry.
+    pending_raw: Vec<u8>,
     /// Console mode flag - provides better terminal experience for Linux serial consoles.
     /// When enabled: no timestamps, local echo, line-buffered sending.
     console_mode: bool,
-    /// Show timestamp in log file and display.
+    /// Show timestamp in the log file.
     /// When false (default): raw data format without timestamps.
     /// When true: adds [timestamp source] prefix to each line.
     show_timestamp: bool,
+    /// How timestamps are shown in the receive view.
+    timestamp_mode: TimestampMode,
+    /// strftime format overriding the default of wall-clock timestamp modes.
+    timestamp_format: Option<String>,
+    /// When the port was last opened, for [`TimestampMode::DeltaSincePortOpen`].
+    opened_at: Option<DateTime<Local>>,
+    /// Whether hex input with invalid characters or odd length is refused instead of cleaned.
+    strict_hex: bool,
+    /// Whether hex input is regrouped into space-separated bytes as it is typed.
+    hex_auto_space: bool,
+    /// Whether escape sequences such as `\n` and `\xNN` are parsed in text modes.
+    parse_escapes: bool,
+    /// Whether UTF-8 data that fails to decode is tried as GBK.
+    auto_detect_encoding: bool,
+    /// Whether the last non-ASCII UTF-8 data was decoded as GBK instead.
+    gbk_detected: bool,
     /// In-memory display buffer to avoid reading disk every frame.
-    display_buffer: VecDeque<String>,
+    display_buffer: VecDeque<DisplayEntry>,
     /// Accumulated display text cache for efficient reading.
     /// Updated in sync with `display_buffer` to avoid rebuilding every frame.
     display_text: String,
-    /// Persistent file writer for logging.
-    file_writer: Option<BufWriter<std::fs::File>>,
+    /// Page of the display text shown in the receive view, or `None` to
+    /// follow the last page as data arrives.
+    display_page: Option<usize>,
+    /// Whether the receive view shows the received bytes as a heatmap
+    /// instead of text.
+    heatmap_view: bool,
+    /// Latest modem status lines, if the port reports them.
+    signal_state: Option<SignalState>,
+    /// NMEA 0183 parser fed with received data, if enabled.
+    nmea: Option<NmeaParser>,
+    /// Latest error reported by the port thread.
+    last_error: Option<String>,
+    /// When the latest error was reported.
+    last_error_at: Option<DateTime<Local>>,
+    /// Most recent error lines of the log, read when the latest error was
+    /// reported.
+    recent_errors: Vec<String>,
+    /// Send sequence edited in the sequence window.
+    sequence: SendSequence,
+    /// Current or last run of the send sequence.
+    sequence_run: Option<SequenceRun>,
+    /// How data submitted from the input is sent.
+    send_options: SendOptions,
+    /// Remaining sends of data sent with repeats.
+    repeat: Option<RepeatSchedule>,
+    /// Most recently received raw bytes, at most `max_ring_bytes` of them.
+    ring_buffer: VecDeque<u8>,
+    /// Size limit of the receive ring buffer.
+    max_ring_bytes: usize,
+    /// Raw recording of received bytes, while recording.
+    binary_recorder: Option<BinaryRecorder>,
+    /// Session recording of received and sent bytes, while recording.
+    session_recorder: Option<SessionRecorder>,
+    /// Recording being played back to the port.
+    binary_playback: Option<BinaryPlayback>,
+    /// Session log being replayed as received data.
+    log_replay: Option<LogReplay>,
+    /// When data was last received, or the port opened.
+    last_received_at: Option<Instant>,
+    /// Whether the receive timeout elapsed since data was last received.
+    receive_timed_out: bool,
+    /// When data was last received, for the activity indicator.
+    last_rx_instant: Option<Instant>,
+    /// When data was last sent, for the activity indicator.
+    last_tx_instant: Option<Instant>,
+    /// Whether flow control pauses sending.
+    flow_monitor: FlowMonitor,
+    /// Bytes received since the port was last opened.
+    rx_bytes: u64,
+    /// Bytes sent since the port was last opened.
+    tx_bytes: u64,
+    /// Errors reported since the port was last opened.
+    error_count: u32,
+    /// When the current session ended, once the port closed.
+    closed_at: Option<DateTime<Local>>,
+    /// Counters of the sessions before the current one, oldest first, at
+    /// most [`MAX_SESSION_HISTORY`].
+    session_history: Vec<SessionStats>,
+    /// Counters of the sessions dropped from the history, added up.
+    earlier_sessions: Option<SessionStats>,
+    /// Number of the sessions dropped from the history.
+    earlier_session_count: usize,
+    /// Packet templates edited in the templates panel.
+    templates: Vec<PacketTemplate>,
 }
 
 impl Default for PortData {
@@ -62,107 +282,339 @@ impl PortData {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            source_file: FileData { file: Vec::new() },
-            send_data: Vec::new(),
+            session_log: SessionLog::new(),
+            clear_pending: false,
+            send_queue: Vec::new(),
             cache_data: CacheData::new(),
             state: PortState::Close,
             data_type: DataType::Utf8,
-            line_feed: false,
+            line_ending: LineEnding::None,
+            line_feed: None,
+            local_echo: LocalEcho::Always,
+            last_sent: None,
+            response_timer: ResponseTimer::default(),
+            packet_delimiter: PacketDelimiter::None,
+            delimiter_buffer: RecordSplitter::default(),
             utf8_buffer: Vec::new(),
+            pending_raw: Vec::new(),
             console_mode: false,
             show_timestamp: false,
+            timestamp_mode: TimestampMode::None,
+            timestamp_format: None,
+            opened_at: None,
+            strict_hex: false,
+            hex_auto_space: true,
+            parse_escapes: false,
+            auto_detect_encoding: false,
+            gbk_detected: false,
             display_buffer: VecDeque::new(),
             display_text: String::new(),
-            file_writer: None,
+            display_page: None,
+            heatmap_view: false,
+            signal_state: None,
+            nmea: None,
+            last_error: None,
+            last_error_at: None,
+            recent_errors: Vec::new(),
+            sequence: SendSequence::default(),
+            sequence_run: None,
+            send_options: SendOptions::default(),
+            repeat: None,
+            ring_buffer: VecDeque::new(),
+            max_ring_bytes: DEFAULT_RING_BYTES,
+            binary_recorder: None,
+            session_recorder: None,
+            binary_playback: None,
+            log_replay: None,
+            last_received_at: None,
+            receive_timed_out: false,
+            last_rx_instant: None,
+            last_tx_instant: None,
+            flow_monitor: FlowMonitor::default(),
+            rx_bytes: 0,
+            tx_bytes: 0,
+            error_count: 0,
+            closed_at: None,
+            session_history: Vec::new(),
+            earlier_sessions: None,
+            earlier_session_count: 0,
+            templates: Vec::new(),
         }
     }
 
-    /// Adds a source file for logging under the relative `logs/` directory and returns the new file count.
-    ///
-    /// Sanitization rules:
-    /// - Leading `/` or `\` is stripped (prevents absolute paths).
-    /// - Inner `/` or `\` are replaced with `_`.
-    /// - `..` components are removed to prevent directory traversal attacks.
-    ///
-    /// The final stored path is always `logs/<sanitized_name>`.
-    /// On failure to create the file, an error is logged but the path is still recorded.
+    /// Returns the session log files of the port.
+    #[must_use]
+    pub const fn session_log(&self) -> &SessionLog {
+        &self.session_log
+    }
+
+    /// Returns the session log files of the port, to write or read them.
+    pub const fn session_log_mut(&mut self) -> &mut SessionLog {
+        &mut self.session_log
+    }
+
+    /// Adds a source file for logging under the log directory and returns the new file count.
+    #[deprecated(note = "use `session_log_mut().add_file`")]
     pub fn add_source_file(&mut self, name: String) -> usize {
-        // Ensure logs directory exists (best-effort; ignore errors here).
-        let _ = std::fs::create_dir_all("logs");
-
-        // Sanitize user-provided file name (e.g. "/dev/ttyUSB0_20250101_010101.txt").
-        // Strip leading slashes, replace inner slashes/backslashes with underscores,
-        // and remove `..` components to prevent path traversal attacks.
-        let sanitized = name
-            .trim_start_matches('/')
-            .trim_start_matches('\\')
-            .replace(['/', '\\'], "_")
-            .replace("..", "");
-
-        let path = format!("logs/{sanitized}");
-
-        match OpenOptions::new()
-            .create(true)
-            .read(true)
-            .append(true)
-            .open(&path)
-        {
-            Ok(file) => {
-                self.file_writer = Some(BufWriter::new(file));
-            }
-            Err(e) => {
-                error!("Failed to create source file {path}: {e}");
-                self.file_writer = None;
-            }
-        }
+        self.session_log.add_file(name)
+    }
+
+    /// Starts the log file of a new port session and returns its path.
+    #[deprecated(note = "use `session_log_mut().start_session`")]
+    pub fn start_session_log(&mut self, port_name: &str) -> String {
+        self.session_log.start_session(port_name)
+    }
+
+    /// Returns the directory log files are written to.
+    #[deprecated(note = "use `session_log().dir`")]
+    #[must_use]
+    pub fn log_dir(&self) -> &Path {
+        self.session_log.dir()
+    }
+
+    /// Returns the log directory and size override in use.
+    #[deprecated(note = "use `session_log().config`")]
+    #[must_use]
+    pub const fn log_config(&self) -> &LogConfig {
+        self.session_log.config()
+    }
+
+    /// Sets the log directory and size override; applies from the next log
+    /// file.
+    #[deprecated(note = "use `session_log_mut().set_config`")]
+    pub fn set_log_config(&mut self, config: &LogConfig) {
+        self.session_log.set_config(config);
+    }
+
+    /// Gets the log size and retention policy.
+    #[deprecated(note = "use `session_log().policy`")]
+    #[must_use]
+    pub const fn log_policy(&self) -> LogPolicy {
+        self.session_log.policy()
+    }
 
-        self.source_file.file.push(path);
-        self.source_file.file.len()
+    /// Sets the log size and retention policy.
+    #[deprecated(note = "use `session_log_mut().set_policy`")]
+    pub fn set_log_policy(&mut self, policy: LogPolicy) {
+        self.session_log.set_policy(policy);
     }
 
     /// Gets the number of source files.
+    #[deprecated(note = "use `session_log().file_count`")]
     #[must_use]
     pub const fn source_file_index(&self) -> usize {
-        self.source_file.file.len()
+        self.session_log.file_count()
     }
 
     /// Writes data to the last source file and memory display buffer.
-    /// Format depends on show_timestamp setting:
-    /// - If show_timestamp is true: writes with [timestamp source] prefix
+    ///
+    /// The log file format depends on the show_timestamp setting:
+    /// - If show_timestamp is true: writes `[timestamp source]data` followed by
+    ///   a line feed (unless the data already ends with one)
     /// - If show_timestamp is false: writes raw data without prefix
     ///
-    /// This also maintains a cached `display_text` string for efficient reads.
-    /// When `display_buffer` exceeds 5000 entries, the oldest entries are trimmed
-    /// from both the buffer and the cached text.
+    /// The log file is written by the [`SessionLog`], which buffers it and
+    /// rolls it over when it grows too large. If [`Self::clear_log`] could
+    /// not hand the file to the log writer thread to be emptied, that is
+    /// retried first, so the new data does not end up after the old.
+    ///
+    /// The display buffer keeps the entry time and source so the receive view
+    /// can be rendered in any [`TimestampMode`]. This also maintains a cached
+    /// `display_text` string for efficient reads. When `display_buffer` exceeds
+    /// 5000 entries, the oldest entries are trimmed from both the buffer and
+    /// the cached text.
     pub fn write_source_file(&mut self, data: &[u8], source: DataSource) {
-        let line = if self.show_timestamp {
-            let time = chrono::Local::now()
-                .format("%Y%m%d %H:%M:%S.%3f")
-                .to_string();
-            format!("\n[{time} {source}]{}", String::from_utf8_lossy(data))
-        } else {
-            String::from_utf8_lossy(data).into_owned()
-        };
+        self.write_source_entry(data, data.to_vec(), source);
+    }
+
+    /// Writes data like [`Self::write_source_file`], keeping `raw`, the bytes
+    /// it was decoded from or encoded to, for [`Self::display_raw_bytes`].
+    pub fn write_source_entry(&mut self, data: &[u8], raw: Vec<u8>, source: DataSource) {
+        let time = Local::now();
+        let text = String::from_utf8_lossy(data).into_owned();
+        self.write_log_line(time, &text, source);
+        self.push_display_entry(time, source, text, raw, None);
+    }
+
+    /// Writes sent data, encoded to `raw`, as set by [`Self::local_echo`]:
+    /// with [`LocalEcho::Never`] it goes to the log file only.
+    ///
+    /// With [`LocalEcho::AutoDedupe`] the sent bytes are kept to recognize
+    /// their echo, see [`Self::take_echo`].
+    ///
+    /// The send is also recorded to time the response to it, see
+    /// [`Self::response_timing`].
+    pub fn write_sent(&mut self, data: &[u8], raw: Vec<u8>) {
+        self.response_timer.sent(Instant::now());
+        match self.local_echo {
+            LocalEcho::Always => self.write_source_entry(data, raw, DataSource::Write),
+            LocalEcho::Never => {
+                let text = String::from_utf8_lossy(data);
+                self.write_log_line(Local::now(), &text, DataSource::Write);
+            }
+            LocalEcho::AutoDedupe { .. } => {
+                self.last_sent = Some(PendingEcho {
+                    remaining: raw.clone(),
+                    started: false,
+                    sent_at: Instant::now(),
+                });
+                self.write_source_entry(data, raw, DataSource::Write);
+            }
+        }
+    }
+
+    /// Writes received data that echoes sent data to the log file only,
+    /// marked as an echo.
+    pub fn write_echo(&mut self, text: &[u8]) {
+        let text = format!("(echo) {}", String::from_utf8_lossy(text));
+        self.write_log_line(Local::now(), &text, DataSource::Read);
+    }
 
-        // Write to persistent file writer with proper error logging
-        if let Some(writer) = &mut self.file_writer {
-            if let Err(e) = writer.write_all(line.as_bytes()) {
-                warn!("Failed to write to source file: {e}");
+    /// Returns how many leading bytes of the received `raw` chunk echo the
+    /// data sent last: with [`LocalEcho::AutoDedupe`], the bytes that repeat
+    /// it byte for byte within its window.
+    ///
+    /// The echo may arrive split over several chunks: each chunk continuing
+    /// the sent bytes counts, until all of them came back. Data received
+    /// before the echo starts is passed over; data breaking off a started
+    /// echo, or the end of the window, ends the wait for it.
+    pub fn take_echo(&mut self, raw: &[u8], now: Instant) -> usize {
+        let LocalEcho::AutoDedupe { window_ms } = self.local_echo else {
+            self.last_sent = None;
+            return 0;
+        };
+        let Some(pending) = &mut self.last_sent else {
+            return 0;
+        };
+        if now.saturating_duration_since(pending.sent_at) > Duration::from_millis(window_ms) {
+            self.last_sent = None;
+            return 0;
+        }
+        let len = raw.len().min(pending.remaining.len());
+        if len == 0 || raw[..len] != pending.remaining[..len] {
+            if pending.started {
+                self.last_sent = None;
             }
-            if let Err(e) = writer.flush() {
-                warn!("Failed to flush source file writer: {e}");
+            return 0;
+        }
+        pending.remaining.drain(..len);
+        pending.started = true;
+        if pending.remaining.is_empty() {
+            self.last_sent = None;
+        }
+        len
+    }
+
+    /// Gets how sent data is echoed into the receive view.
+    #[must_use]
+    pub const fn local_echo(&self) -> LocalEcho {
+        self.local_echo
+    }
+
+    /// Sets how sent data is echoed into the receive view.
+    pub fn set_local_echo(&mut self, local_echo: LocalEcho) {
+        if !matches!(local_echo, LocalEcho::AutoDedupe { .. }) {
+            self.last_sent = None;
+        }
+        self.local_echo = local_echo;
+    }
+
+    /// Writes one entry to the log file, if one is open, prefixed with its
+    /// time and source if log timestamps are on.
+    fn write_log_line(&mut self, time: DateTime<Local>, text: &str, source: DataSource) {
+        if self.clear_pending {
+            // Retried once, so the cleared file does not get the new data
+            // appended to the old.
+            self.clear_pending = false;
+            if let Err(e) = self.session_log.clear() {
+                warn!("Failed to clear the log file: {e}");
             }
         }
+        self.session_log
+            .write_entry(time, text, source, self.show_timestamp);
+    }
+
+    /// Writes `text` decoded from the received `raw` bytes.
+    ///
+    /// Bytes that decoded to no text yet, such as the start of a multi-byte
+    /// character, are kept and attributed to the next text written. If the
+    /// text answers a send, its response time is shown after it.
+    pub fn write_received(&mut self, raw: &[u8], text: &[u8]) {
+        self.pending_raw.extend_from_slice(raw);
+        if !text.is_empty() {
+            let raw = std::mem::take(&mut self.pending_raw);
+            let time = Local::now();
+            let response_time = self.response_timer.received(Instant::now());
+            let text = String::from_utf8_lossy(text).into_owned();
+            self.write_log_line(time, &text, DataSource::Read);
+            self.push_display_entry(time, DataSource::Read, text, raw, response_time);
+        }
+    }
+
+    /// Gets how received data is matched to sends to time responses.
+    #[must_use]
+    pub const fn response_timing(&self) -> ResponseTiming {
+        self.response_timer.timing()
+    }
+
+    /// Sets how received data is matched to sends to time responses.
+    pub fn set_response_timing(&mut self, timing: ResponseTiming) {
+        if self.response_timer.timing() != timing {
+            self.response_timer.set_timing(timing);
+        }
+    }
+
+    /// Returns the statistics of the recent response times, or `None` if
+    /// none were timed.
+    #[must_use]
+    pub fn response_stats(&self) -> Option<ResponseStats> {
+        self.response_timer.stats()
+    }
 
-        // Push to memory display buffer and update cached text
-        self.display_buffer.push_back(line.clone());
-        self.display_text.push_str(&line);
+    /// Flushes the log file if data has been buffered for longer than
+    /// [`LOG_FLUSH_INTERVAL`].
+    #[deprecated(note = "use `session_log_mut().flush_if_due`")]
+    pub fn flush_file_writer_if_due(&mut self) {
+        self.session_log.flush_if_due();
+    }
+
+    /// Appends an entry to the display buffer and the cached text.
+    fn push_display_entry(
+        &mut self,
+        time: DateTime<Local>,
+        source: DataSource,
+        text: String,
+        raw: Vec<u8>,
+        response_time: Option<Duration>,
+    ) {
+        let reference = match self.timestamp_mode {
+            TimestampMode::DeltaSinceLast => self.display_buffer.back().map(|entry| entry.time),
+            TimestampMode::DeltaSincePortOpen => self.opened_at,
+            _ => None,
+        };
+        let mut entry = DisplayEntry {
+            time,
+            source,
+            text,
+            raw,
+            response_time,
+            rendered_len: 0,
+        };
+        let rendered = render_entry(
+            self.timestamp_mode,
+            self.timestamp_format.as_deref(),
+            &entry,
+            reference,
+        );
+        entry.rendered_len = rendered.len();
+        self.display_buffer.push_back(entry);
+        self.display_text.push_str(&rendered);
 
         // Trim buffer if it exceeds the maximum size
-        while self.display_buffer.len() > 5000 {
+        while self.display_buffer.len() > MAX_DISPLAY_ENTRIES {
             if let Some(removed) = self.display_buffer.pop_front() {
                 // Remove the same content from the front of the cached text
-                let remove_len = removed.len();
+                let remove_len = removed.rendered_len;
                 if remove_len <= self.display_text.len() {
                     self.display_text.drain(..remove_len);
                 }
@@ -170,232 +622,1821 @@ impl PortData {
         }
     }
 
-    /// Reads the current display data from the in-memory cache.
-    ///
-    /// This uses the pre-built `display_text` cache rather than concatenating
-    /// the buffer on every call, providing O(1) access to accumulated data.
+    /// Re-renders the cached display text, e.g. after the timestamp mode changed.
+    fn rebuild_display_text(&mut self) {
+        let mode = self.timestamp_mode;
+        let format = self.timestamp_format.clone();
+        let mut text = String::new();
+        let mut previous = None;
+        for entry in &mut self.display_buffer {
+            let reference = match mode {
+                TimestampMode::DeltaSinceLast => previous,
+                TimestampMode::DeltaSincePortOpen => self.opened_at,
+                _ => None,
+            };
+            let rendered = render_entry(mode, format.as_deref(), entry, reference);
+            entry.rendered_len = rendered.len();
+            text.push_str(&rendered);
+            previous = Some(entry.time);
+        }
+        self.display_text = text;
+    }
+
+    /// Gets the receive view timestamp mode.
     #[must_use]
-    pub fn read_current_source_file_bytes(&self) -> Vec<u8> {
-        self.display_text.as_bytes().to_vec()
+    pub const fn timestamp_mode(&self) -> TimestampMode {
+        self.timestamp_mode
     }
 
-    /// Clears the in-memory display buffer and cached text for the current log view.
-    pub fn clear_display_buffer(&mut self) {
-        self.display_buffer.clear();
-        self.display_text.clear();
+    /// Sets the receive view timestamp mode and re-renders the view.
+    pub fn set_timestamp_mode(&mut self, mode: TimestampMode) {
+        if self.timestamp_mode != mode {
+            self.timestamp_mode = mode;
+            self.rebuild_display_text();
+        }
     }
 
-    /// Flushes the persistent file writer.
-    pub fn flush_file_writer(&mut self) {
-        if let Some(writer) = &mut self.file_writer
-            && let Err(e) = writer.flush()
-        {
-            warn!("Failed to flush file writer: {e}");
+    /// Gets the strftime format override of the receive view.
+    #[must_use]
+    pub fn timestamp_format(&self) -> Option<&str> {
+        self.timestamp_format.as_deref()
+    }
+
+    /// Sets the strftime format override and re-renders the view.
+    ///
+    /// An empty format clears the override.
+    pub fn set_timestamp_format(&mut self, format: Option<String>) {
+        let format = format.filter(|f| !f.is_empty());
+        if self.timestamp_format != format {
+            self.timestamp_format = format;
+            self.rebuild_display_text();
         }
     }
 
-    /// Reads a specific source file by index.
+    /// Records that the port was just opened, starting a new session.
+    ///
+    /// A previous session that did not close, e.g. after a port error, is
+    /// moved to the session history first.
+    pub fn mark_opened(&mut self) {
+        self.end_session();
+        self.opened_at = Some(Local::now());
+        self.closed_at = None;
+        self.last_received_at = Some(Instant::now());
+        self.receive_timed_out = false;
+        self.reset_stats();
+    }
+
+    /// Ends the current session and adds its counters to the session history.
+    ///
+    /// Does nothing if the port was never opened or the session already
+    /// ended. The counters stay readable until the port is opened again.
+    /// Beyond [`MAX_SESSION_HISTORY`] sessions, the oldest one is dropped
+    /// from the history and only kept in the lifetime totals.
+    pub fn end_session(&mut self) {
+        if self.closed_at.is_some() {
+            return;
+        }
+        if let Some(mut session) = self.current_session() {
+            let now = Local::now();
+            session.closed_at = Some(now);
+            self.closed_at = Some(now);
+            self.session_history.push(session);
+            if self.session_history.len() > MAX_SESSION_HISTORY {
+                let oldest = self.session_history.remove(0);
+                self.earlier_sessions =
+                    SessionStats::total(self.earlier_sessions.iter().chain([&oldest]));
+                self.earlier_session_count += 1;
+            }
+        }
+    }
+
+    /// Resets the counters and response times of the current session,
+    /// keeping the session history.
+    pub fn reset_stats(&mut self) {
+        self.rx_bytes = 0;
+        self.tx_bytes = 0;
+        self.error_count = 0;
+        self.response_timer.reset();
+    }
+
+    /// Returns the counters of the current session, or of the last one once
+    /// the port closed, if the port was ever opened.
     #[must_use]
-    pub fn read_source_file(&self, index: usize) -> String {
-        self.source_file
-            .file
-            .get(index)
-            .and_then(|path| {
-                OpenOptions::new()
-                    .read(true)
-                    .open(path)
-                    .ok()
-                    .map(|mut file| {
-                        let mut data = String::new();
-                        let _ = file.read_to_string(&mut data);
-                        data
-                    })
-            })
-            .unwrap_or_default()
+    pub fn current_session(&self) -> Option<SessionStats> {
+        self.opened_at.map(|opened_at| SessionStats {
+            opened_at,
+            closed_at: self.closed_at,
+            bytes_sent: self.tx_bytes,
+            bytes_received: self.rx_bytes,
+            error_count: self.error_count,
+        })
     }
 
-    /// Gets a source file name by index.
+    /// Returns the counters of the last [`MAX_SESSION_HISTORY`] ended
+    /// sessions, oldest first.
     #[must_use]
-    pub fn get_source_file_name(&self, index: usize) -> &str {
-        self.source_file
-            .file
-            .get(index)
-            .map(String::as_str)
-            .unwrap_or_default()
+    pub fn session_history(&self) -> &[SessionStats] {
+        &self.session_history
     }
 
-    /// Queues data to be sent.
-    pub fn send_data(&mut self, data: String) {
-        self.send_data.push(data);
+    /// Returns the number of ended sessions dropped from the session history.
+    #[must_use]
+    pub const fn earlier_session_count(&self) -> usize {
+        self.earlier_session_count
     }
 
-    /// Gets and clears the send data queue.
-    pub fn get_send_data(&mut self) -> Vec<String> {
-        std::mem::take(&mut self.send_data)
+    /// Returns the counters of all sessions added up, including the ones
+    /// dropped from the history, or `None` if the port was never opened.
+    #[must_use]
+    pub fn lifetime_stats(&self) -> Option<SessionStats> {
+        let current = self.current_session().filter(|_| self.closed_at.is_none());
+        SessionStats::total(
+            self.earlier_sessions
+                .iter()
+                .chain(&self.session_history)
+                .chain(current.as_ref()),
+        )
     }
 
-    /// Clears the send data queue.
-    pub fn clear_send_data(&mut self) {
-        self.send_data.clear();
+    /// Records that data was just received, clearing a receive timeout.
+    pub fn mark_received(&mut self) {
+        let now = Instant::now();
+        self.last_received_at = Some(now);
+        self.last_rx_instant = Some(now);
+        self.receive_timed_out = false;
     }
 
-    /// Sets the data encoding type.
-    pub const fn set_data_type(&mut self, data_type: DataType) {
-        self.data_type = data_type;
+    /// Records that data was just sent.
+    pub fn mark_sent(&mut self) {
+        self.last_tx_instant = Some(Instant::now());
     }
 
-    /// Gets a mutable reference to the cache data.
-    pub const fn get_cache_data(&mut self) -> &mut CacheData {
-        &mut self.cache_data
+    /// Adds `len` received bytes to the session count.
+    pub const fn count_received(&mut self, len: usize) {
+        self.rx_bytes += len as u64;
     }
 
-    /// Gets a mutable reference to the port state.
-    pub const fn state(&mut self) -> &mut PortState {
-        &mut self.state
+    /// Adds `len` sent bytes to the session count.
+    pub const fn count_sent(&mut self, len: usize) {
+        self.tx_bytes += len as u64;
     }
 
-    /// Gets a reference to the port state (read-only).
-    #[must_use]
-    pub const fn state_ref(&self) -> &PortState {
-        &self.state
+    /// Counts an error reported by the port thread.
+    pub const fn count_error(&mut self) {
+        self.error_count += 1;
     }
 
-    /// Gets a mutable reference to the data type.
-    pub const fn data_type(&mut self) -> &mut DataType {
-        &mut self.data_type
+    /// Returns the errors reported since the port was last opened.
+    #[must_use]
+    pub const fn error_count(&self) -> u32 {
+        self.error_count
     }
 
-    /// Gets a mutable reference to the line feed setting.
-    pub const fn line_feed(&mut self) -> &mut bool {
-        &mut self.line_feed
+    /// Returns the bytes received since the port was last opened.
+    #[must_use]
+    pub const fn rx_bytes(&self) -> u64 {
+        self.rx_bytes
     }
 
-    /// Gets a mutable reference to the console mode setting.
-    pub const fn console_mode(&mut self) -> &mut bool {
-        &mut self.console_mode
+    /// Returns the bytes sent since the port was last opened.
+    #[must_use]
+    pub const fn tx_bytes(&self) -> u64 {
+        self.tx_bytes
     }
 
-    /// Returns true if console mode is enabled.
+    /// Returns the path of the log file currently written, if any.
     #[must_use]
-    pub const fn is_console_mode(&self) -> bool {
-        self.console_mode
+    #[deprecated(note = "use `session_log().current_path`")]
+    pub fn current_log_path(&self) -> Option<&str> {
+        self.session_log.current_path()
     }
 
-    /// Gets a mutable reference to the show timestamp setting.
-    pub const fn show_timestamp(&mut self) -> &mut bool {
-        &mut self.show_timestamp
+    /// Returns when data was last received, if ever.
+    #[must_use]
+    pub const fn last_rx_instant(&self) -> Option<Instant> {
+        self.last_rx_instant
     }
 
-    /// Returns true if timestamps should be shown.
+    /// Returns when data was last sent, if ever.
     #[must_use]
-    pub const fn is_show_timestamp(&self) -> bool {
-        self.show_timestamp
+    pub const fn last_tx_instant(&self) -> Option<Instant> {
+        self.last_tx_instant
     }
 
-    /// Processes raw bytes with UTF-8 buffer handling.
-    /// Also normalizes line endings: converts \r\n to \n and removes standalone \r
-    pub fn process_raw_bytes(&mut self, data: &[u8]) -> Vec<u8> {
-        // Add new data to buffer
-        self.utf8_buffer.extend_from_slice(data);
+    /// Gets whether flow control pauses sending.
+    pub const fn flow_monitor(&mut self) -> &mut FlowMonitor {
+        &mut self.flow_monitor
+    }
+
+    /// Returns when data was last received, or the port opened.
+    #[must_use]
+    pub const fn last_received_at(&self) -> Option<Instant> {
+        self.last_received_at
+    }
 
-        // Try to decode as much as possible
-        let (valid_str, incomplete_len) = self.extract_valid_utf8();
+    /// Returns true if the receive timeout elapsed since data was last received.
+    #[must_use]
+    pub const fn is_receive_timed_out(&self) -> bool {
+        self.receive_timed_out
+    }
 
-        // Remove processed bytes from buffer
-        if incomplete_len > 0 {
-            self.utf8_buffer
-                .drain(..(self.utf8_buffer.len() - incomplete_len));
-        } else {
-            self.utf8_buffer.clear();
+    /// Returns true if nothing was received for `timeout` at `now`.
+    ///
+    /// A timeout is reported once; it is reported again only after data has
+    /// been received in between.
+    pub fn check_receive_timeout(&mut self, timeout: Duration, now: Instant) -> bool {
+        if self.receive_timed_out {
+            return false;
         }
+        let elapsed = self
+            .last_received_at
+            .is_some_and(|last| now.saturating_duration_since(last) >= timeout);
+        self.receive_timed_out = elapsed;
+        elapsed
+    }
 
-        // Normalize line endings: \r\n -> \n, standalone \r -> \n
-        let normalized = valid_str.replace("\r\n", "\n").replace('\r', "\n");
+    /// Reads the current display data from the in-memory cache.
+    ///
+    /// This uses the pre-built `display_text` cache rather than concatenating
+    /// the buffer on every call, providing O(1) access to accumulated data.
+    #[must_use]
+    pub fn read_current_source_file_bytes(&self) -> Vec<u8> {
+        self.display_text.as_bytes().to_vec()
+    }
 
-        normalized.into_bytes()
+    /// Gets the cached display text shown in the receive view.
+    #[must_use]
+    pub fn display_text(&self) -> &str {
+        &self.display_text
     }
 
-    /// Extracts valid UTF-8 from buffer, returns (valid_string, incomplete_bytes_count)
-    fn extract_valid_utf8(&self) -> (String, usize) {
-        if self.utf8_buffer.is_empty() {
-            return (String::new(), 0);
+    /// Returns the raw bytes of the display entries overlapping `range`, a
+    /// byte range of the display text.
+    ///
+    /// Received entries give the bytes as read from the port, sent entries the
+    /// bytes as written to it.
+    #[must_use]
+    pub fn display_raw_bytes(&self, range: Range<usize>) -> Vec<u8> {
+        let mut raw = Vec::new();
+        let mut start = 0;
+        for entry in &self.display_buffer {
+            let end = start + entry.rendered_len;
+            if start < range.end && range.start < end {
+                raw.extend_from_slice(&entry.raw);
+            }
+            start = end;
         }
+        raw
+    }
 
-        // Try to decode the entire buffer
-        match std::str::from_utf8(&self.utf8_buffer) {
-            Ok(valid_str) => {
-                // All bytes are valid UTF-8
-                (valid_str.to_string(), 0)
+    /// Returns the most recently received text, at least `max_bytes` of it if
+    /// that much is buffered.
+    ///
+    /// Only data read from the port is included; sent data and errors are
+    /// skipped.
+    #[must_use]
+    pub fn recent_received_text(&self, max_bytes: usize) -> String {
+        let mut entries = Vec::new();
+        let mut len = 0;
+        for entry in self.display_buffer.iter().rev() {
+            if len >= max_bytes {
+                break;
             }
-            Err(e) => {
-                let valid_len = e.valid_up_to();
-                if valid_len > 0 {
-                    // We have some valid UTF-8 at the beginning
-                    let valid_str =
-                        std::str::from_utf8(&self.utf8_buffer[..valid_len]).unwrap_or("�");
-                    (valid_str.to_string(), self.utf8_buffer.len() - valid_len)
-                } else {
-                    // No valid UTF-8 at start, check if we have incomplete UTF-8 at end
-                    let incomplete_len = self.count_incomplete_utf8_suffix();
-                    if incomplete_len > 0 && incomplete_len < 4 {
-                        // Likely incomplete UTF-8 sequence, keep it for next time
-                        let valid_len = self.utf8_buffer.len() - incomplete_len;
-                        if valid_len > 0 {
-                            let valid_str =
-                                std::str::from_utf8(&self.utf8_buffer[..valid_len]).unwrap_or("�");
-                            (valid_str.to_string(), incomplete_len)
-                        } else {
-                            // All bytes are incomplete, keep them all
-                            (String::new(), incomplete_len)
-                        }
-                    } else {
-                        // Invalid UTF-8, replace with replacement char
-                        ("�".to_string(), 0)
-                    }
-                }
+            if entry.source == DataSource::Read {
+                len += entry.text.len();
+                entries.push(entry.text.as_str());
             }
         }
+        entries.iter().rev().copied().collect()
     }
 
-    /// Counts incomplete UTF-8 sequence at the end of buffer
-    fn count_incomplete_utf8_suffix(&self) -> usize {
-        if self.utf8_buffer.is_empty() {
-            return 0;
-        }
+    /// Appends received bytes to the receive ring buffer, dropping the oldest
+    /// bytes beyond its size limit.
+    pub fn push_received(&mut self, data: &[u8]) {
+        let data = &data[data.len().saturating_sub(self.max_ring_bytes)..];
+        let excess = (self.ring_buffer.len() + data.len()).saturating_sub(self.max_ring_bytes);
+        self.ring_buffer.drain(..excess);
+        self.ring_buffer.extend(data);
+    }
+
+    /// Gets the receive ring buffer: the most recently received raw bytes.
+    #[must_use]
+    pub const fn ring_buffer(&self) -> &VecDeque<u8> {
+        &self.ring_buffer
+    }
+
+    /// Gets the receive ring buffer as one slice, rearranging it in place if
+    /// it wrapped around.
+    pub fn received_bytes(&mut self) -> &[u8] {
+        self.ring_buffer.make_contiguous()
+    }
+
+    /// Gets the size limit of the receive ring buffer.
+    #[must_use]
+    pub const fn max_ring_bytes(&self) -> usize {
+        self.max_ring_bytes
+    }
+
+    /// Sets the size limit of the receive ring buffer, dropping the oldest
+    /// bytes beyond it right away.
+    pub fn set_max_ring_bytes(&mut self, max_bytes: usize) {
+        self.max_ring_bytes = max_bytes;
+        let excess = self.ring_buffer.len().saturating_sub(max_bytes);
+        self.ring_buffer.drain(..excess);
+    }
+
+    /// Clears the in-memory display buffer and cached text for the current log view.
+    pub fn clear_display_buffer(&mut self) {
+        self.display_buffer.clear();
+        self.display_text.clear();
+        self.display_page = None;
+    }
+
+    /// Clears the current log: empties the current log file, dropping the
+    /// data not flushed yet, clears the receive view and the receive ring
+    /// buffer, and returns the view to the latest data. The session goes on
+    /// logging to the same file.
+    ///
+    /// The byte and error counters are kept, as they count the whole session.
+    /// The file is emptied on the log writer thread; a failure there is
+    /// reported by [`Self::take_log_clear_failure`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be handed to the log writer
+    /// thread to be emptied; the next data logged tries again first.
+    pub fn clear_log(&mut self) -> Result<()> {
+        self.clear_display_buffer();
+        self.ring_buffer.clear();
+        self.clear_pending = true;
+        self.session_log.clear()?;
+        self.clear_pending = false;
+        Ok(())
+    }
+
+    /// Continues the session log in the next `_partN` file and clears the
+    /// receive view, which shows the new file. The earlier files are kept.
+    pub fn rotate_log(&mut self) {
+        self.session_log.rotate();
+        self.clear_display_buffer();
+    }
+
+    /// Returns why the log file of the last [`Self::clear_log`] could not be
+    /// emptied, once the log writer thread reported it.
+    pub fn take_log_clear_failure(&mut self) -> Option<String> {
+        self.session_log.take_clear_failure()
+    }
+
+    /// Gets the page of the display text shown in the receive view, or
+    /// `None` if the view follows the last page.
+    #[must_use]
+    pub const fn display_page(&self) -> Option<usize> {
+        self.display_page
+    }
+
+    /// Sets the page shown in the receive view; `None` follows the last page.
+    pub const fn set_display_page(&mut self, page: Option<usize>) {
+        self.display_page = page;
+    }
+
+    /// Returns true if the receive view shows the received bytes as a
+    /// heatmap instead of text.
+    #[must_use]
+    pub const fn is_heatmap_view(&self) -> bool {
+        self.heatmap_view
+    }
+
+    /// Sets whether the receive view shows the received bytes as a heatmap.
+    pub const fn set_heatmap_view(&mut self, heatmap_view: bool) {
+        self.heatmap_view = heatmap_view;
+    }
+
+    /// Returns the byte range of the display text on `page`, counted from 0
+    /// with `lines_per_page` lines each, and the number of pages.
+    ///
+    /// There is always at least one page; a `page` past the last one gives
+    /// the last page, which holds the most recent data.
+    #[must_use]
+    pub fn display_page_range(&self, page: usize, lines_per_page: usize) -> (Range<usize>, usize) {
+        let text = &self.display_text;
+        let lines_per_page = lines_per_page.max(1);
+        let pages = text
+            .split_inclusive('\n')
+            .count()
+            .div_ceil(lines_per_page)
+            .max(1);
+        let page = page.min(pages - 1);
+        let line_start = |line: usize| match line {
+            0 => 0,
+            line => text
+                .match_indices('\n')
+                .nth(line - 1)
+                .map_or(text.len(), |(index, _)| index + 1),
+        };
+        let start = line_start(page * lines_per_page);
+        let end = line_start((page + 1) * lines_per_page);
+        (start..end, pages)
+    }
+
+    /// Returns the lines of the display text on `page`, without their line
+    /// breaks, and the number of pages; see [`Self::display_page_range`].
+    ///
+    /// Only the requested page is copied, so a long session does not cost
+    /// more per frame than one page of it.
+    #[must_use]
+    pub fn read_source_page(&self, page: usize, lines_per_page: usize) -> (Vec<String>, usize) {
+        let (range, pages) = self.display_page_range(page, lines_per_page);
+        let lines = self.display_text[range]
+            .lines()
+            .map(str::to_string)
+            .collect();
+        (lines, pages)
+    }
 
-        // Check last 1-3 bytes for incomplete UTF-8 sequence
-        let len = self.utf8_buffer.len();
-        let check_len = std::cmp::min(3, len);
-
-        for i in 1..=check_len {
-            let start = len - i;
-            let slice = &self.utf8_buffer[start..];
-
-            // Check if this could be the start of a UTF-8 sequence
-            if slice[0] >= 0x80 {
-                // Check if this is a continuation byte or start of multi-byte sequence
-                // Check if it is a valid UTF-8 start byte
-                if (slice[0] & 0xE0) == 0xC0 && (1..=2).contains(&i) {
-                    // 2-byte sequence
-                    return if i == 1 { 1 } else { 0 };
-                } else if (slice[0] & 0xF0) == 0xE0 && (1..=3).contains(&i) {
-                    // 3-byte sequence
-                    return if i <= 2 { i } else { 0 };
-                } else if (slice[0] & 0xF8) == 0xF0 && (1..=4).contains(&i) {
-                    // 4-byte sequence
-                    return if i <= 3 { i } else { 0 };
-                } else if (slice[0] & 0xC0) == 0x80 {
-                    // Continuation byte
-                    return i;
+    /// Returns the byte range of the display text drawn as window `index`
+    /// of the `page` range, counted back from its end in windows of about
+    /// `window` bytes.
+    ///
+    /// The windows tile the page: each ends where the later one starts. A
+    /// window starting inside a line starts at the next line instead, unless
+    /// that line does not end within the window; one starting inside a
+    /// character starts before it. Windows before the page start are empty.
+    #[must_use]
+    pub fn display_text_window(
+        &self,
+        page: Range<usize>,
+        window: usize,
+        index: usize,
+    ) -> Range<usize> {
+        let text = &self.display_text;
+        let floor = |mut index: usize| {
+            index = index.min(text.len());
+            while !text.is_char_boundary(index) {
+                index -= 1;
+            }
+            index
+        };
+        let page_start = floor(page.start);
+        let mut end = floor(page.end).max(page_start);
+        let mut range = end..end;
+        for _ in 0..=index {
+            let mut start = floor(end.saturating_sub(window.max(1))).max(page_start);
+            if start > 0 && text.as_bytes()[start - 1] != b'\n' {
+                match text[start..end].find('\n') {
+                    Some(offset) if start + offset + 1 < end => start += offset + 1,
+                    _ => {}
                 }
             }
+            range = start..end;
+            end = start;
         }
+        range
+    }
 
-        0
+    /// Hands the buffered log data to the log writer thread, without waiting
+    /// for it to be written.
+    #[deprecated(note = "use `session_log_mut().queue_flush`")]
+    pub fn queue_log_flush(&mut self) {
+        self.session_log.queue_flush();
     }
 
-    /// Clears the UTF-8 buffer.
+    /// Flushes the buffered log data and blocks until the log writer thread
+    /// has written it.
+    #[deprecated(note = "use `session_log_mut().flush`")]
+    pub fn flush_file_writer(&mut self) {
+        self.session_log.flush();
+    }
+
+    /// Reads a specific source file by index.
+    #[deprecated(note = "use `session_log_mut().read_file`")]
+    #[must_use]
+    pub fn read_source_file(&mut self, index: usize) -> String {
+        self.session_log.read_file(index)
+    }
+
+    /// Parses the current source file into its entries.
+    #[deprecated(note = "use `session_log_mut().entries`")]
+    pub fn parse_source_file_entries(&mut self) -> impl Iterator<Item = LogEntry> + use<> {
+        self.session_log.entries()
+    }
+
+    /// Gets a source file name by index.
+    #[deprecated(note = "use `session_log().file_name`")]
+    #[must_use]
+    pub fn get_source_file_name(&self, index: usize) -> &str {
+        self.session_log.file_name(index)
+    }
+
+    /// Queues encoded data to be sent.
+    pub fn queue_send(&mut self, payload: SendPayload) {
+        self.send_queue.push(payload);
+    }
+
+    /// Queues text to be sent, encoded now with the port's
+    /// [`Self::send_settings`]. Text that is refused, see
+    /// [`encode_for_send`], is dropped.
+    pub fn send_data(&mut self, data: String) {
+        match SendPayload::encode(&data, &self.send_settings()) {
+            Ok(payload) => self.queue_send(payload),
+            Err(refusal) => warn!("Refusing to send input: {}", refusal.message()),
+        }
+    }
+
+    /// Queues text to be sent as set by `options`, encoded now with the
+    /// port's [`Self::send_settings`]. Text that is refused, see
+    /// [`encode_for_send`], is dropped.
+    pub fn send_data_with_options(&mut self, data: String, options: SendOptions) {
+        match SendPayload::encode(&data, &self.send_settings()) {
+            Ok(payload) => self.queue_send_with_options(payload, options),
+            Err(refusal) => warn!("Refusing to send input: {}", refusal.message()),
+        }
+    }
+
+    /// Queues encoded data to be sent as set by `options`.
+    ///
+    /// Repeats with an interval queue the first send now and replace any
+    /// repeats in progress; without an interval all sends are queued now.
+    pub fn queue_send_with_options(&mut self, payload: SendPayload, options: SendOptions) {
+        let count = options.repeat_count.unwrap_or(1).max(1);
+        match options
+            .repeat_interval
+            .filter(|interval| !interval.is_zero())
+        {
+            Some(interval) if count > 1 => {
+                self.queue_send(payload.clone());
+                self.repeat = Some(RepeatSchedule::start(payload, count, interval));
+            }
+            _ => {
+                for _ in 0..count {
+                    self.queue_send(payload.clone());
+                }
+            }
+        }
+    }
+
+    /// Gets how data submitted from the input is sent.
+    #[must_use]
+    pub const fn send_options(&self) -> SendOptions {
+        self.send_options
+    }
+
+    /// Gets how data submitted from the input is sent, mutably.
+    pub const fn send_options_mut(&mut self) -> &mut SendOptions {
+        &mut self.send_options
+    }
+
+    /// Gets the repeats in progress.
+    #[must_use]
+    pub const fn repeat_schedule(&self) -> Option<&RepeatSchedule> {
+        self.repeat.as_ref()
+    }
+
+    /// Advances the repeats in progress by `delta` and queues the sends due,
+    /// dropping the schedule once all were queued.
+    pub fn advance_repeat(&mut self, delta: Duration) {
+        let Some(repeat) = &mut self.repeat else {
+            return;
+        };
+        let due = repeat.advance(delta);
+        let payload = repeat.payload().clone();
+        if repeat.is_finished() {
+            self.repeat = None;
+        }
+        for _ in 0..due {
+            self.queue_send(payload.clone());
+        }
+    }
+
+    /// Discards the remaining repeats.
+    pub fn stop_repeat(&mut self) {
+        self.repeat = None;
+    }
+
+    /// Queues an encoded packet to be sent as is.
+    pub fn send_packet(&mut self, packet: Vec<u8>) {
+        self.queue_send(SendPayload::bytes(packet));
+    }
+
+    /// Gets and clears the send queue.
+    pub fn get_send_data(&mut self) -> Vec<SendPayload> {
+        std::mem::take(&mut self.send_queue)
+    }
+
+    /// Clears the send queue.
+    pub fn clear_send_data(&mut self) {
+        self.send_queue.clear();
+    }
+
+    /// Gets the packet templates of this port.
+    pub const fn templates_mut(&mut self) -> &mut Vec<PacketTemplate> {
+        &mut self.templates
+    }
+
+    /// Sets the data encoding type.
+    pub const fn set_data_type(&mut self, data_type: DataType) {
+        self.data_type = data_type;
+    }
+
+    /// Gets a mutable reference to the cache data.
+    pub const fn get_cache_data(&mut self) -> &mut CacheData {
+        &mut self.cache_data
+    }
+
+    /// Gets a mutable reference to the port state.
+    pub const fn state(&mut self) -> &mut PortState {
+        &mut self.state
+    }
+
+    /// Gets a reference to the port state (read-only).
+    #[must_use]
+    pub const fn state_ref(&self) -> &PortState {
+        &self.state
+    }
+
+    /// Gets a mutable reference to the data type.
+    pub const fn data_type(&mut self) -> &mut DataType {
+        &mut self.data_type
+    }
+
+    /// Gets a mutable reference to the line ending appended to sent data.
+    pub fn line_ending(&mut self) -> &mut LineEnding {
+        self.line_ending = self.effective_line_ending();
+        self.line_feed = None;
+        &mut self.line_ending
+    }
+
+    /// Gets a mutable reference to the line feed setting: true if a line
+    /// ending is appended to sent data.
+    ///
+    /// Turning it on appends a line feed, turning it off appends nothing.
+    #[deprecated(note = "use `line_ending`, which also offers CR and CRLF")]
+    pub fn line_feed(&mut self) -> &mut bool {
+        let line_feed = self.effective_line_ending() != LineEnding::None;
+        self.line_feed.insert(line_feed)
+    }
+
+    /// Appends a line feed to sent data, or nothing.
+    #[deprecated(note = "use `line_ending`, which also offers CR and CRLF")]
+    pub const fn set_line_feed(&mut self, line_feed: bool) {
+        self.line_ending = LineEnding::from_line_feed(line_feed);
+        self.line_feed = None;
+    }
+
+    /// Returns the line ending appended to sent data, taking a toggle of
+    /// the deprecated [`Self::line_feed`] into account.
+    const fn effective_line_ending(&self) -> LineEnding {
+        match self.line_feed {
+            Some(true) if matches!(self.line_ending, LineEnding::None) => LineEnding::Lf,
+            Some(false) => LineEnding::None,
+            _ => self.line_ending,
+        }
+    }
+
+    /// Gets a mutable reference to the console mode setting.
+    pub const fn console_mode(&mut self) -> &mut bool {
+        &mut self.console_mode
+    }
+
+    /// Returns true if console mode is enabled.
+    #[must_use]
+    pub const fn is_console_mode(&self) -> bool {
+        self.console_mode
+    }
+
+    /// Gets a mutable reference to the show timestamp setting.
+    pub const fn show_timestamp(&mut self) -> &mut bool {
+        &mut self.show_timestamp
+    }
+
+    /// Returns true if timestamps should be shown.
+    #[must_use]
+    pub const fn is_show_timestamp(&self) -> bool {
+        self.show_timestamp
+    }
+
+    /// Gets the latest modem status lines, if known.
+    #[must_use]
+    pub const fn signal_state(&self) -> Option<SignalState> {
+        self.signal_state
+    }
+
+    /// Sets the latest modem status lines.
+    pub const fn set_signal_state(&mut self, signal_state: Option<SignalState>) {
+        self.signal_state = signal_state;
+    }
+
+    /// Gets the latest error reported by the port thread, if any.
+    #[must_use]
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Gets when the latest error was reported, if any.
+    #[must_use]
+    pub const fn last_error_at(&self) -> Option<DateTime<Local>> {
+        self.last_error_at
+    }
+
+    /// Sets the latest error reported by the port thread, reported now, and
+    /// takes the last [`RECENT_ERROR_LINES`] error lines of the log for it
+    /// from the lines the session log keeps in memory.
+    ///
+    /// Call it after logging the error, so its own line is included.
+    pub fn set_last_error(&mut self, error: Option<String>) {
+        self.last_error_at = error.as_ref().map(|_| Local::now());
+        self.recent_errors = if error.is_some() {
+            self.session_log.recent_errors(RECENT_ERROR_LINES)
+        } else {
+            Vec::new()
+        };
+        self.last_error = error;
+    }
+
+    /// Returns the most recent error lines of the log, oldest first, as read
+    /// when the latest error was reported.
+    #[must_use]
+    pub fn recent_error_lines(&self) -> &[String] {
+        &self.recent_errors
+    }
+
+    /// Gets a mutable reference to the strict hex setting.
+    pub const fn strict_hex(&mut self) -> &mut bool {
+        &mut self.strict_hex
+    }
+
+    /// Returns true if invalid hex input should be refused.
+    #[must_use]
+    pub const fn is_strict_hex(&self) -> bool {
+        self.strict_hex
+    }
+
+    /// Gets a mutable reference to the hex auto-spacing setting.
+    pub const fn hex_auto_space(&mut self) -> &mut bool {
+        &mut self.hex_auto_space
+    }
+
+    /// Returns true if hex input should be regrouped into space-separated bytes.
+    #[must_use]
+    pub const fn is_hex_auto_space(&self) -> bool {
+        self.hex_auto_space
+    }
+
+    /// Returns the settings that decide how input is encoded for sending.
+    #[must_use]
+    pub const fn send_settings(&self) -> SendSettings {
+        SendSettings {
+            data_type: self.data_type,
+            line_ending: self.effective_line_ending(),
+            strict_hex: self.strict_hex,
+            escapes: self.parse_escapes,
+        }
+    }
+
+    /// Gets a mutable reference to the escape parsing setting.
+    pub const fn parse_escapes(&mut self) -> &mut bool {
+        &mut self.parse_escapes
+    }
+
+    /// Returns true if escape sequences should be parsed in text modes.
+    #[must_use]
+    pub const fn is_parse_escapes(&self) -> bool {
+        self.parse_escapes
+    }
+
+    /// Gets a mutable reference to the encoding auto-detection setting.
+    pub const fn auto_detect_encoding(&mut self) -> &mut bool {
+        &mut self.auto_detect_encoding
+    }
+
+    /// Returns true if the last non-ASCII data received in UTF-8 mode was
+    /// auto-detected as GBK.
+    #[must_use]
+    pub const fn is_gbk_detected(&self) -> bool {
+        self.gbk_detected
+    }
+
+    /// Decodes received bytes according to the port's data type.
+    ///
+    /// UTF-8 and the double-byte Chinese encodings (GBK, GB2312, Big5) are
+    /// decoded as a stream: a multi-byte character split across two reads is
+    /// held back until its remaining bytes arrive. [`DataType::Raw`] data is
+    /// shown as `\xNN` escapes. Other data types are passed through unchanged.
+    ///
+    /// With encoding auto-detection on, UTF-8 data containing invalid bytes is
+    /// decoded as GBK if it looks like GBK-encoded Chinese text.
+    pub fn decode_received(&mut self, data: &[u8]) -> Vec<u8> {
+        match self.data_type {
+            DataType::Utf8 if self.auto_detect_encoding => {
+                let mut pending = self.utf8_buffer.clone();
+                pending.extend_from_slice(data);
+                if is_valid_utf8_prefix(&pending) {
+                    if !pending.is_ascii() {
+                        self.gbk_detected = false;
+                    }
+                    self.process_raw_bytes(data)
+                } else if is_probably_gbk(&pending) {
+                    self.gbk_detected = true;
+                    self.process_dbcs_bytes(data, encoding_rs::GBK)
+                } else {
+                    self.process_raw_bytes(data)
+                }
+            }
+            DataType::Utf8 => self.process_raw_bytes(data),
+            DataType::Gbk | DataType::Gb2312 => self.process_dbcs_bytes(data, encoding_rs::GBK),
+            DataType::Big5 => self.process_dbcs_bytes(data, encoding_rs::BIG5),
+            DataType::Raw => decode_bytes(data, DataType::Raw).into_bytes(),
+            _ => data.to_vec(),
+        }
+    }
+
+    /// Processes raw bytes with UTF-8 buffer handling.
+    ///
+    /// Only complete characters are emitted; up to 3 trailing bytes of an
+    /// incomplete sequence are kept for the next call. Invalid bytes are
+    /// replaced with U+FFFD, including a kept sequence that the next bytes
+    /// do not complete, so the buffer never holds more than 3 bytes. Line
+    /// endings are normalized: `\r\n` and standalone `\r` become `\n`.
+    pub fn process_raw_bytes(&mut self, data: &[u8]) -> Vec<u8> {
+        self.utf8_buffer.extend_from_slice(data);
+
+        let mut decoded = String::with_capacity(self.utf8_buffer.len());
+        let mut rest = self.utf8_buffer.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    decoded.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    decoded.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(invalid_len) => {
+                            decoded.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[invalid_len..];
+                        }
+                        None => {
+                            // Incomplete sequence at the end: keep it for the next read.
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        let consumed = self.utf8_buffer.len() - rest.len();
+        self.utf8_buffer.drain(..consumed);
+
+        normalize_line_endings(&decoded)
+    }
+
+    /// Processes raw bytes of a double-byte encoding such as GBK or Big5.
+    ///
+    /// A trailing lead byte without its trail byte is kept for the next call.
+    fn process_dbcs_bytes(
+        &mut self,
+        data: &[u8],
+        encoding: &'static encoding_rs::Encoding,
+    ) -> Vec<u8> {
+        self.utf8_buffer.extend_from_slice(data);
+
+        let complete_len = self.utf8_buffer.len() - incomplete_dbcs_suffix(&self.utf8_buffer);
+        let (decoded, _) = encoding.decode_without_bom_handling(&self.utf8_buffer[..complete_len]);
+        let decoded = normalize_line_endings(&decoded);
+        self.utf8_buffer.drain(..complete_len);
+
+        decoded
+    }
+
+    /// Returns true if received data is parsed as NMEA 0183.
+    #[must_use]
+    pub const fn is_nmea_enabled(&self) -> bool {
+        self.nmea.is_some()
+    }
+
+    /// Enables or disables NMEA 0183 parsing; disabling discards the parsed sentences.
+    pub fn set_nmea_enabled(&mut self, enabled: bool) {
+        if enabled != self.nmea.is_some() {
+            self.nmea = enabled.then(NmeaParser::new);
+        }
+    }
+
+    /// Gets the NMEA parser, if enabled.
+    #[must_use]
+    pub const fn nmea_parser(&self) -> Option<&NmeaParser> {
+        self.nmea.as_ref()
+    }
+
+    /// Gets the NMEA parser mutably, if enabled.
+    pub const fn nmea_parser_mut(&mut self) -> Option<&mut NmeaParser> {
+        self.nmea.as_mut()
+    }
+
+    /// Gets the send sequence edited for this port.
+    pub const fn sequence_mut(&mut self) -> &mut SendSequence {
+        &mut self.sequence
+    }
+
+    /// Starts running the send sequence, replacing any previous run.
+    pub fn start_sequence(&mut self) {
+        self.sequence_run = Some(SequenceRun::start(self.sequence.clone()));
+    }
+
+    /// Gets the current or last sequence run.
+    #[must_use]
+    pub const fn sequence_run(&self) -> Option<&SequenceRun> {
+        self.sequence_run.as_ref()
+    }
+
+    /// Gets the current or last sequence run mutably.
+    pub const fn sequence_run_mut(&mut self) -> Option<&mut SequenceRun> {
+        self.sequence_run.as_mut()
+    }
+
+    /// Starts recording raw received bytes to `path`, appending if it exists.
+    ///
+    /// Replaces any recording in progress. Chunk intervals are written to
+    /// the recording's timing file next to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files cannot be opened.
+    pub fn start_binary_recording(&mut self, path: &Path) -> Result<()> {
+        self.stop_binary_recording();
+        self.binary_recorder = Some(BinaryRecorder::create(path)?);
+        Ok(())
+    }
+
+    /// Stops the raw recording, flushing and closing its files.
+    pub fn stop_binary_recording(&mut self) {
+        if let Some(mut recorder) = self.binary_recorder.take()
+            && let Err(e) = recorder.flush()
+        {
+            warn!(
+                "Failed to flush recording {}: {e}",
+                recorder.path().display()
+            );
+        }
+    }
+
+    /// Returns the path of the raw recording in progress.
+    #[must_use]
+    pub fn binary_recording_path(&self) -> Option<&Path> {
+        self.binary_recorder.as_ref().map(BinaryRecorder::path)
+    }
+
+    /// Appends received bytes to the raw recording, if recording.
+    ///
+    /// A write error stops the recording.
+    pub fn record_raw(&mut self, data: &[u8]) {
+        if let Some(recorder) = self.binary_recorder.as_mut()
+            && let Err(e) = recorder.record(data)
+        {
+            warn!(
+                "Failed to write recording {}: {e}",
+                recorder.path().display()
+            );
+            self.binary_recorder = None;
+        }
+    }
+
+    /// Starts or stops recording the session: the raw bytes received and
+    /// sent with their timing, see [`super::session`].
+    ///
+    /// The recording is written next to the current log file, with the
+    /// `.jsonl` extension, and replaces any recording in progress. It is
+    /// written on a writer thread of its own, which logs any failure.
+    pub fn record_session(&mut self, enabled: bool) {
+        self.stop_session_recording();
+        if enabled {
+            let path = session_recording_path(
+                self.session_log.dir(),
+                self.session_log.base(),
+                Local::now(),
+            );
+            self.session_recorder = Some(SessionRecorder::create(&path));
+        }
+    }
+
+    /// Stops the session recording; its writer thread writes the frames
+    /// recorded so far and closes the file.
+    pub fn stop_session_recording(&mut self) {
+        self.session_recorder = None;
+    }
+
+    /// Returns the path of the session recording in progress.
+    #[must_use]
+    pub fn session_recording_path(&self) -> Option<&Path> {
+        self.session_recorder.as_ref().map(SessionRecorder::path)
+    }
+
+    /// Appends received or sent bytes to the session recording, if
+    /// recording.
+    ///
+    /// A write error stops the recording.
+    pub fn record_session_frame(&mut self, direction: SessionDirection, data: &[u8]) {
+        if let Some(recorder) = self.session_recorder.as_mut()
+            && let Err(e) = recorder.record(direction, data)
+        {
+            warn!(
+                "Failed to write session recording {}: {e}",
+                recorder.path().display()
+            );
+            self.session_recorder = None;
+        }
+    }
+
+    /// Starts sending the recording at `path` to the port with its original
+    /// chunk intervals, replacing any playback in progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recording cannot be read.
+    pub fn play_binary_recording(&mut self, path: &Path) -> Result<()> {
+        self.binary_playback = Some(BinaryPlayback::load(path)?);
+        Ok(())
+    }
+
+    /// Gets the playback in progress.
+    #[must_use]
+    pub const fn binary_playback(&self) -> Option<&BinaryPlayback> {
+        self.binary_playback.as_ref()
+    }
+
+    /// Gets the playback in progress mutably.
+    pub const fn binary_playback_mut(&mut self) -> Option<&mut BinaryPlayback> {
+        self.binary_playback.as_mut()
+    }
+
+    /// Stops the playback in progress.
+    pub fn stop_binary_playback(&mut self) {
+        self.binary_playback = None;
+    }
+
+    /// Starts replaying the received entries of the session log at `path`
+    /// as received data, replacing any replay in progress.
+    ///
+    /// The returned future reads the log and sends the entries with their
+    /// original intervals divided by `speed_multiplier`, or all at once if
+    /// it is 0; it must be spawned on the runtime. See
+    /// [`super::replay`].
+    pub fn replay_from_log(
+        &mut self,
+        path: &Path,
+        speed_multiplier: f32,
+    ) -> impl Future<Output = Result<()>> + Send + use<> {
+        let (replay, task) = LogReplay::start(path, speed_multiplier);
+        self.log_replay = Some(replay);
+        task
+    }
+
+    /// Gets the log replay in progress.
+    #[must_use]
+    pub const fn log_replay(&self) -> Option<&LogReplay> {
+        self.log_replay.as_ref()
+    }
+
+    /// Takes the messages replayed since the last call. The replay is
+    /// dropped once it has finished.
+    pub fn take_replayed(&mut self) -> Vec<PortChannelData> {
+        let Some(replay) = &mut self.log_replay else {
+            return Vec::new();
+        };
+        let messages = replay.take_messages();
+        if replay.is_finished() {
+            self.log_replay = None;
+        }
+        messages
+    }
+
+    /// Stops the log replay in progress.
+    pub fn stop_log_replay(&mut self) {
+        self.log_replay = None;
+    }
+
+    /// Gets how received data is split into records.
+    #[must_use]
+    pub const fn packet_delimiter(&self) -> &PacketDelimiter {
+        &self.packet_delimiter
+    }
+
+    /// Sets how received data is split into records.
+    ///
+    /// Bytes of an incomplete record are kept and lead the next record.
+    pub fn set_packet_delimiter(&mut self, delimiter: PacketDelimiter) {
+        self.packet_delimiter = delimiter;
+    }
+
+    /// Adds received bytes and returns the records they complete, as set by
+    /// [`Self::packet_delimiter`]; see [`RecordSplitter::push`].
+    pub fn split_records(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        self.delimiter_buffer.push(data, &self.packet_delimiter)
+    }
+
+    /// Returns the bytes of an incomplete record waiting for its delimiter
+    /// and empties the buffer.
+    ///
+    /// Called when the port closes, as the record can no longer be completed.
+    pub fn take_partial_record(&mut self) -> Vec<u8> {
+        self.delimiter_buffer.take()
+    }
+
+    /// Flushes bytes held back by the stream decoder.
+    ///
+    /// Called when the port closes: a pending incomplete sequence can never be
+    /// completed, so it is emitted as a replacement character.
+    pub fn flush_utf8_buffer(&mut self) -> Vec<u8> {
+        if self.utf8_buffer.is_empty() {
+            return Vec::new();
+        }
+        self.utf8_buffer.clear();
+        char::REPLACEMENT_CHARACTER.to_string().into_bytes()
+    }
+
+    /// Clears the UTF-8 buffer.
     pub fn clear_utf8_buffer(&mut self) {
         self.utf8_buffer.clear();
+        self.pending_raw.clear();
+    }
+}
+
+/// Renders a display entry as a `[stamp source]data` line unless the mode is
+/// [`TimestampMode::None`].
+fn render_entry(
+    mode: TimestampMode,
+    format: Option<&str>,
+    entry: &DisplayEntry,
+    reference: Option<DateTime<Local>>,
+) -> String {
+    let mut rendered = match mode.format(entry.time, reference, format) {
+        Some(stamp) => terminate_line(format!("[{stamp} {}]{}", entry.source, entry.text)),
+        None => entry.text.clone(),
+    };
+    if let Some(elapsed) = entry.response_time {
+        // The response time goes at the end of the entry's last line.
+        let end = rendered.trim_end_matches(['\r', '\n']).len();
+        let (start_style, end_style) = RESPONSE_TIME_STYLE;
+        let suffix = format!(" {start_style}{}{end_style}", format_response_time(elapsed));
+        rendered.insert_str(end, &suffix);
+    }
+    rendered
+}
+
+/// Normalizes line endings: `\r\n` and standalone `\r` become `\n`.
+fn normalize_line_endings(text: &str) -> Vec<u8> {
+    text.replace("\r\n", "\n").replace('\r', "\n").into_bytes()
+}
+
+/// Returns true if `bytes` are valid UTF-8, possibly ending in an incomplete
+/// character.
+fn is_valid_utf8_prefix(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Returns true if `bytes` look like GBK-encoded Chinese text, ignoring a
+/// trailing lead byte.
+fn is_probably_gbk(bytes: &[u8]) -> bool {
+    let complete_len = bytes.len() - incomplete_dbcs_suffix(bytes);
+    gbk_confidence(&bytes[..complete_len]) > GBK_CONFIDENCE_THRESHOLD
+}
+
+/// Returns the number of trailing bytes that form an incomplete double-byte
+/// (or GB18030 four-byte) character.
+fn incomplete_dbcs_suffix(bytes: &[u8]) -> usize {
+    let mut i = 0;
+    while i < bytes.len() {
+        let width = if bytes[i] < 0x80 {
+            1
+        } else if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+            // GB18030 four-byte sequence: lead, digit, lead, digit.
+            4
+        } else {
+            2
+        };
+        if i + width > bytes.len() {
+            return bytes.len() - i;
+        }
+        i += width;
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `chunks` through the UTF-8 stream decoder and returns the output.
+    fn decode_chunks(data: &mut PortData, chunks: &[&[u8]]) -> String {
+        let mut output = Vec::new();
+        for chunk in chunks {
+            output.extend(data.decode_received(chunk));
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_utf8_split_across_two_chunks() {
+        let bytes = "你好".as_bytes();
+        for split in 0..=bytes.len() {
+            let mut data = PortData::new();
+            let (a, b) = bytes.split_at(split);
+            assert_eq!(
+                decode_chunks(&mut data, &[a, b]),
+                "你好",
+                "split at {split}"
+            );
+            assert!(data.flush_utf8_buffer().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_utf8_split_across_three_chunks() {
+        let bytes = "你好".as_bytes();
+        for first in 0..=bytes.len() {
+            for second in first..=bytes.len() {
+                let mut data = PortData::new();
+                let chunks = [&bytes[..first], &bytes[first..second], &bytes[second..]];
+                assert_eq!(
+                    decode_chunks(&mut data, &chunks),
+                    "你好",
+                    "split at {first}/{second}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_utf8_four_byte_sequence_split() {
+        let bytes = "a🚀b".as_bytes();
+        for split in 0..=bytes.len() {
+            let mut data = PortData::new();
+            let (a, b) = bytes.split_at(split);
+            assert_eq!(decode_chunks(&mut data, &[a, b]), "a🚀b");
+        }
+    }
+
+    #[test]
+    fn test_utf8_invalid_bytes_replaced() {
+        let mut data = PortData::new();
+        assert_eq!(decode_chunks(&mut data, &[b"a\xFFb"]), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn test_utf8_byte_by_byte() {
+        let text = "a¢€🚀\n温度 25℃";
+        let mut data = PortData::new();
+        let chunks: Vec<&[u8]> = text.as_bytes().chunks(1).collect();
+        assert_eq!(decode_chunks(&mut data, &chunks), text);
+        assert!(data.utf8_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_utf8_interrupted_sequence_replaced() {
+        let mut data = PortData::new();
+        // A truncated "你" followed by ASCII instead of its last byte.
+        assert!(data.decode_received(b"\xE4\xBD").is_empty());
+        assert_eq!(data.utf8_buffer.len(), 2);
+        assert_eq!(decode_chunks(&mut data, &[b"ok"]), "\u{FFFD}ok");
+        assert!(data.utf8_buffer.is_empty());
+
+        // A lead byte followed by another lead byte.
+        assert_eq!(
+            decode_chunks(&mut data, &[b"\xF0\x9F", b"\xE4\xBD\xA0"]),
+            "\u{FFFD}你"
+        );
+    }
+
+    #[test]
+    fn test_utf8_buffer_stays_bounded() {
+        let mut data = PortData::new();
+        for _ in 0..100 {
+            data.decode_received(b"\xF0\x9F\x9A");
+            assert!(data.utf8_buffer.len() <= 3);
+        }
+        assert_eq!(data.flush_utf8_buffer(), "\u{FFFD}".as_bytes());
+    }
+
+    #[test]
+    fn test_utf8_incomplete_tail_flushed_on_close() {
+        let mut data = PortData::new();
+        let bytes = "你".as_bytes();
+        assert!(data.decode_received(&bytes[..2]).is_empty());
+        assert_eq!(data.flush_utf8_buffer(), "\u{FFFD}".as_bytes());
+        assert!(data.flush_utf8_buffer().is_empty());
+    }
+
+    #[test]
+    fn test_line_endings_normalized() {
+        let mut data = PortData::new();
+        assert_eq!(decode_chunks(&mut data, &[b"a\r\nb\rc\n"]), "a\nb\nc\n");
+    }
+
+    #[test]
+    fn test_timestamp_mode_rerenders_display() {
+        let mut data = PortData::new();
+        data.write_source_file(b"OK", DataSource::Read);
+        assert_eq!(data.read_current_source_file_bytes(), b"OK");
+
+        data.set_timestamp_mode(TimestampMode::DeltaSinceLast);
+        let text = String::from_utf8(data.read_current_source_file_bytes()).unwrap();
+        assert_eq!(text, "[+0.000s R]OK\n");
+
+        data.set_timestamp_mode(TimestampMode::TimeOnly);
+        data.set_timestamp_format(Some("T%H".to_string()));
+        let text = String::from_utf8(data.read_current_source_file_bytes()).unwrap();
+        assert!(text.starts_with("[T"));
+        assert!(text.ends_with(" R]OK\n"));
+
+        data.set_timestamp_mode(TimestampMode::None);
+        assert_eq!(data.read_current_source_file_bytes(), b"OK");
+    }
+
+    #[test]
+    fn test_display_buffer_trimmed_in_timestamp_mode() {
+        let mut data = PortData::new();
+        data.set_timestamp_mode(TimestampMode::DeltaSinceLast);
+        for _ in 0..MAX_DISPLAY_ENTRIES + 10 {
+            data.write_source_file(b"x", DataSource::Read);
+        }
+        let text = String::from_utf8(data.read_current_source_file_bytes()).unwrap();
+        assert_eq!(text.matches("R]x").count(), MAX_DISPLAY_ENTRIES);
+        assert!(text.starts_with('['));
+    }
+
+    #[test]
+    fn test_receive_timeout_fires_once_until_data_arrives() {
+        let timeout = Duration::from_millis(500);
+        let mut data = PortData::new();
+        assert!(!data.check_receive_timeout(timeout, Instant::now()));
+
+        data.mark_opened();
+        let start = data.last_received_at().unwrap();
+        assert!(!data.check_receive_timeout(timeout, start + Duration::from_millis(499)));
+        assert!(data.check_receive_timeout(timeout, start + timeout));
+        assert!(data.is_receive_timed_out());
+        assert!(!data.check_receive_timeout(timeout, start + 2 * timeout));
+
+        data.mark_received();
+        assert!(!data.is_receive_timed_out());
+        let last = data.last_received_at().unwrap();
+        assert!(data.check_receive_timeout(timeout, last + timeout));
+    }
+
+    #[test]
+    fn test_activity_instants() {
+        let mut data = PortData::new();
+        data.mark_opened();
+        assert_eq!(data.last_rx_instant(), None);
+        assert_eq!(data.last_tx_instant(), None);
+
+        data.mark_received();
+        assert_eq!(data.last_rx_instant(), data.last_received_at());
+        data.mark_sent();
+        assert!(data.last_tx_instant() >= data.last_rx_instant());
+    }
+
+    #[test]
+    fn test_byte_counts_reset_on_open() {
+        let mut data = PortData::new();
+        data.count_received(10);
+        data.count_sent(3);
+        data.count_received(5);
+        assert_eq!((data.rx_bytes(), data.tx_bytes()), (15, 3));
+        data.mark_opened();
+        assert_eq!((data.rx_bytes(), data.tx_bytes()), (0, 0));
+    }
+
+    #[test]
+    fn test_session_history_keeps_closed_sessions() {
+        let mut data = PortData::new();
+        assert_eq!(data.lifetime_stats(), None);
+
+        data.mark_opened();
+        data.count_received(10);
+        data.count_error();
+        data.end_session();
+        data.end_session();
+        assert_eq!(data.session_history().len(), 1);
+        // The closed session's counters stay visible until the next open.
+        assert_eq!(data.rx_bytes(), 10);
+
+        data.mark_opened();
+        data.count_sent(4);
+        data.count_received(2);
+        let lifetime = data.lifetime_stats().unwrap();
+        assert_eq!(
+            (
+                lifetime.bytes_received,
+                lifetime.bytes_sent,
+                lifetime.error_count
+            ),
+            (12, 4, 1)
+        );
+        assert_eq!(lifetime.closed_at, None);
+
+        data.reset_stats();
+        assert_eq!(
+            (data.rx_bytes(), data.tx_bytes(), data.error_count()),
+            (0, 0, 0)
+        );
+        assert_eq!(data.lifetime_stats().unwrap().bytes_received, 10);
+
+        // A session left open by an error is recorded on the next open.
+        data.count_received(7);
+        data.mark_opened();
+        assert_eq!(data.session_history().len(), 2);
+        assert_eq!(data.session_history()[1].bytes_received, 7);
+    }
+
+    #[test]
+    fn test_session_history_is_capped() {
+        let mut data = PortData::new();
+        for _ in 0..MAX_SESSION_HISTORY + 2 {
+            data.mark_opened();
+            data.count_received(1);
+            data.end_session();
+        }
+        assert_eq!(data.session_history().len(), MAX_SESSION_HISTORY);
+        assert_eq!(data.earlier_session_count(), 2);
+        // The dropped sessions still count towards the lifetime totals.
+        assert_eq!(
+            data.lifetime_stats().unwrap().bytes_received,
+            MAX_SESSION_HISTORY as u64 + 2
+        );
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_last_bytes() {
+        let bytes: Vec<u8> = (0..100).collect();
+        let mut data = PortData::new();
+        assert_eq!(data.max_ring_bytes(), DEFAULT_RING_BYTES);
+        data.set_max_ring_bytes(50);
+        for chunk in bytes.chunks(30) {
+            data.push_received(chunk);
+        }
+        assert_eq!(data.ring_buffer().len(), 50);
+        assert!(data.ring_buffer().iter().eq(&bytes[50..]));
+
+        data.push_received(&bytes);
+        assert!(data.ring_buffer().iter().eq(&bytes[50..]));
+    }
+
+    #[test]
+    fn test_lowering_ring_limit_evicts_immediately() {
+        let bytes: Vec<u8> = (0..100).collect();
+        let mut data = PortData::new();
+        data.push_received(&bytes);
+        assert_eq!(data.ring_buffer().len(), 100);
+        data.set_max_ring_bytes(50);
+        assert!(data.ring_buffer().iter().eq(&bytes[50..]));
+        data.set_max_ring_bytes(80);
+        assert_eq!(data.ring_buffer().len(), 50);
+    }
+
+    #[test]
+    fn test_read_source_page_boundaries() {
+        let mut data = PortData::new();
+        assert_eq!(data.read_source_page(0, 10), (Vec::new(), 1));
+
+        for line in 0..25 {
+            data.write_source_file(format!("line {line}\n").as_bytes(), DataSource::Read);
+        }
+        let (first, pages) = data.read_source_page(0, 10);
+        assert_eq!(pages, 3);
+        assert_eq!(first.len(), 10);
+        assert_eq!(first[0], "line 0");
+        assert_eq!(first[9], "line 9");
+
+        let (second, _) = data.read_source_page(1, 10);
+        assert_eq!(second.first().map(String::as_str), Some("line 10"));
+        assert_eq!(second.last().map(String::as_str), Some("line 19"));
+
+        // The last page holds the most recent data, also for pages past it.
+        let (last, _) = data.read_source_page(2, 10);
+        assert_eq!(last.len(), 5);
+        assert_eq!(last.last().map(String::as_str), Some("line 24"));
+        assert_eq!(data.read_source_page(usize::MAX, 10).0, last);
+
+        let (range, _) = data.display_page_range(2, 10);
+        assert_eq!(range.end, data.display_text().len());
+        assert_eq!(
+            &data.display_text()[range],
+            "line 20\nline 21\nline 22\nline 23\nline 24\n"
+        );
+        assert_eq!(data.read_source_page(0, 25).1, 1);
+        assert_eq!(data.read_source_page(0, 24).1, 2);
+
+        data.set_display_page(Some(1));
+        data.clear_display_buffer();
+        assert_eq!(data.display_page(), None);
+    }
+
+    #[test]
+    fn test_display_text_windows_tile_whole_lines() {
+        let mut data = PortData::new();
+        assert_eq!(data.display_text_window(0..0, 100, 0), 0..0);
+
+        data.write_source_file("line 0\nline 1\nline 2\n".as_bytes(), DataSource::Read);
+        let text = data.display_text().to_string();
+        let page = 0..text.len();
+        assert_eq!(data.display_text_window(page.clone(), 100, 0), page);
+        // Windows start at a line and end where the later one starts.
+        let windows: Vec<&str> = (0..4)
+            .map(|index| &text[data.display_text_window(page.clone(), 10, index)])
+            .collect();
+        assert_eq!(windows, ["line 2\n", "line 1\n", "line 0\n", ""]);
+        // A line longer than the window is cut instead of dropped.
+        let range = data.display_text_window(page, 3, 0);
+        assert_eq!(&text[range], " 2\n");
+
+        data.clear_display_buffer();
+        data.write_source_file("温度\n".as_bytes(), DataSource::Read);
+        assert_eq!(data.display_text_window(0..7, 6, 0), 0..7);
+        assert_eq!(data.display_text_window(0..7, 6, 1), 0..0);
+    }
+
+    #[test]
+    fn test_local_echo_modes() {
+        let mut data = PortData::new();
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        assert_eq!(data.display_text(), "AT\n");
+        assert_eq!(data.take_echo(b"AT\r\n", Instant::now()), 0);
+
+        data.clear_display_buffer();
+        data.set_local_echo(LocalEcho::Never);
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        assert_eq!(data.display_text(), "");
+
+        data.set_local_echo(LocalEcho::AutoDedupe { window_ms: 100 });
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        assert_eq!(data.display_text(), "AT\n");
+        let sent_at = Instant::now();
+        assert_eq!(data.take_echo(b"AT\r\n", sent_at), 4);
+        // The echo is only expected once.
+        assert_eq!(data.take_echo(b"AT\r\n", sent_at), 0);
+
+        // An echo split over reads matches, and the reply after it does not.
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        assert_eq!(data.take_echo(b"A", Instant::now()), 1);
+        assert_eq!(data.take_echo(b"T\r", Instant::now()), 2);
+        assert_eq!(data.take_echo(b"\nOK\r\n", Instant::now()), 1);
+        assert_eq!(data.take_echo(b"AT\r\n", Instant::now()), 0);
+
+        // Data before the echo is passed over; data breaking it off ends it.
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        assert_eq!(data.take_echo(b"RING\r\n", Instant::now()), 0);
+        assert_eq!(data.take_echo(b"AT", Instant::now()), 2);
+        assert_eq!(data.take_echo(b"OK", Instant::now()), 0);
+        assert_eq!(data.take_echo(b"\r\n", Instant::now()), 0);
+
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        let late = Instant::now() + Duration::from_millis(101);
+        assert_eq!(data.take_echo(b"AT\r\n", late), 0);
+    }
+
+    #[test]
+    fn test_response_time_follows_reply() {
+        let mut data = PortData::new();
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        data.write_received(b"OK\r\n", b"OK\r\n");
+        assert_eq!(data.display_text(), "AT\nOK\r\n");
+        assert_eq!(data.response_stats(), None);
+
+        data.clear_display_buffer();
+        data.set_response_timing(ResponseTiming::MostRecent);
+        data.write_sent(b"AT\n", b"AT\r\n".to_vec());
+        data.write_received(b"OK\r\n", b"OK\r\n");
+        data.write_received(b"READY\r\n", b"READY\r\n");
+        let text = data.display_text();
+        assert!(text.starts_with("AT\nOK \x1b[90m(+"), "{text:?}");
+        assert!(text.ends_with(" ms)\x1b[39m\r\nREADY\r\n"), "{text:?}");
+        assert_eq!(data.response_stats().map(|stats| stats.count), Some(1));
+
+        data.reset_stats();
+        assert_eq!(data.response_stats(), None);
+    }
+
+    #[test]
+    fn test_recent_received_text_skips_sent_data() {
+        let mut data = PortData::new();
+        data.write_source_file(b"boot\n", DataSource::Read);
+        data.write_source_file(b"AT\r\n", DataSource::Write);
+        data.write_source_file(b"OK\r\n", DataSource::Read);
+        data.write_source_file(b"READY\r\n", DataSource::Read);
+
+        assert_eq!(data.recent_received_text(4096), "boot\nOK\r\nREADY\r\n");
+        assert_eq!(data.recent_received_text(7), "READY\r\n");
+        assert_eq!(data.recent_received_text(8), "OK\r\nREADY\r\n");
+        assert_eq!(data.recent_received_text(0), "");
+    }
+
+    #[test]
+    fn test_display_raw_bytes_keep_undecoded_bytes() {
+        let bytes = encoding_rs::GBK.encode("串口\n").0.into_owned();
+        let mut data = PortData::new();
+        data.set_data_type(DataType::Gbk);
+        for chunk in [&bytes[..1], &bytes[1..]] {
+            let text = data.decode_received(chunk);
+            data.write_received(chunk, &text);
+        }
+        data.write_source_entry(b"AT\n", b"AT\r\n".to_vec(), DataSource::Write);
+
+        let text = data.display_text().to_string();
+        assert_eq!(text, "串口\nAT\n");
+        let sent = text.find("AT").unwrap();
+        assert_eq!(data.display_raw_bytes(0..1), bytes);
+        assert_eq!(data.display_raw_bytes(sent..sent + 1), b"AT\r\n");
+        assert_eq!(
+            data.display_raw_bytes(0..text.len()),
+            [bytes.as_slice(), b"AT\r\n"].concat()
+        );
+        assert!(data.display_raw_bytes(sent..sent).is_empty());
+    }
+
+    #[test]
+    fn test_written_data_is_logged_and_displayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = PortData::new();
+        data.session_log_mut().set_policy(LogPolicy {
+            max_file_size: 1024,
+            max_files_per_port: 3,
+        });
+        data.session_log_mut()
+            .open(dir.path().join("port.txt").to_string_lossy().into_owned());
+        *data.show_timestamp() = true;
+        data.write_source_file(b"AT", DataSource::Write);
+        data.session_log_mut().flush();
+        let entries: Vec<LogEntry> = data.session_log_mut().entries().collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].data, b"AT\n");
+
+        // The receive view keeps the full live tail across rollovers.
+        *data.show_timestamp() = false;
+        for _ in 0..20 {
+            data.write_source_file(&[b'x'; 200], DataSource::Read);
+        }
+        assert_eq!(data.session_log().file_count(), 3);
+        assert_eq!(data.display_text.len(), 2 + 20 * 200);
+    }
+
+    #[test]
+    fn test_clear_log_keeps_byte_counters() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = PortData::new();
+        data.session_log_mut()
+            .open(dir.path().join("port.txt").to_string_lossy().into_owned());
+        data.mark_opened();
+        data.push_received(b"OK\r\n");
+        data.count_received(4);
+        data.write_received(b"OK\r\n", b"OK\r\n");
+        data.count_sent(2);
+        data.set_display_page(Some(0));
+
+        data.clear_log().unwrap();
+        data.session_log_mut().flush();
+        assert_eq!(data.session_log_mut().read_file(0), "");
+        assert_eq!(data.take_log_clear_failure(), None);
+        assert!(data.display_text().is_empty());
+        assert!(data.ring_buffer().is_empty());
+        assert_eq!(data.display_page(), None);
+        assert_eq!((data.rx_bytes(), data.tx_bytes()), (4, 2));
+
+        data.write_source_file(b"READY", DataSource::Read);
+        data.session_log_mut().flush();
+        assert_eq!(data.session_log_mut().read_file(0), "READY");
+    }
+
+    #[test]
+    fn test_rotate_log_starts_the_next_part() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = PortData::new();
+        *data.show_timestamp() = false;
+        data.session_log_mut()
+            .open(dir.path().join("port.txt").to_string_lossy().into_owned());
+        data.write_source_file(b"old", DataSource::Read);
+
+        data.rotate_log();
+        assert!(data.display_text().is_empty());
+        data.write_source_file(b"new", DataSource::Read);
+        assert_eq!(data.session_log().file_count(), 2);
+        data.session_log_mut().flush();
+        assert_eq!(data.session_log_mut().read_file(0), "old");
+        assert_eq!(data.session_log_mut().read_file(1), "new");
+        assert!(dir.path().join("port_part2.txt").exists());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_log_wrappers_delegate() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = PortData::new();
+        data.set_log_config(&LogConfig {
+            base_dir: dir.path().to_path_buf(),
+            max_file_size: None,
+        });
+        assert_eq!(data.log_dir(), dir.path());
+        let path = data.start_session_log("COM1");
+        assert_eq!(data.current_log_path(), Some(path.as_str()));
+        assert_eq!(data.source_file_index(), 1);
+        data.write_source_file(b"OK", DataSource::Read);
+        data.flush_file_writer();
+        assert_eq!(data.read_source_file(0), "OK");
+        assert_eq!(data.get_source_file_name(0), path);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_line_feed_toggles_line_ending() {
+        let mut data = PortData::new();
+        assert!(!*data.line_feed());
+        *data.line_feed() = true;
+        assert_eq!(data.send_settings().line_ending, LineEnding::Lf);
+        assert_eq!(*data.line_ending(), LineEnding::Lf);
+
+        *data.line_ending() = LineEnding::CrLf;
+        assert!(*data.line_feed());
+        assert_eq!(data.send_settings().line_ending, LineEnding::CrLf);
+        *data.line_feed() = false;
+        assert_eq!(data.send_settings().line_ending, LineEnding::None);
+        assert_eq!(*data.line_ending(), LineEnding::None);
+    }
+
+    #[test]
+    fn test_gbk_lead_byte_held_over() {
+        let bytes = encoding_rs::GBK.encode("串口ok").0.into_owned();
+        for split in 0..=bytes.len() {
+            let mut data = PortData::new();
+            data.set_data_type(DataType::Gbk);
+            let (a, b) = bytes.split_at(split);
+            assert_eq!(
+                decode_chunks(&mut data, &[a, b]),
+                "串口ok",
+                "split at {split}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_gbk_detected_in_utf8_mode() {
+        let bytes = encoding_rs::GBK
+            .encode("设备就绪，温度正常\n")
+            .0
+            .into_owned();
+        let mut data = PortData::new();
+        *data.auto_detect_encoding() = true;
+        let (a, b) = bytes.split_at(5);
+        assert_eq!(decode_chunks(&mut data, &[a, b]), "设备就绪，温度正常\n");
+        assert!(data.is_gbk_detected());
+
+        assert_eq!(decode_chunks(&mut data, &["正常".as_bytes()]), "正常");
+        assert!(!data.is_gbk_detected());
+    }
+
+    #[test]
+    fn test_gbk_not_detected_without_auto_detection() {
+        let bytes = encoding_rs::GBK.encode("设备").0.into_owned();
+        let mut data = PortData::new();
+        assert!(decode_chunks(&mut data, &[&bytes]).contains(char::REPLACEMENT_CHARACTER));
+        assert!(!data.is_gbk_detected());
+    }
+
+    #[test]
+    fn test_big5_lead_byte_held_over() {
+        let bytes = encoding_rs::BIG5.encode("中文").0.into_owned();
+        let mut data = PortData::new();
+        data.set_data_type(DataType::Big5);
+        assert_eq!(
+            decode_chunks(&mut data, &[&bytes[..1], &bytes[1..]]),
+            "中文"
+        );
+    }
+
+    #[test]
+    fn test_binary_recording_keeps_raw_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("port.bin");
+        let mut data = PortData::new();
+        data.record_raw(b"ignored");
+        data.start_binary_recording(&path).unwrap();
+        assert_eq!(data.binary_recording_path(), Some(path.as_path()));
+        data.record_raw(&[0xE4, 0xB8]);
+        data.record_raw(&[0xAD, b'\n']);
+        data.stop_binary_recording();
+        data.record_raw(b"ignored");
+        assert_eq!(data.binary_recording_path(), None);
+        assert_eq!(std::fs::read(&path).unwrap(), "中\n".as_bytes());
+
+        data.play_binary_recording(&path).unwrap();
+        let playback = data.binary_playback_mut().unwrap();
+        assert_eq!(playback.progress(), (0, 2));
+        assert_eq!(
+            playback.advance(Duration::from_secs(60)).concat(),
+            "中\n".as_bytes()
+        );
     }
 }
@@ -0,0 +1,347 @@
+//! # Recording Module
+//!
+//! Raw binary recordings of received data, and their playback.
+//!
+//! A recording is made of two files. `<path>` holds the received bytes exactly
+//! as they arrived, without any decoration, so it can be opened in a hex
+//! editor. `<path>.timing` holds one record per received chunk: the interval
+//! since the previous chunk in microseconds and the chunk length, both as
+//! 8-byte little-endian integers. Playback sends the chunks again with their
+//! original intervals.
+
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use chrono::{DateTime, Local};
+
+use super::io::write_to_port;
+use super::log::sanitize_port_name;
+use super::snapshot::lock_serial;
+use super::status::SerialIo;
+use super::traffic::TrafficLog;
+use crate::error::{Result, SerialBevyError};
+
+/// Suffix appended to a recording's path to get its timing file.
+pub const TIMING_SUFFIX: &str = ".timing";
+
+/// Size of one record in the timing file.
+const TIMING_RECORD_LEN: usize = 16;
+
+/// Returns the timing file of the recording at `path`.
+#[must_use]
+pub fn timing_path(path: &Path) -> PathBuf {
+    let mut timing = OsString::from(path.as_os_str());
+    timing.push(TIMING_SUFFIX);
+    PathBuf::from(timing)
+}
+
+/// Returns the default recording file of a port started at `time`:
+/// `<dir>/<port>_<time>.bin`, with the port name made file-name safe. `dir`
+/// is usually the port's log directory.
+#[must_use]
+pub fn recording_path(dir: &Path, port_name: &str, time: DateTime<Local>) -> PathBuf {
+    dir.join(format!(
+        "{}_{}.bin",
+        sanitize_port_name(port_name),
+        time.format("%Y%m%d_%H%M%S")
+    ))
+}
+
+/// Writes received chunks to a recording.
+pub struct BinaryRecorder {
+    /// Path of the raw data file.
+    path: PathBuf,
+    /// Raw data file.
+    data: BufWriter<File>,
+    /// Timing file.
+    timing: BufWriter<File>,
+    /// When the previous chunk was recorded, or the recording started.
+    last_chunk: Instant,
+}
+
+impl BinaryRecorder {
+    /// Opens the recording at `path` for appending, creating its directory
+    /// and files if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be opened.
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let open = |path: &Path| OpenOptions::new().create(true).append(true).open(path);
+        Ok(Self {
+            path: path.to_path_buf(),
+            data: BufWriter::new(open(path)?),
+            timing: BufWriter::new(open(&timing_path(path))?),
+            last_chunk: Instant::now(),
+        })
+    }
+
+    /// Returns the path of the raw data file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records a received chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be written.
+    pub fn record(&mut self, chunk: &[u8]) -> Result<()> {
+        let now = Instant::now();
+        let interval = now.duration_since(self.last_chunk);
+        self.last_chunk = now;
+        self.record_after(interval, chunk)
+    }
+
+    /// Records a chunk received `interval` after the previous one.
+    fn record_after(&mut self, interval: Duration, chunk: &[u8]) -> Result<()> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        let micros = u64::try_from(interval.as_micros()).unwrap_or(u64::MAX);
+        self.data.write_all(chunk)?;
+        self.timing.write_all(&micros.to_le_bytes())?;
+        self.timing.write_all(&(chunk.len() as u64).to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Flushes both files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be written.
+    pub fn flush(&mut self) -> Result<()> {
+        self.data.flush()?;
+        self.timing.flush()?;
+        Ok(())
+    }
+}
+
+/// Playback of a recording: its chunks with the interval before each.
+#[derive(Debug, Default)]
+pub struct BinaryPlayback {
+    /// Chunks not played yet.
+    chunks: VecDeque<(Duration, Vec<u8>)>,
+    /// Number of chunks in the recording.
+    total: usize,
+    /// Time since the previous chunk was played.
+    elapsed: Duration,
+}
+
+impl BinaryPlayback {
+    /// Loads the recording at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read or the timing file does not
+    /// match the raw data.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read(path)?;
+        let timing = fs::read(timing_path(path))?;
+        if timing.len() % TIMING_RECORD_LEN != 0 {
+            return Err(SerialBevyError::serialization(format!(
+                "{}: truncated timing record",
+                timing_path(path).display()
+            )));
+        }
+
+        let mut chunks = VecDeque::new();
+        let mut offset = 0usize;
+        for record in timing.chunks_exact(TIMING_RECORD_LEN) {
+            let (micros, len) = record.split_at(8);
+            let micros = u64::from_le_bytes(micros.try_into().unwrap_or_default());
+            let len = u64::from_le_bytes(len.try_into().unwrap_or_default());
+            let end = usize::try_from(len)
+                .ok()
+                .and_then(|len| offset.checked_add(len))
+                .filter(|end| *end <= data.len())
+                .ok_or_else(|| {
+                    SerialBevyError::serialization(format!(
+                        "{}: timing file refers past the end of the data",
+                        path.display()
+                    ))
+                })?;
+            chunks.push_back((Duration::from_micros(micros), data[offset..end].to_vec()));
+            offset = end;
+        }
+        if offset != data.len() {
+            return Err(SerialBevyError::serialization(format!(
+                "{}: {} bytes without timing records",
+                path.display(),
+                data.len() - offset
+            )));
+        }
+
+        Ok(Self {
+            total: chunks.len(),
+            chunks,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    /// Advances playback by `delta` and returns the chunks that are due, in order.
+    pub fn advance(&mut self, delta: Duration) -> Vec<Vec<u8>> {
+        self.elapsed += delta;
+        let mut due = Vec::new();
+        while let Some((interval, _)) = self.chunks.front() {
+            if self.elapsed < *interval {
+                break;
+            }
+            self.elapsed -= *interval;
+            if let Some((_, chunk)) = self.chunks.pop_front() {
+                due.push(chunk);
+            }
+        }
+        due
+    }
+
+    /// Returns the number of played chunks and the total number of chunks.
+    #[must_use]
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.chunks.len(), self.total)
+    }
+
+    /// Returns true once every chunk has been played.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+/// System: sends the due chunks of the recordings being played back.
+///
+/// Playback stops when it is finished or the port is closed.
+pub fn play_binary_recordings(
+    time: Res<Time>,
+    ports: Query<&SerialIo>,
+    mut traffic: ResMut<TrafficLog>,
+) {
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        let is_open = serial.is_open();
+        let Some(playback) = serial.data().binary_playback_mut() else {
+            continue;
+        };
+        let chunks = if is_open {
+            playback.advance(time.delta())
+        } else {
+            Vec::new()
+        };
+        let finished = !is_open || playback.is_finished();
+
+        for chunk in chunks {
+            let text = String::from_utf8_lossy(&chunk).into_owned();
+            write_to_port(&mut serial, chunk, &text, &mut traffic);
+        }
+        if finished {
+            serial.data().stop_binary_playback();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_keeps_raw_bytes_and_intervals() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture").join("port.bin");
+        let mut recorder = BinaryRecorder::create(&path).unwrap();
+        recorder
+            .record_after(Duration::from_millis(5), &[0x00, 0xFF, b'\r'])
+            .unwrap();
+        recorder.record_after(Duration::ZERO, b"").unwrap();
+        recorder
+            .record_after(Duration::from_millis(250), b"OK\r\n")
+            .unwrap();
+        recorder.flush().unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"\x00\xFF\rOK\r\n");
+        let timing = fs::read(timing_path(&path)).unwrap();
+        assert_eq!(timing.len(), 2 * TIMING_RECORD_LEN);
+        assert_eq!(timing[..8], 5000u64.to_le_bytes());
+        assert_eq!(timing[8..16], 3u64.to_le_bytes());
+        assert_eq!(timing[16..24], 250_000u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_playback_keeps_original_intervals() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("port.bin");
+        let mut recorder = BinaryRecorder::create(&path).unwrap();
+        recorder
+            .record_after(Duration::from_millis(10), b"A")
+            .unwrap();
+        recorder
+            .record_after(Duration::from_millis(100), b"BC")
+            .unwrap();
+        recorder.record_after(Duration::ZERO, b"D").unwrap();
+        recorder.flush().unwrap();
+
+        let mut playback = BinaryPlayback::load(&path).unwrap();
+        assert_eq!(playback.progress(), (0, 3));
+        assert!(playback.advance(Duration::from_millis(5)).is_empty());
+        assert_eq!(playback.advance(Duration::from_millis(5)), [b"A".to_vec()]);
+        assert!(playback.advance(Duration::from_millis(99)).is_empty());
+        assert_eq!(
+            playback.advance(Duration::from_millis(1)),
+            [b"BC".to_vec(), b"D".to_vec()]
+        );
+        assert!(playback.is_finished());
+        assert_eq!(playback.progress(), (3, 3));
+    }
+
+    #[test]
+    fn test_recording_appends_to_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("port.bin");
+        for chunk in [b"AT\r\n", b"OK\r\n"] {
+            let mut recorder = BinaryRecorder::create(&path).unwrap();
+            recorder.record_after(Duration::ZERO, chunk).unwrap();
+            recorder.flush().unwrap();
+        }
+        let mut playback = BinaryPlayback::load(&path).unwrap();
+        assert_eq!(
+            playback.advance(Duration::ZERO),
+            [b"AT\r\n".to_vec(), b"OK\r\n".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_playback_rejects_mismatched_timing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("port.bin");
+        fs::write(&path, b"ABC").unwrap();
+        let mut timing = 0u64.to_le_bytes().to_vec();
+        timing.extend(2u64.to_le_bytes());
+        fs::write(timing_path(&path), &timing).unwrap();
+        assert!(BinaryPlayback::load(&path).is_err());
+
+        timing.extend(5u64.to_le_bytes());
+        fs::write(timing_path(&path), &timing).unwrap();
+        assert!(BinaryPlayback::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_recording_path() {
+        let time = chrono::TimeZone::with_ymd_and_hms(&Local, 2025, 1, 2, 3, 4, 5).unwrap();
+        assert_eq!(
+            recording_path(Path::new("logs"), "/dev/ttyUSB0", time),
+            Path::new("logs").join("dev_ttyUSB0_20250102_030405.bin")
+        );
+        assert_eq!(
+            timing_path(Path::new("logs/port.bin")),
+            Path::new("logs/port.bin.timing")
+        );
+    }
+}
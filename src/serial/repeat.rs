@@ -0,0 +1,158 @@
+//! # Repeat Module
+//!
+//! Repeated sends for stress-testing a device with the same packet.
+//!
+//! Sending with [`SendOptions`] that ask for repeats queues the data once and
+//! keeps a [`RepeatSchedule`] for the rest, advanced every frame by
+//! [`run_send_repeats`] with the frame time. The remaining repeats are
+//! discarded if the port closes.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::port_data::SendPayload;
+use super::snapshot::lock_serial;
+use super::status::SerialIo;
+
+/// Number of sends offered when repeating is turned on.
+pub const DEFAULT_REPEAT_COUNT: u32 = 10;
+
+/// Interval between repeats offered when repeating is turned on.
+pub const DEFAULT_REPEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How submitted data is sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SendOptions {
+    /// Number of times the data is sent, or `None` to send it once.
+    pub repeat_count: Option<u32>,
+    /// Time between two sends, or `None` to queue all of them at once.
+    pub repeat_interval: Option<Duration>,
+}
+
+/// Remaining sends of data sent with repeats.
+#[derive(Clone, Debug)]
+pub struct RepeatSchedule {
+    /// Data sent each time.
+    payload: SendPayload,
+    /// Number of sends queued so far.
+    sent: u32,
+    /// Total number of sends.
+    count: u32,
+    /// Time between two sends.
+    interval: Duration,
+    /// Time since the last send.
+    elapsed: Duration,
+}
+
+impl RepeatSchedule {
+    /// Starts repeating `payload`, whose first send was just queued, until it
+    /// was sent `count` times, one send every `interval`.
+    #[must_use]
+    pub const fn start(payload: SendPayload, count: u32, interval: Duration) -> Self {
+        Self {
+            payload,
+            sent: 1,
+            count,
+            interval,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Returns the data sent each time.
+    #[must_use]
+    pub const fn payload(&self) -> &SendPayload {
+        &self.payload
+    }
+
+    /// Returns the number of sends queued so far and the total number.
+    #[must_use]
+    pub const fn progress(&self) -> (u32, u32) {
+        (self.sent, self.count)
+    }
+
+    /// Returns true once all sends were queued.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.sent >= self.count
+    }
+
+    /// Advances the schedule by `delta` and returns the number of sends due.
+    ///
+    /// Sends missed during a long frame are all due at once.
+    pub fn advance(&mut self, delta: Duration) -> u32 {
+        self.elapsed += delta;
+        let mut due = 0;
+        while !self.is_finished() && self.elapsed >= self.interval {
+            self.elapsed -= self.interval;
+            self.sent += 1;
+            due += 1;
+        }
+        due
+    }
+}
+
+/// System: queues the repeats due on all ports, discarding the remaining
+/// repeats of ports that closed.
+pub fn run_send_repeats(time: Res<Time>, ports: Query<&SerialIo>) {
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        if serial.data().repeat_schedule().is_none() {
+            continue;
+        }
+        if serial.is_open() {
+            serial.data().advance_repeat(time.delta());
+        } else {
+            serial.data().stop_repeat();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::{Serial, Serials};
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn test_schedule_sends_at_interval_until_count() {
+        let payload = SendPayload::bytes(vec![0x55]);
+        let mut schedule = RepeatSchedule::start(payload, 4, Duration::from_millis(100));
+        assert_eq!(schedule.progress(), (1, 4));
+
+        assert_eq!(schedule.advance(Duration::from_millis(60)), 0);
+        assert_eq!(schedule.advance(Duration::from_millis(60)), 1);
+        // A long frame catches up, but never past the count.
+        assert_eq!(schedule.advance(Duration::from_secs(1)), 2);
+        assert!(schedule.is_finished());
+        assert_eq!(schedule.advance(Duration::from_secs(1)), 0);
+        assert_eq!(schedule.progress(), (4, 4));
+    }
+
+    #[test]
+    fn test_repeats_of_closed_ports_are_discarded() {
+        let options = SendOptions {
+            repeat_count: Some(1000),
+            repeat_interval: Some(Duration::from_millis(100)),
+        };
+        let mut serial = Serial::new();
+        serial
+            .data()
+            .send_data_with_options("AT".to_string(), options);
+        assert_eq!(serial.data().get_send_data().len(), 1);
+        assert!(serial.data().repeat_schedule().is_some());
+
+        let mut serials = Serials::new();
+        serials.add(serial);
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        crate::serial::status::spawn_ports(&mut world, serials);
+        world.run_system_once(run_send_repeats).unwrap();
+
+        let mut serials = world.query::<&Serials>();
+        let serials = serials.single(&world).unwrap();
+        let mut serial = serials.get(0).lock().unwrap();
+        assert!(serial.data().repeat_schedule().is_none());
+        assert!(serial.data().get_send_data().is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! # Replay Module
+//!
+//! Playback of session logs as received data.
+//!
+//! The received entries of a session log (see [`LogEntries`]) are sent again
+//! as [`PortChannelData::PortRead`] messages, waiting between them for the
+//! interval between their timestamps divided by a speed multiplier. The port
+//! processes them like data from its thread, so a recorded session can be
+//! played into the receive view, the parsers and the send sequences without
+//! the device.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use tokio::sync::{mpsc, watch};
+
+use super::log_entry::{LogEntries, LogEntry};
+use super::state::{DataSource, PortChannelData, PortRwData};
+use crate::error::{Result, SerialBevyError};
+
+/// Progress of a replay.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplayProgress {
+    /// Number of entries replayed.
+    pub played: usize,
+    /// Number of received entries in the log.
+    pub total: usize,
+    /// Timestamp of the entry replayed last.
+    pub timestamp: Option<DateTime<Local>>,
+}
+
+/// Replay of a session log into a port, as seen by the port.
+///
+/// Dropping it stops the replay.
+#[derive(Debug)]
+pub struct LogReplay {
+    /// Replayed messages not processed yet.
+    messages: mpsc::UnboundedReceiver<PortChannelData>,
+    /// Progress reported by the replay task.
+    progress: watch::Receiver<ReplayProgress>,
+}
+
+impl LogReplay {
+    /// Creates a replay of the log at `path` at `speed_multiplier`, and the
+    /// future that reads the log and sends its received entries, see
+    /// [`replay_interval`].
+    pub fn start(
+        path: &Path,
+        speed_multiplier: f32,
+    ) -> (Self, impl Future<Output = Result<()>> + Send + use<>) {
+        let (tx, messages) = mpsc::unbounded_channel();
+        let (progress_tx, progress) = watch::channel(ReplayProgress::default());
+        let task = replay_log(path.to_path_buf(), speed_multiplier, tx, progress_tx);
+        (Self { messages, progress }, task)
+    }
+
+    /// Takes the messages replayed so far.
+    pub fn take_messages(&mut self) -> Vec<PortChannelData> {
+        let mut messages = Vec::new();
+        while let Ok(message) = self.messages.try_recv() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    /// Returns the progress of the replay.
+    #[must_use]
+    pub fn progress(&self) -> ReplayProgress {
+        self.progress.borrow().clone()
+    }
+
+    /// Returns true once the replay task ended and its messages were taken.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.messages.is_closed() && self.messages.is_empty()
+    }
+}
+
+/// Returns the time to wait between two entries logged at `from` and `to`
+/// when replaying at `speed_multiplier`: the logged interval divided by it,
+/// or zero if it is not positive, which replays everything at once.
+#[must_use]
+pub fn replay_interval(
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    speed_multiplier: f32,
+) -> Duration {
+    if !speed_multiplier.is_finite() || speed_multiplier <= 0.0 {
+        return Duration::ZERO;
+    }
+    (to - from).to_std().map_or(Duration::ZERO, |interval| {
+        Duration::try_from_secs_f64(interval.as_secs_f64() / f64::from(speed_multiplier))
+            .unwrap_or(Duration::MAX)
+    })
+}
+
+/// Reads the log at `path` and sends its received entries to `messages`
+/// with their scaled intervals, reporting to `progress`.
+///
+/// Stops early, without an error, once the replay is dropped.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be read or has no timestamped received
+/// entries.
+async fn replay_log(
+    path: PathBuf,
+    speed_multiplier: f32,
+    messages: mpsc::UnboundedSender<PortChannelData>,
+    progress: watch::Sender<ReplayProgress>,
+) -> Result<()> {
+    let log = tokio::fs::read(&path).await?;
+    let entries: Vec<LogEntry> = LogEntries::new(log.as_slice())
+        .filter(|entry| entry.source == DataSource::Read)
+        .collect();
+    if entries.is_empty() {
+        return Err(SerialBevyError::serialization(format!(
+            "{}: no timestamped received data",
+            path.display()
+        )));
+    }
+    progress.send_modify(|progress| progress.total = entries.len());
+
+    let mut previous = None;
+    for entry in entries {
+        let interval = previous.map_or(Duration::ZERO, |previous| {
+            replay_interval(previous, entry.timestamp, speed_multiplier)
+        });
+        if !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        previous = Some(entry.timestamp);
+        let read = PortChannelData::PortRead(PortRwData { data: entry.data });
+        if messages.send(read).is_err() {
+            return Ok(());
+        }
+        progress.send_modify(|progress| {
+            progress.played += 1;
+            progress.timestamp = Some(entry.timestamp);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &[u8] = b"[20250102 03:04:05.000 R]first\n\
+        [20250102 03:04:05.100 T]AT\n\
+        [20250102 03:04:05.500 R]second\n\
+        [20250102 03:04:06.500 R]third\n";
+
+    fn read_data(message: PortChannelData) -> Vec<u8> {
+        match message {
+            PortChannelData::PortRead(read) => read.data,
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn test_replay_keeps_scaled_intervals() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.txt");
+        std::fs::write(&path, LOG).unwrap();
+
+        let (mut replay, task) = LogReplay::start(&path, 2.0);
+        let task = tokio::spawn(task);
+        let mut received = Vec::new();
+        let mut first = None;
+        while let Some(message) = replay.messages.recv().await {
+            let now = tokio::time::Instant::now();
+            let first = *first.get_or_insert(now);
+            received.push((now - first, read_data(message)));
+        }
+        task.await.unwrap().unwrap();
+
+        assert_eq!(
+            received,
+            [
+                (Duration::ZERO, b"first\n".to_vec()),
+                (Duration::from_millis(250), b"second\n".to_vec()),
+                (Duration::from_millis(750), b"third\n".to_vec()),
+            ]
+        );
+        let progress = replay.progress();
+        assert_eq!((progress.played, progress.total), (3, 3));
+        assert!(replay.is_finished());
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn test_replay_at_zero_speed_sends_everything_at_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.txt");
+        std::fs::write(&path, LOG).unwrap();
+
+        let (mut replay, task) = LogReplay::start(&path, 0.0);
+        let start = tokio::time::Instant::now();
+        task.await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(replay.take_messages().len(), 3);
+
+        std::fs::write(&path, b"no timestamps\n").unwrap();
+        let (_, task) = LogReplay::start(&path, 1.0);
+        assert!(task.await.is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! # Response Module
+//!
+//! Timing of how long a device takes to answer the data sent to it.
+//!
+//! Each send records its instant, and the next received record is matched to
+//! a send to compute the response time. With several sends before a reply,
+//! the reply is matched either to the most recent send or, for pipelined
+//! commands, to the oldest unanswered one. The last [`RESPONSE_WINDOW`]
+//! response times are kept for the minimum, average and maximum.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of response times the statistics cover.
+pub const RESPONSE_WINDOW: usize = 100;
+
+/// Unanswered sends kept for [`ResponseTiming::Fifo`]; older ones are
+/// dropped.
+pub const MAX_PENDING_SENDS: usize = 64;
+
+/// How received data is matched to sends to time responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResponseTiming {
+    /// Responses are not timed.
+    #[default]
+    Off,
+    /// Received data answers the most recent send; earlier unanswered sends
+    /// are dropped.
+    MostRecent,
+    /// Received data answers the oldest unanswered send, for commands sent
+    /// before the previous ones were answered.
+    Fifo,
+}
+
+/// Minimum, average and maximum of the recent response times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseStats {
+    /// Shortest response time.
+    pub min: Duration,
+    /// Average response time.
+    pub avg: Duration,
+    /// Longest response time.
+    pub max: Duration,
+    /// Number of response times covered, at most [`RESPONSE_WINDOW`].
+    pub count: usize,
+}
+
+/// Matches received data to sends and keeps the recent response times.
+#[derive(Debug, Default)]
+pub struct ResponseTimer {
+    /// How received data is matched to sends.
+    timing: ResponseTiming,
+    /// When the unanswered sends were made, oldest first.
+    pending: VecDeque<Instant>,
+    /// Recent response times, oldest first.
+    samples: VecDeque<Duration>,
+}
+
+impl ResponseTimer {
+    /// Gets how received data is matched to sends.
+    #[must_use]
+    pub const fn timing(&self) -> ResponseTiming {
+        self.timing
+    }
+
+    /// Sets how received data is matched to sends, forgetting the unanswered
+    /// sends.
+    pub fn set_timing(&mut self, timing: ResponseTiming) {
+        self.timing = timing;
+        self.pending.clear();
+    }
+
+    /// Records a send made at `at`.
+    pub fn sent(&mut self, at: Instant) {
+        match self.timing {
+            ResponseTiming::Off => return,
+            ResponseTiming::MostRecent => self.pending.clear(),
+            ResponseTiming::Fifo => {
+                if self.pending.len() >= MAX_PENDING_SENDS {
+                    self.pending.pop_front();
+                }
+            }
+        }
+        self.pending.push_back(at);
+    }
+
+    /// Records data received at `at` and returns its response time, or
+    /// `None` if no send is waiting for an answer.
+    pub fn received(&mut self, at: Instant) -> Option<Duration> {
+        let sent_at = match self.timing {
+            ResponseTiming::Off => return None,
+            ResponseTiming::MostRecent => self.pending.pop_back(),
+            ResponseTiming::Fifo => self.pending.pop_front(),
+        }?;
+        let elapsed = at.saturating_duration_since(sent_at);
+        if self.samples.len() >= RESPONSE_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(elapsed);
+        Some(elapsed)
+    }
+
+    /// Returns the statistics of the recent response times, or `None` if
+    /// there are none.
+    #[must_use]
+    pub fn stats(&self) -> Option<ResponseStats> {
+        let min = *self.samples.iter().min()?;
+        let max = *self.samples.iter().max()?;
+        let count = self.samples.len();
+        let total: Duration = self.samples.iter().sum();
+        Some(ResponseStats {
+            min,
+            avg: total / count as u32,
+            max,
+            count,
+        })
+    }
+
+    /// Forgets the unanswered sends and the response times.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.samples.clear();
+    }
+}
+
+/// Formats a response time as shown after a received entry, e.g.
+/// `(+12.3 ms)`.
+#[must_use]
+pub fn format_response_time(elapsed: Duration) -> String {
+    format!("(+{:.1} ms)", elapsed.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_recent_send_is_answered() {
+        let start = Instant::now();
+        let mut timer = ResponseTimer::default();
+        timer.sent(start);
+        assert_eq!(timer.received(start + Duration::from_millis(5)), None);
+
+        timer.set_timing(ResponseTiming::MostRecent);
+        timer.sent(start);
+        timer.sent(start + Duration::from_millis(10));
+        assert_eq!(
+            timer.received(start + Duration::from_millis(25)),
+            Some(Duration::from_millis(15))
+        );
+        assert_eq!(timer.received(start + Duration::from_millis(30)), None);
+    }
+
+    #[test]
+    fn test_fifo_matches_pipelined_sends_in_order() {
+        let start = Instant::now();
+        let mut timer = ResponseTimer::default();
+        timer.set_timing(ResponseTiming::Fifo);
+        timer.sent(start);
+        timer.sent(start + Duration::from_millis(10));
+        assert_eq!(
+            timer.received(start + Duration::from_millis(20)),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            timer.received(start + Duration::from_millis(40)),
+            Some(Duration::from_millis(30))
+        );
+        assert_eq!(timer.received(start + Duration::from_millis(50)), None);
+
+        let stats = timer.stats().unwrap();
+        assert_eq!(stats.min, Duration::from_millis(20));
+        assert_eq!(stats.avg, Duration::from_millis(25));
+        assert_eq!(stats.max, Duration::from_millis(30));
+        assert_eq!(stats.count, 2);
+
+        timer.reset();
+        assert_eq!(timer.stats(), None);
+    }
+
+    #[test]
+    fn test_stats_cover_recent_window() {
+        let start = Instant::now();
+        let mut timer = ResponseTimer::default();
+        timer.set_timing(ResponseTiming::MostRecent);
+        for ms in 1..=RESPONSE_WINDOW as u64 + 10 {
+            timer.sent(start);
+            timer.received(start + Duration::from_millis(ms));
+        }
+        let stats = timer.stats().unwrap();
+        assert_eq!(stats.count, RESPONSE_WINDOW);
+        assert_eq!(stats.min, Duration::from_millis(11));
+        assert_eq!(
+            format_response_time(Duration::from_micros(12_345)),
+            "(+12.3 ms)"
+        );
+    }
+}
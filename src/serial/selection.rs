@@ -1,27 +1,356 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
-/// Resource for tracking the currently selected serial port.
+use super::SerialHandle;
+use super::status::{PortEntities, SerialIo, SerialPortName, SerialState};
+
+/// Maximum number of receive panes shown in split view.
+pub const MAX_SPLIT_PANES: usize = 4;
+
+/// Resource for tracking the selected serial port and the split view panes.
+///
+/// Ports are referred to by their entity, see [`PortEntities`]. The selected
+/// port is the focused one: settings, the input area and the LLM panel apply
+/// to it. In split view, each pane shows one port and the focused port is
+/// always one of them.
 #[derive(Resource, Default)]
 pub struct Selected {
-    /// The name of the selected port.
-    selected: String,
+    /// The entity of the selected port.
+    selected: Option<Entity>,
+    /// Ports shown in split view, one per pane; empty when split view is off.
+    panes: Vec<Entity>,
 }
 
 impl Selected {
-    /// Returns true if the given port name is selected.
+    /// Returns true if the given port is selected.
     #[must_use]
-    pub fn is_selected(&self, port_name: &str) -> bool {
-        self.selected == port_name
+    pub fn is_selected(&self, port: Entity) -> bool {
+        self.selected == Some(port)
     }
 
     /// Selects the given port.
+    ///
+    /// In split view, the focused pane switches to the port unless it is
+    /// already shown in another pane.
+    pub fn select(&mut self, port: Entity) {
+        if self.is_split_view()
+            && !self.panes.contains(&port)
+            && let Some(pane) = self
+                .panes
+                .iter_mut()
+                .find(|pane| Some(**pane) == self.selected)
+        {
+            *pane = port;
+        }
+        self.selected = Some(port);
+    }
+
+    /// Returns the entity of the selected port, if any.
+    #[must_use]
+    pub fn selected(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    /// Returns true if split view is enabled.
+    #[must_use]
+    pub fn is_split_view(&self) -> bool {
+        !self.panes.is_empty()
+    }
+
+    /// Returns the ports shown in split view, in pane order.
+    #[must_use]
+    pub fn panes(&self) -> &[Entity] {
+        &self.panes
+    }
+
+    /// Enables split view, or changes its pane count, with up to `count`
+    /// panes filled from `open_ports`.
+    ///
+    /// Existing panes are kept, the first ones and the focused one if there
+    /// are fewer panes now; when split view is first enabled, the selected
+    /// port comes first if it is open. Nothing changes if fewer than two ports
+    /// are open.
+    pub fn enable_split_view(&mut self, open_ports: &[Entity], count: usize) {
+        let count = count.clamp(2, MAX_SPLIT_PANES).min(open_ports.len());
+        if count < 2 {
+            return;
+        }
+        if self.panes.is_empty()
+            && let Some(selected) = self.selected.filter(|port| open_ports.contains(port))
+        {
+            self.panes.push(selected);
+        }
+        // The focused port stays shown when panes are removed.
+        if let Some(focused) = self
+            .panes
+            .iter()
+            .position(|pane| Some(*pane) == self.selected)
+            && focused >= count
+        {
+            self.panes.swap(focused, count - 1);
+        }
+        self.panes.truncate(count);
+        for port in open_ports {
+            if self.panes.len() >= count {
+                break;
+            }
+            if !self.panes.contains(port) {
+                self.panes.push(*port);
+            }
+        }
+        if !self.selected.is_some_and(|port| self.panes.contains(&port)) {
+            self.selected = Some(self.panes[0]);
+        }
+    }
+
+    /// Disables split view, keeping the selected port.
+    pub fn disable_split_view(&mut self) {
+        self.panes.clear();
+    }
+
+    /// Shows `port` in pane `index` and focuses it.
+    ///
+    /// If the port is already shown in another pane, the two panes swap.
+    pub fn set_pane(&mut self, index: usize, port: Entity) {
+        if index >= self.panes.len() {
+            return;
+        }
+        if let Some(other) = self.panes.iter().position(|pane| *pane == port) {
+            self.panes.swap(index, other);
+        } else {
+            self.panes[index] = port;
+        }
+        self.selected = Some(port);
+    }
+
+    /// Drops panes whose port is no longer available.
+    ///
+    /// Split view is disabled once fewer than two panes remain.
+    pub fn retain_panes(&mut self, ports: &[Entity]) {
+        self.panes.retain(|pane| ports.contains(pane));
+        if self.panes.len() < 2 {
+            self.panes.clear();
+        }
+    }
+}
+
+/// System parameter bundling the [`Selected`] port with the port entities
+/// it refers to, for code drawing or changing the selected port.
+///
+/// Ports are named by their port name here, like in the
+/// [`PortSnapshots`](super::snapshot::PortSnapshots).
+#[derive(SystemParam)]
+pub struct PortSelection<'w, 's> {
+    /// The selected port and the split view panes.
+    pub selected: ResMut<'w, Selected>,
+    /// The port entities, by name and in list order.
+    pub entities: Res<'w, PortEntities>,
+    /// The ports of the entities.
+    ports: Query<
+        'w,
+        's,
+        (
+            &'static SerialPortName,
+            &'static SerialIo,
+            &'static SerialState,
+        ),
+    >,
+}
+
+impl PortSelection<'_, '_> {
+    /// Returns the port of the given entity.
+    #[must_use]
+    pub fn get(&self, port: Entity) -> Option<&SerialHandle> {
+        self.ports.get(port).ok().map(|(_, io, _)| &io.0)
+    }
+
+    /// Returns the selected port and its entity.
+    #[must_use]
+    pub fn port(&self) -> Option<(Entity, &SerialHandle)> {
+        let selected = self.selected.selected()?;
+        self.get(selected).map(|port| (selected, port))
+    }
+
+    /// Returns the name of the port of the given entity.
+    #[must_use]
+    pub fn name(&self, port: Entity) -> Option<&str> {
+        self.ports
+            .get(port)
+            .ok()
+            .map(|(name, _, _)| name.0.as_str())
+    }
+
+    /// Returns the name of the selected port.
+    #[must_use]
+    pub fn selected_name(&self) -> Option<&str> {
+        self.name(self.selected.selected()?)
+    }
+
+    /// Returns true if the port named `port_name` is selected.
+    #[must_use]
+    pub fn is_selected(&self, port_name: &str) -> bool {
+        self.entities
+            .get(port_name)
+            .is_some_and(|port| self.selected.is_selected(port))
+    }
+
+    /// Selects the port named `port_name`, if it has an entity.
     pub fn select(&mut self, port_name: &str) {
-        self.selected = port_name.to_string();
+        if let Some(port) = self.entities.get(port_name) {
+            self.selected.select(port);
+        }
     }
 
-    /// Returns the selected port name.
+    /// Returns the entities of the open ports, in list order.
     #[must_use]
-    pub fn selected(&self) -> &str {
-        &self.selected
+    pub fn open_ports(&self) -> Vec<Entity> {
+        self.entities
+            .ordered()
+            .iter()
+            .copied()
+            .filter(|port| {
+                self.ports
+                    .get(*port)
+                    .is_ok_and(|(_, _, state)| state.0.is_open())
+            })
+            .collect()
+    }
+}
+
+/// System: selects the first port when no port, or a removed one, is
+/// selected.
+pub fn select_first_port(entities: Res<PortEntities>, mut selected: ResMut<Selected>) {
+    let ordered = entities.ordered();
+    if selected
+        .selected()
+        .is_some_and(|port| ordered.contains(&port))
+    {
+        return;
+    }
+    if let Some(&first) = ordered.first() {
+        selected.select(first);
+    } else if selected.selected().is_some() {
+        selected.selected = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in port entities, `ports(&[1, 2])` for COM1 and COM2.
+    fn ports(indices: &[u32]) -> Vec<Entity> {
+        indices
+            .iter()
+            .map(|index| Entity::from_raw_u32(*index).unwrap())
+            .collect()
+    }
+
+    fn port(index: u32) -> Entity {
+        ports(&[index])[0]
+    }
+
+    #[test]
+    fn test_selected_default() {
+        let selected = Selected::default();
+        assert_eq!(selected.selected(), None);
+    }
+
+    #[test]
+    fn test_selected_operations() {
+        let mut selected = Selected::default();
+        selected.select(port(1));
+        assert!(selected.is_selected(port(1)));
+        assert!(!selected.is_selected(port(2)));
+        assert_eq!(selected.selected(), Some(port(1)));
+    }
+
+    #[test]
+    fn test_split_view_needs_two_open_ports() {
+        let mut selected = Selected::default();
+        selected.select(port(1));
+        selected.enable_split_view(&ports(&[1]), 2);
+        assert!(!selected.is_split_view());
+
+        selected.enable_split_view(&ports(&[2, 1, 3]), 2);
+        assert_eq!(selected.panes(), ports(&[1, 2]));
+        assert!(selected.is_selected(port(1)));
+    }
+
+    #[test]
+    fn test_split_view_pane_count_and_focus() {
+        let open = ports(&[1, 2, 3, 4, 5]);
+        let mut selected = Selected::default();
+        selected.select(port(3));
+        selected.enable_split_view(&open, 8);
+        assert_eq!(selected.panes().len(), MAX_SPLIT_PANES);
+        assert_eq!(selected.panes()[0], port(3));
+
+        selected.set_pane(1, port(5));
+        assert!(selected.is_selected(port(5)));
+        assert_eq!(selected.panes(), ports(&[3, 5, 2, 4]));
+
+        // Selecting a port that is not shown replaces the focused pane.
+        selected.select(port(1));
+        assert_eq!(selected.panes(), ports(&[3, 1, 2, 4]));
+
+        // Choosing a port shown in another pane swaps the panes.
+        selected.set_pane(0, port(2));
+        assert_eq!(selected.panes(), ports(&[2, 1, 3, 4]));
+        assert!(selected.is_selected(port(2)));
+
+        // Fewer panes keep the first ones, and the focused port.
+        selected.enable_split_view(&open, 2);
+        assert_eq!(selected.panes(), ports(&[2, 1]));
+        selected.enable_split_view(&open, 4);
+        selected.select(port(4));
+        selected.enable_split_view(&open, 2);
+        assert_eq!(selected.panes(), ports(&[2, 4]));
+        assert!(selected.is_selected(port(4)));
+    }
+
+    #[test]
+    fn test_split_view_drops_removed_ports() {
+        let mut selected = Selected::default();
+        selected.enable_split_view(&ports(&[1, 2, 3]), 3);
+        assert!(selected.is_selected(port(1)));
+
+        selected.retain_panes(&ports(&[1, 3]));
+        assert_eq!(selected.panes(), ports(&[1, 3]));
+        selected.retain_panes(&ports(&[3]));
+        assert!(!selected.is_split_view());
+
+        selected.enable_split_view(&ports(&[1, 2]), 2);
+        selected.disable_split_view();
+        assert!(!selected.is_split_view());
+        assert!(selected.is_selected(port(1)));
+    }
+
+    #[test]
+    fn test_first_port_is_selected_until_another_is() {
+        use crate::serial::Serials;
+        use crate::serial::status::{spawn_ports, sync_port_entities};
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string(), "COM2".to_string()], 1);
+        let mut world = World::new();
+        world.init_resource::<Selected>();
+        spawn_ports(&mut world, serials);
+        let com1 = world.resource::<PortEntities>().get("COM1").unwrap();
+        let com2 = world.resource::<PortEntities>().get("COM2").unwrap();
+
+        world.run_system_once(select_first_port).unwrap();
+        assert!(world.resource::<Selected>().is_selected(com1));
+        world.resource_mut::<Selected>().select(com2);
+        world.run_system_once(select_first_port).unwrap();
+        assert!(world.resource::<Selected>().is_selected(com2));
+
+        // Removing the selected port selects the first one again.
+        let mut query = world.query::<&mut Serials>();
+        query.single_mut(&mut world).unwrap().remove(1);
+        world.run_system_once(sync_port_entities).unwrap();
+        world.run_system_once(select_first_port).unwrap();
+        assert!(world.resource::<Selected>().is_selected(com1));
     }
 }
@@ -0,0 +1,447 @@
+//! # Sequence Module
+//!
+//! Send sequences: small bring-up scripts such as "send `AT`, wait 200 ms,
+//! send `AT+CSQ`, wait for `OK`".
+//!
+//! A [`SendSequence`] is edited per port and saved as TOML. Starting it
+//! creates a [`SequenceRun`], a state machine advanced every frame by
+//! [`run_send_sequences`] with the frame time. `WaitFor` steps match the data
+//! received since the last `Send`; a step that times out fails the run and
+//! is logged as an error line.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::data_types::DataType;
+use super::encoding::{encode_string, parse_escapes};
+use super::port_data::SendPayload;
+use super::snapshot::lock_serial;
+use super::state::DataSource;
+use super::status::SerialIo;
+use super::traffic::TrafficLog;
+use crate::error::{Result, SerialBevyError};
+
+/// Maximum number of received bytes kept for matching `WaitFor` patterns.
+const MAX_MATCH_BYTES: usize = 4096;
+
+/// One step of a send sequence.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SequenceStep {
+    /// Sends the text encoded as the data type.
+    ///
+    /// Escape sequences such as `\r\n` are parsed for UTF-8 and ASCII text.
+    Send(String, DataType),
+    /// Waits for the given number of milliseconds.
+    WaitMs(u64),
+    /// Waits until data received since the last `Send` contains `pattern`.
+    WaitFor {
+        /// Text to wait for.
+        pattern: String,
+        /// Time after which the run fails.
+        timeout_ms: u64,
+    },
+}
+
+impl SequenceStep {
+    /// Returns a short description of the step, e.g. `Send "AT"`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self {
+            Self::Send(text, data_type) => format!("Send {text:?} ({data_type})"),
+            Self::WaitMs(ms) => format!("Wait {ms} ms"),
+            Self::WaitFor {
+                pattern,
+                timeout_ms,
+            } => format!("Wait for {pattern:?} (timeout {timeout_ms} ms)"),
+        }
+    }
+}
+
+/// A named list of steps, saved and loaded as TOML.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendSequence {
+    /// Name shown in the sequence window.
+    #[serde(default)]
+    pub name: String,
+    /// Steps, in order.
+    #[serde(default)]
+    pub steps: Vec<SequenceStep>,
+}
+
+impl SendSequence {
+    /// Parses a sequence from TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a valid sequence.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| SerialBevyError::serialization(e.to_string()))
+    }
+
+    /// Formats the sequence as TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sequence cannot be serialized.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| SerialBevyError::serialization(e.to_string()))
+    }
+
+    /// Loads a sequence from a TOML file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Saves the sequence to a TOML file, creating its directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+}
+
+/// Outcome of a sequence run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SequenceStatus {
+    /// Steps are still being executed.
+    Running,
+    /// All steps completed.
+    Passed,
+    /// A step failed, with the reason.
+    Failed(String),
+    /// The user aborted the run.
+    Aborted,
+}
+
+/// Execution state of a [`SendSequence`].
+#[derive(Clone, Debug)]
+pub struct SequenceRun {
+    /// The sequence being run.
+    sequence: SendSequence,
+    /// Index of the current step.
+    current: usize,
+    /// Time spent in the current step.
+    step_elapsed: Duration,
+    /// Text received since the last `Send`, for `WaitFor` steps.
+    received: String,
+    /// Outcome of the run.
+    status: SequenceStatus,
+}
+
+impl SequenceRun {
+    /// Starts running `sequence`.
+    #[must_use]
+    pub fn start(sequence: SendSequence) -> Self {
+        Self {
+            sequence,
+            current: 0,
+            step_elapsed: Duration::ZERO,
+            received: String::new(),
+            status: SequenceStatus::Running,
+        }
+    }
+
+    /// Returns the outcome of the run.
+    #[must_use]
+    pub const fn status(&self) -> &SequenceStatus {
+        &self.status
+    }
+
+    /// Returns true while steps are being executed.
+    #[must_use]
+    pub const fn is_running(&self) -> bool {
+        matches!(self.status, SequenceStatus::Running)
+    }
+
+    /// Returns the number of completed steps and the total number of steps.
+    #[must_use]
+    pub const fn progress(&self) -> (usize, usize) {
+        (self.current, self.sequence.steps.len())
+    }
+
+    /// Returns the step being executed, if the run is still going.
+    #[must_use]
+    pub fn current_step(&self) -> Option<&SequenceStep> {
+        if self.is_running() {
+            self.sequence.steps.get(self.current)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the sequence being run.
+    #[must_use]
+    pub const fn sequence(&self) -> &SendSequence {
+        &self.sequence
+    }
+
+    /// Records received data for `WaitFor` steps.
+    pub fn feed(&mut self, data: &[u8]) {
+        if !self.is_running() {
+            return;
+        }
+        self.received.push_str(&String::from_utf8_lossy(data));
+        if self.received.len() > MAX_MATCH_BYTES {
+            let mut start = self.received.len() - MAX_MATCH_BYTES;
+            while !self.received.is_char_boundary(start) {
+                start += 1;
+            }
+            self.received.drain(..start);
+        }
+    }
+
+    /// Aborts the run.
+    pub fn abort(&mut self) {
+        if self.is_running() {
+            self.status = SequenceStatus::Aborted;
+        }
+    }
+
+    /// Marks the run as failed.
+    pub fn fail(&mut self, reason: impl Into<String>) {
+        if self.is_running() {
+            self.status = SequenceStatus::Failed(reason.into());
+        }
+    }
+
+    /// Advances the run by `delta` and returns the data to send, in order.
+    ///
+    /// Steps are executed until one has to wait, so several sends can be
+    /// returned at once.
+    pub fn advance(&mut self, delta: Duration) -> Vec<(String, DataType)> {
+        let mut sends = Vec::new();
+        if !self.is_running() {
+            return sends;
+        }
+        self.step_elapsed += delta;
+
+        while let Some(step) = self.sequence.steps.get(self.current) {
+            match step {
+                SequenceStep::Send(text, data_type) => {
+                    sends.push((text.clone(), *data_type));
+                    self.received.clear();
+                }
+                SequenceStep::WaitMs(ms) => {
+                    if self.step_elapsed < Duration::from_millis(*ms) {
+                        return sends;
+                    }
+                }
+                SequenceStep::WaitFor {
+                    pattern,
+                    timeout_ms,
+                } => {
+                    if !self.received.contains(pattern.as_str()) {
+                        if self.step_elapsed >= Duration::from_millis(*timeout_ms) {
+                            self.status = SequenceStatus::Failed(format!(
+                                "Sequence step {}: {pattern:?} not received within {timeout_ms} ms",
+                                self.current + 1
+                            ));
+                        }
+                        return sends;
+                    }
+                }
+            }
+            self.current += 1;
+            self.step_elapsed = Duration::ZERO;
+        }
+        self.status = SequenceStatus::Passed;
+        sends
+    }
+}
+
+/// Encodes the text of a `Send` step.
+#[must_use]
+pub fn encode_step_data(text: &str, data_type: DataType) -> Vec<u8> {
+    match data_type {
+        DataType::Utf8 | DataType::Ascii => parse_escapes(text),
+        _ => encode_string(text, data_type),
+    }
+}
+
+/// System: advances the running sequences of all ports.
+///
+/// Queues the data of `Send` steps and logs failures as error lines. A run
+/// fails if its port closes.
+pub fn run_send_sequences(
+    time: Res<Time>,
+    ports: Query<&SerialIo>,
+    mut traffic: ResMut<TrafficLog>,
+) {
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        let is_open = serial.is_open();
+        let Some(run) = serial.data().sequence_run_mut() else {
+            continue;
+        };
+        if !run.is_running() {
+            continue;
+        }
+
+        if !is_open {
+            run.fail("Sequence failed: port closed");
+        }
+        let sends = run.advance(time.delta());
+        let failure = match run.status() {
+            SequenceStatus::Failed(reason) => Some(reason.clone()),
+            _ => None,
+        };
+
+        for (text, data_type) in sends {
+            serial.data().queue_send(SendPayload {
+                raw: encode_step_data(&text, data_type),
+                display: text,
+                source_type: data_type,
+            });
+        }
+        if let Some(reason) = failure {
+            warn!("{}: {reason}", serial.set.port_name);
+            serial
+                .data()
+                .write_source_file(reason.as_bytes(), DataSource::Error);
+            traffic.record(&serial.set.port_name, DataSource::Error, reason.as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bring_up() -> SendSequence {
+        SendSequence {
+            name: "Bring-up".to_string(),
+            steps: vec![
+                SequenceStep::Send("AT\\r\\n".to_string(), DataType::Utf8),
+                SequenceStep::WaitMs(200),
+                SequenceStep::Send("AT+CSQ\\r\\n".to_string(), DataType::Utf8),
+                SequenceStep::WaitFor {
+                    pattern: "OK".to_string(),
+                    timeout_ms: 1000,
+                },
+                SequenceStep::Send("AT+CGATT=1\\r\\n".to_string(), DataType::Utf8),
+            ],
+        }
+    }
+
+    fn texts(sends: Vec<(String, DataType)>) -> Vec<String> {
+        sends.into_iter().map(|(text, _)| text).collect()
+    }
+
+    #[test]
+    fn test_run_waits_and_matches_received_data() {
+        let mut run = SequenceRun::start(bring_up());
+        assert_eq!(texts(run.advance(Duration::ZERO)), ["AT\\r\\n"]);
+        assert_eq!(run.progress(), (1, 5));
+
+        assert!(run.advance(Duration::from_millis(150)).is_empty());
+        assert_eq!(
+            texts(run.advance(Duration::from_millis(50))),
+            ["AT+CSQ\\r\\n"]
+        );
+        assert_eq!(
+            run.current_step().map(SequenceStep::label).as_deref(),
+            Some("Wait for \"OK\" (timeout 1000 ms)")
+        );
+
+        run.feed(b"+CSQ: 20,99\r\n");
+        assert!(run.advance(Duration::from_millis(100)).is_empty());
+        run.feed(b"O");
+        run.feed(b"K\r\n");
+        assert_eq!(
+            texts(run.advance(Duration::from_millis(10))),
+            ["AT+CGATT=1\\r\\n"]
+        );
+        assert_eq!(run.status(), &SequenceStatus::Passed);
+        assert_eq!(run.progress(), (5, 5));
+    }
+
+    #[test]
+    fn test_data_before_send_does_not_match() {
+        let mut run = SequenceRun::start(SendSequence {
+            name: String::new(),
+            steps: vec![
+                SequenceStep::Send("AT".to_string(), DataType::Utf8),
+                SequenceStep::WaitFor {
+                    pattern: "OK".to_string(),
+                    timeout_ms: 100,
+                },
+            ],
+        });
+        run.feed(b"OK\r\n");
+        run.advance(Duration::ZERO);
+        assert!(run.is_running());
+    }
+
+    #[test]
+    fn test_wait_for_timeout_fails_run() {
+        let mut run = SequenceRun::start(bring_up());
+        run.advance(Duration::ZERO);
+        run.advance(Duration::from_millis(200));
+        run.feed(b"ERROR\r\n");
+        run.advance(Duration::from_millis(999));
+        assert!(run.is_running());
+
+        assert!(run.advance(Duration::from_millis(1)).is_empty());
+        assert_eq!(
+            run.status(),
+            &SequenceStatus::Failed(
+                "Sequence step 4: \"OK\" not received within 1000 ms".to_string()
+            )
+        );
+        assert!(run.current_step().is_none());
+    }
+
+    #[test]
+    fn test_abort_stops_run() {
+        let mut run = SequenceRun::start(bring_up());
+        run.advance(Duration::ZERO);
+        run.abort();
+        assert_eq!(run.status(), &SequenceStatus::Aborted);
+        assert!(run.advance(Duration::from_secs(1)).is_empty());
+        run.fail("late failure");
+        assert_eq!(run.status(), &SequenceStatus::Aborted);
+    }
+
+    #[test]
+    fn test_sequence_toml_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sequences").join("bring_up.toml");
+        let sequence = bring_up();
+        sequence.save(&path).unwrap();
+        assert_eq!(SendSequence::load(&path).unwrap(), sequence);
+
+        let parsed = SendSequence::from_toml(
+            "name = \"Ping\"\n\n[[steps]]\nSend = [\"0A0B\", \"Hex\"]\n\n[[steps]]\nWaitMs = 50\n",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.steps,
+            [
+                SequenceStep::Send("0A0B".to_string(), DataType::Hex),
+                SequenceStep::WaitMs(50),
+            ]
+        );
+        assert!(SendSequence::from_toml("steps = 3").is_err());
+    }
+
+    #[test]
+    fn test_encode_step_data() {
+        assert_eq!(encode_step_data("AT\\r\\n", DataType::Utf8), b"AT\r\n");
+        assert_eq!(encode_step_data("0A0B", DataType::Hex), [0x0A_u8, 0x0B]);
+    }
+}
@@ -0,0 +1,311 @@
+//! # Session Module
+//!
+//! Session recordings: the raw bytes received and sent by a port with their
+//! timing, for demos and for regression-testing parsers against real traffic.
+//!
+//! A recording is a JSON Lines file with one frame per line:
+//!
+//! ```text
+//! {"t_offset_ms":0,"direction":"tx","bytes_hex":"41540d"}
+//! {"t_offset_ms":12,"direction":"rx","bytes_hex":"4f4b0d0a"}
+//! ```
+//!
+//! `t_offset_ms` counts from the start of the recording. A recording is
+//! replayed by a virtual port (see [`super::mock`]) that emits its received
+//! frames with the recorded timing, so it works without the device.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::log_writer::LogWriter;
+use super::mock::{MOCK_PORT_PREFIX, MockScriptStep, MockTraffic};
+use crate::error::{Result, SerialBevyError};
+
+/// Extension of session recordings.
+pub const SESSION_EXTENSION: &str = "jsonl";
+
+/// Direction of a recorded frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionDirection {
+    /// Received from the device.
+    Rx,
+    /// Sent to the device.
+    Tx,
+}
+
+/// One recorded frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionFrame {
+    /// Time since the start of the recording.
+    pub offset: Duration,
+    /// Whether the frame was received or sent.
+    pub direction: SessionDirection,
+    /// Raw bytes of the frame.
+    pub data: Vec<u8>,
+}
+
+/// One line of a recording.
+#[derive(Serialize, Deserialize)]
+struct SessionLine {
+    t_offset_ms: u64,
+    direction: SessionDirection,
+    bytes_hex: String,
+}
+
+/// Returns the recording of a session whose log file has the base path
+/// `log_base` (the log path without `.txt`), or of a session started at
+/// `time` in `dir` if it has no log file.
+#[must_use]
+pub fn session_recording_path(
+    dir: &Path,
+    log_base: Option<&str>,
+    time: DateTime<Local>,
+) -> PathBuf {
+    match log_base {
+        Some(base) => PathBuf::from(format!("{base}.{SESSION_EXTENSION}")),
+        None => dir.join(format!(
+            "session_{}.{SESSION_EXTENSION}",
+            time.format("%Y%m%d_%H%M%S")
+        )),
+    }
+}
+
+/// Writes the frames of a port to a recording.
+///
+/// The file is written by a [`LogWriter`] thread, so recording never waits
+/// for the disk on the frame loop; the thread logs any failure.
+pub struct SessionRecorder {
+    /// Path of the recording.
+    path: PathBuf,
+    /// Writer thread of the recording file.
+    writer: LogWriter,
+    /// When the recording started.
+    started: Instant,
+}
+
+impl SessionRecorder {
+    /// Starts the recording at `path`, replacing any file there. The writer
+    /// thread creates the file and its directory.
+    #[must_use]
+    pub fn create(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            writer: LogWriter::create(path.to_path_buf()),
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns the path of the recording.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records a frame received or sent now, handing it to the writer thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame cannot be serialized.
+    pub fn record(&mut self, direction: SessionDirection, data: &[u8]) -> Result<()> {
+        self.record_at(self.started.elapsed(), direction, data)
+    }
+
+    /// Records a frame received or sent `offset` after the start.
+    fn record_at(
+        &mut self,
+        offset: Duration,
+        direction: SessionDirection,
+        data: &[u8],
+    ) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let line = SessionLine {
+            t_offset_ms: u64::try_from(offset.as_millis()).unwrap_or(u64::MAX),
+            direction,
+            bytes_hex: data.iter().map(|b| format!("{b:02x}")).collect(),
+        };
+        let json = serde_json::to_string(&line)
+            .map_err(|e| SerialBevyError::serialization(e.to_string()))?;
+        self.writer.write(json.as_bytes());
+        self.writer.write(b"\n");
+        self.writer.flush();
+        Ok(())
+    }
+
+    /// Blocks until the writer thread has written the recorded frames.
+    pub fn sync(&mut self) {
+        self.writer.sync();
+    }
+}
+
+/// Parses a recording.
+///
+/// # Errors
+///
+/// Returns an error naming the line if a line is not a valid frame.
+pub fn parse_session(text: &str) -> Result<Vec<SessionFrame>> {
+    let mut frames = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |reason: String| {
+            SerialBevyError::serialization(format!("session line {}: {reason}", line_no + 1))
+        };
+        let line: SessionLine = serde_json::from_str(line).map_err(|e| invalid(e.to_string()))?;
+        let data = parse_hex(&line.bytes_hex)
+            .ok_or_else(|| invalid(format!("invalid bytes_hex {:?}", line.bytes_hex)))?;
+        frames.push(SessionFrame {
+            offset: Duration::from_millis(line.t_offset_ms),
+            direction: line.direction,
+            data,
+        });
+    }
+    Ok(frames)
+}
+
+/// Loads a recording.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed.
+pub fn load_session(path: &Path) -> Result<Vec<SessionFrame>> {
+    parse_session(&fs::read_to_string(path)?)
+}
+
+/// Parses contiguous hex digit pairs.
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect()
+}
+
+/// Returns the traffic of a virtual port replaying the received frames of a
+/// recording, with the recorded intervals divided by `speed_multiplier`, or
+/// all at once if it is not positive. Sent frames are skipped.
+#[must_use]
+pub fn replay_traffic(frames: &[SessionFrame], speed_multiplier: f32) -> MockTraffic {
+    let mut previous = Duration::ZERO;
+    let steps = frames
+        .iter()
+        .filter(|frame| frame.direction == SessionDirection::Rx)
+        .map(|frame| {
+            let gap = frame.offset.saturating_sub(previous);
+            previous = frame.offset;
+            let delay = if speed_multiplier.is_finite() && speed_multiplier > 0.0 {
+                Duration::try_from_secs_f64(gap.as_secs_f64() / f64::from(speed_multiplier))
+                    .unwrap_or(Duration::MAX)
+            } else {
+                Duration::ZERO
+            };
+            MockScriptStep {
+                delay,
+                data: frame.data.clone(),
+            }
+        })
+        .collect();
+    MockTraffic::Script(steps)
+}
+
+/// Returns the name of the virtual port replaying the recording at `path`.
+#[must_use]
+pub fn replay_port_name(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    format!("{MOCK_PORT_PREFIX}replay/{stem}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("session.jsonl");
+        let mut recorder = SessionRecorder::create(&path);
+        recorder
+            .record_at(Duration::ZERO, SessionDirection::Tx, b"AT\r")
+            .unwrap();
+        recorder
+            .record_at(Duration::from_millis(12), SessionDirection::Rx, b"OK\r\n")
+            .unwrap();
+        recorder
+            .record_at(Duration::from_millis(20), SessionDirection::Rx, b"")
+            .unwrap();
+        recorder.sync();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            text.lines().next(),
+            Some(r#"{"t_offset_ms":0,"direction":"tx","bytes_hex":"41540d"}"#)
+        );
+        let frames = load_session(&path).unwrap();
+        assert_eq!(
+            frames,
+            [
+                SessionFrame {
+                    offset: Duration::ZERO,
+                    direction: SessionDirection::Tx,
+                    data: b"AT\r".to_vec(),
+                },
+                SessionFrame {
+                    offset: Duration::from_millis(12),
+                    direction: SessionDirection::Rx,
+                    data: b"OK\r\n".to_vec(),
+                },
+            ]
+        );
+
+        let error = parse_session("\n{\"t_offset_ms\":0,\"direction\":\"rx\",\"bytes_hex\":\"4\"}")
+            .unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_replay_traffic_scales_received_frames() {
+        let frames = parse_session(
+            "{\"t_offset_ms\":100,\"direction\":\"rx\",\"bytes_hex\":\"01\"}\n\
+             {\"t_offset_ms\":150,\"direction\":\"tx\",\"bytes_hex\":\"02\"}\n\
+             {\"t_offset_ms\":300,\"direction\":\"rx\",\"bytes_hex\":\"03\"}\n",
+        )
+        .unwrap();
+        let MockTraffic::Script(steps) = replay_traffic(&frames, 2.0) else {
+            panic!("replay should be scripted");
+        };
+        assert_eq!(
+            steps,
+            [
+                MockScriptStep {
+                    delay: Duration::from_millis(50),
+                    data: vec![0x01],
+                },
+                MockScriptStep {
+                    delay: Duration::from_millis(100),
+                    data: vec![0x03],
+                },
+            ]
+        );
+        let MockTraffic::Script(steps) = replay_traffic(&frames, 0.0) else {
+            panic!("replay should be scripted");
+        };
+        assert!(steps.iter().all(|step| step.delay.is_zero()));
+
+        assert_eq!(
+            replay_port_name(Path::new("logs/COM1_20250102.jsonl")),
+            "mock://replay/COM1_20250102"
+        );
+    }
+}
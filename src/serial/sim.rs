@@ -0,0 +1,144 @@
+//! # Simulation Module
+//!
+//! Scripted request/response devices for offline tests.
+//!
+//! A [`SimulatedSerial`] behaves like a device on the other end of the cable:
+//! every chunk written by the application is answered after a simulated
+//! latency. It runs on the same mock backend as the demo port, so it goes
+//! through the regular open, read and write paths of a [`Serial`](super::Serial).
+
+use std::collections::VecDeque;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::Duration;
+
+use super::mock::{DeviceReader, DeviceWriter};
+
+/// Response function of a simulated device.
+///
+/// Receives the bytes written by the application and returns the reply, or
+/// `None` to fall back to the next pre-canned response.
+pub type SimulatedResponder = fn(Vec<u8>) -> Option<Vec<u8>>;
+
+/// A simulated device answering writes with scripted responses.
+#[derive(Clone, Debug)]
+pub struct SimulatedSerial {
+    /// Name of the simulated port.
+    pub port_name: String,
+    /// Pre-canned responses, used in order when the responder returns `None`.
+    pub responses: Vec<Vec<u8>>,
+    /// Computes the reply to a write.
+    pub respond: SimulatedResponder,
+    /// Delay between a write and its reply.
+    pub latency: Duration,
+}
+
+impl PartialEq for SimulatedSerial {
+    fn eq(&self, other: &Self) -> bool {
+        self.port_name == other.port_name
+            && self.responses == other.responses
+            && self.latency == other.latency
+            && std::ptr::fn_addr_eq(self.respond, other.respond)
+    }
+}
+
+impl Eq for SimulatedSerial {}
+
+impl SimulatedSerial {
+    /// Creates a simulated device with no latency.
+    #[must_use]
+    pub fn new(
+        port_name: impl Into<String>,
+        responses: Vec<Vec<u8>>,
+        respond: SimulatedResponder,
+    ) -> Self {
+        Self {
+            port_name: port_name.into(),
+            responses,
+            respond,
+            latency: Duration::ZERO,
+        }
+    }
+
+    /// Creates a simulated device that replays `responses` in order, one per write.
+    #[must_use]
+    pub fn canned(port_name: impl Into<String>, responses: Vec<Vec<u8>>) -> Self {
+        Self::new(port_name, responses, |_| None)
+    }
+
+    /// Sets the simulated latency between a write and its reply.
+    #[must_use]
+    pub const fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+}
+
+/// Answers every application write according to `sim`.
+pub(super) async fn run_simulated(
+    mut reader: DeviceReader,
+    mut writer: DeviceWriter,
+    sim: SimulatedSerial,
+) {
+    let mut canned: VecDeque<Vec<u8>> = sim.responses.into();
+    let mut buffer = [0u8; 1024];
+    loop {
+        let n = match reader.read(&mut buffer).await {
+            Ok(n) if n > 0 => n,
+            _ => break,
+        };
+
+        let Some(reply) = (sim.respond)(buffer[..n].to_vec()).or_else(|| canned.pop_front()) else {
+            continue;
+        };
+
+        if !sim.latency.is_zero() {
+            tokio::time::sleep(sim.latency).await;
+        }
+        if writer.write_all(&reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::mock::{MockTraffic, spawn_mock_device};
+
+    fn uppercase(data: Vec<u8>) -> Option<Vec<u8>> {
+        (data != b"skip").then(|| data.to_ascii_uppercase())
+    }
+
+    #[tokio::test]
+    async fn test_simulated_responder() {
+        let sim = SimulatedSerial::new("sim://test", vec![], uppercase);
+        let mut backend = spawn_mock_device(MockTraffic::Simulated(sim));
+        backend.write_all(b"ping").await.unwrap();
+        let mut buffer = [0u8; 4];
+        backend.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"PING");
+    }
+
+    #[tokio::test]
+    async fn test_simulated_canned_fallback() {
+        let sim = SimulatedSerial::new("sim://test", vec![b"OK".to_vec()], uppercase);
+        let mut backend = spawn_mock_device(MockTraffic::Simulated(sim));
+        backend.write_all(b"skip").await.unwrap();
+        let mut buffer = [0u8; 2];
+        backend.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"OK");
+    }
+
+    #[tokio::test]
+    async fn test_simulated_latency() {
+        let sim = SimulatedSerial::canned("sim://test", vec![b"OK".to_vec()])
+            .with_latency(Duration::from_millis(50));
+        let mut backend = spawn_mock_device(MockTraffic::Simulated(sim));
+        let start = tokio::time::Instant::now();
+        backend.write_all(b"AT").await.unwrap();
+        let mut buffer = [0u8; 2];
+        backend.read_exact(&mut buffer).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
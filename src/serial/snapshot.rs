@@ -0,0 +1,248 @@
+//! # Snapshot Module
+//!
+//! Read-only copies of the port state, rebuilt once per frame.
+//!
+//! Display code reads [`PortSnapshots`] instead of locking every port, so a
+//! port held by a long operation cannot stall rendering. Only interactions
+//! that change a port lock it, with [`lock_serial`].
+
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+use bevy::prelude::*;
+use chrono::{DateTime, Local};
+
+use super::SerialHandle;
+use super::data_types::DataType;
+use super::port::{PortSettings, Serial};
+use super::response::ResponseStats;
+use super::state::PortState;
+use super::status::{PortEntities, SerialIo};
+
+/// Locks a port, recovering the lock if a previous holder panicked.
+///
+/// The port state is plain data that stays consistent between statements,
+/// so it is safe to keep using after a panic elsewhere.
+pub fn lock_serial(serial: &Mutex<Serial>) -> MutexGuard<'_, Serial> {
+    serial.lock().unwrap_or_else(|poisoned| {
+        serial.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// Display state of one port, copied from its [`Serial`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortSnapshot {
+    /// Port name.
+    pub name: String,
+    /// Key of the port's alias and color, see [`Serial::label_key`].
+    pub label_key: String,
+    /// Connection state.
+    pub state: PortState,
+    /// Configured baud rate.
+    pub baud_rate: u32,
+    /// Port settings.
+    pub settings: PortSettings,
+    /// Data type of the receive view and input.
+    pub data_type: DataType,
+    /// Whether the port is open.
+    pub is_open: bool,
+    /// Latest error reported by the port thread.
+    pub last_error: Option<String>,
+    /// When the latest error was reported.
+    pub last_error_at: Option<DateTime<Local>>,
+    /// Most recent error log lines, while the port is in error state.
+    pub recent_errors: Vec<String>,
+    /// Settings changed from the ones the port was discovered with, see
+    /// [`PortSettings::diff`].
+    pub settings_diff: Vec<String>,
+    /// Whether the port's receive timeout elapsed since data was last
+    /// received.
+    pub receive_timed_out: bool,
+    /// Whether the last discovery scans did not find the port.
+    pub missing: bool,
+    /// When data was last received, if ever.
+    pub last_rx: Option<Instant>,
+    /// When data was last sent, if ever.
+    pub last_tx: Option<Instant>,
+    /// Short form of the port settings, e.g. `115200 8N1`.
+    pub summary: String,
+    /// Bytes received since the port was last opened.
+    pub rx_bytes: u64,
+    /// Bytes sent since the port was last opened.
+    pub tx_bytes: u64,
+    /// Errors reported since the port was last opened.
+    pub error_count: u32,
+    /// Path of the log file currently written, if any.
+    pub log_path: Option<String>,
+    /// Statistics of the recent response times, if any were timed.
+    pub response_stats: Option<ResponseStats>,
+}
+
+impl PortSnapshot {
+    /// Copies the display state of a port.
+    #[must_use]
+    pub fn capture(serial: &mut Serial) -> Self {
+        Self {
+            name: serial.set.port_name.clone(),
+            label_key: serial.label_key(),
+            state: *serial.data().state_ref(),
+            baud_rate: serial.set.baud_rate,
+            settings: serial.set.clone(),
+            data_type: *serial.data().data_type(),
+            is_open: serial.is_open(),
+            last_error: serial.data().last_error().map(str::to_string),
+            last_error_at: serial.data().last_error_at(),
+            recent_errors: if serial.is_error() {
+                serial.data().recent_error_lines().to_vec()
+            } else {
+                Vec::new()
+            },
+            settings_diff: PortSettings::discovered(&serial.set.port_name).diff(&serial.set),
+            receive_timed_out: serial.data().is_receive_timed_out(),
+            missing: serial.missing_scans() > 0,
+            last_rx: serial.data().last_rx_instant(),
+            last_tx: serial.data().last_tx_instant(),
+            summary: serial.set.summary(),
+            rx_bytes: serial.data().rx_bytes(),
+            tx_bytes: serial.data().tx_bytes(),
+            error_count: serial.data().error_count(),
+            log_path: serial
+                .data()
+                .session_log()
+                .current_path()
+                .map(str::to_string),
+            response_stats: serial.data().response_stats(),
+        }
+    }
+
+    /// Returns true if the port is in error state.
+    #[must_use]
+    pub const fn is_error(&self) -> bool {
+        self.state.is_error()
+    }
+}
+
+/// Snapshots of all ports, in list order.
+#[derive(Resource, Default, Debug)]
+pub struct PortSnapshots(pub Vec<PortSnapshot>);
+
+impl PortSnapshots {
+    /// Rebuilds the snapshots from `ports`, locking each port only while
+    /// copying it.
+    pub fn refresh<'a>(&mut self, ports: impl IntoIterator<Item = &'a SerialHandle>) {
+        self.0.clear();
+        self.0.extend(
+            ports
+                .into_iter()
+                .map(|serial| PortSnapshot::capture(&mut lock_serial(serial))),
+        );
+    }
+
+    /// Returns the snapshot of the named port.
+    #[must_use]
+    pub fn get(&self, port_name: &str) -> Option<&PortSnapshot> {
+        self.0.iter().find(|port| port.name == port_name)
+    }
+
+    /// Returns the snapshots of all ports.
+    pub fn iter(&self) -> impl Iterator<Item = &PortSnapshot> {
+        self.0.iter()
+    }
+
+    /// Returns the snapshots of the open ports.
+    pub fn open(&self) -> impl Iterator<Item = &PortSnapshot> {
+        self.0.iter().filter(|port| port.is_open)
+    }
+}
+
+/// System: rebuilds the [`PortSnapshots`] resource from the port entities,
+/// in list order.
+pub fn update_port_snapshots(
+    entities: Res<PortEntities>,
+    ports: Query<&SerialIo>,
+    mut snapshots: ResMut<PortSnapshots>,
+) {
+    snapshots.refresh(ports.iter_many(entities.ordered()).map(|port| &port.0));
+}
+
+/// Poisons the lock of `serial` by panicking on another thread while it is
+/// held.
+#[cfg(test)]
+pub(crate) fn poison(serial: &Mutex<Serial>) {
+    std::thread::scope(|scope| {
+        let _ = scope
+            .spawn(|| {
+                let _guard = serial.lock().unwrap();
+                panic!("poisoning the port lock");
+            })
+            .join();
+    });
+    assert!(serial.is_poisoned());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::Serials;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn test_lock_serial_recovers_poisoned_lock() {
+        let serial = Mutex::new(Serial::new());
+        poison(&serial);
+
+        lock_serial(&serial).set.port_name = "COM7".to_string();
+        assert!(!serial.is_poisoned());
+        assert_eq!(serial.lock().unwrap().set.port_name, "COM7");
+    }
+
+    #[test]
+    fn test_snapshots_capture_port_state() {
+        let mut serials = Serials::new();
+        let mut serial = Serial::new();
+        serial.set.port_name = "COM1".to_string();
+        serial.set.baud_rate = 9600;
+        serial.open();
+        serial
+            .data()
+            .set_last_error(Some("Device disconnected".to_string()));
+        serials.add(serial);
+        serials.add_mock("mock://demo", crate::serial::mock::MockTraffic::Echo);
+
+        let mut snapshots = PortSnapshots::default();
+        snapshots.refresh(&serials.serial);
+        assert_eq!(snapshots.iter().count(), 2);
+
+        let com1 = snapshots.get("COM1").unwrap();
+        assert!(com1.is_open);
+        assert_eq!(com1.baud_rate, 9600);
+        assert_eq!(com1.data_type, DataType::Utf8);
+        assert_eq!(com1.last_error.as_deref(), Some("Device disconnected"));
+        assert_eq!(
+            snapshots
+                .open()
+                .map(|port| port.name.as_str())
+                .collect::<Vec<_>>(),
+            ["COM1"]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_system_survives_poisoned_port() {
+        let mut serials = Serials::new();
+        serials.add_mock("mock://a", crate::serial::mock::MockTraffic::Echo);
+        serials.add_mock("mock://b", crate::serial::mock::MockTraffic::Echo);
+        let first = std::sync::Arc::clone(&serials.serial[0]);
+
+        let mut world = World::new();
+        crate::serial::status::spawn_ports(&mut world, serials);
+        poison(&first);
+        world.init_resource::<PortSnapshots>();
+        world.run_system_once(update_port_snapshots).unwrap();
+
+        let snapshots = world.resource::<PortSnapshots>();
+        let names: Vec<&str> = snapshots.iter().map(|port| port.name.as_str()).collect();
+        assert_eq!(names, ["mock://a", "mock://b"]);
+    }
+}
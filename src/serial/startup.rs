@@ -0,0 +1,236 @@
+//! # Startup Module
+//!
+//! Port opened when the app starts, so a kiosk or a script can launch it
+//! straight into a session.
+//!
+//! The port comes from the `--port <name>` and `--baud <rate>` command-line
+//! arguments, or else from the [`STARTUP_PORT_ENV`] and [`STARTUP_BAUD_ENV`]
+//! environment variables. [`open_startup_port`] opens it as soon as discovery
+//! finds it, and gives up with a warning after [`STARTUP_PORT_TIMEOUT`].
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::Selected;
+use super::port::parse_baud_rate;
+use super::snapshot::lock_serial;
+use super::status::{SerialIo, SerialPortName};
+
+/// Environment variable naming the port opened at startup.
+pub const STARTUP_PORT_ENV: &str = "SERIAL_BEVY_PORT";
+
+/// Environment variable with the baud rate of the port opened at startup.
+pub const STARTUP_BAUD_ENV: &str = "SERIAL_BEVY_BAUD";
+
+/// Time discovery gets to find the startup port.
+pub const STARTUP_PORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Port opened when the app starts, if any.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StartupArgs {
+    /// Name of the port to open.
+    pub port: Option<String>,
+    /// Baud rate to open it with, or `None` to keep its current one.
+    pub baud_rate: Option<u32>,
+}
+
+impl StartupArgs {
+    /// Builds the startup port from command-line arguments, falling back to
+    /// the environment variables for what they do not set.
+    pub fn from_args_or_env<I: IntoIterator<Item = String>>(args: I) -> Self {
+        Self::from_args(args).or(Self::from_vars(|name| std::env::var(name).ok()))
+    }
+
+    /// Builds the startup port from command-line arguments.
+    ///
+    /// - `--port <name>` opens the named port; an empty name opens none, and
+    ///   makes the app forget a remembered one.
+    /// - `--baud <rate>` sets its baud rate.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Self {
+        let mut startup = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--port" => match args.next() {
+                    Some(port) => startup.port = Some(port),
+                    None => warn!("--port requires a port name"),
+                },
+                "--baud" => match args.next() {
+                    Some(rate) => startup.baud_rate = parse_baud(&rate),
+                    None => warn!("--baud requires a baud rate"),
+                },
+                _ => {}
+            }
+        }
+        startup
+    }
+
+    /// Builds the startup port from the environment variables, read with
+    /// `var`.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        Self {
+            port: var(STARTUP_PORT_ENV).filter(|port| !port.is_empty()),
+            baud_rate: var(STARTUP_BAUD_ENV).and_then(|rate| parse_baud(&rate)),
+        }
+    }
+
+    /// Returns this startup port, with the settings it lacks taken from
+    /// `other` if it names no port or the same one.
+    #[must_use]
+    pub fn or(self, other: Self) -> Self {
+        match self.port {
+            None => other,
+            Some(port) => {
+                let baud_rate = match other.port {
+                    Some(other_port) if other_port == port => self.baud_rate.or(other.baud_rate),
+                    _ => self.baud_rate,
+                };
+                Self {
+                    port: Some(port),
+                    baud_rate,
+                }
+            }
+        }
+    }
+}
+
+/// Parses a baud rate argument, warning about invalid ones.
+fn parse_baud(rate: &str) -> Option<u32> {
+    parse_baud_rate(rate)
+        .inspect_err(|e| warn!("Ignoring startup baud rate {rate:?}: {e}"))
+        .ok()
+}
+
+/// System: opens the startup port once discovery finds it and its port
+/// thread runs, and selects it.
+///
+/// Waits [`STARTUP_PORT_TIMEOUT`] for it, then warns and stops looking.
+pub fn open_startup_port(
+    time: Res<Time>,
+    startup: Option<Res<StartupArgs>>,
+    selected: Option<ResMut<Selected>>,
+    ports: Query<(Entity, &SerialPortName, &SerialIo)>,
+    mut waited: Local<Duration>,
+    mut done: Local<bool>,
+) {
+    if *done {
+        return;
+    }
+    let port = startup.as_ref().and_then(|startup| startup.port.as_deref());
+    let Some(port) = port.filter(|port| !port.is_empty()) else {
+        *done = true;
+        return;
+    };
+
+    for (entity, name, io) in &ports {
+        if name.0 != port {
+            continue;
+        }
+        let mut serial = lock_serial(&io.0);
+        if serial.tx_channel().is_none() {
+            continue;
+        }
+        if !serial.is_open() {
+            if let Some(baud_rate) = startup.as_ref().and_then(|startup| startup.baud_rate) {
+                serial.set.baud_rate = baud_rate;
+            }
+            match serial.open_session() {
+                Ok(_) => info!("Opening startup port {port}"),
+                Err(e) => warn!("Failed to open startup port {port}: {e}"),
+            }
+        }
+        if let Some(mut selected) = selected {
+            selected.select(entity);
+        }
+        *done = true;
+        return;
+    }
+
+    *waited += time.delta();
+    if *waited >= STARTUP_PORT_TIMEOUT {
+        warn!(
+            "Startup port {port} not found within {} s",
+            STARTUP_PORT_TIMEOUT.as_secs()
+        );
+        *done = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::state::PortChannelData;
+    use crate::serial::{Serial, Serials};
+    use bevy::ecs::system::RunSystemOnce;
+    use std::collections::HashMap;
+    use tokio::sync::broadcast;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_startup_port_from_args_and_env() {
+        let startup = StartupArgs::from_args(args(&["--mock", "--port", "COM7", "--baud", "9600"]));
+        assert_eq!(startup.port.as_deref(), Some("COM7"));
+        assert_eq!(startup.baud_rate, Some(9600));
+        assert_eq!(
+            StartupArgs::from_args(args(&["--baud", "fast"])).baud_rate,
+            None
+        );
+
+        let env = HashMap::from([
+            (STARTUP_PORT_ENV, "/dev/ttyUSB0".to_string()),
+            (STARTUP_BAUD_ENV, "115200".to_string()),
+        ]);
+        let from_env = StartupArgs::from_vars(|name| env.get(name).cloned());
+        assert_eq!(
+            from_env,
+            StartupArgs {
+                port: Some("/dev/ttyUSB0".to_string()),
+                baud_rate: Some(115_200),
+            }
+        );
+        assert_eq!(StartupArgs::from_vars(|_| None), StartupArgs::default());
+
+        // Arguments win; the environment only fills in the same port.
+        assert_eq!(startup.clone().or(from_env.clone()), startup);
+        let port_only = StartupArgs::from_args(args(&["--port", "/dev/ttyUSB0"]));
+        assert_eq!(port_only.or(from_env.clone()), from_env);
+        assert_eq!(StartupArgs::default().or(from_env.clone()), from_env);
+    }
+
+    #[test]
+    fn test_startup_port_is_opened_and_selected() {
+        let (tx, mut rx) = broadcast::channel(4);
+        let mut serial = Serial::new();
+        serial.set.port_name = "COM7".to_string();
+        *serial.tx_channel() = Some(tx);
+        let mut serials = Serials::new();
+        serials.add(serial);
+
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Selected>();
+        world.insert_resource(StartupArgs {
+            port: Some("COM7".to_string()),
+            baud_rate: Some(9600),
+        });
+        crate::serial::status::spawn_ports(&mut world, serials);
+        world.run_system_once(open_startup_port).unwrap();
+
+        match rx.try_recv() {
+            Ok(PortChannelData::PortOpen(settings)) => {
+                assert_eq!(settings.port_name, "COM7");
+                assert_eq!(settings.baud_rate, 9600);
+            }
+            other => panic!("expected an open request, got {other:?}"),
+        }
+        let com7 = world
+            .resource::<crate::serial::status::PortEntities>()
+            .get("COM7")
+            .unwrap();
+        assert!(world.resource::<Selected>().is_selected(com7));
+    }
+}
@@ -5,10 +5,12 @@
 
 use std::fmt;
 
+use bevy::reflect::Reflect;
+
 use super::port::PortSettings;
 
 /// Serial port connection state.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
 pub enum PortState {
     /// Port is ready for communication.
     Ready,
@@ -60,11 +62,26 @@ pub struct PortRwData {
     pub data: Vec<u8>,
 }
 
+/// RS-232 modem status lines reported by the port.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignalState {
+    /// Clear To Send.
+    pub cts: bool,
+    /// Data Set Ready.
+    pub dsr: bool,
+    /// Data Carrier Detect.
+    pub dcd: bool,
+    /// Ring Indicator.
+    pub ri: bool,
+}
+
 /// Channel data for communication between threads.
 #[derive(Clone, Debug)]
 pub enum PortChannelData {
     /// Available port names.
     PortName(Vec<String>),
+    /// USB serial numbers of discovered ports, as `(port name, serial number)`.
+    PortSerialNumbers(Vec<(String, String)>),
     /// Data to write to the port.
     PortWrite(PortRwData),
     /// Data read from the port.
@@ -77,6 +94,20 @@ pub enum PortChannelData {
     PortState(PortState),
     /// Port error occurred.
     PortError(PortRwData),
+    /// Modem status lines changed.
+    SignalUpdate(SignalState),
+    /// Data from a network proxy client to write to the port.
+    ProxyWrite(PortRwData),
+    /// Request to apply new line settings to the open port.
+    PortReconfigure(PortSettings),
+    /// Request to hold the line in the break condition, after the data
+    /// written before it.
+    SendBreak {
+        /// How long the line is held, in milliseconds.
+        duration_ms: u32,
+    },
+    /// Request to the discovery task to scan the port list now.
+    RequestScan,
 }
 
 impl From<PortChannelData> for Vec<String> {
@@ -89,6 +120,7 @@ impl From<PortChannelData> for Vec<String> {
 }
 
 /// Data source identifier for logging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DataSource {
     /// Data was written/sent.
     Write,
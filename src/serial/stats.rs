@@ -0,0 +1,111 @@
+//! # Stats Module
+//!
+//! Traffic statistics of port sessions.
+//!
+//! A session runs from the port opening until it closes. The port data keeps
+//! the counters of the current session and the sessions before it, so
+//! reopening a port does not lose what was sent and received.
+
+use chrono::{DateTime, Duration, Local};
+
+/// Ended sessions kept in a port's session history; older ones only count
+/// towards its lifetime totals.
+pub const MAX_SESSION_HISTORY: usize = 100;
+
+/// Traffic counters of one port session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionStats {
+    /// When the port was opened.
+    pub opened_at: DateTime<Local>,
+    /// When the port was closed, or `None` while it is open.
+    pub closed_at: Option<DateTime<Local>>,
+    /// Bytes written to the port.
+    pub bytes_sent: u64,
+    /// Bytes read from the port.
+    pub bytes_received: u64,
+    /// Errors reported by the port thread.
+    pub error_count: u32,
+}
+
+impl SessionStats {
+    /// Creates the counters of a session opened at `opened_at`.
+    #[must_use]
+    pub const fn new(opened_at: DateTime<Local>) -> Self {
+        Self {
+            opened_at,
+            closed_at: None,
+            bytes_sent: 0,
+            bytes_received: 0,
+            error_count: 0,
+        }
+    }
+
+    /// Returns how long the session lasted, or has lasted until `now` if it
+    /// is still open.
+    #[must_use]
+    pub fn duration(&self, now: DateTime<Local>) -> Duration {
+        self.closed_at.unwrap_or(now) - self.opened_at
+    }
+
+    /// Sums `sessions`: from the first opening to the last closing, which is
+    /// `None` if a session is still open, with all counters added up.
+    ///
+    /// Returns `None` if there are no sessions.
+    pub fn total<'a>(sessions: impl IntoIterator<Item = &'a Self>) -> Option<Self> {
+        sessions
+            .into_iter()
+            .fold(None, |total: Option<Self>, session| {
+                let Some(total) = total else {
+                    return Some(*session);
+                };
+                let closed_at = match (total.closed_at, session.closed_at) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                };
+                Some(Self {
+                    opened_at: total.opened_at.min(session.opened_at),
+                    closed_at,
+                    bytes_sent: total.bytes_sent + session.bytes_sent,
+                    bytes_received: total.bytes_received + session.bytes_received,
+                    error_count: total.error_count + session.error_count,
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_total_sums_sessions() {
+        let start = Local.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+        let first = SessionStats {
+            closed_at: Some(start + Duration::minutes(10)),
+            bytes_sent: 3,
+            bytes_received: 100,
+            error_count: 1,
+            ..SessionStats::new(start)
+        };
+        let second = SessionStats {
+            bytes_sent: 5,
+            bytes_received: 20,
+            ..SessionStats::new(start + Duration::hours(1))
+        };
+
+        assert_eq!(SessionStats::total([]), None);
+        assert_eq!(SessionStats::total([&first]), Some(first));
+        let total = SessionStats::total([&first, &second]).unwrap();
+        assert_eq!(total.opened_at, start);
+        assert_eq!(total.closed_at, None);
+        assert_eq!(
+            (total.bytes_sent, total.bytes_received, total.error_count),
+            (8, 120, 1)
+        );
+        assert_eq!(
+            second.duration(start + Duration::hours(2)),
+            Duration::hours(1)
+        );
+    }
+}
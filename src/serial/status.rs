@@ -0,0 +1,505 @@
+//! # Status Module
+//!
+//! One entity per port, for per-port systems, inspectors and user systems.
+//!
+//! Every port gets an entity with these components:
+//!
+//! - [`SerialPortName`], the port name;
+//! - [`SerialIo`], the port itself, with its thread, channels and data,
+//!   shared with [`Serials`];
+//! - [`SerialState`], [`SerialSettingsComp`] and [`SerialStatus`], reflected
+//!   copies of its state, settings and counters, updated each frame from the
+//!   [`PortSnapshots`], so external code can query them instead of locking
+//!   the port.
+//!
+//! [`sync_port_entities`] spawns the entity when a port is added and
+//! despawns it, with any user components attached to it, when the port is
+//! removed, giving it the [`LogConfig`] on the way. Its entity is looked up
+//! by port name, or in list order, in [`PortEntities`]. The settings are
+//! mirrored by [`PortSettingsReflect`], since the serial settings enums of
+//! `tokio_serial` do not implement [`Reflect`].
+//!
+//! Systems working on ports, and the selection, use these entities. The
+//! [`Serials`] component is only the registry they are spawned from: it
+//! holds the ports discovery, mocks and replays add, the ignored ports and
+//! the list order.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::data_types::DataType;
+use super::log::LogConfig;
+use super::port::{DataBits, FlowControl, Parity, PortSettings, Serial, StopBits, TimeoutAction};
+use super::snapshot::{PortSnapshot, PortSnapshots, lock_serial};
+use super::state::PortState;
+use super::{SerialHandle, Serials};
+use crate::error::SerialBevyError;
+
+/// Reflected mirror of [`Parity`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum ParityReflect {
+    /// No parity bit.
+    None,
+    /// Odd parity.
+    Odd,
+    /// Even parity.
+    Even,
+}
+
+/// Reflected mirror of [`FlowControl`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum FlowControlReflect {
+    /// No flow control.
+    None,
+    /// XON/XOFF flow control.
+    Software,
+    /// RTS/CTS flow control.
+    Hardware,
+}
+
+/// Reflected mirror of [`PortSettings`].
+#[derive(Clone, Debug, PartialEq, Eq, Reflect)]
+pub struct PortSettingsReflect {
+    /// Port name.
+    pub port_name: String,
+    /// Baud rate in bits per second.
+    pub baud_rate: u32,
+    /// Number of data bits, 5 to 8.
+    pub data_bits: u8,
+    /// Number of stop bits, 1 or 2.
+    pub stop_bits: u8,
+    /// Parity checking mode.
+    pub parity: ParityReflect,
+    /// Flow control mode.
+    pub flow_control: FlowControlReflect,
+    /// Timeout duration.
+    pub timeout: Duration,
+    /// Longest time an open port may go without receiving data, if watched.
+    pub receive_timeout: Option<Duration>,
+    /// What happens when the receive timeout elapses.
+    pub timeout_action: TimeoutAction,
+    /// Largest number of bytes written per second, if limited.
+    pub send_rate_limit: Option<u32>,
+    /// Silence on the line that ends a received frame, if framed by gaps.
+    pub frame_gap: Option<Duration>,
+}
+
+impl From<&PortSettings> for PortSettingsReflect {
+    fn from(settings: &PortSettings) -> Self {
+        Self {
+            port_name: settings.port_name.clone(),
+            baud_rate: settings.baud_rate,
+            data_bits: match settings.data_bits {
+                DataBits::Five => 5,
+                DataBits::Six => 6,
+                DataBits::Seven => 7,
+                DataBits::Eight => 8,
+            },
+            stop_bits: match settings.stop_bits {
+                StopBits::One => 1,
+                StopBits::Two => 2,
+            },
+            parity: match settings.parity {
+                Parity::None => ParityReflect::None,
+                Parity::Odd => ParityReflect::Odd,
+                Parity::Even => ParityReflect::Even,
+            },
+            flow_control: match settings.flow_control {
+                FlowControl::None => FlowControlReflect::None,
+                FlowControl::Software => FlowControlReflect::Software,
+                FlowControl::Hardware => FlowControlReflect::Hardware,
+            },
+            timeout: settings.timeout,
+            receive_timeout: settings.receive_timeout,
+            timeout_action: settings.timeout_action,
+            send_rate_limit: settings.send_rate_limit,
+            frame_gap: settings.frame_gap,
+        }
+    }
+}
+
+impl TryFrom<&PortSettingsReflect> for PortSettings {
+    type Error = SerialBevyError;
+
+    /// Converts the mirror back, e.g. after an inspector edited it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerialBevyError::InvalidConfig`] if the data bits are not 5
+    /// to 8 or the stop bits not 1 or 2.
+    fn try_from(settings: &PortSettingsReflect) -> Result<Self, Self::Error> {
+        let invalid = |name: &str, value: u8| {
+            SerialBevyError::InvalidConfig(format!(
+                "{}: invalid {name} {value}",
+                settings.port_name
+            ))
+        };
+        Ok(Self {
+            port_name: settings.port_name.clone(),
+            baud_rate: settings.baud_rate,
+            data_bits: match settings.data_bits {
+                5 => DataBits::Five,
+                6 => DataBits::Six,
+                7 => DataBits::Seven,
+                8 => DataBits::Eight,
+                bits => return Err(invalid("data bits", bits)),
+            },
+            stop_bits: match settings.stop_bits {
+                1 => StopBits::One,
+                2 => StopBits::Two,
+                bits => return Err(invalid("stop bits", bits)),
+            },
+            parity: match settings.parity {
+                ParityReflect::None => Parity::None,
+                ParityReflect::Odd => Parity::Odd,
+                ParityReflect::Even => Parity::Even,
+            },
+            flow_control: match settings.flow_control {
+                FlowControlReflect::None => FlowControl::None,
+                FlowControlReflect::Software => FlowControl::Software,
+                FlowControlReflect::Hardware => FlowControl::Hardware,
+            },
+            timeout: settings.timeout,
+            receive_timeout: settings.receive_timeout,
+            timeout_action: settings.timeout_action,
+            send_rate_limit: settings.send_rate_limit,
+            frame_gap: settings.frame_gap,
+        })
+    }
+}
+
+/// The port an entity stands for, shared with [`Serials`]; lock it to use
+/// its thread, channels and data.
+#[derive(Component, Clone)]
+pub struct SerialIo(pub SerialHandle);
+
+/// Connection state of the port, on the port's entity.
+///
+/// Only changes when the state does, so `Changed<SerialState>` finds the
+/// ports that opened, closed or failed.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct SerialState(pub PortState);
+
+/// Settings of the port, on the port's entity.
+///
+/// Changes made to it are not applied to the port; it only changes when the
+/// port's settings do.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct SerialSettingsComp(pub PortSettingsReflect);
+
+/// Name of the port an entity stands for.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub struct SerialPortName(pub String);
+
+/// Entities of the ports, by port name and in list order.
+#[derive(Resource, Default, Debug)]
+pub struct PortEntities {
+    /// Entities by port name.
+    by_name: BTreeMap<String, Entity>,
+    /// Entities in the list order of the [`Serials`].
+    ordered: Vec<Entity>,
+}
+
+impl PortEntities {
+    /// Returns the entity of the port named `port_name`.
+    #[must_use]
+    pub fn get(&self, port_name: &str) -> Option<Entity> {
+        self.by_name.get(port_name).copied()
+    }
+
+    /// Returns the port names and their entities, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Entity)> {
+        self.by_name
+            .iter()
+            .map(|(name, entity)| (name.as_str(), *entity))
+    }
+
+    /// Returns the entities in list order, for [`Query::iter_many`].
+    #[must_use]
+    pub fn ordered(&self) -> &[Entity] {
+        &self.ordered
+    }
+
+    /// Returns the number of port entities.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ordered.len()
+    }
+
+    /// Returns true if there are no port entities.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ordered.is_empty()
+    }
+}
+
+/// Status of one port, on the port's entity.
+///
+/// Changes made to it are not applied to the port; it is overwritten with
+/// the port's state every frame.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct SerialStatus {
+    /// Port name.
+    pub name: String,
+    /// Connection state.
+    pub state: PortState,
+    /// Configured baud rate.
+    pub baud_rate: u32,
+    /// Data type of the receive view and input.
+    pub data_type: DataType,
+    /// Port settings.
+    pub settings: PortSettingsReflect,
+    /// Bytes received since the port was last opened.
+    pub rx_bytes: u64,
+    /// Bytes sent since the port was last opened.
+    pub tx_bytes: u64,
+    /// Errors reported since the port was last opened.
+    pub error_count: u32,
+    /// Latest error reported by the port thread.
+    pub last_error: Option<String>,
+}
+
+impl SerialStatus {
+    /// Copies the status of a port.
+    #[must_use]
+    pub fn capture(serial: &mut Serial) -> Self {
+        Self::from(&PortSnapshot::capture(serial))
+    }
+}
+
+impl From<&PortSnapshot> for SerialStatus {
+    fn from(snapshot: &PortSnapshot) -> Self {
+        Self {
+            name: snapshot.name.clone(),
+            state: snapshot.state,
+            baud_rate: snapshot.baud_rate,
+            data_type: snapshot.data_type,
+            settings: PortSettingsReflect::from(&snapshot.settings),
+            rx_bytes: snapshot.rx_bytes,
+            tx_bytes: snapshot.tx_bytes,
+            error_count: snapshot.error_count,
+            last_error: snapshot.last_error.clone(),
+        }
+    }
+}
+
+/// System: keeps one entity per port of the [`Serials`], spawning it when a
+/// port is added and despawning it when the port is removed.
+///
+/// Ports are matched to entities by their [`SerialIo`] handle, so only new
+/// ports are locked, once, to give them the [`LogConfig`] and read their
+/// initial status.
+pub fn sync_port_entities(
+    mut commands: Commands,
+    serials: Query<&Serials>,
+    ports: Query<(Entity, &SerialPortName, &SerialIo)>,
+    log_config: Option<Res<LogConfig>>,
+    mut entities: ResMut<PortEntities>,
+) {
+    let Ok(serials) = serials.single() else {
+        return;
+    };
+
+    for (entity, name, io) in &ports {
+        if serials
+            .serial
+            .iter()
+            .any(|serial| Arc::ptr_eq(serial, &io.0))
+        {
+            continue;
+        }
+        if entities.get(&name.0) == Some(entity) {
+            entities.by_name.remove(&name.0);
+        }
+        commands.entity(entity).despawn();
+    }
+    entities.ordered.clear();
+    for serial in &serials.serial {
+        if let Some((entity, _, _)) = ports.iter().find(|(_, _, io)| Arc::ptr_eq(serial, &io.0)) {
+            entities.ordered.push(entity);
+            continue;
+        }
+        let mut port = lock_serial(serial);
+        if let Some(config) = &log_config
+            && port.data().session_log().config() != &**config
+        {
+            port.data().session_log_mut().set_config(config);
+        }
+        let status = SerialStatus::capture(&mut port);
+        drop(port);
+        let name = status.name.clone();
+        let entity = commands
+            .spawn((
+                SerialPortName(name.clone()),
+                Name::new(format!("Serial {name}")),
+                SerialIo(Arc::clone(serial)),
+                SerialState(status.state),
+                SerialSettingsComp(status.settings.clone()),
+                status,
+            ))
+            .id();
+        entities.by_name.insert(name, entity);
+        entities.ordered.push(entity);
+    }
+}
+
+/// System: updates the [`SerialState`], [`SerialSettingsComp`] and
+/// [`SerialStatus`] of the port entities from the [`PortSnapshots`], without
+/// locking the ports.
+pub fn update_serial_statuses(
+    snapshots: Res<PortSnapshots>,
+    mut ports: Query<(
+        &SerialPortName,
+        &mut SerialState,
+        &mut SerialSettingsComp,
+        &mut SerialStatus,
+    )>,
+) {
+    for (name, mut state, mut settings, mut status) in &mut ports {
+        let Some(snapshot) = snapshots.get(&name.0) else {
+            continue;
+        };
+        let current = SerialStatus::from(snapshot);
+        state.set_if_neq(SerialState(current.state));
+        settings.set_if_neq(SerialSettingsComp(current.settings.clone()));
+        status.set_if_neq(current);
+    }
+}
+
+/// Spawns `serials` and the entities of its ports into `world`, as the
+/// plugin does on the first frame.
+#[cfg(test)]
+pub(crate) fn spawn_ports(world: &mut World, serials: Serials) {
+    use bevy::ecs::system::RunSystemOnce;
+
+    world.spawn(serials);
+    world.init_resource::<PortEntities>();
+    world.run_system_once(sync_port_entities).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::Serials;
+    use crate::serial::snapshot::update_port_snapshots;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn test_port_settings_mirror_round_trips() {
+        let mut settings = PortSettings::discovered("COM1");
+        settings.data_bits = DataBits::Seven;
+        settings.stop_bits = StopBits::Two;
+        settings.parity = Parity::Even;
+        settings.flow_control = FlowControl::Hardware;
+        settings.receive_timeout = Some(Duration::from_secs(2));
+        settings.timeout_action = TimeoutAction::Close;
+
+        let mirror = PortSettingsReflect::from(&settings);
+        assert_eq!(mirror.data_bits, 7);
+        assert_eq!(mirror.parity, ParityReflect::Even);
+        let back = PortSettings::try_from(&mirror).unwrap();
+        assert!(settings.diff(&back).is_empty());
+
+        let invalid = PortSettingsReflect {
+            stop_bits: 3,
+            ..mirror
+        };
+        assert!(PortSettings::try_from(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_status_entities_follow_ports() {
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string(), "COM2".to_string()], 1);
+        let mut world = World::new();
+        spawn_ports(&mut world, serials);
+        world.init_resource::<PortSnapshots>();
+
+        world.run_system_once(update_port_snapshots).unwrap();
+        world.run_system_once(update_serial_statuses).unwrap();
+        let mut query = world.query::<&SerialStatus>();
+        let mut names: Vec<_> = query.iter(&world).map(|s| s.name.clone()).collect();
+        names.sort();
+        assert_eq!(names, ["COM1", "COM2"]);
+        let com1 = world.resource::<PortEntities>().get("COM1").unwrap();
+        let com2 = world.resource::<PortEntities>().get("COM2").unwrap();
+        assert_eq!(
+            world.get::<SerialPortName>(com1),
+            Some(&SerialPortName("COM1".to_string()))
+        );
+
+        let mut serials = world.query::<&mut Serials>();
+        {
+            let mut serials = serials.single_mut(&mut world).unwrap();
+            serials.get(0).lock().unwrap().set.baud_rate = 9600;
+            serials.remove(1);
+        }
+        world.run_system_once(sync_port_entities).unwrap();
+        world.run_system_once(update_port_snapshots).unwrap();
+        world.run_system_once(update_serial_statuses).unwrap();
+        let statuses: Vec<_> = query.iter(&world).cloned().collect();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].name, "COM1");
+        assert_eq!(statuses[0].baud_rate, 9600);
+        assert_eq!(statuses[0].settings.baud_rate, 9600);
+        assert_eq!(statuses[0].state, PortState::Close);
+
+        // The entity of a kept port stays; a removed port's is despawned.
+        let entities = world.resource::<PortEntities>();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities.get("COM1"), Some(com1));
+        assert!(world.get_entity(com2).is_err());
+        assert_eq!(
+            world.get::<SerialSettingsComp>(com1).unwrap().0.baud_rate,
+            9600
+        );
+    }
+
+    #[test]
+    fn test_port_entity_shares_the_port() {
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string()], 1);
+        let mut world = World::new();
+        spawn_ports(&mut world, serials);
+        world.init_resource::<PortSnapshots>();
+        let com1 = world.resource::<PortEntities>().get("COM1").unwrap();
+
+        // Changes made through the entity reach the port in `Serials`.
+        world
+            .get::<SerialIo>(com1)
+            .unwrap()
+            .0
+            .lock()
+            .unwrap()
+            .open();
+        let mut query = world.query::<&Serials>();
+        assert!(
+            query
+                .single(&world)
+                .unwrap()
+                .get(0)
+                .lock()
+                .unwrap()
+                .is_open()
+        );
+
+        world.run_system_once(update_port_snapshots).unwrap();
+        world.run_system_once(update_serial_statuses).unwrap();
+        assert_eq!(
+            world.get::<SerialState>(com1),
+            Some(&SerialState(PortState::Ready))
+        );
+
+        // Syncing again keeps the entity.
+        world.run_system_once(sync_port_entities).unwrap();
+        assert_eq!(world.resource::<PortEntities>().get("COM1"), Some(com1));
+        assert_eq!(world.query::<&SerialIo>().iter(&world).count(), 1);
+    }
+}
@@ -0,0 +1,283 @@
+//! # Testing Module
+//!
+//! Hardware-free integration tests of serial protocols.
+//!
+//! [`SerialTestPlugin`] plays a list of [`TestStep`]s against a virtual
+//! device, by default a loopback echoing every write. Each step runs once the
+//! previous one has completed; an expected reply that does not arrive in time
+//! panics with what was expected and what was received. The app exits with
+//! [`AppExit::Success`] after the last step.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use bevy::prelude::*;
+//! use serial_bevy::serial::testing::{SerialTestPlugin, TestStep};
+//!
+//! let exit = App::new()
+//!     .add_plugins(SerialTestPlugin::from_steps(vec![
+//!         TestStep::Send(b"ping\n".to_vec()),
+//!         TestStep::ExpectReceive(b"ping\n".to_vec(), Duration::from_millis(500)),
+//!     ]))
+//!     .run();
+//! assert_eq!(exit, AppExit::Success);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, WriteHalf};
+use tokio::sync::mpsc;
+
+use super::discovery::Runtime;
+use super::mock::{MockBackend, MockTraffic, spawn_mock_device};
+
+/// Pause between two updates of the test runner.
+const RUNNER_TICK: Duration = Duration::from_millis(1);
+
+/// One step of a serial test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestStep {
+    /// Writes the bytes to the device.
+    Send(Vec<u8>),
+    /// Waits until the device has sent the bytes, failing after the timeout.
+    ///
+    /// Bytes received before the expected ones are skipped.
+    ExpectReceive(Vec<u8>, Duration),
+    /// Waits for the given time.
+    Sleep(Duration),
+}
+
+/// Plugin running a serial test against a virtual device.
+///
+/// The plugin installs its own runner, which updates the app until the test
+/// has passed. A failing step panics, failing the surrounding test.
+#[derive(Clone, Debug)]
+pub struct SerialTestPlugin {
+    /// Steps to run, in order.
+    steps: Vec<TestStep>,
+    /// Traffic of the device on the other end.
+    device: MockTraffic,
+}
+
+impl SerialTestPlugin {
+    /// Creates a test running `steps` against a loopback device.
+    #[must_use]
+    pub const fn from_steps(steps: Vec<TestStep>) -> Self {
+        Self {
+            steps,
+            device: MockTraffic::Echo,
+        }
+    }
+
+    /// Runs the steps against `device` instead of a loopback, e.g. a
+    /// [`SimulatedSerial`](super::sim::SimulatedSerial).
+    #[must_use]
+    pub fn with_device(mut self, device: MockTraffic) -> Self {
+        self.device = device;
+        self
+    }
+}
+
+impl Plugin for SerialTestPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SerialTestRun::start(
+            self.steps.clone(),
+            self.device.clone(),
+        ))
+        .add_systems(Update, run_serial_test)
+        .set_runner(run_until_exit);
+    }
+}
+
+/// State of a running serial test.
+#[derive(Resource)]
+pub struct SerialTestRun {
+    /// Runtime the device and its reader task run on.
+    runtime: Runtime,
+    /// Application side of the device, for writes.
+    writer: WriteHalf<MockBackend>,
+    /// Chunks read from the device.
+    received: mpsc::UnboundedReceiver<Vec<u8>>,
+    /// Steps to run, in order.
+    steps: Vec<TestStep>,
+    /// Index of the current step.
+    current: usize,
+    /// When the current step started.
+    step_started: Instant,
+    /// Bytes received and not yet matched by an [`TestStep::ExpectReceive`].
+    pending: Vec<u8>,
+}
+
+impl SerialTestRun {
+    /// Spawns the device and prepares to run `steps` against it.
+    fn start(steps: Vec<TestStep>, device: MockTraffic) -> Self {
+        let runtime = Runtime::init();
+        let backend = runtime.block_on(async { spawn_mock_device(device) });
+        let (mut reader, writer) = tokio::io::split(backend);
+        let (tx, received) = mpsc::unbounded_channel();
+        runtime.spawn(async move {
+            let mut buffer = [0u8; 1024];
+            while let Ok(n) = reader.read(&mut buffer).await {
+                if n == 0 || tx.send(buffer[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            runtime,
+            writer,
+            received,
+            steps,
+            current: 0,
+            step_started: Instant::now(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the number of completed steps.
+    #[must_use]
+    pub const fn completed_steps(&self) -> usize {
+        self.current
+    }
+
+    /// Returns true once every step has completed.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    /// Runs steps until one has to wait or all are done.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a write fails or an expected reply times out.
+    fn advance(&mut self) {
+        while let Ok(chunk) = self.received.try_recv() {
+            self.pending.extend_from_slice(&chunk);
+        }
+
+        while let Some(step) = self.steps.get(self.current) {
+            let elapsed = self.step_started.elapsed();
+            match step {
+                TestStep::Send(data) => {
+                    if let Err(e) = self.runtime.block_on(self.writer.write_all(data)) {
+                        panic!(
+                            "serial test step {}: failed to send \"{}\": {e}",
+                            self.current + 1,
+                            data.escape_ascii()
+                        );
+                    }
+                }
+                TestStep::ExpectReceive(expected, timeout) => {
+                    if let Some(end) = find(&self.pending, expected) {
+                        self.pending.drain(..end);
+                    } else if elapsed >= *timeout {
+                        panic!(
+                            "serial test step {}: expected \"{}\" within {timeout:?}, received \"{}\"",
+                            self.current + 1,
+                            expected.escape_ascii(),
+                            self.pending.escape_ascii()
+                        );
+                    } else {
+                        return;
+                    }
+                }
+                TestStep::Sleep(duration) => {
+                    if elapsed < *duration {
+                        return;
+                    }
+                }
+            }
+            self.current += 1;
+            self.step_started = Instant::now();
+        }
+    }
+}
+
+/// Returns the end of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|start| start + needle.len())
+}
+
+/// System: advances the serial test and exits once it has passed.
+fn run_serial_test(mut run: ResMut<SerialTestRun>, mut exit: MessageWriter<AppExit>) {
+    run.advance();
+    if run.is_finished() {
+        exit.write(AppExit::Success);
+    }
+}
+
+/// Runner updating the app until it requests to exit.
+fn run_until_exit(mut app: App) -> AppExit {
+    app.finish();
+    app.cleanup();
+    loop {
+        app.update();
+        if let Some(exit) = app.should_exit() {
+            return exit;
+        }
+        std::thread::sleep(RUNNER_TICK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::sim::SimulatedSerial;
+
+    #[test]
+    fn test_at_command_is_answered_with_ok() {
+        let modem = SimulatedSerial::new("sim://modem", vec![], |data| {
+            (data == b"AT\r\n").then(|| b"OK\r\n".to_vec())
+        });
+        let exit = App::new()
+            .add_plugins(
+                SerialTestPlugin::from_steps(vec![
+                    TestStep::Send(b"AT\r\n".to_vec()),
+                    TestStep::ExpectReceive(b"OK\r\n".to_vec(), Duration::from_secs(1)),
+                ])
+                .with_device(MockTraffic::Simulated(modem)),
+            )
+            .run();
+        assert_eq!(exit, AppExit::Success);
+    }
+
+    #[test]
+    fn test_loopback_echoes_after_sleep() {
+        let exit = App::new()
+            .add_plugins(SerialTestPlugin::from_steps(vec![
+                TestStep::Send(b"AT\r\n".to_vec()),
+                TestStep::Sleep(Duration::from_millis(20)),
+                TestStep::Send(b"ATI\r\n".to_vec()),
+                TestStep::ExpectReceive(b"AT\r\n".to_vec(), Duration::from_secs(1)),
+                TestStep::ExpectReceive(b"ATI\r\n".to_vec(), Duration::from_secs(1)),
+            ]))
+            .run();
+        assert_eq!(exit, AppExit::Success);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected \"OK\\r\\n\" within 50ms, received \"AT\\r\\n\"")]
+    fn test_missing_reply_panics_with_received_data() {
+        App::new()
+            .add_plugins(SerialTestPlugin::from_steps(vec![
+                TestStep::Send(b"AT\r\n".to_vec()),
+                TestStep::ExpectReceive(b"OK\r\n".to_vec(), Duration::from_millis(50)),
+            ]))
+            .run();
+    }
+
+    #[test]
+    fn test_find_returns_end_of_match() {
+        assert_eq!(find(b"xxOK\r\n", b"OK"), Some(4));
+        assert_eq!(find(b"ERROR", b"OK"), None);
+        assert_eq!(find(b"", b""), Some(0));
+    }
+}
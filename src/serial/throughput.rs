@@ -0,0 +1,198 @@
+//! # Throughput Module
+//!
+//! Measurement of the bytes per second a port actually carries, which OS
+//! buffering and USB latency can keep well below its baud rate.
+//!
+//! The test writes numbered packets to a device that echoes what it receives
+//! and counts the echoed packets as they come back. Each packet starts with
+//! its sequence number as a 4-byte big-endian integer, followed by filler
+//! bytes. At most [`THROUGHPUT_WINDOW`] packets are in flight at a time, so
+//! the channel to the port thread never overflows.
+
+use tokio::sync::broadcast;
+use tokio::time::{Duration, Instant};
+
+use super::state::{PortChannelData, PortRwData};
+use crate::error::{Result, SerialBevyError};
+
+/// Size of the sequence number at the start of each packet.
+pub const MIN_THROUGHPUT_PAYLOAD: usize = 4;
+
+/// Packets written before waiting for their echoes.
+pub const THROUGHPUT_WINDOW: u32 = 8;
+
+/// Time to wait for more echoed data before the test gives up.
+pub const THROUGHPUT_IDLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Result of a throughput test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThroughputResult {
+    /// Echoed bytes per second, from the first write to the last echo.
+    pub actual_bps: f64,
+    /// Packets echoed with an unexpected sequence number, or never echoed.
+    pub drop_count: u32,
+}
+
+/// Returns the packet with sequence number `sequence`, `payload_size` bytes
+/// long but at least [`MIN_THROUGHPUT_PAYLOAD`].
+#[must_use]
+pub fn throughput_packet(sequence: u32, payload_size: usize) -> Vec<u8> {
+    let mut packet = sequence.to_be_bytes().to_vec();
+    packet.extend(
+        (0..payload_size.saturating_sub(MIN_THROUGHPUT_PAYLOAD))
+            .map(|i| (sequence as usize + i) as u8),
+    );
+    packet
+}
+
+/// Splits echoed data back into packets and checks their sequence numbers.
+#[derive(Debug)]
+pub struct ThroughputCounter {
+    /// Packet size.
+    payload_size: usize,
+    /// Echoed bytes not yet making up a whole packet.
+    partial: Vec<u8>,
+    /// Number of whole packets echoed.
+    packets: u32,
+    /// Sequence number expected next.
+    expected: u32,
+    /// Packets echoed with an unexpected sequence number.
+    wrong: u32,
+    /// Number of echoed bytes.
+    bytes: u64,
+}
+
+impl ThroughputCounter {
+    /// Creates a counter for packets of `payload_size` bytes, but at least
+    /// [`MIN_THROUGHPUT_PAYLOAD`].
+    #[must_use]
+    pub fn new(payload_size: usize) -> Self {
+        Self {
+            payload_size: payload_size.max(MIN_THROUGHPUT_PAYLOAD),
+            partial: Vec::new(),
+            packets: 0,
+            expected: 0,
+            wrong: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Adds echoed bytes.
+    pub fn push(&mut self, data: &[u8]) {
+        self.bytes += data.len() as u64;
+        self.partial.extend_from_slice(data);
+        let whole = self.partial.len() / self.payload_size * self.payload_size;
+        for packet in self.partial[..whole].chunks_exact(self.payload_size) {
+            let sequence = u32::from_be_bytes([packet[0], packet[1], packet[2], packet[3]]);
+            if sequence != self.expected {
+                self.wrong += 1;
+            }
+            self.expected = sequence.wrapping_add(1);
+            self.packets += 1;
+        }
+        self.partial.drain(..whole);
+    }
+
+    /// Returns the number of whole packets echoed.
+    #[must_use]
+    pub const fn packets(&self) -> u32 {
+        self.packets
+    }
+
+    /// Returns the result of a test of `iterations` packets whose echoes
+    /// took `elapsed`. Packets never echoed count as dropped.
+    #[must_use]
+    pub fn finish(&self, iterations: u32, elapsed: Duration) -> ThroughputResult {
+        let seconds = elapsed.as_secs_f64();
+        ThroughputResult {
+            actual_bps: if seconds > 0.0 {
+                self.bytes as f64 / seconds
+            } else {
+                0.0
+            },
+            drop_count: self.wrong + iterations.saturating_sub(self.packets),
+        }
+    }
+}
+
+/// Runs a throughput test of `iterations` packets of `payload_size` bytes,
+/// writing through `tx` and reading the echoes from `rx`.
+///
+/// # Errors
+///
+/// Returns an error if the port thread exits, or if nothing is echoed
+/// within [`THROUGHPUT_IDLE_TIMEOUT`].
+pub async fn run_throughput_test(
+    tx: broadcast::Sender<PortChannelData>,
+    mut rx: broadcast::Receiver<PortChannelData>,
+    port_name: String,
+    payload_size: usize,
+    iterations: u32,
+) -> Result<ThroughputResult> {
+    let mut counter = ThroughputCounter::new(payload_size);
+    let mut sent = 0;
+    let start = Instant::now();
+    let mut last_echo = start;
+
+    while counter.packets() < iterations {
+        while sent < iterations && sent.saturating_sub(counter.packets()) < THROUGHPUT_WINDOW {
+            tx.send(PortChannelData::PortWrite(PortRwData {
+                data: throughput_packet(sent, payload_size),
+            }))
+            .map_err(|e| SerialBevyError::channel(e.to_string()))?;
+            sent += 1;
+        }
+
+        match tokio::time::timeout(THROUGHPUT_IDLE_TIMEOUT, rx.recv()).await {
+            Err(_) => break,
+            Ok(Ok(PortChannelData::PortRead(data))) => {
+                counter.push(&data.data);
+                last_echo = Instant::now();
+            }
+            Ok(Ok(_) | Err(broadcast::error::RecvError::Lagged(_))) => {}
+            Ok(Err(broadcast::error::RecvError::Closed)) => {
+                return Err(SerialBevyError::channel("port thread exited"));
+            }
+        }
+    }
+
+    if counter.bytes == 0 && iterations > 0 {
+        return Err(SerialBevyError::timeout(format!(
+            "no echo from {port_name} within {} ms",
+            THROUGHPUT_IDLE_TIMEOUT.as_millis()
+        )));
+    }
+    Ok(counter.finish(iterations, last_echo - start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throughput_packet_layout() {
+        assert_eq!(throughput_packet(0x0102_0304, 6), [1, 2, 3, 4, 4, 5]);
+        assert_eq!(throughput_packet(7, 0), [0, 0, 0, 7]);
+    }
+
+    #[test]
+    fn test_counter_splits_echoes_into_packets() {
+        let mut echo: Vec<u8> = (0..4).flat_map(|seq| throughput_packet(seq, 8)).collect();
+        let mut counter = ThroughputCounter::new(8);
+        for chunk in echo.chunks(5) {
+            counter.push(chunk);
+        }
+        assert_eq!(counter.packets(), 4);
+        let result = counter.finish(4, Duration::from_secs(2));
+        assert_eq!(result.drop_count, 0);
+        assert!((result.actual_bps - 16.0).abs() < f64::EPSILON);
+
+        // A lost packet shows up as a jump in the sequence numbers, and as
+        // one packet missing at the end.
+        echo.drain(8..16);
+        let mut counter = ThroughputCounter::new(8);
+        counter.push(&echo);
+        assert_eq!(counter.packets(), 3);
+        assert_eq!(counter.finish(4, Duration::from_secs(1)).drop_count, 2);
+    }
+}
@@ -0,0 +1,174 @@
+//! # Timestamp Module
+//!
+//! Timestamp display modes for the receive view.
+
+use std::fmt::{self, Write};
+
+use chrono::{DateTime, Local};
+
+/// Full timestamp format used in log files.
+pub const LOG_TIMESTAMP_FORMAT: &str = "%Y%m%d %H:%M:%S.%3f";
+
+/// How timestamps are shown in the receive view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampMode {
+    /// No timestamp or source indicator.
+    #[default]
+    None,
+    /// Time of day, e.g. `12:34:56.789`.
+    TimeOnly,
+    /// Date and time, e.g. `20250101 12:34:56.789`.
+    DateTime,
+    /// Time elapsed since the previous entry.
+    DeltaSinceLast,
+    /// Time elapsed since the port was opened.
+    DeltaSincePortOpen,
+}
+
+impl fmt::Display for TimestampMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::TimeOnly => write!(f, "Time"),
+            Self::DateTime => write!(f, "Date & Time"),
+            Self::DeltaSinceLast => write!(f, "Delta (last)"),
+            Self::DeltaSincePortOpen => write!(f, "Delta (open)"),
+        }
+    }
+}
+
+impl TimestampMode {
+    /// All modes, in the order shown in the UI.
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::TimeOnly,
+        Self::DateTime,
+        Self::DeltaSinceLast,
+        Self::DeltaSincePortOpen,
+    ];
+
+    /// Returns true if the mode shows elapsed time rather than wall-clock time.
+    #[must_use]
+    pub const fn is_delta(&self) -> bool {
+        matches!(self, Self::DeltaSinceLast | Self::DeltaSincePortOpen)
+    }
+
+    /// Default strftime format of wall-clock modes.
+    #[must_use]
+    pub const fn default_format(&self) -> &'static str {
+        match self {
+            Self::TimeOnly => "%H:%M:%S.%3f",
+            _ => LOG_TIMESTAMP_FORMAT,
+        }
+    }
+
+    /// Formats the timestamp of an entry.
+    ///
+    /// `reference` is the instant deltas are measured from (previous entry or
+    /// port open). `format` overrides the strftime format of wall-clock modes;
+    /// an invalid format falls back to the default one. Returns `None` for
+    /// [`TimestampMode::None`].
+    #[must_use]
+    pub fn format(
+        &self,
+        time: DateTime<Local>,
+        reference: Option<DateTime<Local>>,
+        format: Option<&str>,
+    ) -> Option<String> {
+        match self {
+            Self::None => None,
+            Self::TimeOnly | Self::DateTime => {
+                let format = format.filter(|f| !f.is_empty());
+                let stamp = format
+                    .and_then(|f| format_time(time, f))
+                    .or_else(|| format_time(time, self.default_format()))
+                    .unwrap_or_default();
+                Some(stamp)
+            }
+            Self::DeltaSinceLast | Self::DeltaSincePortOpen => {
+                let elapsed = reference
+                    .map_or_else(chrono::TimeDelta::zero, |reference| time - reference)
+                    .max(chrono::TimeDelta::zero());
+                Some(format_delta(elapsed))
+            }
+        }
+    }
+}
+
+/// Formats `time` with a strftime format, or returns `None` if the format is invalid.
+#[must_use]
+pub fn format_time(time: DateTime<Local>, format: &str) -> Option<String> {
+    let mut out = String::new();
+    write!(out, "{}", time.format(format)).ok()?;
+    Some(out)
+}
+
+/// Formats an elapsed time as `+S.mmms`.
+fn format_delta(elapsed: chrono::TimeDelta) -> String {
+    let millis = elapsed.num_milliseconds();
+    format!("+{}.{:03}s", millis / 1000, millis % 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32, s: u32, ms: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 1, 2, h, m, s)
+            .unwrap()
+            .checked_add_signed(chrono::TimeDelta::milliseconds(ms.into()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_wall_clock_modes() {
+        let time = at(12, 34, 56, 789);
+        assert_eq!(TimestampMode::None.format(time, None, None), None);
+        assert_eq!(
+            TimestampMode::TimeOnly.format(time, None, None).as_deref(),
+            Some("12:34:56.789")
+        );
+        assert_eq!(
+            TimestampMode::DateTime.format(time, None, None).as_deref(),
+            Some("20250102 12:34:56.789")
+        );
+    }
+
+    #[test]
+    fn test_format_override() {
+        let time = at(12, 34, 56, 0);
+        assert_eq!(
+            TimestampMode::TimeOnly
+                .format(time, None, Some("%H:%M"))
+                .as_deref(),
+            Some("12:34")
+        );
+        // An invalid override falls back to the default format.
+        assert_eq!(
+            TimestampMode::TimeOnly
+                .format(time, None, Some("%Q"))
+                .as_deref(),
+            Some("12:34:56.000")
+        );
+    }
+
+    #[test]
+    fn test_delta_modes() {
+        let reference = at(12, 0, 0, 0);
+        let time = at(12, 0, 1, 250);
+        assert_eq!(
+            TimestampMode::DeltaSinceLast
+                .format(time, Some(reference), None)
+                .as_deref(),
+            Some("+1.250s")
+        );
+        assert_eq!(
+            TimestampMode::DeltaSincePortOpen
+                .format(time, None, None)
+                .as_deref(),
+            Some("+0.000s")
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! # Traffic Module
+//!
+//! A shared, chronological log of the data sent and received on all ports,
+//! used by the merged receive view.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use chrono::{DateTime, Local};
+
+use super::state::DataSource;
+
+/// Default maximum number of entries kept in the [`TrafficLog`].
+pub const DEFAULT_TRAFFIC_CAPACITY: usize = 5000;
+
+/// Maximum bytes of text kept per [`TrafficEntry`].
+///
+/// Longer data is cut and ends with [`TRUNCATION_MARK`].
+pub const MAX_TRAFFIC_ENTRY_BYTES: usize = 4 * 1024;
+
+/// Marker appended to an entry whose text was cut.
+pub const TRUNCATION_MARK: &str = "…";
+
+/// One chunk of traffic on a port.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrafficEntry {
+    /// When the entry was recorded.
+    pub time: DateTime<Local>,
+    /// Name of the port the data belongs to.
+    pub port_name: String,
+    /// Direction of the data.
+    pub source: DataSource,
+    /// The data, decoded as (lossy) UTF-8.
+    pub text: String,
+}
+
+/// Chronological log of the traffic on all ports.
+///
+/// Appended by `send_serial_data` and `receive_serial_data`. Data received in
+/// several chunks is joined into one entry until a line ends, as long as no
+/// other traffic comes in between. The oldest entries are dropped once the
+/// capacity is reached, and each entry keeps at most
+/// [`MAX_TRAFFIC_ENTRY_BYTES`] of text.
+#[derive(Resource, Debug)]
+pub struct TrafficLog {
+    /// Entries, oldest first.
+    entries: VecDeque<TrafficEntry>,
+    /// Maximum number of entries.
+    capacity: usize,
+}
+
+impl Default for TrafficLog {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_TRAFFIC_CAPACITY)
+    }
+}
+
+impl TrafficLog {
+    /// Creates an empty log keeping at most `capacity` entries.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Records data sent or received on a port at the current time.
+    pub fn record(&mut self, port_name: &str, source: DataSource, data: &[u8]) {
+        self.record_at(Local::now(), port_name, source, data);
+    }
+
+    /// Records data sent or received on a port at `time`.
+    pub fn record_at(
+        &mut self,
+        time: DateTime<Local>,
+        port_name: &str,
+        source: DataSource,
+        data: &[u8],
+    ) {
+        if data.is_empty() {
+            return;
+        }
+        let text = String::from_utf8_lossy(data);
+        if let Some(last) = self.entries.back_mut()
+            && last.port_name == port_name
+            && last.source == source
+            && source == DataSource::Read
+            && !last.text.ends_with('\n')
+            && !last.text.ends_with(TRUNCATION_MARK)
+        {
+            push_capped(&mut last.text, &text);
+            return;
+        }
+
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(TrafficEntry {
+            time,
+            port_name: port_name.to_string(),
+            source,
+            text: {
+                let mut capped = String::new();
+                push_capped(&mut capped, &text);
+                capped
+            },
+        });
+    }
+
+    /// Returns the entries, oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TrafficEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the log is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Maximum number of entries kept.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Appends `text` to `entry`, cutting it at [`MAX_TRAFFIC_ENTRY_BYTES`] on a
+/// character boundary and marking the cut with [`TRUNCATION_MARK`].
+fn push_capped(entry: &mut String, text: &str) {
+    let room = MAX_TRAFFIC_ENTRY_BYTES.saturating_sub(entry.len());
+    if text.len() <= room {
+        entry.push_str(text);
+        return;
+    }
+    let mut end = room;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    entry.push_str(&text[..end]);
+    entry.push_str(TRUNCATION_MARK);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_are_chronological_across_ports() {
+        let mut log = TrafficLog::default();
+        log.record("COM1", DataSource::Write, b"AT\n");
+        log.record("COM2", DataSource::Read, b"AT\n");
+        log.record("COM2", DataSource::Write, b"OK\n");
+        log.record("COM1", DataSource::Read, b"OK\n");
+
+        let entries: Vec<(&str, DataSource)> = log
+            .entries()
+            .map(|entry| (entry.port_name.as_str(), entry.source))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("COM1", DataSource::Write),
+                ("COM2", DataSource::Read),
+                ("COM2", DataSource::Write),
+                ("COM1", DataSource::Read),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_received_chunks_join_until_line_end() {
+        let mut log = TrafficLog::default();
+        log.record("COM1", DataSource::Read, b"hel");
+        log.record("COM1", DataSource::Read, b"lo\n");
+        log.record("COM1", DataSource::Read, b"next");
+        log.record("COM2", DataSource::Read, b"other");
+        log.record("COM1", DataSource::Read, b" line");
+
+        let texts: Vec<&str> = log.entries().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts, ["hello\n", "next", "other", " line"]);
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest_entries() {
+        let mut log = TrafficLog::with_capacity(3);
+        for i in 0..5 {
+            log.record("COM1", DataSource::Write, format!("{i}").as_bytes());
+        }
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.capacity(), 3);
+        let texts: Vec<&str> = log.entries().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts, ["2", "3", "4"]);
+
+        log.clear();
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_entries_are_capped_in_bytes() {
+        let mut log = TrafficLog::default();
+        let long = vec![b'a'; MAX_TRAFFIC_ENTRY_BYTES + 10];
+        log.record("COM1", DataSource::Write, &long);
+        log.record(
+            "COM1",
+            DataSource::Read,
+            &vec![b'b'; MAX_TRAFFIC_ENTRY_BYTES - 1],
+        );
+        log.record("COM1", DataSource::Read, "éé".as_bytes());
+        log.record("COM1", DataSource::Read, b"rest\n");
+
+        let texts: Vec<&str> = log.entries().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts.len(), 3);
+        assert_eq!(
+            texts[0].len(),
+            MAX_TRAFFIC_ENTRY_BYTES + TRUNCATION_MARK.len()
+        );
+        assert!(texts[0].ends_with(TRUNCATION_MARK));
+        // "é" is two bytes and does not fit into the last free byte.
+        assert_eq!(
+            texts[1].len(),
+            MAX_TRAFFIC_ENTRY_BYTES - 1 + TRUNCATION_MARK.len()
+        );
+        assert!(texts[1].ends_with(TRUNCATION_MARK));
+        assert_eq!(texts[2], "rest\n");
+    }
+}
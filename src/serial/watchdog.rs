@@ -0,0 +1,193 @@
+//! # Watchdog Module
+//!
+//! Receive timeout of open ports.
+//!
+//! A port with a [`PortSettings::receive_timeout`] that receives nothing for
+//! that long raises a [`PortStateChangeEvent`], logs an error line and applies
+//! its [`TimeoutAction`]. The timeout is raised again only after data has been
+//! received in between.
+//!
+//! [`PortSettings::receive_timeout`]: super::port::PortSettings::receive_timeout
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use log::{debug, warn};
+
+use super::port::{Serial, TimeoutAction};
+use super::snapshot::lock_serial;
+use super::state::{DataSource, PortChannelData};
+use super::status::SerialIo;
+use super::traffic::TrafficLog;
+
+/// Kind of a [`PortStateChangeEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortStateChangeKind {
+    /// The port received nothing for its receive timeout.
+    ReceiveTimeout,
+}
+
+/// Sent when the state of a port changes outside of the user's control.
+#[derive(Message, Clone, Debug, PartialEq, Eq)]
+pub struct PortStateChangeEvent {
+    /// Name of the port.
+    pub port_name: String,
+    /// What changed.
+    pub kind: PortStateChangeKind,
+}
+
+/// Returns the error line logged when a port times out after `timeout`.
+#[must_use]
+pub fn receive_timeout_message(timeout: Duration) -> String {
+    format!("No data received for {} ms", timeout.as_millis())
+}
+
+/// Checks the receive timeout of `serial` at `now`, applying its action if
+/// it elapsed. Returns the event to send, if any.
+fn check_port(
+    serial: &mut Serial,
+    now: Instant,
+    traffic: &mut TrafficLog,
+) -> Option<PortStateChangeEvent> {
+    let timeout = serial.set.receive_timeout?;
+    if !serial.is_open() || !serial.data().check_receive_timeout(timeout, now) {
+        return None;
+    }
+
+    let port_name = serial.set.port_name.clone();
+    let message = receive_timeout_message(timeout);
+    warn!("{port_name}: {message}");
+    serial
+        .data()
+        .write_source_file(message.as_bytes(), DataSource::Error);
+    traffic.record(&port_name, DataSource::Error, message.as_bytes());
+    serial.data().set_last_error(Some(message));
+
+    match serial.set.timeout_action {
+        TimeoutAction::Alert => {}
+        TimeoutAction::Close => {
+            debug!("Closing {port_name} after receive timeout");
+            if let Some(tx) = serial.tx_channel()
+                && let Err(e) = tx.send(PortChannelData::PortClose(port_name.clone()))
+            {
+                warn!("Failed to close {port_name}: {e}");
+            }
+        }
+        TimeoutAction::Error => serial.error(),
+    }
+
+    Some(PortStateChangeEvent {
+        port_name,
+        kind: PortStateChangeKind::ReceiveTimeout,
+    })
+}
+
+/// System: raises the receive timeout of open ports that received nothing
+/// for too long.
+pub fn check_receive_timeout(
+    ports: Query<&SerialIo>,
+    mut traffic: ResMut<TrafficLog>,
+    mut events: MessageWriter<PortStateChangeEvent>,
+) {
+    let now = Instant::now();
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        if let Some(event) = check_port(&mut serial, now, &mut traffic) {
+            events.write(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::Serials;
+    use crate::serial::mock::MockTraffic;
+    use bevy::ecs::system::RunSystemOnce;
+    use tokio::sync::broadcast;
+
+    fn world_with_port(timeout: Option<Duration>, action: TimeoutAction) -> World {
+        let mut serial = Serial::new_mock("mock://timeout", MockTraffic::Echo);
+        serial.set.receive_timeout = timeout;
+        serial.set.timeout_action = action;
+        serial.open();
+        let mut serials = Serials::new();
+        serials.add(serial);
+
+        let mut world = World::new();
+        crate::serial::status::spawn_ports(&mut world, serials);
+        world.init_resource::<TrafficLog>();
+        world.init_resource::<Messages<PortStateChangeEvent>>();
+        world
+    }
+
+    fn sent_events(world: &World) -> Vec<PortStateChangeEvent> {
+        world
+            .resource::<Messages<PortStateChangeEvent>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect()
+    }
+
+    fn with_serial<T>(world: &mut World, f: impl FnOnce(&mut Serial) -> T) -> T {
+        let mut query = world.query::<&SerialIo>();
+        let port = query.single(&*world).unwrap();
+        f(&mut port.0.lock().unwrap())
+    }
+
+    #[test]
+    fn test_receive_timeout_fires_once() {
+        let mut world = world_with_port(Some(Duration::ZERO), TimeoutAction::Alert);
+        world.run_system_once(check_receive_timeout).unwrap();
+        assert_eq!(
+            sent_events(&world),
+            [PortStateChangeEvent {
+                port_name: "mock://timeout".to_string(),
+                kind: PortStateChangeKind::ReceiveTimeout,
+            }]
+        );
+        with_serial(&mut world, |serial| {
+            assert!(serial.is_open());
+            assert!(serial.data().is_receive_timed_out());
+            assert_eq!(
+                serial.data().last_error(),
+                Some(receive_timeout_message(Duration::ZERO).as_str())
+            );
+        });
+        assert_eq!(world.resource::<TrafficLog>().len(), 1);
+
+        world.run_system_once(check_receive_timeout).unwrap();
+        assert_eq!(sent_events(&world).len(), 1);
+
+        with_serial(&mut world, |serial| serial.data().mark_received());
+        world.run_system_once(check_receive_timeout).unwrap();
+        assert_eq!(sent_events(&world).len(), 2);
+    }
+
+    #[test]
+    fn test_receive_timeout_not_elapsed_or_disabled() {
+        let mut world = world_with_port(Some(Duration::from_secs(60)), TimeoutAction::Alert);
+        world.run_system_once(check_receive_timeout).unwrap();
+        assert!(sent_events(&world).is_empty());
+
+        let mut world = world_with_port(None, TimeoutAction::Alert);
+        world.run_system_once(check_receive_timeout).unwrap();
+        assert!(sent_events(&world).is_empty());
+    }
+
+    #[test]
+    fn test_receive_timeout_actions() {
+        let mut world = world_with_port(Some(Duration::ZERO), TimeoutAction::Close);
+        let (tx, mut rx) = broadcast::channel(4);
+        with_serial(&mut world, |serial| *serial.tx_channel() = Some(tx));
+        world.run_system_once(check_receive_timeout).unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(PortChannelData::PortClose(name)) if name == "mock://timeout"
+        ));
+
+        let mut world = world_with_port(Some(Duration::ZERO), TimeoutAction::Error);
+        world.run_system_once(check_receive_timeout).unwrap();
+        assert!(with_serial(&mut world, |serial| serial.is_error()));
+    }
+}
@@ -1,10 +1,61 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
 use bevy::app::AppExit;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::i18n::{Locale, UiStrings, strings};
+use super::llm_settings::{DEFAULT_MODEL, load_llm_settings, save_llm_settings_to_disk};
+use crate::fonts::UiFontPreferences;
+use crate::serial::encoding::LineEnding;
+use crate::serial::llm::DEFAULT_BASE_URL;
+use crate::serial::port::COMMON_BAUD_RATES;
+use crate::serial::port_data::LocalEcho;
+use crate::serial::response::ResponseTiming;
+use crate::serial::snapshot::{PortSnapshot, lock_serial};
+use crate::serial::startup::StartupArgs;
+use crate::serial::status::SerialIo;
+use crate::serial::traffic::TrafficLog;
+use crate::serial::{Serial, Serials};
+
 /// Configuration file path for app persistence.
 const CONFIG_FILE: &str = "config/app_memory.ron";
 
+/// Smallest allowed font size of the receive view, labels and input.
+pub const MIN_RECEIVE_FONT_SIZE: f32 = 8.0;
+
+/// Largest allowed font size of the receive view, labels and input.
+pub const MAX_RECEIVE_FONT_SIZE: f32 = 36.0;
+
+/// Data of a receive view page drawn by default, in KB.
+pub const DEFAULT_MAX_DISPLAY_KB: usize = 256;
+
+/// Smallest allowed maximum of displayed data, in KB.
+pub const MIN_MAX_DISPLAY_KB: usize = 16;
+
+/// Largest allowed maximum of displayed data, in KB.
+pub const MAX_MAX_DISPLAY_KB: usize = 16 * 1024;
+
+/// User-assigned alias and color of a port.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PortLabel {
+    /// Name shown instead of the port name.
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// Color of the port name in tabs and selectors.
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+}
+
+impl PortLabel {
+    /// Returns true if neither an alias nor a color is set.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.alias.is_none() && self.color.is_none()
+    }
+}
+
 /// Resource storing current (and persisted) UI configuration.
 /// Saved to disk directly, independent of egui memory.
 #[derive(Resource, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,15 +70,92 @@ pub struct PanelWidths {
     /// Whether the LLM side panel is visible.
     #[serde(default)]
     pub show_llm_panel: bool,
-    /// Global LLM API key (shared across all serial ports).
+    /// Whether the packet templates side panel is visible.
     #[serde(default)]
+    pub show_templates_panel: bool,
+    /// Whether the high-contrast colors are used.
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Global LLM API key (shared across all serial ports).
+    ///
+    /// Saved in the OS keychain instead, see
+    /// [`llm_settings`](super::llm_settings); only read here to migrate older
+    /// files.
+    #[serde(default, skip_serializing)]
     pub llm_key: String,
+    /// Whether the API key is saved in the OS keychain.
+    #[serde(skip, default = "default_true")]
+    pub llm_remember_key: bool,
+    /// Whether the API key was asked for on the first run, saved in
+    /// `llm.toml`.
+    #[serde(skip)]
+    pub llm_key_prompted: bool,
     /// Global LLM model selection (shared across all serial ports).
-    #[serde(default)]
+    ///
+    /// Saved in `llm.toml` instead; only read here to migrate older files.
+    #[serde(default = "default_model", skip_serializing)]
     pub llm_model: String,
+    /// Chat completions endpoint of the global LLM panel and of ports
+    /// without their own, saved in `llm.toml`.
+    #[serde(skip, default = "default_base_url")]
+    pub llm_base_url: String,
     /// Global LLM coding plan toggle (shared across all serial ports).
     #[serde(default)]
     pub llm_with_coding_plan: bool,
+    /// Font size of the receive view, from older files; only read here to
+    /// migrate them to [`font_prefs`](Self::font_prefs).
+    #[serde(rename = "receive_font_size", default, skip_serializing)]
+    pub legacy_receive_font_size: Option<f32>,
+    /// Font sizes and monospace font of the UI.
+    #[serde(default)]
+    pub font_prefs: UiFontPreferences,
+    /// Whether the UI preferences window is open.
+    #[serde(skip)]
+    pub show_ui_preferences: bool,
+    /// Most recent data of a receive view page drawn, in KB; earlier data
+    /// is only drawn on request.
+    #[serde(default = "default_max_display_kb")]
+    pub max_display_kb: usize,
+    /// Receive view timestamp format overrides, keyed by port name.
+    #[serde(default)]
+    pub timestamp_formats: BTreeMap<String, String>,
+    /// Line endings of sent data other than none, keyed by port name.
+    #[serde(default)]
+    pub line_endings: BTreeMap<String, LineEnding>,
+    /// Local echo settings other than always, keyed by port name.
+    #[serde(default)]
+    pub local_echo: BTreeMap<String, LocalEcho>,
+    /// Response timing settings other than off, keyed by port name.
+    #[serde(default)]
+    pub response_timing: BTreeMap<String, ResponseTiming>,
+    /// Port aliases and colors, keyed by [`Serial::label_key`].
+    #[serde(default)]
+    pub port_labels: BTreeMap<String, PortLabel>,
+    /// Language of the UI.
+    #[serde(default)]
+    pub locale: Locale,
+    /// Baud rates entered by the user that are not in [`COMMON_BAUD_RATES`],
+    /// offered alongside them.
+    #[serde(default)]
+    pub custom_baud_rates: Vec<u32>,
+    /// Port names in the order the user dragged them into; empty until the
+    /// list is first reordered.
+    #[serde(default)]
+    pub port_order: Vec<String>,
+    /// Whether the ignored ports are kept ignored on the next launch.
+    #[serde(default)]
+    pub remember_ignored_ports: bool,
+    /// Ports ignored by the user, saved while
+    /// [`remember_ignored_ports`](Self::remember_ignored_ports) is set.
+    #[serde(default)]
+    pub ignored_ports: Vec<String>,
+    /// Port opened at startup, remembered from the last one given on the
+    /// command line or in the environment.
+    #[serde(default)]
+    pub startup_port: Option<String>,
+    /// Baud rate of the [`startup_port`](Self::startup_port).
+    #[serde(default)]
+    pub startup_baud: Option<u32>,
 }
 
 impl Default for PanelWidths {
@@ -37,18 +165,204 @@ impl Default for PanelWidths {
             right_width: 220.0,
             show_settings_panel: true,
             show_llm_panel: false,
+            show_templates_panel: false,
+            high_contrast: false,
             llm_key: String::new(),
-            llm_model: String::from("glm-4.5-air"),
+            llm_remember_key: true,
+            llm_key_prompted: false,
+            llm_model: default_model(),
+            llm_base_url: default_base_url(),
             llm_with_coding_plan: false,
+            legacy_receive_font_size: None,
+            font_prefs: UiFontPreferences::default(),
+            show_ui_preferences: false,
+            max_display_kb: DEFAULT_MAX_DISPLAY_KB,
+            timestamp_formats: BTreeMap::new(),
+            line_endings: BTreeMap::new(),
+            local_echo: BTreeMap::new(),
+            response_timing: BTreeMap::new(),
+            port_labels: BTreeMap::new(),
+            locale: Locale::default(),
+            custom_baud_rates: Vec::new(),
+            port_order: Vec::new(),
+            remember_ignored_ports: false,
+            ignored_ports: Vec::new(),
+            startup_port: None,
+            startup_baud: None,
         }
     }
 }
 
 impl PanelWidths {
-    /// Clamp widths to valid ranges.
+    /// Clamp widths and sizes to valid ranges.
     fn clamp(&mut self) {
         self.left_width = self.left_width.clamp(120.0, 600.0);
         self.right_width = self.right_width.clamp(160.0, 800.0);
+        if let Some(size) = self.legacy_receive_font_size.take() {
+            self.font_prefs.receive_view_size = size;
+        }
+        let defaults = UiFontPreferences::default();
+        let prefs = &mut self.font_prefs;
+        prefs.receive_view_size =
+            clamp_font_size(prefs.receive_view_size, defaults.receive_view_size);
+        prefs.label_size = clamp_font_size(prefs.label_size, defaults.label_size);
+        prefs.input_size = clamp_font_size(prefs.input_size, defaults.input_size);
+        self.max_display_kb = self
+            .max_display_kb
+            .clamp(MIN_MAX_DISPLAY_KB, MAX_MAX_DISPLAY_KB);
+    }
+
+    /// Returns the receive view font size.
+    #[must_use]
+    pub const fn receive_font_size(&self) -> f32 {
+        self.font_prefs.receive_view_size
+    }
+
+    /// Sets the receive view font size, clamped to the supported range.
+    pub fn set_receive_font_size(&mut self, size: f32) {
+        self.font_prefs.receive_view_size =
+            clamp_font_size(size, UiFontPreferences::default().receive_view_size);
+    }
+
+    /// Returns the most recent data of a receive view page drawn, in bytes.
+    #[must_use]
+    pub const fn max_display_bytes(&self) -> usize {
+        self.max_display_kb * 1024
+    }
+
+    /// Returns the UI strings of the selected language.
+    #[must_use]
+    pub const fn strings(&self) -> &'static UiStrings {
+        strings(self.locale)
+    }
+
+    /// Returns the baud rates offered in the selector: the common rates and
+    /// the custom ones, in ascending order.
+    #[must_use]
+    pub fn baud_rate_options(&self) -> Vec<u32> {
+        let mut rates: Vec<u32> = COMMON_BAUD_RATES
+            .iter()
+            .chain(&self.custom_baud_rates)
+            .copied()
+            .collect();
+        rates.sort_unstable();
+        rates.dedup();
+        rates
+    }
+
+    /// Remembers a baud rate entered by the user, unless it is already offered.
+    pub fn add_custom_baud_rate(&mut self, rate: u32) {
+        if !COMMON_BAUD_RATES.contains(&rate) && !self.custom_baud_rates.contains(&rate) {
+            self.custom_baud_rates.push(rate);
+            self.custom_baud_rates.sort_unstable();
+        }
+    }
+
+    /// Remembers the port list order after the user reordered it. Ports
+    /// that are currently missing keep their place after the listed ones.
+    pub fn set_port_order(&mut self, port_names: Vec<String>) {
+        let missing: Vec<String> = self
+            .port_order
+            .drain(..)
+            .filter(|name| !port_names.contains(name))
+            .collect();
+        self.port_order = port_names;
+        self.port_order.extend(missing);
+    }
+
+    /// Ignores the saved ignored ports that are not ignored yet, then saves
+    /// the ports ignored now, while ignored ports are remembered.
+    ///
+    /// A port the user unignores must be dropped from
+    /// [`ignored_ports`](Self::ignored_ports) at the same time with
+    /// [`PanelWidths::unignore_port`].
+    pub fn sync_ignored_ports(&mut self, serials: &mut Serials, traffic: &mut TrafficLog) {
+        if !self.remember_ignored_ports {
+            return;
+        }
+        for name in &self.ignored_ports {
+            if !serials.is_ignored(name) {
+                serials.ignore(name, traffic);
+            }
+        }
+        let ignored = serials.ignored_ports();
+        if self.ignored_ports != ignored {
+            self.ignored_ports = ignored;
+        }
+    }
+
+    /// Lets discovery add `port_name` again and forgets it was ignored.
+    pub fn unignore_port(&mut self, serials: &mut Serials, port_name: &str) {
+        serials.unignore(port_name);
+        self.ignored_ports.retain(|name| name != port_name);
+    }
+
+    /// Sets whether the ignored ports are kept ignored on the next launch.
+    pub fn set_remember_ignored_ports(&mut self, remember: bool) {
+        self.remember_ignored_ports = remember;
+        if !remember {
+            self.ignored_ports.clear();
+        }
+    }
+
+    /// Forgets the remembered startup port, so the next launch opens none
+    /// unless one is given again.
+    pub fn forget_startup_port(&mut self) {
+        self.startup_port = None;
+        self.startup_baud = None;
+    }
+
+    /// Returns the name to show for a port: its alias, or the port name.
+    #[must_use]
+    pub fn port_display_name(&self, serial: &Serial) -> String {
+        self.display_name(&serial.label_key(), &serial.set.port_name)
+    }
+
+    /// Returns the label color of a port, if one is set.
+    #[must_use]
+    pub fn port_color(&self, serial: &Serial) -> Option<[u8; 3]> {
+        self.label_color(&serial.label_key())
+    }
+
+    /// Returns the name to show for a port snapshot: its alias, or the port name.
+    #[must_use]
+    pub fn snapshot_display_name(&self, port: &PortSnapshot) -> String {
+        self.display_name(&port.label_key, &port.name)
+    }
+
+    /// Returns the label color of a port snapshot, if one is set.
+    #[must_use]
+    pub fn snapshot_color(&self, port: &PortSnapshot) -> Option<[u8; 3]> {
+        self.label_color(&port.label_key)
+    }
+
+    /// Returns the alias stored under `key`, or `port_name`.
+    fn display_name(&self, key: &str, port_name: &str) -> String {
+        self.port_labels
+            .get(key)
+            .and_then(|label| label.alias.clone())
+            .unwrap_or_else(|| port_name.to_string())
+    }
+
+    /// Returns the color stored under `key`, if any.
+    fn label_color(&self, key: &str) -> Option<[u8; 3]> {
+        self.port_labels.get(key).and_then(|label| label.color)
+    }
+
+    /// Edits the label stored under `key`, removing it once it is empty.
+    pub fn edit_port_label(&mut self, key: &str, edit: impl FnOnce(&mut PortLabel)) {
+        let mut label = self.port_labels.remove(key).unwrap_or_default();
+        edit(&mut label);
+        if label
+            .alias
+            .as_ref()
+            .is_some_and(|alias| alias.trim().is_empty())
+        {
+            label.alias = None;
+        }
+        if !label.is_empty() {
+            self.port_labels.insert(key.to_string(), label);
+        }
     }
 }
 
@@ -56,9 +370,36 @@ const fn default_true() -> bool {
     true
 }
 
+const fn default_max_display_kb() -> usize {
+    DEFAULT_MAX_DISPLAY_KB
+}
+
+/// Clamps a font size to the supported range, or returns `default` if it is
+/// not a number.
+fn clamp_font_size(size: f32, default: f32) -> f32 {
+    if size.is_nan() {
+        default
+    } else {
+        size.clamp(MIN_RECEIVE_FONT_SIZE, MAX_RECEIVE_FONT_SIZE)
+    }
+}
+
+fn default_model() -> String {
+    DEFAULT_MODEL.to_string()
+}
+
+fn default_base_url() -> String {
+    DEFAULT_BASE_URL.to_string()
+}
+
 /// Load configuration directly from disk file.
 fn load_config_from_disk() -> Option<PanelWidths> {
-    if let Ok(data) = std::fs::read_to_string(CONFIG_FILE) {
+    load_config_from(Path::new(CONFIG_FILE))
+}
+
+/// Load configuration from the file at `path`.
+fn load_config_from(path: &Path) -> Option<PanelWidths> {
+    if let Ok(data) = std::fs::read_to_string(path) {
         match ron::from_str::<PanelWidths>(&data) {
             Ok(mut widths) => {
                 widths.clamp();
@@ -75,20 +416,27 @@ fn load_config_from_disk() -> Option<PanelWidths> {
 
 /// Save configuration directly to disk file.
 fn save_config_to_disk(widths: &PanelWidths) {
+    save_config_to(Path::new(CONFIG_FILE), widths);
+}
+
+/// Save configuration to the file at `path`, creating its directory.
+fn save_config_to(path: &Path, widths: &PanelWidths) {
     log::debug!(
         "[serial_ui] Saving panel config to disk: left={}, right={}",
         widths.left_width,
         widths.right_width
     );
 
-    if let Err(e) = std::fs::create_dir_all("config") {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty())
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
         eprintln!("[serial_ui] Failed to create config directory: {e}");
         return;
     }
 
     match ron::to_string(widths) {
         Ok(data) => {
-            if let Err(e) = std::fs::write(CONFIG_FILE, data) {
+            if let Err(e) = std::fs::write(path, data) {
                 eprintln!("[serial_ui] Failed to write config file: {e}");
             } else {
                 log::debug!("[serial_ui] Saved panel config to disk");
@@ -100,12 +448,67 @@ fn save_config_to_disk(widths: &PanelWidths) {
     }
 }
 
-/// System: initialize panel config resource, loading from disk if available.
-pub fn init_panel_widths(mut commands: Commands) {
-    let config = load_config_from_disk().unwrap_or_default();
+/// System: initialize panel config resource, loading from disk if available,
+/// along with the LLM settings.
+pub fn init_panel_widths(mut commands: Commands, startup: Option<ResMut<StartupArgs>>) {
+    let mut config = load_config_from_disk().unwrap_or_default();
+    load_llm_settings(&mut config);
+    if let Some(mut startup) = startup {
+        remember_startup_port(&mut config, &mut startup);
+    }
     commands.insert_resource(config);
 }
 
+/// Opens the remembered startup port if none was given, and remembers the
+/// one given otherwise; an empty port name forgets it.
+fn remember_startup_port(config: &mut PanelWidths, startup: &mut StartupArgs) {
+    let remembered = StartupArgs {
+        port: config.startup_port.clone(),
+        baud_rate: config.startup_baud,
+    };
+    *startup = std::mem::take(startup).or(remembered);
+    if startup.port.as_deref() == Some("") {
+        *startup = StartupArgs::default();
+    }
+    config.startup_port.clone_from(&startup.port);
+    config.startup_baud = startup.baud_rate;
+}
+
+/// System: hands the font preferences to the font plugin, which restyles
+/// egui with them without reloading the fonts.
+pub fn sync_font_preferences(
+    panel_widths: Option<Res<PanelWidths>>,
+    font_prefs: Option<ResMut<UiFontPreferences>>,
+) {
+    if let (Some(panel_widths), Some(mut font_prefs)) = (panel_widths, font_prefs)
+        && *font_prefs != panel_widths.font_prefs
+    {
+        font_prefs.clone_from(&panel_widths.font_prefs);
+    }
+}
+
+/// System: sets the saved line ending of each port once, when its entity
+/// is spawned; after that the line ending selector keeps both in sync.
+pub fn load_port_line_endings(
+    panel_widths: Option<Res<PanelWidths>>,
+    ports: Query<&SerialIo, Added<SerialIo>>,
+) {
+    let Some(panel_widths) = panel_widths else {
+        return;
+    };
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        let Some(line_ending) = panel_widths
+            .line_endings
+            .get(&serial.set.port_name)
+            .copied()
+        else {
+            continue;
+        };
+        *serial.data().line_ending() = line_ending;
+    }
+}
+
 /// System: save configuration directly from resource when app is exiting.
 pub fn save_config_on_exit(
     panel_widths: Res<PanelWidths>,
@@ -115,5 +518,265 @@ pub fn save_config_on_exit(
         exit_events.clear();
         log::debug!("[serial_ui] App exit detected, saving configuration...");
         save_config_to_disk(&panel_widths);
+        save_llm_settings_to_disk(&panel_widths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::status::spawn_ports;
+
+    #[test]
+    fn test_saved_line_ending_is_loaded_once_per_port() {
+        let mut config = PanelWidths::default();
+        config
+            .line_endings
+            .insert("COM1".to_string(), LineEnding::CrLf);
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string(), "COM2".to_string()], 1);
+        let com1 = serials.serial[0].clone();
+        let com2 = serials.serial[1].clone();
+        let mut world = World::new();
+        world.insert_resource(config);
+        spawn_ports(&mut world, serials);
+
+        let load = world.register_system(load_port_line_endings);
+        world.run_system(load).unwrap();
+        assert_eq!(*lock_serial(&com1).data().line_ending(), LineEnding::CrLf);
+        assert_eq!(*lock_serial(&com2).data().line_ending(), LineEnding::None);
+
+        // Later changes of the port are kept.
+        *lock_serial(&com1).data().line_ending() = LineEnding::Lf;
+        world.run_system(load).unwrap();
+        assert_eq!(*lock_serial(&com1).data().line_ending(), LineEnding::Lf);
+    }
+
+    #[test]
+    fn test_receive_font_size_clamped() {
+        let mut config = PanelWidths::default();
+        assert_eq!(config.receive_font_size(), 18.0);
+
+        config.set_receive_font_size(2.0);
+        assert_eq!(config.receive_font_size(), MIN_RECEIVE_FONT_SIZE);
+
+        config.set_receive_font_size(100.0);
+        assert_eq!(config.receive_font_size(), MAX_RECEIVE_FONT_SIZE);
+
+        config.set_receive_font_size(f32::NAN);
+        assert_eq!(config.receive_font_size(), 18.0);
+    }
+
+    #[test]
+    fn test_port_alias_falls_back_to_port_name() {
+        let mut config = PanelWidths::default();
+        let mut serial = Serial::new();
+        serial.set.port_name = "/dev/ttyUSB3".to_string();
+        assert_eq!(config.port_display_name(&serial), "/dev/ttyUSB3");
+
+        serial.set_usb_serial_number(Some("0042".to_string()));
+        config.edit_port_label(&serial.label_key(), |label| {
+            label.alias = Some("GPS".to_string());
+            label.color = Some([255, 0, 0]);
+        });
+        assert_eq!(config.port_display_name(&serial), "GPS");
+        assert_eq!(config.port_color(&serial), Some([255, 0, 0]));
+
+        // The label follows the adapter to another port name.
+        serial.set.port_name = "/dev/ttyUSB0".to_string();
+        assert_eq!(config.port_display_name(&serial), "GPS");
+    }
+
+    #[test]
+    fn test_empty_port_label_is_removed() {
+        let mut config = PanelWidths::default();
+        config.edit_port_label("COM3", |label| label.alias = Some("Modem".to_string()));
+        assert_eq!(config.port_labels.len(), 1);
+
+        config.edit_port_label("COM3", |label| label.alias = Some("  ".to_string()));
+        assert!(config.port_labels.is_empty());
+    }
+
+    #[test]
+    fn test_port_labels_persist() {
+        let mut config = PanelWidths::default();
+        config.edit_port_label("usb:0042", |label| label.color = Some([1, 2, 3]));
+        let data = ron::to_string(&config).unwrap();
+        let loaded: PanelWidths = ron::from_str(&data).unwrap();
+        assert_eq!(loaded.port_labels, config.port_labels);
+    }
+
+    #[test]
+    fn test_receive_font_size_clamped_on_load() {
+        let mut config: PanelWidths = ron::from_str(
+            "(left_width: 160.0, right_width: 220.0, receive_font_size: 72.0, max_display_kb: 1)",
+        )
+        .unwrap();
+        config.clamp();
+        assert_eq!(config.receive_font_size(), MAX_RECEIVE_FONT_SIZE);
+        assert_eq!(config.max_display_kb, MIN_MAX_DISPLAY_KB);
+
+        let config: PanelWidths = ron::from_str("(left_width: 160.0, right_width: 220.0)").unwrap();
+        assert_eq!(config.receive_font_size(), 18.0);
+        assert_eq!(config.max_display_bytes(), DEFAULT_MAX_DISPLAY_KB * 1024);
+    }
+
+    #[test]
+    fn test_font_preferences_restored_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join("app_memory.ron");
+        let mut config = PanelWidths::default();
+        config.font_prefs = UiFontPreferences {
+            receive_view_size: 24.0,
+            label_size: 15.0,
+            input_size: 11.0,
+            monospace_family: "Noto".to_string(),
+        };
+        config.show_ui_preferences = true;
+        save_config_to(&path, &config);
+
+        let loaded = load_config_from(&path).unwrap();
+        assert_eq!(loaded.font_prefs, config.font_prefs);
+        assert!(!loaded.show_ui_preferences);
+
+        // Sizes saved out of range are clamped on load.
+        config.font_prefs.label_size = 2.0;
+        save_config_to(&path, &config);
+        let loaded = load_config_from(&path).unwrap();
+        assert_eq!(loaded.font_prefs.label_size, MIN_RECEIVE_FONT_SIZE);
+        assert_eq!(loaded.font_prefs.receive_view_size, 24.0);
+    }
+
+    #[test]
+    fn test_font_preferences_reach_the_font_plugin() {
+        let mut config = PanelWidths::default();
+        config.font_prefs.label_size = 16.0;
+        let mut world = World::new();
+        world.insert_resource(config);
+        world.init_resource::<UiFontPreferences>();
+        let sync = world.register_system(sync_font_preferences);
+
+        world.run_system(sync).unwrap();
+        assert_eq!(world.resource::<UiFontPreferences>().label_size, 16.0);
+
+        // Unchanged preferences leave the resource untouched, so egui is not
+        // restyled every frame.
+        world.clear_trackers();
+        world.run_system(sync).unwrap();
+        assert!(!world.is_resource_changed::<UiFontPreferences>());
+    }
+
+    #[test]
+    fn test_startup_port_is_remembered() {
+        let mut config = PanelWidths::default();
+        let mut startup = StartupArgs {
+            port: Some("COM7".to_string()),
+            baud_rate: Some(9600),
+        };
+        remember_startup_port(&mut config, &mut startup);
+        assert_eq!(config.startup_port.as_deref(), Some("COM7"));
+        assert_eq!(config.startup_baud, Some(9600));
+
+        // The next launch without arguments opens it again.
+        let mut next = StartupArgs::default();
+        remember_startup_port(&mut config, &mut next);
+        assert_eq!(next, startup);
+
+        let mut forget = StartupArgs {
+            port: Some(String::new()),
+            baud_rate: None,
+        };
+        remember_startup_port(&mut config, &mut forget);
+        assert_eq!(forget, StartupArgs::default());
+        assert_eq!(config.startup_port, None);
+        assert_eq!(config.startup_baud, None);
+
+        // Forgetting it in the preferences has the same effect.
+        remember_startup_port(&mut config, &mut startup.clone());
+        config.forget_startup_port();
+        let mut next = StartupArgs::default();
+        remember_startup_port(&mut config, &mut next);
+        assert_eq!(next, StartupArgs::default());
+    }
+
+    #[test]
+    fn test_custom_baud_rates_are_offered_and_persisted() {
+        let mut config = PanelWidths::default();
+        config.add_custom_baud_rate(250_000);
+        config.add_custom_baud_rate(74_880);
+        config.add_custom_baud_rate(250_000);
+        config.add_custom_baud_rate(9600);
+        assert_eq!(config.custom_baud_rates, [74_880, 250_000]);
+
+        let options = config.baud_rate_options();
+        assert_eq!(options.len(), COMMON_BAUD_RATES.len() + 2);
+        assert!(options.is_sorted());
+        assert!(options.contains(&74_880));
+
+        let loaded: PanelWidths = ron::from_str(&ron::to_string(&config).unwrap()).unwrap();
+        assert_eq!(loaded.custom_baud_rates, [74_880, 250_000]);
+    }
+
+    #[test]
+    fn test_port_order_keeps_missing_ports() {
+        let mut config = PanelWidths::default();
+        config.set_port_order(vec!["COM2".to_string(), "COM1".to_string()]);
+        config.set_port_order(vec!["COM3".to_string(), "COM2".to_string()]);
+        assert_eq!(config.port_order, ["COM3", "COM2", "COM1"]);
+
+        let loaded: PanelWidths = ron::from_str(&ron::to_string(&config).unwrap()).unwrap();
+        assert_eq!(loaded.port_order, config.port_order);
+    }
+
+    #[test]
+    fn test_remembered_ignored_ports_are_applied() {
+        let mut traffic = TrafficLog::default();
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string(), "COM2".to_string()], 1);
+        let mut config = PanelWidths {
+            ignored_ports: vec!["COM1".to_string()],
+            ..PanelWidths::default()
+        };
+
+        // Not remembered: nothing is applied.
+        config.sync_ignored_ports(&mut serials, &mut traffic);
+        assert_eq!(serials.len(), 2);
+
+        let mut config: PanelWidths = ron::from_str(
+            &ron::to_string(&PanelWidths {
+                remember_ignored_ports: true,
+                ..config
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        config.sync_ignored_ports(&mut serials, &mut traffic);
+        assert_eq!(serials.port_names(), ["COM2"]);
+
+        serials.ignore("COM2", &mut traffic);
+        config.sync_ignored_ports(&mut serials, &mut traffic);
+        assert_eq!(config.ignored_ports, ["COM1", "COM2"]);
+
+        config.unignore_port(&mut serials, "COM1");
+        config.sync_ignored_ports(&mut serials, &mut traffic);
+        assert_eq!(config.ignored_ports, ["COM2"]);
+        assert_eq!(serials.ignored_ports(), ["COM2"]);
+
+        config.set_remember_ignored_ports(false);
+        assert!(config.ignored_ports.is_empty());
+    }
+
+    #[test]
+    fn test_locale_persists() {
+        let config = PanelWidths {
+            locale: Locale::ChineseSimplified,
+            ..Default::default()
+        };
+        let loaded: PanelWidths = ron::from_str(&ron::to_string(&config).unwrap()).unwrap();
+        assert_eq!(loaded.locale, Locale::ChineseSimplified);
+        assert_eq!(loaded.strings().open, "打开");
+
+        let config: PanelWidths = ron::from_str("(left_width: 160.0, right_width: 220.0)").unwrap();
+        assert_eq!(config.locale, Locale::English);
     }
 }
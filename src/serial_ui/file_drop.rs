@@ -0,0 +1,361 @@
+//! # File Drop Module
+//!
+//! Loading files dropped onto the window into the send input of the
+//! selected port.
+//!
+//! UTF-8 text files are loaded as they are. Other files are loaded as a hex
+//! string when the port's data type is Hex, and refused otherwise. When the
+//! data type is Intel HEX, the file must parse as Intel HEX records; sending
+//! it writes the binary image the records describe. Files larger than
+//! [`FileDrop::limit_bytes`] are held back until the user chooses to load
+//! them anyway or to send them to the port directly.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::window::FileDragAndDrop;
+use bevy_egui::{EguiContexts, egui};
+
+use crate::error::{Result, SerialBevyError};
+use crate::serial::Selected;
+use crate::serial::data_types::DataType;
+use crate::serial::encoding::intel_hex::decode_from_ihex;
+use crate::serial::port::Serial;
+use crate::serial::snapshot::lock_serial;
+use crate::serial::status::{SerialIo, SerialPortName};
+use crate::serial_ui::PanelWidths;
+use crate::serial_ui::palette::UiPalette;
+
+/// Default size above which a dropped file is not loaded without asking.
+pub const DEFAULT_FILE_DROP_LIMIT: u64 = 1024 * 1024;
+
+/// A dropped file above the size limit, waiting for the user's choice.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LargeFile {
+    /// Port the file was dropped on.
+    port_name: String,
+    /// Path of the file.
+    path: PathBuf,
+    /// Size of the file in bytes.
+    len: u64,
+}
+
+/// Dropped file state.
+#[derive(Resource, Debug)]
+pub struct FileDrop {
+    /// Files larger than this many bytes are not loaded without asking.
+    pub limit_bytes: u64,
+    /// Dropped file above the limit, if any.
+    pending: Option<LargeFile>,
+    /// Why the last dropped file was not loaded.
+    error: Option<String>,
+}
+
+impl Default for FileDrop {
+    fn default() -> Self {
+        Self {
+            limit_bytes: DEFAULT_FILE_DROP_LIMIT,
+            pending: None,
+            error: None,
+        }
+    }
+}
+
+impl FileDrop {
+    /// Handles a file dropped while `serial` is the selected port: loads it
+    /// into the send input, or holds it back if it is above the limit.
+    pub fn handle_drop(&mut self, serial: &mut Serial, path: &Path) {
+        self.pending = None;
+        self.error = None;
+        if !serial.is_open() {
+            self.error = Some("Open the port to load a dropped file".to_string());
+            return;
+        }
+
+        let result = fs::metadata(path)
+            .map_err(SerialBevyError::from)
+            .and_then(|metadata| {
+                if metadata.len() > self.limit_bytes {
+                    self.pending = Some(LargeFile {
+                        port_name: serial.set.port_name.clone(),
+                        path: path.to_path_buf(),
+                        len: metadata.len(),
+                    });
+                    Ok(())
+                } else {
+                    load_into_input(serial, path)
+                }
+            });
+        if let Err(e) = result {
+            self.error = Some(format!("{}: {e}", path.display()));
+        }
+    }
+
+    /// Returns why the last dropped file was not loaded.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns true if a file above the limit waits for the user's choice.
+    #[must_use]
+    pub const fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// Returns the send input text of a dropped file: the file itself if it is
+/// UTF-8 text, or its bytes as hex if `data_type` is Hex.
+///
+/// # Errors
+///
+/// Returns an error for a binary file while the data type is not Hex, and
+/// for a file that is not valid Intel HEX while the data type is Intel HEX.
+pub fn dropped_file_text(bytes: &[u8], data_type: DataType) -> Result<String> {
+    if data_type == DataType::IntelHex {
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| SerialBevyError::encoding("not an Intel HEX file"))?;
+        decode_from_ihex(text)?;
+        return Ok(text.to_string());
+    }
+    if !bytes.contains(&0)
+        && let Ok(text) = std::str::from_utf8(bytes)
+    {
+        return Ok(text.to_string());
+    }
+    if data_type == DataType::Hex {
+        let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+        return Ok(hex.join(" "));
+    }
+    Err(SerialBevyError::encoding(
+        "binary file; switch the data type to Hex to load it",
+    ))
+}
+
+/// Returns the bytes sent for a dropped file sent directly: the binary image
+/// of an Intel HEX file if `data_type` is Intel HEX, or the file as it is.
+///
+/// # Errors
+///
+/// Returns an error for a file that is not valid Intel HEX while the data
+/// type is Intel HEX.
+pub fn dropped_file_packet(bytes: Vec<u8>, data_type: DataType) -> Result<Vec<u8>> {
+    if data_type != DataType::IntelHex {
+        return Ok(bytes);
+    }
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|_| SerialBevyError::encoding("not an Intel HEX file"))?;
+    decode_from_ihex(text)
+}
+
+/// Replaces the send input of `serial` with the contents of the file at `path`.
+fn load_into_input(serial: &mut Serial, path: &Path) -> Result<()> {
+    let bytes = fs::read(path)?;
+    let text = dropped_file_text(&bytes, *serial.data().data_type())?;
+    *serial.data().get_cache_data().get_current_data() = text;
+    Ok(())
+}
+
+/// System: loads files dropped onto the window into the send input of the
+/// selected port.
+pub fn receive_dropped_files(
+    mut drops: MessageReader<FileDragAndDrop>,
+    ports: Query<&SerialIo>,
+    selected: Res<Selected>,
+    mut file_drop: ResMut<FileDrop>,
+) {
+    for drop in drops.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = drop else {
+            continue;
+        };
+        let port = selected.selected().and_then(|port| ports.get(port).ok());
+        match port {
+            Some(port) => file_drop.handle_drop(&mut lock_serial(&port.0), path_buf),
+            None => file_drop.error = Some("Select a port to load a dropped file".to_string()),
+        }
+    }
+}
+
+/// Choice made for a dropped file above the limit.
+enum LargeFileChoice {
+    Load,
+    Send,
+    Cancel,
+}
+
+/// System: draws the window asking what to do with a dropped file above the
+/// limit, or why a dropped file was not loaded.
+pub fn draw_file_drop_window(
+    mut contexts: EguiContexts,
+    ports: Query<(&SerialPortName, &SerialIo)>,
+    config: Res<PanelWidths>,
+    palette: Res<UiPalette>,
+    mut file_drop: ResMut<FileDrop>,
+) {
+    if file_drop.pending.is_none() && file_drop.error.is_none() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let strings = config.strings();
+    let mut choice = None;
+    egui::Window::new(strings.dropped_file)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            if let Some(file) = &file_drop.pending {
+                ui.label(format!(
+                    "{} is {} KB, above the {} KB limit for the send input.",
+                    file.path.display(),
+                    file.len.div_ceil(1024),
+                    file_drop.limit_bytes / 1024
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button(strings.load_anyway).clicked() {
+                        choice = Some(LargeFileChoice::Load);
+                    }
+                    if ui
+                        .button(strings.send_directly)
+                        .on_hover_text(strings.send_directly_hover)
+                        .clicked()
+                    {
+                        choice = Some(LargeFileChoice::Send);
+                    }
+                    if ui.button(strings.cancel).clicked() {
+                        choice = Some(LargeFileChoice::Cancel);
+                    }
+                });
+            } else if let Some(error) = &file_drop.error {
+                ui.colored_label(palette.error_text, error);
+                if ui.button(strings.ok).clicked() {
+                    choice = Some(LargeFileChoice::Cancel);
+                }
+            }
+        });
+
+    let Some(choice) = choice else {
+        return;
+    };
+    file_drop.error = None;
+    let Some(file) = file_drop.pending.take() else {
+        return;
+    };
+    let Some((_, io)) = ports.iter().find(|(name, _)| name.0 == file.port_name) else {
+        return;
+    };
+    let mut serial = lock_serial(&io.0);
+    let result = match choice {
+        LargeFileChoice::Load => load_into_input(&mut serial, &file.path),
+        LargeFileChoice::Send => fs::read(&file.path)
+            .map_err(SerialBevyError::from)
+            .and_then(|bytes| dropped_file_packet(bytes, *serial.data().data_type()))
+            .map(|packet| serial.data().send_packet(packet)),
+        LargeFileChoice::Cancel => Ok(()),
+    };
+    if let Err(e) = result {
+        file_drop.error = Some(format!("{}: {e}", file.path.display()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_port(data_type: DataType) -> Serial {
+        let mut serial = Serial::new();
+        serial.set.port_name = "COM1".to_string();
+        *serial.data().data_type() = data_type;
+        serial.open();
+        serial
+    }
+
+    #[test]
+    fn test_dropped_file_text() {
+        assert_eq!(
+            dropped_file_text("AT+GMR\r\n温度".as_bytes(), DataType::Utf8).unwrap(),
+            "AT+GMR\r\n温度"
+        );
+        assert_eq!(
+            dropped_file_text(&[0x00, 0xAB, 0x10], DataType::Hex).unwrap(),
+            "00 AB 10"
+        );
+        assert!(dropped_file_text(&[0xFF, 0xFE, 0x00], DataType::Utf8).is_err());
+        assert_eq!(dropped_file_text(b"", DataType::Hex).unwrap(), "");
+    }
+
+    #[test]
+    fn test_dropped_intel_hex_file() {
+        let hex = b":020000000102FB\r\n:00000001FF\r\n".to_vec();
+        assert_eq!(
+            dropped_file_text(&hex, DataType::IntelHex).unwrap(),
+            ":020000000102FB\r\n:00000001FF\r\n"
+        );
+        assert!(dropped_file_text(b":020000000102FC", DataType::IntelHex).is_err());
+        assert!(dropped_file_text(&[0x55, 0x00, 0xAA], DataType::IntelHex).is_err());
+
+        assert_eq!(
+            dropped_file_packet(hex.clone(), DataType::IntelHex).unwrap(),
+            [0x01, 0x02]
+        );
+        assert_eq!(
+            dropped_file_packet(hex.clone(), DataType::Utf8).unwrap(),
+            hex
+        );
+    }
+
+    #[test]
+    fn test_drop_loads_small_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("cmd.txt");
+        let binary = dir.path().join("fw.bin");
+        fs::write(&text, "AT\r\n").unwrap();
+        fs::write(&binary, [0x55, 0x00, 0xAA]).unwrap();
+
+        let mut file_drop = FileDrop::default();
+        let mut serial = open_port(DataType::Utf8);
+        file_drop.handle_drop(&mut serial, &text);
+        assert_eq!(file_drop.error(), None);
+        assert_eq!(serial.data().get_cache_data().get_current_data(), "AT\r\n");
+
+        file_drop.handle_drop(&mut serial, &binary);
+        assert!(file_drop.error().is_some());
+        assert_eq!(serial.data().get_cache_data().get_current_data(), "AT\r\n");
+
+        let mut serial = open_port(DataType::Hex);
+        file_drop.handle_drop(&mut serial, &binary);
+        assert_eq!(file_drop.error(), None);
+        assert_eq!(
+            serial.data().get_cache_data().get_current_data(),
+            "55 00 AA"
+        );
+
+        serial.close();
+        file_drop.handle_drop(&mut serial, &text);
+        assert!(file_drop.error().is_some());
+    }
+
+    #[test]
+    fn test_drop_holds_back_large_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.txt");
+        fs::write(&path, "x".repeat(2048)).unwrap();
+
+        let mut file_drop = FileDrop {
+            limit_bytes: 1024,
+            ..FileDrop::default()
+        };
+        let mut serial = open_port(DataType::Utf8);
+        file_drop.handle_drop(&mut serial, &path);
+        assert!(file_drop.is_pending());
+        assert_eq!(file_drop.error(), None);
+        assert!(serial.data().get_cache_data().get_current_data().is_empty());
+
+        file_drop.handle_drop(&mut serial, &dir.path().join("missing.txt"));
+        assert!(!file_drop.is_pending());
+        assert!(file_drop.error().is_some());
+    }
+}
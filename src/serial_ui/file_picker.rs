@@ -0,0 +1,215 @@
+//! # File Picker Module
+//!
+//! A file picker drawn as an egui window, listing the folders and files of a
+//! directory to choose a file from, such as a session log to replay. The
+//! directory is read when it is opened, not every frame.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy_egui::egui;
+
+use super::i18n::UiStrings;
+use super::palette::UiPalette;
+
+/// One entry of the listed directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PickerEntry {
+    /// File or folder name.
+    pub name: String,
+    /// Whether it is a folder.
+    pub is_dir: bool,
+}
+
+/// What the user did in the picker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PickerAction {
+    /// Chose the file at the path.
+    Picked(PathBuf),
+    /// Closed the picker without choosing a file.
+    Cancelled,
+}
+
+/// Directory listed by a file picker, and its entries.
+#[derive(Clone, Debug)]
+pub struct FilePicker {
+    /// Directory listed.
+    dir: PathBuf,
+    /// Folders, then files, of `dir`, each by name.
+    entries: Vec<PickerEntry>,
+    /// Why `dir` could not be listed.
+    error: Option<String>,
+    /// Extensions of the files listed, lowercase; all files if empty.
+    extensions: &'static [&'static str],
+}
+
+impl FilePicker {
+    /// Creates a picker listing `dir`, showing only the files with one of
+    /// `extensions`, or all files if it is empty.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>, extensions: &'static [&'static str]) -> Self {
+        let mut picker = Self {
+            dir: PathBuf::new(),
+            entries: Vec::new(),
+            error: None,
+            extensions,
+        };
+        picker.open_dir(dir.into());
+        picker
+    }
+
+    /// Returns the directory listed.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the entries of the directory, folders first.
+    #[must_use]
+    pub fn entries(&self) -> &[PickerEntry] {
+        &self.entries
+    }
+
+    /// Returns why the directory could not be listed, if it could not.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Lists `dir`, made absolute so its parents can be reached.
+    pub fn open_dir(&mut self, dir: PathBuf) {
+        self.dir = fs::canonicalize(&dir).unwrap_or(dir);
+        self.entries.clear();
+        self.error = None;
+        let read = match fs::read_dir(&self.dir) {
+            Ok(read) => read,
+            Err(e) => {
+                self.error = Some(format!("{}: {e}", self.dir.display()));
+                return;
+            }
+        };
+        for entry in read.flatten() {
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_dir || self.matches(&name) {
+                self.entries.push(PickerEntry { name, is_dir });
+            }
+        }
+        self.entries
+            .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    /// Returns true if a file named `name` is listed.
+    fn matches(&self, name: &str) -> bool {
+        self.extensions.is_empty()
+            || Path::new(name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| self.extensions.contains(&ext.to_lowercase().as_str()))
+    }
+
+    /// Draws the picker window with the id `id`, and returns the file chosen
+    /// or the picker closed this frame.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        id: egui::Id,
+        strings: &UiStrings,
+        palette: &UiPalette,
+    ) -> Option<PickerAction> {
+        let mut action = None;
+        let mut open = true;
+        let mut next_dir = None;
+        egui::Window::new(strings.choose_file_title)
+            .id(id)
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([420.0, 320.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let parent = self.dir.parent();
+                    if ui
+                        .add_enabled(parent.is_some(), egui::Button::new(".."))
+                        .on_hover_text(strings.parent_folder)
+                        .clicked()
+                    {
+                        next_dir = parent.map(Path::to_path_buf);
+                    }
+                    ui.label(egui::RichText::new(self.dir.display().to_string()).monospace());
+                });
+                ui.separator();
+                if let Some(error) = &self.error {
+                    ui.colored_label(palette.error_text, error);
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for entry in &self.entries {
+                            let label = if entry.is_dir {
+                                format!("{}/", entry.name)
+                            } else {
+                                entry.name.clone()
+                            };
+                            if ui.add(egui::Button::selectable(false, label)).clicked() {
+                                let path = self.dir.join(&entry.name);
+                                if entry.is_dir {
+                                    next_dir = Some(path);
+                                } else {
+                                    action = Some(PickerAction::Picked(path));
+                                }
+                            }
+                        }
+                        if self.entries.is_empty() && self.error.is_none() {
+                            ui.label(egui::RichText::new(strings.no_matching_files).weak());
+                        }
+                    });
+                ui.separator();
+                if ui.button(strings.cancel).clicked() {
+                    action = Some(PickerAction::Cancelled);
+                }
+            });
+        if let Some(dir) = next_dir {
+            self.open_dir(dir);
+        }
+        if !open {
+            action = Some(PickerAction::Cancelled);
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lists_folders_then_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("old")).unwrap();
+        fs::write(dir.path().join("b.txt"), "").unwrap();
+        fs::write(dir.path().join("a.TXT"), "").unwrap();
+        fs::write(dir.path().join("c.bin"), "").unwrap();
+
+        let picker = FilePicker::new(dir.path(), &["txt"]);
+        let names: Vec<(&str, bool)> = picker
+            .entries()
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.is_dir))
+            .collect();
+        assert_eq!(names, [("old", true), ("a.TXT", false), ("b.txt", false)]);
+        assert!(picker.dir().is_absolute());
+        assert_eq!(picker.error(), None);
+
+        let all = FilePicker::new(dir.path(), &[]);
+        assert_eq!(all.entries().len(), 4);
+    }
+
+    #[test]
+    fn test_missing_directory_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let picker = FilePicker::new(dir.path().join("missing"), &[]);
+        assert!(picker.entries().is_empty());
+        assert!(picker.error().is_some());
+    }
+}
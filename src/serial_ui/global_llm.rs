@@ -1,9 +1,15 @@
+use std::sync::{Arc, Mutex};
+
 use bevy::prelude::*;
+use tokio::task::JoinHandle;
 
 use crate::serial::ai::send_ai_chat;
 use crate::serial::data::AiResponse;
 use crate::serial::discovery::Runtime;
-use crate::serial::llm::LlmMessage;
+use crate::serial::llm::{
+    DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, LlmMessage, LlmProvider, chat_endpoint,
+    chat_request_body,
+};
 
 use super::config::PanelWidths;
 
@@ -20,6 +26,44 @@ pub struct GlobalLlmState {
     pub is_processing: bool,
     /// Global LLM request already dispatched (prevents duplicate spawns).
     pub request_in_flight: bool,
+    /// Response text streamed so far by the request in flight.
+    pub streaming_response: Arc<Mutex<String>>,
+    /// Task of the request in flight, aborted by [`GlobalLlmState::stop`].
+    pub request_task: Option<JoinHandle<()>>,
+}
+
+impl GlobalLlmState {
+    /// Returns the response text streamed so far.
+    #[must_use]
+    pub fn streaming_text(&self) -> String {
+        self.streaming_response
+            .lock()
+            .map(|text| text.clone())
+            .unwrap_or_default()
+    }
+
+    /// Marks the request in flight as finished and clears the streamed response.
+    fn finish_request(&mut self) {
+        self.is_processing = false;
+        self.request_in_flight = false;
+        self.request_task = None;
+        if let Ok(mut text) = self.streaming_response.lock() {
+            text.clear();
+        }
+    }
+
+    /// Aborts the request in flight, keeping the text streamed so far as the
+    /// assistant's answer.
+    pub fn stop(&mut self) {
+        if let Some(task) = self.request_task.take() {
+            task.abort();
+        }
+        let partial = self.streaming_text();
+        if !partial.is_empty() {
+            self.messages.push(LlmMessage::assistant(partial));
+        }
+        self.finish_request();
+    }
 }
 
 /// A dedicated channel for global LLM AI responses, completely separate from
@@ -64,9 +108,19 @@ pub fn process_global_llm_requests(
 
     state.request_in_flight = true;
 
-    let model = panel_widths.llm_model.clone();
-    let key = panel_widths.llm_key.clone();
-    let with_coding_plan = panel_widths.llm_with_coding_plan;
+    // The global panel has no per-port settings and uses the shared endpoint.
+    let url = chat_endpoint(
+        &panel_widths.llm_base_url,
+        panel_widths.llm_with_coding_plan,
+    )
+    .to_string();
+    let headers = LlmProvider::default().request_headers(&panel_widths.llm_key);
+    let body = chat_request_body(
+        &panel_widths.llm_model,
+        &messages,
+        DEFAULT_TEMPERATURE,
+        DEFAULT_MAX_TOKENS,
+    );
 
     let tx = global_response
         .tx
@@ -74,8 +128,12 @@ pub fn process_global_llm_requests(
         .expect("GlobalLlmResponse tx poisoned")
         .clone();
 
-    runtime.spawn(async move {
-        let result = send_ai_chat(&model, key, with_coding_plan, messages).await;
+    let partial = Arc::clone(&state.streaming_response);
+    if let Ok(mut text) = partial.lock() {
+        text.clear();
+    }
+    state.request_task = Some(runtime.spawn(async move {
+        let result = send_ai_chat(url, headers, body, partial).await;
         let (content, is_error) = match result {
             Ok(c) => (c, false),
             Err(c) => (c, true),
@@ -85,7 +143,7 @@ pub fn process_global_llm_requests(
             content,
             is_error,
         });
-    });
+    }));
 }
 
 /// Receives completed global LLM responses and appends them into runtime state.
@@ -99,8 +157,11 @@ pub fn receive_global_llm_responses(
         .expect("GlobalLlmResponse rx poisoned")
         .try_recv()
     {
-        global_state.is_processing = false;
-        global_state.request_in_flight = false;
+        // Responses of stopped requests are dropped.
+        if !global_state.request_in_flight {
+            continue;
+        }
+        global_state.finish_request();
 
         if response.is_error {
             global_state.messages.push(LlmMessage::assistant(format!(
@@ -0,0 +1,1603 @@
+//! # Localization Module
+//!
+//! UI languages and the translated strings of the main window: the top bar,
+//! the settings side panel, the input toolbar and the status bar.
+//!
+//! The selected [`Locale`] is part of the persisted UI configuration; drawing
+//! code looks its strings up with [`strings`].
+
+use serde::{Deserialize, Serialize};
+
+/// Language of the UI.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    /// English.
+    #[default]
+    English,
+    /// Simplified Chinese.
+    ChineseSimplified,
+}
+
+impl Locale {
+    /// All languages, in display order.
+    pub const ALL: [Self; 2] = [Self::English, Self::ChineseSimplified];
+
+    /// Returns the name of the language in that language.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::ChineseSimplified => "简体中文",
+        }
+    }
+}
+
+/// Translated UI strings.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UiStrings {
+    /// Title of the connection section.
+    pub connection: &'static str,
+    /// Title of the serial settings section.
+    pub serial_settings: &'static str,
+    /// Title of the LLM settings section.
+    pub llm_settings: &'static str,
+    /// Hover text of the language selector.
+    pub language: &'static str,
+    /// Port selector label.
+    pub port: &'static str,
+    /// Port selector text while no port is selected.
+    pub select_port: &'static str,
+    /// Open port button.
+    pub open: &'static str,
+    /// Close port button.
+    pub close: &'static str,
+    /// Port list label.
+    pub ports: &'static str,
+    /// Shown while a port scan is in flight.
+    pub scanning: &'static str,
+    /// Port refresh button.
+    pub refresh: &'static str,
+    /// Hover text of the port refresh button.
+    pub refresh_hover: &'static str,
+    /// Selected port label.
+    pub selected: &'static str,
+    /// Selected port text while no port is selected.
+    pub no_port_selected: &'static str,
+    /// Serial settings placeholder while no port is selected.
+    pub select_port_for_settings: &'static str,
+    /// Port alias label.
+    pub alias: &'static str,
+    /// Port color label.
+    pub color: &'static str,
+    /// Port label reset button.
+    pub reset: &'static str,
+    /// Baud rate label.
+    pub baud_rate: &'static str,
+    /// Hover text of the baud rates.
+    pub baud_rate_hover: &'static str,
+    /// Baud rate option revealing a field for any rate.
+    pub custom_baud_rate: &'static str,
+    /// Hint of the custom baud rate field.
+    pub custom_baud_rate_hint: &'static str,
+    /// Shown when the custom baud rate is not a number above zero.
+    pub invalid_baud_rate: &'static str,
+    /// Shown when the custom baud rate is unusually high.
+    pub high_baud_rate: &'static str,
+    /// Data bits label.
+    pub data_bits: &'static str,
+    /// Stop bits label.
+    pub stop_bits: &'static str,
+    /// Parity label.
+    pub parity: &'static str,
+    /// Flow control label.
+    pub flow_control: &'static str,
+    /// Timeout label.
+    pub timeout: &'static str,
+    /// Receive buffer label.
+    pub rx_buffer: &'static str,
+    /// Hover text of the receive buffer.
+    pub rx_buffer_hover: &'static str,
+    /// Receive timeout label.
+    pub rx_timeout: &'static str,
+    /// Hover text of the receive timeout.
+    pub rx_timeout_hover: &'static str,
+    /// Receive timeout option that disables it.
+    pub off: &'static str,
+    /// Timeout action label.
+    pub on_timeout: &'static str,
+    /// Send rate label.
+    pub tx_rate: &'static str,
+    /// Hover text of the send rate checkbox.
+    pub tx_rate_hover: &'static str,
+    /// Hover text of the send rate slider.
+    pub tx_rate_reopen: &'static str,
+    /// Send rate text while unlimited.
+    pub no_limit: &'static str,
+    /// Frame gap label.
+    pub frame_gap: &'static str,
+    /// Hover text of the frame gap checkbox.
+    pub frame_gap_hover: &'static str,
+    /// Modem status lines label.
+    pub signals: &'static str,
+    /// Break label.
+    pub line_break: &'static str,
+    /// Button sending a break.
+    pub send_break: &'static str,
+    /// Hover text of the break button.
+    pub send_break_hover: &'static str,
+    /// Latency label.
+    pub latency: &'static str,
+    /// Latency measurement button.
+    pub measure_latency: &'static str,
+    /// Hover text of the latency measurement button.
+    pub measure_latency_hover: &'static str,
+    /// Failed latency measurement.
+    pub failed: &'static str,
+    /// Throughput label.
+    pub throughput: &'static str,
+    /// Throughput test button and dialog title.
+    pub throughput_test: &'static str,
+    /// Hover text of the throughput test button.
+    pub throughput_hover: &'static str,
+    /// LLM model label.
+    pub model: &'static str,
+    /// LLM API key label.
+    pub api_key: &'static str,
+    /// Remember key checkbox.
+    pub remember_key: &'static str,
+    /// Hover text of the remember key checkbox.
+    pub save_key_hover: &'static str,
+    /// Title of the window asking for the API key on the first run.
+    pub llm_key_prompt_title: &'static str,
+    /// Text of the window asking for the API key on the first run.
+    pub llm_key_prompt: &'static str,
+    /// Button closing the API key window without a key.
+    pub llm_key_prompt_later: &'static str,
+    /// LLM endpoint label.
+    pub base_url: &'static str,
+    /// Hover text of the global LLM endpoint.
+    pub base_url_hover: &'static str,
+    /// Coding plan label.
+    pub coding: &'static str,
+    /// Coding plan button while enabled.
+    pub coding_on: &'static str,
+    /// Coding plan button while disabled.
+    pub coding_off: &'static str,
+    /// Hover text of the coding plan button.
+    pub coding_hover: &'static str,
+    /// Settings panel toggle.
+    pub settings: &'static str,
+    /// Templates panel toggle.
+    pub templates: &'static str,
+    /// Hover text of the templates panel toggle.
+    pub templates_hover: &'static str,
+    /// LLM panel toggle.
+    pub llm: &'static str,
+    /// Data type selector label.
+    pub data_type: &'static str,
+    /// Line ending selector entry appending nothing.
+    pub no_line_ending: &'static str,
+    /// Hover text of the line ending selector.
+    pub line_ending_hover: &'static str,
+    /// Escape sequence button while parsing them.
+    pub escapes_on: &'static str,
+    /// Hover text of the escape sequence button while parsing them.
+    pub escapes_on_hover: &'static str,
+    /// Escape sequence button while not parsing them.
+    pub escapes_off: &'static str,
+    /// Hover text of the escape sequence button while not parsing them.
+    pub escapes_off_hover: &'static str,
+    /// GBK auto-detection checkbox.
+    pub auto_gbk: &'static str,
+    /// Hover text of the GBK auto-detection checkbox.
+    pub auto_gbk_hover: &'static str,
+    /// Hover text of the GBK auto-detection checkbox once GBK is detected.
+    pub gbk_detected_hover: &'static str,
+    /// NMEA button while parsing sentences.
+    pub nmea_on: &'static str,
+    /// Hover text of the NMEA button while parsing sentences.
+    pub nmea_on_hover: &'static str,
+    /// NMEA button while not parsing sentences.
+    pub nmea_off: &'static str,
+    /// Hover text of the NMEA button while not parsing sentences.
+    pub nmea_off_hover: &'static str,
+    /// Timestamp menu button while timestamps are off.
+    pub time_off: &'static str,
+    /// Timestamp menu button, followed by the mode.
+    pub time: &'static str,
+    /// Title of the receive view timestamp modes.
+    pub receive_view: &'static str,
+    /// Label of the timestamp format override.
+    pub time_format: &'static str,
+    /// Hover text of the timestamp format override.
+    pub time_format_hover: &'static str,
+    /// Log file timestamps checkbox.
+    pub log_timestamps: &'static str,
+    /// Heading of the local echo options.
+    pub local_echo: &'static str,
+    /// Hover text of the local echo options.
+    pub local_echo_hover: &'static str,
+    /// Local echo option showing sent data.
+    pub echo_always: &'static str,
+    /// Local echo option hiding sent data.
+    pub echo_never: &'static str,
+    /// Local echo option hiding received data that repeats sent data.
+    pub echo_dedupe: &'static str,
+    /// Label of the echo window field.
+    pub echo_window: &'static str,
+    /// Label of the response timing options, and of the response time
+    /// statistics in the status bar.
+    pub response_time: &'static str,
+    /// Hover text of the response timing options.
+    pub response_time_hover: &'static str,
+    /// Response timing option that turns it off.
+    pub response_off: &'static str,
+    /// Response timing option matching replies to the most recent send.
+    pub response_most_recent: &'static str,
+    /// Response timing option matching replies to sends in order.
+    pub response_fifo: &'static str,
+    /// Hover text of the response time statistics, followed by their count.
+    pub response_stats_hover: &'static str,
+    /// Console mode button while enabled.
+    pub console_on: &'static str,
+    /// Hover text of the console mode button while enabled.
+    pub console_on_hover: &'static str,
+    /// Console mode button while disabled.
+    pub console_off: &'static str,
+    /// Hover text of the console mode button while disabled.
+    pub console_off_hover: &'static str,
+    /// Send sequence window toggle.
+    pub sequence: &'static str,
+    /// Hover text of the send sequence window toggle.
+    pub sequence_hover: &'static str,
+    /// Clear display button.
+    pub clear_display: &'static str,
+    /// Hover text of the clear display button.
+    pub clear_display_hover: &'static str,
+    /// Clear log file button.
+    pub clear_log_file: &'static str,
+    /// Hover text of the clear log file button.
+    pub clear_log_file_hover: &'static str,
+    /// Question confirming the clear log file button.
+    pub clear_log_confirm: &'static str,
+    /// Notification of a log file that could not be cleared, before the error.
+    pub clear_log_failed: &'static str,
+    /// Raw recording start button.
+    pub record_raw: &'static str,
+    /// Hover text of the raw recording start button.
+    pub record_raw_hover: &'static str,
+    /// Raw recording stop button.
+    pub stop: &'static str,
+    /// Hover text of the raw recording stop button, followed by the file path.
+    pub stop_recording_to: &'static str,
+    /// Session recording start button.
+    pub record_session: &'static str,
+    /// Hover text of the session recording start button.
+    pub record_session_hover: &'static str,
+    /// Hover text of the session recording stop button, followed by the file
+    /// path.
+    pub stop_session_recording_to: &'static str,
+    /// Indicator of a raw recording in progress.
+    pub recording_indicator: &'static str,
+    /// Indicator of a session recording in progress.
+    pub session_recording_indicator: &'static str,
+    /// Log replay menu button.
+    pub replay_log: &'static str,
+    /// Hover text of the log replay menu button.
+    pub replay_log_hover: &'static str,
+    /// Replay speed label.
+    pub replay_speed: &'static str,
+    /// Hover text of the replay speed.
+    pub replay_speed_hover: &'static str,
+    /// Replay start button.
+    pub replay_start: &'static str,
+    /// Label of a replay that could not be started or failed; hovering it
+    /// shows why.
+    pub replay_failed: &'static str,
+    /// Button opening the file picker.
+    pub choose_file: &'static str,
+    /// Label shown while no file was chosen.
+    pub no_file_chosen: &'static str,
+    /// Title of the file picker window.
+    pub choose_file_title: &'static str,
+    /// Hover text of the file picker button opening the parent folder.
+    pub parent_folder: &'static str,
+    /// Shown by the file picker in a folder without matching files.
+    pub no_matching_files: &'static str,
+    /// Port list menu button opening a session recording as a virtual port.
+    pub open_replay: &'static str,
+    /// Hover text of the open replay menu button.
+    pub open_replay_hover: &'static str,
+    /// Hover text of the smaller receive font button.
+    pub font_smaller: &'static str,
+    /// Hover text of the larger receive font button.
+    pub font_larger: &'static str,
+    /// Title and hover text of the UI preferences window and its button.
+    pub ui_preferences: &'static str,
+    /// Receive view font size preference.
+    pub receive_view_size: &'static str,
+    /// Label and button font size preference.
+    pub label_size: &'static str,
+    /// Send input font size preference.
+    pub input_size: &'static str,
+    /// Monospace font preference.
+    pub monospace_family: &'static str,
+    /// Monospace font option keeping egui's font.
+    pub default_font: &'static str,
+    /// Maximum displayed data preference.
+    pub max_display_size: &'static str,
+    /// Hover text of the maximum displayed data preference.
+    pub max_display_size_hover: &'static str,
+    /// Startup port preference.
+    pub startup_port: &'static str,
+    /// Hover text of the startup port preference.
+    pub startup_port_hover: &'static str,
+    /// Value of the startup port preference while none is remembered.
+    pub no_startup_port: &'static str,
+    /// Button forgetting the startup port.
+    pub forget_startup_port: &'static str,
+    /// Hint of the send input.
+    pub input_hint: &'static str,
+    /// Send button.
+    pub send: &'static str,
+    /// Clear input button.
+    pub clear: &'static str,
+    /// Previous history entry button.
+    pub prev: &'static str,
+    /// Next history entry button.
+    pub next: &'static str,
+    /// Strict hex checkbox.
+    pub strict_hex: &'static str,
+    /// Hover text of the strict hex checkbox.
+    pub strict_hex_hover: &'static str,
+    /// Hex auto-spacing checkbox.
+    pub hex_auto_space: &'static str,
+    /// Hover text of the hex auto-spacing checkbox.
+    pub hex_auto_space_hover: &'static str,
+    /// Repeat send checkbox.
+    pub repeat: &'static str,
+    /// Hover text of the repeat send checkbox.
+    pub repeat_hover: &'static str,
+    /// Hover text of the repeat count.
+    pub repeat_count_hover: &'static str,
+    /// Hover text of the interval between repeats.
+    pub repeat_interval_hover: &'static str,
+    /// Shown under the send input while the port is closed.
+    pub open_port_to_send: &'static str,
+    /// Keys of the history search, after its prompt.
+    pub search_help: &'static str,
+    /// Send preview of invalid hex input.
+    pub preview_refused: &'static str,
+    /// Hover text of the send preview.
+    pub preview_hover: &'static str,
+    /// Status bar label of the last transfer.
+    pub last_activity: &'static str,
+    /// Hover text of the log path in the status bar.
+    pub open_log_folder: &'static str,
+    /// Status bar alert of a port that received nothing for too long.
+    pub receive_timeout: &'static str,
+    /// Warning of a port whose device holds CTS low while data is sent.
+    pub cts_low_paused: &'static str,
+    /// Warning of a port whose device sent XOFF while data is sent.
+    pub xoff_paused: &'static str,
+    /// Title of a port's error window, after its name.
+    pub error: &'static str,
+    /// Error window button reopening the port.
+    pub retry_open: &'static str,
+    /// Hover text of the retry button.
+    pub retry_open_hover: &'static str,
+    /// Hover text of the error window's close button.
+    pub close_error_hover: &'static str,
+    /// Heading of the recent error log lines in the error window.
+    pub recent_errors: &'static str,
+    /// Port context menu entry removing the port from the list.
+    pub ignore_port: &'static str,
+    /// Hover text of the ignore port entry.
+    pub ignore_port_hover: &'static str,
+    /// Title of the ignored ports section.
+    pub ignored_ports: &'static str,
+    /// Button letting discovery add an ignored port again.
+    pub unignore_port: &'static str,
+    /// Hover text of the unignore button.
+    pub unignore_port_hover: &'static str,
+    /// Checkbox keeping the ignored ports ignored on the next launch.
+    pub remember_ignored_ports: &'static str,
+    /// Heatmap view toggle.
+    pub heatmap: &'static str,
+    /// Hover text of the heatmap view toggle.
+    pub heatmap_hover: &'static str,
+    /// Throughput test packet size label.
+    pub packet_size: &'static str,
+    /// Throughput test packet count label.
+    pub packets: &'static str,
+    /// Throughput test start button.
+    pub start: &'static str,
+    /// Hover text of the disabled throughput test start button.
+    pub open_port_to_test: &'static str,
+    /// Throughput test dropped packet count label.
+    pub dropped_packets: &'static str,
+    /// Title of the dropped file window.
+    pub dropped_file: &'static str,
+    /// Loads a large dropped file into the send input.
+    pub load_anyway: &'static str,
+    /// Sends a large dropped file to the port.
+    pub send_directly: &'static str,
+    /// Hover text of the send directly button.
+    pub send_directly_hover: &'static str,
+    /// Cancel button.
+    pub cancel: &'static str,
+    /// Confirmation button.
+    pub ok: &'static str,
+    /// Button resuming the scroll-locked receive view.
+    pub latest: &'static str,
+    /// Hover text of the latest button.
+    pub latest_hover: &'static str,
+    /// Start of the banner over a truncated receive view, before the size
+    /// of the data not shown.
+    pub display_truncated: &'static str,
+    /// End of the banner over a truncated receive view.
+    pub display_truncated_load: &'static str,
+    /// Button showing the previous receive view page.
+    pub previous_page: &'static str,
+    /// Button showing the next receive view page.
+    pub next_page: &'static str,
+    /// Hover text of the receive view page indicator.
+    pub page_hover: &'static str,
+    /// Receive view menu item copying all shown text.
+    pub copy_all: &'static str,
+    /// Receive view menu item copying the selected text.
+    pub copy_selected: &'static str,
+    /// Receive view menu item copying the raw bytes of the selection as hex.
+    pub copy_as_hex: &'static str,
+    /// Receive view menu item saving the selected text to a file.
+    pub save_selected: &'static str,
+    /// Hover text of the save selection menu item.
+    pub save_selected_hover: &'static str,
+    /// Label of the statistics row.
+    pub statistics: &'static str,
+    /// Button opening the session history window.
+    pub history: &'static str,
+    /// Hover text of the history button.
+    pub history_hover: &'static str,
+    /// Session history column of the opening time.
+    pub opened_at: &'static str,
+    /// Session history column of the closing time.
+    pub closed_at: &'static str,
+    /// Session history column of the session length.
+    pub duration: &'static str,
+    /// Session history column of the bytes received.
+    pub rx_bytes_header: &'static str,
+    /// Session history column of the bytes sent.
+    pub tx_bytes_header: &'static str,
+    /// Session history column of the error count.
+    pub errors: &'static str,
+    /// Closing time of a session still open.
+    pub still_open: &'static str,
+    /// Session history row adding up all sessions.
+    pub lifetime: &'static str,
+    /// Button resetting the current session's counters.
+    pub reset_stats: &'static str,
+    /// Hover text of the reset counters button.
+    pub reset_stats_hover: &'static str,
+    /// High-contrast colors toggle.
+    pub high_contrast: &'static str,
+    /// Hover text of the high-contrast colors toggle.
+    pub high_contrast_hover: &'static str,
+    /// Title of the keyboard shortcuts help.
+    pub keyboard_shortcuts: &'static str,
+    /// Shortcut description: open or close the selected port.
+    pub shortcut_toggle_port: &'static str,
+    /// Shortcut description: close the selected port.
+    pub shortcut_close_port: &'static str,
+    /// Shortcut description: select the next port.
+    pub shortcut_next_port: &'static str,
+    /// Shortcut description: select the previous port.
+    pub shortcut_previous_port: &'static str,
+    /// Shortcut description: select a port by its position.
+    pub shortcut_select_port: &'static str,
+    /// Shortcut description: continue the log in a new file.
+    pub shortcut_rotate_log: &'static str,
+    /// Shortcut description: search the receive view.
+    pub shortcut_search: &'static str,
+    /// Shortcut description: search the send history.
+    pub shortcut_history_search: &'static str,
+    /// Label of the receive view search bar.
+    pub find: &'static str,
+    /// Hint text of the receive view search field.
+    pub find_hint: &'static str,
+    /// Hover text of the button closing the search bar.
+    pub close_search: &'static str,
+    /// Placeholder of an empty receive view, after the port name.
+    pub receive_window: &'static str,
+    /// Split view toggle.
+    pub split_view: &'static str,
+    /// Hover text of the split view toggle.
+    pub split_view_hover: &'static str,
+    /// Hover text of the split view toggle while fewer than two ports are open.
+    pub split_view_disabled_hover: &'static str,
+    /// Unit of the split view pane count, after the count.
+    pub split_panes: &'static str,
+    /// Merged view toggle.
+    pub merged_view: &'static str,
+    /// Hover text of the merged view toggle.
+    pub merged_view_hover: &'static str,
+    /// Hover text of the merged view clear button.
+    pub merged_clear_hover: &'static str,
+    /// Unit of the merged view entry count, after the count.
+    pub merged_entries: &'static str,
+    /// Placeholder of an empty merged view.
+    pub merged_placeholder: &'static str,
+    /// Hint text of the standalone LLM input.
+    pub ask_ai_hint: &'static str,
+    /// Hover text of the button stopping an LLM answer.
+    pub stop_generating_hover: &'static str,
+    /// Shown while an LLM answer is generated.
+    pub waiting_for_response: &'static str,
+    /// Shown below the LLM input while no key or model is set.
+    pub llm_not_configured: &'static str,
+    /// Button clearing a port's LLM conversation.
+    pub clear_llm_history: &'static str,
+    /// Hover text of the button clearing a port's LLM conversation.
+    pub clear_llm_history_hover: &'static str,
+    /// Title of the LLM panel while no port is selected.
+    pub llm_standalone: &'static str,
+    /// Hover text of the button clearing the standalone LLM conversation.
+    pub clear_conversation_hover: &'static str,
+    /// Title of the popup asking for the LLM key and model.
+    pub llm_config_required: &'static str,
+    /// Text of the popup asking for the LLM key and model.
+    pub llm_config_required_text: &'static str,
+    /// Shown in the templates panel while no port is selected.
+    pub templates_select_port: &'static str,
+    /// Name of a template, sequence or field.
+    pub name: &'static str,
+    /// Hover text of the button removing a template.
+    pub remove_template: &'static str,
+    /// Hover text of a template send button that is disabled.
+    pub template_send_disabled_hover: &'static str,
+    /// Shown for a template without fields.
+    pub no_fields: &'static str,
+    /// Header of the template field size column.
+    pub field_bytes: &'static str,
+    /// Header of the template field value column.
+    pub field_value: &'static str,
+    /// Header of the template field byte order column.
+    pub field_order: &'static str,
+    /// Hover text of the button removing a template field.
+    pub remove_field: &'static str,
+    /// Title of the send sequence window.
+    pub sequence_title: &'static str,
+    /// Shown in the sequence window while no port is selected.
+    pub sequence_select_port: &'static str,
+    /// Shown for a sequence without steps.
+    pub no_steps: &'static str,
+    /// Hover text of the button removing a sequence step.
+    pub remove_step: &'static str,
+    /// Sequence step waiting for a time.
+    pub step_wait: &'static str,
+    /// Sequence step waiting for received text.
+    pub step_wait_for: &'static str,
+    /// Timeout of a sequence step waiting for received text.
+    pub step_timeout: &'static str,
+    /// Label of a file path field.
+    pub file: &'static str,
+    /// Button loading a file.
+    pub load: &'static str,
+    /// Button saving a file.
+    pub save: &'static str,
+    /// Shown after a file was loaded, before its path.
+    pub loaded: &'static str,
+    /// Shown when a file could not be loaded, before its path.
+    pub load_failed: &'static str,
+    /// Shown after a file was saved, before its path.
+    pub saved: &'static str,
+    /// Shown when a file could not be saved, before its path.
+    pub save_failed: &'static str,
+    /// Button running a sequence.
+    pub run: &'static str,
+    /// Button aborting a running sequence.
+    pub abort: &'static str,
+    /// Hover text of the sequence run button while it is disabled.
+    pub sequence_run_disabled_hover: &'static str,
+    /// Status of a sequence that ran to its end.
+    pub sequence_passed: &'static str,
+    /// Status of an aborted sequence.
+    pub sequence_aborted: &'static str,
+    /// Prompt of the send history search.
+    pub history_search_prompt: &'static str,
+    /// Prompt of the send history search while nothing matches.
+    pub history_search_failing: &'static str,
+    /// Button adding a field to a template.
+    pub add_field: &'static str,
+    /// Button adding a template.
+    pub add_template: &'static str,
+    /// Header of the per-port LLM request settings.
+    pub llm_request_settings: &'static str,
+    /// LLM provider selector.
+    pub llm_provider: &'static str,
+    /// Model field of the local Ollama provider.
+    pub llm_local_model: &'static str,
+    /// Hint text of the local model field.
+    pub llm_local_model_hint: &'static str,
+    /// Hover text of the local model field.
+    pub llm_local_model_hover: &'static str,
+    /// Hover text of the per-port LLM endpoint field.
+    pub llm_endpoint_hover: &'static str,
+    /// LLM temperature slider.
+    pub llm_temperature: &'static str,
+    /// LLM maximum answer tokens field.
+    pub llm_max_tokens: &'static str,
+    /// LLM history budget field.
+    pub llm_history_chars: &'static str,
+    /// Hover text of the LLM history budget field.
+    pub llm_history_chars_hover: &'static str,
+    /// LLM received data context size field.
+    pub llm_context_bytes: &'static str,
+    /// Hover text of the LLM received data context size field.
+    pub llm_context_bytes_hover: &'static str,
+    /// Offset of the heatmap cell clicked.
+    pub heatmap_offset: &'static str,
+    /// Hexadecimal value of the heatmap cell clicked.
+    pub heatmap_hex: &'static str,
+    /// Decimal value of the heatmap cell clicked.
+    pub heatmap_dec: &'static str,
+    /// Port context menu entry ignoring all the marked ports.
+    pub ignore_marked_ports: &'static str,
+    /// Hover text of the entry ignoring all the marked ports.
+    pub ignore_marked_ports_hover: &'static str,
+}
+
+const ENGLISH: UiStrings = UiStrings {
+    connection: "Connection",
+    serial_settings: "Serial Settings",
+    llm_settings: "LLM Settings",
+    language: "Language",
+    port: "Port",
+    select_port: "Select a port",
+    open: "Open",
+    close: "Close",
+    ports: "Ports",
+    scanning: "Scanning…",
+    refresh: "Refresh",
+    refresh_hover: "Scan for serial ports now",
+    selected: "Selected",
+    no_port_selected: "No port selected",
+    select_port_for_settings: "Select a port to edit its serial settings.",
+    alias: "Alias",
+    color: "Color",
+    reset: "Reset",
+    baud_rate: "Baud Rate",
+    baud_rate_hover: "Select baud rate",
+    custom_baud_rate: "Custom…",
+    custom_baud_rate_hint: "Any rate, e.g. 250000",
+    invalid_baud_rate: "Enter a whole number above 0",
+    high_baud_rate: "Above 4000000 baud, which few adapters support",
+    data_bits: "Data Bits",
+    stop_bits: "Stop Bits",
+    parity: "Parity",
+    flow_control: "Flow Ctrl",
+    timeout: "Timeout",
+    rx_buffer: "Rx Buffer",
+    rx_buffer_hover: "Most recently received bytes kept in memory for this port",
+    rx_timeout: "Rx Timeout",
+    rx_timeout_hover: "Raise an alert when an open port receives nothing for this long",
+    off: "Off",
+    on_timeout: "On Timeout",
+    tx_rate: "Tx Rate",
+    tx_rate_hover: "Limit the bytes written per second, for slow devices",
+    tx_rate_reopen: "Applies from the next time the port is opened",
+    no_limit: "No limit",
+    frame_gap: "Frame Gap",
+    frame_gap_hover: "Split received data into frames at silences this long, e.g. for Modbus RTU",
+    signals: "Signals",
+    line_break: "Break",
+    send_break: "Send Break",
+    send_break_hover: "Hold the line low for this long, e.g. to synchronize a LIN bus",
+    latency: "Latency",
+    measure_latency: "Measure Latency",
+    measure_latency_hover: "Send a probe and time its echo (needs a loopback or echoing device)",
+    failed: "Failed",
+    throughput: "Throughput",
+    throughput_test: "Throughput Test",
+    throughput_hover: "Send numbered packets and count their echoes (needs a loopback or echoing device)",
+    model: "Model",
+    api_key: "API Key",
+    remember_key: "Remember key",
+    save_key_hover: "Save the key in the system keychain; without a saved key, SERIAL_BEVY_LLM_KEY is used",
+    llm_key_prompt_title: "LLM API Key",
+    llm_key_prompt: "Enter the API key of the LLM provider; it is saved in the system keychain.",
+    llm_key_prompt_later: "Later",
+    base_url: "Base URL",
+    base_url_hover: "Endpoint of the global panel and of ports without their own",
+    coding: "Coding",
+    coding_on: "Coding: ON",
+    coding_off: "Coding: OFF",
+    coding_hover: "Toggle coding plan mode",
+    settings: "Settings",
+    templates: "Templates",
+    templates_hover: "Edit and send binary packet templates",
+    llm: "LLM",
+    data_type: "Data Type:",
+    no_line_ending: "No ending",
+    line_ending_hover: "Line ending appended to sent data, replacing any typed at the end",
+    escapes_on: "Esc ON",
+    escapes_on_hover: "Escape sequences (\\n, \\r, \\t, \\0, \\xNN) are sent as bytes",
+    escapes_off: "Esc OFF",
+    escapes_off_hover: "Parse escape sequences such as \\n and \\xNN",
+    auto_gbk: "Auto GBK",
+    auto_gbk_hover: "Decode received data as GBK when it is not valid UTF-8 but looks like Chinese text",
+    gbk_detected_hover: "Received data is decoded as GBK because it is not valid UTF-8",
+    nmea_on: "NMEA ON",
+    nmea_on_hover: "Received NMEA 0183 sentences are parsed into a table",
+    nmea_off: "NMEA OFF",
+    nmea_off_hover: "Parse NMEA 0183 sentences from GPS/navigation devices",
+    time_off: "Time OFF",
+    time: "Time",
+    receive_view: "Receive view",
+    time_format: "Format override (strftime)",
+    time_format_hover: "Applies to the Time and Date & Time modes, e.g. %H:%M:%S%.3f",
+    log_timestamps: "Timestamps in log file",
+    local_echo: "Local echo",
+    local_echo_hover: "How sent data is shown here; the log file always gets it",
+    echo_always: "Always",
+    echo_never: "Never",
+    echo_dedupe: "Hide device echo",
+    echo_window: "Echo within",
+    response_time: "Response time",
+    response_time_hover: "Time from each send to the next received data, shown after it",
+    response_off: "Off",
+    response_most_recent: "Since the last send",
+    response_fifo: "Oldest unanswered send (pipelined)",
+    response_stats_hover: "Minimum / average / maximum of the recent responses",
+    console_on: "Console ON",
+    console_on_hover: "Console mode enabled. Terminal handles echo. Toggle to disable.",
+    console_off: "Console OFF",
+    console_off_hover: "Enable console mode for Linux serial terminal experience (no local echo, raw data)",
+    sequence: "Sequence",
+    sequence_hover: "Edit and run a send sequence on this port",
+    clear_display: "Clear Display",
+    clear_display_hover: "Clear the receive view; the log file is kept",
+    clear_log_file: "Clear Log File",
+    clear_log_file_hover: "Empty the current log file and clear the receive view and received bytes; the byte counters are kept",
+    clear_log_confirm: "Empty the log file? This cannot be undone.",
+    clear_log_failed: "failed to clear the log file",
+    record_raw: "Record Raw",
+    record_raw_hover: "Record received bytes unmodified, with their timing",
+    stop: "Stop",
+    stop_recording_to: "Stop recording raw data to",
+    record_session: "Record Session",
+    record_session_hover: "Record received and sent bytes with their timing, for replay by a virtual port",
+    stop_session_recording_to: "Stop recording the session to",
+    recording_indicator: "● REC",
+    session_recording_indicator: "● SES",
+    replay_log: "Replay Log",
+    replay_log_hover: "Replay the received data of a session log into this port, with its original timing",
+    replay_speed: "Speed",
+    replay_speed_hover: "Multiplier of the logged timing; 0 replays everything at once",
+    replay_start: "Start",
+    replay_failed: "Replay failed",
+    choose_file: "Choose File…",
+    no_file_chosen: "No file chosen",
+    choose_file_title: "Choose File",
+    parent_folder: "Parent folder",
+    no_matching_files: "No matching files",
+    open_replay: "Open Replay",
+    open_replay_hover: "Add a virtual port replaying the received data of a session recording",
+    font_smaller: "Decrease receive font size",
+    font_larger: "Increase receive font size",
+    ui_preferences: "UI Preferences",
+    receive_view_size: "Receive view size",
+    label_size: "Label size",
+    input_size: "Input size",
+    monospace_family: "Monospace font",
+    default_font: "Default",
+    max_display_size: "Max displayed data (KB)",
+    max_display_size_hover: "Only the most recent data of a receive view page is drawn, to keep the UI responsive",
+    startup_port: "Startup port",
+    startup_port_hover: "Opened on every launch; remembered from the last --port or SERIAL_BEVY_PORT",
+    no_startup_port: "None",
+    forget_startup_port: "Forget",
+    input_hint: "Type data to send...",
+    send: "Send",
+    clear: "Clear",
+    prev: "Prev",
+    next: "Next",
+    strict_hex: "Strict hex",
+    strict_hex_hover: "Refuse to send hex input with invalid characters or odd length",
+    hex_auto_space: "Auto space",
+    hex_auto_space_hover: "Group typed hex digits into space-separated bytes",
+    repeat: "Repeat",
+    repeat_hover: "Send the input several times, e.g. to stress-test a device",
+    repeat_count_hover: "Number of times the input is sent",
+    repeat_interval_hover: "Time between two sends; 0 sends all at once",
+    open_port_to_send: "Open the port before sending",
+    search_help: "Enter: keep · Esc: cancel",
+    preview_refused: "Refused: invalid hex",
+    preview_hover: "Bytes written to the port, after line feed, escapes and encoding",
+    last_activity: "Last activity",
+    open_log_folder: "Open the folder containing the log",
+    receive_timeout: "receive timeout",
+    cts_low_paused: "CTS low — transmission paused",
+    xoff_paused: "XOFF received — transmission paused",
+    error: "Error",
+    retry_open: "Retry Open",
+    retry_open_hover: "Clear the error and open the port again",
+    close_error_hover: "Clear the error and leave the port closed",
+    recent_errors: "Recent errors in the log",
+    ignore_port: "Ignore this port",
+    ignore_port_hover: "Close the port and remove it from the list; discovery will not add it again",
+    ignored_ports: "Ignored",
+    unignore_port: "Restore",
+    unignore_port_hover: "Let discovery add this port again",
+    remember_ignored_ports: "Keep ignored after restart",
+    heatmap: "Heatmap",
+    heatmap_hover: "Show each received byte as a cell, from black (0x00) to white (0xFF); click a cell for its offset and value",
+    packet_size: "Packet size",
+    packets: "Packets",
+    start: "Start",
+    open_port_to_test: "Open the port to test it",
+    dropped_packets: "Dropped packets",
+    dropped_file: "Dropped File",
+    load_anyway: "Load anyway",
+    send_directly: "Send directly",
+    send_directly_hover: "Write the file's bytes to the port as they are",
+    cancel: "Cancel",
+    ok: "OK",
+    latest: "Latest",
+    latest_hover: "Follow new data again (End)",
+    display_truncated: "Truncated, the first",
+    display_truncated_load: "are not shown (click to page back)",
+    previous_page: "◀ Prev",
+    next_page: "Next ▶",
+    page_hover: "Page of the receive view; the last page shows the latest data",
+    copy_all: "Copy all",
+    copy_selected: "Copy selected",
+    copy_as_hex: "Copy as hex",
+    save_selected: "Save selected to file",
+    save_selected_hover: "Save the selected text to a file in the log folder",
+    statistics: "Statistics",
+    history: "History",
+    history_hover: "Bytes and errors of this and earlier sessions of the port",
+    opened_at: "Opened",
+    closed_at: "Closed",
+    duration: "Duration",
+    rx_bytes_header: "RX",
+    tx_bytes_header: "TX",
+    errors: "Errors",
+    still_open: "open",
+    lifetime: "Total",
+    reset_stats: "Reset counters",
+    reset_stats_hover: "Reset the current session's byte and error counters, keeping the history",
+    high_contrast: "◐",
+    high_contrast_hover: "High-contrast colors",
+    keyboard_shortcuts: "Keyboard Shortcuts",
+    shortcut_toggle_port: "Open or close the selected port",
+    shortcut_close_port: "Close the selected port",
+    shortcut_next_port: "Select the next port",
+    shortcut_previous_port: "Select the previous port",
+    shortcut_select_port: "Select the 1st to 9th port",
+    shortcut_rotate_log: "Continue the log in a new file and clear the receive view",
+    shortcut_search: "Search the receive view",
+    shortcut_history_search: "Search the send history",
+    find: "Find",
+    find_hint: "Show only lines containing...",
+    close_search: "Close search",
+    receive_window: "Data Receive Window",
+    split_view: "Split",
+    split_view_hover: "Show several open ports side by side",
+    split_view_disabled_hover: "Open at least two ports to split the view",
+    split_panes: "panes",
+    merged_view: "Merged",
+    merged_view_hover: "Show the traffic of all ports in one list",
+    merged_clear_hover: "Clear the merged traffic log",
+    merged_entries: "entries",
+    merged_placeholder: "Merged Traffic",
+    ask_ai_hint: "Ask AI...",
+    stop_generating_hover: "Stop generating the answer",
+    waiting_for_response: "Waiting for response...",
+    llm_not_configured: "Set key/model to enable sending",
+    clear_llm_history: "Clear History",
+    clear_llm_history_hover: "Clear the conversation and delete its saved file",
+    llm_standalone: "LLM (standalone)",
+    clear_conversation_hover: "Clear conversation history",
+    llm_config_required: "LLM Configuration Required",
+    llm_config_required_text: "Please enter your LLM API key and select a model in the left settings panel.",
+    templates_select_port: "Select a port to edit its packet templates.",
+    name: "Name",
+    remove_template: "Remove template",
+    template_send_disabled_hover: "Open the port and add fields to send the packet",
+    no_fields: "No fields yet.",
+    field_bytes: "Bytes",
+    field_value: "Value",
+    field_order: "Order",
+    remove_field: "Remove field",
+    sequence_title: "Send Sequence",
+    sequence_select_port: "Select a port to edit its send sequence.",
+    no_steps: "No steps yet.",
+    remove_step: "Remove step",
+    step_wait: "Wait",
+    step_wait_for: "Wait for",
+    step_timeout: "timeout",
+    file: "File",
+    load: "Load",
+    save: "Save",
+    loaded: "Loaded",
+    load_failed: "Failed to load",
+    saved: "Saved",
+    save_failed: "Failed to save",
+    run: "Run",
+    abort: "Abort",
+    sequence_run_disabled_hover: "Open the port and add steps to run the sequence",
+    sequence_passed: "Passed",
+    sequence_aborted: "Aborted",
+    history_search_prompt: "(reverse-i-search)",
+    history_search_failing: "(failing reverse-i-search)",
+    add_field: "+ Field",
+    add_template: "+ Template",
+    llm_request_settings: "Request settings",
+    llm_provider: "Provider",
+    llm_local_model: "Local model",
+    llm_local_model_hint: "llama3",
+    llm_local_model_hover: "Model pulled into the local Ollama server",
+    llm_endpoint_hover: "Chat endpoint of the provider",
+    llm_temperature: "Temperature",
+    llm_max_tokens: "Max tokens",
+    llm_history_chars: "History chars",
+    llm_history_chars_hover: "Older messages beyond this many characters are not sent",
+    llm_context_bytes: "Context bytes",
+    llm_context_bytes_hover: "Recently received bytes sent along with a query",
+    heatmap_offset: "Offset",
+    heatmap_hex: "Hex",
+    heatmap_dec: "Dec",
+    ignore_marked_ports: "Ignore the marked ports",
+    ignore_marked_ports_hover: "Close the ports marked with Ctrl+click and remove them from the list",
+};
+
+const CHINESE_SIMPLIFIED: UiStrings = UiStrings {
+    connection: "连接",
+    serial_settings: "串口设置",
+    llm_settings: "LLM 设置",
+    language: "语言",
+    port: "串口",
+    select_port: "选择串口",
+    open: "打开",
+    close: "关闭",
+    ports: "端口列表",
+    scanning: "扫描中…",
+    refresh: "刷新",
+    refresh_hover: "立即扫描串口",
+    selected: "当前串口",
+    no_port_selected: "未选择串口",
+    select_port_for_settings: "选择串口以编辑其设置。",
+    alias: "别名",
+    color: "颜色",
+    reset: "重置",
+    baud_rate: "波特率",
+    baud_rate_hover: "选择波特率",
+    custom_baud_rate: "自定义…",
+    custom_baud_rate_hint: "任意波特率，例如 250000",
+    invalid_baud_rate: "请输入大于 0 的整数",
+    high_baud_rate: "超过 4000000，很少有适配器支持",
+    data_bits: "数据位",
+    stop_bits: "停止位",
+    parity: "校验位",
+    flow_control: "流控",
+    timeout: "超时",
+    rx_buffer: "接收缓存",
+    rx_buffer_hover: "此串口在内存中保留的最近接收字节数",
+    rx_timeout: "接收超时",
+    rx_timeout_hover: "打开的串口在此时长内未收到数据时发出警告",
+    off: "关",
+    on_timeout: "超时动作",
+    tx_rate: "发送速率",
+    tx_rate_hover: "限制每秒写入的字节数，用于慢速设备",
+    tx_rate_reopen: "下次打开串口时生效",
+    no_limit: "不限速",
+    frame_gap: "帧间隔",
+    frame_gap_hover: "按此时长的静默将接收数据分帧，例如用于 Modbus RTU",
+    signals: "信号线",
+    line_break: "中断",
+    send_break: "发送中断",
+    send_break_hover: "将线路拉低这么长时间，例如用于 LIN 总线同步",
+    latency: "延迟",
+    measure_latency: "测量延迟",
+    measure_latency_hover: "发送探测数据并计时其回显（需要回环或回显设备）",
+    failed: "失败",
+    throughput: "吞吐量",
+    throughput_test: "吞吐量测试",
+    throughput_hover: "发送带序号的数据包并统计回显（需要回环或回显设备）",
+    model: "模型",
+    api_key: "API 密钥",
+    remember_key: "记住密钥",
+    save_key_hover: "将密钥保存到系统钥匙串；未保存密钥时使用 SERIAL_BEVY_LLM_KEY",
+    llm_key_prompt_title: "LLM API 密钥",
+    llm_key_prompt: "请输入 LLM 服务的 API 密钥，密钥将保存到系统钥匙串。",
+    llm_key_prompt_later: "稍后",
+    base_url: "接口地址",
+    base_url_hover: "全局面板及未单独设置地址的串口所用的接口",
+    coding: "编程",
+    coding_on: "编程：开",
+    coding_off: "编程：关",
+    coding_hover: "切换编程套餐模式",
+    settings: "设置",
+    templates: "模板",
+    templates_hover: "编辑并发送二进制数据包模板",
+    llm: "LLM",
+    data_type: "数据类型：",
+    no_line_ending: "无换行",
+    line_ending_hover: "发送的数据末尾添加的换行符，替换输入末尾的换行",
+    escapes_on: "转义：开",
+    escapes_on_hover: "转义序列（\\n、\\r、\\t、\\0、\\xNN）按字节发送",
+    escapes_off: "转义：关",
+    escapes_off_hover: "解析 \\n、\\xNN 等转义序列",
+    auto_gbk: "自动 GBK",
+    auto_gbk_hover: "接收的数据不是有效 UTF-8 但像中文文本时，按 GBK 解码",
+    gbk_detected_hover: "接收的数据不是有效 UTF-8，已按 GBK 解码",
+    nmea_on: "NMEA：开",
+    nmea_on_hover: "接收的 NMEA 0183 语句被解析为表格",
+    nmea_off: "NMEA：关",
+    nmea_off_hover: "解析 GPS/导航设备的 NMEA 0183 语句",
+    time_off: "时间戳：关",
+    time: "时间戳",
+    receive_view: "接收区",
+    time_format: "自定义格式（strftime）",
+    time_format_hover: "用于 Time 和 Date & Time 模式，例如 %H:%M:%S%.3f",
+    log_timestamps: "日志文件带时间戳",
+    local_echo: "本地回显",
+    local_echo_hover: "发送的数据在此如何显示；日志文件始终记录",
+    echo_always: "始终",
+    echo_never: "从不",
+    echo_dedupe: "隐藏设备回显",
+    echo_window: "回显时限",
+    response_time: "响应时间",
+    response_time_hover: "从每次发送到下一次接收数据的时间，显示在接收数据之后",
+    response_off: "关闭",
+    response_most_recent: "从最近一次发送计时",
+    response_fifo: "从最早未应答的发送计时（流水线）",
+    response_stats_hover: "最近响应的最小 / 平均 / 最大时间",
+    console_on: "终端模式：开",
+    console_on_hover: "终端模式已开启，回显由终端处理。点击关闭。",
+    console_off: "终端模式：关",
+    console_off_hover: "开启终端模式，获得 Linux 串口终端体验（无本地回显，原始数据）",
+    sequence: "序列",
+    sequence_hover: "编辑并在此串口上运行发送序列",
+    clear_display: "清空显示",
+    clear_display_hover: "清空接收区，日志文件保留",
+    clear_log_file: "清空日志文件",
+    clear_log_file_hover: "清空当前日志文件、接收区和已接收的字节，字节计数保留",
+    clear_log_confirm: "清空日志文件？此操作无法撤销。",
+    clear_log_failed: "清空日志文件失败",
+    record_raw: "录制原始数据",
+    record_raw_hover: "按原样录制接收的字节及其时序",
+    stop: "停止",
+    stop_recording_to: "停止录制原始数据到",
+    record_session: "录制会话",
+    record_session_hover: "录制接收与发送的字节及其时序，可由虚拟串口回放",
+    stop_session_recording_to: "停止录制会话到",
+    recording_indicator: "● 录制",
+    session_recording_indicator: "● 会话",
+    replay_log: "回放日志",
+    replay_log_hover: "按原有时序将会话日志中接收的数据回放到此串口",
+    replay_speed: "速度",
+    replay_speed_hover: "日志时序的倍速；0 表示一次性全部回放",
+    replay_start: "开始",
+    replay_failed: "回放失败",
+    choose_file: "选择文件…",
+    no_file_chosen: "未选择文件",
+    choose_file_title: "选择文件",
+    parent_folder: "上级文件夹",
+    no_matching_files: "没有匹配的文件",
+    open_replay: "打开回放",
+    open_replay_hover: "添加一个回放会话录制中接收数据的虚拟串口",
+    font_smaller: "减小接收区字号",
+    font_larger: "增大接收区字号",
+    ui_preferences: "界面偏好",
+    receive_view_size: "接收区字号",
+    label_size: "标签字号",
+    input_size: "输入框字号",
+    monospace_family: "等宽字体",
+    default_font: "默认",
+    max_display_size: "最大显示数据（KB）",
+    max_display_size_hover: "接收窗口每页只绘制最新的数据，以保持界面流畅",
+    startup_port: "启动串口",
+    startup_port_hover: "每次启动时打开；记住最近一次 --port 或 SERIAL_BEVY_PORT 指定的串口",
+    no_startup_port: "无",
+    forget_startup_port: "清除",
+    input_hint: "输入要发送的数据…",
+    send: "发送",
+    clear: "清空",
+    prev: "上一条",
+    next: "下一条",
+    strict_hex: "严格 Hex",
+    strict_hex_hover: "拒绝发送含无效字符或长度为奇数的 Hex 输入",
+    hex_auto_space: "自动空格",
+    hex_auto_space_hover: "将输入的 Hex 数字按字节以空格分组",
+    repeat: "重复",
+    repeat_hover: "多次发送输入内容，例如用于设备压力测试",
+    repeat_count_hover: "输入内容的发送次数",
+    repeat_interval_hover: "两次发送之间的间隔；0 表示一次全部发送",
+    open_port_to_send: "请先打开串口再发送",
+    search_help: "Enter：保留 · Esc：取消",
+    preview_refused: "拒绝发送：Hex 无效",
+    preview_hover: "写入串口的字节（已处理换行、转义和编码）",
+    last_activity: "最近收发",
+    open_log_folder: "打开日志所在文件夹",
+    receive_timeout: "接收超时",
+    cts_low_paused: "CTS 为低 — 发送已暂停",
+    xoff_paused: "收到 XOFF — 发送已暂停",
+    error: "错误",
+    retry_open: "重试打开",
+    retry_open_hover: "清除错误并重新打开端口",
+    close_error_hover: "清除错误，保持端口关闭",
+    recent_errors: "日志中最近的错误",
+    ignore_port: "忽略此串口",
+    ignore_port_hover: "关闭串口并将其从列表中移除；扫描不会再添加它",
+    ignored_ports: "已忽略",
+    unignore_port: "恢复",
+    unignore_port_hover: "允许扫描重新添加此串口",
+    remember_ignored_ports: "重启后仍然忽略",
+    heatmap: "热力图",
+    heatmap_hover: "将每个接收字节显示为一个方格，从黑色（0x00）到白色（0xFF）；点击方格查看其偏移和值",
+    packet_size: "包大小",
+    packets: "包数",
+    start: "开始",
+    open_port_to_test: "请先打开串口再测试",
+    dropped_packets: "丢包数",
+    dropped_file: "拖入的文件",
+    load_anyway: "仍然载入",
+    send_directly: "直接发送",
+    send_directly_hover: "将文件字节原样写入串口",
+    cancel: "取消",
+    ok: "确定",
+    latest: "最新",
+    latest_hover: "重新跟随最新数据（End）",
+    display_truncated: "已截断，前",
+    display_truncated_load: "未显示（点击向前翻页）",
+    previous_page: "◀ 上一页",
+    next_page: "下一页 ▶",
+    page_hover: "接收区的页码，最后一页显示最新数据",
+    copy_all: "复制全部",
+    copy_selected: "复制选中",
+    copy_as_hex: "复制为 hex",
+    save_selected: "保存选中到文件",
+    save_selected_hover: "将选中的文本保存到日志文件夹中的文件",
+    statistics: "统计",
+    history: "历史",
+    history_hover: "该串口本次及以往会话的字节数和错误数",
+    opened_at: "打开时间",
+    closed_at: "关闭时间",
+    duration: "时长",
+    rx_bytes_header: "接收",
+    tx_bytes_header: "发送",
+    errors: "错误",
+    still_open: "打开中",
+    lifetime: "累计",
+    reset_stats: "重置计数",
+    reset_stats_hover: "重置本次会话的字节和错误计数，保留历史记录",
+    high_contrast: "◐",
+    high_contrast_hover: "高对比度配色",
+    keyboard_shortcuts: "快捷键",
+    shortcut_toggle_port: "打开或关闭所选串口",
+    shortcut_close_port: "关闭所选串口",
+    shortcut_next_port: "选择下一个串口",
+    shortcut_previous_port: "选择上一个串口",
+    shortcut_select_port: "选择第 1 至第 9 个串口",
+    shortcut_rotate_log: "在新文件中继续记录日志并清空接收区",
+    shortcut_search: "搜索接收区",
+    shortcut_history_search: "搜索发送历史",
+    find: "查找",
+    find_hint: "只显示包含以下内容的行…",
+    close_search: "关闭搜索",
+    receive_window: "数据接收窗口",
+    split_view: "分屏",
+    split_view_hover: "并排显示多个已打开的串口",
+    split_view_disabled_hover: "至少打开两个串口才能分屏",
+    split_panes: "个窗格",
+    merged_view: "合并",
+    merged_view_hover: "在一个列表中显示所有串口的收发数据",
+    merged_clear_hover: "清空合并的收发记录",
+    merged_entries: "条",
+    merged_placeholder: "合并收发数据",
+    ask_ai_hint: "向 AI 提问…",
+    stop_generating_hover: "停止生成回答",
+    waiting_for_response: "等待回复…",
+    llm_not_configured: "设置密钥和模型后即可发送",
+    clear_llm_history: "清空历史",
+    clear_llm_history_hover: "清空对话并删除其保存的文件",
+    llm_standalone: "LLM（独立）",
+    clear_conversation_hover: "清空对话历史",
+    llm_config_required: "需要配置 LLM",
+    llm_config_required_text: "请在左侧设置面板中输入 LLM API 密钥并选择模型。",
+    templates_select_port: "选择一个串口以编辑其数据包模板。",
+    name: "名称",
+    remove_template: "删除模板",
+    template_send_disabled_hover: "打开串口并添加字段后才能发送数据包",
+    no_fields: "还没有字段。",
+    field_bytes: "字节数",
+    field_value: "值",
+    field_order: "字节序",
+    remove_field: "删除字段",
+    sequence_title: "发送序列",
+    sequence_select_port: "选择一个串口以编辑其发送序列。",
+    no_steps: "还没有步骤。",
+    remove_step: "删除步骤",
+    step_wait: "等待",
+    step_wait_for: "等待接收",
+    step_timeout: "超时",
+    file: "文件",
+    load: "加载",
+    save: "保存",
+    loaded: "已加载",
+    load_failed: "加载失败",
+    saved: "已保存",
+    save_failed: "保存失败",
+    run: "运行",
+    abort: "中止",
+    sequence_run_disabled_hover: "打开串口并添加步骤后才能运行序列",
+    sequence_passed: "通过",
+    sequence_aborted: "已中止",
+    history_search_prompt: "(反向搜索历史)",
+    history_search_failing: "(反向搜索历史 无匹配)",
+    add_field: "+ 字段",
+    add_template: "+ 模板",
+    llm_request_settings: "请求设置",
+    llm_provider: "服务商",
+    llm_local_model: "本地模型",
+    llm_local_model_hint: "llama3",
+    llm_local_model_hover: "已拉取到本地 Ollama 服务器的模型",
+    llm_endpoint_hover: "服务商的对话接口地址",
+    llm_temperature: "温度",
+    llm_max_tokens: "最大令牌数",
+    llm_history_chars: "历史字符数",
+    llm_history_chars_hover: "超出此字符数的较早消息不会发送",
+    llm_context_bytes: "上下文字节数",
+    llm_context_bytes_hover: "随提问一起发送的最近接收字节数",
+    heatmap_offset: "偏移",
+    heatmap_hex: "十六进制",
+    heatmap_dec: "十进制",
+    ignore_marked_ports: "忽略已标记的串口",
+    ignore_marked_ports_hover: "关闭通过 Ctrl+单击标记的串口并将其从列表中移除",
+};
+
+/// Returns the UI strings of `locale`.
+#[must_use]
+pub const fn strings(locale: Locale) -> &'static UiStrings {
+    match locale {
+        Locale::English => &ENGLISH,
+        Locale::ChineseSimplified => &CHINESE_SIMPLIFIED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_strings(s: &UiStrings) -> Vec<&'static str> {
+        vec![
+            s.connection,
+            s.serial_settings,
+            s.llm_settings,
+            s.language,
+            s.port,
+            s.select_port,
+            s.open,
+            s.close,
+            s.ports,
+            s.scanning,
+            s.refresh,
+            s.refresh_hover,
+            s.selected,
+            s.no_port_selected,
+            s.select_port_for_settings,
+            s.alias,
+            s.color,
+            s.reset,
+            s.baud_rate,
+            s.baud_rate_hover,
+            s.custom_baud_rate,
+            s.custom_baud_rate_hint,
+            s.invalid_baud_rate,
+            s.high_baud_rate,
+            s.data_bits,
+            s.stop_bits,
+            s.parity,
+            s.flow_control,
+            s.timeout,
+            s.rx_buffer,
+            s.rx_buffer_hover,
+            s.rx_timeout,
+            s.rx_timeout_hover,
+            s.off,
+            s.on_timeout,
+            s.tx_rate,
+            s.tx_rate_hover,
+            s.tx_rate_reopen,
+            s.no_limit,
+            s.frame_gap,
+            s.frame_gap_hover,
+            s.signals,
+            s.line_break,
+            s.send_break,
+            s.send_break_hover,
+            s.latency,
+            s.measure_latency,
+            s.measure_latency_hover,
+            s.failed,
+            s.throughput,
+            s.throughput_test,
+            s.throughput_hover,
+            s.model,
+            s.api_key,
+            s.remember_key,
+            s.save_key_hover,
+            s.llm_key_prompt_title,
+            s.llm_key_prompt,
+            s.llm_key_prompt_later,
+            s.base_url,
+            s.base_url_hover,
+            s.coding,
+            s.coding_on,
+            s.coding_off,
+            s.coding_hover,
+            s.settings,
+            s.templates,
+            s.templates_hover,
+            s.llm,
+            s.data_type,
+            s.no_line_ending,
+            s.line_ending_hover,
+            s.escapes_on,
+            s.escapes_on_hover,
+            s.escapes_off,
+            s.escapes_off_hover,
+            s.auto_gbk,
+            s.auto_gbk_hover,
+            s.gbk_detected_hover,
+            s.nmea_on,
+            s.nmea_on_hover,
+            s.nmea_off,
+            s.nmea_off_hover,
+            s.time_off,
+            s.time,
+            s.receive_view,
+            s.time_format,
+            s.time_format_hover,
+            s.log_timestamps,
+            s.local_echo,
+            s.local_echo_hover,
+            s.echo_always,
+            s.echo_never,
+            s.echo_dedupe,
+            s.echo_window,
+            s.response_time,
+            s.response_time_hover,
+            s.response_off,
+            s.response_most_recent,
+            s.response_fifo,
+            s.response_stats_hover,
+            s.console_on,
+            s.console_on_hover,
+            s.console_off,
+            s.console_off_hover,
+            s.sequence,
+            s.sequence_hover,
+            s.clear_display,
+            s.clear_display_hover,
+            s.clear_log_file,
+            s.clear_log_file_hover,
+            s.clear_log_confirm,
+            s.clear_log_failed,
+            s.record_raw,
+            s.record_raw_hover,
+            s.stop,
+            s.stop_recording_to,
+            s.record_session,
+            s.record_session_hover,
+            s.stop_session_recording_to,
+            s.recording_indicator,
+            s.session_recording_indicator,
+            s.replay_log,
+            s.replay_log_hover,
+            s.replay_speed,
+            s.replay_speed_hover,
+            s.replay_start,
+            s.replay_failed,
+            s.choose_file,
+            s.no_file_chosen,
+            s.choose_file_title,
+            s.parent_folder,
+            s.no_matching_files,
+            s.open_replay,
+            s.open_replay_hover,
+            s.font_smaller,
+            s.font_larger,
+            s.ui_preferences,
+            s.receive_view_size,
+            s.label_size,
+            s.input_size,
+            s.monospace_family,
+            s.default_font,
+            s.max_display_size,
+            s.max_display_size_hover,
+            s.startup_port,
+            s.startup_port_hover,
+            s.no_startup_port,
+            s.forget_startup_port,
+            s.input_hint,
+            s.send,
+            s.clear,
+            s.prev,
+            s.next,
+            s.strict_hex,
+            s.strict_hex_hover,
+            s.hex_auto_space,
+            s.hex_auto_space_hover,
+            s.repeat,
+            s.repeat_hover,
+            s.repeat_count_hover,
+            s.repeat_interval_hover,
+            s.open_port_to_send,
+            s.search_help,
+            s.preview_refused,
+            s.preview_hover,
+            s.last_activity,
+            s.open_log_folder,
+            s.receive_timeout,
+            s.cts_low_paused,
+            s.xoff_paused,
+            s.error,
+            s.retry_open,
+            s.retry_open_hover,
+            s.close_error_hover,
+            s.recent_errors,
+            s.ignore_port,
+            s.ignore_port_hover,
+            s.ignored_ports,
+            s.unignore_port,
+            s.unignore_port_hover,
+            s.remember_ignored_ports,
+            s.heatmap,
+            s.heatmap_hover,
+            s.packet_size,
+            s.packets,
+            s.start,
+            s.open_port_to_test,
+            s.dropped_packets,
+            s.dropped_file,
+            s.load_anyway,
+            s.send_directly,
+            s.send_directly_hover,
+            s.cancel,
+            s.ok,
+            s.latest,
+            s.latest_hover,
+            s.display_truncated,
+            s.display_truncated_load,
+            s.previous_page,
+            s.next_page,
+            s.page_hover,
+            s.copy_all,
+            s.copy_selected,
+            s.copy_as_hex,
+            s.save_selected,
+            s.save_selected_hover,
+            s.statistics,
+            s.history,
+            s.history_hover,
+            s.opened_at,
+            s.closed_at,
+            s.duration,
+            s.rx_bytes_header,
+            s.tx_bytes_header,
+            s.errors,
+            s.still_open,
+            s.lifetime,
+            s.reset_stats,
+            s.reset_stats_hover,
+            s.high_contrast,
+            s.high_contrast_hover,
+            s.keyboard_shortcuts,
+            s.shortcut_toggle_port,
+            s.shortcut_close_port,
+            s.shortcut_next_port,
+            s.shortcut_previous_port,
+            s.shortcut_select_port,
+            s.shortcut_rotate_log,
+            s.shortcut_search,
+            s.shortcut_history_search,
+            s.find,
+            s.find_hint,
+            s.close_search,
+            s.receive_window,
+            s.split_view,
+            s.split_view_hover,
+            s.split_view_disabled_hover,
+            s.split_panes,
+            s.merged_view,
+            s.merged_view_hover,
+            s.merged_clear_hover,
+            s.merged_entries,
+            s.merged_placeholder,
+            s.ask_ai_hint,
+            s.stop_generating_hover,
+            s.waiting_for_response,
+            s.llm_not_configured,
+            s.clear_llm_history,
+            s.clear_llm_history_hover,
+            s.llm_standalone,
+            s.clear_conversation_hover,
+            s.llm_config_required,
+            s.llm_config_required_text,
+            s.templates_select_port,
+            s.name,
+            s.remove_template,
+            s.template_send_disabled_hover,
+            s.no_fields,
+            s.field_bytes,
+            s.field_value,
+            s.field_order,
+            s.remove_field,
+            s.sequence_title,
+            s.sequence_select_port,
+            s.no_steps,
+            s.remove_step,
+            s.step_wait,
+            s.step_wait_for,
+            s.step_timeout,
+            s.file,
+            s.load,
+            s.save,
+            s.loaded,
+            s.load_failed,
+            s.saved,
+            s.save_failed,
+            s.run,
+            s.abort,
+            s.sequence_run_disabled_hover,
+            s.sequence_passed,
+            s.sequence_aborted,
+            s.history_search_prompt,
+            s.history_search_failing,
+            s.add_field,
+            s.add_template,
+            s.llm_request_settings,
+            s.llm_provider,
+            s.llm_local_model,
+            s.llm_local_model_hint,
+            s.llm_local_model_hover,
+            s.llm_endpoint_hover,
+            s.llm_temperature,
+            s.llm_max_tokens,
+            s.llm_history_chars,
+            s.llm_history_chars_hover,
+            s.llm_context_bytes,
+            s.llm_context_bytes_hover,
+            s.heatmap_offset,
+            s.heatmap_hex,
+            s.heatmap_dec,
+            s.ignore_marked_ports,
+            s.ignore_marked_ports_hover,
+        ]
+    }
+
+    #[test]
+    fn test_all_strings_are_translated() {
+        for locale in Locale::ALL {
+            assert!(!locale.as_str().is_empty());
+            let strings = all_strings(strings(locale));
+            assert!(
+                strings.iter().all(|s| !s.trim().is_empty()),
+                "{locale:?} has an empty string"
+            );
+        }
+        assert_ne!(strings(Locale::English), strings(Locale::ChineseSimplified));
+    }
+
+    #[test]
+    fn test_locale_round_trips_through_ron() {
+        for locale in Locale::ALL {
+            let text = ron::to_string(&locale).unwrap();
+            assert_eq!(ron::from_str::<Locale>(&text).unwrap(), locale);
+        }
+    }
+}
@@ -1,19 +1,20 @@
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
 use bevy::prelude::*;
-use bevy_egui::EguiContexts;
+use bevy_egui::{EguiContexts, egui};
 
-use crate::serial::{Selected, Serials};
+use crate::serial::Selected;
+use crate::serial::port::CacheData;
+use crate::serial::snapshot::lock_serial;
+use crate::serial::status::SerialIo;
 
-use super::ui::submit_serial_input;
+use super::shortcuts::KeybindingsConfig;
+use super::ui::{SERIAL_INPUT_ID, submit_serial_input};
 
 /// System: send cached data if newline present (user pressed Enter).
-pub fn send_cache_data(mut serials: Query<&mut Serials>) {
-    let Ok(mut serials) = serials.single_mut() else {
-        return;
-    };
-    for serial in &mut serials.serial {
-        let Ok(mut serial) = serial.lock() else {
-            continue;
-        };
+pub fn send_cache_data(ports: Query<&SerialIo>) {
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
         if serial.is_open() {
             let should_submit = {
                 let current = serial.data().get_cache_data().get_current_data();
@@ -26,39 +27,104 @@ pub fn send_cache_data(mut serials: Query<&mut Serials>) {
     }
 }
 
-/// System: navigate cached input history with Up/Down arrows for current open port.
+/// Moves the cursor of the serial send input to the end of its text.
+pub fn move_input_cursor_to_end(ctx: &egui::Context, text: &str) {
+    let id = egui::Id::new(SERIAL_INPUT_ID);
+    if let Some(mut state) = egui::TextEdit::load_state(ctx, id) {
+        let end = egui::text::CCursor::new(text.chars().count());
+        state
+            .cursor
+            .set_char_range(Some(egui::text::CCursorRange::one(end)));
+        state.store(ctx, id);
+    }
+}
+
+/// Applies a key press to the reverse history search of `cache`.
+fn handle_search_key(cache: &mut CacheData, input: &KeyboardInput, ctrl_held: bool) {
+    match &input.logical_key {
+        Key::Escape => cache.cancel_search(),
+        Key::Enter => cache.confirm_search(),
+        Key::Backspace => cache.pop_search_text(),
+        _ => {
+            if let Some(text) = &input.text
+                && !ctrl_held
+                && !text.chars().any(char::is_control)
+            {
+                cache.push_search_text(text);
+            }
+        }
+    }
+}
+
+/// System: navigate cached input history with Up/Down arrows for current open port,
+/// complete the input from history with Tab, and search it backwards with Ctrl+R.
+///
+/// While searching, typed text narrows the search, Enter keeps the found
+/// entry and Escape restores the input from before the search.
 pub fn history_data_checkout(
-    mut serials: Query<&mut Serials>,
+    ports: Query<&SerialIo>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut key_events: MessageReader<KeyboardInput>,
     selected: Res<Selected>,
+    bindings: Res<KeybindingsConfig>,
     mut contexts: EguiContexts,
 ) {
-    if let Ok(ctx) = contexts.ctx_mut()
-        && ctx.wants_keyboard_input()
-    {
-        return;
-    }
+    let ctx = contexts.ctx_mut().ok();
+    let typing = ctx.as_ref().is_some_and(|ctx| ctx.wants_keyboard_input());
+    let input_focused = ctx
+        .as_ref()
+        .is_some_and(|ctx| ctx.memory(|memory| memory.has_focus(egui::Id::new(SERIAL_INPUT_ID))));
+    let complete = keyboard_input.just_pressed(KeyCode::Tab) && (!typing || input_focused);
+    let search =
+        bindings.history_search.just_pressed(&keyboard_input) && (!typing || input_focused);
+    let ctrl_held = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let key_presses: Vec<KeyboardInput> = key_events
+        .read()
+        .filter(|input| input.state == ButtonState::Pressed)
+        .cloned()
+        .collect();
 
-    let Ok(mut serials) = serials.single_mut() else {
+    let Some(port) = selected.selected().and_then(|port| ports.get(port).ok()) else {
         return;
     };
-    for serial in &mut serials.serial {
-        let Ok(mut serial) = serial.lock() else {
-            continue;
-        };
-        if selected.is_selected(&serial.set.port_name) && serial.is_open() {
-            if keyboard_input.just_pressed(KeyCode::ArrowUp) {
-                serial.data().get_cache_data().sub_history_index();
-                let index = serial.data().get_cache_data().get_current_data_index();
-                *serial.data().get_cache_data().get_current_data() =
-                    serial.data().get_cache_data().get_history_data(index);
-            }
-            if keyboard_input.just_pressed(KeyCode::ArrowDown) {
-                serial.data().get_cache_data().add_history_index();
-                let index = serial.data().get_cache_data().get_current_data_index();
-                *serial.data().get_cache_data().get_current_data() =
-                    serial.data().get_cache_data().get_history_data(index);
+    let mut serial = lock_serial(&port.0);
+    if !serial.is_open() {
+        return;
+    }
+    let cache = serial.data().get_cache_data();
+    if cache.is_searching() {
+        if search {
+            cache.search_older();
+        } else if !typing {
+            for input in &key_presses {
+                handle_search_key(cache, input, ctrl_held);
             }
         }
+        return;
+    }
+    if search {
+        cache.start_search();
+        return;
+    }
+    if typing && !complete {
+        return;
+    }
+    if complete
+        && cache.complete_current_data()
+        && let Some(ctx) = &ctx
+    {
+        move_input_cursor_to_end(ctx, cache.get_current_data());
+    }
+    if !typing {
+        if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+            cache.sub_history_index();
+            let index = cache.get_current_data_index();
+            *cache.get_current_data() = cache.get_history_data(index);
+        }
+        if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+            cache.add_history_index();
+            let index = cache.get_current_data_index();
+            *cache.get_current_data() = cache.get_history_data(index);
+        }
     }
 }
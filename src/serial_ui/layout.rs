@@ -1,111 +1,236 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 
+use crate::serial::ai::LlmRequestEvent;
+use crate::serial::discovery::{PortScan, Runtime};
+use crate::serial::flow::FlowStallKind;
 use crate::serial::llm::LlmMessage;
-use crate::serial::{Selected, Serials};
+use crate::serial::log::LogConfig;
+use crate::serial::snapshot::{PortSnapshot, PortSnapshots, lock_serial};
+use crate::serial::status::PortEntities;
+use crate::serial::traffic::TrafficLog;
+use crate::serial::{MAX_SPLIT_PANES, PortSelection, Selected, Serial, Serials};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::MutexGuard;
+use std::time::{Duration, Instant};
 
 use super::config::PanelWidths;
 use super::global_llm::GlobalLlmState;
+use super::i18n::UiStrings;
+use super::merged::{MergedPort, MergedView, draw_merged_view};
+use super::notifications::{FlowStalls, UiNotifications};
+use super::palette::UiPalette;
+use super::receive_text::{ReceiveText, hex_text, selection_path};
+use super::sequence::{SequenceEditor, sequence_ui};
+use super::shortcuts::{KeybindingsConfig, ReceiveSearch, draw_shortcuts_help};
+use super::tail::{BOTTOM_TOLERANCE, ReceiveTail, ReceiveTails, ScrollAnchor};
+use super::templates::draw_templates_panel;
 use super::ui::{
-    INPUT_PANEL_HEIGHT, INPUT_TEXT_EDIT_HEIGHT, INPUT_TOOLBAR_HEIGHT, MarkdownViewerCache,
+    HEATMAP_BYTES_PER_ROW, INPUT_PANEL_HEIGHT, INPUT_TEXT_EDIT_HEIGHT, INPUT_TOOLBAR_HEIGHT,
+    MarkdownViewerCache, PORT_LABEL_COLORS, WidgetKind, auto_encoding_ui, binary_recording_ui,
     clear_log_ui, console_mode_ui, data_line_feed_ui, data_type_ui, draw_baud_rate_selector,
-    draw_data_bits_selector, draw_flow_control_selector, draw_llm_coding_plan_toggle,
-    draw_llm_conversation, draw_llm_input_area, draw_llm_key_input, draw_llm_model_selector,
-    draw_parity_selector, draw_select_serial_ui, draw_serial_context_label_ui,
-    draw_serial_input_area, draw_serial_setting_ui, draw_sidebar_section, draw_stop_bits_selector,
-    draw_timeout_selector, render_message_content, timestamp_ui,
+    draw_break_button, draw_data_bits_selector, draw_flow_control_selector,
+    draw_frame_gap_selector, draw_heatmap, draw_ignored_ports, draw_language_selector,
+    draw_latency_probe, draw_llm_base_url_input, draw_llm_coding_plan_toggle,
+    draw_llm_context_toggle, draw_llm_conversation, draw_llm_history_bar, draw_llm_input_area,
+    draw_llm_key_input, draw_llm_message, draw_llm_model_selector, draw_llm_progress,
+    draw_llm_request_settings, draw_nmea_table, draw_open_replay, draw_parity_selector,
+    draw_port_refresh, draw_receive_buffer_selector, draw_receive_timeout_selector,
+    draw_select_serial_ui, draw_send_rate_selector, draw_serial_context_label_ui,
+    draw_serial_input_area, draw_serial_setting_ui, draw_sidebar_section, draw_signal_indicators,
+    draw_stop_bits_selector, draw_throughput_probe, draw_timeout_selector, escape_ui, heatmap_ui,
+    idle_text, log_replay_ui, monospace_font, nmea_ui, receive_font_size_ui, session_recording_ui,
+    timestamp_ui, widget_id,
 };
 
-/// Converts bytes to string, skipping control characters but preserving ANSI sequences.
-fn bytes_to_str_with_ansi(data: &[u8]) -> String {
-    let mut result = String::with_capacity(data.len());
-    let mut i = 0;
-    while i < data.len() {
-        let b = data[i];
-        if b == 0x00 || b == 0x0D {
-            i += 1;
-            continue;
-        }
-        if b < 0x80 {
-            result.push(b as char);
-            i += 1;
-            continue;
-        }
-        let len = if b & 0xE0 == 0xC0 {
-            2
-        } else if b & 0xF0 == 0xE0 {
-            3
-        } else if b & 0xF8 == 0xF0 {
-            4
-        } else {
-            i += 1;
-            continue;
-        };
-        if i + len <= data.len()
-            && let Ok(s) = std::str::from_utf8(&data[i..i + len])
-        {
-            result.push_str(s);
-        }
-        i += len;
-    }
-    result
-}
+/// Maximum height of the NMEA table above the receive view.
+const NMEA_TABLE_MAX_HEIGHT: f32 = 220.0;
 
-fn selected_serial_exists(serials: &Serials, selected: &Selected) -> bool {
-    serials.serial.iter().any(|serial_ref| {
-        serial_ref
-            .lock()
-            .map(|serial| selected.is_selected(&serial.set.port_name))
-            .unwrap_or(false)
-    })
+/// Lines of the display text shown per receive view page.
+const RECEIVE_LINES_PER_PAGE: usize = 1000;
+
+/// Returns the snapshot of the selected port.
+fn selected_snapshot<'a>(
+    snapshots: &'a PortSnapshots,
+    selection: &PortSelection<'_, '_>,
+) -> Option<&'a PortSnapshot> {
+    snapshots.get(selection.selected_name()?)
 }
 
-fn selected_serial_name(serials: &Serials, selected: &Selected) -> Option<String> {
-    serials.serial.iter().find_map(|serial_ref| {
-        serial_ref.lock().ok().and_then(|serial| {
-            if selected.is_selected(&serial.set.port_name) {
-                Some(serial.set.port_name.clone())
-            } else {
-                None
-            }
-        })
-    })
+/// Returns the display name (alias or port name) of the selected port.
+fn selected_serial_name(
+    snapshots: &PortSnapshots,
+    selection: &PortSelection<'_, '_>,
+    panel_widths: &PanelWidths,
+) -> Option<String> {
+    selected_snapshot(snapshots, selection).map(|port| panel_widths.snapshot_display_name(port))
 }
 
 fn draw_top_bar(
     ctx: &egui::Context,
-    serials: &mut Serials,
-    selected: &Selected,
+    selection: &PortSelection<'_, '_>,
     panel_widths: &mut PanelWidths,
-    selected_serial_exists: bool,
+    bindings: &KeybindingsConfig,
 ) {
+    let strings = panel_widths.strings();
     egui::TopBottomPanel::top("serial_ui_topbar").show(ctx, |ui| {
         ui.horizontal(|ui| {
             if ui
-                .selectable_label(panel_widths.show_settings_panel, "Settings")
+                .selectable_label(panel_widths.show_settings_panel, strings.settings)
                 .clicked()
             {
                 panel_widths.show_settings_panel = !panel_widths.show_settings_panel;
             }
 
-            let llm_response = ui.add(egui::Button::selectable(panel_widths.show_llm_panel, "LLM"));
+            if ui
+                .selectable_label(panel_widths.show_templates_panel, strings.templates)
+                .on_hover_text(strings.templates_hover)
+                .clicked()
+            {
+                panel_widths.show_templates_panel = !panel_widths.show_templates_panel;
+            }
+
+            let llm_response = ui.add(egui::Button::selectable(
+                panel_widths.show_llm_panel,
+                strings.llm,
+            ));
             if llm_response.clicked() {
                 panel_widths.show_llm_panel = !panel_widths.show_llm_panel;
-                if selected_serial_exists {
-                    for serial_ref in &mut serials.serial {
-                        let Ok(mut serial) = serial_ref.lock() else {
-                            continue;
-                        };
-                        if selected.is_selected(&serial.set.port_name) {
-                            *serial.llm().enable() = panel_widths.show_llm_panel;
-                            break;
-                        }
-                    }
+                if let Some((_, port)) = selection.port() {
+                    *lock_serial(port).llm().enable() = panel_widths.show_llm_panel;
                 }
             }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 egui::widgets::global_theme_preference_switch(ui);
+                ui.toggle_value(&mut panel_widths.high_contrast, strings.high_contrast)
+                    .on_hover_text(strings.high_contrast_hover);
+                draw_language_selector(ui, panel_widths);
+                draw_shortcuts_help(ui, bindings, strings);
+                ui.toggle_value(&mut panel_widths.show_ui_preferences, "⚙")
+                    .on_hover_text(strings.ui_preferences);
+            });
+        });
+    });
+}
+
+/// Returns the color of a port's state in the status bar.
+fn state_color(port: &PortSnapshot, palette: &UiPalette) -> egui::Color32 {
+    if port.is_error() {
+        palette.port_error
+    } else if port.is_open {
+        palette.port_open
+    } else {
+        palette.port_closed
+    }
+}
+
+/// Opens the folder containing `path` in the system file manager.
+fn open_containing_folder(path: &Path) {
+    let folder = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let opener = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    if let Err(e) = std::process::Command::new(opener).arg(folder).spawn() {
+        warn!("Failed to open {}: {e}", folder.display());
+    }
+}
+
+/// Draws the status bar: the summary of the selected port, the receive
+/// timeout alerts and the notifications.
+fn draw_status_bar(
+    ctx: &egui::Context,
+    panel_widths: &PanelWidths,
+    snapshots: &PortSnapshots,
+    selection: &PortSelection<'_, '_>,
+    notifications: &mut UiNotifications,
+    palette: &UiPalette,
+) {
+    let now = Instant::now();
+    notifications.prune(now);
+    if !notifications.is_empty() {
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+
+    let strings = panel_widths.strings();
+    egui::TopBottomPanel::bottom("serial_ui_statusbar").show(ctx, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            if let Some(port) = selected_snapshot(snapshots, selection) {
+                ui.label(egui::RichText::new("●").color(state_color(port, palette)))
+                    .on_hover_text(format!("{:?}", port.state));
+                ui.label(panel_widths.snapshot_display_name(port));
+                if port.is_open {
+                    ctx.request_repaint_after(Duration::from_secs(1));
+                }
+                ui.label(egui::RichText::new(&port.summary).monospace());
+                ui.separator();
+                ui.label(format!(
+                    "{} {} B  {} {} B",
+                    strings.rx_bytes_header, port.rx_bytes, strings.tx_bytes_header, port.tx_bytes
+                ));
+                ui.separator();
+                let last_activity = port.last_rx.max(port.last_tx);
+                ui.label(format!(
+                    "{}: {}",
+                    strings.last_activity,
+                    idle_text(last_activity, now)
+                ));
+                if let Some(stats) = port.response_stats {
+                    ui.separator();
+                    let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+                    ui.label(format!(
+                        "{}: {:.1} / {:.1} / {:.1} ms",
+                        strings.response_time,
+                        ms(stats.min),
+                        ms(stats.avg),
+                        ms(stats.max)
+                    ))
+                    .on_hover_text(format!(
+                        "{} ({})",
+                        strings.response_stats_hover, stats.count
+                    ));
+                }
+                if let Some(path) = &port.log_path {
+                    ui.separator();
+                    if ui
+                        .link(path)
+                        .on_hover_text(strings.open_log_folder)
+                        .clicked()
+                    {
+                        open_containing_folder(Path::new(path));
+                    }
+                }
+            } else {
+                ui.label(egui::RichText::new(strings.no_port_selected).weak());
+            }
+
+            for port in snapshots.iter().filter(|port| port.receive_timed_out) {
+                ui.separator();
+                let text = format!(
+                    "⚠ {}: {}",
+                    panel_widths.snapshot_display_name(port),
+                    port.last_error
+                        .as_deref()
+                        .unwrap_or(strings.receive_timeout)
+                );
+                ui.label(egui::RichText::new(text).color(palette.warning_text))
+                    .on_hover_text(&port.name);
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                for notification in notifications.iter().rev() {
+                    ui.label(egui::RichText::new(&notification.text).strong());
+                }
             });
         });
     });
@@ -113,11 +238,19 @@ fn draw_top_bar(
 
 fn draw_left_panel(
     serials: &mut Serials,
-    selected: &mut Selected,
+    selection: &mut PortSelection<'_, '_>,
     ctx: &egui::Context,
     panel_widths: &mut PanelWidths,
+    resources: &mut PanelResources<'_>,
+    traffic: &mut TrafficLog,
 ) {
+    let runtime: &Runtime = &resources.runtime;
+    let snapshots: &PortSnapshots = &resources.snapshots;
+    let palette: &UiPalette = &resources.palette;
+    let port_scan: &mut PortScan = &mut resources.port_scan;
+    let log_dir: &Path = &resources.log_config.base_dir;
     if panel_widths.show_settings_panel {
+        let strings = panel_widths.strings();
         let left_show = egui::SidePanel::left("serial_ui_left")
             .resizable(true)
             .default_width(panel_widths.left_width)
@@ -127,45 +260,56 @@ fn draw_left_panel(
                 egui::ScrollArea::vertical()
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        draw_sidebar_section(ui, "Connection", |ui| {
-                            draw_select_serial_ui(ui, serials, selected);
+                        draw_sidebar_section(ui, strings.connection, |ui| {
+                            draw_select_serial_ui(
+                                ui,
+                                serials,
+                                selection,
+                                panel_widths,
+                                snapshots,
+                                traffic,
+                                palette,
+                            );
+                            draw_port_refresh(ui, port_scan, strings);
+                            draw_open_replay(ui, serials, log_dir, strings, palette);
+                            draw_ignored_ports(ui, serials, panel_widths, port_scan);
                             ui.add_space(6.0);
-                            draw_serial_setting_ui(ui, selected);
+                            let name = selected_serial_name(snapshots, selection, panel_widths);
+                            draw_serial_setting_ui(ui, name.as_deref(), strings);
                         });
 
                         ui.add_space(8.0);
 
-                        draw_sidebar_section(ui, "Serial Settings", |ui| {
-                            let mut drew_selected_serial = false;
-                            for serial in &mut serials.serial {
-                                let Ok(mut serial) = serial.lock() else {
-                                    continue;
-                                };
-                                if selected.is_selected(&serial.set.port_name) {
-                                    drew_selected_serial = true;
-                                    draw_baud_rate_selector(ui, &mut serial);
-                                    draw_data_bits_selector(ui, &mut serial);
-                                    draw_stop_bits_selector(ui, &mut serial);
-                                    draw_parity_selector(ui, &mut serial);
-                                    draw_flow_control_selector(ui, &mut serial);
-                                    draw_timeout_selector(ui, &mut serial);
-                                    break;
-                                }
-                            }
-                            if !drew_selected_serial {
+                        draw_sidebar_section(ui, strings.serial_settings, |ui| {
+                            if let Some((_, port)) = selection.port() {
+                                let mut serial = lock_serial(port);
+                                draw_baud_rate_selector(ui, &mut serial, panel_widths, palette);
+                                draw_data_bits_selector(ui, &mut serial, strings);
+                                draw_stop_bits_selector(ui, &mut serial, strings);
+                                draw_parity_selector(ui, &mut serial, strings);
+                                draw_flow_control_selector(ui, &mut serial, strings);
+                                draw_timeout_selector(ui, &mut serial, strings);
+                                draw_receive_buffer_selector(ui, &mut serial, strings);
+                                draw_receive_timeout_selector(ui, &mut serial, strings);
+                                draw_send_rate_selector(ui, &mut serial, strings);
+                                draw_frame_gap_selector(ui, &mut serial, strings);
+                                draw_signal_indicators(ui, &mut serial, strings, palette);
+                                draw_break_button(ui, &mut serial, strings);
+                                draw_latency_probe(ui, &mut serial, runtime, strings, palette);
+                                draw_throughput_probe(ui, &mut serial, runtime, strings, palette);
+                                draw_session_history(ui, &mut serial, strings);
+                            } else {
                                 ui.label(
-                                    egui::RichText::new(
-                                        "Select a port to edit its serial settings.",
-                                    )
-                                    .weak(),
+                                    egui::RichText::new(strings.select_port_for_settings).weak(),
                                 );
                             }
                         });
 
                         ui.add_space(8.0);
 
-                        draw_sidebar_section(ui, "LLM Settings", |ui| {
+                        draw_sidebar_section(ui, strings.llm_settings, |ui| {
                             draw_llm_key_input(ui, panel_widths);
+                            draw_llm_base_url_input(ui, panel_widths);
                             draw_llm_model_selector(ui, panel_widths);
                             draw_llm_coding_plan_toggle(ui, panel_widths);
                         });
@@ -176,104 +320,676 @@ fn draw_left_panel(
     }
 }
 
-fn draw_serial_output(ui: &mut egui::Ui, port_name: &str, data: &[u8], data_height: f32) {
-    egui::ScrollArea::vertical()
-        .stick_to_bottom(true)
+/// Draws the receive view of a port, following new data unless `tail` is
+/// scroll-locked, with a button jumping to the latest data while it is.
+///
+/// The text is selectable; its context menu copies it or the selection, as
+/// text or as the raw bytes behind it in hex, or saves the selection.
+///
+/// Only one window of the page, up to the configured maximum, is drawn,
+/// the most recent one by default; a banner above it tells how much earlier
+/// data is not, and clicking it or scrolling up past the top loads the
+/// window before. Scrolling down past the bottom of an earlier window loads
+/// the one after.
+fn draw_serial_output(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    display_name: &str,
+    data_height: f32,
+    tail: &mut ReceiveTail,
+    style: ReceiveStyle<'_>,
+) {
+    let ReceiveStyle {
+        panel_widths,
+        filter,
+        palette,
+    } = style;
+    let strings = panel_widths.strings();
+    let font = monospace_font(ui, panel_widths.receive_font_size());
+    let filter = filter.to_lowercase();
+    let empty = serial.data().display_text().is_empty();
+    let page = serial.data().display_page();
+    let (page_range, pages) = serial
+        .data()
+        .display_page_range(page.unwrap_or(usize::MAX), RECEIVE_LINES_PER_PAGE);
+    let current_page = page.map_or(pages - 1, |page| page.min(pages - 1));
+    let on_last_page = current_page + 1 == pages;
+    let page_start = page_range.start;
+    let page_range = serial.data().display_text_window(
+        page_range,
+        panel_widths.max_display_bytes(),
+        tail.window_index(),
+    );
+    let hidden = page_range.start - page_start;
+    let now = ui.input(|i| i.time);
+    let mut data_height = data_height;
+    if pages > 1 {
+        let top = ui.cursor().top();
+        draw_page_navigation(ui, serial, current_page, pages, tail, strings);
+        data_height = (data_height - (ui.cursor().top() - top)).max(0.0);
+    }
+    if hidden > 0 {
+        let top = ui.cursor().top();
+        let label = format!(
+            "…{} {} KB {}",
+            strings.display_truncated,
+            hidden.div_ceil(1024),
+            strings.display_truncated_load
+        );
+        if ui
+            .add(
+                egui::Button::new(egui::RichText::new(label).color(palette.warning_text))
+                    .frame(false),
+            )
+            .clicked()
+        {
+            tail.load_earlier(now);
+        }
+        data_height = (data_height - (ui.cursor().top() - top)).max(0.0);
+    }
+    let receive = ReceiveText::new(
+        &serial.data().display_text()[page_range.clone()],
+        &font,
+        ui.visuals().text_color(),
+        &filter,
+        palette,
+    );
+    let id = widget_id(&serial.set.port_name, WidgetKind::ReceiveText);
+    // A right click collapses the selection; keep it for the context menu.
+    let kept = ui
+        .input(|i| i.pointer.secondary_pressed())
+        .then(|| egui::text_edit::TextEditState::load(ui.ctx(), id))
+        .flatten()
+        .and_then(|state| state.cursor.char_range())
+        .filter(|range| !range.is_empty());
+    let jump = tail.take_jump();
+    let anchor = tail.take_anchor();
+    let output = egui::ScrollArea::vertical()
+        .id_salt(widget_id(&serial.set.port_name, WidgetKind::ReceiveOutput))
+        .stick_to_bottom(tail.is_following() && on_last_page)
         .auto_shrink([false, false])
         .max_height(data_height)
         .show(ui, |ui| {
-            if data.is_empty() {
+            let text_output = if empty {
                 ui.heading(
-                    egui::RichText::new(format!("{port_name} Data Receive Window"))
+                    egui::RichText::new(format!("{display_name} {}", strings.receive_window))
                         .color(egui::Color32::GRAY),
                 );
+                None
             } else {
-                let text = bytes_to_str_with_ansi(data);
-                let mut parser = egui_sgr::AnsiParser::new();
-                let colored_segments = parser.parse(&text);
-
-                let mut current_line: Vec<(String, Option<egui::Color32>, Option<egui::Color32>)> =
-                    Vec::new();
-
-                for seg in &colored_segments {
-                    let fg = seg.foreground_color;
-                    let bg = seg.background_color;
-                    let mut current_part = String::new();
-
-                    for ch in seg.text.chars() {
-                        if ch == '\n' {
-                            if !current_part.is_empty() {
-                                current_line.push((current_part.clone(), fg, bg));
-                                current_part.clear();
-                            }
-                            if !current_line.is_empty() {
-                                ui.horizontal(|ui| {
-                                    for (text, fg, bg) in &current_line {
-                                        let mut rt = egui::RichText::new(text).monospace();
-                                        if let Some(color) = fg {
-                                            rt = rt.color(*color);
-                                        }
-                                        if let Some(color) = bg {
-                                            rt = rt.background_color(*color);
-                                        }
-                                        ui.label(rt);
-                                    }
-                                });
-                                current_line.clear();
-                            }
-                        } else {
-                            current_part.push(ch);
-                        }
-                    }
+                let mut layouter = |ui: &egui::Ui, _: &dyn egui::TextBuffer, wrap_width: f32| {
+                    let mut job = receive.job().clone();
+                    job.wrap.max_width = wrap_width;
+                    ui.fonts_mut(|fonts| fonts.layout_job(job))
+                };
+                let mut text = receive.text();
+                Some(
+                    egui::TextEdit::multiline(&mut text)
+                        .id(id)
+                        .frame(false)
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(1)
+                        .layouter(&mut layouter)
+                        .show(ui),
+                )
+            };
+            if jump || anchor == Some(ScrollAnchor::Bottom) {
+                ui.scroll_to_cursor(Some(egui::Align::BOTTOM));
+            } else if anchor == Some(ScrollAnchor::Top) {
+                ui.scroll_to_rect(
+                    egui::Rect::from_min_size(ui.min_rect().min, egui::Vec2::ZERO),
+                    Some(egui::Align::TOP),
+                );
+            }
+            text_output
+        });
 
-                    if !current_part.is_empty() {
-                        current_line.push((current_part, fg, bg));
-                    }
+    if let Some(text_output) = output.inner {
+        let mut selection = text_output.cursor_range.filter(|range| !range.is_empty());
+        if let Some(range) = kept {
+            let mut state = text_output.state;
+            state.cursor.set_char_range(Some(range));
+            state.store(ui.ctx(), id);
+            selection = Some(range);
+        }
+        let selection = selection.map(|range| range.as_sorted_char_range());
+        text_output.response.context_menu(|ui| {
+            draw_receive_context_menu(ui, serial, &receive, page_range.start, selection, strings);
+        });
+    }
+
+    let lines = receive.line_count();
+    let to_bottom =
+        (output.content_size.y - output.inner_rect.height() - output.state.offset.y).max(0.0);
+    let scroll = if ui.rect_contains_pointer(output.inner_rect) {
+        ui.input(|i| i.raw_scroll_delta.y)
+    } else {
+        0.0
+    };
+    if hidden > 0 && output.state.offset.y <= 0.0 && scroll > 0.0 {
+        tail.load_earlier(now);
+    } else if to_bottom <= BOTTOM_TOLERANCE && scroll < 0.0 {
+        tail.load_later(now);
+    }
+    tail.update(output.state.offset.y, to_bottom, lines);
+    // Scrolling up on the last page keeps that page when new pages start;
+    // scrolling back to its bottom follows the last page again.
+    match page {
+        None if !tail.is_following() => serial.data().set_display_page(Some(current_page)),
+        Some(_) if tail.is_following() && on_last_page => serial.data().set_display_page(None),
+        _ => {}
+    }
+    if tail.is_following() && on_last_page {
+        return;
+    }
+
+    let end_pressed =
+        !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(egui::Key::End));
+    let label = match tail.new_lines(lines) {
+        0 => format!("↓ {}", strings.latest),
+        count => format!("↓ {} ({count})", strings.latest),
+    };
+    let size = egui::vec2(120.0, 24.0);
+    let rect = egui::Rect::from_min_size(
+        output.inner_rect.right_bottom() - size - egui::vec2(16.0, 8.0),
+        size,
+    );
+    let clicked = ui
+        .put(rect, egui::Button::new(label))
+        .on_hover_text(strings.latest_hover)
+        .clicked();
+    if clicked || end_pressed {
+        tail.jump_to_latest();
+        serial.data().set_display_page(None);
+    }
+}
+
+/// Draws the page navigation of a receive view with more than one page.
+///
+/// Moving to another page stops following new data; moving to the last page
+/// follows it again.
+fn draw_page_navigation(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    current_page: usize,
+    pages: usize,
+    tail: &mut ReceiveTail,
+    strings: &UiStrings,
+) {
+    ui.horizontal(|ui| {
+        let mut target = None;
+        if ui
+            .add_enabled(current_page > 0, egui::Button::new(strings.previous_page))
+            .clicked()
+        {
+            target = Some(current_page - 1);
+        }
+        ui.label(format!("{} / {pages}", current_page + 1))
+            .on_hover_text(strings.page_hover);
+        if ui
+            .add_enabled(
+                current_page + 1 < pages,
+                egui::Button::new(strings.next_page),
+            )
+            .clicked()
+        {
+            target = Some(current_page + 1);
+        }
+        if target.is_some() {
+            tail.reset_window();
+        }
+        match target {
+            Some(page) if page + 1 == pages => {
+                tail.jump_to_latest();
+                serial.data().set_display_page(None);
+            }
+            Some(page) => serial.data().set_display_page(Some(page)),
+            None => {}
+        }
+    });
+}
+
+/// Draws the receive view context menu for the `selection`, a character
+/// range of the shown text, which starts at byte `offset` of the display
+/// text.
+fn draw_receive_context_menu(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    receive: &ReceiveText,
+    offset: usize,
+    selection: Option<Range<usize>>,
+    strings: &UiStrings,
+) {
+    if ui.button(strings.copy_all).clicked() {
+        ui.ctx().copy_text(receive.text().to_string());
+        ui.close();
+    }
+    let selected = selection.is_some();
+    let chars = selection.unwrap_or_default();
+    if ui
+        .add_enabled(selected, egui::Button::new(strings.copy_selected))
+        .clicked()
+    {
+        ui.ctx().copy_text(receive.slice(chars.clone()).to_string());
+        ui.close();
+    }
+    if ui
+        .add_enabled(selected, egui::Button::new(strings.copy_as_hex))
+        .clicked()
+    {
+        let raw = receive
+            .display_range(chars.clone())
+            .map(|range| {
+                serial
+                    .data()
+                    .display_raw_bytes(range.start + offset..range.end + offset)
+            })
+            .unwrap_or_default();
+        ui.ctx().copy_text(hex_text(&raw));
+        ui.close();
+    }
+    if ui
+        .add_enabled(selected, egui::Button::new(strings.save_selected))
+        .on_hover_text(strings.save_selected_hover)
+        .clicked()
+    {
+        let dir = serial.data().session_log().dir().to_path_buf();
+        let path = selection_path(&dir, &serial.set.port_name, chrono::Local::now());
+        let result = std::fs::create_dir_all(&dir)
+            .and_then(|()| std::fs::write(&path, receive.slice(chars)));
+        match result {
+            Ok(()) => info!("Saved selection to {}", path.display()),
+            Err(e) => warn!("Failed to save selection to {}: {e}", path.display()),
+        }
+        ui.close();
+    }
+}
+
+/// Draws the receive view search bar.
+fn draw_search_bar(ui: &mut egui::Ui, search: &mut ReceiveSearch, strings: &UiStrings) {
+    ui.horizontal(|ui| {
+        ui.label(strings.find);
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut search.query)
+                .hint_text(strings.find_hint)
+                .desired_width(240.0),
+        );
+        if search.focus_requested {
+            response.request_focus();
+            search.focus_requested = false;
+        }
+        let escape = response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape));
+        if ui.button("✖").on_hover_text(strings.close_search).clicked() || escape {
+            search.close();
+        }
+    });
+    ui.separator();
+}
+
+/// What the receive views of a frame are drawn with.
+#[derive(Clone, Copy)]
+struct ReceiveStyle<'a> {
+    /// UI configuration.
+    panel_widths: &'a PanelWidths,
+    /// Text a line must contain to be shown, if not empty.
+    filter: &'a str,
+    /// UI colors.
+    palette: &'a UiPalette,
+}
+
+/// Draws the receive view of one port: the NMEA table if enabled, then the output.
+fn port_view(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    tails: &mut ReceiveTails,
+    height: f32,
+    style: ReceiveStyle<'_>,
+) {
+    let ReceiveStyle {
+        panel_widths,
+        palette,
+        ..
+    } = style;
+    let port_name = serial.set.port_name.clone();
+    let display_name = panel_widths.port_display_name(serial);
+    let format = panel_widths.timestamp_formats.get(&port_name).cloned();
+    serial.data().set_timestamp_format(format);
+    let local_echo = panel_widths
+        .local_echo
+        .get(&port_name)
+        .copied()
+        .unwrap_or_default();
+    if serial.data().local_echo() != local_echo {
+        serial.data().set_local_echo(local_echo);
+    }
+    let response_timing = panel_widths
+        .response_timing
+        .get(&port_name)
+        .copied()
+        .unwrap_or_default();
+    serial.data().set_response_timing(response_timing);
+    let mut output_height = height;
+    if serial.data().is_nmea_enabled() {
+        let top = ui.cursor().top();
+        let table_height = (height * 0.4).min(NMEA_TABLE_MAX_HEIGHT);
+        draw_nmea_table(ui, serial, table_height, palette);
+        ui.separator();
+        output_height = (height - (ui.cursor().top() - top)).max(0.0);
+    }
+    if serial.data().is_heatmap_view() {
+        ui.allocate_ui(egui::vec2(ui.available_width(), output_height), |ui| {
+            ui.push_id(widget_id(&port_name, WidgetKind::Heatmap), |ui| {
+                draw_heatmap(
+                    ui,
+                    serial.data().received_bytes(),
+                    HEATMAP_BYTES_PER_ROW,
+                    panel_widths.strings(),
+                );
+            });
+        });
+        return;
+    }
+    draw_serial_output(
+        ui,
+        serial,
+        &display_name,
+        output_height,
+        tails.port(&port_name),
+        style,
+    );
+}
+
+/// Returns the `(port entity, display name)` of every open port.
+fn open_port_names(
+    snapshots: &PortSnapshots,
+    entities: &PortEntities,
+    panel_widths: &PanelWidths,
+) -> Vec<(Entity, String)> {
+    snapshots
+        .open()
+        .filter_map(|port| {
+            Some((
+                entities.get(&port.name)?,
+                panel_widths.snapshot_display_name(port),
+            ))
+        })
+        .collect()
+}
+
+/// Draws the split view toggle and pane count selector.
+fn draw_split_view_controls(
+    ui: &mut egui::Ui,
+    selected: &mut Selected,
+    open_ports: &[Entity],
+    strings: &UiStrings,
+) {
+    let split = selected.is_split_view();
+    if split {
+        let mut count = selected.panes().len();
+        egui::ComboBox::from_id_salt("split_pane_count")
+            .selected_text(format!("{count} {}", strings.split_panes))
+            .show_ui(ui, |ui| {
+                for n in 2..=MAX_SPLIT_PANES.min(open_ports.len()).max(count) {
+                    ui.selectable_value(&mut count, n, format!("{n} {}", strings.split_panes));
                 }
+            });
+        if count != selected.panes().len() {
+            selected.enable_split_view(open_ports, count);
+        }
+    }
+    if ui
+        .add_enabled(
+            split || open_ports.len() >= 2,
+            egui::Button::selectable(split, strings.split_view),
+        )
+        .on_hover_text(strings.split_view_hover)
+        .on_disabled_hover_text(strings.split_view_disabled_hover)
+        .clicked()
+    {
+        if split {
+            selected.disable_split_view();
+        } else {
+            selected.enable_split_view(open_ports, 2);
+        }
+    }
+}
 
-                if !current_line.is_empty() {
-                    ui.horizontal(|ui| {
-                        for (text, fg, bg) in &current_line {
-                            let mut rt = egui::RichText::new(text).monospace();
-                            if let Some(color) = fg {
-                                rt = rt.color(*color);
-                            }
-                            if let Some(color) = bg {
-                                rt = rt.background_color(*color);
-                            }
-                            ui.label(rt);
+/// Draws the split view: one receive pane per port in [`Selected::panes`],
+/// two per row.
+///
+/// Each pane has its own port selector and scroll state; clicking a pane
+/// focuses its port.
+fn draw_split_view(
+    ui: &mut egui::Ui,
+    selection: &mut PortSelection<'_, '_>,
+    tails: &mut ReceiveTails,
+    open_ports: &[(Entity, String)],
+    height: f32,
+    style: ReceiveStyle<'_>,
+) {
+    let pane_count = selection.selected.panes().len();
+    let rows = pane_count.div_ceil(2).max(1);
+    let spacing = ui.spacing().item_spacing.y;
+    let row_height = ((height - spacing * (rows - 1) as f32) / rows as f32).max(0.0);
+
+    for row in 0..rows {
+        let row_panes = pane_count.saturating_sub(row * 2).min(2);
+        ui.allocate_ui_with_layout(
+            egui::Vec2::new(ui.available_width(), row_height),
+            egui::Layout::top_down(egui::Align::LEFT),
+            |ui| {
+                ui.set_height(row_height);
+                ui.columns(2, |columns| {
+                    for (column, ui) in columns.iter_mut().take(row_panes).enumerate() {
+                        draw_split_pane(ui, selection, tails, open_ports, row * 2 + column, style);
+                    }
+                });
+            },
+        );
+    }
+}
+
+/// Draws split view pane `index`: a port selector above the port's receive view.
+fn draw_split_pane(
+    ui: &mut egui::Ui,
+    selection: &mut PortSelection<'_, '_>,
+    tails: &mut ReceiveTails,
+    open_ports: &[(Entity, String)],
+    index: usize,
+    style: ReceiveStyle<'_>,
+) {
+    let Some(&pane_port) = selection.selected.panes().get(index) else {
+        return;
+    };
+    let focused = selection.selected.is_selected(pane_port);
+    let stroke = if focused {
+        ui.visuals().selection.stroke
+    } else {
+        ui.visuals().widgets.noninteractive.bg_stroke
+    };
+    let response = egui::Frame::group(ui.style())
+        .stroke(stroke)
+        .inner_margin(4.0)
+        .show(ui, |ui| {
+            ui.set_min_size(ui.available_size());
+            let display_name = open_ports
+                .iter()
+                .find(|(port, _)| *port == pane_port)
+                .map(|(_, display)| display.clone())
+                .or_else(|| selection.name(pane_port).map(str::to_owned))
+                .unwrap_or_default();
+            egui::ComboBox::from_id_salt(("split_pane", index))
+                .selected_text(display_name)
+                .show_ui(ui, |ui| {
+                    for (port, display) in open_ports {
+                        if ui.selectable_label(*port == pane_port, display).clicked() {
+                            selection.selected.set_pane(index, *port);
                         }
-                    });
-                }
+                    }
+                });
+
+            let height = ui.available_height();
+            if let Some(port) = selection.get(pane_port) {
+                port_view(ui, &mut lock_serial(port), tails, height, style);
             }
-        });
+        })
+        .response;
+
+    if ui.rect_contains_pointer(response.rect) && ui.input(|i| i.pointer.primary_pressed()) {
+        selection.selected.select(pane_port);
+    }
+}
+
+/// Returns the ports listed in the merged view: open ports and ports with
+/// logged traffic, colored with their label color or a palette color.
+fn merged_ports(
+    snapshots: &PortSnapshots,
+    panel_widths: &PanelWidths,
+    traffic: &TrafficLog,
+) -> Vec<MergedPort> {
+    let logged: BTreeSet<&str> = traffic
+        .entries()
+        .map(|entry| entry.port_name.as_str())
+        .collect();
+    snapshots
+        .iter()
+        .enumerate()
+        .filter(|(_, port)| port.is_open || logged.contains(port.name.as_str()))
+        .map(|(index, port)| {
+            let [r, g, b] = panel_widths
+                .snapshot_color(port)
+                .unwrap_or(PORT_LABEL_COLORS[index % PORT_LABEL_COLORS.len()]);
+            MergedPort {
+                name: port.name.clone(),
+                display_name: panel_widths.snapshot_display_name(port),
+                color: egui::Color32::from_rgb(r, g, b),
+            }
+        })
+        .collect()
+}
+
+/// State of the receive views and tools drawn in the central panel.
+#[derive(SystemParam)]
+pub struct ReceiveViews<'w> {
+    search: ResMut<'w, ReceiveSearch>,
+    traffic: ResMut<'w, TrafficLog>,
+    merged: ResMut<'w, MergedView>,
+    sequences: ResMut<'w, SequenceEditor>,
+    tails: ResMut<'w, ReceiveTails>,
+    stalls: Res<'w, FlowStalls>,
+}
+
+/// Resources shared by the panels of the serial UI.
+#[derive(SystemParam)]
+pub struct PanelResources<'w> {
+    bindings: Res<'w, KeybindingsConfig>,
+    runtime: Res<'w, Runtime>,
+    snapshots: Res<'w, PortSnapshots>,
+    palette: Res<'w, UiPalette>,
+    port_scan: ResMut<'w, PortScan>,
+    notifications: ResMut<'w, UiNotifications>,
+    log_config: Res<'w, LogConfig>,
+}
+
+/// State of the LLM side panel.
+#[derive(SystemParam)]
+pub struct LlmPanel<'w> {
+    global_state: ResMut<'w, GlobalLlmState>,
+    markdown_cache: ResMut<'w, MarkdownViewerCache>,
+    requests: MessageWriter<'w, LlmRequestEvent>,
 }
 
-fn draw_central_panel(serials: &mut Serials, selected: &mut Selected, ctx: &egui::Context) {
+fn draw_central_panel(
+    selection: &mut PortSelection<'_, '_>,
+    ctx: &egui::Context,
+    panel_widths: &mut PanelWidths,
+    views: &mut ReceiveViews<'_>,
+    resources: &PanelResources<'_>,
+) {
+    let snapshots: &PortSnapshots = &resources.snapshots;
+    let runtime: &Runtime = &resources.runtime;
+    let palette: &UiPalette = &resources.palette;
+    let ReceiveViews {
+        search,
+        traffic,
+        merged,
+        sequences,
+        tails,
+        stalls,
+    } = views;
+    selection
+        .selected
+        .retain_panes(selection.entities.ordered());
+    let open_ports = open_port_names(snapshots, &selection.entities, panel_widths);
+    let open_entities: Vec<Entity> = open_ports.iter().map(|(port, _)| *port).collect();
+
     egui::CentralPanel::default().show(ctx, |ui| {
+        let strings = panel_widths.strings();
         ui.horizontal(|ui| {
-            for serial in &mut serials.serial {
-                let Ok(mut serial) = serial.lock() else {
-                    continue;
-                };
-                draw_serial_context_label_ui(ui, selected, &mut serial);
+            if ui
+                .selectable_label(merged.enabled, strings.merged_view)
+                .on_hover_text(strings.merged_view_hover)
+                .clicked()
+            {
+                merged.enabled = !merged.enabled;
+            }
+            for port in snapshots.iter() {
+                if draw_serial_context_label_ui(ui, selection, port, panel_widths) {
+                    merged.enabled = false;
+                }
+            }
+            if !merged.enabled {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    draw_split_view_controls(ui, &mut selection.selected, &open_entities, strings);
+                });
             }
         });
         ui.separator();
 
+        if search.visible {
+            draw_search_bar(ui, search, strings);
+        }
+
+        if !merged.enabled {
+            for port in snapshots.iter() {
+                if selection.is_selected(&port.name)
+                    && let Some(kind) = stalls.get(&port.name)
+                {
+                    let name = panel_widths.snapshot_display_name(port);
+                    draw_flow_stall_banner(ui, &name, kind, strings, palette);
+                }
+            }
+        }
+
         let available_height = ui.available_height();
         let input_height = INPUT_PANEL_HEIGHT;
         let data_height = (available_height - input_height).max(0.0);
 
-        for serial in &mut serials.serial {
-            let Ok(mut serial) = serial.lock() else {
-                continue;
+        if merged.enabled {
+            let ports = merged_ports(snapshots, panel_widths, traffic);
+            draw_merged_view(
+                ui,
+                traffic,
+                merged,
+                &ports,
+                panel_widths,
+                search.filter(),
+                data_height,
+            );
+        } else if selection.selected.is_split_view() {
+            let style = ReceiveStyle {
+                panel_widths,
+                filter: search.filter(),
+                palette,
             };
-            if selected.is_selected(&serial.set.port_name) {
-                let data = serial.data().read_current_source_file_bytes();
-                let port_name = serial.set.port_name.clone();
-                draw_serial_output(ui, &port_name, &data, data_height);
-            }
+            draw_split_view(ui, selection, tails, &open_ports, data_height, style);
+        } else if let Some((_, port)) = selection.port() {
+            let style = ReceiveStyle {
+                panel_widths,
+                filter: search.filter(),
+                palette,
+            };
+            port_view(ui, &mut lock_serial(port), tails, data_height, style);
         }
 
         ui.separator();
@@ -282,31 +998,42 @@ fn draw_central_panel(serials: &mut Serials, selected: &mut Selected, ctx: &egui
             egui::Vec2::new(ui.available_width(), input_height),
             egui::Layout::top_down(egui::Align::LEFT),
             |ui| {
-                for serial in &mut serials.serial {
-                    let Ok(mut serial) = serial.lock() else {
-                        continue;
-                    };
-                    if selected.is_selected(&serial.set.port_name) {
-                        ui.allocate_ui_with_layout(
-                            egui::Vec2::new(ui.available_width(), INPUT_TOOLBAR_HEIGHT),
-                            egui::Layout::left_to_right(egui::Align::Center),
-                            |ui| {
-                                data_type_ui(ui, &mut serial);
-                                data_line_feed_ui(ui, &mut serial);
-                                timestamp_ui(ui, &mut serial);
-                                console_mode_ui(ui, &mut serial);
-                                ui.with_layout(
-                                    egui::Layout::right_to_left(egui::Align::Center),
-                                    |ui| {
-                                        clear_log_ui(ui, &mut serial);
-                                    },
-                                );
-                            },
-                        );
+                if let Some((_, port)) = selection.port() {
+                    let mut serial = lock_serial(port);
+                    ui.allocate_ui_with_layout(
+                        egui::Vec2::new(ui.available_width(), INPUT_TOOLBAR_HEIGHT),
+                        egui::Layout::left_to_right(egui::Align::Center),
+                        |ui| {
+                            data_type_ui(ui, &mut serial, strings);
+                            auto_encoding_ui(ui, &mut serial, strings);
+                            data_line_feed_ui(ui, &mut serial, panel_widths);
+                            escape_ui(ui, &mut serial, strings);
+                            nmea_ui(ui, &mut serial, strings);
+                            timestamp_ui(ui, &mut serial, panel_widths);
+                            console_mode_ui(ui, &mut serial, strings);
+                            heatmap_ui(ui, &mut serial, strings);
+                            sequence_ui(ui, sequences, strings);
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    clear_log_ui(ui, &mut serial, strings);
+                                    binary_recording_ui(ui, &mut serial, strings, palette);
+                                    session_recording_ui(ui, &mut serial, strings, palette);
+                                    log_replay_ui(ui, &mut serial, runtime, strings, palette);
+                                    receive_font_size_ui(ui, panel_widths);
+                                },
+                            );
+                        },
+                    );
 
-                        draw_serial_input_area(ui, &mut serial);
-                        ui.add_space(8.0);
-                    }
+                    draw_serial_input_area(
+                        ui,
+                        &mut serial,
+                        panel_widths.font_prefs.input_size,
+                        strings,
+                        palette,
+                    );
+                    ui.add_space(8.0);
                 }
             },
         );
@@ -315,12 +1042,31 @@ fn draw_central_panel(serials: &mut Serials, selected: &mut Selected, ctx: &egui
     });
 }
 
+/// Draws the warning shown above the receive view while sending on a port is
+/// paused by flow control.
+fn draw_flow_stall_banner(
+    ui: &mut egui::Ui,
+    port_name: &str,
+    kind: FlowStallKind,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    let text = match kind {
+        FlowStallKind::CtsLow => strings.cts_low_paused,
+        FlowStallKind::Xoff => strings.xoff_paused,
+    };
+    ui.label(
+        egui::RichText::new(format!("⚠ {port_name}: {text}"))
+            .color(palette.warning_text)
+            .strong(),
+    );
+}
+
 fn draw_global_llm_conversation(
     ui: &mut egui::Ui,
     global_state: &mut GlobalLlmState,
     markdown_cache: &mut MarkdownViewerCache,
 ) {
-    let visuals = ui.visuals().clone();
     let available_height = ui.available_height().max(120.0);
 
     egui::ScrollArea::vertical()
@@ -329,87 +1075,12 @@ fn draw_global_llm_conversation(
         .stick_to_bottom(true)
         .show(ui, |ui| {
             for msg in &global_state.messages {
-                let is_user = msg.role == "user";
-
-                let (bubble_color, text_color, role_color, role_text) = if is_user {
-                    (
-                        egui::Color32::from_rgb(37, 99, 235),
-                        egui::Color32::WHITE,
-                        egui::Color32::from_rgb(59, 130, 246),
-                        "You",
-                    )
-                } else if visuals.dark_mode {
-                    (
-                        egui::Color32::from_rgb(55, 65, 81),
-                        egui::Color32::from_rgb(229, 231, 235),
-                        egui::Color32::from_rgb(16, 185, 129),
-                        "AI",
-                    )
-                } else {
-                    (
-                        egui::Color32::from_rgb(243, 244, 246),
-                        egui::Color32::from_rgb(31, 41, 55),
-                        egui::Color32::from_rgb(5, 150, 105),
-                        "AI",
-                    )
-                };
-
-                ui.with_layout(
-                    egui::Layout::top_down(if is_user {
-                        egui::Align::RIGHT
-                    } else {
-                        egui::Align::LEFT
-                    })
-                    .with_cross_align(if is_user {
-                        egui::Align::RIGHT
-                    } else {
-                        egui::Align::LEFT
-                    }),
-                    |ui| {
-                        ui.horizontal(|ui| {
-                            if is_user {
-                                ui.label(egui::RichText::new(&msg.timestamp).weak().small());
-                                ui.label(egui::RichText::new(role_text).strong().color(role_color));
-                            } else {
-                                ui.label(egui::RichText::new(role_text).strong().color(role_color));
-                                ui.label(egui::RichText::new(&msg.timestamp).weak().small());
-                            }
-                        });
-
-                        let frame = egui::Frame::new()
-                            .fill(bubble_color)
-                            .corner_radius(10.0)
-                            .inner_margin(egui::Margin::symmetric(12, 10));
-                        frame.show(ui, |ui| {
-                            let max_w = ui.available_width().min(280.0);
-                            ui.set_max_width(max_w);
-                            render_message_content(
-                                ui,
-                                &msg.content,
-                                text_color,
-                                &mut markdown_cache.0,
-                            );
-                        });
-                    },
-                );
-                ui.add_space(10.0);
+                draw_llm_message(ui, msg, &mut markdown_cache.0);
             }
 
             if global_state.is_processing {
-                ui.with_layout(
-                    egui::Layout::top_down(egui::Align::LEFT).with_cross_align(egui::Align::LEFT),
-                    |ui| {
-                        ui.horizontal(|ui| {
-                            ui.spinner();
-                            ui.label(
-                                egui::RichText::new("AI is thinking...")
-                                    .italics()
-                                    .color(egui::Color32::GRAY),
-                            );
-                        });
-                    },
-                );
-                ui.add_space(4.0);
+                let streaming = global_state.streaming_text();
+                draw_llm_progress(ui, &streaming, &mut markdown_cache.0);
             }
         });
 }
@@ -419,6 +1090,7 @@ fn draw_global_llm_input_area(
     panel_widths: &mut PanelWidths,
     global_state: &mut GlobalLlmState,
 ) {
+    let strings = panel_widths.strings();
     let font = egui::FontId::new(18.0, egui::FontFamily::Monospace);
     let can_send = !global_state.input_buffer.trim().is_empty() && !global_state.is_processing;
 
@@ -426,7 +1098,7 @@ fn draw_global_llm_input_area(
         ui.add_sized(
             [ui.available_width(), INPUT_TEXT_EDIT_HEIGHT],
             egui::TextEdit::multiline(&mut global_state.input_buffer)
-                .hint_text("Ask AI...")
+                .hint_text(strings.ask_ai_hint)
                 .font(font),
         );
         ui.add_space(6.0);
@@ -435,7 +1107,7 @@ fn draw_global_llm_input_area(
             if ui
                 .add_enabled(
                     can_send,
-                    egui::Button::new(egui::RichText::new("Send").strong()),
+                    egui::Button::new(egui::RichText::new(strings.send).strong()),
                 )
                 .clicked()
             {
@@ -452,34 +1124,39 @@ fn draw_global_llm_input_area(
                 }
             }
 
-            if ui.button("Clear").clicked() {
+            if ui.button(strings.clear).clicked() {
                 global_state.input_buffer.clear();
             }
 
             if global_state.is_processing {
-                ui.label(egui::RichText::new("Waiting for response...").weak());
+                if ui
+                    .button(strings.stop)
+                    .on_hover_text(strings.stop_generating_hover)
+                    .clicked()
+                {
+                    global_state.stop();
+                }
+                ui.label(egui::RichText::new(strings.waiting_for_response).weak());
             } else if panel_widths.llm_key.is_empty() || panel_widths.llm_model.is_empty() {
-                ui.label(egui::RichText::new("Set key/model to enable sending").weak());
+                ui.label(egui::RichText::new(strings.llm_not_configured).weak());
             }
         });
     });
 }
 
 fn draw_right_panel(
-    serials: &mut Serials,
-    selected: &Selected,
+    selection: &PortSelection<'_, '_>,
     ctx: &egui::Context,
     panel_widths: &mut PanelWidths,
-    global_state: &mut GlobalLlmState,
-    markdown_cache: &mut MarkdownViewerCache,
-    selected_serial_exists: bool,
+    llm: &mut LlmPanel<'_>,
+    snapshots: &PortSnapshots,
 ) {
+    let global_state: &mut GlobalLlmState = &mut llm.global_state;
+    let markdown_cache: &mut MarkdownViewerCache = &mut llm.markdown_cache;
+    let llm_requests = &mut llm.requests;
     if panel_widths.show_llm_panel {
-        let llm_context = if selected_serial_exists {
-            selected_serial_name(serials, selected)
-        } else {
-            None
-        };
+        let strings = panel_widths.strings();
+        let llm_context = selected_serial_name(snapshots, selection, panel_widths);
 
         let right_show = egui::SidePanel::right("serial_ui_right")
             .resizable(true)
@@ -489,65 +1166,67 @@ fn draw_right_panel(
             .show(ctx, |ui| {
                 let llm_input_height = INPUT_PANEL_HEIGHT;
                 if let Some(ref port_name) = llm_context {
-                    for serial_ref in &mut serials.serial {
-                        let Ok(mut serial) = serial_ref.lock() else {
-                            continue;
-                        };
-                        if selected.is_selected(&serial.set.port_name) {
-                            ui.horizontal(|ui| {
-                                ui.label(egui::RichText::new(format!("LLM: {port_name}")).strong());
-                                ui.with_layout(
-                                    egui::Layout::right_to_left(egui::Align::Center),
-                                    |ui| {
-                                        if ui
-                                            .button("Clear")
-                                            .on_hover_text("Clear conversation history")
-                                            .clicked()
-                                        {
-                                            serial.llm().clear_messages();
-                                        }
-                                    },
-                                );
-                            });
-                            ui.separator();
-                            ui.allocate_ui_with_layout(
-                                egui::Vec2::new(
-                                    ui.available_width(),
-                                    (ui.available_height() - llm_input_height).max(120.0),
-                                ),
-                                egui::Layout::top_down(egui::Align::LEFT),
-                                |ui| {
-                                    draw_llm_conversation(ui, &mut serial, markdown_cache);
-                                },
+                    if let Some((_, port)) = selection.port() {
+                        let mut serial = lock_serial(port);
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!("{}: {port_name}", strings.llm))
+                                    .strong(),
                             );
-                            ui.separator();
-                            ui.allocate_ui_with_layout(
-                                egui::Vec2::new(ui.available_width(), llm_input_height),
-                                egui::Layout::top_down(egui::Align::LEFT),
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
                                 |ui| {
-                                    ui.allocate_ui_with_layout(
-                                        egui::Vec2::new(ui.available_width(), INPUT_TOOLBAR_HEIGHT),
-                                        egui::Layout::left_to_right(egui::Align::Center),
-                                        |_ui| {},
-                                    );
-                                    draw_llm_input_area(
-                                        ui,
-                                        &mut serial,
-                                        panel_widths,
-                                        &mut global_state.show_key_missing_popup,
-                                    );
+                                    if ui
+                                        .button(strings.clear_llm_history)
+                                        .on_hover_text(strings.clear_llm_history_hover)
+                                        .clicked()
+                                    {
+                                        serial.clear_llm_history();
+                                    }
                                 },
                             );
-                            break;
-                        }
+                        });
+                        draw_llm_history_bar(ui, &mut serial);
+                        draw_llm_request_settings(ui, &mut serial, strings);
+                        draw_llm_context_toggle(ui, &mut serial);
+                        ui.separator();
+                        ui.allocate_ui_with_layout(
+                            egui::Vec2::new(
+                                ui.available_width(),
+                                (ui.available_height() - llm_input_height).max(120.0),
+                            ),
+                            egui::Layout::top_down(egui::Align::LEFT),
+                            |ui| {
+                                draw_llm_conversation(ui, &mut serial, markdown_cache);
+                            },
+                        );
+                        ui.separator();
+                        ui.allocate_ui_with_layout(
+                            egui::Vec2::new(ui.available_width(), llm_input_height),
+                            egui::Layout::top_down(egui::Align::LEFT),
+                            |ui| {
+                                ui.allocate_ui_with_layout(
+                                    egui::Vec2::new(ui.available_width(), INPUT_TOOLBAR_HEIGHT),
+                                    egui::Layout::left_to_right(egui::Align::Center),
+                                    |_ui| {},
+                                );
+                                draw_llm_input_area(
+                                    ui,
+                                    &mut serial,
+                                    panel_widths,
+                                    &mut global_state.show_key_missing_popup,
+                                    llm_requests,
+                                );
+                            },
+                        );
                     }
                 } else {
                     ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new("LLM (standalone)").strong());
+                        ui.label(egui::RichText::new(strings.llm_standalone).strong());
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             if ui
-                                .button("Clear")
-                                .on_hover_text("Clear conversation history")
+                                .button(strings.clear)
+                                .on_hover_text(strings.clear_conversation_hover)
                                 .clicked()
                             {
                                 global_state.messages.clear();
@@ -586,19 +1265,21 @@ fn draw_right_panel(
     }
 }
 
-fn draw_missing_config_popup(ctx: &egui::Context, global_state: &mut GlobalLlmState) {
+fn draw_missing_config_popup(
+    ctx: &egui::Context,
+    global_state: &mut GlobalLlmState,
+    strings: &UiStrings,
+) {
     if global_state.show_key_missing_popup {
-        egui::Window::new("LLM Configuration Required")
+        egui::Window::new(strings.llm_config_required)
             .collapsible(false)
             .resizable(false)
             .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
             .show(ctx, |ui| {
-                ui.label(
-                    "Please enter your LLM API key and select a model in the left settings panel.",
-                );
+                ui.label(strings.llm_config_required_text);
                 ui.horizontal(|ui| {
                     ui.add_space(ui.available_width() / 2.0 - 40.0);
-                    if ui.button("  OK  ").clicked() {
+                    if ui.button(format!("  {}  ", strings.ok)).clicked() {
                         global_state.show_key_missing_popup = false;
                     }
                 });
@@ -610,10 +1291,11 @@ fn draw_missing_config_popup(ctx: &egui::Context, global_state: &mut GlobalLlmSt
 pub fn serial_ui(
     mut contexts: EguiContexts,
     mut serials: Query<&mut Serials>,
-    mut selected: ResMut<Selected>,
+    mut selection: PortSelection,
     mut panel_widths: ResMut<PanelWidths>,
-    mut global_state: ResMut<GlobalLlmState>,
-    mut markdown_cache: ResMut<MarkdownViewerCache>,
+    mut views: ReceiveViews,
+    mut llm: LlmPanel,
+    mut resources: PanelResources,
 ) {
     let Ok(mut serials_data) = serials.single_mut() else {
         return;
@@ -622,25 +1304,39 @@ pub fn serial_ui(
         return;
     };
 
-    let selected_serial_exists = selected_serial_exists(&serials_data, &selected);
+    panel_widths.sync_ignored_ports(&mut serials_data, &mut views.traffic);
 
-    draw_top_bar(
+    draw_top_bar(ctx, &selection, &mut panel_widths, &resources.bindings);
+    draw_status_bar(
         ctx,
+        &panel_widths,
+        &resources.snapshots,
+        &selection,
+        &mut resources.notifications,
+        &resources.palette,
+    );
+    draw_left_panel(
         &mut serials_data,
-        selected.as_ref(),
+        &mut selection,
+        ctx,
         &mut panel_widths,
-        selected_serial_exists,
+        &mut resources,
+        &mut views.traffic,
+    );
+    draw_templates_panel(&selection, ctx, &panel_widths);
+    draw_central_panel(
+        &mut selection,
+        ctx,
+        &mut panel_widths,
+        &mut views,
+        &resources,
     );
-    draw_left_panel(&mut serials_data, selected.as_mut(), ctx, &mut panel_widths);
-    draw_central_panel(&mut serials_data, selected.as_mut(), ctx);
     draw_right_panel(
-        &mut serials_data,
-        selected.as_ref(),
+        &selection,
         ctx,
         &mut panel_widths,
-        &mut global_state,
-        &mut markdown_cache,
-        selected_serial_exists,
+        &mut llm,
+        &resources.snapshots,
     );
-    draw_missing_config_popup(ctx, &mut global_state);
+    draw_missing_config_popup(ctx, &mut llm.global_state, panel_widths.strings());
 }
@@ -0,0 +1,525 @@
+//! # LLM Settings Module
+//!
+//! Persistence of the shared LLM settings (model and base URL) in `llm.toml`
+//! under the platform configuration directory, e.g.
+//! `~/.config/serial_bevy/llm.toml` on Linux.
+//!
+//! The API key is kept in the OS keychain (Keychain on macOS, Credential
+//! Manager on Windows, Secret Service on Linux) when "Remember key" is
+//! checked, never in `llm.toml`; a key found in an older file is moved to
+//! the keychain. Without a saved key, the `SERIAL_BEVY_LLM_KEY` environment
+//! variable is used. The key is never logged: [`LlmSettings`] redacts it
+//! from its `Debug` output. The file carries a `version` so later formats
+//! can migrate older files.
+//!
+//! Changes are saved once they stayed unchanged for
+//! [`LLM_SETTINGS_SAVE_DELAY`], so typing the key does not write the keychain
+//! on every keystroke, and on exit. On the first run without a key, a window
+//! asks for it.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use serde::{Deserialize, Serialize};
+
+use super::config::PanelWidths;
+use crate::error::{Result, SerialBevyError};
+use crate::serial::llm::DEFAULT_BASE_URL;
+
+/// File name of the LLM settings.
+pub const LLM_SETTINGS_FILE: &str = "llm.toml";
+
+/// Current version of the LLM settings file format.
+pub const LLM_SETTINGS_VERSION: u32 = 1;
+
+/// Default model.
+pub const DEFAULT_MODEL: &str = "glm-4.5-air";
+
+/// Keychain service the API key is saved under.
+pub const KEYRING_SERVICE: &str = "serial_bevy";
+
+/// Keychain user name of the API key.
+const KEYRING_USER: &str = "llm_api_key";
+
+/// Environment variable read for the API key when none is saved.
+pub const LLM_KEY_ENV: &str = "SERIAL_BEVY_LLM_KEY";
+
+/// How long changed LLM settings stay unchanged before they are saved.
+pub const LLM_SETTINGS_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Returns the path of the LLM settings file, falling back to the `config`
+/// directory if the platform has no configuration directory.
+#[must_use]
+pub fn llm_settings_path() -> PathBuf {
+    dirs::config_dir()
+        .map_or_else(|| PathBuf::from("config"), |dir| dir.join("serial_bevy"))
+        .join(LLM_SETTINGS_FILE)
+}
+
+/// Shared LLM settings as stored in `llm.toml`.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmSettings {
+    /// Version of the file format.
+    pub version: u32,
+    /// Whether the API key is saved.
+    pub remember_key: bool,
+    /// API key, empty unless `remember_key` is set. It is saved in the
+    /// keychain, so `llm.toml` only has one if written by an older version.
+    pub key: String,
+    /// Model selection.
+    pub model: String,
+    /// Chat completions endpoint of the global panel, and of ports without
+    /// their own.
+    pub base_url: String,
+    /// Whether the API key was asked for on the first run.
+    pub key_prompted: bool,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            version: LLM_SETTINGS_VERSION,
+            remember_key: true,
+            key: String::new(),
+            model: DEFAULT_MODEL.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            key_prompted: false,
+        }
+    }
+}
+
+impl fmt::Debug for LlmSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LlmSettings")
+            .field("version", &self.version)
+            .field("remember_key", &self.remember_key)
+            .field(
+                "key",
+                &if self.key.is_empty() {
+                    ""
+                } else {
+                    "<redacted>"
+                },
+            )
+            .field("model", &self.model)
+            .field("base_url", &self.base_url)
+            .field("key_prompted", &self.key_prompted)
+            .finish()
+    }
+}
+
+impl LlmSettings {
+    /// Copies the settings to save from the UI configuration.
+    #[must_use]
+    pub fn from_config(config: &PanelWidths) -> Self {
+        Self {
+            version: LLM_SETTINGS_VERSION,
+            remember_key: config.llm_remember_key,
+            key: if config.llm_remember_key {
+                config.llm_key.clone()
+            } else {
+                String::new()
+            },
+            model: config.llm_model.clone(),
+            base_url: config.llm_base_url.clone(),
+            key_prompted: config.llm_key_prompted,
+        }
+    }
+
+    /// Applies loaded settings to the UI configuration.
+    pub fn apply(self, config: &mut PanelWidths) {
+        config.llm_remember_key = self.remember_key;
+        config.llm_key_prompted = self.key_prompted;
+        if self.remember_key {
+            config.llm_key = self.key;
+        }
+        if !self.model.is_empty() {
+            config.llm_model = self.model;
+        }
+        if !self.base_url.is_empty() {
+            config.llm_base_url = self.base_url;
+        }
+    }
+
+    /// Saves the API key in the OS keychain under `service`, or removes the
+    /// saved key if it is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keychain cannot be accessed.
+    pub fn save_key_to_keyring(&self, service: &str) -> Result<()> {
+        let entry = keyring_entry(service)?;
+        let result = if self.key.is_empty() {
+            match entry.delete_credential() {
+                Err(keyring::Error::NoEntry) => Ok(()),
+                result => result,
+            }
+        } else {
+            entry.set_password(&self.key)
+        };
+        result.map_err(|e| SerialBevyError::keyring(e.to_string()))
+    }
+
+    /// Loads the API key saved in the OS keychain under `service`, or an
+    /// empty key if none is saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keychain cannot be accessed.
+    pub fn load_key_from_keyring(service: &str) -> Result<String> {
+        match keyring_entry(service)?.get_password() {
+            Ok(key) => Ok(key),
+            Err(keyring::Error::NoEntry) => Ok(String::new()),
+            Err(e) => Err(SerialBevyError::keyring(e.to_string())),
+        }
+    }
+
+    /// Parses settings, migrating older versions of the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not valid TOML or was written by a
+    /// newer version.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let value: toml::Value =
+            toml::from_str(text).map_err(|e| SerialBevyError::serialization(e.to_string()))?;
+        let version = value
+            .get("version")
+            .and_then(toml::Value::as_integer)
+            .unwrap_or(0);
+        let mut settings: Self = match version {
+            // Version 0 (no `version` field) has the same fields as version 1.
+            0 | 1 => value
+                .try_into()
+                .map_err(|e: toml::de::Error| SerialBevyError::serialization(e.to_string()))?,
+            _ => {
+                return Err(SerialBevyError::InvalidConfig(format!(
+                    "unsupported {LLM_SETTINGS_FILE} version {version}, expected at most {LLM_SETTINGS_VERSION}"
+                )));
+            }
+        };
+        settings.version = LLM_SETTINGS_VERSION;
+        Ok(settings)
+    }
+
+    /// Formats the settings as TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| SerialBevyError::serialization(e.to_string()))
+    }
+
+    /// Loads settings from `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Saves settings to `path`, creating its directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+}
+
+/// Returns the keychain entry of the API key under `service`.
+fn keyring_entry(service: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(service, KEYRING_USER).map_err(|e| SerialBevyError::keyring(e.to_string()))
+}
+
+/// Applies the saved LLM settings to `config`, and the saved API key.
+///
+/// A key still in the settings file is moved to the keychain. Without a
+/// settings file, a key and model read from an older `app_memory.ron` are
+/// moved to a new one. Without a saved key, the key is read from
+/// [`LLM_KEY_ENV`].
+pub fn load_llm_settings(config: &mut PanelWidths) {
+    let path = llm_settings_path();
+    match LlmSettings::load(&path) {
+        Ok(settings) => {
+            let key_in_file = !settings.key.is_empty();
+            settings.apply(config);
+            if key_in_file {
+                save_llm_settings_to_disk(config);
+            }
+        }
+        Err(SerialBevyError::FileIo(e)) if e.kind() == ErrorKind::NotFound => {
+            if !config.llm_key.is_empty() || config.llm_model != DEFAULT_MODEL {
+                save_llm_settings_to_disk(config);
+            }
+        }
+        Err(e) => log::warn!("[serial_ui] Failed to load {}: {e}", path.display()),
+    }
+
+    if config.llm_remember_key && config.llm_key.is_empty() {
+        match LlmSettings::load_key_from_keyring(KEYRING_SERVICE) {
+            Ok(key) => config.llm_key = key,
+            Err(e) => log::warn!("[serial_ui] Failed to load the LLM key: {e}"),
+        }
+    }
+    use_env_key(config, env::var(LLM_KEY_ENV).ok());
+}
+
+/// Uses `env_key`, the value of [`LLM_KEY_ENV`], as the API key if no key is
+/// saved.
+fn use_env_key(config: &mut PanelWidths, env_key: Option<String>) {
+    if config.llm_key.is_empty()
+        && let Some(key) = env_key
+    {
+        config.llm_key = key;
+    }
+}
+
+/// Returns the settings of `config` to save; a key given through the
+/// environment as `env_key` stays there and is not saved.
+fn settings_to_save(config: &PanelWidths, env_key: Option<&str>) -> LlmSettings {
+    let mut settings = LlmSettings::from_config(config);
+    if env_key == Some(settings.key.as_str()) {
+        settings.key.clear();
+    }
+    settings
+}
+
+/// Saves the LLM settings of `config`, with the API key in the keychain if
+/// it is remembered, logging failures.
+pub fn save_llm_settings_to_disk(config: &PanelWidths) {
+    let mut settings = settings_to_save(config, env::var(LLM_KEY_ENV).ok().as_deref());
+    if let Err(e) = settings.save_key_to_keyring(KEYRING_SERVICE) {
+        log::warn!("[serial_ui] Failed to save the LLM key: {e}");
+    }
+    settings.key.clear();
+
+    let path = llm_settings_path();
+    if let Err(e) = settings.save(&path) {
+        log::warn!("[serial_ui] Failed to write {}: {e}", path.display());
+    }
+}
+
+/// LLM settings changed since they were last saved, waiting to stay
+/// unchanged for [`LLM_SETTINGS_SAVE_DELAY`].
+#[derive(Default)]
+pub struct PendingLlmSettings {
+    /// Settings last saved, or loaded at startup.
+    saved: Option<LlmSettings>,
+    /// Latest changed settings and when they changed.
+    changed: Option<(LlmSettings, Instant)>,
+}
+
+impl PendingLlmSettings {
+    /// Notes the current `settings` at `now`, and returns true if they are to
+    /// be saved: they differ from the saved ones and did not change for
+    /// [`LLM_SETTINGS_SAVE_DELAY`].
+    fn update(&mut self, settings: LlmSettings, now: Instant) -> bool {
+        let Some(saved) = &self.saved else {
+            // The settings as loaded at startup are already on disk.
+            self.saved = Some(settings);
+            return false;
+        };
+        if *saved == settings {
+            self.changed = None;
+            return false;
+        }
+        match &self.changed {
+            Some((latest, since)) if *latest == settings => {
+                if now.saturating_duration_since(*since) < LLM_SETTINGS_SAVE_DELAY {
+                    return false;
+                }
+                self.saved = Some(settings);
+                self.changed = None;
+                true
+            }
+            _ => {
+                self.changed = Some((settings, now));
+                false
+            }
+        }
+    }
+}
+
+/// System: saves the LLM settings once a change stayed unchanged for
+/// [`LLM_SETTINGS_SAVE_DELAY`]; on exit they are saved along with the
+/// configuration.
+pub fn save_llm_settings(config: Res<PanelWidths>, mut pending: Local<PendingLlmSettings>) {
+    if !config.is_changed() && pending.changed.is_none() {
+        return;
+    }
+    if pending.update(LlmSettings::from_config(&config), Instant::now()) {
+        save_llm_settings_to_disk(&config);
+    }
+}
+
+/// System: on the first run without an API key, asks for it in a window and
+/// saves it in the keychain, or lets it be entered later in the LLM panel.
+pub fn draw_llm_key_prompt(
+    mut contexts: EguiContexts,
+    config: Option<ResMut<PanelWidths>>,
+    mut key: Local<String>,
+) {
+    let Some(mut config) = config else {
+        return;
+    };
+    if config.llm_key_prompted || !config.llm_key.is_empty() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let strings = config.strings();
+    egui::Window::new(strings.llm_key_prompt_title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(strings.llm_key_prompt);
+            ui.add(egui::TextEdit::singleline(&mut *key).password(true));
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!key.trim().is_empty(), egui::Button::new(strings.ok))
+                    .clicked()
+                {
+                    config.llm_key = key.trim().to_string();
+                    config.llm_remember_key = true;
+                    config.llm_key_prompted = true;
+                    key.clear();
+                    save_llm_settings_to_disk(&config);
+                }
+                if ui.button(strings.llm_key_prompt_later).clicked() {
+                    config.llm_key_prompted = true;
+                    key.clear();
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_is_only_saved_when_remembered() {
+        let mut config = PanelWidths {
+            llm_key: "secret-key".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(LlmSettings::from_config(&config).key, "secret-key");
+
+        config.llm_remember_key = false;
+        let settings = LlmSettings::from_config(&config);
+        assert!(settings.key.is_empty());
+        assert!(!settings.to_toml().unwrap().contains("secret-key"));
+    }
+
+    #[test]
+    fn test_debug_redacts_key() {
+        let settings = LlmSettings {
+            key: "secret-key".to_string(),
+            ..Default::default()
+        };
+        let debug = format!("{settings:?}");
+        assert!(!debug.contains("secret-key"));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("serial_bevy").join(LLM_SETTINGS_FILE);
+        let settings = LlmSettings {
+            key: "k".to_string(),
+            model: "glm-4.7".to_string(),
+            base_url: "http://localhost:11434/v1/chat/completions".to_string(),
+            ..Default::default()
+        };
+        settings.save(&path).unwrap();
+        assert_eq!(LlmSettings::load(&path).unwrap(), settings);
+
+        let mut config = PanelWidths::default();
+        LlmSettings::load(&path).unwrap().apply(&mut config);
+        assert_eq!(config.llm_key, "k");
+        assert_eq!(config.llm_model, "glm-4.7");
+        assert_eq!(
+            config.llm_base_url,
+            "http://localhost:11434/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn test_unversioned_file_is_migrated() {
+        let settings = LlmSettings::from_toml("key = \"k\"\nmodel = \"glm-4.6\"\n").unwrap();
+        assert_eq!(settings.version, LLM_SETTINGS_VERSION);
+        assert_eq!(settings.key, "k");
+        assert_eq!(settings.model, "glm-4.6");
+        assert_eq!(settings.base_url, DEFAULT_BASE_URL);
+        assert!(settings.remember_key);
+    }
+
+    #[test]
+    fn test_environment_key_is_a_fallback() {
+        let mut config = PanelWidths::default();
+        use_env_key(&mut config, None);
+        assert!(config.llm_key.is_empty());
+        use_env_key(&mut config, Some("env-key".to_string()));
+        assert_eq!(config.llm_key, "env-key");
+        // The key from the environment stays there.
+        assert!(settings_to_save(&config, Some("env-key")).key.is_empty());
+
+        // A saved key wins, and is saved again.
+        config.llm_key = "saved-key".to_string();
+        use_env_key(&mut config, Some("env-key".to_string()));
+        assert_eq!(config.llm_key, "saved-key");
+        assert_eq!(settings_to_save(&config, Some("env-key")).key, "saved-key");
+    }
+
+    #[test]
+    fn test_settings_are_saved_once_unchanged() {
+        let start = Instant::now();
+        let mut pending = PendingLlmSettings::default();
+        let loaded = LlmSettings::default();
+        assert!(!pending.update(loaded.clone(), start));
+
+        // Typing the key changes the settings every keystroke.
+        let mut typed = loaded;
+        for (i, c) in "abc".chars().enumerate() {
+            typed.key.push(c);
+            let at = start + LLM_SETTINGS_SAVE_DELAY / 2 * u32::try_from(i).unwrap();
+            assert!(!pending.update(typed.clone(), at));
+        }
+        let last = start + LLM_SETTINGS_SAVE_DELAY;
+        assert!(!pending.update(typed.clone(), last));
+        assert!(pending.update(typed.clone(), last + LLM_SETTINGS_SAVE_DELAY));
+        assert!(!pending.update(typed.clone(), last + LLM_SETTINGS_SAVE_DELAY * 3));
+
+        // A change undone before the delay is not saved.
+        let mut other = typed.clone();
+        other.model = "other".to_string();
+        let at = last + LLM_SETTINGS_SAVE_DELAY * 4;
+        assert!(!pending.update(other, at));
+        assert!(!pending.update(typed.clone(), at));
+        assert!(!pending.update(typed, at + LLM_SETTINGS_SAVE_DELAY * 2));
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        assert!(LlmSettings::from_toml("version = 99\n").is_err());
+    }
+}
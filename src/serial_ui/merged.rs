@@ -0,0 +1,222 @@
+//! # Merged View Module
+//!
+//! Receive view interleaving the traffic of all ports in one chronological
+//! list, built from the shared [`TrafficLog`].
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::serial::traffic::{TrafficEntry, TrafficLog};
+use crate::serial_ui::config::PanelWidths;
+use crate::serial_ui::ui::monospace_font;
+
+/// Format of the time shown in front of merged view lines.
+const MERGED_TIME_FORMAT: &str = "%H:%M:%S%.3f";
+
+/// Runtime state of the merged receive view.
+#[derive(Resource, Default, Debug)]
+pub struct MergedView {
+    /// Whether the merged view replaces the per-port view.
+    pub enabled: bool,
+    /// Ports unchecked in the port filter.
+    hidden_ports: BTreeSet<String>,
+}
+
+impl MergedView {
+    /// Returns true if the port's traffic is shown.
+    #[must_use]
+    pub fn is_port_visible(&self, port_name: &str) -> bool {
+        !self.hidden_ports.contains(port_name)
+    }
+
+    /// Shows or hides the port's traffic.
+    pub fn set_port_visible(&mut self, port_name: &str, visible: bool) {
+        if visible {
+            self.hidden_ports.remove(port_name);
+        } else {
+            self.hidden_ports.insert(port_name.to_string());
+        }
+    }
+}
+
+/// A port shown in the merged view.
+pub struct MergedPort {
+    /// Port name, as recorded in the traffic log.
+    pub name: String,
+    /// Alias or port name shown in front of its lines.
+    pub display_name: String,
+    /// Color of its lines.
+    pub color: egui::Color32,
+}
+
+/// Returns the lines of the visible entries as `(port name, line)` pairs.
+///
+/// Each line is prefixed with the entry time, the port's display name and the
+/// source indicator. Lines not containing `filter` (case-insensitive) are
+/// skipped.
+fn merged_lines<'a>(
+    entries: impl Iterator<Item = &'a TrafficEntry>,
+    view: &MergedView,
+    ports: &[MergedPort],
+    filter: &str,
+) -> Vec<(&'a str, String)> {
+    let filter = filter.to_lowercase();
+    let mut lines = Vec::new();
+    for entry in entries.filter(|entry| view.is_port_visible(&entry.port_name)) {
+        let display_name = ports
+            .iter()
+            .find(|port| port.name == entry.port_name)
+            .map_or(entry.port_name.as_str(), |port| port.display_name.as_str());
+        let time = entry.time.format(MERGED_TIME_FORMAT);
+        let text = entry.text.strip_suffix('\n').unwrap_or(&entry.text);
+        for line in text.split('\n') {
+            let line = line.trim_end_matches('\r');
+            if !filter.is_empty() && !line.to_lowercase().contains(&filter) {
+                continue;
+            }
+            lines.push((
+                entry.port_name.as_str(),
+                format!("[{time} {display_name} {}] {line}", entry.source),
+            ));
+        }
+    }
+    lines
+}
+
+/// Draws the merged view: port filter checkboxes above the interleaved traffic.
+pub fn draw_merged_view(
+    ui: &mut egui::Ui,
+    traffic: &mut TrafficLog,
+    view: &mut MergedView,
+    ports: &[MergedPort],
+    panel_widths: &PanelWidths,
+    filter: &str,
+    height: f32,
+) {
+    let strings = panel_widths.strings();
+    let top = ui.cursor().top();
+    ui.horizontal_wrapped(|ui| {
+        ui.label(strings.ports);
+        for port in ports {
+            let mut visible = view.is_port_visible(&port.name);
+            let text = egui::RichText::new(&port.display_name).color(port.color);
+            if ui
+                .checkbox(&mut visible, text)
+                .on_hover_text(&port.name)
+                .changed()
+            {
+                view.set_port_visible(&port.name, visible);
+            }
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .button(strings.clear)
+                .on_hover_text(strings.merged_clear_hover)
+                .clicked()
+            {
+                traffic.clear();
+            }
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} / {} {}",
+                    traffic.len(),
+                    traffic.capacity(),
+                    strings.merged_entries
+                ))
+                .weak(),
+            );
+        });
+    });
+    ui.separator();
+
+    let lines = merged_lines(traffic.entries(), view, ports, filter);
+    let font = monospace_font(ui, panel_widths.receive_font_size());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font));
+    let output_height = (height - (ui.cursor().top() - top)).max(0.0);
+
+    egui::ScrollArea::vertical()
+        .id_salt("merged_output")
+        .stick_to_bottom(true)
+        .auto_shrink([false, false])
+        .max_height(output_height)
+        .show_rows(ui, row_height, lines.len(), |ui, rows| {
+            if lines.is_empty() {
+                ui.heading(
+                    egui::RichText::new(strings.merged_placeholder).color(egui::Color32::GRAY),
+                );
+            }
+            for (port_name, line) in &lines[rows] {
+                let color = ports
+                    .iter()
+                    .find(|port| port.name == *port_name)
+                    .map(|port| port.color);
+                let mut text = egui::RichText::new(line).font(font.clone());
+                if let Some(color) = color {
+                    text = text.color(color);
+                }
+                ui.label(text);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::state::DataSource;
+    use chrono::{Local, TimeZone};
+
+    fn port(name: &str, display_name: &str) -> MergedPort {
+        MergedPort {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            color: egui::Color32::WHITE,
+        }
+    }
+
+    fn sample_log() -> TrafficLog {
+        let time = Local.with_ymd_and_hms(2025, 1, 2, 12, 0, 0).unwrap();
+        let mut log = TrafficLog::default();
+        log.record_at(time, "COM1", DataSource::Write, b"AT");
+        log.record_at(time, "COM2", DataSource::Read, b"AT\r\nOK\r\n");
+        log.record_at(time, "COM1", DataSource::Read, b"READY\n");
+        log
+    }
+
+    #[test]
+    fn test_merged_lines_are_prefixed_with_alias() {
+        let log = sample_log();
+        let ports = [port("COM1", "Modem"), port("COM2", "COM2")];
+        let lines = merged_lines(log.entries(), &MergedView::default(), &ports, "");
+        assert_eq!(
+            lines,
+            [
+                ("COM1", "[12:00:00.000 Modem T] AT".to_string()),
+                ("COM2", "[12:00:00.000 COM2 R] AT".to_string()),
+                ("COM2", "[12:00:00.000 COM2 R] OK".to_string()),
+                ("COM1", "[12:00:00.000 Modem R] READY".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merged_lines_port_and_text_filters() {
+        let log = sample_log();
+        let ports = [port("COM1", "COM1"), port("COM2", "COM2")];
+        let mut view = MergedView::default();
+        view.set_port_visible("COM1", false);
+        assert!(!view.is_port_visible("COM1"));
+
+        let lines = merged_lines(log.entries(), &view, &ports, "");
+        assert!(lines.iter().all(|(port, _)| *port == "COM2"));
+        assert_eq!(lines.len(), 2);
+
+        let lines = merged_lines(log.entries(), &view, &ports, "ok");
+        assert_eq!(lines.len(), 1);
+
+        view.set_port_visible("COM1", true);
+        let lines = merged_lines(log.entries(), &view, &ports, "a");
+        assert_eq!(lines.len(), 3);
+    }
+}
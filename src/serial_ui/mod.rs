@@ -2,27 +2,65 @@
 //!
 //! This module provides the UI plugin and composes focused submodules for:
 //! - persisted UI configuration
+//! - UI localization
+//! - persisted LLM settings
 //! - runtime-only global LLM state
 //! - main layout rendering
+//! - merged multi-port receive view
+//! - receive view text and selection
+//! - send sequence window
+//! - UI preferences window for font sizes
+//! - packet templates panel
+//! - files dropped onto the window
+//! - file picker window
+//! - status bar notifications
+//! - theme-aware colors
+//! - scroll lock of the receive views
 //! - keyboard/input systems
+//! - remappable keyboard shortcuts
 
 pub mod config;
+pub mod file_drop;
+pub mod file_picker;
 pub mod global_llm;
+pub mod i18n;
 pub mod input;
 pub mod layout;
+pub mod llm_settings;
+pub mod merged;
+pub mod notifications;
+pub mod palette;
+pub mod preferences;
+pub mod receive_text;
+pub mod sequence;
+pub mod shortcuts;
+pub mod tail;
+pub mod templates;
 pub mod ui;
 
 use bevy::prelude::*;
 use bevy_egui::{EguiPlugin, EguiPrimaryContextPass};
 
-use crate::serial::Selected;
-
-use config::{init_panel_widths, save_config_on_exit};
+use config::{
+    init_panel_widths, load_port_line_endings, save_config_on_exit, sync_font_preferences,
+};
+use file_drop::{FileDrop, draw_file_drop_window, receive_dropped_files};
 use global_llm::{
     GlobalLlmResponse, GlobalLlmState, process_global_llm_requests, receive_global_llm_responses,
 };
 use input::{history_data_checkout, send_cache_data};
 use layout::serial_ui;
+use llm_settings::{draw_llm_key_prompt, save_llm_settings};
+use merged::MergedView;
+use notifications::{
+    FlowStalls, UiNotifications, notify_log_clear_failures, notify_port_state_changes,
+    track_flow_stalls,
+};
+use palette::{UiPalette, update_ui_palette};
+use preferences::draw_ui_preferences_window;
+use sequence::{SequenceEditor, draw_sequence_window};
+use shortcuts::{KeybindingsConfig, ReceiveSearch, keyboard_shortcuts};
+use tail::ReceiveTails;
 use ui::{MarkdownViewerCache, draw_serial_context_ui};
 
 pub use config::PanelWidths;
@@ -38,25 +76,50 @@ impl Plugin for SerialUiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EguiPlugin::default())
             .insert_resource(ClearColor(Color::srgb(0.96875, 0.96875, 0.96875)))
-            .insert_resource(Selected::default())
             .insert_resource(MarkdownViewerCache::default())
             .insert_resource(GlobalLlmState::default())
             .insert_resource(GlobalLlmResponse::init())
+            .init_resource::<KeybindingsConfig>()
+            .init_resource::<ReceiveSearch>()
+            .init_resource::<ReceiveTails>()
+            .init_resource::<MergedView>()
+            .init_resource::<SequenceEditor>()
+            .init_resource::<FileDrop>()
+            .init_resource::<UiNotifications>()
+            .init_resource::<FlowStalls>()
+            .init_resource::<UiPalette>()
             .add_systems(Startup, (setup_camera_system, init_panel_widths))
             .add_systems(Last, save_config_on_exit)
             .add_systems(
                 EguiPrimaryContextPass,
                 (
+                    update_ui_palette,
                     serial_ui,
                     draw_serial_context_ui,
+                    draw_sequence_window,
+                    draw_ui_preferences_window,
+                    draw_file_drop_window,
+                    draw_llm_key_prompt,
                     send_cache_data,
                     history_data_checkout,
+                    keyboard_shortcuts,
                 )
                     .chain(),
             )
             .add_systems(
                 Update,
-                (process_global_llm_requests, receive_global_llm_responses).chain(),
+                (
+                    process_global_llm_requests,
+                    receive_global_llm_responses,
+                    save_llm_settings,
+                    receive_dropped_files,
+                    notify_port_state_changes,
+                    notify_log_clear_failures,
+                    track_flow_stalls,
+                    sync_font_preferences,
+                    load_port_line_endings,
+                )
+                    .chain(),
             );
     }
 }
@@ -0,0 +1,224 @@
+//! # Notifications Module
+//!
+//! Short messages shown in the status bar for a few seconds, such as a port
+//! timing out. Any system can push one through the [`UiNotifications`]
+//! resource. Ports whose sending is paused by flow control are tracked in
+//! [`FlowStalls`] for as long as the pause lasts.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use super::config::PanelWidths;
+use super::i18n::{Locale, strings};
+use crate::serial::flow::{FlowStall, FlowStallKind};
+use crate::serial::snapshot::lock_serial;
+use crate::serial::status::SerialIo;
+use crate::serial::watchdog::{PortStateChangeEvent, PortStateChangeKind};
+
+/// How long a notification stays in the status bar.
+pub const NOTIFICATION_DURATION: Duration = Duration::from_secs(4);
+
+/// Most notifications kept at a time; older ones are dropped first.
+pub const MAX_NOTIFICATIONS: usize = 5;
+
+/// A message shown in the status bar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Notification {
+    /// Text of the message.
+    pub text: String,
+    /// When the message was pushed.
+    pub at: Instant,
+}
+
+/// Queue of the notifications shown in the status bar.
+#[derive(Resource, Default, Debug)]
+pub struct UiNotifications {
+    /// Notifications, oldest first.
+    queue: VecDeque<Notification>,
+}
+
+impl UiNotifications {
+    /// Shows `text` in the status bar for [`NOTIFICATION_DURATION`].
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.push_at(text, Instant::now());
+    }
+
+    /// Shows `text` as if pushed at `at`.
+    pub fn push_at(&mut self, text: impl Into<String>, at: Instant) {
+        self.queue.push_back(Notification {
+            text: text.into(),
+            at,
+        });
+        while self.queue.len() > MAX_NOTIFICATIONS {
+            self.queue.pop_front();
+        }
+    }
+
+    /// Drops the notifications that expired at `now`.
+    pub fn prune(&mut self, now: Instant) {
+        self.queue.retain(|notification| {
+            now.saturating_duration_since(notification.at) < NOTIFICATION_DURATION
+        });
+    }
+
+    /// Returns the notifications shown, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Notification> {
+        self.queue.iter()
+    }
+
+    /// Returns true if no notification is shown.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// Ports whose sending is paused by flow control, by port name.
+#[derive(Resource, Default, Debug)]
+pub struct FlowStalls {
+    /// Why sending is paused, by port name.
+    stalls: BTreeMap<String, FlowStallKind>,
+}
+
+impl FlowStalls {
+    /// Applies a stall starting or ending.
+    pub fn apply(&mut self, event: &FlowStall) {
+        match event.kind {
+            Some(kind) => {
+                self.stalls.insert(event.port_name.clone(), kind);
+            }
+            None => {
+                self.stalls.remove(&event.port_name);
+            }
+        }
+    }
+
+    /// Returns why sending on `port_name` is paused, if it is.
+    #[must_use]
+    pub fn get(&self, port_name: &str) -> Option<FlowStallKind> {
+        self.stalls.get(port_name).copied()
+    }
+}
+
+/// System: tracks the ports whose sending is paused by flow control.
+pub fn track_flow_stalls(mut events: MessageReader<FlowStall>, mut stalls: ResMut<FlowStalls>) {
+    for event in events.read() {
+        stalls.apply(event);
+    }
+}
+
+/// System: notifies port state changes raised outside of the user's control.
+pub fn notify_port_state_changes(
+    mut events: MessageReader<PortStateChangeEvent>,
+    mut notifications: ResMut<UiNotifications>,
+) {
+    for event in events.read() {
+        match event.kind {
+            PortStateChangeKind::ReceiveTimeout => {
+                notifications.push(format!("{}: receive timeout", event.port_name));
+            }
+        }
+    }
+}
+
+/// System: notifies log files that could not be cleared, once their log
+/// writer thread reported it.
+pub fn notify_log_clear_failures(
+    panel_widths: Option<Res<PanelWidths>>,
+    ports: Query<&SerialIo>,
+    mut notifications: ResMut<UiNotifications>,
+) {
+    let strings =
+        panel_widths.map_or_else(|| strings(Locale::default()), |config| config.strings());
+    for port in &ports {
+        let mut serial = lock_serial(&port.0);
+        if let Some(error) = serial.data().take_log_clear_failure() {
+            notifications.push(format!(
+                "{}: {}: {error}",
+                serial.set.port_name, strings.clear_log_failed
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notifications_expire_and_are_capped() {
+        let start = Instant::now();
+        let mut notifications = UiNotifications::default();
+        notifications.push_at("first", start);
+        notifications.push_at("second", start + Duration::from_secs(2));
+
+        notifications.prune(start + NOTIFICATION_DURATION - Duration::from_millis(1));
+        assert_eq!(notifications.iter().count(), 2);
+        notifications.prune(start + NOTIFICATION_DURATION);
+        let texts: Vec<_> = notifications.iter().map(|n| n.text.as_str()).collect();
+        assert_eq!(texts, ["second"]);
+
+        for i in 0..10 {
+            notifications.push_at(i.to_string(), start);
+        }
+        assert_eq!(notifications.iter().count(), MAX_NOTIFICATIONS);
+        assert_eq!(notifications.iter().next().unwrap().text, "5");
+        notifications.prune(start + NOTIFICATION_DURATION * 2);
+        assert!(notifications.is_empty());
+    }
+
+    #[test]
+    fn test_log_clear_failures_are_notified() {
+        use crate::serial::Serials;
+        use crate::serial::status::spawn_ports;
+
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        let mut serials = Serials::new();
+        serials.sync_discovered_ports(&["COM1".to_string()], 1);
+        let com1 = serials.serial[0].clone();
+        let mut world = World::new();
+        world.init_resource::<UiNotifications>();
+        spawn_ports(&mut world, serials);
+        lock_serial(&com1)
+            .data()
+            .session_log_mut()
+            .open(sub.join("port.txt").to_string_lossy().into_owned());
+        lock_serial(&com1).data().session_log_mut().flush();
+
+        std::fs::remove_dir_all(&sub).unwrap();
+        lock_serial(&com1).data().clear_log().unwrap();
+        let notify = world.register_system(notify_log_clear_failures);
+        // The failure is retried once before it is notified.
+        for _ in 0..2 {
+            lock_serial(&com1).data().session_log_mut().flush();
+            world.run_system(notify).unwrap();
+        }
+        let texts: Vec<String> = world
+            .resource::<UiNotifications>()
+            .iter()
+            .map(|n| n.text.clone())
+            .collect();
+        assert_eq!(texts.len(), 1);
+        assert!(texts[0].starts_with("COM1: failed to clear the log file: "));
+    }
+
+    #[test]
+    fn test_flow_stalls_follow_events() {
+        let mut stalls = FlowStalls::default();
+        stalls.apply(&FlowStall {
+            port_name: "COM1".to_string(),
+            kind: Some(FlowStallKind::CtsLow),
+        });
+        assert_eq!(stalls.get("COM1"), Some(FlowStallKind::CtsLow));
+        assert_eq!(stalls.get("COM2"), None);
+
+        stalls.apply(&FlowStall {
+            port_name: "COM1".to_string(),
+            kind: None,
+        });
+        assert_eq!(stalls.get("COM1"), None);
+    }
+}
@@ -0,0 +1,138 @@
+//! # Palette Module
+//!
+//! Colors of the UI by role, such as the port states and the sent and
+//! received text.
+//!
+//! The palette follows the egui theme, so switching between the dark and
+//! light themes changes the colors from the next frame on. A high-contrast
+//! variant of each theme can be turned on in the top bar.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+use super::config::PanelWidths;
+
+/// Colors of the UI by role.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UiPalette {
+    /// An open port, or an active signal line.
+    pub port_open: egui::Color32,
+    /// A closed port, or an inactive signal line.
+    pub port_closed: egui::Color32,
+    /// A port in error.
+    pub port_error: egui::Color32,
+    /// Sent (`T`) data.
+    pub tx_text: egui::Color32,
+    /// Received (`R`) data.
+    pub rx_text: egui::Color32,
+    /// Errors, including error (`E`) log lines.
+    pub error_text: egui::Color32,
+    /// Warnings, such as a receive timeout.
+    pub warning_text: egui::Color32,
+    /// Successful results, such as a passed sequence.
+    pub success_text: egui::Color32,
+}
+
+impl UiPalette {
+    /// Palette of the dark theme.
+    pub const DARK: Self = Self {
+        port_open: egui::Color32::from_rgb(50, 200, 50),
+        port_closed: egui::Color32::GRAY,
+        port_error: egui::Color32::from_rgb(240, 70, 70),
+        tx_text: egui::Color32::from_rgb(100, 150, 255),
+        rx_text: egui::Color32::from_rgb(50, 200, 50),
+        error_text: egui::Color32::from_rgb(240, 70, 70),
+        warning_text: egui::Color32::from_rgb(230, 140, 30),
+        success_text: egui::Color32::from_rgb(50, 200, 50),
+    };
+
+    /// Palette of the light theme.
+    pub const LIGHT: Self = Self {
+        port_open: egui::Color32::from_rgb(0, 140, 0),
+        port_closed: egui::Color32::from_rgb(120, 120, 120),
+        port_error: egui::Color32::from_rgb(200, 20, 20),
+        tx_text: egui::Color32::from_rgb(30, 80, 200),
+        rx_text: egui::Color32::from_rgb(0, 130, 0),
+        error_text: egui::Color32::from_rgb(200, 20, 20),
+        warning_text: egui::Color32::from_rgb(180, 90, 0),
+        success_text: egui::Color32::from_rgb(0, 130, 0),
+    };
+
+    /// High-contrast palette of the dark theme.
+    pub const DARK_HIGH_CONTRAST: Self = Self {
+        port_open: egui::Color32::from_rgb(0, 255, 0),
+        port_closed: egui::Color32::from_rgb(200, 200, 200),
+        port_error: egui::Color32::from_rgb(255, 80, 80),
+        tx_text: egui::Color32::from_rgb(140, 200, 255),
+        rx_text: egui::Color32::from_rgb(0, 255, 0),
+        error_text: egui::Color32::from_rgb(255, 80, 80),
+        warning_text: egui::Color32::from_rgb(255, 210, 0),
+        success_text: egui::Color32::from_rgb(0, 255, 0),
+    };
+
+    /// High-contrast palette of the light theme.
+    pub const LIGHT_HIGH_CONTRAST: Self = Self {
+        port_open: egui::Color32::from_rgb(0, 90, 0),
+        port_closed: egui::Color32::from_rgb(60, 60, 60),
+        port_error: egui::Color32::from_rgb(160, 0, 0),
+        tx_text: egui::Color32::from_rgb(0, 0, 170),
+        rx_text: egui::Color32::from_rgb(0, 90, 0),
+        error_text: egui::Color32::from_rgb(160, 0, 0),
+        warning_text: egui::Color32::from_rgb(130, 60, 0),
+        success_text: egui::Color32::from_rgb(0, 90, 0),
+    };
+
+    /// Returns the palette of the dark or light theme.
+    #[must_use]
+    pub const fn new(dark_mode: bool, high_contrast: bool) -> Self {
+        match (dark_mode, high_contrast) {
+            (true, false) => Self::DARK,
+            (false, false) => Self::LIGHT,
+            (true, true) => Self::DARK_HIGH_CONTRAST,
+            (false, true) => Self::LIGHT_HIGH_CONTRAST,
+        }
+    }
+}
+
+impl Default for UiPalette {
+    fn default() -> Self {
+        Self::LIGHT
+    }
+}
+
+/// System: updates the palette to the current egui theme and the
+/// high-contrast setting.
+pub fn update_ui_palette(
+    mut contexts: EguiContexts,
+    config: Res<PanelWidths>,
+    mut palette: ResMut<UiPalette>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let dark_mode = ctx.style().visuals.dark_mode;
+    palette.set_if_neq(UiPalette::new(dark_mode, config.high_contrast));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_follows_theme_and_contrast() {
+        assert_eq!(UiPalette::new(true, false), UiPalette::DARK);
+        assert_eq!(UiPalette::new(false, false), UiPalette::default());
+        assert_eq!(UiPalette::new(false, true), UiPalette::LIGHT_HIGH_CONTRAST);
+
+        for palette in [
+            UiPalette::DARK,
+            UiPalette::LIGHT,
+            UiPalette::DARK_HIGH_CONTRAST,
+            UiPalette::LIGHT_HIGH_CONTRAST,
+        ] {
+            assert_ne!(palette.port_open, palette.port_closed);
+            assert_ne!(palette.port_open, palette.port_error);
+            assert_ne!(palette.tx_text, palette.rx_text);
+        }
+    }
+}
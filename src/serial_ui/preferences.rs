@@ -0,0 +1,130 @@
+//! # UI Preferences Window Module
+//!
+//! Window, opened from the gear button of the top bar, editing the font
+//! sizes and the monospace font of the UI and the data drawn by the receive
+//! view, and the port opened at startup. They are saved with the rest of the
+//! [`PanelWidths`]; the fonts are applied to egui by the font plugin.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+use crate::fonts::EguiFontConfig;
+use crate::serial_ui::PanelWidths;
+use crate::serial_ui::config::{
+    MAX_MAX_DISPLAY_KB, MAX_RECEIVE_FONT_SIZE, MIN_MAX_DISPLAY_KB, MIN_RECEIVE_FONT_SIZE,
+};
+
+/// System: draws the UI preferences window while it is open.
+pub fn draw_ui_preferences_window(
+    mut contexts: EguiContexts,
+    panel_widths: Option<ResMut<PanelWidths>>,
+    font_config: Option<Res<EguiFontConfig>>,
+) {
+    let Some(mut panel_widths) = panel_widths else {
+        return;
+    };
+    if !panel_widths.show_ui_preferences {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let strings = panel_widths.strings();
+    let families = font_config
+        .as_deref()
+        .map(|config| font_names(&config.fonts))
+        .unwrap_or_default();
+    let mut open = true;
+    egui::Window::new(strings.ui_preferences)
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let panel_widths = &mut *panel_widths;
+            let prefs = &mut panel_widths.font_prefs;
+            egui::Grid::new("ui_preferences")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    let sizes = [
+                        (strings.receive_view_size, &mut prefs.receive_view_size),
+                        (strings.label_size, &mut prefs.label_size),
+                        (strings.input_size, &mut prefs.input_size),
+                    ];
+                    for (label, size) in sizes {
+                        ui.label(label);
+                        ui.add(
+                            egui::Slider::new(size, MIN_RECEIVE_FONT_SIZE..=MAX_RECEIVE_FONT_SIZE)
+                                .step_by(0.5),
+                        );
+                        ui.end_row();
+                    }
+
+                    ui.label(strings.monospace_family);
+                    let selected = if prefs.monospace_family.is_empty() {
+                        strings.default_font
+                    } else {
+                        prefs.monospace_family.as_str()
+                    };
+                    egui::ComboBox::from_id_salt("monospace_family")
+                        .selected_text(selected.to_string())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut prefs.monospace_family,
+                                String::new(),
+                                strings.default_font,
+                            );
+                            for name in &families {
+                                ui.selectable_value(
+                                    &mut prefs.monospace_family,
+                                    name.clone(),
+                                    name.as_str(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(strings.max_display_size)
+                        .on_hover_text(strings.max_display_size_hover);
+                    ui.add(
+                        egui::Slider::new(
+                            &mut panel_widths.max_display_kb,
+                            MIN_MAX_DISPLAY_KB..=MAX_MAX_DISPLAY_KB,
+                        )
+                        .logarithmic(true),
+                    );
+                    ui.end_row();
+
+                    ui.label(strings.startup_port)
+                        .on_hover_text(strings.startup_port_hover);
+                    ui.horizontal(|ui| match panel_widths.startup_port.as_deref() {
+                        Some(port) => {
+                            ui.label(port);
+                            if ui.small_button(strings.forget_startup_port).clicked() {
+                                panel_widths.forget_startup_port();
+                            }
+                        }
+                        None => {
+                            ui.label(egui::RichText::new(strings.no_startup_port).weak());
+                        }
+                    });
+                    ui.end_row();
+                });
+        });
+    if !open {
+        panel_widths.show_ui_preferences = false;
+    }
+}
+
+/// Returns the names of the loaded fonts, in order.
+fn font_names(fonts: &egui::FontDefinitions) -> Vec<String> {
+    let mut names: Vec<String> = fonts
+        .families
+        .keys()
+        .filter_map(|family| match family {
+            egui::FontFamily::Name(name) => Some(name.to_string()),
+            _ => None,
+        })
+        .collect();
+    names.sort();
+    names
+}
@@ -0,0 +1,359 @@
+//! # Receive Text Module
+//!
+//! Text shown in the per-port receive views.
+//!
+//! A port's display text is turned into a single colored layout: ANSI colors
+//! are applied, lines with a `[... T/R/E]` header are colored by source, and
+//! lines hidden by the search filter are skipped. Each shown line remembers
+//! where it comes from in the display text, so a selection can be mapped
+//! back to the raw bytes of the entries it covers.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use bevy_egui::egui;
+use chrono::{DateTime, Local};
+use egui::text::{LayoutJob, TextFormat};
+
+use super::palette::UiPalette;
+use crate::serial::log::sanitize_port_name;
+
+/// A run of output text with its ANSI foreground and background colors.
+type OutputSegment = (String, Option<egui::Color32>, Option<egui::Color32>);
+
+/// Converts bytes to string, skipping control characters but preserving ANSI sequences.
+fn bytes_to_str_with_ansi(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let b = data[i];
+        if b == 0x00 || b == 0x0D {
+            i += 1;
+            continue;
+        }
+        if b < 0x80 {
+            result.push(b as char);
+            i += 1;
+            continue;
+        }
+        let len = if b & 0xE0 == 0xC0 {
+            2
+        } else if b & 0xF0 == 0xE0 {
+            3
+        } else if b & 0xF8 == 0xF0 {
+            4
+        } else {
+            i += 1;
+            continue;
+        };
+        if i + len <= data.len()
+            && let Ok(s) = std::str::from_utf8(&data[i..i + len])
+        {
+            result.push_str(s);
+        }
+        i += len;
+    }
+    result
+}
+
+/// Returns the `palette` color for a log line starting with a
+/// `[<timestamp> <source>]` header.
+///
+/// Returns `None` if the line has no recognized header.
+fn source_color_of_line(line: &str, palette: &UiPalette) -> Option<egui::Color32> {
+    let header = line.strip_prefix('[')?.split_once(']')?.0;
+    match header.rsplit_once(' ')?.1 {
+        "T" => Some(palette.tx_text),
+        "R" => Some(palette.rx_text),
+        "E" => Some(palette.error_text),
+        _ => None,
+    }
+}
+
+/// Returns the byte range of each `\n`-separated line of `text`, without
+/// the line feed.
+fn line_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut start = 0;
+    text.split('\n')
+        .map(|line| {
+            let range = start..start + line.len();
+            start = range.end + 1;
+            range
+        })
+        .collect()
+}
+
+/// How the lines of a [`ReceiveText`] are drawn and filtered.
+#[derive(Clone, Copy)]
+struct LineStyle<'a> {
+    /// Font of the text.
+    font: &'a egui::FontId,
+    /// Color of text without an ANSI or source color.
+    color: egui::Color32,
+    /// Lowercase text a line must contain to be shown, if not empty.
+    filter: &'a str,
+    /// Colors of the sources.
+    palette: &'a UiPalette,
+}
+
+/// A line of the receive view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ShownLine {
+    /// Character range in the shown text.
+    chars: Range<usize>,
+    /// Byte range in the display text.
+    bytes: Range<usize>,
+}
+
+/// Text shown in a receive view, with its colors.
+pub struct ReceiveText {
+    /// Shown text and its colored sections.
+    job: LayoutJob,
+    /// Number of characters in the shown text.
+    char_count: usize,
+    /// Shown lines.
+    lines: Vec<ShownLine>,
+}
+
+impl ReceiveText {
+    /// Builds the text shown for `display`, a port's display text, in `font`.
+    ///
+    /// Text without an ANSI or source color is drawn in `color`. A line with
+    /// a `[... T/R/E]` header sets the source color from `palette`, which
+    /// carries over to the following lines of the same entry. Empty lines and
+    /// lines not containing the lowercase `filter` are skipped.
+    #[must_use]
+    pub fn new(
+        display: &str,
+        font: &egui::FontId,
+        color: egui::Color32,
+        filter: &str,
+        palette: &UiPalette,
+    ) -> Self {
+        let mut shown = Self {
+            job: LayoutJob::default(),
+            char_count: 0,
+            lines: Vec::new(),
+        };
+        let line_bytes = line_ranges(display);
+        let text = bytes_to_str_with_ansi(display.as_bytes());
+        let mut parser = egui_sgr::AnsiParser::new();
+        let colored_segments = parser.parse(&text);
+
+        let style = LineStyle {
+            font,
+            color,
+            filter,
+            palette,
+        };
+        let mut current_line: Vec<OutputSegment> = Vec::new();
+        let mut source_color = None;
+        let mut index = 0;
+        let mut push_line = |shown: &mut Self, line: &[OutputSegment], index: usize| {
+            let bytes = line_bytes.get(index).cloned().unwrap_or_default();
+            shown.push_line(line, bytes, &style, &mut source_color);
+        };
+
+        for seg in &colored_segments {
+            let fg = seg.foreground_color;
+            let bg = seg.background_color;
+            let mut current_part = String::new();
+
+            for ch in seg.text.chars() {
+                if ch == '\n' {
+                    if !current_part.is_empty() {
+                        current_line.push((std::mem::take(&mut current_part), fg, bg));
+                    }
+                    push_line(&mut shown, &current_line, index);
+                    current_line.clear();
+                    index += 1;
+                } else {
+                    current_part.push(ch);
+                }
+            }
+
+            if !current_part.is_empty() {
+                current_line.push((current_part, fg, bg));
+            }
+        }
+        push_line(&mut shown, &current_line, index);
+        shown
+    }
+
+    /// Appends one line coming from the `bytes` of the display text.
+    fn push_line(
+        &mut self,
+        line: &[OutputSegment],
+        bytes: Range<usize>,
+        style: &LineStyle<'_>,
+        source_color: &mut Option<egui::Color32>,
+    ) {
+        let LineStyle {
+            font,
+            color,
+            filter,
+            palette,
+        } = *style;
+        let text: String = line.iter().map(|(text, _, _)| text.as_str()).collect();
+        if text.is_empty() {
+            return;
+        }
+        if let Some(color) = source_color_of_line(&text, palette) {
+            *source_color = Some(color);
+        }
+        if !filter.is_empty() && !text.to_lowercase().contains(filter) {
+            return;
+        }
+
+        if !self.lines.is_empty() {
+            self.job
+                .append("\n", 0.0, TextFormat::simple(font.clone(), color));
+            self.char_count += 1;
+        }
+        for (text, fg, bg) in line {
+            let mut format =
+                TextFormat::simple(font.clone(), fg.or(*source_color).unwrap_or(color));
+            if let Some(bg) = bg {
+                format.background = *bg;
+            }
+            self.job.append(text, 0.0, format);
+        }
+        let start = self.char_count;
+        self.char_count += text.chars().count();
+        self.lines.push(ShownLine {
+            chars: start..self.char_count,
+            bytes,
+        });
+    }
+
+    /// Returns the shown text.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.job.text
+    }
+
+    /// Returns the shown text and its colors, to be laid out.
+    #[must_use]
+    pub const fn job(&self) -> &LayoutJob {
+        &self.job
+    }
+
+    /// Returns the number of shown lines.
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the shown text in the character range `chars`.
+    #[must_use]
+    pub fn slice(&self, chars: Range<usize>) -> &str {
+        let text = self.text();
+        let byte = |index: usize| {
+            text.char_indices()
+                .nth(index)
+                .map_or(text.len(), |(byte, _)| byte)
+        };
+        let start = byte(chars.start);
+        &text[start..byte(chars.end).max(start)]
+    }
+
+    /// Returns the byte range of the display text covered by the shown lines
+    /// overlapping the character range `chars`, including lines hidden by the
+    /// filter in between.
+    #[must_use]
+    pub fn display_range(&self, chars: Range<usize>) -> Option<Range<usize>> {
+        let mut covered = self
+            .lines
+            .iter()
+            .filter(|line| line.chars.start < chars.end && chars.start < line.chars.end);
+        let first = covered.next()?;
+        let last = covered.last().unwrap_or(first);
+        Some(first.bytes.start..last.bytes.end)
+    }
+}
+
+/// Returns the file a receive view selection made at `time` is saved to:
+/// `<dir>/<port>_selection_<time>.txt`, with the port name made file-name
+/// safe. `dir` is usually the port's log directory.
+#[must_use]
+pub fn selection_path(dir: &Path, port_name: &str, time: DateTime<Local>) -> PathBuf {
+    dir.join(format!(
+        "{}_selection_{}.txt",
+        sanitize_port_name(port_name),
+        time.format("%Y%m%d_%H%M%S")
+    ))
+}
+
+/// Formats bytes as space-separated uppercase hex.
+#[must_use]
+pub fn hex_text(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_color_of_line() {
+        let palette = UiPalette::DARK;
+        assert_eq!(
+            source_color_of_line("[20250101 12:00:00.000 T]AT+GMR", &palette),
+            Some(palette.tx_text)
+        );
+        assert_eq!(
+            source_color_of_line("[20250101 12:00:00.000 R]OK", &palette),
+            Some(palette.rx_text)
+        );
+        assert_eq!(
+            source_color_of_line("[20250101 12:00:00.000 E]Port closed", &palette),
+            Some(palette.error_text)
+        );
+        assert_eq!(
+            source_color_of_line("[20250101 12:00:00.000 R]OK", &UiPalette::LIGHT),
+            Some(UiPalette::LIGHT.rx_text)
+        );
+    }
+
+    #[test]
+    fn test_source_color_of_line_without_header() {
+        let palette = UiPalette::default();
+        assert_eq!(source_color_of_line("plain data", &palette), None);
+        assert_eq!(source_color_of_line("[INFO] boot", &palette), None);
+        assert_eq!(
+            source_color_of_line("[20250101 12:00:00.000 X]?", &palette),
+            None
+        );
+        assert_eq!(source_color_of_line("[unterminated T", &palette), None);
+    }
+
+    #[test]
+    fn test_selection_maps_to_display_bytes() {
+        let display = "boot\r\n\nAT\nOK\n";
+        let font = egui::FontId::monospace(14.0);
+        let palette = UiPalette::default();
+        let shown = ReceiveText::new(display, &font, egui::Color32::WHITE, "", &palette);
+        assert_eq!(shown.text(), "boot\nAT\nOK");
+        assert_eq!(shown.line_count(), 3);
+
+        // "ot\nA" covers the lines "boot" and "AT".
+        assert_eq!(shown.slice(2..6), "ot\nA");
+        assert_eq!(shown.display_range(2..6), Some(0..9));
+        assert_eq!(&display[shown.display_range(8..10).unwrap()], "OK");
+        assert_eq!(shown.display_range(4..5), None);
+
+        let filtered = ReceiveText::new(display, &font, egui::Color32::WHITE, "ok", &palette);
+        assert_eq!(filtered.text(), "OK");
+        assert_eq!(filtered.display_range(0..1), Some(10..12));
+    }
+
+    #[test]
+    fn test_hex_text() {
+        assert_eq!(hex_text(&[0x01, 0xab, 0x0d]), "01 AB 0D");
+        assert_eq!(hex_text(&[]), "");
+    }
+}
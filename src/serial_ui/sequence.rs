@@ -0,0 +1,310 @@
+//! # Sequence Window Module
+//!
+//! Window for editing, saving, loading and running the send sequence of the
+//! selected port.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+use crate::serial::Selected;
+use crate::serial::data_types::DataType;
+use crate::serial::port::Serial;
+use crate::serial::sequence::{SendSequence, SequenceRun, SequenceStatus, SequenceStep};
+use crate::serial::snapshot::lock_serial;
+use crate::serial::status::SerialIo;
+use crate::serial_ui::config::PanelWidths;
+use crate::serial_ui::i18n::UiStrings;
+use crate::serial_ui::palette::UiPalette;
+
+/// Default file sequences are saved to and loaded from.
+pub const DEFAULT_SEQUENCE_PATH: &str = "config/sequences/sequence.toml";
+
+/// Runtime state of the sequence window.
+#[derive(Resource, Debug)]
+pub struct SequenceEditor {
+    /// Whether the window is shown.
+    pub open: bool,
+    /// Path of the TOML file to save to or load from.
+    path: String,
+    /// Result of the last save or load.
+    message: Option<String>,
+}
+
+impl Default for SequenceEditor {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: DEFAULT_SEQUENCE_PATH.to_string(),
+            message: None,
+        }
+    }
+}
+
+/// Change to the step list requested by a step's buttons.
+enum StepAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+}
+
+/// Draws the toolbar button toggling the sequence window.
+pub fn sequence_ui(ui: &mut egui::Ui, editor: &mut SequenceEditor, strings: &UiStrings) {
+    if ui
+        .selectable_label(editor.open, strings.sequence)
+        .on_hover_text(strings.sequence_hover)
+        .clicked()
+    {
+        editor.open = !editor.open;
+    }
+}
+
+/// System: draws the sequence window for the selected port.
+pub fn draw_sequence_window(
+    mut contexts: EguiContexts,
+    ports: Query<&SerialIo>,
+    selected: Res<Selected>,
+    mut editor: ResMut<SequenceEditor>,
+    config: Res<PanelWidths>,
+    palette: Res<UiPalette>,
+) {
+    if !editor.open {
+        return;
+    }
+    let port = selected.selected().and_then(|port| ports.get(port).ok());
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let strings = config.strings();
+    let mut open = editor.open;
+    egui::Window::new(strings.sequence_title)
+        .open(&mut open)
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            if let Some(port) = port {
+                let mut serial = lock_serial(&port.0);
+                draw_sequence_editor(ui, &mut serial, &mut editor, strings, &palette);
+            } else {
+                ui.label(egui::RichText::new(strings.sequence_select_port).weak());
+            }
+        });
+    editor.open = open;
+}
+
+/// Draws the sequence of `serial`: its steps, file controls and run controls.
+fn draw_sequence_editor(
+    ui: &mut egui::Ui,
+    serial: &mut Serial,
+    editor: &mut SequenceEditor,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    let running = serial
+        .data()
+        .sequence_run()
+        .is_some_and(SequenceRun::is_running);
+
+    ui.add_enabled_ui(!running, |ui| {
+        let sequence = serial.data().sequence_mut();
+        ui.horizontal(|ui| {
+            ui.label(strings.name);
+            ui.text_edit_singleline(&mut sequence.name);
+        });
+        ui.add_space(4.0);
+        draw_sequence_steps(ui, sequence, strings);
+        ui.horizontal(|ui| {
+            if ui.button(format!("+ {}", strings.send)).clicked() {
+                sequence
+                    .steps
+                    .push(SequenceStep::Send(String::new(), DataType::Utf8));
+            }
+            if ui.button(format!("+ {}", strings.step_wait)).clicked() {
+                sequence.steps.push(SequenceStep::WaitMs(200));
+            }
+            if ui.button(format!("+ {}", strings.step_wait_for)).clicked() {
+                sequence.steps.push(SequenceStep::WaitFor {
+                    pattern: "OK".to_string(),
+                    timeout_ms: 1000,
+                });
+            }
+        });
+        ui.separator();
+        draw_sequence_file(ui, sequence, editor, strings);
+    });
+
+    ui.separator();
+    draw_sequence_run(ui, serial, strings, palette);
+}
+
+/// Draws the editable step list.
+fn draw_sequence_steps(ui: &mut egui::Ui, sequence: &mut SendSequence, strings: &UiStrings) {
+    if sequence.steps.is_empty() {
+        ui.label(egui::RichText::new(strings.no_steps).weak());
+        return;
+    }
+
+    let count = sequence.steps.len();
+    let mut action = None;
+    egui::Grid::new("sequence_steps")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, step) in sequence.steps.iter_mut().enumerate() {
+                ui.label(format!("{}.", index + 1));
+                ui.horizontal(|ui| draw_step_fields(ui, index, step, strings));
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(index > 0, egui::Button::new("↑")).clicked() {
+                        action = Some(StepAction::MoveUp(index));
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("↓"))
+                        .clicked()
+                    {
+                        action = Some(StepAction::MoveDown(index));
+                    }
+                    if ui.button("✖").on_hover_text(strings.remove_step).clicked() {
+                        action = Some(StepAction::Remove(index));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    match action {
+        Some(StepAction::MoveUp(index)) => sequence.steps.swap(index, index - 1),
+        Some(StepAction::MoveDown(index)) => sequence.steps.swap(index, index + 1),
+        Some(StepAction::Remove(index)) => {
+            sequence.steps.remove(index);
+        }
+        None => {}
+    }
+}
+
+/// Draws the fields of one step.
+fn draw_step_fields(ui: &mut egui::Ui, index: usize, step: &mut SequenceStep, strings: &UiStrings) {
+    match step {
+        SequenceStep::Send(text, data_type) => {
+            ui.label(strings.send);
+            ui.add(
+                egui::TextEdit::singleline(text)
+                    .hint_text(r"AT\r\n")
+                    .desired_width(160.0),
+            );
+            egui::ComboBox::from_id_salt(("sequence_step_type", index))
+                .width(90.0)
+                .selected_text(data_type.as_str_en())
+                .show_ui(ui, |ui| {
+                    for option in DataType::ALL {
+                        ui.selectable_value(data_type, option, option.as_str_en());
+                    }
+                });
+        }
+        SequenceStep::WaitMs(ms) => {
+            ui.label(strings.step_wait);
+            ui.add(egui::DragValue::new(ms).range(0..=600_000).suffix(" ms"));
+        }
+        SequenceStep::WaitFor {
+            pattern,
+            timeout_ms,
+        } => {
+            ui.label(strings.step_wait_for);
+            ui.add(egui::TextEdit::singleline(pattern).desired_width(100.0));
+            ui.label(strings.step_timeout);
+            ui.add(
+                egui::DragValue::new(timeout_ms)
+                    .range(1..=600_000)
+                    .suffix(" ms"),
+            );
+        }
+    }
+}
+
+/// Draws the file path with the load and save buttons.
+fn draw_sequence_file(
+    ui: &mut egui::Ui,
+    sequence: &mut SendSequence,
+    editor: &mut SequenceEditor,
+    strings: &UiStrings,
+) {
+    ui.horizontal(|ui| {
+        ui.label(strings.file);
+        ui.add(egui::TextEdit::singleline(&mut editor.path).desired_width(220.0));
+        let path = Path::new(&editor.path);
+        if ui.button(strings.load).clicked() {
+            editor.message = Some(match SendSequence::load(path) {
+                Ok(loaded) => {
+                    *sequence = loaded;
+                    format!("{} {}", strings.loaded, path.display())
+                }
+                Err(e) => format!("{} {}: {e}", strings.load_failed, path.display()),
+            });
+        }
+        if ui.button(strings.save).clicked() {
+            editor.message = Some(match sequence.save(path) {
+                Ok(()) => format!("{} {}", strings.saved, path.display()),
+                Err(e) => format!("{} {}: {e}", strings.save_failed, path.display()),
+            });
+        }
+    });
+    if let Some(message) = &editor.message {
+        ui.label(egui::RichText::new(message).weak());
+    }
+}
+
+/// Draws the run and abort buttons with the progress of the current run.
+fn draw_sequence_run(
+    ui: &mut egui::Ui,
+    serial: &mut Serial,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    let is_open = serial.is_open();
+    let has_steps = !serial.data().sequence_mut().steps.is_empty();
+    let running = serial
+        .data()
+        .sequence_run()
+        .is_some_and(SequenceRun::is_running);
+
+    ui.horizontal(|ui| {
+        if running {
+            if ui.button(strings.abort).clicked()
+                && let Some(run) = serial.data().sequence_run_mut()
+            {
+                run.abort();
+            }
+        } else if ui
+            .add_enabled(is_open && has_steps, egui::Button::new(strings.run))
+            .on_disabled_hover_text(strings.sequence_run_disabled_hover)
+            .clicked()
+        {
+            serial.data().start_sequence();
+        }
+    });
+
+    let Some(run) = serial.data().sequence_run() else {
+        return;
+    };
+    let (done, total) = run.progress();
+    let fraction = if total == 0 {
+        1.0
+    } else {
+        done as f32 / total as f32
+    };
+    ui.add(egui::ProgressBar::new(fraction).text(format!("{done} / {total}")));
+    let (text, color) = match run.status() {
+        SequenceStatus::Running => (
+            run.current_step()
+                .map_or_else(String::new, SequenceStep::label),
+            ui.visuals().text_color(),
+        ),
+        SequenceStatus::Passed => (strings.sequence_passed.to_string(), palette.success_text),
+        SequenceStatus::Failed(reason) => (reason.clone(), palette.error_text),
+        SequenceStatus::Aborted => (
+            strings.sequence_aborted.to_string(),
+            ui.visuals().weak_text_color(),
+        ),
+    };
+    ui.label(egui::RichText::new(text).color(color));
+}
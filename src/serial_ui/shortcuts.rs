@@ -0,0 +1,370 @@
+//! # Keyboard Shortcuts Module
+//!
+//! Remappable keyboard shortcuts for common port actions, switching between
+//! ports and the receive view search bar.
+//!
+//! While a text field has keyboard focus, only the shortcuts meant for text
+//! fields (the receive view search and the send history search) are handled,
+//! so typing is never taken over by a port action.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+
+use crate::serial::Selected;
+use crate::serial::snapshot::lock_serial;
+use crate::serial::status::{PortEntities, SerialIo};
+
+use super::i18n::UiStrings;
+use super::ui::{request_port_close, request_port_open};
+
+/// A key combined with optional Ctrl and Shift modifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Keybinding {
+    /// The key that triggers the action.
+    pub key: KeyCode,
+    /// Whether Ctrl must be held.
+    pub ctrl: bool,
+    /// Whether Shift must be held.
+    pub shift: bool,
+}
+
+impl Keybinding {
+    /// Creates a binding for Ctrl + `key`.
+    #[must_use]
+    pub const fn ctrl(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: true,
+            shift: false,
+        }
+    }
+
+    /// Creates a binding for Ctrl + Shift + `key`.
+    #[must_use]
+    pub const fn ctrl_shift(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: true,
+            shift: true,
+        }
+    }
+
+    /// Returns true if the binding was triggered this frame.
+    #[must_use]
+    pub fn just_pressed(&self, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+        let ctrl_held = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+        let shift_held = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        keyboard_input.just_pressed(self.key) && ctrl_held == self.ctrl && shift_held == self.shift
+    }
+
+    /// Returns a human-readable label such as `Ctrl+F`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        let key = format!("{:?}", self.key);
+        let key = key
+            .strip_prefix("Key")
+            .or_else(|| key.strip_prefix("Digit"))
+            .unwrap_or(&key);
+        let ctrl = if self.ctrl { "Ctrl+" } else { "" };
+        let shift = if self.shift { "Shift+" } else { "" };
+        format!("{ctrl}{shift}{key}")
+    }
+}
+
+/// Keyboard shortcut bindings.
+///
+/// Insert a customized resource before adding the UI plugin to remap them.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct KeybindingsConfig {
+    /// Shows and focuses the receive view search bar.
+    pub focus_search: Keybinding,
+    /// Opens the selected port if it is closed, or closes it if it is open.
+    pub toggle_port: Keybinding,
+    /// Closes the selected port.
+    pub close_port: Keybinding,
+    /// Starts a reverse search of the send history, or finds the next older
+    /// match while searching.
+    pub history_search: Keybinding,
+    /// Selects the next port in the port list, wrapping around.
+    pub next_port: Keybinding,
+    /// Selects the previous port in the port list, wrapping around.
+    pub previous_port: Keybinding,
+    /// Continues the log of the selected port in a new file and clears its
+    /// receive view.
+    pub rotate_log: Keybinding,
+    /// Selects the first to ninth port in the port list, in order.
+    pub select_port: [Keybinding; 9],
+}
+
+impl Default for KeybindingsConfig {
+    fn default() -> Self {
+        Self {
+            focus_search: Keybinding::ctrl(KeyCode::KeyF),
+            toggle_port: Keybinding::ctrl(KeyCode::KeyO),
+            close_port: Keybinding::ctrl(KeyCode::KeyW),
+            history_search: Keybinding::ctrl(KeyCode::KeyR),
+            next_port: Keybinding::ctrl(KeyCode::Tab),
+            previous_port: Keybinding::ctrl_shift(KeyCode::Tab),
+            rotate_log: Keybinding::ctrl(KeyCode::KeyL),
+            select_port: [
+                KeyCode::Digit1,
+                KeyCode::Digit2,
+                KeyCode::Digit3,
+                KeyCode::Digit4,
+                KeyCode::Digit5,
+                KeyCode::Digit6,
+                KeyCode::Digit7,
+                KeyCode::Digit8,
+                KeyCode::Digit9,
+            ]
+            .map(Keybinding::ctrl),
+        }
+    }
+}
+
+impl KeybindingsConfig {
+    /// Returns `(binding label, description)` pairs for the help panel.
+    #[must_use]
+    pub fn entries(&self, strings: &UiStrings) -> Vec<(String, &'static str)> {
+        let select_port = format!(
+            "{} … {}",
+            self.select_port[0].label(),
+            self.select_port[8].label()
+        );
+        vec![
+            (self.toggle_port.label(), strings.shortcut_toggle_port),
+            (self.close_port.label(), strings.shortcut_close_port),
+            (self.next_port.label(), strings.shortcut_next_port),
+            (self.previous_port.label(), strings.shortcut_previous_port),
+            (select_port, strings.shortcut_select_port),
+            (self.rotate_log.label(), strings.shortcut_rotate_log),
+            (self.focus_search.label(), strings.shortcut_search),
+            (self.history_search.label(), strings.shortcut_history_search),
+        ]
+    }
+}
+
+/// Returns the port `step` places after `current` in `ports`, wrapping
+/// around; a negative `step` goes backwards.
+///
+/// Starts from the first port if `current` is not in `ports`, and returns
+/// `None` if there are no ports.
+#[must_use]
+pub fn cycle_port(ports: &[Entity], current: Option<Entity>, step: isize) -> Option<Entity> {
+    if ports.is_empty() {
+        return None;
+    }
+    let Some(index) = ports.iter().position(|port| Some(*port) == current) else {
+        return ports.first().copied();
+    };
+    let index = (index as isize + step).rem_euclid(ports.len() as isize) as usize;
+    Some(ports[index])
+}
+
+/// Runtime state of the receive view search bar.
+#[derive(Resource, Default, Debug)]
+pub struct ReceiveSearch {
+    /// Whether the search bar is shown.
+    pub visible: bool,
+    /// Text that displayed lines must contain.
+    pub query: String,
+    /// Set when the search field should take keyboard focus on the next frame.
+    pub focus_requested: bool,
+}
+
+impl ReceiveSearch {
+    /// Shows the search bar and requests focus for it.
+    pub fn open(&mut self) {
+        self.visible = true;
+        self.focus_requested = true;
+    }
+
+    /// Hides the search bar and clears the query.
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.query.clear();
+        self.focus_requested = false;
+    }
+
+    /// Returns the active filter, or an empty string if the bar is hidden.
+    #[must_use]
+    pub fn filter(&self) -> &str {
+        if self.visible { &self.query } else { "" }
+    }
+}
+
+/// System: handle keyboard shortcuts for the selected port and switching
+/// between ports.
+///
+/// The send history search is handled by the history system.
+pub fn keyboard_shortcuts(
+    entities: Res<PortEntities>,
+    ports: Query<&SerialIo>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut selected: ResMut<Selected>,
+    bindings: Res<KeybindingsConfig>,
+    mut search: ResMut<ReceiveSearch>,
+    mut contexts: EguiContexts,
+) {
+    if bindings.focus_search.just_pressed(&keyboard_input) {
+        search.open();
+    }
+
+    let typing = contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.wants_keyboard_input());
+    if typing {
+        return;
+    }
+
+    let step = if bindings.next_port.just_pressed(&keyboard_input) {
+        Some(1)
+    } else if bindings.previous_port.just_pressed(&keyboard_input) {
+        Some(-1)
+    } else {
+        None
+    };
+    let nth = bindings
+        .select_port
+        .iter()
+        .position(|binding| binding.just_pressed(&keyboard_input));
+    if step.is_some() || nth.is_some() {
+        let ports = entities.ordered();
+        let port = match (nth, step) {
+            (Some(nth), _) => ports.get(nth).copied(),
+            (None, Some(step)) => cycle_port(ports, selected.selected(), step),
+            (None, None) => None,
+        };
+        if let Some(port) = port {
+            selected.select(port);
+        }
+        return;
+    }
+
+    let toggle = bindings.toggle_port.just_pressed(&keyboard_input);
+    let close = bindings.close_port.just_pressed(&keyboard_input);
+    let rotate = bindings.rotate_log.just_pressed(&keyboard_input);
+    if !toggle && !close && !rotate {
+        return;
+    }
+
+    let Some(port) = selected.selected().and_then(|port| ports.get(port).ok()) else {
+        return;
+    };
+    let mut serial = lock_serial(&port.0);
+    if rotate {
+        serial.data().rotate_log();
+    } else if toggle && serial.is_close() {
+        request_port_open(&mut serial);
+    } else if (toggle || close) && serial.is_open() {
+        request_port_close(&mut serial);
+    }
+}
+
+/// Draws the `?` button whose tooltip lists the keyboard shortcuts.
+pub fn draw_shortcuts_help(ui: &mut egui::Ui, bindings: &KeybindingsConfig, strings: &UiStrings) {
+    ui.button("?").on_hover_ui(|ui| {
+        ui.strong(strings.keyboard_shortcuts);
+        egui::Grid::new("keyboard_shortcuts_help")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (label, description) in bindings.entries(strings) {
+                    ui.monospace(label);
+                    ui.label(description);
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keybinding_requires_ctrl() {
+        let binding = Keybinding::ctrl(KeyCode::KeyO);
+        let mut input = ButtonInput::<KeyCode>::default();
+
+        input.press(KeyCode::KeyO);
+        assert!(!binding.just_pressed(&input));
+
+        input.clear();
+        input.release(KeyCode::KeyO);
+        input.press(KeyCode::ControlLeft);
+        input.press(KeyCode::KeyO);
+        assert!(binding.just_pressed(&input));
+    }
+
+    #[test]
+    fn test_keybinding_label() {
+        assert_eq!(Keybinding::ctrl(KeyCode::KeyF).label(), "Ctrl+F");
+        assert_eq!(
+            Keybinding {
+                key: KeyCode::Digit1,
+                ctrl: false,
+                shift: false
+            }
+            .label(),
+            "1"
+        );
+        assert_eq!(
+            Keybinding::ctrl_shift(KeyCode::Tab).label(),
+            "Ctrl+Shift+Tab"
+        );
+    }
+
+    #[test]
+    fn test_keybinding_distinguishes_shift() {
+        let next = Keybinding::ctrl(KeyCode::Tab);
+        let previous = Keybinding::ctrl_shift(KeyCode::Tab);
+        let mut input = ButtonInput::<KeyCode>::default();
+
+        input.press(KeyCode::ControlLeft);
+        input.press(KeyCode::Tab);
+        assert!(next.just_pressed(&input));
+        assert!(!previous.just_pressed(&input));
+
+        input.clear();
+        input.release(KeyCode::Tab);
+        input.press(KeyCode::ShiftRight);
+        input.press(KeyCode::Tab);
+        assert!(!next.just_pressed(&input));
+        assert!(previous.just_pressed(&input));
+    }
+
+    #[test]
+    fn test_cycle_port_wraps_around() {
+        let [com1, com3, com7] = [1, 3, 7].map(|index| Entity::from_raw_u32(index).unwrap());
+        let ports = [com1, com3, com7];
+        assert_eq!(cycle_port(&ports, Some(com3), 1), Some(com7));
+        assert_eq!(cycle_port(&ports, Some(com7), 1), Some(com1));
+        assert_eq!(cycle_port(&ports, Some(com1), -1), Some(com7));
+        assert_eq!(cycle_port(&ports, None, 1), Some(com1));
+        assert_eq!(cycle_port(&[], Some(com1), 1), None);
+    }
+
+    #[test]
+    fn test_default_select_port_bindings() {
+        let bindings = KeybindingsConfig::default();
+        let labels: Vec<String> = bindings.select_port.iter().map(Keybinding::label).collect();
+        assert_eq!(labels.first().map(String::as_str), Some("Ctrl+1"));
+        assert_eq!(labels.last().map(String::as_str), Some("Ctrl+9"));
+    }
+
+    #[test]
+    fn test_receive_search_filter() {
+        let mut search = ReceiveSearch {
+            query: "OK".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(search.filter(), "");
+
+        search.open();
+        assert!(search.focus_requested);
+        assert_eq!(search.filter(), "OK");
+
+        search.close();
+        assert_eq!(search.filter(), "");
+    }
+}
@@ -0,0 +1,246 @@
+//! # Tail Module
+//!
+//! Scroll lock of the per-port receive views.
+//!
+//! A receive view follows new data while it is scrolled to the bottom. Once
+//! the user scrolls up it stays where it is, and a "Latest" button counts the
+//! lines received since; the button, the End key or scrolling back to the
+//! bottom resume following. The state is kept per port, so switching ports
+//! does not reset it.
+//!
+//! Only one window of a page's data is drawn at a time, the most recent one
+//! by default. Scrolling past the top of a window, or past the bottom of an
+//! earlier one, pages to the adjoining window at most once per
+//! [`WINDOW_LOAD_DEBOUNCE`], and the view lands on the seam between the two.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+/// Distance from the bottom, in points, still counted as at the bottom.
+pub const BOTTOM_TOLERANCE: f32 = 2.0;
+
+/// Shortest time between two window loads, in seconds, so that scrolling
+/// on at the top of the view does not page back every frame.
+pub const WINDOW_LOAD_DEBOUNCE: f64 = 0.3;
+
+/// Where the view is scrolled to after another window was loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollAnchor {
+    /// The top, where the later window continues.
+    Top,
+    /// The bottom, where the earlier window continues.
+    Bottom,
+}
+
+/// Scroll state of one port's receive view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReceiveTail {
+    /// Whether the view follows new data.
+    following: bool,
+    /// Scroll offset of the last frame.
+    last_offset: f32,
+    /// Number of lines when the view stopped following.
+    lines_at_lock: usize,
+    /// Whether the view should scroll to the bottom in the next frame.
+    jump: bool,
+    /// Window drawn, counted back from the most recent one.
+    window_index: usize,
+    /// When a window was last loaded, in seconds of UI time.
+    last_window_load: f64,
+    /// Where to scroll in the next frame after a window was loaded.
+    anchor: Option<ScrollAnchor>,
+}
+
+impl Default for ReceiveTail {
+    fn default() -> Self {
+        Self {
+            following: true,
+            last_offset: 0.0,
+            lines_at_lock: 0,
+            jump: false,
+            window_index: 0,
+            last_window_load: f64::NEG_INFINITY,
+            anchor: None,
+        }
+    }
+}
+
+impl ReceiveTail {
+    /// Returns true if the view follows new data.
+    #[must_use]
+    pub const fn is_following(&self) -> bool {
+        self.following
+    }
+
+    /// Updates the state from the frame just drawn: the vertical scroll
+    /// `offset`, the distance `to_bottom` left to scroll and the number of
+    /// `lines` shown.
+    ///
+    /// Scrolling up stops following; reaching the bottom of the most recent
+    /// window resumes it.
+    pub fn update(&mut self, offset: f32, to_bottom: f32, lines: usize) {
+        let at_bottom = to_bottom <= BOTTOM_TOLERANCE;
+        if self.following {
+            if offset < self.last_offset && !at_bottom {
+                self.following = false;
+                self.lines_at_lock = lines;
+            }
+        } else if at_bottom && self.window_index == 0 && self.anchor.is_none() {
+            self.following = true;
+        }
+        self.last_offset = offset;
+    }
+
+    /// Returns the number of lines received since the view stopped following.
+    #[must_use]
+    pub const fn new_lines(&self, lines: usize) -> usize {
+        if self.following {
+            0
+        } else {
+            lines.saturating_sub(self.lines_at_lock)
+        }
+    }
+
+    /// Resumes following and scrolls to the bottom in the next frame.
+    pub const fn jump_to_latest(&mut self) {
+        self.following = true;
+        self.jump = true;
+        self.window_index = 0;
+        self.anchor = None;
+    }
+
+    /// Returns true once after [`Self::jump_to_latest`].
+    pub const fn take_jump(&mut self) -> bool {
+        let jump = self.jump;
+        self.jump = false;
+        jump
+    }
+
+    /// Returns the window of the page drawn, counted back from the most
+    /// recent one.
+    #[must_use]
+    pub const fn window_index(&self) -> usize {
+        self.window_index
+    }
+
+    /// Draws the window before the current one and scrolls to its bottom,
+    /// unless a window was loaded less than [`WINDOW_LOAD_DEBOUNCE`] before
+    /// `now`, in seconds. Returns true if it was loaded.
+    pub fn load_earlier(&mut self, now: f64) -> bool {
+        if now - self.last_window_load < WINDOW_LOAD_DEBOUNCE {
+            return false;
+        }
+        self.following = false;
+        self.window_index += 1;
+        self.last_window_load = now;
+        self.anchor = Some(ScrollAnchor::Bottom);
+        true
+    }
+
+    /// Draws the window after the current one and scrolls to its top, unless
+    /// the most recent window is drawn or a window was loaded less than
+    /// [`WINDOW_LOAD_DEBOUNCE`] before `now`. Returns true if it was loaded.
+    pub fn load_later(&mut self, now: f64) -> bool {
+        if self.window_index == 0 || now - self.last_window_load < WINDOW_LOAD_DEBOUNCE {
+            return false;
+        }
+        self.window_index -= 1;
+        self.last_window_load = now;
+        self.anchor = Some(ScrollAnchor::Top);
+        true
+    }
+
+    /// Returns where to scroll once after a window was loaded.
+    pub const fn take_anchor(&mut self) -> Option<ScrollAnchor> {
+        self.anchor.take()
+    }
+
+    /// Draws the most recent window again, e.g. on another page.
+    pub const fn reset_window(&mut self) {
+        self.window_index = 0;
+        self.anchor = None;
+    }
+}
+
+/// Scroll state of the receive view of each port.
+#[derive(Resource, Default, Debug)]
+pub struct ReceiveTails(HashMap<String, ReceiveTail>);
+
+impl ReceiveTails {
+    /// Returns the scroll state of the named port's receive view.
+    pub fn port(&mut self, port_name: &str) -> &mut ReceiveTail {
+        self.0.entry(port_name.to_string()).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrolling_up_locks_until_bottom_or_jump() {
+        let mut tails = ReceiveTails::default();
+        let tail = tails.port("COM1");
+        tail.update(100.0, 0.0, 10);
+        tail.update(120.0, 0.0, 12);
+        assert!(tail.is_following());
+
+        tail.update(80.0, 40.0, 12);
+        assert!(!tail.is_following());
+        tail.update(80.0, 90.0, 15);
+        assert_eq!(tail.new_lines(15), 3);
+
+        tail.update(170.0, 0.0, 15);
+        assert!(tail.is_following());
+        assert_eq!(tail.new_lines(20), 0);
+
+        tail.update(50.0, 120.0, 20);
+        tail.jump_to_latest();
+        assert!(tail.is_following());
+        assert!(tail.take_jump());
+        assert!(!tail.take_jump());
+
+        // Other ports keep their own state.
+        assert!(tails.port("COM2").is_following());
+    }
+
+    #[test]
+    fn test_windows_page_with_debounce_and_anchor() {
+        let mut tail = ReceiveTail::default();
+        assert_eq!(tail.window_index(), 0);
+        assert!(!tail.load_later(0.0));
+        tail.update(100.0, 0.0, 10);
+        tail.update(0.0, 100.0, 10);
+
+        // Scrolling on at the top loads one window per debounce interval.
+        assert!(tail.load_earlier(10.0));
+        assert!(!tail.load_earlier(10.1));
+        assert_eq!(tail.window_index(), 1);
+        assert_eq!(tail.take_anchor(), Some(ScrollAnchor::Bottom));
+        assert_eq!(tail.take_anchor(), None);
+        assert!(tail.load_earlier(10.0 + WINDOW_LOAD_DEBOUNCE));
+        assert_eq!(tail.window_index(), 2);
+
+        // The bottom of an earlier window pages forward instead of following.
+        tail.take_anchor();
+        tail.update(100.0, 0.0, 10);
+        assert!(!tail.is_following());
+        assert!(tail.load_later(11.0));
+        assert_eq!(tail.window_index(), 1);
+        assert_eq!(tail.take_anchor(), Some(ScrollAnchor::Top));
+
+        tail.jump_to_latest();
+        assert_eq!(tail.window_index(), 0);
+        assert!(tail.is_following());
+    }
+
+    #[test]
+    fn test_growing_content_keeps_following() {
+        let mut tail = ReceiveTail::default();
+        // New data grows the content before the view catches up.
+        tail.update(0.0, 30.0, 5);
+        tail.update(30.0, 25.0, 8);
+        assert!(tail.is_following());
+    }
+}
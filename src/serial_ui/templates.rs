@@ -0,0 +1,182 @@
+//! # Templates Panel Module
+//!
+//! Side panel for editing the packet templates of the selected port and
+//! sending the encoded packets.
+
+use bevy_egui::egui;
+
+use crate::serial::PortSelection;
+use crate::serial::data_types::DataType;
+use crate::serial::encoding::decode_bytes;
+use crate::serial::packet::{Endian, MAX_FIELD_BYTES, PacketField, PacketTemplate};
+use crate::serial::port::Serial;
+use crate::serial::snapshot::lock_serial;
+
+use super::config::PanelWidths;
+use super::i18n::UiStrings;
+use super::ui::{WidgetKind, widget_id};
+
+/// Change to a template's field list requested by a field's buttons.
+enum FieldAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+}
+
+/// Draws the templates side panel, if shown.
+pub fn draw_templates_panel(
+    selection: &PortSelection<'_, '_>,
+    ctx: &egui::Context,
+    panel_widths: &PanelWidths,
+) {
+    if !panel_widths.show_templates_panel {
+        return;
+    }
+    egui::SidePanel::right("serial_ui_templates")
+        .resizable(true)
+        .default_width(280.0)
+        .min_width(220.0)
+        .show(ctx, |ui| {
+            let strings = panel_widths.strings();
+            ui.label(egui::RichText::new(strings.templates).strong());
+            ui.separator();
+            if let Some((_, port)) = selection.port() {
+                let mut serial = lock_serial(port);
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    draw_templates(ui, &mut serial, strings);
+                });
+            } else {
+                ui.label(egui::RichText::new(strings.templates_select_port).weak());
+            }
+        });
+}
+
+/// Draws the templates of `serial`, each with its fields and a send button.
+fn draw_templates(ui: &mut egui::Ui, serial: &mut Serial, strings: &UiStrings) {
+    let is_open = serial.is_open();
+    let port_name = serial.set.port_name.clone();
+    let mut remove = None;
+    let mut send = None;
+
+    for (index, template) in serial.data().templates_mut().iter_mut().enumerate() {
+        ui.push_id(
+            widget_id(&port_name, WidgetKind::Template).with(index),
+            |ui| {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut template.name)
+                                .hint_text(strings.name)
+                                .desired_width(140.0),
+                        );
+                        if ui
+                            .button("✖")
+                            .on_hover_text(strings.remove_template)
+                            .clicked()
+                        {
+                            remove = Some(index);
+                        }
+                    });
+                    draw_template_fields(ui, template, strings);
+                    if ui.button(strings.add_field).clicked() {
+                        template.fields.push(PacketField::default());
+                    }
+
+                    let packet = template.encode();
+                    ui.label(
+                        egui::RichText::new(decode_bytes(&packet, DataType::Hex))
+                            .monospace()
+                            .weak(),
+                    );
+                    if ui
+                        .add_enabled(
+                            is_open && !packet.is_empty(),
+                            egui::Button::new(strings.send),
+                        )
+                        .on_disabled_hover_text(strings.template_send_disabled_hover)
+                        .clicked()
+                    {
+                        send = Some(packet);
+                    }
+                });
+            },
+        );
+    }
+
+    if ui.button(strings.add_template).clicked() {
+        serial.data().templates_mut().push(PacketTemplate {
+            name: "Packet".to_string(),
+            fields: vec![PacketField {
+                name: "header".to_string(),
+                ..PacketField::default()
+            }],
+        });
+    }
+
+    if let Some(index) = remove {
+        serial.data().templates_mut().remove(index);
+    }
+    if let Some(packet) = send {
+        serial.data().send_packet(packet);
+    }
+}
+
+/// Draws the editable field table of a template.
+fn draw_template_fields(ui: &mut egui::Ui, template: &mut PacketTemplate, strings: &UiStrings) {
+    if template.fields.is_empty() {
+        ui.label(egui::RichText::new(strings.no_fields).weak());
+        return;
+    }
+
+    let count = template.fields.len();
+    let mut action = None;
+    egui::Grid::new("template_fields")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label(strings.name);
+            ui.label(strings.field_bytes);
+            ui.label(strings.field_value);
+            ui.label(strings.field_order);
+            ui.end_row();
+            for (index, field) in template.fields.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut field.name).desired_width(70.0));
+                ui.add(egui::DragValue::new(&mut field.size_bytes).range(1..=MAX_FIELD_BYTES));
+                let max_value = field.max_value();
+                field.value = field.value.min(max_value);
+                ui.add(egui::DragValue::new(&mut field.value).range(0..=max_value));
+                egui::ComboBox::from_id_salt(("template_field_endian", index))
+                    .width(44.0)
+                    .selected_text(field.endian.as_str())
+                    .show_ui(ui, |ui| {
+                        for endian in Endian::ALL {
+                            ui.selectable_value(&mut field.endian, endian, endian.as_str());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(index > 0, egui::Button::new("↑")).clicked() {
+                        action = Some(FieldAction::MoveUp(index));
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("↓"))
+                        .clicked()
+                    {
+                        action = Some(FieldAction::MoveDown(index));
+                    }
+                    if ui.button("✖").on_hover_text(strings.remove_field).clicked() {
+                        action = Some(FieldAction::Remove(index));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    match action {
+        Some(FieldAction::MoveUp(index)) => template.fields.swap(index, index - 1),
+        Some(FieldAction::MoveDown(index)) => template.fields.swap(index, index + 1),
+        Some(FieldAction::Remove(index)) => {
+            template.fields.remove(index);
+        }
+        None => {}
+    }
+}
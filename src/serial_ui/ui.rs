@@ -2,14 +2,48 @@
 //!
 //! This module provides individual UI components for serial port configuration and control.
 
-use crate::serial::Selected;
+use crate::serial::SerialConfig;
 use crate::serial::Serials;
-use crate::serial::port::{COMMON_BAUD_RATES, DataType, PortChannelData, Serial, TEXT_MODELS};
+use crate::serial::ai::LlmRequestEvent;
+use crate::serial::discovery::{PortScan, Runtime};
+use crate::serial::encoding::intel_hex::decode_from_ihex;
+use crate::serial::encoding::{
+    LineEnding, apply_line_ending, format_hex_input, preview_encoded, validate_hex_input,
+};
+use crate::serial::io::reopen_port;
+use crate::serial::llm::{
+    DEFAULT_BASE_URL, LlmConfig, LlmMessage, LlmProvider, TEMPERATURE_RANGE, TRANSCRIPT_DIR,
+    saved_transcripts,
+};
+use crate::serial::port::{
+    BREAK_DURATION_RANGE_MS, DEFAULT_BREAK_DURATION_MS, DEFAULT_FRAME_GAP, DEFAULT_SEND_RATE,
+    DEFAULT_SETTINGS_RULES, DataType, FRAME_GAP_RANGE_MS, MAX_TYPICAL_BAUD_RATE, PortChannelData,
+    SEND_RATE_RANGE, Serial, TEXT_MODELS, TimeoutAction, parse_baud_rate,
+};
+use crate::serial::port_data::{DEFAULT_ECHO_WINDOW_MS, LocalEcho, RING_KB_RANGE, SendPayload};
+use crate::serial::recording::recording_path;
+use crate::serial::repeat::{DEFAULT_REPEAT_COUNT, DEFAULT_REPEAT_INTERVAL, SendOptions};
+use crate::serial::response::ResponseTiming;
+use crate::serial::session::SESSION_EXTENSION;
+use crate::serial::snapshot::{PortSnapshot, PortSnapshots, lock_serial};
+use crate::serial::stats::SessionStats;
+use crate::serial::status::{SerialIo, SerialPortName};
+use crate::serial::throughput::MIN_THROUGHPUT_PAYLOAD;
+use crate::serial::timestamp::TimestampMode;
+use crate::serial::traffic::TrafficLog;
+use crate::serial::{PortSelection, Selected};
+use crate::serial_ui::file_picker::{FilePicker, PickerAction};
+use crate::serial_ui::i18n::{Locale, UiStrings};
+use crate::serial_ui::input::move_input_cursor_to_end;
+use crate::serial_ui::palette::UiPalette;
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
 
-use std::sync::MutexGuard;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 use tokio_serial::{DataBits, FlowControl, Parity, StopBits};
 
 /// Shared text edit height for serial and LLM input boxes.
@@ -23,6 +57,114 @@ pub const INPUT_TOOLBAR_HEIGHT: f32 = 28.0;
 
 const SIDEBAR_LABEL_WIDTH: f32 = 74.0;
 
+/// Received bytes per row of the heatmap view.
+pub const HEATMAP_BYTES_PER_ROW: usize = 32;
+
+/// Side of one heatmap cell, in points.
+const HEATMAP_CELL_SIZE: f32 = 12.0;
+
+/// Id salt of the serial send input, which completes from history on Tab.
+pub const SERIAL_INPUT_ID: &str = "serial_send_input";
+
+/// Per-port widget whose egui id is derived from the port name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WidgetKind {
+    /// Port alias field.
+    Alias,
+    /// Baud rate selector.
+    BaudRate,
+    /// Custom baud rate field.
+    CustomBaudRate,
+    /// Data bits selector.
+    DataBits,
+    /// Stop bits selector.
+    StopBits,
+    /// Flow control selector.
+    FlowControl,
+    /// Parity selector.
+    Parity,
+    /// Read timeout selector.
+    Timeout,
+    /// Receive timeout selector.
+    ReceiveTimeout,
+    /// Receive timeout action selector.
+    TimeoutAction,
+    /// Data type selector.
+    DataType,
+    /// Line ending selector.
+    LineEnding,
+    /// Timestamp format field.
+    TimestampFormat,
+    /// LLM prompt input.
+    LlmInput,
+    /// Receive view text.
+    ReceiveText,
+    /// Confirmation of the clear log file button.
+    ClearLog,
+    /// LLM request settings header.
+    LlmRequestSettings,
+    /// LLM provider selector.
+    LlmProvider,
+    /// Entry of the port in the port dropdown, dragged to reorder it.
+    PortDrag,
+    /// Break duration field.
+    BreakDuration,
+    /// Throughput test dialog.
+    ThroughputDialog,
+    /// Session history window.
+    SessionHistory,
+    /// Port error window.
+    PortError,
+    /// Log replay form.
+    LogReplay,
+    /// Receive view scroll area.
+    ReceiveOutput,
+    /// Received bytes heatmap.
+    Heatmap,
+    /// Packet templates, one id per template index.
+    Template,
+}
+
+impl WidgetKind {
+    /// All widget kinds.
+    pub const ALL: [Self; 27] = [
+        Self::Alias,
+        Self::BaudRate,
+        Self::CustomBaudRate,
+        Self::DataBits,
+        Self::StopBits,
+        Self::FlowControl,
+        Self::Parity,
+        Self::Timeout,
+        Self::ReceiveTimeout,
+        Self::TimeoutAction,
+        Self::DataType,
+        Self::LineEnding,
+        Self::TimestampFormat,
+        Self::LlmInput,
+        Self::ReceiveText,
+        Self::ClearLog,
+        Self::LlmRequestSettings,
+        Self::LlmProvider,
+        Self::PortDrag,
+        Self::BreakDuration,
+        Self::ThroughputDialog,
+        Self::SessionHistory,
+        Self::PortError,
+        Self::LogReplay,
+        Self::ReceiveOutput,
+        Self::Heatmap,
+        Self::Template,
+    ];
+}
+
+/// Returns the egui id of the `kind` widget of port `port`, unique per port
+/// and kind so that the widgets of one port never share state.
+#[must_use]
+pub fn widget_id(port: &str, kind: WidgetKind) -> egui::Id {
+    egui::Id::new(("port_widget", port, kind))
+}
+
 #[derive(Resource, Default)]
 pub struct MarkdownViewerCache(pub CommonMarkCache);
 
@@ -52,68 +194,487 @@ pub fn draw_sidebar_section(
     });
 }
 
+/// Preset colors offered for port labels.
+pub(crate) const PORT_LABEL_COLORS: [[u8; 3]; 8] = [
+    [220, 60, 60],
+    [230, 140, 30],
+    [200, 170, 0],
+    [50, 170, 50],
+    [0, 160, 170],
+    [60, 110, 230],
+    [150, 80, 210],
+    [210, 80, 160],
+];
+
+/// Returns the port's alias (or name) in its label color.
+pub fn port_label_text(
+    config: &crate::serial_ui::PanelWidths,
+    port: &PortSnapshot,
+) -> egui::RichText {
+    let text = egui::RichText::new(config.snapshot_display_name(port));
+    match config.snapshot_color(port) {
+        Some([r, g, b]) => text.color(egui::Color32::from_rgb(r, g, b)),
+        None => text,
+    }
+}
+
+/// How long an activity dot stays fully lit after a transfer.
+const ACTIVITY_FLASH: Duration = Duration::from_millis(150);
+
+/// How long an activity dot then takes to fade out.
+const ACTIVITY_FADE: Duration = Duration::from_millis(150);
+
+/// Returns how lit an activity dot is at `now`, from 0 (idle) to 1, given
+/// the time of the last transfer.
+fn activity_intensity(last: Option<Instant>, now: Instant) -> f32 {
+    let Some(last) = last else {
+        return 0.0;
+    };
+    let elapsed = now.saturating_duration_since(last);
+    match elapsed.checked_sub(ACTIVITY_FLASH) {
+        None => 1.0,
+        Some(fading) => 1.0 - (fading.as_secs_f32() / ACTIVITY_FADE.as_secs_f32()).min(1.0),
+    }
+}
+
+/// Draws the receive and transmit activity dots of a port.
+fn draw_activity_dots(ui: &mut egui::Ui, port: &PortSnapshot, now: Instant, palette: &UiPalette) {
+    for (last, color) in [
+        (port.last_rx, palette.rx_text),
+        (port.last_tx, palette.tx_text),
+    ] {
+        let color = palette
+            .port_closed
+            .lerp_to_gamma(color, activity_intensity(last, now));
+        ui.label(egui::RichText::new("●").small().color(color));
+    }
+}
+
+/// Returns how long ago a transfer happened, e.g. `12 s ago`, or `never`.
+pub fn idle_text(last: Option<Instant>, now: Instant) -> String {
+    last.map_or_else(
+        || "never".to_string(),
+        |last| format!("{} s ago", now.saturating_duration_since(last).as_secs()),
+    )
+}
+
+/// Returns the hover text of a port in the selector: its name, its last
+/// transfers and the settings changed since it was discovered.
+fn port_hover_text(port: &PortSnapshot, now: Instant) -> String {
+    let mut text = port.name.clone();
+    if port.missing {
+        text.push_str("\nNot found by the last port scan");
+    }
+    if port.is_open {
+        text.push_str(&format!(
+            "\nLast RX: {}\nLast TX: {}",
+            idle_text(port.last_rx, now),
+            idle_text(port.last_tx, now)
+        ));
+    }
+    if !port.settings_diff.is_empty() {
+        text.push_str("\nChanged settings:\n");
+        text.push_str(&port.settings_diff.join("\n"));
+    }
+    text
+}
+
 /// Draws the serial port selection dropdown and open/close button for the selected port.
-pub fn draw_select_serial_ui(ui: &mut egui::Ui, serials: &mut Serials, selected: &mut Selected) {
-    sidebar_row(ui, "Port", |ui, width| {
-        let selected_text = if selected.selected().is_empty() {
-            "Select a port".to_string()
-        } else {
-            selected.selected().to_string()
+///
+/// Right-clicking the dropdown edits the alias and color of the selected port.
+/// Ports in the dropdown can be dragged into a custom order, which is kept in
+/// the UI configuration. Ctrl+clicking ports in the dropdown marks them, so
+/// that their context menu ignores all of them at once.
+pub fn draw_select_serial_ui(
+    ui: &mut egui::Ui,
+    serials: &mut Serials,
+    selection: &mut PortSelection<'_, '_>,
+    config: &mut crate::serial_ui::PanelWidths,
+    snapshots: &PortSnapshots,
+    traffic: &mut TrafficLog,
+    palette: &UiPalette,
+) {
+    let strings = config.strings();
+    let now = Instant::now();
+    let mut drop_target = None;
+    let mut reorder = None;
+    let mut ignore = Vec::new();
+    let marks_id = egui::Id::new("port_ignore_marks");
+    let mut marks: BTreeSet<String> = ui.data(|d| d.get_temp(marks_id)).unwrap_or_default();
+    sidebar_row(ui, strings.port, |ui, width| {
+        let selected_snapshot = selection
+            .selected_name()
+            .and_then(|name| snapshots.get(name));
+        if let Some(port) = selected_snapshot {
+            draw_activity_dots(ui, port, now, palette);
+        }
+        let selected_port = selected_snapshot.map(|port| {
+            (
+                port.name.clone(),
+                port.label_key.clone(),
+                port_label_text(config, port),
+                port_hover_text(port, now),
+            )
+        });
+        let selected_text = match &selected_port {
+            Some((_, _, text, _)) => text.clone(),
+            None => egui::RichText::new(selection.selected_name().unwrap_or(strings.select_port)),
         };
 
-        egui::ComboBox::from_id_salt("serial_port_selector")
-            .width((width - 58.0).max(80.0))
+        let combo = egui::ComboBox::from_id_salt("serial_port_selector")
+            .width((ui.available_width().min(width) - 58.0).max(80.0))
             .selected_text(selected_text)
             .show_ui(ui, |ui| {
-                for serial in &mut serials.serial {
-                    let Ok(serial) = serial.lock() else {
-                        continue;
-                    };
+                let (_, dropped) = ui.dnd_drop_zone::<usize, _>(egui::Frame::NONE, |ui| {
+                    for (index, port) in snapshots.iter().enumerate() {
+                        let row = ui
+                            .dnd_drag_source(
+                                widget_id(&port.name, WidgetKind::PortDrag),
+                                index,
+                                |ui| {
+                                    ui.horizontal(|ui| {
+                                        draw_activity_dots(ui, port, now, palette);
+                                        let label = ui
+                                            .selectable_label(
+                                                selection.is_selected(&port.name)
+                                                    || marks.contains(&port.name),
+                                                port_label_text(config, port),
+                                            )
+                                            .on_hover_text(port_hover_text(port, now));
+                                        if label.clicked() {
+                                            if ui.input(|i| i.modifiers.command) {
+                                                if !marks.remove(&port.name) {
+                                                    marks.insert(port.name.clone());
+                                                }
+                                            } else {
+                                                marks.clear();
+                                                selection.select(&port.name);
+                                            }
+                                        }
+                                        label.context_menu(|ui| {
+                                            let (text, hover) =
+                                                if marks.len() > 1 && marks.contains(&port.name) {
+                                                    (
+                                                        strings.ignore_marked_ports,
+                                                        strings.ignore_marked_ports_hover,
+                                                    )
+                                                } else {
+                                                    (strings.ignore_port, strings.ignore_port_hover)
+                                                };
+                                            if ui.button(text).on_hover_text(hover).clicked() {
+                                                ignore = ports_to_ignore(&port.name, &marks);
+                                                ui.close();
+                                            }
+                                        });
+                                    });
+                                },
+                            )
+                            .response;
+                        if let Some(pointer) = ui.input(|i| i.pointer.interact_pos())
+                            && row.dnd_hover_payload::<usize>().is_some()
+                        {
+                            let target = if pointer.y < row.rect.center().y {
+                                index
+                            } else {
+                                index + 1
+                            };
+                            let y = if target == index {
+                                row.rect.top()
+                            } else {
+                                row.rect.bottom()
+                            };
+                            ui.painter().hline(
+                                row.rect.x_range(),
+                                y,
+                                ui.visuals().selection.stroke,
+                            );
+                            drop_target = Some(target);
+                        }
+                    }
+                });
+                if let (Some(from), Some(target)) = (dropped, drop_target) {
+                    reorder = Some((*from, target));
+                }
+            });
+        if let Some((port_name, key, _, hover)) = &selected_port {
+            let response = combo.response.on_hover_text(hover);
+            response.context_menu(|ui| {
+                draw_port_label_menu(ui, config, port_name, key);
+            });
+        }
+
+        if let Some((entity, port)) = selection.port() {
+            let port = Arc::clone(port);
+            open_ui(
+                ui,
+                &mut lock_serial(&port),
+                entity,
+                &mut selection.selected,
+                strings,
+            );
+            return;
+        }
+
+        ui.add_enabled(false, egui::Button::new(strings.open));
+    });
+
+    if let Some((from, target)) = reorder {
+        let to = if target > from { target - 1 } else { target };
+        serials.reorder(from, to);
+        config.set_port_order(serials.port_names());
+    }
+    if serials.port_order() != config.port_order.as_slice() {
+        serials.set_port_order(config.port_order.clone());
+    }
+    if !ignore.is_empty() {
+        marks.clear();
+    }
+    // A selected port that is ignored is replaced by the first port once its
+    // entity is despawned, see `select_first_port`.
+    for port_name in &ignore {
+        serials.ignore(port_name, traffic);
+    }
+    marks.retain(|port_name| snapshots.get(port_name).is_some());
+    ui.data_mut(|d| d.insert_temp(marks_id, marks));
+}
+
+/// Returns the ports ignored from the context menu of `port_name`: all the
+/// marked ports if it is one of them, or only itself.
+fn ports_to_ignore(port_name: &str, marks: &BTreeSet<String>) -> Vec<String> {
+    if marks.contains(port_name) {
+        marks.iter().cloned().collect()
+    } else {
+        vec![port_name.to_string()]
+    }
+}
+
+/// Draws the collapsible list of ignored ports, each with a button letting
+/// discovery add it again, and whether they stay ignored on the next launch.
+///
+/// Nothing is drawn while no port is ignored.
+pub fn draw_ignored_ports(
+    ui: &mut egui::Ui,
+    serials: &mut Serials,
+    config: &mut crate::serial_ui::PanelWidths,
+    scan: &mut PortScan,
+) {
+    let ignored = serials.ignored_ports();
+    if ignored.is_empty() {
+        return;
+    }
+    let strings = config.strings();
+    egui::CollapsingHeader::new(format!("{} ({})", strings.ignored_ports, ignored.len()))
+        .id_salt("ignored_ports")
+        .show(ui, |ui| {
+            for port_name in &ignored {
+                ui.horizontal(|ui| {
+                    ui.label(port_name);
                     if ui
-                        .selectable_label(
-                            selected.is_selected(&serial.set.port_name),
-                            &serial.set.port_name,
-                        )
+                        .small_button(strings.unignore_port)
+                        .on_hover_text(strings.unignore_port_hover)
                         .clicked()
                     {
-                        selected.select(&serial.set.port_name);
+                        config.unignore_port(serials, port_name);
+                        scan.request();
                     }
-                }
-            });
+                });
+            }
+            let mut remember = config.remember_ignored_ports;
+            if ui
+                .checkbox(&mut remember, strings.remember_ignored_ports)
+                .changed()
+            {
+                config.set_remember_ignored_ports(remember);
+            }
+        });
+}
 
-        for serial in &mut serials.serial {
-            let Ok(mut serial) = serial.lock() else {
-                continue;
-            };
-            if selected.is_selected(&serial.set.port_name) {
-                open_ui(ui, &mut serial, selected);
-                return;
+/// Draws the button refreshing the port list, busy while a scan is in flight.
+pub fn draw_port_refresh(ui: &mut egui::Ui, scan: &mut PortScan, strings: &UiStrings) {
+    sidebar_row(ui, strings.ports, |ui, _width| {
+        if scan.is_busy() {
+            ui.spinner();
+            ui.label(egui::RichText::new(strings.scanning).weak());
+        } else if ui
+            .button(strings.refresh)
+            .on_hover_text(strings.refresh_hover)
+            .clicked()
+        {
+            scan.request();
+        }
+    });
+}
+
+/// Draws the menu adding a virtual port that replays a session recording.
+///
+/// The recording is chosen in a file picker starting in `log_dir`, the log
+/// directory; why it could not be opened shows next to the menu.
+pub fn draw_open_replay(
+    ui: &mut egui::Ui,
+    serials: &mut Serials,
+    log_dir: &Path,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    let form_id = egui::Id::new("open_replay");
+    let mut form = ui
+        .data(|data| data.get_temp::<ReplayForm>(form_id))
+        .unwrap_or_else(|| ReplayForm::new(String::new()));
+    ui.menu_button(strings.open_replay, |ui| {
+        if form.ui(ui, log_dir, SESSION_EXTENSIONS, strings, palette) {
+            let path = form.path.clone();
+            let result = serials.add_session_replay(Path::new(&path), form.speed);
+            if let Err(e) = &result {
+                warn!("Failed to open replay {path}: {e}");
             }
+            form.set_error(result.err().map(|e| format!("{path}: {e}")));
+            ui.close();
+        }
+    })
+    .response
+    .on_hover_text(strings.open_replay_hover);
+    form.error_label(ui, strings, palette);
+    form.show_picker(ui.ctx(), form_id.with("picker"), strings, palette);
+    ui.data_mut(|data| data.insert_temp(form_id, form));
+}
+
+/// Draws the alias and color editor of the port labelled `key`.
+fn draw_port_label_menu(
+    ui: &mut egui::Ui,
+    config: &mut crate::serial_ui::PanelWidths,
+    port_name: &str,
+    key: &str,
+) {
+    let strings = config.strings();
+    ui.label(egui::RichText::new(port_name).weak());
+    let label = config.port_labels.get(key).cloned().unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        ui.label(strings.alias);
+        let mut alias = label.alias.clone().unwrap_or_default();
+        if ui
+            .add(
+                egui::TextEdit::singleline(&mut alias)
+                    .id(widget_id(port_name, WidgetKind::Alias))
+                    .desired_width(120.0),
+            )
+            .changed()
+        {
+            config.edit_port_label(key, |label| label.alias = Some(alias));
         }
+    });
 
-        ui.add_enabled(false, egui::Button::new("Open"));
+    ui.horizontal(|ui| {
+        ui.label(strings.color);
+        for [r, g, b] in PORT_LABEL_COLORS {
+            let color = egui::Color32::from_rgb(r, g, b);
+            let current = label.color == Some([r, g, b]);
+            let swatch = egui::RichText::new(if current { "●" } else { "○" }).color(color);
+            if ui.add(egui::Button::new(swatch).frame(false)).clicked() {
+                config.edit_port_label(key, |label| label.color = Some([r, g, b]));
+            }
+        }
     });
+
+    if ui.button(strings.reset).clicked() {
+        config.edit_port_label(key, |label| {
+            *label = crate::serial_ui::config::PortLabel::default()
+        });
+        ui.close();
+    }
 }
 
 /// Draws the baud rate selector.
-pub fn draw_baud_rate_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    sidebar_row(ui, "Baud Rate", |ui, width| {
-        egui::ComboBox::from_id_salt(format!("{}_baud", serial.set.port_name))
+///
+/// "Custom…" reveals a field for rates missing from the list, e.g. 250000 for
+/// DMX. An entered rate is applied when the field loses focus and is then
+/// offered in the list too.
+pub fn draw_baud_rate_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    config: &mut crate::serial_ui::PanelWidths,
+    palette: &UiPalette,
+) {
+    let strings = config.strings();
+    let custom_field_id = widget_id(&serial.set.port_name, WidgetKind::CustomBaudRate);
+    let custom_id = custom_field_id.with("text");
+    let mut custom: Option<String> = ui.data(|data| data.get_temp(custom_id));
+
+    sidebar_row(ui, strings.baud_rate, |ui, width| {
+        egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::BaudRate))
             .width(width)
             .selected_text(serial.set.baud_rate().to_string())
             .show_ui(ui, |ui| {
-                for baud_rate in COMMON_BAUD_RATES {
-                    ui.selectable_value(serial.set.baud_rate(), *baud_rate, baud_rate.to_string())
-                        .on_hover_text("Select baud rate");
+                for baud_rate in config.baud_rate_options() {
+                    if ui
+                        .selectable_value(serial.set.baud_rate(), baud_rate, baud_rate.to_string())
+                        .on_hover_text(strings.baud_rate_hover)
+                        .clicked()
+                    {
+                        custom = None;
+                    }
+                }
+                if ui
+                    .selectable_label(custom.is_some(), strings.custom_baud_rate)
+                    .clicked()
+                {
+                    custom = Some(serial.set.baud_rate.to_string());
                 }
             })
     });
+
+    if let Some(text) = &mut custom {
+        let response = sidebar_row(ui, "", |ui, width| {
+            ui.add(
+                egui::TextEdit::singleline(text)
+                    .id(custom_field_id)
+                    .hint_text(strings.custom_baud_rate_hint)
+                    .desired_width(width),
+            )
+        });
+        match parse_baud_rate(text) {
+            Ok(rate) => {
+                if rate > MAX_TYPICAL_BAUD_RATE {
+                    sidebar_row(ui, "", |ui, _width| {
+                        ui.label(
+                            egui::RichText::new(strings.high_baud_rate)
+                                .small()
+                                .color(palette.warning_text),
+                        );
+                    });
+                }
+                if response.lost_focus() {
+                    serial.set.baud_rate = rate;
+                    config.add_custom_baud_rate(rate);
+                }
+            }
+            Err(_) if !text.trim().is_empty() => {
+                sidebar_row(ui, "", |ui, _width| {
+                    ui.label(
+                        egui::RichText::new(strings.invalid_baud_rate)
+                            .small()
+                            .color(palette.error_text),
+                    );
+                });
+            }
+            Err(_) => {}
+        }
+    }
+
+    ui.data_mut(|data| match custom {
+        Some(text) => data.insert_temp(custom_id, text),
+        None => data.remove::<String>(custom_id),
+    });
 }
 
 /// Draws the data bits selector.
-pub fn draw_data_bits_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    sidebar_row(ui, "Data Bits", |ui, width| {
-        egui::ComboBox::from_id_salt(format!("{}_data", serial.set.port_name))
+pub fn draw_data_bits_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.data_bits, |ui, width| {
+        egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::DataBits))
             .width(width)
             .selected_text(serial.set.data_size().to_string())
             .show_ui(ui, |ui| {
@@ -130,9 +691,13 @@ pub fn draw_data_bits_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Se
 }
 
 /// Draws the stop bits selector.
-pub fn draw_stop_bits_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    sidebar_row(ui, "Stop Bits", |ui, width| {
-        egui::ComboBox::from_id_salt(format!("{}_stop", serial.set.port_name))
+pub fn draw_stop_bits_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.stop_bits, |ui, width| {
+        egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::StopBits))
             .width(width)
             .selected_text(serial.set.stop_bits().to_string())
             .show_ui(ui, |ui| {
@@ -144,9 +709,13 @@ pub fn draw_stop_bits_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Se
 }
 
 /// Draws the flow control selector.
-pub fn draw_flow_control_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    sidebar_row(ui, "Flow Ctrl", |ui, width| {
-        egui::ComboBox::from_id_salt(format!("{}_flow", serial.set.port_name))
+pub fn draw_flow_control_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.flow_control, |ui, width| {
+        egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::FlowControl))
             .width(width)
             .selected_text(serial.set.flow_control().to_string())
             .show_ui(ui, |ui| {
@@ -161,10 +730,36 @@ pub fn draw_flow_control_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_,
     });
 }
 
+/// Draws the receive ring buffer size limit, in KB.
+pub fn draw_receive_buffer_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.rx_buffer, |ui, _width| {
+        let mut kb = serial.data().max_ring_bytes() / 1024;
+        if ui
+            .add(
+                egui::DragValue::new(&mut kb)
+                    .range(RING_KB_RANGE)
+                    .suffix(" KB"),
+            )
+            .on_hover_text(strings.rx_buffer_hover)
+            .changed()
+        {
+            serial.data().set_max_ring_bytes(kb * 1024);
+        }
+    });
+}
+
 /// Draws the parity selector.
-pub fn draw_parity_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    sidebar_row(ui, "Parity", |ui, width| {
-        egui::ComboBox::from_id_salt(format!("{}_parity", serial.set.port_name))
+pub fn draw_parity_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.parity, |ui, width| {
+        egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::Parity))
             .width(width)
             .selected_text(serial.set.parity().to_string())
             .show_ui(ui, |ui| {
@@ -176,12 +771,16 @@ pub fn draw_parity_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Seria
 }
 
 /// Draws the timeout selector.
-pub fn draw_timeout_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    sidebar_row(ui, "Timeout", |ui, width| {
+pub fn draw_timeout_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.timeout, |ui, width| {
         // Convert timeout from Duration to milliseconds for display (capped at u64::MAX)
         let timeout_ms = serial.set.timeout.as_millis().min(u64::MAX.into()) as u64;
 
-        egui::ComboBox::from_id_salt(format!("{}_timeout", serial.set.port_name))
+        egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::Timeout))
             .width(width)
             .selected_text(format!("{timeout_ms} ms"))
             .show_ui(ui, |ui| {
@@ -198,158 +797,1071 @@ pub fn draw_timeout_selector(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Seri
     });
 }
 
-/// Draws the open/close port button.
-pub fn open_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>, selected: &mut Selected) {
-    if serial.is_close() {
-        if ui.button("Open").clicked() {
-            selected.select(&serial.set.port_name);
-            debug!("Opening port {}", serial.set.port_name);
-
-            // Clone settings before borrowing tx_channel to avoid borrow conflict
-            let settings = serial.set.clone();
-            if let Some(tx) = serial.tx_channel() {
-                match tx.send(PortChannelData::PortOpen(settings)) {
-                    Ok(_) => {
-                        debug!("Sent open port message");
-                    }
-                    Err(e) => warn!("Failed to open port: {e}"),
-                }
-                let _ = std::fs::create_dir_all("logs");
-                let time = chrono::Local::now().format("%Y%m%d_%H%M%S_%f").to_string();
-                let port_name = &serial.set.port_name;
-                let safe_port = port_name.trim_start_matches('/').replace('/', "_");
-                let file_name = format!("logs/{}_{}.txt", safe_port, time);
-                serial.data().add_source_file(file_name);
+/// Draws the send rate limit: off, or a number of bytes per second.
+pub fn draw_send_rate_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.tx_rate, |ui, _width| {
+        let mut limited = serial.set.send_rate_limit.is_some();
+        if ui
+            .checkbox(&mut limited, "")
+            .on_hover_text(strings.tx_rate_hover)
+            .changed()
+        {
+            serial.set.send_rate_limit = limited.then_some(DEFAULT_SEND_RATE);
+        }
+        match &mut serial.set.send_rate_limit {
+            Some(rate) => {
+                ui.add(
+                    egui::Slider::new(rate, SEND_RATE_RANGE)
+                        .logarithmic(true)
+                        .suffix(" B/s"),
+                )
+                .on_hover_text(strings.tx_rate_reopen);
+            }
+            None => {
+                ui.label(egui::RichText::new(strings.no_limit).weak());
             }
         }
-    } else if serial.is_open() && ui.button("Close").clicked() {
-        selected.select(&serial.set.port_name);
-        debug!("Closing port {}", serial.set.port_name);
-        let port_name = serial.set.port_name.clone();
+    });
+}
 
-        if let Some(tx) = serial.tx_channel() {
-            match tx.send(PortChannelData::PortClose(port_name)) {
-                Ok(_) => {
-                    debug!("Sent close port message");
+/// Draws the receive frame gap: off, or a number of milliseconds of silence
+/// that ends a frame.
+pub fn draw_frame_gap_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    sidebar_row(ui, strings.frame_gap, |ui, _width| {
+        let mut framed = serial.set.frame_gap.is_some();
+        if ui
+            .checkbox(&mut framed, "")
+            .on_hover_text(strings.frame_gap_hover)
+            .changed()
+        {
+            serial.set.frame_gap = framed.then_some(DEFAULT_FRAME_GAP);
+        }
+        match &mut serial.set.frame_gap {
+            Some(gap) => {
+                let mut millis = gap.as_millis() as u64;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut millis)
+                            .range(FRAME_GAP_RANGE_MS)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(strings.tx_rate_reopen)
+                    .changed()
+                {
+                    *gap = std::time::Duration::from_millis(millis);
                 }
-                Err(e) => warn!("Failed to close port: {e}"),
+            }
+            None => {
+                ui.label(egui::RichText::new(strings.off).weak());
             }
         }
+    });
+}
+
+/// Draws the receive timeout selector and the action taken when it elapses.
+pub fn draw_receive_timeout_selector(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    let label = |timeout: Option<std::time::Duration>| {
+        timeout.map_or_else(|| strings.off.to_string(), |t| format!("{} s", t.as_secs()))
+    };
+    sidebar_row(ui, strings.rx_timeout, |ui, width| {
+        egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::ReceiveTimeout))
+            .width(width)
+            .selected_text(label(serial.set.receive_timeout))
+            .show_ui(ui, |ui| {
+                let options = std::iter::once(None).chain(
+                    [1, 2, 5, 10, 30, 60].map(|secs| Some(std::time::Duration::from_secs(secs))),
+                );
+                for option in options {
+                    ui.selectable_value(&mut serial.set.receive_timeout, option, label(option));
+                }
+            })
+            .response
+            .on_hover_text(strings.rx_timeout_hover)
+    });
+    if serial.set.receive_timeout.is_some() {
+        sidebar_row(ui, strings.on_timeout, |ui, width| {
+            egui::ComboBox::from_id_salt(widget_id(
+                &serial.set.port_name,
+                WidgetKind::TimeoutAction,
+            ))
+            .width(width)
+            .selected_text(serial.set.timeout_action.as_str())
+            .show_ui(ui, |ui| {
+                for action in TimeoutAction::ALL {
+                    ui.selectable_value(&mut serial.set.timeout_action, action, action.as_str());
+                }
+            })
+        });
     }
 }
 
-/// Draws the serial setting status UI.
-pub fn draw_serial_setting_ui(ui: &mut egui::Ui, selected: &mut Selected) {
-    sidebar_row(ui, "Selected", |ui, width| {
-        let text = if selected.selected().is_empty() {
-            "No port selected"
-        } else {
-            selected.selected()
-        };
-        ui.add_sized(
-            [width, 20.0],
-            egui::Label::new(egui::RichText::new(text).weak()).truncate(),
-        );
+/// Draws the modem status line indicators (CTS, DSR, DCD, RI).
+///
+/// Asserted lines are green, deasserted ones gray. All dots are hollow while
+/// the state is unknown (port closed or no status lines).
+pub fn draw_signal_indicators(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    let signals = serial.data().signal_state();
+    sidebar_row(ui, strings.signals, |ui, _width| {
+        let lines = [
+            ("CTS", "Clear To Send", signals.map(|s| s.cts)),
+            ("DSR", "Data Set Ready", signals.map(|s| s.dsr)),
+            ("DCD", "Data Carrier Detect", signals.map(|s| s.dcd)),
+            ("RI", "Ring Indicator", signals.map(|s| s.ri)),
+        ];
+        for (name, description, state) in lines {
+            let (dot, color) = match state {
+                Some(true) => ("●", palette.port_open),
+                Some(false) => ("●", palette.port_closed),
+                None => ("○", palette.port_closed),
+            };
+            ui.label(egui::RichText::new(dot).color(color))
+                .on_hover_text(description);
+            ui.label(egui::RichText::new(name).small());
+        }
     });
 }
 
-/// Draws the serial context label in the tab bar.
-pub fn draw_serial_context_label_ui(
+/// Draws the break button of an open port, with the break duration.
+pub fn draw_break_button(
     ui: &mut egui::Ui,
-    selected: &mut Selected,
     serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
 ) {
-    if serial.is_open()
-        && ui
-            .selectable_label(
-                selected.is_selected(&serial.set.port_name),
-                egui::RichText::new(&serial.set.port_name),
-            )
-            .clicked()
-    {
-        selected.select(&serial.set.port_name);
+    if !serial.is_open() {
+        return;
     }
+    let id = widget_id(&serial.set.port_name, WidgetKind::BreakDuration);
+    let mut duration_ms: u32 = ui
+        .data(|data| data.get_temp(id))
+        .unwrap_or(DEFAULT_BREAK_DURATION_MS);
+    sidebar_row(ui, strings.line_break, |ui, _width| {
+        ui.add(
+            egui::DragValue::new(&mut duration_ms)
+                .range(BREAK_DURATION_RANGE_MS)
+                .suffix(" ms"),
+        );
+        if ui
+            .button(strings.send_break)
+            .on_hover_text(strings.send_break_hover)
+            .clicked()
+            && let Err(e) = serial.send_break(duration_ms)
+        {
+            warn!("Failed to send a break: {e}");
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(id, duration_ms));
 }
 
-/// Draws error windows for ports in error state.
-pub fn draw_serial_context_ui(serials: Query<&Serials>, mut context: EguiContexts) {
-    let Ok(serials) = serials.single() else {
-        return;
-    };
+/// Draws the latency measurement button and the last result.
+pub fn draw_latency_probe(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    runtime: &Runtime,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    serial.update_latency_measurement(runtime);
 
-    let Ok(ctx) = context.ctx_mut() else {
-        return;
-    };
+    sidebar_row(ui, strings.latency, |ui, _width| {
+        let measuring = serial.is_measuring_latency();
+        if ui
+            .add_enabled(
+                serial.is_open() && !measuring,
+                egui::Button::new(strings.measure_latency),
+            )
+            .on_hover_text(strings.measure_latency_hover)
+            .clicked()
+        {
+            serial.start_latency_measurement(runtime);
+        }
 
-    for serial in &serials.serial {
-        let Ok(mut serial) = serial.lock() else {
-            continue;
-        };
-        if serial.is_error() {
-            egui::Window::new(format!("{} Error", serial.set.port_name)).show(ctx, |ui| {
-                ui.label(
-                    egui::RichText::new(format!("{} Error", serial.set.port_name))
-                        .color(egui::Color32::RED)
-                        .strong(),
-                );
-                if ui.button("Clear Error").clicked() {
-                    serial.close();
+        if measuring {
+            ui.spinner();
+        } else if let Some(result) = serial.latency_result() {
+            match result {
+                Ok(latency) => {
+                    ui.label(format!("{:.1} ms", latency.as_secs_f64() * 1000.0));
                 }
-            });
+                Err(e) => {
+                    ui.label(egui::RichText::new(strings.failed).color(palette.error_text))
+                        .on_hover_text(e);
+                }
+            }
         }
-    }
+    });
 }
 
-/// Draws the data type selector.
-pub fn data_type_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    ui.add(egui::Label::new(egui::RichText::new("Data Type:")));
-    egui::ComboBox::from_id_salt(format!("{}_datatype", serial.set.port_name))
-        .width(90f32)
-        .selected_text(serial.data().data_type().as_str_en())
-        .show_ui(ui, |ui| {
-            for data_type in [
-                DataType::Hex,
-                DataType::Utf8,
-                DataType::Ascii,
-                DataType::Binary,
-                DataType::Utf16,
-                DataType::Utf32,
-                DataType::Gbk,
-            ] {
-                ui.selectable_value(serial.data().data_type(), data_type, data_type.as_str_en());
-            }
-        });
+/// Parameters of the throughput test dialog of a port, kept in egui's
+/// temporary data.
+#[derive(Clone, Copy, Debug)]
+struct ThroughputDialog {
+    /// Whether the dialog is shown.
+    open: bool,
+    /// Size of each test packet, in bytes.
+    payload_size: usize,
+    /// Number of test packets.
+    iterations: u32,
 }
 
-/// Draws the line feed toggle button.
-pub fn data_line_feed_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    ui.horizontal(|ui| {
-        let (button_text, hover_text) = if *serial.data().line_feed() {
-            ("No LF", "Disable line feed in sent data")
-        } else {
-            ("With LF", "Include line feed in sent data")
-        };
-
-        if ui.button(button_text).on_hover_text(hover_text).clicked() {
-            *serial.data().line_feed() = !*serial.data().line_feed();
+impl Default for ThroughputDialog {
+    fn default() -> Self {
+        Self {
+            open: false,
+            payload_size: 64,
+            iterations: 100,
         }
-    });
+    }
 }
 
-/// Draws the clear-log button for the current serial log view.
-pub fn clear_log_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    if ui
-        .button("Clear Log")
-        .on_hover_text("Clear the current serial log view")
-        .clicked()
-    {
+/// Draws the button opening the throughput test dialog, and the dialog.
+pub fn draw_throughput_probe(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    runtime: &Runtime,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    serial.update_throughput_test(runtime);
+
+    let id = widget_id(&serial.set.port_name, WidgetKind::ThroughputDialog);
+    let mut dialog: ThroughputDialog = ui.ctx().data(|data| data.get_temp(id).unwrap_or_default());
+    sidebar_row(ui, strings.throughput, |ui, _width| {
+        if ui
+            .selectable_label(dialog.open, strings.throughput_test)
+            .on_hover_text(strings.throughput_hover)
+            .clicked()
+        {
+            dialog.open = !dialog.open;
+        }
+    });
+
+    let mut open = dialog.open;
+    egui::Window::new(format!(
+        "{} – {}",
+        strings.throughput_test, serial.set.port_name
+    ))
+    .id(id.with("window"))
+    .open(&mut open)
+    .resizable(false)
+    .show(ui.ctx(), |ui| {
+        let testing = serial.is_testing_throughput();
+        ui.add_enabled_ui(!testing, |ui| {
+            egui::Grid::new("throughput_params")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(strings.packet_size);
+                    ui.add(
+                        egui::DragValue::new(&mut dialog.payload_size)
+                            .range(MIN_THROUGHPUT_PAYLOAD..=4096)
+                            .suffix(" B"),
+                    );
+                    ui.end_row();
+                    ui.label(strings.packets);
+                    ui.add(egui::DragValue::new(&mut dialog.iterations).range(1..=10_000));
+                    ui.end_row();
+                });
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    serial.is_open() && !testing,
+                    egui::Button::new(strings.start),
+                )
+                .on_disabled_hover_text(strings.open_port_to_test)
+                .clicked()
+            {
+                serial.start_throughput_test(runtime, dialog.payload_size, dialog.iterations);
+            }
+            if testing {
+                ui.spinner();
+            }
+        });
+        if !testing && let Some(result) = serial.throughput_result() {
+            match result {
+                Ok(result) => {
+                    // 10 bits per byte on the wire with 8N1 framing.
+                    ui.label(format!(
+                        "{:.0} B/s (≈ {:.0} baud at 8N1)",
+                        result.actual_bps,
+                        result.actual_bps * 10.0
+                    ));
+                    let dropped = format!("{}: {}", strings.dropped_packets, result.drop_count);
+                    if result.drop_count == 0 {
+                        ui.label(dropped);
+                    } else {
+                        ui.colored_label(palette.error_text, dropped);
+                    }
+                }
+                Err(e) => {
+                    ui.colored_label(palette.error_text, e);
+                }
+            }
+        }
+    });
+    dialog.open = open;
+    ui.ctx().data_mut(|data| data.insert_temp(id, dialog));
+}
+
+/// Formats a session length as `h:mm:ss`.
+fn session_duration_text(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Draws one row of the session history grid.
+fn session_row(ui: &mut egui::Ui, label: &str, session: &SessionStats, strings: &UiStrings) {
+    let now = chrono::Local::now();
+    ui.label(label);
+    ui.label(session.opened_at.format("%Y-%m-%d %H:%M:%S").to_string());
+    ui.label(session.closed_at.map_or_else(
+        || strings.still_open.to_string(),
+        |closed_at| closed_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+    ));
+    ui.label(session_duration_text(session.duration(now)));
+    ui.label(format!("{} B", session.bytes_received));
+    ui.label(format!("{} B", session.bytes_sent));
+    ui.label(session.error_count.to_string());
+    ui.end_row();
+}
+
+/// Draws the button opening the session history window, and the window:
+/// the counters of each session of the port, their total, and a button
+/// resetting the current session's counters.
+pub fn draw_session_history(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    let id = widget_id(&serial.set.port_name, WidgetKind::SessionHistory);
+    let mut open: bool = ui.ctx().data(|data| data.get_temp(id).unwrap_or_default());
+    sidebar_row(ui, strings.statistics, |ui, _width| {
+        if ui
+            .selectable_label(open, strings.history)
+            .on_hover_text(strings.history_hover)
+            .clicked()
+        {
+            open = !open;
+        }
+    });
+
+    egui::Window::new(format!("{} – {}", strings.history, serial.set.port_name))
+        .id(id.with("window"))
+        .open(&mut open)
+        .show(ui.ctx(), |ui| {
+            let data = serial.data();
+            let current = data
+                .current_session()
+                .filter(|session| session.closed_at.is_none());
+            egui::Grid::new(id.with("grid"))
+                .num_columns(7)
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in [
+                        "#",
+                        strings.opened_at,
+                        strings.closed_at,
+                        strings.duration,
+                        strings.rx_bytes_header,
+                        strings.tx_bytes_header,
+                        strings.errors,
+                    ] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    let first = data.earlier_session_count() + 1;
+                    for (index, session) in data.session_history().iter().enumerate() {
+                        session_row(ui, &(first + index).to_string(), session, strings);
+                    }
+                    if let Some(session) = &current {
+                        let label = (first + data.session_history().len()).to_string();
+                        session_row(ui, &label, session, strings);
+                    }
+                    if let Some(total) = data.lifetime_stats() {
+                        session_row(ui, strings.lifetime, &total, strings);
+                    }
+                });
+            if ui
+                .button(strings.reset_stats)
+                .on_hover_text(strings.reset_stats_hover)
+                .clicked()
+            {
+                data.reset_stats();
+            }
+        });
+    ui.ctx().data_mut(|data| data.insert_temp(id, open));
+}
+
+/// Draws the open/close port button of `serial`, whose entity is `port`.
+pub fn open_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    port: Entity,
+    selected: &mut Selected,
+    strings: &UiStrings,
+) {
+    if serial.is_close() {
+        let problems = serial.set.problems(DEFAULT_SETTINGS_RULES);
+        if ui
+            .add_enabled(problems.is_empty(), egui::Button::new(strings.open))
+            .on_disabled_hover_text(problems.join("\n"))
+            .clicked()
+        {
+            selected.select(port);
+            request_port_open(serial);
+        }
+    } else if serial.is_open() && ui.button(strings.close).clicked() {
+        selected.select(port);
+        request_port_close(serial);
+    }
+}
+
+/// Asks the port thread to open the port and starts a new log file.
+pub fn request_port_open(serial: &mut Serial) {
+    debug!("Opening port {}", serial.set.port_name);
+    match serial.open_session() {
+        Ok(path) => debug!("Sent open port message, logging to {path}"),
+        Err(e) => warn!("Failed to open port: {e}"),
+    }
+}
+
+/// Asks the port thread to close the port.
+pub fn request_port_close(serial: &mut Serial) {
+    debug!("Closing port {}", serial.set.port_name);
+    match serial.request_close() {
+        Ok(()) => debug!("Sent close port message"),
+        Err(e) => warn!("Failed to close port: {e}"),
+    }
+}
+
+/// Draws the serial setting status UI.
+pub fn draw_serial_setting_ui(ui: &mut egui::Ui, selected_name: Option<&str>, strings: &UiStrings) {
+    sidebar_row(ui, strings.selected, |ui, width| {
+        let text = selected_name.unwrap_or(strings.no_port_selected);
+        ui.add_sized(
+            [width, 20.0],
+            egui::Label::new(egui::RichText::new(text).weak()).truncate(),
+        );
+    });
+}
+
+/// Draws the serial context label in the tab bar.
+///
+/// Returns true if the tab was clicked.
+pub fn draw_serial_context_label_ui(
+    ui: &mut egui::Ui,
+    selection: &mut PortSelection<'_, '_>,
+    port: &PortSnapshot,
+    config: &crate::serial_ui::PanelWidths,
+) -> bool {
+    let clicked = port.is_open
+        && ui
+            .selectable_label(
+                selection.is_selected(&port.name),
+                port_label_text(config, port),
+            )
+            .on_hover_text(&port.name)
+            .clicked();
+    if clicked {
+        selection.select(&port.name);
+    }
+    clicked
+}
+
+/// Draws error windows for ports in error state.
+///
+/// Each shows the port's latest error, when it was reported and the recent
+/// error lines of its log. Retrying opens the port again on a new port
+/// thread; closing leaves it closed.
+pub fn draw_serial_context_ui(
+    ports: Query<(&SerialPortName, &SerialIo)>,
+    mut context: EguiContexts,
+    config: Res<crate::serial_ui::PanelWidths>,
+    snapshots: Res<PortSnapshots>,
+    palette: Res<UiPalette>,
+    runtime: Res<Runtime>,
+    serial_config: Res<SerialConfig>,
+) {
+    let Ok(ctx) = context.ctx_mut() else {
+        return;
+    };
+
+    for port in snapshots.iter() {
+        if !port.is_error() {
+            continue;
+        }
+        let Some((_, io)) = ports.iter().find(|(name, _)| name.0 == port.name) else {
+            continue;
+        };
+        let serial = &io.0;
+        let name = config.snapshot_display_name(port);
+        let strings = config.strings();
+        egui::Window::new(format!("{name} {}", strings.error))
+            .id(widget_id(&port.name, WidgetKind::PortError))
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(format!("{name} {}", strings.error))
+                        .color(palette.port_error)
+                        .strong(),
+                );
+                if let Some(error) = &port.last_error {
+                    match port.last_error_at {
+                        Some(time) => ui.label(format!("{} {error}", time.format("%H:%M:%S"))),
+                        None => ui.label(error),
+                    };
+                }
+                if !port.recent_errors.is_empty() {
+                    ui.separator();
+                    ui.label(egui::RichText::new(strings.recent_errors).small());
+                    for line in &port.recent_errors {
+                        ui.label(egui::RichText::new(line).monospace().small());
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(strings.retry_open)
+                        .on_hover_text(strings.retry_open_hover)
+                        .clicked()
+                    {
+                        reopen_port(
+                            &mut lock_serial(serial),
+                            &runtime,
+                            serial_config.channel_buffer_size,
+                        );
+                    }
+                    if ui
+                        .button(strings.close)
+                        .on_hover_text(strings.close_error_hover)
+                        .clicked()
+                    {
+                        lock_serial(serial).close();
+                    }
+                });
+            });
+    }
+}
+
+/// Draws the data type selector.
+pub fn data_type_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>, strings: &UiStrings) {
+    ui.add(egui::Label::new(egui::RichText::new(strings.data_type)));
+    egui::ComboBox::from_id_salt(widget_id(&serial.set.port_name, WidgetKind::DataType))
+        .width(90f32)
+        .selected_text(serial.data().data_type().as_str_en())
+        .show_ui(ui, |ui| {
+            for data_type in DataType::ALL {
+                ui.selectable_value(serial.data().data_type(), data_type, data_type.as_str_en())
+                    .on_hover_text(data_type.description());
+            }
+        });
+}
+
+/// Draws the line ending selector of sent data, persisted per port.
+pub fn data_line_feed_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    config: &mut crate::serial_ui::PanelWidths,
+) {
+    let strings = config.strings();
+    let port_name = serial.set.port_name.clone();
+    let current = *serial.data().line_ending();
+    let mut line_ending = current;
+    let label = |line_ending: LineEnding| match line_ending {
+        LineEnding::None => strings.no_line_ending.to_string(),
+        line_ending => line_ending.to_string(),
+    };
+    egui::ComboBox::from_id_salt(widget_id(&port_name, WidgetKind::LineEnding))
+        .width(70f32)
+        .selected_text(label(line_ending))
+        .show_ui(ui, |ui| {
+            for option in LineEnding::ALL {
+                ui.selectable_value(&mut line_ending, option, label(option));
+            }
+        })
+        .response
+        .on_hover_text(strings.line_ending_hover);
+
+    if line_ending != current {
+        *serial.data().line_ending() = line_ending;
+        if line_ending == LineEnding::default() {
+            config.line_endings.remove(&port_name);
+        } else {
+            config.line_endings.insert(port_name, line_ending);
+        }
+    }
+}
+
+/// Draws the escape sequence parsing toggle for text data types.
+pub fn escape_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>, strings: &UiStrings) {
+    if !matches!(*serial.data().data_type(), DataType::Utf8 | DataType::Ascii) {
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        let parse_escapes = *serial.data().parse_escapes();
+        let (button_text, hover_text) = if parse_escapes {
+            (strings.escapes_on, strings.escapes_on_hover)
+        } else {
+            (strings.escapes_off, strings.escapes_off_hover)
+        };
+
+        if ui.button(button_text).on_hover_text(hover_text).clicked() {
+            *serial.data().parse_escapes() = !parse_escapes;
+        }
+    });
+}
+
+/// Draws the encoding auto-detection toggle for UTF-8 data.
+pub fn auto_encoding_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    if *serial.data().data_type() != DataType::Utf8 {
+        return;
+    }
+
+    let detected = serial.data().is_gbk_detected();
+    let auto_detect = serial.data().auto_detect_encoding();
+    let hover = if *auto_detect && detected {
+        strings.gbk_detected_hover
+    } else {
+        strings.auto_gbk_hover
+    };
+    ui.checkbox(auto_detect, strings.auto_gbk)
+        .on_hover_text(hover);
+}
+
+/// Draws the buttons clearing the receive view, and the receive view with
+/// the current log file.
+///
+/// Clearing the log file asks for confirmation first, as it cannot be
+/// undone.
+pub fn clear_log_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>, strings: &UiStrings) {
+    let id = widget_id(&serial.set.port_name, WidgetKind::ClearLog);
+    let confirming = ui.data(|data| data.get_temp::<bool>(id).unwrap_or(false));
+    if confirming {
+        ui.label(strings.clear_log_confirm);
+        if ui.button(strings.ok).clicked() {
+            if let Err(e) = serial.data().clear_log() {
+                warn!(
+                    "Failed to clear the log file of {}: {e}",
+                    serial.set.port_name
+                );
+            }
+            ui.data_mut(|data| data.remove::<bool>(id));
+        }
+        if ui.button(strings.cancel).clicked() {
+            ui.data_mut(|data| data.remove::<bool>(id));
+        }
+    } else if ui
+        .button(strings.clear_log_file)
+        .on_hover_text(strings.clear_log_file_hover)
+        .clicked()
+    {
+        ui.data_mut(|data| data.insert_temp(id, true));
+    }
+    if ui
+        .button(strings.clear_display)
+        .on_hover_text(strings.clear_display_hover)
+        .clicked()
+    {
         serial.data().clear_display_buffer();
     }
 }
 
+/// Draws the heatmap toggle, which shows the received bytes as a grid of
+/// cells instead of text.
+pub fn heatmap_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>, strings: &UiStrings) {
+    let heatmap_view = serial.data().is_heatmap_view();
+    if ui
+        .add(egui::Button::selectable(heatmap_view, strings.heatmap))
+        .on_hover_text(strings.heatmap_hover)
+        .clicked()
+    {
+        serial.data().set_heatmap_view(!heatmap_view);
+    }
+}
+
+/// Returns the color of a heatmap cell: black for `0x00` to white for
+/// `0xFF`.
+#[must_use]
+pub const fn heatmap_color(byte: u8) -> egui::Color32 {
+    egui::Color32::from_gray(byte)
+}
+
+/// Draws `data` as a scrollable grid with one cell per byte and
+/// `bytes_per_row` cells per row, colored by [`heatmap_color`].
+///
+/// Clicking a cell shows its offset and value in a tooltip until another
+/// cell, or the same one, is clicked.
+pub fn draw_heatmap(ui: &mut egui::Ui, data: &[u8], bytes_per_row: usize, strings: &UiStrings) {
+    let bytes_per_row = bytes_per_row.max(1);
+    let rows = data.len().div_ceil(bytes_per_row);
+    let selected_id = ui.id().with("heatmap_selected");
+    egui::ScrollArea::vertical()
+        .id_salt("heatmap")
+        .auto_shrink([false, false])
+        .show_rows(ui, HEATMAP_CELL_SIZE, rows, |ui, row_range| {
+            let pitch = HEATMAP_CELL_SIZE + ui.spacing().item_spacing.y;
+            let size = egui::vec2(
+                HEATMAP_CELL_SIZE * bytes_per_row as f32,
+                pitch * row_range.len() as f32,
+            );
+            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+            let cell = egui::vec2(HEATMAP_CELL_SIZE - 1.0, HEATMAP_CELL_SIZE - 1.0);
+            let cell_rect = |offset: usize| {
+                let column = offset % bytes_per_row;
+                let row = offset / bytes_per_row - row_range.start;
+                let min =
+                    rect.min + egui::vec2(column as f32 * HEATMAP_CELL_SIZE, row as f32 * pitch);
+                egui::Rect::from_min_size(min, cell)
+            };
+
+            let visible =
+                row_range.start * bytes_per_row..(row_range.end * bytes_per_row).min(data.len());
+            let painter = ui.painter_at(rect);
+            for offset in visible.clone() {
+                painter.rect_filled(cell_rect(offset), 0.0, heatmap_color(data[offset]));
+            }
+
+            if response.clicked()
+                && let Some(pos) = response.interact_pointer_pos()
+            {
+                let column = ((pos.x - rect.min.x) / HEATMAP_CELL_SIZE) as usize;
+                let row = row_range.start + ((pos.y - rect.min.y) / pitch) as usize;
+                let offset = row * bytes_per_row + column.min(bytes_per_row - 1);
+                let previous = ui.data(|d| d.get_temp::<usize>(selected_id));
+                ui.data_mut(|d| {
+                    if previous == Some(offset) || offset >= data.len() {
+                        d.remove::<usize>(selected_id);
+                    } else {
+                        d.insert_temp(selected_id, offset);
+                    }
+                });
+            }
+            let selected = ui
+                .data(|d| d.get_temp::<usize>(selected_id))
+                .filter(|offset| *offset < data.len());
+            if let Some(offset) = selected {
+                if visible.contains(&offset) {
+                    painter.rect_stroke(
+                        cell_rect(offset),
+                        0.0,
+                        egui::Stroke::new(1.0, ui.visuals().selection.stroke.color),
+                        egui::StrokeKind::Outside,
+                    );
+                }
+                let byte = data[offset];
+                response.show_tooltip_text(format!(
+                    "{} {offset} (0x{offset:04X})\n{} 0x{byte:02X}\n{} {byte}",
+                    strings.heatmap_offset, strings.heatmap_hex, strings.heatmap_dec
+                ));
+            }
+        });
+}
+
+/// Draws the raw recording button: "Record Raw" starts recording received
+/// bytes to a new file in the log directory, "Stop" closes it.
+pub fn binary_recording_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    if let Some(path) = serial.data().binary_recording_path() {
+        let hover = format!("{} {}", strings.stop_recording_to, path.display());
+        if ui.button(strings.stop).on_hover_text(hover).clicked() {
+            serial.data().stop_binary_recording();
+        }
+        ui.label(egui::RichText::new(strings.recording_indicator).color(palette.error_text));
+    } else if ui
+        .button(strings.record_raw)
+        .on_hover_text(strings.record_raw_hover)
+        .clicked()
+    {
+        let dir = serial.data().session_log().dir().to_path_buf();
+        let path = recording_path(&dir, &serial.set.port_name, chrono::Local::now());
+        if let Err(e) = serial.data().start_binary_recording(&path) {
+            warn!("Failed to start recording {}: {e}", path.display());
+        }
+    }
+}
+
+/// Draws the session recording button: "Record Session" records the received
+/// and sent bytes next to the log file, "Stop" closes the recording.
+pub fn session_recording_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    if let Some(path) = serial.data().session_recording_path() {
+        let hover = format!("{} {}", strings.stop_session_recording_to, path.display());
+        if ui.button(strings.stop).on_hover_text(hover).clicked() {
+            serial.data().stop_session_recording();
+        }
+        ui.label(
+            egui::RichText::new(strings.session_recording_indicator).color(palette.error_text),
+        );
+    } else if ui
+        .button(strings.record_session)
+        .on_hover_text(strings.record_session_hover)
+        .clicked()
+    {
+        serial.data().record_session(true);
+    }
+}
+
+/// Extensions of the session logs offered for replay.
+const LOG_EXTENSIONS: &[&str] = &["txt"];
+
+/// Extensions of the session recordings offered for replay.
+const SESSION_EXTENSIONS: &[&str] = &[SESSION_EXTENSION];
+
+/// File and speed chosen in the log replay menu of a port.
+#[derive(Clone)]
+struct ReplayForm {
+    /// Path of the session log, empty until one is chosen.
+    path: String,
+    /// Multiplier of the logged timing.
+    speed: f32,
+    /// Picker choosing the file, while it is open.
+    picker: Option<FilePicker>,
+    /// Why the last replay could not be started or failed, set by the replay
+    /// task once it ends.
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl ReplayForm {
+    /// Creates a form replaying `path` at the original speed.
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            speed: 1.0,
+            picker: None,
+            error: Arc::default(),
+        }
+    }
+
+    /// Returns why the last replay failed, if it did.
+    fn error(&self) -> Option<String> {
+        self.error.lock().ok().and_then(|error| error.clone())
+    }
+
+    /// Sets or clears why the last replay failed.
+    fn set_error(&self, error: Option<String>) {
+        if let Ok(mut slot) = self.error.lock() {
+            *slot = error;
+        }
+    }
+
+    /// Draws the chosen file with the button opening the picker in `dir`,
+    /// the speed and the error of the last replay, and returns true if the
+    /// start button was clicked.
+    fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        dir: &Path,
+        extensions: &'static [&'static str],
+        strings: &UiStrings,
+        palette: &UiPalette,
+    ) -> bool {
+        ui.horizontal(|ui| {
+            if ui.button(strings.choose_file).clicked() {
+                let start = Path::new(&self.path)
+                    .parent()
+                    .filter(|parent| parent.is_dir())
+                    .unwrap_or(dir);
+                self.picker = Some(FilePicker::new(start, extensions));
+                ui.close();
+            }
+            if self.path.is_empty() {
+                ui.label(egui::RichText::new(strings.no_file_chosen).weak());
+            } else {
+                let name = Path::new(&self.path).file_name().map_or_else(
+                    || self.path.clone(),
+                    |name| name.to_string_lossy().into_owned(),
+                );
+                ui.label(name).on_hover_text(self.path.as_str());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(strings.replay_speed);
+            ui.add(
+                egui::DragValue::new(&mut self.speed)
+                    .range(0.0..=100.0)
+                    .speed(0.1)
+                    .suffix("×"),
+            )
+            .on_hover_text(strings.replay_speed_hover);
+        });
+        if let Some(error) = self.error() {
+            ui.colored_label(palette.error_text, error);
+        }
+        ui.add_enabled(
+            !self.path.is_empty(),
+            egui::Button::new(strings.replay_start),
+        )
+        .clicked()
+    }
+
+    /// Draws the picker while it is open, taking the file chosen in it.
+    fn show_picker(
+        &mut self,
+        ctx: &egui::Context,
+        id: egui::Id,
+        strings: &UiStrings,
+        palette: &UiPalette,
+    ) {
+        let Some(picker) = &mut self.picker else {
+            return;
+        };
+        match picker.show(ctx, id, strings, palette) {
+            Some(PickerAction::Picked(path)) => {
+                self.path = path.to_string_lossy().into_owned();
+                self.set_error(None);
+                self.picker = None;
+            }
+            Some(PickerAction::Cancelled) => self.picker = None,
+            None => {}
+        }
+    }
+
+    /// Draws the label of a failed replay, which shows why on hover.
+    fn error_label(&self, ui: &mut egui::Ui, strings: &UiStrings, palette: &UiPalette) {
+        if let Some(error) = self.error() {
+            ui.colored_label(palette.error_text, strings.replay_failed)
+                .on_hover_text(error);
+        }
+    }
+}
+
+/// Draws the log replay menu, or the progress of the replay in progress with
+/// a stop button.
+///
+/// The log is chosen in a file picker starting in the log directory; why a
+/// replay could not be read shows next to the menu.
+pub fn log_replay_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    runtime: &Runtime,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    if let Some(replay) = serial.data().log_replay() {
+        let progress = replay.progress();
+        if ui.button(strings.stop).clicked() {
+            serial.data().stop_log_replay();
+        }
+        let fraction = if progress.total == 0 {
+            0.0
+        } else {
+            progress.played as f32 / progress.total as f32
+        };
+        ui.add(
+            egui::ProgressBar::new(fraction)
+                .desired_width(100.0)
+                .text(format!("{} / {}", progress.played, progress.total)),
+        );
+        if let Some(timestamp) = progress.timestamp {
+            ui.label(timestamp.format("%H:%M:%S%.3f").to_string());
+        }
+        return;
+    }
+
+    let form_id = widget_id(&serial.set.port_name, WidgetKind::LogReplay);
+    let mut form = ui
+        .data(|data| data.get_temp::<ReplayForm>(form_id))
+        .unwrap_or_else(|| {
+            ReplayForm::new(
+                serial
+                    .data()
+                    .session_log()
+                    .current_path()
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        });
+    let dir = serial.data().session_log().dir().to_path_buf();
+    ui.menu_button(strings.replay_log, |ui| {
+        if form.ui(ui, &dir, LOG_EXTENSIONS, strings, palette) {
+            form.set_error(None);
+            let path = form.path.clone();
+            let task = serial.data().replay_from_log(Path::new(&path), form.speed);
+            let error = Arc::clone(&form.error);
+            runtime.spawn(async move {
+                if let Err(e) = task.await {
+                    warn!("Failed to replay {path}: {e}");
+                    if let Ok(mut slot) = error.lock() {
+                        *slot = Some(format!("{path}: {e}"));
+                    }
+                }
+            });
+            ui.close();
+        }
+    })
+    .response
+    .on_hover_text(strings.replay_log_hover);
+    form.error_label(ui, strings, palette);
+    form.show_picker(ui.ctx(), form_id.with("picker"), strings, palette);
+    ui.data_mut(|data| data.insert_temp(form_id, form));
+}
+
+/// Returns the monospace font of `size`, in the family chosen in the UI
+/// preferences.
+pub fn monospace_font(ui: &egui::Ui, size: f32) -> egui::FontId {
+    egui::FontId::new(size, egui::TextStyle::Monospace.resolve(ui.style()).family)
+}
+
+/// Draws the receive view font size controls.
+pub fn receive_font_size_ui(ui: &mut egui::Ui, config: &mut crate::serial_ui::PanelWidths) {
+    let strings = config.strings();
+    ui.horizontal(|ui| {
+        let size = config.receive_font_size();
+        if ui
+            .add_enabled(
+                size > crate::serial_ui::config::MIN_RECEIVE_FONT_SIZE,
+                egui::Button::new("A-"),
+            )
+            .on_hover_text(strings.font_smaller)
+            .clicked()
+        {
+            config.set_receive_font_size(size - 1.0);
+        }
+        ui.label(format!("{size:.0}"));
+        if ui
+            .add_enabled(
+                size < crate::serial_ui::config::MAX_RECEIVE_FONT_SIZE,
+                egui::Button::new("A+"),
+            )
+            .on_hover_text(strings.font_larger)
+            .clicked()
+        {
+            config.set_receive_font_size(size + 1.0);
+        }
+    });
+}
+
 /// Draws the model selector for LLM (global config).
 pub fn draw_llm_model_selector(ui: &mut egui::Ui, config: &mut crate::serial_ui::PanelWidths) {
-    sidebar_row(ui, "Model", |ui, width| {
+    sidebar_row(ui, config.strings().model, |ui, width| {
         egui::ComboBox::from_id_salt("llm_model_selector")
             .width(width)
             .selected_text(&config.llm_model)
@@ -363,31 +1875,262 @@ pub fn draw_llm_model_selector(ui: &mut egui::Ui, config: &mut crate::serial_ui:
 
 /// Draws the API key input for LLM (global config).
 pub fn draw_llm_key_input(ui: &mut egui::Ui, config: &mut crate::serial_ui::PanelWidths) {
-    sidebar_row(ui, "API Key", |ui, width| {
+    let strings = config.strings();
+    sidebar_row(ui, strings.api_key, |ui, width| {
         ui.add(
             egui::TextEdit::singleline(&mut config.llm_key)
                 .password(true)
                 .desired_width(width),
         );
     });
+    sidebar_row(ui, "", |ui, _width| {
+        ui.checkbox(&mut config.llm_remember_key, strings.remember_key)
+            .on_hover_text(strings.save_key_hover);
+    });
+}
+
+/// Draws the shared chat completions endpoint (global config).
+pub fn draw_llm_base_url_input(ui: &mut egui::Ui, config: &mut crate::serial_ui::PanelWidths) {
+    let strings = config.strings();
+    sidebar_row(ui, strings.base_url, |ui, width| {
+        ui.add(
+            egui::TextEdit::singleline(&mut config.llm_base_url)
+                .hint_text(DEFAULT_BASE_URL)
+                .desired_width(width),
+        )
+        .on_hover_text(strings.base_url_hover);
+    });
+}
+
+/// Draws the coding plan toggle for LLM (global config).
+pub fn draw_llm_coding_plan_toggle(ui: &mut egui::Ui, config: &mut crate::serial_ui::PanelWidths) {
+    let strings = config.strings();
+    sidebar_row(ui, strings.coding, |ui, _width| {
+        let with_coding = config.llm_with_coding_plan;
+        let button_text = if with_coding {
+            strings.coding_on
+        } else {
+            strings.coding_off
+        };
+        if ui
+            .button(button_text)
+            .on_hover_text(strings.coding_hover)
+            .clicked()
+        {
+            config.llm_with_coding_plan = !with_coding;
+        }
+    });
+}
+
+/// Draws the UI language selector of the top bar.
+pub fn draw_language_selector(ui: &mut egui::Ui, config: &mut crate::serial_ui::PanelWidths) {
+    egui::ComboBox::from_id_salt("ui_language_selector")
+        .selected_text(config.locale.as_str())
+        .show_ui(ui, |ui| {
+            for locale in Locale::ALL {
+                ui.selectable_value(&mut config.locale, locale, locale.as_str());
+            }
+        })
+        .response
+        .on_hover_text(config.strings().language);
+}
+
+/// Draws the per-port LLM request settings: provider, endpoint URL,
+/// temperature, max tokens and the size of the recent data context.
+pub fn draw_llm_request_settings(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    let port = serial.set.port_name.clone();
+    egui::CollapsingHeader::new(strings.llm_request_settings)
+        .id_salt(widget_id(&port, WidgetKind::LlmRequestSettings))
+        .default_open(false)
+        .show(ui, |ui| {
+            let llm = serial.llm();
+            sidebar_row(ui, strings.llm_provider, |ui, width| {
+                let mut provider = llm.provider.clone();
+                egui::ComboBox::from_id_salt(widget_id(&port, WidgetKind::LlmProvider))
+                    .width(width)
+                    .selected_text(provider.as_str())
+                    .show_ui(ui, |ui| {
+                        let local = match &llm.provider {
+                            LlmProvider::OllamaLocal(model) => model.clone(),
+                            _ => String::new(),
+                        };
+                        for option in [
+                            LlmProvider::ZhipuAI,
+                            LlmProvider::OpenAI,
+                            LlmProvider::Anthropic,
+                            LlmProvider::OllamaLocal(local),
+                        ] {
+                            let label = option.as_str();
+                            ui.selectable_value(&mut provider, option, label);
+                        }
+                    });
+                if provider != llm.provider {
+                    llm.set_provider(provider);
+                }
+            });
+            if let LlmProvider::OllamaLocal(model) = &mut llm.provider {
+                sidebar_row(ui, strings.llm_local_model, |ui, width| {
+                    ui.add(
+                        egui::TextEdit::singleline(model)
+                            .hint_text(strings.llm_local_model_hint)
+                            .desired_width(width),
+                    )
+                    .on_hover_text(strings.llm_local_model_hover);
+                });
+            }
+            sidebar_row(ui, strings.base_url, |ui, width| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut llm.base_url)
+                        .hint_text(llm.provider.default_base_url())
+                        .desired_width(width),
+                )
+                .on_hover_text(strings.llm_endpoint_hover);
+            });
+            sidebar_row(ui, strings.llm_temperature, |ui, _width| {
+                let mut temperature = llm.temperature;
+                if ui
+                    .add(egui::Slider::new(&mut temperature, TEMPERATURE_RANGE).step_by(0.05))
+                    .changed()
+                {
+                    llm.set_temperature(temperature);
+                }
+            });
+            sidebar_row(ui, strings.llm_max_tokens, |ui, _width| {
+                ui.add(egui::DragValue::new(&mut llm.max_tokens).range(1..=131_072));
+            });
+            sidebar_row(ui, strings.llm_history_chars, |ui, _width| {
+                ui.add(
+                    egui::DragValue::new(&mut llm.history_budget_chars)
+                        .range(1_000..=200_000)
+                        .speed(100),
+                )
+                .on_hover_text(strings.llm_history_chars_hover);
+            });
+            sidebar_row(ui, strings.llm_context_bytes, |ui, _width| {
+                ui.add(
+                    egui::DragValue::new(&mut llm.context_window_bytes)
+                        .range(256..=65_536)
+                        .speed(64),
+                )
+                .on_hover_text(strings.llm_context_bytes_hover);
+            });
+        });
+}
+
+/// Draws the toggle including recently received data in LLM queries.
+pub fn draw_llm_context_toggle(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
+    let llm = serial.llm();
+    let hover = format!(
+        "Send the last {} received bytes as context with each query",
+        llm.context_window_bytes
+    );
+    ui.checkbox(
+        &mut llm.include_recent_data,
+        "Include recent data in context",
+    )
+    .on_hover_text(hover);
+}
+
+/// Draws the error of the last failed LLM request with a button dismissing it.
+fn draw_llm_error_banner(ui: &mut egui::Ui, llm: &mut LlmConfig) {
+    let Some(error) = &llm.last_error else {
+        return;
+    };
+    let mut dismissed = false;
+    egui::Frame::new()
+        .fill(ui.visuals().error_fg_color.gamma_multiply(0.15))
+        .stroke(egui::Stroke::new(1.0, ui.visuals().error_fg_color))
+        .corner_radius(4.0)
+        .inner_margin(6.0)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                dismissed = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                ui.add(
+                    egui::Label::new(
+                        egui::RichText::new(format!("Request failed: {error}"))
+                            .color(ui.visuals().error_fg_color),
+                    )
+                    .wrap(),
+                );
+            });
+        });
+    ui.add_space(4.0);
+    if dismissed {
+        llm.last_error = None;
+    }
+}
+
+/// Draws a saved transcript read-only, with a button going back to the conversation.
+fn draw_llm_transcript(
+    ui: &mut egui::Ui,
+    llm: &mut LlmConfig,
+    markdown_cache: &mut MarkdownViewerCache,
+) {
+    let Some((path, text)) = &llm.viewed_transcript else {
+        return;
+    };
+    let mut close = false;
+    ui.horizontal(|ui| {
+        close = ui.button("Close").clicked();
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        ui.label(egui::RichText::new(format!("{name} (read-only)")).weak())
+            .on_hover_text(path.display().to_string());
+    });
+    ui.separator();
+    egui::ScrollArea::vertical()
+        .id_salt("llm_transcript")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            CommonMarkViewer::new().show(ui, &mut markdown_cache.0, text);
+        });
+    if close {
+        llm.viewed_transcript = None;
+    }
 }
 
-/// Draws the coding plan toggle for LLM (global config).
-pub fn draw_llm_coding_plan_toggle(ui: &mut egui::Ui, config: &mut crate::serial_ui::PanelWidths) {
-    sidebar_row(ui, "Coding", |ui, _width| {
-        let with_coding = config.llm_with_coding_plan;
-        let button_text = if with_coding {
-            "Coding: ON"
-        } else {
-            "Coding: OFF"
-        };
+/// Draws the conversation management buttons: starting a new conversation,
+/// saving a transcript and opening saved transcripts read-only.
+pub fn draw_llm_history_bar(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
+    ui.horizontal(|ui| {
         if ui
-            .button(button_text)
-            .on_hover_text("Toggle coding plan mode")
+            .button("New Chat")
+            .on_hover_text("Archive this conversation as a transcript and start a new one")
             .clicked()
         {
-            config.llm_with_coding_plan = !with_coding;
+            serial.start_new_llm_conversation();
+        }
+        if ui
+            .add_enabled(serial.llm().has_messages(), egui::Button::new("Save"))
+            .on_hover_text(format!("Save a Markdown transcript to {TRANSCRIPT_DIR}/"))
+            .clicked()
+            && let Some(path) = serial.save_llm_transcript()
+        {
+            info!("Saved LLM transcript to {}", path.display());
         }
+        ui.menu_button("Load", |ui| {
+            let transcripts = saved_transcripts(Path::new(TRANSCRIPT_DIR), &serial.set.port_name);
+            if transcripts.is_empty() {
+                ui.label(egui::RichText::new("No saved transcripts").weak());
+            }
+            for path in transcripts {
+                let name = path
+                    .file_name()
+                    .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+                if ui.button(name).clicked() {
+                    if let Err(e) = serial.llm().view_transcript(&path) {
+                        serial.llm().last_error =
+                            Some(format!("Failed to open {}: {e}", path.display()));
+                    }
+                    ui.close();
+                }
+            }
+        });
     });
 }
 
@@ -397,7 +2140,11 @@ pub fn draw_llm_conversation(
     serial: &mut MutexGuard<'_, Serial>,
     markdown_cache: &mut MarkdownViewerCache,
 ) {
-    let visuals = ui.visuals().clone();
+    draw_llm_error_banner(ui, serial.llm());
+    if serial.llm().viewed_transcript.is_some() {
+        draw_llm_transcript(ui, serial.llm(), markdown_cache);
+        return;
+    }
     let available_height = ui.available_height().max(120.0);
 
     egui::ScrollArea::vertical()
@@ -406,96 +2153,115 @@ pub fn draw_llm_conversation(
         .stick_to_bottom(true)
         .show(ui, |ui| {
             for msg in &serial.llm().messages {
-                let is_user = msg.role == "user";
-
-                // Choose bubble colors based on role and theme
-                let (bubble_color, text_color, role_color, role_text) = if is_user {
-                    (
-                        egui::Color32::from_rgb(37, 99, 235),
-                        egui::Color32::WHITE,
-                        egui::Color32::from_rgb(59, 130, 246),
-                        "You",
-                    )
-                } else if visuals.dark_mode {
-                    (
-                        egui::Color32::from_rgb(55, 65, 81),
-                        egui::Color32::from_rgb(229, 231, 235),
-                        egui::Color32::from_rgb(16, 185, 129),
-                        "AI",
-                    )
-                } else {
-                    (
-                        egui::Color32::from_rgb(243, 244, 246),
-                        egui::Color32::from_rgb(31, 41, 55),
-                        egui::Color32::from_rgb(5, 150, 105),
-                        "AI",
-                    )
-                };
-
-                // Align user messages to the right, AI to the left
-                ui.with_layout(
-                    egui::Layout::top_down(if is_user {
-                        egui::Align::RIGHT
-                    } else {
-                        egui::Align::LEFT
-                    })
-                    .with_cross_align(if is_user {
-                        egui::Align::RIGHT
-                    } else {
-                        egui::Align::LEFT
-                    }),
-                    |ui| {
-                        // Message header: role + timestamp
-                        ui.horizontal(|ui| {
-                            if is_user {
-                                ui.label(egui::RichText::new(&msg.timestamp).weak().small());
-                                ui.label(egui::RichText::new(role_text).strong().color(role_color));
-                            } else {
-                                ui.label(egui::RichText::new(role_text).strong().color(role_color));
-                                ui.label(egui::RichText::new(&msg.timestamp).weak().small());
-                            }
-                        });
-
-                        // Bubble frame
-                        let frame = egui::Frame::new()
-                            .fill(bubble_color)
-                            .corner_radius(10.0)
-                            .inner_margin(egui::Margin::symmetric(12, 10));
-                        frame.show(ui, |ui| {
-                            let max_w = ui.available_width().min(280.0);
-                            ui.set_max_width(max_w);
-                            render_message_content(
-                                ui,
-                                &msg.content,
-                                text_color,
-                                &mut markdown_cache.0,
-                            );
-                        });
-                    },
-                );
-
-                ui.add_space(10.0);
+                draw_llm_message(ui, msg, &mut markdown_cache.0);
             }
 
             if serial.llm().is_processing {
-                ui.with_layout(
-                    egui::Layout::top_down(egui::Align::LEFT).with_cross_align(egui::Align::LEFT),
-                    |ui| {
-                        ui.horizontal(|ui| {
-                            ui.spinner();
-                            ui.label(
-                                egui::RichText::new("AI is thinking...")
-                                    .italics()
-                                    .color(egui::Color32::GRAY),
-                            );
-                        });
-                    },
-                );
-                ui.add_space(4.0);
+                let streaming = serial.llm().streaming_text();
+                draw_llm_progress(ui, &streaming, &mut markdown_cache.0);
             }
         });
 }
 
+/// Draws one conversation message as a chat bubble.
+///
+/// User messages are aligned to the right, AI messages to the left.
+pub(crate) fn draw_llm_message(
+    ui: &mut egui::Ui,
+    msg: &LlmMessage,
+    markdown_cache: &mut CommonMarkCache,
+) {
+    let is_user = msg.role == "user";
+
+    // Choose bubble colors based on role and theme
+    let (bubble_color, text_color, role_color, role_text) = if is_user {
+        (
+            egui::Color32::from_rgb(37, 99, 235),
+            egui::Color32::WHITE,
+            egui::Color32::from_rgb(59, 130, 246),
+            "You",
+        )
+    } else if ui.visuals().dark_mode {
+        (
+            egui::Color32::from_rgb(55, 65, 81),
+            egui::Color32::from_rgb(229, 231, 235),
+            egui::Color32::from_rgb(16, 185, 129),
+            "AI",
+        )
+    } else {
+        (
+            egui::Color32::from_rgb(243, 244, 246),
+            egui::Color32::from_rgb(31, 41, 55),
+            egui::Color32::from_rgb(5, 150, 105),
+            "AI",
+        )
+    };
+
+    let align = if is_user {
+        egui::Align::RIGHT
+    } else {
+        egui::Align::LEFT
+    };
+    ui.with_layout(
+        egui::Layout::top_down(align).with_cross_align(align),
+        |ui| {
+            // Message header: role + timestamp
+            ui.horizontal(|ui| {
+                if is_user {
+                    ui.label(egui::RichText::new(&msg.timestamp).weak().small());
+                    ui.label(egui::RichText::new(role_text).strong().color(role_color));
+                } else {
+                    ui.label(egui::RichText::new(role_text).strong().color(role_color));
+                    ui.label(egui::RichText::new(&msg.timestamp).weak().small());
+                }
+            });
+
+            // Bubble frame
+            let frame = egui::Frame::new()
+                .fill(bubble_color)
+                .corner_radius(10.0)
+                .inner_margin(egui::Margin::symmetric(12, 10));
+            frame.show(ui, |ui| {
+                let max_w = ui.available_width().min(280.0);
+                ui.set_max_width(max_w);
+                render_message_content(ui, &msg.content, text_color, markdown_cache);
+            });
+        },
+    );
+
+    ui.add_space(10.0);
+}
+
+/// Draws the answer being streamed, followed by a spinner.
+pub(crate) fn draw_llm_progress(
+    ui: &mut egui::Ui,
+    streaming: &str,
+    markdown_cache: &mut CommonMarkCache,
+) {
+    if !streaming.is_empty() {
+        draw_llm_message(ui, &LlmMessage::assistant(streaming), markdown_cache);
+    }
+    ui.with_layout(
+        egui::Layout::top_down(egui::Align::LEFT).with_cross_align(egui::Align::LEFT),
+        |ui| {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                let status = if streaming.is_empty() {
+                    "AI is thinking..."
+                } else {
+                    "AI is typing..."
+                };
+                ui.label(
+                    egui::RichText::new(status)
+                        .italics()
+                        .color(egui::Color32::GRAY),
+                );
+            });
+        },
+    );
+    ui.add_space(4.0);
+}
+
 /// Renders message content with code block highlighting.
 /// Renders message content with code block highlighting.
 pub(crate) fn render_message_content(
@@ -531,14 +2297,17 @@ pub fn draw_llm_input_area(
     serial: &mut MutexGuard<'_, Serial>,
     config: &mut crate::serial_ui::PanelWidths,
     show_key_missing_popup: &mut bool,
+    llm_requests: &mut MessageWriter<LlmRequestEvent>,
 ) {
     let font = egui::FontId::new(18.0, egui::FontFamily::Monospace);
     let can_send = !serial.llm().input_buffer.trim().is_empty() && !serial.llm().is_processing;
+    let input_id = widget_id(&serial.set.port_name, WidgetKind::LlmInput);
 
     ui.vertical(|ui| {
         ui.add_sized(
             [ui.available_width(), INPUT_TEXT_EDIT_HEIGHT],
             egui::TextEdit::multiline(&mut serial.llm().input_buffer)
+                .id(input_id)
                 .hint_text("Ask AI...")
                 .font(font),
         );
@@ -551,8 +2320,9 @@ pub fn draw_llm_input_area(
                     egui::Button::new(egui::RichText::new("Send").strong()),
                 )
                 .clicked()
+                && let Some(request) = submit_llm_input(serial, config, show_key_missing_popup)
             {
-                submit_llm_input(serial, config, show_key_missing_popup);
+                llm_requests.write(request);
             }
 
             if ui.button("Clear").clicked() {
@@ -560,64 +2330,236 @@ pub fn draw_llm_input_area(
             }
 
             if serial.llm().is_processing {
+                if ui
+                    .button("Stop")
+                    .on_hover_text("Stop generating the answer")
+                    .clicked()
+                {
+                    serial.llm().stop();
+                }
                 ui.label(egui::RichText::new("Waiting for response...").weak());
-            } else if config.llm_key.is_empty() || config.llm_model.is_empty() {
+            } else if llm_settings_missing(serial, config) {
                 ui.label(egui::RichText::new("Set key/model to enable sending").weak());
             }
         });
     });
 }
 
-/// Draws the main serial input area and its action buttons.
-pub fn draw_serial_input_area(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
-    let font = egui::FontId::new(18.0, egui::FontFamily::Monospace);
-    let can_send =
-        serial.is_open() && !serial.data().get_cache_data().get_current_data().is_empty();
+/// Returns true if the key or model needed by the port's LLM provider is
+/// not set.
+fn llm_settings_missing(serial: &mut Serial, config: &crate::serial_ui::PanelWidths) -> bool {
+    (serial.llm().provider.needs_key() && config.llm_key.is_empty()) || config.llm_model.is_empty()
+}
 
-    ui.add_sized(
+/// Draws the main serial input area and its action buttons.
+pub fn draw_serial_input_area(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    font_size: f32,
+    strings: &UiStrings,
+    palette: &UiPalette,
+) {
+    let font = monospace_font(ui, font_size);
+    let is_hex = *serial.data().data_type() == DataType::Hex;
+    let hex_validation = is_hex
+        .then(|| validate_hex_input(serial.data().get_cache_data().get_current_data()))
+        .filter(|validation| !validation.is_valid());
+    // Intel HEX that does not decode is always refused, see `encode_for_send`.
+    let intel_hex_error = (*serial.data().data_type() == DataType::IntelHex)
+        .then(|| decode_from_ihex(serial.data().get_cache_data().get_current_data()).err())
+        .flatten();
+    let can_send = serial.is_open()
+        && !serial.data().get_cache_data().get_current_data().is_empty()
+        && !(hex_validation.is_some() && serial.data().is_strict_hex())
+        && intel_hex_error.is_none();
+
+    let input_id = egui::Id::new(SERIAL_INPUT_ID);
+    let searching = serial.data().get_cache_data().is_searching();
+    if searching {
+        // Keys go to the history search (see `history_data_checkout`).
+        ui.memory_mut(|memory| memory.surrender_focus(input_id));
+        let cache = serial.data().get_cache_data();
+        let prompt = if cache.is_search_failing() {
+            strings.history_search_failing
+        } else {
+            strings.history_search_prompt
+        };
+        ui.label(
+            egui::RichText::new(format!(
+                "{prompt}`{}`  {}",
+                cache.search_query(),
+                strings.search_help
+            ))
+            .monospace()
+            .weak(),
+        );
+    } else if ui.memory(|memory| memory.has_focus(input_id)) {
+        // Tab completes from history (see `history_data_checkout`); the
+        // input locks the focus so Tab neither moves it nor inserts a tab.
+        ui.input_mut(|input| input.consume_key(egui::Modifiers::NONE, egui::Key::Tab));
+    }
+    let response = ui.add_sized(
         [ui.available_width(), INPUT_TEXT_EDIT_HEIGHT],
         egui::TextEdit::multiline(serial.data().get_cache_data().get_current_data())
-            .hint_text("Type data to send...")
+            .id(input_id)
+            .lock_focus(true)
+            .interactive(!searching)
+            .hint_text(strings.input_hint)
             .font(font)
             .desired_width(f32::INFINITY),
     );
+    if response.changed() && is_hex && serial.data().is_hex_auto_space() {
+        auto_space_hex_input(ui.ctx(), serial);
+    }
+    let input_error = hex_validation
+        .map(|validation| validation.message())
+        .or_else(|| intel_hex_error.map(|err| err.to_string()));
+    if let Some(message) = input_error {
+        ui.painter().rect_stroke(
+            response.rect,
+            egui::CornerRadius::same(2),
+            egui::Stroke::new(1.5, palette.error_text),
+            egui::StrokeKind::Outside,
+        );
+        response.on_hover_text(message);
+    }
+    draw_send_preview(ui, serial, strings);
     ui.add_space(6.0);
 
     ui.horizontal(|ui| {
         if ui
             .add_enabled(
                 can_send,
-                egui::Button::new(egui::RichText::new("Send").strong()),
+                egui::Button::new(egui::RichText::new(strings.send).strong()),
             )
             .clicked()
         {
             submit_serial_input(serial);
         }
 
-        if ui.button("Clear").clicked() {
+        if ui.button(strings.clear).clicked() {
             serial.data().get_cache_data().clear_current_data();
         }
 
-        if ui.button("Prev").clicked() {
+        if ui.button(strings.prev).clicked() {
             serial.data().get_cache_data().sub_history_index();
             let index = serial.data().get_cache_data().get_current_data_index();
             *serial.data().get_cache_data().get_current_data() =
                 serial.data().get_cache_data().get_history_data(index);
         }
 
-        if ui.button("Next").clicked() {
+        if ui.button(strings.next).clicked() {
             serial.data().get_cache_data().add_history_index();
             let index = serial.data().get_cache_data().get_current_data_index();
             *serial.data().get_cache_data().get_current_data() =
                 serial.data().get_cache_data().get_history_data(index);
         }
 
+        draw_repeat_options(ui, serial, strings);
+
+        if is_hex {
+            ui.checkbox(serial.data().strict_hex(), strings.strict_hex)
+                .on_hover_text(strings.strict_hex_hover);
+            ui.checkbox(serial.data().hex_auto_space(), strings.hex_auto_space)
+                .on_hover_text(strings.hex_auto_space_hover);
+        }
+
         if !serial.is_open() {
-            ui.label(egui::RichText::new("Open the port before sending").weak());
+            ui.label(egui::RichText::new(strings.open_port_to_send).weak());
         }
     });
 }
 
+/// Draws the repeat send options, or the progress of the repeats in progress
+/// with a button stopping them.
+fn draw_repeat_options(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
+    if let Some(repeat) = serial.data().repeat_schedule() {
+        let (sent, count) = repeat.progress();
+        ui.label(format!("{} {sent}/{count}", strings.repeat));
+        if ui.button(strings.stop).clicked() {
+            serial.data().stop_repeat();
+        }
+        return;
+    }
+
+    let options = serial.data().send_options_mut();
+    let mut repeat = options.repeat_count.is_some();
+    if ui
+        .checkbox(&mut repeat, strings.repeat)
+        .on_hover_text(strings.repeat_hover)
+        .changed()
+    {
+        *options = if repeat {
+            SendOptions {
+                repeat_count: Some(DEFAULT_REPEAT_COUNT),
+                repeat_interval: Some(DEFAULT_REPEAT_INTERVAL),
+            }
+        } else {
+            SendOptions::default()
+        };
+    }
+    if let Some(count) = &mut options.repeat_count {
+        ui.add(egui::DragValue::new(count).range(1..=u32::MAX).prefix("× "))
+            .on_hover_text(strings.repeat_count_hover);
+        let mut millis = options
+            .repeat_interval
+            .map_or(0, |interval| interval.as_millis() as u64);
+        if ui
+            .add(
+                egui::DragValue::new(&mut millis)
+                    .range(0..=3_600_000)
+                    .suffix(" ms"),
+            )
+            .on_hover_text(strings.repeat_interval_hover)
+            .changed()
+        {
+            options.repeat_interval = Some(std::time::Duration::from_millis(millis));
+        }
+    }
+}
+
+/// Regroups the hex send input into space-separated bytes after an edit at
+/// its end. Edits elsewhere are left as typed so the cursor does not jump,
+/// as is input with anything but hex digits and spaces: a line break submits
+/// it, `0x` prefixes and other separators are the user's own format, and
+/// strict hex must still point at invalid characters.
+fn auto_space_hex_input(ctx: &egui::Context, serial: &mut MutexGuard<'_, Serial>) {
+    let input = serial.data().get_cache_data().get_current_data();
+    let at_end = egui::TextEdit::load_state(ctx, egui::Id::new(SERIAL_INPUT_ID))
+        .and_then(|state| state.cursor.char_range())
+        .is_none_or(|range| range.primary.index == input.chars().count());
+    if !at_end || !input.chars().all(|c| c.is_ascii_hexdigit() || c == ' ') {
+        return;
+    }
+    let formatted = format_hex_input(input);
+    if formatted != *input {
+        *input = formatted;
+        move_input_cursor_to_end(ctx, input);
+    }
+}
+
+/// Draws the byte count and hex dump of what sending the current input
+/// would write to the port.
+fn draw_send_preview(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>, strings: &UiStrings) {
+    let settings = serial.data().send_settings();
+    let input = serial.data().get_cache_data().get_current_data();
+    if input.is_empty() {
+        return;
+    }
+    let preview = preview_encoded(input, &settings);
+    let text = if preview.error.is_some() {
+        strings.preview_refused.to_string()
+    } else {
+        format!("{} B  {}", preview.len, preview.hex_dump())
+    };
+    ui.add(egui::Label::new(egui::RichText::new(text).monospace().small().weak()).truncate())
+        .on_hover_text(strings.preview_hover);
+}
+
 /// Queues the current serial input for sending.
 pub fn submit_serial_input(serial: &mut Serial) -> bool {
     if !serial.is_open() {
@@ -628,69 +2570,83 @@ pub fn submit_serial_input(serial: &mut Serial) -> bool {
     if cache.is_empty() {
         return false;
     }
+    if *serial.data().data_type() == DataType::Hex
+        && serial.data().is_strict_hex()
+        && !validate_hex_input(&cache).is_valid()
+    {
+        return false;
+    }
 
-    let data = if *serial.data().line_feed() {
-        if cache.contains('\r') || cache.contains('\n') {
-            cache.clone()
-        } else {
-            format!("{cache}\n")
-        }
+    let text = if *serial.data().data_type() == DataType::Hex && serial.data().is_hex_auto_space() {
+        format_hex_input(&cache)
     } else {
-        cache.replace(['\r', '\n'], "")
+        cache.clone()
     };
+    let settings = serial.data().send_settings();
+    let data = apply_line_ending(&text, &settings);
     let history_data = cache.replace(['\r', '\n'], "");
     if history_data.is_empty() {
         return false;
     }
+    let Ok(payload) = SendPayload::encode(&data, &settings) else {
+        return false;
+    };
 
     serial
         .data()
         .get_cache_data()
         .add_history_data(history_data);
-    serial.data().send_data(data);
+    let options = serial.data().send_options();
+    serial.data().queue_send_with_options(payload, options);
     serial.data().get_cache_data().clear_current_data();
     true
 }
 
-/// Submits the current LLM input if configuration is complete.
+/// Takes the current LLM input as a request if configuration is complete.
+///
+/// The request is sent by writing the returned [`LlmRequestEvent`].
 pub fn submit_llm_input(
     serial: &mut Serial,
     config: &mut crate::serial_ui::PanelWidths,
     show_key_missing_popup: &mut bool,
-) -> bool {
-    if config.llm_key.is_empty() || config.llm_model.is_empty() {
+) -> Option<LlmRequestEvent> {
+    if llm_settings_missing(serial, config) {
         config.show_settings_panel = true;
         *show_key_missing_popup = true;
-        return false;
+        return None;
     }
 
     if serial.llm().is_processing {
-        return false;
+        return None;
     }
 
-    let content = serial.llm().input_buffer.trim().to_string();
-    if content.is_empty() {
-        return false;
+    let user_message = serial.llm().input_buffer.trim().to_string();
+    if user_message.is_empty() {
+        return None;
     }
 
-    serial.llm().add_user_message(&content);
     serial.llm().input_buffer.clear();
-    *serial.llm().enable() = true;
-    serial.llm().is_processing = true;
-    true
+    Some(LlmRequestEvent {
+        port_name: serial.set.port_name.clone(),
+        user_message,
+    })
 }
 
 /// Draws the console mode toggle button.
 /// Console mode provides better terminal experience for Linux serial consoles:
 /// - No local echo (terminal handles echo)
 /// - Raw data logging (no timestamps)
-pub fn console_mode_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
+pub fn console_mode_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    strings: &UiStrings,
+) {
     ui.horizontal(|ui| {
         let console_mode = *serial.data().console_mode();
         let (button_text, hover_text) = if console_mode {
-            ("Console ON", "Console mode enabled. Terminal handles echo. Toggle to disable.")
+            (strings.console_on, strings.console_on_hover)
         } else {
-            ("Console OFF", "Enable console mode for Linux serial terminal experience (no local echo, raw data)")
+            (strings.console_off, strings.console_off_hover)
         };
 
         let button = ui.button(button_text).on_hover_text(hover_text);
@@ -701,28 +2657,218 @@ pub fn console_mode_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
     });
 }
 
-/// Draws the timestamp display toggle button.
-/// When enabled, shows timestamps and send/receive indicators in the log.
-/// When disabled (default), shows raw data for cleaner display.
-pub fn timestamp_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>) {
+/// Draws the NMEA 0183 parser toggle.
+pub fn nmea_ui(ui: &mut egui::Ui, serial: &mut MutexGuard<'_, Serial>, strings: &UiStrings) {
     ui.horizontal(|ui| {
-        let show_timestamp = *serial.data().show_timestamp();
-        let (button_text, hover_text) = if show_timestamp {
-            (
-                "Time ON",
-                "Timestamps enabled. Toggle to hide timestamps and source indicators.",
-            )
+        let enabled = serial.data().is_nmea_enabled();
+        let (button_text, hover_text) = if enabled {
+            (strings.nmea_on, strings.nmea_on_hover)
         } else {
-            ("Time OFF", "Enable timestamps and send/receive indicators")
+            (strings.nmea_off, strings.nmea_off_hover)
         };
 
-        let button = ui.button(button_text).on_hover_text(hover_text);
-        if button.clicked() {
-            *serial.data().show_timestamp() = !show_timestamp;
+        if ui.button(button_text).on_hover_text(hover_text).clicked() {
+            serial.data().set_nmea_enabled(!enabled);
+        }
+    });
+}
+
+/// Draws the latest parsed NMEA sentence of each type as a table.
+pub fn draw_nmea_table(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    height: f32,
+    palette: &UiPalette,
+) {
+    let Some(parser) = serial.data().nmea_parser() else {
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("NMEA").strong());
+        ui.label(format!("{} valid", parser.valid_count()));
+        let errors = parser.checksum_errors();
+        let text = egui::RichText::new(format!("{errors} checksum errors"));
+        ui.label(if errors > 0 {
+            text.color(palette.error_text)
+        } else {
+            text.weak()
+        });
+    });
+
+    egui::ScrollArea::vertical()
+        .id_salt("nmea_table")
+        .max_height(height)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            egui::Grid::new("nmea_table_grid")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for sentence in parser.latest() {
+                        let summary = sentence.summary();
+                        ui.monospace(&sentence.sentence_type);
+                        for (i, (name, value)) in summary.iter().enumerate() {
+                            if i > 0 {
+                                ui.label("");
+                            }
+                            ui.label(name);
+                            ui.monospace(value);
+                            ui.end_row();
+                        }
+                        if summary.is_empty() {
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+}
+
+/// Draws the timestamp settings popover.
+///
+/// Chooses how timestamps are shown in the receive view, an optional strftime
+/// format override (persisted per port), whether the log file gets
+/// timestamps, and how sent data is echoed into the receive view (persisted
+/// per port).
+pub fn timestamp_ui(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    config: &mut crate::serial_ui::PanelWidths,
+) {
+    let strings = config.strings();
+    let mode = serial.data().timestamp_mode();
+    let button_text = if mode == TimestampMode::None {
+        strings.time_off.to_string()
+    } else {
+        format!("{}: {mode}", strings.time)
+    };
+
+    ui.menu_button(button_text, |ui| {
+        ui.label(egui::RichText::new(strings.receive_view).strong());
+        for option in TimestampMode::ALL {
+            if ui.radio(mode == option, option.to_string()).clicked() {
+                serial.data().set_timestamp_mode(option);
+            }
+        }
+
+        ui.separator();
+        ui.label(strings.time_format);
+        let port_name = serial.set.port_name.clone();
+        let mut format = config
+            .timestamp_formats
+            .get(&port_name)
+            .cloned()
+            .unwrap_or_default();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut format)
+                .id(widget_id(&port_name, WidgetKind::TimestampFormat))
+                .hint_text(mode.default_format())
+                .desired_width(160.0),
+        );
+        if response.changed() {
+            if format.is_empty() {
+                config.timestamp_formats.remove(&port_name);
+            } else {
+                config.timestamp_formats.insert(port_name, format);
+            }
+        }
+        response.on_hover_text(strings.time_format_hover);
+
+        ui.separator();
+        let mut log_timestamps = serial.data().is_show_timestamp();
+        if ui
+            .checkbox(&mut log_timestamps, strings.log_timestamps)
+            .changed()
+        {
+            *serial.data().show_timestamp() = log_timestamps;
         }
+
+        ui.separator();
+        draw_local_echo_options(ui, serial, config);
+
+        ui.separator();
+        draw_response_timing_options(ui, serial, config);
     });
 }
 
+/// Draws the response timing options of the receive view settings popover.
+fn draw_response_timing_options(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    config: &mut crate::serial_ui::PanelWidths,
+) {
+    let strings = config.strings();
+    let current = serial.data().response_timing();
+    let mut timing = current;
+    ui.label(strings.response_time)
+        .on_hover_text(strings.response_time_hover);
+    ui.radio_value(&mut timing, ResponseTiming::Off, strings.response_off);
+    ui.radio_value(
+        &mut timing,
+        ResponseTiming::MostRecent,
+        strings.response_most_recent,
+    );
+    ui.radio_value(&mut timing, ResponseTiming::Fifo, strings.response_fifo);
+
+    if timing != current {
+        serial.data().set_response_timing(timing);
+        let port_name = serial.set.port_name.clone();
+        if timing == ResponseTiming::default() {
+            config.response_timing.remove(&port_name);
+        } else {
+            config.response_timing.insert(port_name, timing);
+        }
+    }
+}
+
+/// Draws the local echo options of the receive view settings popover.
+fn draw_local_echo_options(
+    ui: &mut egui::Ui,
+    serial: &mut MutexGuard<'_, Serial>,
+    config: &mut crate::serial_ui::PanelWidths,
+) {
+    let strings = config.strings();
+    let current = serial.data().local_echo();
+    let window_ms = match current {
+        LocalEcho::AutoDedupe { window_ms } => window_ms,
+        _ => DEFAULT_ECHO_WINDOW_MS,
+    };
+    let mut local_echo = current;
+    ui.label(strings.local_echo)
+        .on_hover_text(strings.local_echo_hover);
+    ui.radio_value(&mut local_echo, LocalEcho::Always, strings.echo_always);
+    ui.radio_value(&mut local_echo, LocalEcho::Never, strings.echo_never);
+    if ui
+        .radio(
+            matches!(local_echo, LocalEcho::AutoDedupe { .. }),
+            strings.echo_dedupe,
+        )
+        .clicked()
+    {
+        local_echo = LocalEcho::AutoDedupe { window_ms };
+    }
+    if let LocalEcho::AutoDedupe { window_ms } = &mut local_echo {
+        ui.horizontal(|ui| {
+            ui.label(strings.echo_window);
+            ui.add(
+                egui::DragValue::new(window_ms)
+                    .range(10..=10_000)
+                    .suffix(" ms"),
+            );
+        });
+    }
+
+    if local_echo != current {
+        serial.data().set_local_echo(local_echo);
+        let port_name = serial.set.port_name.clone();
+        if local_echo == LocalEcho::default() {
+            config.local_echo.remove(&port_name);
+        } else {
+            config.local_echo.insert(port_name, local_echo);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -730,24 +2876,38 @@ mod tests {
     use crate::serial_ui::PanelWidths;
 
     #[test]
-    fn test_selected_default() {
-        let selected = Selected::default();
-        assert!(selected.selected().is_empty());
+    fn test_widget_ids_are_unique_per_port_and_kind() {
+        let ports = ["COM1", "COM13", "/dev/ttyUSB0", ""];
+        let ids: std::collections::HashSet<_> = ports
+            .iter()
+            .flat_map(|port| WidgetKind::ALL.map(|kind| widget_id(port, kind)))
+            .collect();
+        assert_eq!(ids.len(), ports.len() * WidgetKind::ALL.len());
     }
 
     #[test]
-    fn test_selected_operations() {
-        let mut selected = Selected::default();
-        selected.select("COM1");
-        assert!(selected.is_selected("COM1"));
-        assert!(!selected.is_selected("COM2"));
-        assert_eq!(selected.selected(), "COM1");
+    fn test_context_menu_ignores_marked_ports_together() {
+        let marks: BTreeSet<String> = ["COM1", "COM3"].map(String::from).into();
+        assert_eq!(ports_to_ignore("COM3", &marks), ["COM1", "COM3"]);
+        assert_eq!(ports_to_ignore("COM2", &marks), ["COM2"]);
+        assert_eq!(ports_to_ignore("COM2", &BTreeSet::new()), ["COM2"]);
+    }
+
+    #[test]
+    fn test_heatmap_color_boundaries() {
+        assert_eq!(heatmap_color(0x00), egui::Color32::BLACK);
+        assert_eq!(
+            heatmap_color(0x7F),
+            egui::Color32::from_rgb(0x7F, 0x7F, 0x7F)
+        );
+        assert_eq!(heatmap_color(0xFF), egui::Color32::WHITE);
     }
 
     #[test]
-    fn test_submit_llm_input_enables_port_llm_and_marks_processing() {
+    fn test_submit_llm_input_takes_input_as_request() {
         let mut serial = Serial::new();
-        serial.llm().input_buffer = "hello".to_string();
+        serial.set.port_name = "COM1".to_string();
+        serial.llm().input_buffer = " hello ".to_string();
 
         let mut config = PanelWidths {
             llm_key: "test-key".to_string(),
@@ -755,16 +2915,153 @@ mod tests {
         };
         let mut show_key_missing_popup = false;
 
-        assert!(submit_llm_input(
-            &mut serial,
-            &mut config,
-            &mut show_key_missing_popup
-        ));
-        assert!(serial.llm().enable);
-        assert!(serial.llm().is_processing);
-        assert_eq!(serial.llm().messages.len(), 1);
-        assert_eq!(serial.llm().messages[0].role, "user");
-        assert_eq!(serial.llm().messages[0].content, "hello");
+        assert_eq!(
+            submit_llm_input(&mut serial, &mut config, &mut show_key_missing_popup),
+            Some(LlmRequestEvent {
+                port_name: "COM1".to_string(),
+                user_message: "hello".to_string(),
+            })
+        );
+        assert!(serial.llm().input_buffer.is_empty());
         assert!(!show_key_missing_popup);
+        assert_eq!(
+            submit_llm_input(&mut serial, &mut config, &mut show_key_missing_popup),
+            None
+        );
+
+        config.llm_key.clear();
+        serial.llm().input_buffer = "hello".to_string();
+        assert_eq!(
+            submit_llm_input(&mut serial, &mut config, &mut show_key_missing_popup),
+            None
+        );
+        assert!(show_key_missing_popup);
+    }
+
+    #[test]
+    fn test_activity_dot_flashes_then_fades() {
+        let now = Instant::now();
+        assert_eq!(activity_intensity(None, now), 0.0);
+        assert_eq!(activity_intensity(Some(now), now), 1.0);
+        assert_eq!(activity_intensity(Some(now), now + ACTIVITY_FLASH), 1.0);
+        let halfway = activity_intensity(Some(now), now + ACTIVITY_FLASH + ACTIVITY_FADE / 2);
+        assert!((halfway - 0.5).abs() < 0.01);
+        assert_eq!(
+            activity_intensity(Some(now), now + Duration::from_secs(5)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_idle_text() {
+        let now = Instant::now();
+        assert_eq!(idle_text(None, now), "never");
+        assert_eq!(
+            idle_text(Some(now), now + Duration::from_millis(12_400)),
+            "12 s ago"
+        );
+    }
+
+    #[test]
+    fn test_send_preview_matches_sent_bytes() {
+        use crate::serial::io::send_serial_data;
+        use crate::serial::traffic::TrafficLog;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let cases = [
+            ("AT+GMR", DataType::Utf8, LineEnding::Lf, false),
+            ("AT\\r\\n", DataType::Ascii, LineEnding::None, true),
+            ("12 34 AB", DataType::Hex, LineEnding::CrLf, false),
+            ("温度", DataType::Gbk, LineEnding::Cr, false),
+            ("hi", DataType::Utf16, LineEnding::CrLf, false),
+        ];
+        for (input, data_type, line_ending, escapes) in cases {
+            let mut serial = Serial::new();
+            serial.open();
+            let (tx, mut rx) = tokio::sync::broadcast::channel(4);
+            *serial.tx_channel() = Some(tx);
+            *serial.data().data_type() = data_type;
+            *serial.data().line_ending() = line_ending;
+            *serial.data().parse_escapes() = escapes;
+            serial
+                .data()
+                .get_cache_data()
+                .get_current_data()
+                .push_str(input);
+            let preview = preview_encoded(input, &serial.data().send_settings());
+            assert!(submit_serial_input(&mut serial));
+
+            let mut serials = Serials::new();
+            serials.add(serial);
+            let mut world = World::new();
+            crate::serial::status::spawn_ports(&mut world, serials);
+            world.init_resource::<TrafficLog>();
+            world.run_system_once(send_serial_data).unwrap();
+
+            match rx.try_recv() {
+                Ok(PortChannelData::PortWrite(data)) => {
+                    assert_eq!(data.data.len(), preview.len, "{input}");
+                    assert_eq!(data.data, preview.head, "{input}");
+                }
+                other => panic!("expected a write for {input}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_port_selection_survives_poisoned_port() {
+        use crate::serial::status::{PortEntities, spawn_ports};
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut serials = Serials::new();
+        serials.add_mock("mock://a", crate::serial::mock::MockTraffic::Echo);
+        serials.add_mock("mock://b", crate::serial::mock::MockTraffic::Echo);
+        let mut registry = Serials::new();
+        registry.serial.clone_from(&serials.serial);
+        let mut world = World::new();
+        world.init_resource::<Selected>();
+        spawn_ports(&mut world, registry);
+        let mut snapshots = PortSnapshots::default();
+        snapshots.refresh(&serials.serial);
+        std::thread::scope(|scope| {
+            let _ = scope
+                .spawn(|| {
+                    let _guard = lock_serial(&serials.serial[0]);
+                    panic!("poisoning the port lock");
+                })
+                .join();
+        });
+        assert!(serials.serial[0].is_poisoned());
+
+        let port_a = world.resource::<PortEntities>().get("mock://a").unwrap();
+        world.resource_mut::<Selected>().select(port_a);
+        let first = Arc::clone(&serials.serial[0]);
+        let ctx = egui::Context::default();
+        world
+            .run_system_once(move |mut selection: PortSelection| {
+                let mut config = PanelWidths::default();
+                let mut traffic = TrafficLog::default();
+                for _ in 0..2 {
+                    let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                        egui::CentralPanel::default().show(ctx, |ui| {
+                            draw_select_serial_ui(
+                                ui,
+                                &mut serials,
+                                &mut selection,
+                                &mut config,
+                                &snapshots,
+                                &mut traffic,
+                                &UiPalette::default(),
+                            );
+                            for port in snapshots.iter() {
+                                draw_serial_context_label_ui(ui, &mut selection, port, &config);
+                            }
+                        });
+                    });
+                }
+            })
+            .unwrap();
+        assert!(!first.is_poisoned());
+        assert!(world.resource::<Selected>().is_selected(port_a));
     }
 }
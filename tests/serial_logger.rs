@@ -0,0 +1,55 @@
+//! Runs the headless logger against a simulated device.
+
+use std::time::{Duration, Instant};
+
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::prelude::*;
+use serial_bevy::serial::SerialPlugin;
+use serial_bevy::serial::logger::SerialLoggerPlugin;
+use serial_bevy::serial::sim::SimulatedSerial;
+
+#[test]
+fn test_logger_appends_timestamped_replies_and_exits_on_stop() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("capture.log");
+    std::fs::write(&output, "previous run\n").unwrap();
+
+    let modem = SimulatedSerial::new("sim://modem", vec![], |data| {
+        (data == b"AT\r\n").then(|| b"OK\r\n".to_vec())
+    });
+    let logger = SerialLoggerPlugin::new("sim://modem", 115200, &output).with_send(b"AT\r\n");
+
+    // Stops the logger once the reply is in the file, like Ctrl+C would.
+    let stop = logger.stop_handle();
+    let watched = output.clone();
+    let watcher = std::thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            let text = std::fs::read_to_string(&watched).unwrap_or_default();
+            if text.contains("OK") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        stop.stop();
+    });
+
+    let exit = App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_millis(5))),
+            SerialPlugin::default()
+                .with_simulated(modem)
+                .with_log_dir(dir.path().join("logs")),
+            logger,
+        ))
+        .run();
+    watcher.join().unwrap();
+    assert_eq!(exit, AppExit::Success);
+
+    let text = std::fs::read_to_string(&output).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2, "{text}");
+    assert_eq!(lines[0], "previous run");
+    assert!(lines[1].starts_with('['), "{text}");
+    assert!(lines[1].ends_with("] OK"), "{text}");
+}